@@ -4,15 +4,26 @@
 // would be silently ignored and the resulting binary would still embed
 // the previous bundle. Docker builds share `target/`, so the COPY layer
 // alone is too late to invalidate cargo.
+//
+// The script also stamps build information (git commit, build date,
+// rustc version, enabled features) into the binary for `SHOW VERSION`,
+// `/api/version` and `pg_doorman_build_info`. Packagers building from a
+// tarball without `.git` can pass PG_DOORMAN_GIT_COMMIT explicitly;
+// SOURCE_DATE_EPOCH pins the build date for reproducible builds.
 
+use std::env;
 use std::fs;
 use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 fn main() {
     let dist = Path::new("frontend/dist");
     println!("cargo:rerun-if-changed=frontend/dist");
     println!("cargo:rerun-if-changed=frontend/dist/index.html");
     walk(dist);
+
+    build_info();
 }
 
 fn walk(dir: &Path) {
@@ -31,3 +42,104 @@ fn walk(dir: &Path) {
         }
     }
 }
+
+fn build_info() {
+    println!("cargo:rerun-if-env-changed=PG_DOORMAN_GIT_COMMIT");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+
+    let git_commit = env::var("PG_DOORMAN_GIT_COMMIT")
+        .ok()
+        .filter(|sha| !sha.is_empty())
+        .or_else(git_commit)
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=PG_DOORMAN_GIT_COMMIT={git_commit}");
+
+    let epoch = env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0)
+        });
+    println!("cargo:rustc-env=PG_DOORMAN_BUILD_DATE={}", rfc3339(epoch));
+
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .and_then(|out| String::from_utf8(out.stdout).ok())
+        .map(|s| s.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=PG_DOORMAN_RUSTC_VERSION={rustc_version}");
+
+    // Cargo exposes every enabled feature as CARGO_FEATURE_<NAME>.
+    let mut features: Vec<String> = env::vars()
+        .filter_map(|(key, _)| {
+            key.strip_prefix("CARGO_FEATURE_")
+                .map(|name| name.to_lowercase().replace('_', "-"))
+        })
+        .filter(|name| name != "default")
+        .collect();
+    features.sort();
+    println!("cargo:rustc-env=PG_DOORMAN_FEATURES={}", features.join(","));
+}
+
+/// Short commit SHA, with `-dirty` when the work tree has local changes.
+/// Watches `.git/HEAD` and the branch it points to so a new commit
+/// re-stamps the binary.
+fn git_commit() -> Option<String> {
+    let head = Path::new(".git/HEAD");
+    if !head.exists() {
+        return None;
+    }
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    if let Ok(contents) = fs::read_to_string(head) {
+        if let Some(reference) = contents.trim().strip_prefix("ref: ") {
+            println!("cargo:rerun-if-changed=.git/{reference}");
+        }
+    }
+
+    let output = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()
+        .filter(|out| out.status.success())?;
+    let sha = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    if sha.is_empty() {
+        return None;
+    }
+
+    let dirty = Command::new("git")
+        .args(["status", "--porcelain", "--untracked-files=no"])
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .is_some_and(|out| !out.stdout.is_empty());
+    Some(if dirty { format!("{sha}-dirty") } else { sha })
+}
+
+/// Formats a Unix timestamp as `YYYY-MM-DDTHH:MM:SSZ` without pulling a
+/// date crate into build-dependencies (civil-from-days, Howard Hinnant).
+fn rfc3339(epoch: u64) -> String {
+    let days = (epoch / 86_400) as i64;
+    let secs = epoch % 86_400;
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs / 3_600,
+        secs % 3_600 / 60,
+        secs % 60
+    )
+}
@@ -13,7 +13,8 @@ import type { OverviewDto } from "../types";
  * 2. Shutdown — pg_doorman is draining, no new transactions.
  * 3. Migration — binary upgrade in progress, clients moving to the
  *    new process.
- * 4. Unreachable — `/api/overview` has not answered for ~15 s. The UI
+ * 4. DRAIN — the listener is closed, connected clients keep working.
+ * 5. Unreachable — `/api/overview` has not answered for ~15 s. The UI
  *    is talking to a dead pooler; do not trust the rest of the page.
  *
  * Banners are persistent on purpose: toasts vanish in a few seconds
@@ -79,6 +80,14 @@ export function LifecycleBanner() {
       />
     );
   }
+  if (data?.server_state === "draining") {
+    return (
+      <Bar
+        kind="shutdown"
+        text="pg_doorman is drained — the listener is closed and new client connections are refused. Run UNDRAIN to reopen it."
+      />
+    );
+  }
   return null;
 }
 
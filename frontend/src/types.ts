@@ -7,6 +7,8 @@ export interface VersionDto {
   version: string;
   git_commit: string;
   build_date: string;
+  rustc_version: string;
+  features: string;
   ts: number;
 }
 
@@ -40,6 +42,8 @@ export interface OverviewDto {
   clients_in_transactions: number;
   shutdown_in_progress: boolean;
   migration_in_progress: boolean;
+  // SHOW STATE value. "draining" means DRAIN closed the listener.
+  server_state: "accepting" | "draining" | "paused" | "shutting_down";
   // Database currently holding the most live backend connections, summed
   // across every user@db pool that targets it. Sidebar surfaces it so
   // any page shows which database is taking the load right now. Omitted
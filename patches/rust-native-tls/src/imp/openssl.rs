@@ -31,8 +31,8 @@ use self::openssl::nid::Nid;
 use self::openssl::pkcs12::Pkcs12;
 use self::openssl::pkey::{PKey, Private};
 use self::openssl::ssl::{
-    self, MidHandshakeSslStream, SslAcceptor, SslConnector, SslContextBuilder, SslMethod,
-    SslVerifyMode,
+    self, AlpnError, MidHandshakeSslStream, SslAcceptor, SslConnector, SslContextBuilder,
+    SslMethod, SslVerifyMode,
 };
 use self::openssl::x509::{store::X509StoreBuilder, X509VerifyResult, X509};
 use self::openssl_probe::ProbeResult;
@@ -59,6 +59,7 @@ fn supported_protocols(
             Protocol::Tlsv10 => SslVersion::TLS1,
             Protocol::Tlsv11 => SslVersion::TLS1_1,
             Protocol::Tlsv12 => SslVersion::TLS1_2,
+            Protocol::Tlsv13 => SslVersion::TLS1_3,
         }
     }
 
@@ -97,9 +98,16 @@ fn supported_protocols(
                 | SslOptions::NO_TLSV1
                 | SslOptions::NO_TLSV1_1
         }
+        Some(Protocol::Tlsv13) => {
+            SslOptions::NO_SSLV2
+                | SslOptions::NO_SSLV3
+                | SslOptions::NO_TLSV1
+                | SslOptions::NO_TLSV1_1
+                | SslOptions::NO_TLSV1_2
+        }
     };
     options |= match max {
-        None | Some(Protocol::Tlsv12) => SslOptions::empty(),
+        None | Some(Protocol::Tlsv12) | Some(Protocol::Tlsv13) => SslOptions::empty(),
         Some(Protocol::Tlsv11) => SslOptions::NO_TLSV1_2,
         Some(Protocol::Tlsv10) => SslOptions::NO_TLSV1_1 | SslOptions::NO_TLSV1_2,
         Some(Protocol::Sslv3) => {
@@ -415,6 +423,20 @@ impl TlsAcceptor {
             acceptor.add_extra_chain_cert(cert.to_owned())?;
         }
         supported_protocols(builder.min_protocol, builder.max_protocol, &mut acceptor)?;
+        if let Some(cipher_list) = &builder.cipher_list {
+            acceptor.set_cipher_list(cipher_list)?;
+        }
+        if !builder.accept_alpns.is_empty() {
+            // Wire format is each alpn preceded by its length as a byte.
+            let mut alpn_wire_format = Vec::new();
+            for alpn in builder.accept_alpns.iter().map(|s| s.as_bytes()) {
+                alpn_wire_format.push(alpn.len() as u8);
+                alpn_wire_format.extend(alpn);
+            }
+            acceptor.set_alpn_select_callback(move |_, client| {
+                ssl::select_next_proto(&alpn_wire_format, client).ok_or(AlpnError::NOACK)
+            });
+        }
 
         Ok(TlsAcceptor(acceptor.build()))
     }
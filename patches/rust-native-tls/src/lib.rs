@@ -321,6 +321,8 @@ pub enum Protocol {
     Tlsv11,
     /// The TLS 1.2 protocol.
     Tlsv12,
+    /// The TLS 1.3 protocol.
+    Tlsv13,
 }
 
 /// A builder for `TlsConnector`s.
@@ -523,7 +525,11 @@ pub struct TlsAcceptorBuilder {
     #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "ios")))]
     client_cert_verification: TlsClientCertificateVerification,
     #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "ios")))]
-    client_cert_verification_ca_cert: Option<Certificate>
+    client_cert_verification_ca_cert: Option<Certificate>,
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "ios")))]
+    cipher_list: Option<String>,
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "ios")))]
+    accept_alpns: Vec<String>,
 }
 
 impl TlsAcceptorBuilder {
@@ -558,6 +564,28 @@ impl TlsAcceptorBuilder {
         self
     }
 
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "ios")))]
+    /// Sets the OpenSSL cipher list for TLS 1.2 and older.
+    ///
+    /// A value of `None` keeps the backend's default list. TLS 1.3 suites are not affected.
+    ///
+    /// Defaults to `None`.
+    pub fn cipher_list(&mut self, cipher_list: Option<String>) -> &mut TlsAcceptorBuilder {
+        self.cipher_list = cipher_list;
+        self
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "ios")))]
+    /// Sets the ALPN protocols the server selects from, in order of preference.
+    ///
+    /// A client that offers none of them gets no ALPN protocol; the handshake still succeeds.
+    ///
+    /// Defaults to no ALPN.
+    pub fn accept_alpns(&mut self, protocols: &[&str]) -> &mut TlsAcceptorBuilder {
+        self.accept_alpns = protocols.iter().map(|s| (*s).to_owned()).collect();
+        self
+    }
+
     #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "ios")))]
     /// Sets the verification mode for client certificates.
     ///
@@ -635,6 +663,10 @@ impl TlsAcceptor {
             client_cert_verification: TlsClientCertificateVerification::DoNotRequestCertificate,
             #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "ios")))]
             client_cert_verification_ca_cert: None,
+            #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "ios")))]
+            cipher_list: None,
+            #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "ios")))]
+            accept_alpns: vec![],
         }
     }
 
@@ -12,6 +12,7 @@ use crate::messages::protocol::{command_complete, data_row, row_description};
 use crate::messages::socket::write_all_half;
 use crate::messages::types::DataType;
 use crate::pool::ClientServerMap;
+use crate::stats::{get_client_stats, get_server_stats};
 
 /// Reload the configuration file without restarting the process.
 pub async fn reload<T>(stream: &mut T, client_server_map: ClientServerMap) -> Result<(), Error>
@@ -26,23 +27,33 @@ where
     // immediately, but the operator watching the UI is the one who
     // usually needs the visual breadcrumb when the deploy step quietly
     // fails.
-    match reload_config(client_server_map).await {
-        Ok(_) => {
+    let diff = match reload_config(client_server_map).await {
+        Ok(diff) => {
             crate::admin::events::push_event("RELOAD", "config reloaded".to_string());
+            diff
         }
         Err(e) => {
             crate::admin::events::push_event_rate_limited(
                 "CONFIG_VALIDATION_ERROR",
                 format!("admin RELOAD rejected: {e}"),
             );
+            // Config errors keep the admin session open so the operator
+            // sees why the new file was not applied.
+            if let Error::BadConfig(message) = &e {
+                return admin_error_response(stream, message, "F0000").await;
+            }
             return Err(e);
         }
-    }
+    };
 
     get_config().show();
 
+    // One row per applied change; none when the file was unchanged.
     let mut res = BytesMut::new();
-
+    res.put(row_description(&vec![("change", DataType::Text)]));
+    for change in &diff.changes {
+        res.put(data_row(&[change.to_string()]));
+    }
     res.put(command_complete("RELOAD"));
 
     // ReadyForQuery
@@ -53,24 +64,78 @@ where
     write_all_half(stream, &res).await
 }
 
-/// Send response packets for shutdown.
-pub async fn shutdown<T>(stream: &mut T) -> Result<(), Error>
+/// How `SHUTDOWN` stops the process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShutdownMode {
+    /// `SIGINT`: stop accepting clients and wait up to `shutdown_timeout`
+    /// for open transactions to finish.
+    Graceful,
+    /// `SIGTERM`: exit now, closing every client and server connection.
+    Immediate,
+}
+
+impl ShutdownMode {
+    /// Parse the arguments after `SHUTDOWN`: none or `IMMEDIATE`.
+    pub fn parse(args: &[&str]) -> Option<Self> {
+        match args {
+            [] => Some(Self::Graceful),
+            [mode] if mode.eq_ignore_ascii_case("IMMEDIATE") => Some(Self::Immediate),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Graceful => "graceful",
+            Self::Immediate => "immediate",
+        }
+    }
+}
+
+/// Send response packets for shutdown. `IMMEDIATE` reports the client
+/// (other than this admin session) and server connections it closes.
+pub async fn shutdown<T>(stream: &mut T, mode: ShutdownMode) -> Result<(), Error>
 where
     T: tokio::io::AsyncWrite + std::marker::Unpin,
 {
     let mut res = BytesMut::new();
 
-    res.put(row_description(&vec![("success", DataType::Text)]));
-
-    let mut shutdown_success = "t";
+    res.put(row_description(&vec![
+        ("success", DataType::Text),
+        ("mode", DataType::Text),
+        ("closed_clients", DataType::Int4),
+        ("closed_servers", DataType::Int4),
+    ]));
+
+    let (closed_clients, closed_servers) = match mode {
+        ShutdownMode::Graceful => (0, 0),
+        ShutdownMode::Immediate => (
+            get_client_stats().len().saturating_sub(1),
+            get_server_stats().len(),
+        ),
+    };
 
     let pid = std::process::id();
-    if signal::kill(Pid::from_raw(pid.try_into().unwrap()), Signal::SIGINT).is_err() {
-        error!("Unable to send SIGINT to PID: {pid}");
+    let signal = match mode {
+        ShutdownMode::Graceful => Signal::SIGINT,
+        ShutdownMode::Immediate => Signal::SIGTERM,
+    };
+    // SIGTERM exits the process at once, so IMMEDIATE answers before
+    // sending it.
+    let mut shutdown_success = "t";
+    if mode == ShutdownMode::Graceful
+        && signal::kill(Pid::from_raw(pid.try_into().unwrap()), signal).is_err()
+    {
+        error!("Unable to send {signal} to PID: {pid}");
         shutdown_success = "f";
     }
 
-    res.put(data_row(&[shutdown_success.to_string()]));
+    res.put(data_row(&[
+        shutdown_success.to_string(),
+        mode.as_str().to_string(),
+        closed_clients.to_string(),
+        closed_servers.to_string(),
+    ]));
 
     res.put(command_complete("SHUTDOWN"));
 
@@ -78,7 +143,17 @@ where
     res.put_i32(5);
     res.put_u8(b'I');
 
-    write_all_half(stream, &res).await
+    write_all_half(stream, &res).await?;
+
+    if mode == ShutdownMode::Immediate {
+        info!(
+            "SHUTDOWN IMMEDIATE: closing {closed_clients} client and {closed_servers} server connections"
+        );
+        if signal::kill(Pid::from_raw(pid.try_into().unwrap()), signal).is_err() {
+            error!("Unable to send {signal} to PID: {pid}");
+        }
+    }
+    Ok(())
 }
 
 /// Trigger binary upgrade via SIGUSR2 (graceful shutdown + spawn new process).
@@ -191,7 +266,8 @@ where
 }
 
 /// Resume connection pools — unblocks clients waiting due to PAUSE.
-/// If `db` is Some, only pools for that database are resumed.
+/// If `db` is Some, only pools for that database are resumed; otherwise
+/// DRAIN is lifted as well.
 pub async fn resume<T>(stream: &mut T, db: Option<String>) -> Result<(), Error>
 where
     T: tokio::io::AsyncWrite + std::marker::Unpin,
@@ -199,6 +275,36 @@ where
     render_effect(stream, "RESUME", resume_now(db_scope(db))).await
 }
 
+/// Close the client listener; existing clients keep working.
+pub async fn drain<T>(stream: &mut T) -> Result<(), Error>
+where
+    T: tokio::io::AsyncWrite + std::marker::Unpin,
+{
+    crate::app::drain::drain();
+    command_ok(stream, "DRAIN").await
+}
+
+/// Reopen the client listener closed by DRAIN.
+pub async fn undrain<T>(stream: &mut T) -> Result<(), Error>
+where
+    T: tokio::io::AsyncWrite + std::marker::Unpin,
+{
+    crate::app::drain::undrain();
+    command_ok(stream, "UNDRAIN").await
+}
+
+async fn command_ok<T>(stream: &mut T, command: &str) -> Result<(), Error>
+where
+    T: tokio::io::AsyncWrite + std::marker::Unpin,
+{
+    let mut res = BytesMut::new();
+    res.put(command_complete(command));
+    res.put_u8(b'Z');
+    res.put_i32(5);
+    res.put_u8(b'I');
+    write_all_half(stream, &res).await
+}
+
 /// Reconnect connection pools — bumps epoch and drains idle connections.
 /// Active connections are rejected when returned to the pool.
 /// If `db` is Some, only pools for that database are reconnected.
@@ -9,6 +9,8 @@
 //!   `/api/overview` poll.
 //! * `RELOAD` / `PAUSE` / `RESUME` / `RECONNECT` — admin commands
 //!   (`src/admin/commands.rs`, `src/admin/operations.rs`).
+//! * `AUTO_PAUSE` / `AUTO_RESUME` — a pool with `auto_pause_on_unhealthy`
+//!   crossed its failure or success threshold (`src/pool/backend_health.rs`).
 //! * `CONFIG_VALIDATION_ERROR` — pushed when a config reload (admin
 //!   RELOAD or `SIGHUP`) is rejected by `Config::validate`. Carries
 //!   the validator's message so the operator sees *why* the new
@@ -36,7 +38,8 @@ pub struct EventEntry {
     /// Wall-clock timestamp in milliseconds since unix epoch.
     pub ts_ms: u64,
     /// One of `"PROCESS_START"`, `"RELOAD"`, `"PAUSE"`, `"RESUME"`,
-    /// `"RECONNECT"`, `"CONFIG_VALIDATION_ERROR"`. Treat as an open enum:
+    /// `"RECONNECT"`, `"AUTO_PAUSE"`, `"AUTO_RESUME"`,
+    /// `"CONFIG_VALIDATION_ERROR"`. Treat as an open enum:
     /// the frontend maps unknown targets to a neutral chip rather than
     /// failing, so adding a new target on the backend never breaks an
     /// older UI build.
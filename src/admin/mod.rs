@@ -4,6 +4,7 @@
 //! including SHOW commands for statistics and RELOAD/SHUTDOWN commands.
 
 mod commands;
+mod probes;
 mod show;
 
 pub mod events;
@@ -26,23 +27,33 @@ use crate::pool::ClientServerMap;
 pub(crate) const SHOW_SUBCOMMANDS: &[&str] = &[
     "help",
     "config",
+    "config_effective",
+    "hba",
     "databases",
     "pools",
     "pools_extended",
     "pools_memory",
+    "mem",
     "pool_coordinator",
     "pool_scaling",
     "prepared_statements",
     "interner",
     "clients",
     "servers",
+    "active_queries",
+    "wait_events",
     "connections",
     "stats",
+    "stats_by_app",
+    "rates",
     "version",
     "users",
     "auth_query",
     "startup_parameters",
     "log_level",
+    "state",
+    "workers",
+    "bans",
     "lists",
     #[cfg(target_os = "linux")]
     "sockets",
@@ -50,22 +61,27 @@ pub(crate) const SHOW_SUBCOMMANDS: &[&str] = &[
 
 #[cfg(not(windows))]
 use commands::upgrade;
-use commands::{pause, reconnect, reload, resume, shutdown};
+use commands::{drain, pause, reconnect, reload, resume, shutdown, undrain, ShutdownMode};
+use probes::{answer_probe, parse_probe, show_setting};
 #[cfg(target_os = "linux")]
 use show::show_sockets;
 use show::{
-    reset_interner, show_auth_query, show_clients, show_config, show_connections, show_databases,
-    show_help, show_interner, show_interner_top, show_lists, show_log_level, show_pool_coordinator,
+    reset_bans, reset_interner, show_active_queries, show_auth_query, show_bans, show_clients,
+    show_config, show_config_effective, show_connections, show_databases, show_hba, show_help,
+    show_interner, show_interner_top, show_lists, show_log_level, show_mem, show_pool_coordinator,
     show_pool_scaling, show_pools, show_pools_extended, show_pools_memory,
-    show_prepared_statements, show_servers, show_startup_parameters, show_stats, show_users,
-    show_version,
+    show_prepared_statements, show_prepared_statements_clients, show_rates, show_servers,
+    show_startup_parameters, show_state, show_stats, show_stats_by_app, show_users, show_version,
+    show_wait_events, show_workers, test_hba,
 };
 
-/// Handle admin client.
+/// Handle admin client. `readonly` logins (see `general.admin_users`) may
+/// only run commands that [`is_read_only_command`] accepts.
 pub async fn handle_admin<T>(
     stream: &mut T,
     mut query: BytesMut,
     client_server_map: ClientServerMap,
+    readonly: bool,
 ) -> Result<(), Error>
 where
     T: tokio::io::AsyncWrite + std::marker::Unpin,
@@ -90,10 +106,46 @@ where
 
     let query_parts: Vec<&str> = query.trim_end_matches(';').split_whitespace().collect();
 
+    if readonly && !is_read_only_command(&query_parts) {
+        let command = query_parts
+            .first()
+            .map(|part| part.to_ascii_uppercase())
+            .unwrap_or_default();
+        warn!("read-only admin login refused: {command}");
+        return error_response(
+            stream,
+            &format!("permission denied for {command}: this admin login is read-only"),
+            "42501",
+        )
+        .await;
+    }
+
     match query_parts[0].to_ascii_uppercase().as_str() {
+        "SELECT" => match parse_probe(&query) {
+            Some(probe) => answer_probe(stream, probe).await,
+            None => {
+                warn!("unsupported admin SELECT: {query}");
+                error_response(
+                    stream,
+                    "Unsupported query against the admin database",
+                    "58000",
+                )
+                .await
+            }
+        },
         "SET" => set_command(stream, &query_parts).await,
         "RELOAD" => reload(stream, client_server_map).await,
-        "SHUTDOWN" => shutdown(stream).await,
+        "SHUTDOWN" => match ShutdownMode::parse(&query_parts[1..]) {
+            Some(mode) => shutdown(stream, mode).await,
+            None => {
+                error_response(
+                    stream,
+                    "Unsupported SHUTDOWN mode, use SHUTDOWN or SHUTDOWN IMMEDIATE",
+                    "58000",
+                )
+                .await
+            }
+        },
         #[cfg(not(windows))]
         "UPGRADE" => upgrade(stream).await,
         "PAUSE" => {
@@ -104,6 +156,8 @@ where
             let db = query_parts.get(1).map(|s| s.to_string());
             resume(stream, db).await
         }
+        "DRAIN" => drain(stream).await,
+        "UNDRAIN" => undrain(stream).await,
         "RECONNECT" => {
             let db = query_parts.get(1).map(|s| s.to_string());
             reconnect(stream, db).await
@@ -120,21 +174,33 @@ where
             } else {
                 match query_parts[1].to_ascii_uppercase().as_str() {
                     "HELP" => show_help(stream).await,
-                    "CONFIG" => show_config(stream).await,
+                    "CONFIG" => show_config(stream, &query_parts[2..]).await,
+                    "CONFIG_EFFECTIVE" => show_config_effective(stream).await,
+                    "HBA" => show_hba(stream).await,
                     "DATABASES" => show_databases(stream).await,
                     "LISTS" => show_lists(stream).await,
-                    "POOLS" => show_pools(stream).await,
+                    "POOLS" => show_pools(stream, &query_parts[2..]).await,
                     "POOLS_EXTENDED" => show_pools_extended(stream).await,
                     "POOLS_MEMORY" | "POOL_MEMORY" => show_pools_memory(stream).await,
-                    "PREPARED_STATEMENTS" => show_prepared_statements(stream).await,
+                    "MEM" => show_mem(stream).await,
+                    "PREPARED_STATEMENTS" => match query_parts.get(2) {
+                        Some(arg) if arg.eq_ignore_ascii_case("CLIENTS") => {
+                            show_prepared_statements_clients(stream).await
+                        }
+                        _ => show_prepared_statements(stream).await,
+                    },
                     "INTERNER" => match query_parts.get(2).and_then(|s| s.parse::<usize>().ok()) {
                         Some(n) => show_interner_top(stream, n).await,
                         None => show_interner(stream).await,
                     },
-                    "CLIENTS" => show_clients(stream).await,
-                    "SERVERS" => show_servers(stream).await,
+                    "CLIENTS" => show_clients(stream, &query_parts[2..]).await,
+                    "SERVERS" => show_servers(stream, &query_parts[2..]).await,
+                    "ACTIVE_QUERIES" => show_active_queries(stream).await,
+                    "WAIT_EVENTS" => show_wait_events(stream).await,
                     "CONNECTIONS" => show_connections(stream).await,
                     "STATS" => show_stats(stream).await,
+                    "STATS_BY_APP" => show_stats_by_app(stream).await,
+                    "RATES" => show_rates(stream).await,
                     "VERSION" => show_version(stream).await,
                     "USERS" => show_users(stream).await,
                     "AUTH_QUERY" => show_auth_query(stream).await,
@@ -142,8 +208,27 @@ where
                     "POOL_COORDINATOR" => show_pool_coordinator(stream).await,
                     "POOL_SCALING" => show_pool_scaling(stream).await,
                     "LOG_LEVEL" => show_log_level(stream).await,
+                    "STATE" => show_state(stream).await,
+                    "WORKERS" => show_workers(stream).await,
+                    "BANS" => show_bans(stream).await,
                     #[cfg(target_os = "linux")]
                     "SOCKETS" => show_sockets(stream).await,
+                    _ if query_parts.len() == 2 => match show_setting(stream, query_parts[1]).await
+                    {
+                        Some(result) => result,
+                        None => {
+                            warn!(
+                                "unsupported admin subcommand for SHOW: {}",
+                                query_parts[1].to_ascii_uppercase().as_str()
+                            );
+                            error_response(
+                                stream,
+                                "Unsupported SHOW query against the admin database",
+                                "58000",
+                            )
+                            .await
+                        }
+                    },
                     _ => {
                         warn!(
                             "unsupported admin subcommand for SHOW: {}",
@@ -159,14 +244,21 @@ where
                 }
             }
         }
+        "TEST" if query_parts.len() >= 2 && query_parts[1].eq_ignore_ascii_case("HBA") => {
+            test_hba(stream, &query_parts[2..]).await
+        }
         "RESET" => {
             if query_parts.len() == 2 && query_parts[1].eq_ignore_ascii_case("INTERNER") {
                 reset_interner(stream).await
+            } else if matches!(query_parts.len(), 2 | 3)
+                && query_parts[1].eq_ignore_ascii_case("BANS")
+            {
+                reset_bans(stream, query_parts.get(2).copied()).await
             } else {
                 warn!("unsupported admin RESET target: {query_parts:?}");
                 error_response(
                     stream,
-                    "Unsupported RESET target — only RESET INTERNER is supported",
+                    "Unsupported RESET target — use RESET INTERNER or RESET BANS [ip]",
                     "58000",
                 )
                 .await
@@ -187,6 +279,22 @@ where
     }
 }
 
+/// Commands a read-only admin login may run: every `SHOW`, the `SELECT`
+/// introspection probes and `TEST HBA`.
+fn is_read_only_command(query_parts: &[&str]) -> bool {
+    match query_parts {
+        [command, ..]
+            if command.eq_ignore_ascii_case("SHOW") || command.eq_ignore_ascii_case("SELECT") =>
+        {
+            true
+        }
+        [command, target, ..] => {
+            command.eq_ignore_ascii_case("TEST") && target.eq_ignore_ascii_case("HBA")
+        }
+        _ => false,
+    }
+}
+
 /// Respond to psql tab-completion queries that reference pg_catalog.pg_settings.
 /// psql sends these automatically when the user presses TAB after SET or SHOW.
 async fn handle_tab_completion<T>(stream: &mut T, query: &str) -> Result<(), Error>
@@ -291,4 +399,29 @@ mod tests {
             "SHOW_SUBCOMMANDS missing startup_parameters: {SHOW_SUBCOMMANDS:?}"
         );
     }
+
+    #[test]
+    fn shutdown_accepts_only_immediate_mode() {
+        assert_eq!(ShutdownMode::parse(&[]), Some(ShutdownMode::Graceful));
+        assert_eq!(
+            ShutdownMode::parse(&["immediate"]),
+            Some(ShutdownMode::Immediate)
+        );
+        assert_eq!(ShutdownMode::parse(&["FAST"]), None);
+        assert_eq!(ShutdownMode::parse(&["IMMEDIATE", "now"]), None);
+    }
+
+    #[test]
+    fn read_only_commands_are_show_and_test_hba() {
+        assert!(is_read_only_command(&["show", "pools"]));
+        assert!(is_read_only_command(&["SHOW"]));
+        assert!(is_read_only_command(&["SELECT", "version()"]));
+        assert!(is_read_only_command(&["TEST", "hba", "host"]));
+        assert!(!is_read_only_command(&["TEST"]));
+        assert!(!is_read_only_command(&["RELOAD"]));
+        assert!(!is_read_only_command(&["SHUTDOWN"]));
+        assert!(!is_read_only_command(&["SET", "log_level", "=", "'debug'"]));
+        assert!(!is_read_only_command(&["RESET", "BANS"]));
+        assert!(!is_read_only_command(&[]));
+    }
 }
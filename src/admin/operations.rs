@@ -13,7 +13,7 @@
 
 use log::info;
 
-use crate::config::reload_config;
+use crate::config::{reload_config, ConfigDiff};
 use crate::errors::Error;
 use crate::pool::{get_all_pools, get_client_server_map, ConnectionPool, PoolIdentifier};
 
@@ -59,14 +59,14 @@ pub enum AdminEffect {
 }
 
 /// Reload the configuration file. Equivalent to `RELOAD` on the admin
-/// protocol; emits the same RELOAD event. Returns `true` when the config
-/// actually changed and pools were reconciled, `false` when the file
-/// re-parsed identically to the live config (a no-op reload).
-pub async fn reload_now() -> Result<bool, Error> {
+/// protocol; emits the same RELOAD event. Returns the changes applied;
+/// an empty diff means the file re-parsed identically to the live config
+/// (a no-op reload).
+pub async fn reload_now() -> Result<ConfigDiff, Error> {
     let csm = get_client_server_map()
         .ok_or_else(|| Error::SocketError("client_server_map not initialised".into()))?;
     info!("Reloading config (via /api/admin/reload)");
-    let changed = match reload_config(csm).await {
+    let diff = match reload_config(csm).await {
         Ok(c) => c,
         Err(e) => {
             crate::admin::events::push_event_rate_limited(
@@ -78,7 +78,7 @@ pub async fn reload_now() -> Result<bool, Error> {
     };
     crate::admin::events::push_event("RELOAD", "config reloaded".to_string());
     crate::config::get_config().show();
-    Ok(changed)
+    Ok(diff)
 }
 
 /// Pause every pool the scope selects.
@@ -90,8 +90,11 @@ pub fn pause_now(scope: AdminScope) -> AdminEffect {
     })
 }
 
-/// Resume — mirror of [`pause_now`].
+/// Resume — mirror of [`pause_now`]. A server-wide resume also ends DRAIN.
 pub fn resume_now(scope: AdminScope) -> AdminEffect {
+    if scope == AdminScope::AllPools {
+        crate::app::drain::undrain();
+    }
     apply_per_pool(scope, |identifier, pool| {
         pool.database.resume();
         crate::admin::events::push_event("RESUME", format!("pool {identifier} resumed"));
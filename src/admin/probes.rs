@@ -0,0 +1,150 @@
+//! Local answers to the introspection queries that GUI tools and drivers
+//! send right after connecting (`SELECT version()`, `current_setting(...)`,
+//! `SHOW server_version`). The admin database has no backend, so without
+//! these the client gives up before the operator can type `SHOW HELP`.
+
+use bytes::{BufMut, BytesMut};
+
+use crate::config::VERSION;
+use crate::errors::Error;
+use crate::messages::protocol::{command_complete, data_row, error_response, row_description};
+use crate::messages::types::DataType;
+use crate::messages::write_all_half;
+use crate::server::ServerParameters;
+
+/// An introspection query the admin database can answer itself.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Probe {
+    /// `SELECT version()`
+    Version,
+    /// `SELECT current_setting('<name>')`
+    CurrentSetting(String),
+}
+
+/// Recognize a probe in a `SELECT` sent to the admin database. Matching is
+/// case-insensitive and ignores whitespace, a trailing semicolon and a
+/// `pg_catalog.` prefix on the function name.
+pub(crate) fn parse_probe(query: &str) -> Option<Probe> {
+    let query = query.trim().trim_end_matches(';').trim();
+    let (select, rest) = query.split_once(char::is_whitespace)?;
+    if !select.eq_ignore_ascii_case("SELECT") {
+        return None;
+    }
+    let call: String = rest.split_whitespace().collect();
+    let lower = call.to_ascii_lowercase();
+    let lower = lower.strip_prefix("pg_catalog.").unwrap_or(&lower);
+    if lower == "version()" {
+        return Some(Probe::Version);
+    }
+    let arg = lower
+        .strip_prefix("current_setting(")?
+        .strip_suffix(')')?
+        .strip_prefix('\'')?
+        .strip_suffix('\'')?;
+    if arg.is_empty() || arg.contains('\'') {
+        return None;
+    }
+    Some(Probe::CurrentSetting(arg.to_string()))
+}
+
+/// Value of a parameter the admin login reports, matched case-insensitively
+/// like PostgreSQL GUC names.
+pub(crate) fn admin_setting(name: &str) -> Option<String> {
+    ServerParameters::admin()
+        .as_hashmap()
+        .into_iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value)
+}
+
+/// Answer a recognized probe.
+pub async fn answer_probe<T>(stream: &mut T, probe: Probe) -> Result<(), Error>
+where
+    T: tokio::io::AsyncWrite + std::marker::Unpin,
+{
+    match probe {
+        Probe::Version => {
+            single_value(stream, "version", &format!("PgDoorman {VERSION}"), "SELECT").await
+        }
+        Probe::CurrentSetting(name) => match admin_setting(&name) {
+            Some(value) => single_value(stream, "current_setting", &value, "SELECT").await,
+            None => unrecognized_parameter(stream, &name).await,
+        },
+    }
+}
+
+/// `SHOW <parameter>` for a name that is not a SHOW subcommand: answered
+/// from the admin login's parameters, or `None` if it is not one of them.
+pub async fn show_setting<T>(stream: &mut T, name: &str) -> Option<Result<(), Error>>
+where
+    T: tokio::io::AsyncWrite + std::marker::Unpin,
+{
+    let value = admin_setting(name)?;
+    Some(single_value(stream, &name.to_ascii_lowercase(), &value, "SHOW").await)
+}
+
+async fn single_value<T>(stream: &mut T, column: &str, value: &str, tag: &str) -> Result<(), Error>
+where
+    T: tokio::io::AsyncWrite + std::marker::Unpin,
+{
+    let mut res = BytesMut::new();
+    res.put(row_description(&vec![(column, DataType::Text)]));
+    res.put(data_row(&[value]));
+    res.put(command_complete(tag));
+    res.put_u8(b'Z');
+    res.put_i32(5);
+    res.put_u8(b'I');
+    write_all_half(stream, &res).await
+}
+
+async fn unrecognized_parameter<T>(stream: &mut T, name: &str) -> Result<(), Error>
+where
+    T: tokio::io::AsyncWrite + std::marker::Unpin,
+{
+    error_response(
+        stream,
+        &format!("unrecognized configuration parameter \"{name}\""),
+        "42704",
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_version_probes() {
+        assert_eq!(parse_probe("SELECT version()"), Some(Probe::Version));
+        assert_eq!(parse_probe("select  VERSION ( ) ;"), Some(Probe::Version));
+        assert_eq!(
+            parse_probe("SELECT pg_catalog.version();"),
+            Some(Probe::Version)
+        );
+        assert_eq!(parse_probe("SELECT version() FROM foo"), None);
+        assert_eq!(parse_probe("SELECT 1"), None);
+        assert_eq!(parse_probe("SHOW version"), None);
+    }
+
+    #[test]
+    fn parses_current_setting_probes() {
+        assert_eq!(
+            parse_probe("SELECT current_setting('server_version_num')"),
+            Some(Probe::CurrentSetting("server_version_num".into()))
+        );
+        assert_eq!(
+            parse_probe("select pg_catalog.current_setting( 'DateStyle' );"),
+            Some(Probe::CurrentSetting("datestyle".into()))
+        );
+        assert_eq!(parse_probe("SELECT current_setting(name)"), None);
+        assert_eq!(parse_probe("SELECT current_setting('')"), None);
+        assert_eq!(parse_probe("SELECT current_setting('a', true)"), None);
+    }
+
+    #[test]
+    fn admin_setting_is_case_insensitive() {
+        assert_eq!(admin_setting("server_version").as_deref(), Some(VERSION));
+        assert_eq!(admin_setting("DATESTYLE").as_deref(), Some("ISO, MDY"));
+        assert_eq!(admin_setting("work_mem"), None);
+    }
+}
@@ -1,84 +1,51 @@
 //! Admin SHOW commands implementation.
 
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use bytes::{BufMut, BytesMut};
 
-use crate::app::log_level;
-use crate::config::{get_config, VERSION};
+use crate::app::{drain, log_level};
+use crate::config::{get_config, BUILD_DATE, FEATURES, GIT_COMMIT, RUSTC_VERSION, VERSION};
 use crate::errors::Error;
-use crate::messages::protocol::{command_complete, data_row, row_description};
+use crate::messages::protocol::{command_complete, data_row, data_row_nullable, row_description};
 use crate::messages::socket::write_all_half;
 use crate::messages::types::DataType;
-use crate::pool::{get_all_pools, AUTH_QUERY_STATE, COORDINATORS, DYNAMIC_POOLS};
+use crate::pool::{get_all_pools, PoolIdentifier, AUTH_QUERY_STATE, COORDINATORS, DYNAMIC_POOLS};
 #[cfg(target_os = "linux")]
 use crate::stats::cached_socket_states_count;
-use crate::stats::client::{CLIENT_STATE_ACTIVE, CLIENT_STATE_IDLE};
+use crate::stats::client::{
+    CLIENT_STATE_ACTIVE, CLIENT_STATE_WAITING, CLIENT_WAIT_READ, CLIENT_WAIT_WRITE,
+};
 use crate::stats::pool::PoolStats;
-use crate::stats::server::{SERVER_STATE_ACTIVE, SERVER_STATE_IDLE};
+use crate::stats::rates::{rates_snapshot, Rates, RATE_WINDOWS_SECS};
+use crate::stats::server::SERVER_STATE_ACTIVE;
 use crate::stats::{
     get_client_stats, get_server_stats, CANCEL_CONNECTION_COUNTER, PLAIN_CONNECTION_COUNTER,
     TLS_CONNECTION_COUNTER, TOTAL_CONNECTION_COUNTER,
 };
 
-/// Column-oriented statistics.
+/// Object counts in PgBouncer's `SHOW LISTS` layout, so dashboards built
+/// for PgBouncer read them unchanged.
 pub async fn show_lists<T>(stream: &mut T) -> Result<(), Error>
 where
     T: tokio::io::AsyncWrite + std::marker::Unpin,
 {
-    let client_stats = get_client_stats();
-    let server_stats = get_server_stats();
+    let pools = get_all_pools();
+    let rows = lists_rows(
+        pools.keys(),
+        get_client_stats().len(),
+        get_server_stats().len(),
+    );
+
     let columns = vec![("list", DataType::Text), ("items", DataType::Int4)];
-    let mut users = 1;
-    let mut databases = 1;
-    for (_, _) in get_all_pools().iter() {
-        databases += 1; // One db per pool
-        users += 1; // One user per pool
-    }
     let mut res = BytesMut::new();
     res.put(row_description(&columns));
-    res.put(data_row(&["databases".to_string(), databases.to_string()]));
-    res.put(data_row(&["users".to_string(), users.to_string()]));
-    res.put(data_row(&["pools".to_string(), databases.to_string()]));
-    res.put(data_row(&[
-        "free_clients".to_string(),
-        client_stats
-            .keys()
-            .filter(|client_id| client_stats.get(client_id).unwrap().state() == CLIENT_STATE_IDLE)
-            .count()
-            .to_string(),
-    ]));
-    res.put(data_row(&[
-        "used_clients".to_string(),
-        client_stats
-            .keys()
-            .filter(|client_id| client_stats.get(client_id).unwrap().state() == CLIENT_STATE_ACTIVE)
-            .count()
-            .to_string(),
-    ]));
-    res.put(data_row(&["login_clients".to_string(), "0".to_string()]));
-    res.put(data_row(&[
-        "free_servers".to_string(),
-        server_stats
-            .keys()
-            .filter(|server_id| server_stats.get(server_id).unwrap().state() == SERVER_STATE_IDLE)
-            .count()
-            .to_string(),
-    ]));
-    res.put(data_row(&[
-        "used_servers".to_string(),
-        server_stats
-            .keys()
-            .filter(|server_id| server_stats.get(server_id).unwrap().state() == SERVER_STATE_ACTIVE)
-            .count()
-            .to_string(),
-    ]));
-    res.put(data_row(&["dns_names".to_string(), "0".to_string()]));
-    res.put(data_row(&["dns_zones".to_string(), "0".to_string()]));
-    res.put(data_row(&["dns_queries".to_string(), "0".to_string()]));
-    res.put(data_row(&["dns_pending".to_string(), "0".to_string()]));
+    for (list, items) in rows {
+        res.put(data_row(&[list.to_string(), items.to_string()]));
+    }
     res.put(command_complete("SHOW"));
     res.put_u8(b'Z');
     res.put_i32(5);
@@ -86,14 +53,63 @@ where
     write_all_half(stream, &res).await
 }
 
+/// `SHOW LISTS` rows in PgBouncer's order. `databases` and `users` count
+/// the admin database and user too, as PgBouncer counts its own. Like
+/// PgBouncer, `used_clients` and `used_servers` count every connection;
+/// pg_doorman keeps no cache of released connection objects and has no
+/// peers or DNS cache, so the `free_*`, `peer*` and `dns_*` rows are 0.
+/// Clients show up only after authentication, so `login_clients` is 0.
+fn lists_rows<'a>(
+    pools: impl Iterator<Item = &'a PoolIdentifier>,
+    used_clients: usize,
+    used_servers: usize,
+) -> [(&'static str, usize); 14] {
+    let mut databases = HashSet::new();
+    let mut users = HashSet::new();
+    let mut pool_count = 0;
+    for identifier in pools {
+        databases.insert(identifier.db.as_str());
+        users.insert(identifier.user.as_str());
+        pool_count += 1;
+    }
+    [
+        ("databases", databases.len() + 1),
+        ("users", users.len() + 1),
+        ("peers", 0),
+        ("pools", pool_count),
+        ("peer_pools", 0),
+        ("free_clients", 0),
+        ("used_clients", used_clients),
+        ("login_clients", 0),
+        ("free_servers", 0),
+        ("used_servers", used_servers),
+        ("dns_names", 0),
+        ("dns_zones", 0),
+        ("dns_queries", 0),
+        ("dns_pending", 0),
+    ]
+}
+
 /// Show PgDoorman version.
 pub async fn show_version<T>(stream: &mut T) -> Result<(), Error>
 where
     T: tokio::io::AsyncWrite + std::marker::Unpin,
 {
     let mut res = BytesMut::new();
-    res.put(row_description(&vec![("version", DataType::Text)]));
-    res.put(data_row(&[format!("PgDoorman {}", VERSION)]));
+    res.put(row_description(&vec![
+        ("version", DataType::Text),
+        ("git_commit", DataType::Text),
+        ("build_date", DataType::Text),
+        ("rustc_version", DataType::Text),
+        ("features", DataType::Text),
+    ]));
+    res.put(data_row(&[
+        format!("PgDoorman {}", VERSION),
+        GIT_COMMIT.to_string(),
+        BUILD_DATE.to_string(),
+        RUSTC_VERSION.to_string(),
+        FEATURES.to_string(),
+    ]));
     res.put(command_complete("SHOW"));
     res.put_u8(b'Z');
     res.put_i32(5);
@@ -116,25 +132,109 @@ where
     write_all_half(stream, &res).await
 }
 
-/// Show utilization of connection pools for each pool.
-pub async fn show_pools<T>(stream: &mut T) -> Result<(), Error>
+/// Show the server-wide state: `accepting`, `draining` (DRAIN closed the
+/// listener), `paused` (every pool paused) or `shutting_down`.
+pub async fn show_state<T>(stream: &mut T) -> Result<(), Error>
 where
     T: tokio::io::AsyncWrite + std::marker::Unpin,
 {
-    let pool_lookup = PoolStats::construct_pool_lookup();
+    let pools = get_all_pools();
+    let paused = pools
+        .values()
+        .filter(|pool| pool.database.is_paused())
+        .count();
     let mut res = BytesMut::new();
-    res.put(row_description(&PoolStats::generate_show_pools_header()));
-    pool_lookup.iter().for_each(|(_identifier, pool_stats)| {
-        res.put(data_row(&pool_stats.generate_show_pools_row()));
-    });
+    res.put(row_description(&vec![
+        ("state", DataType::Text),
+        ("paused_pools", DataType::Int4),
+        ("total_pools", DataType::Int4),
+    ]));
+    res.put(data_row(&[
+        drain::server_state().as_str().to_string(),
+        paused.to_string(),
+        pools.len().to_string(),
+    ]));
     res.put(command_complete("SHOW"));
-    // ReadyForQuery
     res.put_u8(b'Z');
     res.put_i32(5);
     res.put_u8(b'I');
     write_all_half(stream, &res).await
 }
 
+/// Show the tokio runtime this process runs on. Runtime settings are only
+/// read at start, so after UPGRADE this is how to confirm the new process
+/// picked up a changed `worker_threads`.
+pub async fn show_workers<T>(stream: &mut T) -> Result<(), Error>
+where
+    T: tokio::io::AsyncWrite + std::marker::Unpin,
+{
+    let config = get_config();
+    let metrics = tokio::runtime::Handle::current().metrics();
+    let mut res = BytesMut::new();
+    res.put(row_description(&vec![
+        ("pid", DataType::Int4),
+        ("uptime_seconds", DataType::Numeric),
+        ("worker_threads", DataType::Int4),
+        ("alive_tasks", DataType::Numeric),
+        ("global_queue_depth", DataType::Numeric),
+        ("cpu_affinity_pinning", DataType::Text),
+        ("worker_stack_size", DataType::Text),
+        ("max_blocking_threads", DataType::Text),
+    ]));
+    res.put(data_row(&[
+        std::process::id().to_string(),
+        crate::app::server::STARTED_AT
+            .elapsed()
+            .map_or(0, |d| d.as_secs())
+            .to_string(),
+        metrics.num_workers().to_string(),
+        metrics.num_alive_tasks().to_string(),
+        metrics.global_queue_depth().to_string(),
+        crate::app::server::WORKER_CPU_AFFINITY_PINNED
+            .load(Ordering::Relaxed)
+            .to_string(),
+        config
+            .general
+            .worker_stack_size
+            .map_or_else(|| "default".to_string(), |size| size.to_string()),
+        config
+            .general
+            .max_blocking_threads
+            .map_or_else(|| "default".to_string(), |n| n.to_string()),
+    ]));
+    res.put(command_complete("SHOW"));
+    res.put_u8(b'Z');
+    res.put_i32(5);
+    res.put_u8(b'I');
+    write_all_half(stream, &res).await
+}
+
+/// Show utilization of connection pools for each pool, optionally only
+/// those of one database or `<user>@<database>`.
+pub async fn show_pools<T>(stream: &mut T, args: &[&str]) -> Result<(), Error>
+where
+    T: tokio::io::AsyncWrite + std::marker::Unpin,
+{
+    let rows = PoolStats::construct_pool_lookup()
+        .values()
+        .map(|pool_stats| {
+            pool_stats
+                .generate_show_pools_row()
+                .into_iter()
+                .map(Cow::into_owned)
+                .collect()
+        })
+        .collect();
+    show_rows(
+        stream,
+        "POOLS",
+        PoolStats::generate_show_pools_header(),
+        rows,
+        args,
+    )
+    .await
+}
+
 /// Show memory utilization of connection pools.
 pub async fn show_pools_memory<T>(stream: &mut T) -> Result<(), Error>
 where
@@ -156,6 +256,70 @@ where
     write_all_half(stream, &res).await
 }
 
+/// Process-wide memory view: message buffers against `max_memory_usage`,
+/// the client buffer pool, and prepared statement cache totals.
+pub async fn show_mem<T>(stream: &mut T) -> Result<(), Error>
+where
+    T: tokio::io::AsyncWrite + std::marker::Unpin,
+{
+    use crate::client::buffer_pool::buffer_pool_stats;
+    use crate::messages::{CURRENT_MEMORY, PEAK_MEMORY};
+
+    let config = get_config();
+    let buffers = buffer_pool_stats();
+    let (pool_prepared_bytes, client_prepared_bytes) = PoolStats::construct_pool_lookup()
+        .values()
+        .fold((0u64, 0u64), |(pool, client), stats| {
+            (
+                pool + stats.prepared_statements_bytes,
+                client + stats.client_prepared_bytes,
+            )
+        });
+
+    let columns = vec![("name", DataType::Text), ("value", DataType::Numeric)];
+    let rows = [
+        (
+            "message_buffers_bytes",
+            CURRENT_MEMORY.load(Ordering::Relaxed).to_string(),
+        ),
+        (
+            "message_buffers_peak_bytes",
+            PEAK_MEMORY.load(Ordering::Relaxed).to_string(),
+        ),
+        (
+            "max_memory_usage",
+            config.general.max_memory_usage.as_bytes().to_string(),
+        ),
+        ("buffer_pool_allocated", buffers.allocated().to_string()),
+        ("buffer_pool_checked_out", buffers.checked_out.to_string()),
+        ("buffer_pool_hits", buffers.hits.to_string()),
+        ("buffer_pool_misses", buffers.misses.to_string()),
+        ("buffer_pool_hit_rate", format!("{:.2}", buffers.hit_rate())),
+        (
+            "buffer_pool_dropped_oversized",
+            buffers.dropped_oversized.to_string(),
+        ),
+        ("pool_prepared_bytes", pool_prepared_bytes.to_string()),
+        ("client_prepared_bytes", client_prepared_bytes.to_string()),
+        (
+            "process_rss_bytes",
+            crate::web::metrics::system::get_process_memory_usage().to_string(),
+        ),
+    ];
+
+    let mut res = BytesMut::new();
+    res.put(row_description(&columns));
+    for (name, value) in rows {
+        res.put(data_row(&[name.to_string(), value]));
+    }
+    res.put(command_complete("SHOW"));
+    // ReadyForQuery
+    res.put_u8(b'Z');
+    res.put_i32(5);
+    res.put_u8(b'I');
+    write_all_half(stream, &res).await
+}
+
 /// Show all entries in the global prepared statement cache across all pools.
 pub async fn show_prepared_statements<T>(stream: &mut T) -> Result<(), Error>
 where
@@ -196,6 +360,48 @@ where
     write_all_half(stream, &res).await
 }
 
+/// `SHOW PREPARED_STATEMENTS CLIENTS`: for every connected client, each
+/// Named statement with the server-side name it is prepared under, plus the
+/// client's count of names rebound to a different query.
+pub async fn show_prepared_statements_clients<T>(stream: &mut T) -> Result<(), Error>
+where
+    T: tokio::io::AsyncWrite + std::marker::Unpin,
+{
+    let columns = vec![
+        ("client_id", DataType::Text),
+        ("database", DataType::Text),
+        ("user", DataType::Text),
+        ("client_name", DataType::Text),
+        ("server_name", DataType::Text),
+        ("remaps", DataType::Numeric),
+    ];
+    let mut res = BytesMut::new();
+    res.put(row_description(&columns));
+
+    let mut clients: Vec<_> = get_client_stats().into_values().collect();
+    clients.sort_by_key(|client| client.connection_id());
+    for client in clients {
+        let remaps = client.prepared_remaps().to_string();
+        for (client_name, server_name) in client.prepared_names() {
+            res.put(data_row(&[
+                format!("#c{}", client.connection_id()),
+                client.pool_name().to_string(),
+                client.username().to_string(),
+                client_name,
+                server_name,
+                remaps.clone(),
+            ]));
+        }
+    }
+
+    res.put(command_complete("SHOW"));
+    // ReadyForQuery
+    res.put_u8(b'Z');
+    res.put_i32(5);
+    res.put_u8(b'I');
+    write_all_half(stream, &res).await
+}
+
 /// Aggregate of the global query interner, grouped by kind. Two rows
 /// (named, anonymous) with entry counts and uncompressed byte totals.
 pub async fn show_interner<T>(stream: &mut T) -> Result<(), Error>
@@ -318,6 +524,61 @@ where
     write_all_half(stream, &res).await
 }
 
+/// Show client IPs banned (or tracked) for failed authentication.
+pub async fn show_bans<T>(stream: &mut T) -> Result<(), Error>
+where
+    T: tokio::io::AsyncWrite + std::marker::Unpin,
+{
+    let window = get_config().general.auth_fail_ban_window.into();
+    let mut res = BytesMut::new();
+    res.put(row_description(&vec![
+        ("ip", DataType::Text),
+        ("banned", DataType::Text),
+        ("remaining_seconds", DataType::Numeric),
+        ("recent_failures", DataType::Int4),
+    ]));
+    for ban in crate::auth::ban::bans(window, std::time::Instant::now()) {
+        res.put(data_row(&[
+            ban.ip.to_string(),
+            ban.remaining.is_some().to_string(),
+            ban.remaining.map_or(0, |d| d.as_secs()).to_string(),
+            ban.failures.to_string(),
+        ]));
+    }
+    res.put(command_complete("SHOW"));
+    res.put_u8(b'Z');
+    res.put_i32(5);
+    res.put_u8(b'I');
+    write_all_half(stream, &res).await
+}
+
+/// Lift the ban on one IP, or on every IP without an argument.
+pub async fn reset_bans<T>(stream: &mut T, ip: Option<&str>) -> Result<(), Error>
+where
+    T: tokio::io::AsyncWrite + std::marker::Unpin,
+{
+    let ip = match ip.map(str::parse::<std::net::IpAddr>).transpose() {
+        Ok(ip) => ip,
+        Err(_) => {
+            return crate::messages::protocol::error_response(
+                stream,
+                "RESET BANS expects an IP address",
+                "22023",
+            )
+            .await
+        }
+    };
+    let removed = crate::auth::ban::clear(ip);
+    crate::admin::events::push_event("RESET_BANS", format!("{removed} entries cleared"));
+
+    let mut res = BytesMut::new();
+    res.put(command_complete("RESET"));
+    res.put_u8(b'Z');
+    res.put_i32(5);
+    res.put_u8(b'I');
+    write_all_half(stream, &res).await
+}
+
 /// Show extended utilization of connection pools for each pool.
 pub async fn show_pools_extended<T>(stream: &mut T) -> Result<(), Error>
 where
@@ -352,17 +613,26 @@ where
         .join("|");
     let help_items = [
         format!("SHOW {show_list}"),
+        "SHOW CONFIG [<key>] [JSON]".to_string(),
+        "SHOW CONFIG_EFFECTIVE".to_string(),
+        "SHOW CLIENTS|SERVERS|POOLS [<db>|<user>@<db>] [JSON]".to_string(),
+        "SHOW PREPARED_STATEMENTS [CLIENTS]".to_string(),
         "SHOW LISTS".to_string(),
         "SHOW CONNECTIONS".to_string(),
         "SHOW STATS".to_string(),
+        "SHOW RATES".to_string(),
         "SET log_level = '<filter>'".to_string(),
         "RELOAD".to_string(),
-        "SHUTDOWN".to_string(),
+        "SHUTDOWN [IMMEDIATE]".to_string(),
         "UPGRADE".to_string(),
         "PAUSE [db]".to_string(),
         "RESUME [db]".to_string(),
+        "DRAIN".to_string(),
+        "UNDRAIN".to_string(),
         "RECONNECT [db]".to_string(),
         "RESET INTERNER".to_string(),
+        "RESET BANS [ip]".to_string(),
+        "TEST HBA <ip|local> <user> <db> [ssl|nossl]".to_string(),
     ];
     let mut res = BytesMut::new();
     res.put(row_description(&columns));
@@ -425,26 +695,196 @@ where
     write_all_half(stream, &res).await
 }
 
-/// Shows current configuration.
-pub async fn show_config<T>(stream: &mut T) -> Result<(), Error>
+/// Shows the active HBA rules in evaluation order: `general.pg_hba` after
+/// includes are merged, or the legacy `general.hba` networks rendered as
+/// `host all all <net> any` rows.
+pub async fn show_hba<T>(stream: &mut T) -> Result<(), Error>
 where
     T: tokio::io::AsyncWrite + std::marker::Unpin,
 {
+    let columns = vec![
+        ("line", DataType::Numeric),
+        ("type", DataType::Text),
+        ("database", DataType::Text),
+        ("user", DataType::Text),
+        ("address", DataType::Text),
+        ("method", DataType::Text),
+    ];
+    let config = get_config();
+    let mut res = BytesMut::new();
+    res.put(row_description(&columns));
+    for row in hba_rows(&config.general) {
+        res.put(data_row(&row));
+    }
+    res.put(command_complete("SHOW"));
+    // ReadyForQuery
+    res.put_u8(b'Z');
+    res.put_i32(5);
+    res.put_u8(b'I');
+    write_all_half(stream, &res).await
+}
+
+fn hba_rows(general: &crate::config::General) -> Vec<Vec<String>> {
+    match general.pg_hba {
+        Some(ref pg_hba) => pg_hba
+            .rules
+            .iter()
+            .enumerate()
+            .map(|(idx, rule)| {
+                vec![
+                    (idx + 1).to_string(),
+                    rule.host_type.to_string(),
+                    rule.database.to_string(),
+                    rule.user.to_string(),
+                    rule.address.map(|net| net.to_string()).unwrap_or_default(),
+                    rule.method.to_string(),
+                ]
+            })
+            .collect(),
+        None => general
+            .hba
+            .iter()
+            .enumerate()
+            .map(|(idx, net)| {
+                vec![
+                    (idx + 1).to_string(),
+                    "host".to_string(),
+                    "all".to_string(),
+                    "all".to_string(),
+                    net.to_string(),
+                    "any".to_string(),
+                ]
+            })
+            .collect(),
+    }
+}
+
+/// `TEST HBA <ip|local> <user> <database> [ssl|nossl]`: evaluates the active
+/// HBA rules for a hypothetical client and reports, per password method,
+/// the decision and the rule (as numbered by SHOW HBA) that made it.
+pub async fn test_hba<T>(stream: &mut T, args: &[&str]) -> Result<(), Error>
+where
+    T: tokio::io::AsyncWrite + std::marker::Unpin,
+{
+    let Some(transport) = parse_test_hba_transport(args) else {
+        return crate::messages::protocol::error_response(
+            stream,
+            "TEST HBA requires: TEST HBA <ip|local> <user> <database> [ssl|nossl]",
+            "42601",
+        )
+        .await;
+    };
+    let (user, database) = (args[1], args[2]);
+    let columns = vec![
+        ("auth_method", DataType::Text),
+        ("result", DataType::Text),
+        ("line", DataType::Text),
+        ("rule", DataType::Text),
+    ];
+    let config = get_config();
+    let rows = hba_rows(&config.general);
+    let mut res = BytesMut::new();
+    res.put(row_description(&columns));
+    for method in ["md5", "scram-sha-256"] {
+        let (result, idx) = crate::config::explain_hba_with_general(
+            &config.general,
+            &transport,
+            method,
+            user,
+            database,
+        );
+        let (line, rule) = match idx.and_then(|idx| rows.get(idx)) {
+            Some(row) => (row[0].clone(), row[1..].join(" ")),
+            None => (String::new(), String::new()),
+        };
+        res.put(data_row(&[
+            method.to_string(),
+            result.to_string(),
+            line,
+            rule,
+        ]));
+    }
+    res.put(command_complete("SELECT"));
+    // ReadyForQuery
+    res.put_u8(b'Z');
+    res.put_i32(5);
+    res.put_u8(b'I');
+    write_all_half(stream, &res).await
+}
+
+fn parse_test_hba_transport(args: &[&str]) -> Option<crate::transport::ClientTransport> {
+    if !(3..=4).contains(&args.len()) {
+        return None;
+    }
+    let ssl = match args.get(3).map(|s| s.to_ascii_lowercase()).as_deref() {
+        None | Some("nossl") | Some("false") | Some("off") => false,
+        Some("ssl") | Some("true") | Some("on") => true,
+        Some(_) => return None,
+    };
+    if args[0].eq_ignore_ascii_case("local") {
+        return (!ssl).then_some(crate::transport::ClientTransport::Unix);
+    }
+    let ip: std::net::IpAddr = args[0].parse().ok()?;
+    Some(crate::transport::ClientTransport::Tcp {
+        peer: std::net::SocketAddr::new(ip, 0),
+        ssl,
+    })
+}
+
+/// `SHOW CONFIG [<key>] [JSON]`: the key filter and whether rows come
+/// back as JSON objects. `None` on more than one key.
+fn parse_show_config_args<'a>(args: &[&'a str]) -> Option<(Option<&'a str>, bool)> {
+    let json = args.iter().any(|arg| arg.eq_ignore_ascii_case("JSON"));
+    let mut keys = args.iter().filter(|arg| !arg.eq_ignore_ascii_case("JSON"));
+    let key = keys.next().copied();
+    keys.next().is_none().then_some((key, json))
+}
+
+/// Shows current configuration. `SHOW CONFIG <key>` returns one setting,
+/// `JSON` returns each row as a single JSON object for scripts.
+pub async fn show_config<T>(stream: &mut T, args: &[&str]) -> Result<(), Error>
+where
+    T: tokio::io::AsyncWrite + std::marker::Unpin,
+{
+    let Some((key, json)) = parse_show_config_args(args) else {
+        return crate::messages::protocol::error_response(
+            stream,
+            "SHOW CONFIG requires: SHOW CONFIG [<key>] [JSON]",
+            "42601",
+        )
+        .await;
+    };
     let config = &get_config();
-    let config: HashMap<String, String> = config.into();
+    let mut config: Vec<(String, String)> = HashMap::<String, String>::from(config)
+        .into_iter()
+        .filter(|(name, _)| key.is_none_or(|key| name.eq_ignore_ascii_case(key)))
+        .collect();
+    if let Some(key) = key.filter(|_| config.is_empty()) {
+        return crate::messages::protocol::error_response(
+            stream,
+            &format!("unrecognized configuration parameter \"{key}\""),
+            "42704",
+        )
+        .await;
+    }
+    config.sort();
     // Configs that cannot be changed without restarting. The keys here
     // are the bare names that `From<&Config> for HashMap` emits — the
     // Web `/api/config` view uses flattened paths (`general.host`,
     // `web.host`, …) and its own matcher in `web::routes::collect::config`.
     // `connect_timeout` is reloadable on SIGHUP; do not list it.
-    let immutables = ["host", "port"];
+    let immutables = ["host", "port", "worker_threads"];
     // Columns
-    let columns = vec![
-        ("key", DataType::Text),
-        ("value", DataType::Text),
-        ("default", DataType::Text),
-        ("changeable", DataType::Text),
-    ];
+    let columns = if json {
+        vec![("config", DataType::Text)]
+    } else {
+        vec![
+            ("key", DataType::Text),
+            ("value", DataType::Text),
+            ("default", DataType::Text),
+            ("changeable", DataType::Text),
+        ]
+    };
     // Response data
     let mut res = BytesMut::new();
     res.put(row_description(&columns));
@@ -455,7 +895,17 @@ where
         } else {
             "yes".to_string()
         };
-        let row = vec![key, value, "-".to_string(), changeable];
+        let row = if json {
+            vec![serde_json::json!({
+                "key": key,
+                "value": value,
+                "default": "-",
+                "changeable": changeable,
+            })
+            .to_string()]
+        } else {
+            vec![key, value, "-".to_string(), changeable]
+        };
         res.put(data_row(&row));
     }
     res.put(command_complete("SHOW"));
@@ -466,6 +916,30 @@ where
     write_all_half(stream, &res).await
 }
 
+/// `SHOW CONFIG_EFFECTIVE`: the whole running configuration, includes
+/// merged, as one TOML document with passwords redacted.
+pub async fn show_config_effective<T>(stream: &mut T) -> Result<(), Error>
+where
+    T: tokio::io::AsyncWrite + std::marker::Unpin,
+{
+    let toml = match get_config().effective_toml() {
+        Ok(toml) => toml,
+        Err(err) => {
+            return crate::messages::protocol::error_response(stream, &err.to_string(), "XX000")
+                .await;
+        }
+    };
+    let mut res = BytesMut::new();
+    res.put(row_description(&vec![("config", DataType::Text)]));
+    res.put(data_row(&[toml]));
+    res.put(command_complete("SHOW"));
+    // ReadyForQuery
+    res.put_u8(b'Z');
+    res.put_i32(5);
+    res.put_u8(b'I');
+    write_all_half(stream, &res).await
+}
+
 /// Show stats.
 pub async fn show_stats<T>(stream: &mut T) -> Result<(), Error>
 where
@@ -485,44 +959,80 @@ where
     write_all_half(stream, &res).await
 }
 
-/// Show currently connected clients
-pub async fn show_clients<T>(stream: &mut T) -> Result<(), Error>
+/// Show queries, transactions and bytes per second for each pool over
+/// the 1 s, 5 s and 60 s windows. A window is NULL until the sampler has
+/// taken two samples of the pool.
+pub async fn show_rates<T>(stream: &mut T) -> Result<(), Error>
+where
+    T: tokio::io::AsyncWrite + std::marker::Unpin,
+{
+    let metrics: [(&str, fn(&Rates) -> f64); 4] = [
+        ("qps", |r| r.queries_per_sec),
+        ("tps", |r| r.xacts_per_sec),
+        ("recv_bps", |r| r.bytes_received_per_sec),
+        ("sent_bps", |r| r.bytes_sent_per_sec),
+    ];
+    let names: Vec<String> = metrics
+        .iter()
+        .flat_map(|(metric, _)| RATE_WINDOWS_SECS.map(|secs| format!("{metric}_{secs}s")))
+        .collect();
+    let mut columns = vec![("database", DataType::Text), ("user", DataType::Text)];
+    columns.extend(names.iter().map(|name| (name.as_str(), DataType::Numeric)));
+    let mut res = BytesMut::new();
+    res.put(row_description(&columns));
+    for (identifier, windows) in rates_snapshot() {
+        let mut row = vec![Some(identifier.db), Some(identifier.user)];
+        for (_, value) in &metrics {
+            row.extend(
+                windows
+                    .iter()
+                    .map(|rates| rates.as_ref().map(|r| format!("{:.2}", value(r)))),
+            );
+        }
+        res.put(data_row_nullable(&row));
+    }
+    res.put(command_complete("SHOW"));
+    // ReadyForQuery
+    res.put_u8(b'Z');
+    res.put_i32(5);
+    res.put_u8(b'I');
+    write_all_half(stream, &res).await
+}
+
+/// Show query and transaction totals per pool and client application,
+/// for the names listed in `general.stats_application_names`.
+pub async fn show_stats_by_app<T>(stream: &mut T) -> Result<(), Error>
 where
     T: tokio::io::AsyncWrite + std::marker::Unpin,
 {
     let columns = vec![
-        ("client_id", DataType::Text),
         ("database", DataType::Text),
         ("user", DataType::Text),
         ("application_name", DataType::Text),
-        ("addr", DataType::Text),
-        ("tls", DataType::Text),
-        ("state", DataType::Text),
-        ("wait", DataType::Text),
-        ("transaction_count", DataType::Numeric),
-        ("query_count", DataType::Numeric),
-        ("error_count", DataType::Numeric),
-        ("age_seconds", DataType::Numeric),
+        ("total_xact_count", DataType::Numeric),
+        ("total_query_count", DataType::Numeric),
+        ("total_query_time", DataType::Numeric),
+        ("avg_query_time", DataType::Numeric),
     ];
-    let new_map = get_client_stats();
+    let mut entries: Vec<_> = get_all_pools()
+        .iter()
+        .map(|(id, pool)| (id.clone(), pool.address().stats.by_application_snapshot()))
+        .collect();
+    entries.sort_by(|a, b| (&a.0.db, &a.0.user).cmp(&(&b.0.db, &b.0.user)));
     let mut res = BytesMut::new();
     res.put(row_description(&columns));
-    for (_, client) in new_map {
-        let row = vec![
-            format!("#c{}", client.connection_id()),
-            client.pool_name().to_string(),
-            client.username().to_string(),
-            client.application_name().to_string(),
-            client.ipaddr().to_string(),
-            client.tls().to_string(),
-            client.state_str().to_string(),
-            client.wait_str().to_string(),
-            client.transaction_count.load(Ordering::Relaxed).to_string(),
-            client.query_count.load(Ordering::Relaxed).to_string(),
-            client.error_count.load(Ordering::Relaxed).to_string(),
-            client.connect_time().elapsed().as_secs().to_string(),
-        ];
-        res.put(data_row(&row));
+    for (identifier, apps) in entries {
+        for app in apps {
+            res.put(data_row(&[
+                identifier.db.clone(),
+                identifier.user.clone(),
+                app.application_name.clone(),
+                app.xact_count.to_string(),
+                app.query_count.to_string(),
+                app.query_time_microseconds.to_string(),
+                app.avg_query_time().to_string(),
+            ]));
+        }
     }
     res.put(command_complete("SHOW"));
     // ReadyForQuery
@@ -532,6 +1042,57 @@ where
     write_all_half(stream, &res).await
 }
 
+fn client_columns() -> Vec<(&'static str, DataType)> {
+    vec![
+        ("client_id", DataType::Text),
+        ("database", DataType::Text),
+        ("user", DataType::Text),
+        ("application_name", DataType::Text),
+        ("tag", DataType::Text),
+        ("addr", DataType::Text),
+        ("tls", DataType::Text),
+        ("state", DataType::Text),
+        ("wait", DataType::Text),
+        ("transaction_count", DataType::Numeric),
+        ("query_count", DataType::Numeric),
+        ("error_count", DataType::Numeric),
+        ("age_seconds", DataType::Numeric),
+    ]
+}
+
+fn client_rows() -> Vec<Vec<String>> {
+    get_client_stats()
+        .into_values()
+        .map(|client| {
+            vec![
+                format!("#c{}", client.connection_id()),
+                client.pool_name().to_string(),
+                client.username().to_string(),
+                client.application_name(),
+                client.tag().to_string(),
+                client.ipaddr().to_string(),
+                client.tls().to_string(),
+                client.state_str().to_string(),
+                client.wait_str().to_string(),
+                client.transaction_count.load(Ordering::Relaxed).to_string(),
+                client.query_count.load(Ordering::Relaxed).to_string(),
+                client.error_count.load(Ordering::Relaxed).to_string(),
+                client.connect_time().elapsed().as_secs().to_string(),
+            ]
+        })
+        .collect()
+}
+
+/// Show currently connected clients, optionally only those of one database
+/// or `<user>@<database>`. `SHOW CLIENTS JSON` returns them as one JSON
+/// document instead.
+pub async fn show_clients<T>(stream: &mut T, args: &[&str]) -> Result<(), Error>
+where
+    T: tokio::io::AsyncWrite + std::marker::Unpin,
+{
+    show_rows(stream, "CLIENTS", client_columns(), client_rows(), args).await
+}
+
 /// Show connections.
 pub async fn show_connections<T>(stream: &mut T) -> Result<(), Error>
 where
@@ -567,12 +1128,8 @@ where
     write_all_half(stream, &res).await
 }
 
-/// Show currently connected servers
-pub async fn show_servers<T>(stream: &mut T) -> Result<(), Error>
-where
-    T: tokio::io::AsyncWrite + std::marker::Unpin,
-{
-    let columns = vec![
+fn server_columns() -> Vec<(&'static str, DataType)> {
+    vec![
         ("server_id", DataType::Text),
         ("server_process_id", DataType::Text),
         ("database_name", DataType::Text),
@@ -589,40 +1146,251 @@ where
         ("prepare_cache_hit", DataType::Numeric),
         ("prepare_cache_miss", DataType::Numeric),
         ("prepare_cache_size", DataType::Numeric),
+    ]
+}
+
+fn server_rows() -> Vec<Vec<String>> {
+    get_server_stats()
+        .into_values()
+        .map(|server| {
+            let application_name = server.application_name.lock().clone();
+            vec![
+                format!("{:#010X}", server.server_id()),
+                server.process_id().to_string(),
+                server.pool_name().to_string(),
+                server.username().to_string(),
+                application_name,
+                server.tls().to_string(),
+                server.state_str().to_string(),
+                server.wait_str().to_string(),
+                server.transaction_count.load(Ordering::Relaxed).to_string(),
+                server.query_count.load(Ordering::Relaxed).to_string(),
+                server.bytes_sent.load(Ordering::Relaxed).to_string(),
+                server.bytes_received.load(Ordering::Relaxed).to_string(),
+                server.connect_time().elapsed().as_secs().to_string(),
+                server
+                    .prepared_hit_count
+                    .load(Ordering::Relaxed)
+                    .to_string(),
+                server
+                    .prepared_miss_count
+                    .load(Ordering::Relaxed)
+                    .to_string(),
+                server
+                    .prepared_cache_size
+                    .load(Ordering::Relaxed)
+                    .to_string(),
+            ]
+        })
+        .collect()
+}
+
+/// Show currently connected servers, optionally only those of one database
+/// or `<user>@<database>`. `SHOW SERVERS JSON` returns them as one JSON
+/// document instead.
+pub async fn show_servers<T>(stream: &mut T, args: &[&str]) -> Result<(), Error>
+where
+    T: tokio::io::AsyncWrite + std::marker::Unpin,
+{
+    show_rows(stream, "SERVERS", server_columns(), server_rows(), args).await
+}
+
+/// Render rows as a JSON array of objects keyed by column name. Numeric
+/// columns become JSON numbers so tooling does not have to re-parse them.
+fn rows_json(columns: &[(&str, DataType)], rows: &[Vec<String>]) -> String {
+    let rows: Vec<serde_json::Value> = rows
+        .iter()
+        .map(|row| {
+            let object = columns
+                .iter()
+                .zip(row)
+                .map(|((name, data_type), value)| {
+                    let value = match data_type {
+                        DataType::Numeric => value
+                            .parse::<u64>()
+                            .map(serde_json::Value::from)
+                            .unwrap_or_else(|_| serde_json::Value::from(value.as_str())),
+                        _ => serde_json::Value::from(value.as_str()),
+                    };
+                    (name.to_string(), value)
+                })
+                .collect();
+            serde_json::Value::Object(object)
+        })
+        .collect();
+    serde_json::Value::Array(rows).to_string()
+}
+
+/// `<database>` or `<user>@<database>` argument of `SHOW CLIENTS`,
+/// `SHOW SERVERS` and `SHOW POOLS`.
+#[derive(Debug, PartialEq)]
+struct PoolFilter<'a> {
+    user: Option<&'a str>,
+    database: &'a str,
+}
+
+impl<'a> PoolFilter<'a> {
+    fn parse(arg: &'a str) -> Option<Self> {
+        match arg.split_once('@') {
+            Some((user, database)) if !user.is_empty() && !database.is_empty() => Some(Self {
+                user: Some(user),
+                database,
+            }),
+            Some(_) => None,
+            None => Some(Self {
+                user: None,
+                database: arg,
+            }),
+        }
+    }
+
+    fn matches(&self, database: &str, user: &str) -> bool {
+        self.database == database && self.user.is_none_or(|filter| filter == user)
+    }
+}
+
+/// `SHOW CLIENTS|SERVERS|POOLS [<database>|<user>@<database>] [JSON]`: the
+/// row filter and whether rows come back as JSON. `None` on more than one
+/// filter or a malformed one.
+fn parse_show_rows_args<'a>(args: &[&'a str]) -> Option<(Option<PoolFilter<'a>>, bool)> {
+    let json = args.iter().any(|arg| arg.eq_ignore_ascii_case("JSON"));
+    let mut filters = args.iter().filter(|arg| !arg.eq_ignore_ascii_case("JSON"));
+    let filter = match filters.next() {
+        Some(arg) => Some(PoolFilter::parse(arg)?),
+        None => None,
+    };
+    filters.next().is_none().then_some((filter, json))
+}
+
+/// Write `rows` as a table, or with a `JSON` argument as one row holding
+/// [`rows_json`] in a column named after the command. A
+/// `<database>` or `<user>@<database>` argument keeps only matching rows.
+async fn show_rows<T>(
+    stream: &mut T,
+    command: &str,
+    columns: Vec<(&str, DataType)>,
+    mut rows: Vec<Vec<String>>,
+    args: &[&str],
+) -> Result<(), Error>
+where
+    T: tokio::io::AsyncWrite + std::marker::Unpin,
+{
+    let Some((filter, json)) = parse_show_rows_args(args) else {
+        return crate::messages::protocol::error_response(
+            stream,
+            &format!(
+                "SHOW {command} accepts only: SHOW {command} [<database>|<user>@<database>] [JSON]"
+            ),
+            "42601",
+        )
+        .await;
+    };
+    if let Some(filter) = filter {
+        let position = |names: &[&str]| columns.iter().position(|(name, _)| names.contains(name));
+        if let (Some(database), Some(user)) = (
+            position(&["database", "database_name"]),
+            position(&["user"]),
+        ) {
+            rows.retain(|row| filter.matches(&row[database], &row[user]));
+        }
+    }
+    let mut res = BytesMut::new();
+    if json {
+        let column = command.to_ascii_lowercase();
+        res.put(row_description(&vec![(column.as_str(), DataType::Text)]));
+        res.put(data_row(&[rows_json(columns, &rows)]));
+    } else {
+        res.put(row_description(&columns));
+        for row in rows {
+            res.put(data_row(&row));
+        }
+    }
+    res.put(command_complete("SHOW"));
+    // ReadyForQuery
+    res.put_u8(b'Z');
+    res.put_i32(5);
+    res.put_u8(b'I');
+    write_all_half(stream, &res).await
+}
+
+/// Wait events of `SHOW WAIT_EVENTS` with their descriptions, in output
+/// order: pre-pool phases first, then the per-client states.
+const WAIT_EVENTS: &[(&str, &str)] = &[
+    ("admission_queue", "queued for a max_connections slot"),
+    ("tls_handshake", "in the TLS handshake"),
+    ("auth", "authenticating"),
+    (
+        "server_checkout",
+        "waiting for a server connection from the pool",
+    ),
+    (
+        "server_response",
+        "holding a server, waiting for its response",
+    ),
+    (
+        "client_write",
+        "blocked writing results to the client socket",
+    ),
+    (
+        "client_read_with_server",
+        "holding a server, waiting for the client's next message",
+    ),
+    ("client_idle", "idle without a server"),
+];
+
+/// Wait event of a pooled client from its `ClientStats` state and wait.
+fn client_wait_event(state: u8, wait: u8) -> &'static str {
+    match (state, wait) {
+        (CLIENT_STATE_WAITING, _) => "server_checkout",
+        (_, CLIENT_WAIT_WRITE) => "client_write",
+        (CLIENT_STATE_ACTIVE, CLIENT_WAIT_READ) => "client_read_with_server",
+        (CLIENT_STATE_ACTIVE, _) => "server_response",
+        _ => "client_idle",
+    }
+}
+
+/// Number of clients per wait event across all pools: clients queued for
+/// admission, in the TLS handshake or authenticating, and pooled clients
+/// by what they are blocked on. Every event is listed, zero or not.
+pub async fn show_wait_events<T>(stream: &mut T) -> Result<(), Error>
+where
+    T: tokio::io::AsyncWrite + std::marker::Unpin,
+{
+    use crate::stats::wait_events::{
+        CLIENTS_IN_ADMISSION_QUEUE, CLIENTS_IN_AUTH, CLIENTS_IN_TLS_HANDSHAKE,
+    };
+
+    let mut counts: HashMap<&str, i64> = HashMap::from([
+        (
+            "admission_queue",
+            CLIENTS_IN_ADMISSION_QUEUE.load(Ordering::Relaxed),
+        ),
+        (
+            "tls_handshake",
+            CLIENTS_IN_TLS_HANDSHAKE.load(Ordering::Relaxed),
+        ),
+        ("auth", CLIENTS_IN_AUTH.load(Ordering::Relaxed)),
+    ]);
+    for client in get_client_stats().values() {
+        *counts
+            .entry(client_wait_event(client.state(), client.wait()))
+            .or_default() += 1;
+    }
+
+    let columns = vec![
+        ("wait_event", DataType::Text),
+        ("clients", DataType::Numeric),
+        ("description", DataType::Text),
     ];
-    let new_map = get_server_stats();
     let mut res = BytesMut::new();
     res.put(row_description(&columns));
-    for (_, server) in new_map {
-        let application_name = server.application_name.lock();
-        let row = vec![
-            format!("{:#010X}", server.server_id()),
-            server.process_id().to_string(),
-            server.pool_name().to_string(),
-            server.username().to_string(),
-            application_name.clone(),
-            server.tls().to_string(),
-            server.state_str().to_string(),
-            server.wait_str().to_string(),
-            server.transaction_count.load(Ordering::Relaxed).to_string(),
-            server.query_count.load(Ordering::Relaxed).to_string(),
-            server.bytes_sent.load(Ordering::Relaxed).to_string(),
-            server.bytes_received.load(Ordering::Relaxed).to_string(),
-            server.connect_time().elapsed().as_secs().to_string(),
-            server
-                .prepared_hit_count
-                .load(Ordering::Relaxed)
-                .to_string(),
-            server
-                .prepared_miss_count
-                .load(Ordering::Relaxed)
-                .to_string(),
-            server
-                .prepared_cache_size
-                .load(Ordering::Relaxed)
-                .to_string(),
-        ];
-        res.put(data_row(&row));
+    for (event, description) in WAIT_EVENTS {
+        let clients = counts.get(event).copied().unwrap_or(0).max(0);
+        res.put(data_row(&[
+            event.to_string(),
+            clients.to_string(),
+            description.to_string(),
+        ]));
     }
     res.put(command_complete("SHOW"));
     // ReadyForQuery
@@ -632,6 +1400,56 @@ where
     write_all_half(stream, &res).await
 }
 
+/// Statements running on backends right now, longest-running first.
+/// Built from pg_doorman's own per-server state, so it costs no backend
+/// round trip. `query_age_ms` counts from the moment the statement was
+/// sent; an extended-protocol batch is labelled with its last Bind.
+pub async fn show_active_queries<T>(stream: &mut T) -> Result<(), Error>
+where
+    T: tokio::io::AsyncWrite + std::marker::Unpin,
+{
+    let columns = vec![
+        ("database", DataType::Text),
+        ("user", DataType::Text),
+        ("application_name", DataType::Text),
+        ("server_process_id", DataType::Text),
+        ("wait", DataType::Text),
+        ("query_age_ms", DataType::Numeric),
+        ("query", DataType::Text),
+    ];
+    let mut rows: Vec<(u64, Vec<String>)> = get_server_stats()
+        .values()
+        .filter(|server| server.state() == SERVER_STATE_ACTIVE)
+        .filter_map(|server| {
+            let (query, age_ms) = server.current_query()?;
+            Some((
+                age_ms,
+                vec![
+                    server.pool_name().to_string(),
+                    server.username().to_string(),
+                    server.application_name.lock().clone(),
+                    server.process_id().to_string(),
+                    server.wait_str().to_string(),
+                    age_ms.to_string(),
+                    crate::utils::strings::preview_query_one_line(&query),
+                ],
+            ))
+        })
+        .collect();
+    rows.sort_by(|a, b| b.0.cmp(&a.0));
+
+    let mut res = BytesMut::new();
+    res.put(row_description(&columns));
+    for (_, row) in rows {
+        res.put(data_row(&row));
+    }
+    res.put(command_complete("SHOW"));
+    res.put_u8(b'Z');
+    res.put_i32(5);
+    res.put_u8(b'I');
+    write_all_half(stream, &res).await
+}
+
 /// Show Users.
 pub async fn show_users<T>(stream: &mut T) -> Result<(), Error>
 where
@@ -925,3 +1743,162 @@ where
     res.put_u8(b'I');
     write_all_half(stream, &res).await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn show_config_args_take_one_key_and_json_in_any_order() {
+        assert_eq!(parse_show_config_args(&[]), Some((None, false)));
+        assert_eq!(
+            parse_show_config_args(&["worker_threads"]),
+            Some((Some("worker_threads"), false))
+        );
+        assert_eq!(parse_show_config_args(&["json"]), Some((None, true)));
+        assert_eq!(
+            parse_show_config_args(&["JSON", "worker_threads"]),
+            Some((Some("worker_threads"), true))
+        );
+        assert_eq!(parse_show_config_args(&["port", "host"]), None);
+    }
+
+    #[test]
+    fn lists_rows_follow_pgbouncer_layout() {
+        let pools = [
+            PoolIdentifier::new("shop", "alice"),
+            PoolIdentifier::new("shop", "bob"),
+            PoolIdentifier::new("billing", "alice"),
+        ];
+        let rows = lists_rows(pools.iter(), 7, 4);
+        let names: Vec<&str> = rows.iter().map(|(list, _)| *list).collect();
+        assert_eq!(
+            names,
+            [
+                "databases",
+                "users",
+                "peers",
+                "pools",
+                "peer_pools",
+                "free_clients",
+                "used_clients",
+                "login_clients",
+                "free_servers",
+                "used_servers",
+                "dns_names",
+                "dns_zones",
+                "dns_queries",
+                "dns_pending",
+            ]
+        );
+        let items = |name: &str| rows.iter().find(|(list, _)| *list == name).unwrap().1;
+        assert_eq!(items("databases"), 3);
+        assert_eq!(items("users"), 3);
+        assert_eq!(items("pools"), 3);
+        assert_eq!(items("used_clients"), 7);
+        assert_eq!(items("used_servers"), 4);
+    }
+
+    #[test]
+    fn client_states_map_to_wait_events() {
+        use crate::stats::client::{CLIENT_STATE_IDLE, CLIENT_WAIT_IDLE};
+        assert_eq!(
+            client_wait_event(CLIENT_STATE_WAITING, CLIENT_WAIT_IDLE),
+            "server_checkout"
+        );
+        assert_eq!(
+            client_wait_event(CLIENT_STATE_ACTIVE, CLIENT_WAIT_IDLE),
+            "server_response"
+        );
+        assert_eq!(
+            client_wait_event(CLIENT_STATE_ACTIVE, CLIENT_WAIT_WRITE),
+            "client_write"
+        );
+        assert_eq!(
+            client_wait_event(CLIENT_STATE_IDLE, CLIENT_WAIT_WRITE),
+            "client_write"
+        );
+        assert_eq!(
+            client_wait_event(CLIENT_STATE_ACTIVE, CLIENT_WAIT_READ),
+            "client_read_with_server"
+        );
+        assert_eq!(
+            client_wait_event(CLIENT_STATE_IDLE, CLIENT_WAIT_READ),
+            "client_idle"
+        );
+        // Every event the mapping yields is listed in the output.
+        for state in [CLIENT_STATE_IDLE, CLIENT_STATE_ACTIVE, CLIENT_STATE_WAITING] {
+            for wait in [CLIENT_WAIT_IDLE, CLIENT_WAIT_READ, CLIENT_WAIT_WRITE] {
+                let event = client_wait_event(state, wait);
+                assert!(WAIT_EVENTS.iter().any(|(name, _)| *name == event));
+            }
+        }
+    }
+
+    #[test]
+    fn show_rows_args_take_one_pool_filter_and_json() {
+        assert_eq!(parse_show_rows_args(&[]), Some((None, false)));
+        assert_eq!(parse_show_rows_args(&["json"]), Some((None, true)));
+        assert_eq!(
+            parse_show_rows_args(&["prod"]),
+            Some((
+                Some(PoolFilter {
+                    user: None,
+                    database: "prod"
+                }),
+                false
+            ))
+        );
+        assert_eq!(
+            parse_show_rows_args(&["JSON", "app@prod"]),
+            Some((
+                Some(PoolFilter {
+                    user: Some("app"),
+                    database: "prod"
+                }),
+                true
+            ))
+        );
+        assert_eq!(parse_show_rows_args(&["prod", "staging"]), None);
+        assert_eq!(parse_show_rows_args(&["@prod"]), None);
+        assert_eq!(parse_show_rows_args(&["app@"]), None);
+    }
+
+    #[test]
+    fn pool_filter_matches_database_and_optional_user() {
+        let by_database = PoolFilter::parse("prod").unwrap();
+        assert!(by_database.matches("prod", "app"));
+        assert!(by_database.matches("prod", "batch"));
+        assert!(!by_database.matches("staging", "app"));
+
+        let by_user = PoolFilter::parse("app@prod").unwrap();
+        assert!(by_user.matches("prod", "app"));
+        assert!(!by_user.matches("prod", "batch"));
+    }
+
+    #[test]
+    fn rows_json_keys_by_column_and_keeps_numbers_numeric() {
+        let columns = [
+            ("client_id", DataType::Text),
+            ("addr", DataType::Text),
+            ("query_count", DataType::Numeric),
+        ];
+        let rows = vec![
+            vec![
+                "#c1".to_string(),
+                "10.0.0.1:5000".to_string(),
+                "42".to_string(),
+            ],
+            vec!["#c2".to_string(), "\"quoted\"".to_string(), "7".to_string()],
+        ];
+        let parsed: serde_json::Value = serde_json::from_str(&rows_json(&columns, &rows)).unwrap();
+        assert_eq!(
+            parsed,
+            serde_json::json!([
+                {"client_id": "#c1", "addr": "10.0.0.1:5000", "query_count": 42},
+                {"client_id": "#c2", "addr": "\"quoted\"", "query_count": 7},
+            ])
+        );
+        assert_eq!(rows_json(&columns, &[]), "[]");
+    }
+}
@@ -0,0 +1,111 @@
+//! Client admission against `general.max_connections`.
+//!
+//! With `max_connections_overflow_behavior = "reject"` a client over the
+//! limit gets `53300` straight away. With `"queue"` it waits up to
+//! `max_connections_queue_timeout` for another client to disconnect before
+//! it is rejected, so a short connection spike during a deploy does not
+//! turn into hard failures. Queued clients hold a socket but do not count
+//! towards `CURRENT_CLIENT_COUNT` until they are admitted.
+
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+use crate::app::server::CURRENT_CLIENT_COUNT;
+use crate::stats::wait_events::{WaitGuard, CLIENTS_IN_ADMISSION_QUEUE};
+
+/// Wakes queued clients after a slot was released.
+static SLOT_RELEASED: Lazy<Notify> = Lazy::new(Notify::new);
+
+/// Takes a client slot if the count is within `max_connections`.
+/// Returns the count seen when the slot is refused.
+fn try_admit(max_connections: u64) -> Result<(), i64> {
+    let mut current = CURRENT_CLIENT_COUNT.load(Ordering::SeqCst);
+    loop {
+        if current as u64 > max_connections {
+            return Err(current);
+        }
+        match CURRENT_CLIENT_COUNT.compare_exchange_weak(
+            current,
+            current + 1,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        ) {
+            Ok(_) => return Ok(()),
+            Err(actual) => current = actual,
+        }
+    }
+}
+
+/// Admits a client, waiting up to `queue_timeout` for a free slot when it
+/// is set. On success the caller owns a slot and must give it back with
+/// [`release`]. On failure returns the client count seen last; no slot is
+/// held.
+pub(crate) async fn admit(
+    max_connections: u64,
+    queue_timeout: Option<Duration>,
+) -> Result<(), i64> {
+    let refused = match try_admit(max_connections) {
+        Ok(()) => return Ok(()),
+        Err(current) => current,
+    };
+    let Some(queue_timeout) = queue_timeout else {
+        return Err(refused);
+    };
+
+    let _queued = WaitGuard::enter(&CLIENTS_IN_ADMISSION_QUEUE);
+    let deadline = Instant::now() + queue_timeout;
+    loop {
+        // Register before re-checking so a release in between is not lost.
+        let released = SLOT_RELEASED.notified();
+        tokio::pin!(released);
+        released.as_mut().enable();
+
+        match try_admit(max_connections) {
+            Ok(()) => return Ok(()),
+            Err(current) => {
+                if tokio::time::timeout_at(deadline, released).await.is_err() {
+                    return Err(current);
+                }
+            }
+        }
+    }
+}
+
+/// Gives a slot back and wakes one queued client.
+pub(crate) fn release() {
+    CURRENT_CLIENT_COUNT.fetch_add(-1, Ordering::SeqCst);
+    SLOT_RELEASED.notify_one();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    #[tokio::test]
+    #[serial(client_count)]
+    async fn queued_client_is_admitted_when_a_slot_is_released() {
+        CURRENT_CLIENT_COUNT.store(0, Ordering::SeqCst);
+        // `max_connections` is compared against the count before the
+        // increment, so a limit of 0 admits exactly one client.
+        assert!(admit(0, None).await.is_ok());
+        assert_eq!(admit(0, None).await, Err(1));
+
+        let waiter = tokio::spawn(admit(0, Some(Duration::from_secs(5))));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        release();
+        assert_eq!(waiter.await.unwrap(), Ok(()));
+
+        assert_eq!(
+            admit(0, Some(Duration::from_millis(20))).await,
+            Err(1),
+            "queue gives up after the timeout"
+        );
+        release();
+        assert_eq!(CURRENT_CLIENT_COUNT.load(Ordering::SeqCst), 0);
+    }
+}
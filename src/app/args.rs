@@ -64,6 +64,17 @@ pub enum Commands {
         #[arg(short, long)]
         output_dir: Option<String>,
     },
+    /// Validate a configuration file without starting the server
+    ///
+    /// Parses the file with its includes and loads TLS identities, HBA rules and
+    /// Talos keys. No ports are bound and no backends are contacted. Exits 0 when
+    /// the configuration is valid and 1 otherwise.
+    Validate {
+        /// Configuration file to validate.
+        /// If not specified, uses the CONFIG_FILE argument.
+        #[arg(short, long)]
+        config: Option<String>,
+    },
 }
 
 #[derive(Debug, Clone, Parser)]
@@ -126,6 +137,14 @@ pub struct GenerateConfig {
     /// This flag overrides the auto-detected format.
     #[arg(short, long, value_enum)]
     pub(crate) format: Option<OutputFormat>,
+    /// Convert an existing pgbouncer.ini instead of connecting to PostgreSQL.
+    /// Settings without a pg_doorman equivalent are listed as comments.
+    #[arg(long, value_name = "PGBOUNCER_INI")]
+    pub(crate) from_pgbouncer: Option<String>,
+    /// userlist.txt to import with --from-pgbouncer.
+    /// If not specified, uses auth_file from pgbouncer.ini.
+    #[arg(long, requires = "from_pgbouncer")]
+    pub(crate) userlist: Option<String>,
 }
 
 pub fn parse() -> Args {
@@ -0,0 +1,104 @@
+//! Courtesy notice pushed to connected clients when the node starts going
+//! away (DRAIN, a shutdown or a binary upgrade), so applications that log
+//! server notices can finish their work and reconnect elsewhere before the
+//! connection drops.
+//!
+//! Idle clients get the notice as soon as it is announced. A client inside
+//! a transaction gets it once the transaction ends and it is back to
+//! waiting for its next query. `SHUTDOWN IMMEDIATE` / `SIGTERM` exits right
+//! after announcing, so there the notice is best effort.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use tokio::sync::futures::Notified;
+use tokio::sync::Notify;
+
+/// Notice text for DRAIN.
+pub const DRAIN_NOTICE: &str =
+    "pg_doorman is draining and will shut down soon; finish your work and reconnect";
+
+/// Notice text for a graceful shutdown.
+pub const SHUTDOWN_NOTICE: &str = "pg_doorman is shutting down; finish your work and reconnect";
+
+/// Notice text for a binary upgrade. Idle clients are migrated to the new
+/// process; the rest may see their connection close.
+pub const UPGRADE_NOTICE: &str =
+    "pg_doorman is restarting for a binary upgrade; reconnect if your connection drops";
+
+/// SQLSTATE carried by the notice: plain `warning`.
+pub const NOTICE_SQLSTATE: &str = "01000";
+
+/// Bumped on every announcement; each client remembers the last one it saw.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+static MESSAGE: Lazy<RwLock<Option<Arc<str>>>> = Lazy::new(|| RwLock::new(None));
+
+/// Wakes idle clients after an announcement.
+static ANNOUNCED: Lazy<Notify> = Lazy::new(Notify::new);
+
+/// Queue `message` for every connected client and wake the idle ones.
+pub fn announce(message: &str) {
+    *MESSAGE.write() = Some(Arc::from(message));
+    GENERATION.fetch_add(1, Ordering::AcqRel);
+    ANNOUNCED.notify_waiters();
+}
+
+/// Drop the pending notice, e.g. after UNDRAIN. Clients that already got
+/// it are not told; clients that had not yet seen it never will.
+pub fn withdraw() {
+    *MESSAGE.write() = None;
+}
+
+/// The notice a client whose last seen generation is `seen` still has to
+/// receive, if any. Advances `seen` either way.
+pub fn pending(seen: &mut u64) -> Option<Arc<str>> {
+    let generation = GENERATION.load(Ordering::Acquire);
+    if generation == *seen {
+        return None;
+    }
+    *seen = generation;
+    MESSAGE.read().clone()
+}
+
+/// Resolves on the next `announce`. Enable the future before calling
+/// `pending` so an announcement in between is not missed.
+pub fn announced() -> Notified<'static> {
+    ANNOUNCED.notified()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    #[test]
+    #[serial(client_notice)]
+    fn pending_delivers_each_announcement_once() {
+        let mut seen = GENERATION.load(Ordering::Acquire);
+        assert!(pending(&mut seen).is_none());
+
+        announce(DRAIN_NOTICE);
+        assert_eq!(pending(&mut seen).as_deref(), Some(DRAIN_NOTICE));
+        assert!(pending(&mut seen).is_none());
+
+        announce(SHUTDOWN_NOTICE);
+        withdraw();
+        assert!(pending(&mut seen).is_none());
+    }
+
+    #[tokio::test]
+    #[serial(client_notice)]
+    async fn announce_wakes_enabled_waiters() {
+        let announced = announced();
+        tokio::pin!(announced);
+        announced.as_mut().enable();
+        announce(SHUTDOWN_NOTICE);
+        tokio::time::timeout(std::time::Duration::from_secs(1), announced)
+            .await
+            .expect("waiter woken by announce");
+        withdraw();
+    }
+}
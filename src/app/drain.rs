@@ -0,0 +1,128 @@
+//! Server-wide DRAIN mode.
+//!
+//! DRAIN closes the TCP listener so new connections are refused at the
+//! kernel level, while every client that is already connected keeps working
+//! for as long as it likes. It is the node-cordon step of a rolling upgrade:
+//! the orchestrator stops routing to the node, waits for the sessions to go
+//! away and only then sends SIGTERM. UNDRAIN (or a plain RESUME) binds the
+//! listener again.
+//!
+//! The Unix socket stays open during DRAIN but only admits admin
+//! connections, so the node can still be inspected and undrained locally.
+//! The accept loop in `server.rs` owns the listener; the admin console and
+//! the REST surface only flip the flag here and wake the loop.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use log::info;
+use once_cell::sync::Lazy;
+use tokio::sync::Notify;
+
+use crate::app::server::SHUTDOWN_IN_PROGRESS;
+use crate::pool::get_all_pools;
+
+static DRAINING: AtomicBool = AtomicBool::new(false);
+
+/// Wakes the accept loop after the flag changed.
+static DRAIN_CHANGED: Lazy<Notify> = Lazy::new(Notify::new);
+
+/// Server-wide state reported by `SHOW STATE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerState {
+    /// Listener open, pools serving.
+    Accepting,
+    /// Listener closed by DRAIN; existing clients keep working.
+    Draining,
+    /// Listener open, but every pool is paused.
+    Paused,
+    /// Graceful shutdown or binary upgrade in progress.
+    ShuttingDown,
+}
+
+impl ServerState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ServerState::Accepting => "accepting",
+            ServerState::Draining => "draining",
+            ServerState::Paused => "paused",
+            ServerState::ShuttingDown => "shutting_down",
+        }
+    }
+}
+
+/// Whether DRAIN is in effect.
+pub fn is_draining() -> bool {
+    DRAINING.load(Ordering::Acquire)
+}
+
+/// Enters DRAIN. Returns `false` when the server was already draining.
+pub fn drain() -> bool {
+    let changed = !DRAINING.swap(true, Ordering::AcqRel);
+    if changed {
+        info!("DRAIN: closing listener, existing clients keep running");
+        crate::admin::events::push_event("DRAIN", "listener closed".to_string());
+        crate::app::client_notice::announce(crate::app::client_notice::DRAIN_NOTICE);
+        DRAIN_CHANGED.notify_one();
+    }
+    changed
+}
+
+/// Leaves DRAIN. Returns `false` when the server was not draining.
+pub fn undrain() -> bool {
+    let changed = DRAINING.swap(false, Ordering::AcqRel);
+    if changed {
+        info!("UNDRAIN: reopening listener");
+        crate::admin::events::push_event("UNDRAIN", "listener reopened".to_string());
+        crate::app::client_notice::withdraw();
+        DRAIN_CHANGED.notify_one();
+    }
+    changed
+}
+
+/// Puts the flag back after the accept loop failed to rebind the listener,
+/// so `SHOW STATE` keeps matching the socket.
+pub(crate) fn undrain_failed(reason: &str) {
+    DRAINING.store(true, Ordering::Release);
+    crate::admin::events::push_event("UNDRAIN", format!("failed to reopen listener: {reason}"));
+}
+
+/// Resolves after the next `drain` / `undrain` that changed the flag.
+pub(crate) async fn changed() {
+    DRAIN_CHANGED.notified().await
+}
+
+/// Current server-wide state. Shutdown wins over DRAIN, DRAIN over PAUSE;
+/// `Paused` means every pool is paused, not just some of them.
+pub fn server_state() -> ServerState {
+    if SHUTDOWN_IN_PROGRESS.load(Ordering::Relaxed) {
+        return ServerState::ShuttingDown;
+    }
+    if is_draining() {
+        return ServerState::Draining;
+    }
+    let pools = get_all_pools();
+    if !pools.is_empty() && pools.values().all(|pool| pool.database.is_paused()) {
+        return ServerState::Paused;
+    }
+    ServerState::Accepting
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    #[test]
+    #[serial(drain)]
+    fn drain_and_undrain_report_transitions() {
+        assert!(drain());
+        assert!(!drain());
+        assert!(is_draining());
+        assert_eq!(server_state(), ServerState::Draining);
+
+        assert!(undrain());
+        assert!(!undrain());
+        assert!(!is_draining());
+        assert_ne!(server_state(), ServerState::Draining);
+    }
+}
@@ -1,6 +1,7 @@
 //! Errors.
 
 use crate::auth::hba::CheckResult;
+use crate::config::PoolMode;
 
 /// Various errors.
 #[derive(Debug, PartialEq, Clone)]
@@ -12,6 +13,9 @@ pub enum Error {
     /// Local fd exhaustion while opening a backend connection.
     ConnectResourceExhausted(String),
     ClientBadStartup,
+    /// The client did not finish startup and authentication within
+    /// `general.startup_timeout`.
+    ClientStartupTimeout,
     ProtocolSyncError(String),
     BadQuery(String),
     ServerError,
@@ -57,6 +61,13 @@ pub enum Error {
     JWTPrivKey(String),
     JWTValidate(String),
     ProxyTimeout,
+    /// Backend connection closed mid-exchange. `sqlstate` is the backend's
+    /// own FATAL code (57P01 when the server was shut down or restarted),
+    /// or 08006 when it closed without one.
+    ServerConnectionLost {
+        sqlstate: String,
+        message: String,
+    },
     ConvertError(String),
     /// PostgreSQL unreachable, connection lost. Transient — retry on next request.
     AuthQueryConnectionError(String),
@@ -77,6 +88,10 @@ pub struct ClientIdentifier {
     pub is_talos: bool,
     pub hba_scram: CheckResult,
     pub hba_md5: CheckResult,
+    /// Message of the `reject` rule that denied the connection, if any.
+    pub hba_reject_message: Option<String>,
+    /// Pool mode the client asked for with `pg_doorman.pool_mode`.
+    pub requested_pool_mode: Option<PoolMode>,
 }
 
 impl ClientIdentifier {
@@ -94,6 +109,8 @@ impl ClientIdentifier {
             is_talos: false,
             hba_scram: CheckResult::NotMatched,
             hba_md5: CheckResult::NotMatched,
+            hba_reject_message: None,
+            requested_pool_mode: None,
         }
     }
 }
@@ -144,6 +161,9 @@ impl std::fmt::Display for Error {
                 write!(f, "Backend connect local resource exhausted: {msg}")
             }
             Error::ClientBadStartup => write!(f, "Client sent an invalid startup message"),
+            Error::ClientStartupTimeout => {
+                write!(f, "Client did not complete startup within startup_timeout")
+            }
             Error::ProtocolSyncError(msg) => write!(f, "Protocol synchronization error: {msg}"),
             Error::BadQuery(msg) => write!(f, "Invalid query: {msg}"),
             Error::ServerError => write!(f, "Server encountered an error"),
@@ -195,6 +215,9 @@ impl std::fmt::Display for Error {
             Error::JWTPrivKey(msg) => write!(f, "JWT private key error: {msg}"),
             Error::JWTValidate(msg) => write!(f, "JWT validation error: {msg}"),
             Error::ProxyTimeout => write!(f, "Proxy operation timed out"),
+            Error::ServerConnectionLost { sqlstate, message } => {
+                write!(f, "Server connection lost (sqlstate {sqlstate}): {message}")
+            }
             Error::ConvertError(msg) => write!(f, "Data conversion error: {msg}"),
             Error::AuthQueryConnectionError(msg) => {
                 write!(f, "Auth query connection error: {msg}")
@@ -150,16 +150,53 @@ pub fn generate_reference_config(format: ConfigFormat, russian: bool) -> String
         server_host: "127.0.0.1".to_string(),
         server_port: 5432,
         server_database: None,
+        server_username: None,
+        server_password: None,
         connect_timeout: None,
         idle_timeout: None,
         server_lifetime: None,
+        client_idle_timeout: None,
+        max_query_duration: None,
+        max_result_rows: None,
+        server_max_age: None,
+        ignore_startup_parameters: None,
+        parameter_status_overrides: std::collections::BTreeMap::new(),
+        shards: std::collections::BTreeMap::new(),
+        hosts: Vec::new(),
+        message_size_to_be_stream: None,
         cleanup_server_connections: true,
+        server_cleanup_mode: None,
+        server_cleanup_query: None,
+        connect_queries: Vec::new(),
+        server_statement_timeout_ms: None,
+        server_lock_timeout_ms: None,
+        default_client_statement_timeout_ms: None,
+        server_tcp_keepalives_idle: None,
+        server_tcp_keepalives_interval: None,
+        server_tcp_keepalives_count: None,
+        server_tcp_user_timeout: None,
+        max_concurrent_creates: None,
+        max_concurrent_queries: None,
+        auto_pause_on_unhealthy: false,
+        auto_pause_failure_threshold: Pool::default_auto_pause_failure_threshold(),
+        auto_pause_success_threshold: Pool::default_auto_pause_success_threshold(),
         log_client_parameter_status_changes: false,
+        log_startup_parameters: false,
+        share_server_connections: false,
+        read_only: false,
+        reject_write_queries: false,
+        retry_readonly_on_disconnect: false,
+        prefer_last_server: false,
+        allow_session_override: false,
+        scram_relay: false,
+        client_label_gucs: false,
         application_name: None,
+        prepared_statements: None,
         prepared_statements_cache_size: None,
         server_prepared_statements_cache_size: None,
         scaling_warm_pool_ratio: None,
         scaling_fast_retries: None,
+        scaling_shrink_step: None,
         max_db_connections: None,
         min_connection_lifetime: None,
         reserve_pool_size: None,
@@ -186,6 +223,10 @@ pub fn generate_reference_config(format: ConfigFormat, russian: bool) -> String
             server_username: None,
             server_password: None,
             auth_pam_service: None,
+            connect_queries: None,
+            priority: None,
+            server_role: None,
+            max_concurrent_transactions: None,
         }],
     };
 
@@ -441,6 +482,7 @@ fn write_include_section(w: &mut ConfigWriter) {
     let f = &*FIELDS;
     w.comment(0, f.text("include_desc").get(w.russian));
     w.comment(0, f.text("include_merge").get(w.russian));
+    w.comment(0, f.text("include_dirs").get(w.russian));
     w.section(0, "include");
     let fi = w.field_indent();
     match w.format {
@@ -450,11 +492,14 @@ fn write_include_section(w: &mut ConfigWriter) {
                 "files",
                 "[\"/etc/pg_doorman/pools.toml\", \"/etc/pg_doorman/hba.toml\"]",
             );
+            w.commented_kv(fi, "dirs", "[\"/etc/pg_doorman/pools.d\"]");
         }
         ConfigFormat::Yaml => {
             w.comment(fi, "files:");
             w.comment(fi, "  - \"/etc/pg_doorman/pools.yaml\"");
             w.comment(fi, "  - \"/etc/pg_doorman/hba.yaml\"");
+            w.comment(fi, "dirs:");
+            w.comment(fi, "  - \"/etc/pg_doorman/pools.d\"");
         }
     }
     w.blank();
@@ -480,10 +525,42 @@ fn write_general_section(w: &mut ConfigWriter, config: &Config) {
     w.kv(fi, "port", &w.num_val(g.port));
     w.blank();
 
+    write_field_comment(w, fi, "general", "listeners");
+    match w.format {
+        ConfigFormat::Toml => {
+            w.comment(
+                fi,
+                "listeners = [{ port = 6433, default_database = \"staging\" }]",
+            );
+        }
+        ConfigFormat::Yaml => {
+            w.comment(fi, "listeners:");
+            w.comment(fi, "  - port: 6433");
+            w.comment(fi, "    default_database: \"staging\"");
+        }
+    }
+    w.blank();
+
+    write_field_comment(w, fi, "general", "admin_listen_host");
+    w.commented_kv(fi, "admin_listen_host", &w.str_val("127.0.0.1"));
+    w.blank();
+
+    write_field_comment(w, fi, "general", "admin_listen_port");
+    w.commented_kv(fi, "admin_listen_port", &w.num_val(6431));
+    w.blank();
+
     write_field_comment(w, fi, "general", "backlog");
     w.kv(fi, "backlog", &w.num_val(g.backlog));
     w.blank();
 
+    write_field_comment(w, fi, "general", "accept_proxy_protocol");
+    w.kv(
+        fi,
+        "accept_proxy_protocol",
+        &w.bool_val(g.accept_proxy_protocol),
+    );
+    w.blank();
+
     // --- Connection Timeouts ---
     w.separator(fi, f.section_title("timeouts").get(w.russian));
     w.blank();
@@ -498,6 +575,36 @@ fn write_general_section(w: &mut ConfigWriter, config: &Config) {
         "3000 ms",
     );
 
+    write_field_desc(w, fi, "general", "server_connect_backoff_base");
+    write_duration_value(
+        w,
+        fi,
+        "server_connect_backoff_base",
+        g.server_connect_backoff_base.as_millis(),
+        "10ms",
+        "10 ms",
+    );
+
+    write_field_desc(w, fi, "general", "server_connect_backoff_max");
+    write_duration_value(
+        w,
+        fi,
+        "server_connect_backoff_max",
+        g.server_connect_backoff_max.as_millis(),
+        "1s",
+        "1000 ms",
+    );
+
+    write_field_desc(w, fi, "general", "server_connect_hold");
+    write_duration_value(
+        w,
+        fi,
+        "server_connect_hold",
+        g.server_connect_hold.as_millis(),
+        "3s",
+        "3000 ms",
+    );
+
     write_field_desc(w, fi, "general", "query_wait_timeout");
     write_duration_value(
         w,
@@ -576,6 +683,14 @@ fn write_general_section(w: &mut ConfigWriter, config: &Config) {
         "15000 ms",
     );
 
+    write_field_comment(w, fi, "general", "unknown_message_behavior");
+    w.kv(
+        fi,
+        "unknown_message_behavior",
+        &w.str_val(&g.unknown_message_behavior.to_string()),
+    );
+    w.blank();
+
     // --- TCP Settings ---
     w.separator(fi, f.section_title("tcp").get(w.russian));
     w.blank();
@@ -649,6 +764,70 @@ fn write_general_section(w: &mut ConfigWriter, config: &Config) {
     w.kv(fi, "max_connections", &w.num_val(g.max_connections));
     w.blank();
 
+    write_field_comment(w, fi, "general", "max_connections_per_tag");
+    w.kv(
+        fi,
+        "max_connections_per_tag",
+        &w.num_val(g.max_connections_per_tag),
+    );
+    w.blank();
+
+    write_field_comment(w, fi, "general", "max_connections_overflow_behavior");
+    w.kv(
+        fi,
+        "max_connections_overflow_behavior",
+        &w.str_val(&g.max_connections_overflow_behavior.to_string()),
+    );
+    w.blank();
+
+    write_field_desc(w, fi, "general", "max_connections_queue_timeout");
+    write_duration_value(
+        w,
+        fi,
+        "max_connections_queue_timeout",
+        g.max_connections_queue_timeout.as_millis(),
+        "1s",
+        "1000 ms",
+    );
+
+    write_field_comment(w, fi, "general", "auth_fail_ban_threshold");
+    w.kv(
+        fi,
+        "auth_fail_ban_threshold",
+        &w.num_val(g.auth_fail_ban_threshold),
+    );
+    w.blank();
+
+    write_field_desc(w, fi, "general", "auth_fail_ban_window");
+    write_duration_value(
+        w,
+        fi,
+        "auth_fail_ban_window",
+        g.auth_fail_ban_window.as_millis(),
+        "1m",
+        "60000 ms",
+    );
+
+    write_field_desc(w, fi, "general", "auth_fail_ban_duration");
+    write_duration_value(
+        w,
+        fi,
+        "auth_fail_ban_duration",
+        g.auth_fail_ban_duration.as_millis(),
+        "5m",
+        "300000 ms",
+    );
+
+    write_field_desc(w, fi, "general", "startup_timeout");
+    write_duration_value(
+        w,
+        fi,
+        "startup_timeout",
+        g.startup_timeout.as_millis(),
+        "1m",
+        "60000 ms",
+    );
+
     write_field_comment(w, fi, "general", "max_concurrent_creates");
     w.kv(
         fi,
@@ -657,6 +836,14 @@ fn write_general_section(w: &mut ConfigWriter, config: &Config) {
     );
     w.blank();
 
+    write_field_comment(w, fi, "general", "max_concurrent_queries");
+    w.kv(
+        fi,
+        "max_concurrent_queries",
+        &w.num_val(g.max_concurrent_queries),
+    );
+    w.blank();
+
     write_field_desc(w, fi, "general", "max_memory_usage");
     write_byte_size_value(
         w,
@@ -667,6 +854,34 @@ fn write_general_section(w: &mut ConfigWriter, config: &Config) {
         "268435456 bytes",
     );
 
+    write_field_desc(w, fi, "general", "buffer_pool_initial_capacity");
+    write_byte_size_value(
+        w,
+        fi,
+        "buffer_pool_initial_capacity",
+        g.buffer_pool_initial_capacity.as_bytes(),
+        "8KB",
+        "8192 bytes",
+    );
+
+    write_field_comment(w, fi, "general", "buffer_pool_max_retained");
+    w.kv(
+        fi,
+        "buffer_pool_max_retained",
+        &w.num_val(g.buffer_pool_max_retained),
+    );
+    w.blank();
+
+    write_field_desc(w, fi, "general", "buffer_pool_max_buffer_size");
+    write_byte_size_value(
+        w,
+        fi,
+        "buffer_pool_max_buffer_size",
+        g.buffer_pool_max_buffer_size.as_bytes(),
+        "32KB",
+        "32768 bytes",
+    );
+
     // --- Connection Scaling ---
     w.separator(fi, f.section_title("scaling").get(w.russian));
     w.blank();
@@ -693,6 +908,10 @@ fn write_general_section(w: &mut ConfigWriter, config: &Config) {
         "scaling_max_parallel_creates",
         &w.num_val(g.scaling_max_parallel_creates),
     );
+    w.blank();
+
+    write_field_comment(w, fi, "general", "scaling_shrink_step");
+    w.kv(fi, "scaling_shrink_step", &w.num_val(g.scaling_shrink_step));
 
     // --- Logging ---
     w.separator(fi, f.section_title("logging").get(w.russian));
@@ -714,6 +933,18 @@ fn write_general_section(w: &mut ConfigWriter, config: &Config) {
     );
     w.blank();
 
+    write_field_comment(w, fi, "general", "auth_failures_by_subnet");
+    w.commented_kv(
+        fi,
+        "auth_failures_by_subnet",
+        &w.bool_val(g.auth_failures_by_subnet),
+    );
+    w.blank();
+
+    write_field_comment(w, fi, "general", "stats_application_names");
+    w.commented_kv(fi, "stats_application_names", "[\"billing\", \"reports\"]");
+    w.blank();
+
     write_field_comment(w, fi, "general", "syslog_prog_name");
     if let Some(ref name) = g.syslog_prog_name {
         w.kv(fi, "syslog_prog_name", &w.str_val(name));
@@ -849,6 +1080,14 @@ fn write_general_section(w: &mut ConfigWriter, config: &Config) {
     );
     w.blank();
 
+    write_field_comment(w, fi, "general", "track_application_name");
+    w.kv(
+        fi,
+        "track_application_name",
+        &w.bool_val(g.track_application_name),
+    );
+    w.blank();
+
     write_field_desc(w, fi, "general", "message_size_to_be_stream");
     write_byte_size_value(
         w,
@@ -899,6 +1138,14 @@ fn write_general_section(w: &mut ConfigWriter, config: &Config) {
     }
     w.blank();
 
+    write_field_comment(w, fi, "general", "log_prepared_cache_evictions");
+    w.kv(
+        fi,
+        "log_prepared_cache_evictions",
+        &w.bool_val(g.log_prepared_cache_evictions),
+    );
+    w.blank();
+
     write_field_comment(w, fi, "general", "query_interner_gc_interval_seconds");
     w.kv(
         fi,
@@ -927,6 +1174,31 @@ fn write_general_section(w: &mut ConfigWriter, config: &Config) {
     w.kv(fi, "admin_password", &w.str_val(&g.admin_password));
     w.blank();
 
+    write_field_comment(w, fi, "general", "admin_users");
+    match w.format {
+        ConfigFormat::Toml => {
+            w.comment(
+                fi,
+                "admin_users = [{ username = \"monitoring\", password = \"change_me\", permission = \"readonly\" }]",
+            );
+        }
+        ConfigFormat::Yaml => {
+            w.comment(fi, "admin_users:");
+            w.comment(fi, "  - username: \"monitoring\"");
+            w.comment(fi, "    password: \"change_me\"");
+            w.comment(fi, "    permission: \"readonly\"");
+        }
+    }
+    w.blank();
+
+    write_field_comment(w, fi, "general", "admin_database_name");
+    w.commented_kv(
+        fi,
+        "admin_database_name",
+        &w.str_val(&g.admin_database_name),
+    );
+    w.blank();
+
     // --- TLS Settings (Client-facing) ---
     w.separator(fi, f.section_title("tls_client").get(w.russian));
     w.blank();
@@ -971,6 +1243,26 @@ fn write_general_section(w: &mut ConfigWriter, config: &Config) {
     );
     w.blank();
 
+    write_field_desc(w, fi, "general", "tls_min_protocol_version");
+    if let Some(ref version) = g.tls_min_protocol_version {
+        w.kv(fi, "tls_min_protocol_version", &w.str_val(version));
+    } else {
+        w.commented_kv(fi, "tls_min_protocol_version", &w.str_val("1.2"));
+    }
+    w.blank();
+
+    write_field_desc(w, fi, "general", "tls_ciphers");
+    if let Some(ref ciphers) = g.tls_ciphers {
+        w.kv(fi, "tls_ciphers", &w.str_val(ciphers));
+    } else {
+        w.commented_kv(
+            fi,
+            "tls_ciphers",
+            &w.str_val("ECDHE-ECDSA-AES256-GCM-SHA384:ECDHE-RSA-AES256-GCM-SHA384"),
+        );
+    }
+    w.blank();
+
     // --- TLS Settings (Server-facing) ---
     w.separator(fi, f.section_title("tls_server").get(w.russian));
     w.blank();
@@ -1058,6 +1350,29 @@ fn write_general_section(w: &mut ConfigWriter, config: &Config) {
         }
     }
     w.blank();
+
+    write_field_comment(w, fi, "general", "ignore_startup_parameters");
+    w.commented_kv(
+        fi,
+        "ignore_startup_parameters",
+        "[\"extra_float_digits\", \"DateStyle\"]",
+    );
+    w.blank();
+
+    write_field_comment(w, fi, "general", "parameter_status_overrides");
+    match w.format {
+        ConfigFormat::Toml => {
+            w.comment(
+                fi,
+                "parameter_status_overrides = { server_version = \"14.0\" }",
+            );
+        }
+        ConfigFormat::Yaml => {
+            w.comment(fi, "parameter_status_overrides:");
+            w.comment(fi, "  server_version: \"14.0\"");
+        }
+    }
+    w.blank();
 }
 
 fn write_pg_hba_examples(w: &mut ConfigWriter, fi: usize) {
@@ -1146,6 +1461,14 @@ fn write_web_section(w: &mut ConfigWriter, web: &Web) {
     w.kv(fi, "port", &w.num_val(web.port));
     w.blank();
 
+    write_field_comment(w, fi, "web", "unix_socket");
+    if let Some(ref p) = web.unix_socket {
+        w.kv(fi, "unix_socket", &w.str_val(&p.display().to_string()));
+    } else {
+        w.commented_kv(fi, "unix_socket", "\"/var/run/pg_doorman/web.sock\"");
+    }
+    w.blank();
+
     write_field_comment(w, fi, "web", "ui");
     w.kv(fi, "ui", &w.bool_val(web.ui));
     w.blank();
@@ -1345,6 +1668,22 @@ fn write_single_pool(w: &mut ConfigWriter, pool_name: &str, pool: &Pool) {
     }
     w.blank();
 
+    write_field_desc(w, fi, "pool", "server_username");
+    if let Some(ref name) = pool.server_username {
+        w.kv(fi, "server_username", &w.str_val(name));
+    } else {
+        w.commented_kv(fi, "server_username", "\"app_service\"");
+    }
+    w.blank();
+
+    write_field_desc(w, fi, "pool", "server_password");
+    if let Some(ref password) = pool.server_password {
+        w.kv(fi, "server_password", &w.str_val(password));
+    } else {
+        w.commented_kv(fi, "server_password", "\"app_service_password\"");
+    }
+    w.blank();
+
     // --- Pool Settings ---
     w.separator(fi, f.section_title("pool_settings").get(w.russian));
     w.blank();
@@ -1377,6 +1716,46 @@ fn write_single_pool(w: &mut ConfigWriter, pool_name: &str, pool: &Pool) {
     }
     w.blank();
 
+    write_field_desc(w, fi, "pool", "client_idle_timeout");
+    if let Some(val) = pool.client_idle_timeout {
+        w.kv(fi, "client_idle_timeout", &w.num_val(val));
+    } else {
+        w.commented_kv(fi, "client_idle_timeout", "0");
+    }
+    w.blank();
+
+    write_field_desc(w, fi, "pool", "max_query_duration");
+    if let Some(val) = pool.max_query_duration {
+        w.kv(fi, "max_query_duration", &w.num_val(val));
+    } else {
+        w.commented_kv(fi, "max_query_duration", "0");
+    }
+    w.blank();
+
+    write_field_desc(w, fi, "pool", "max_result_rows");
+    if let Some(val) = pool.max_result_rows {
+        w.kv(fi, "max_result_rows", &w.num_val(val));
+    } else {
+        w.commented_kv(fi, "max_result_rows", "0");
+    }
+    w.blank();
+
+    write_field_desc(w, fi, "pool", "server_max_age");
+    if let Some(val) = pool.server_max_age {
+        w.kv(fi, "server_max_age", &w.num_val(val));
+    } else {
+        w.commented_kv(fi, "server_max_age", "0");
+    }
+    w.blank();
+
+    write_field_desc(w, fi, "pool", "message_size_to_be_stream");
+    if let Some(val) = pool.message_size_to_be_stream {
+        w.kv(fi, "message_size_to_be_stream", &w.num_val(val.as_bytes()));
+    } else {
+        w.commented_kv(fi, "message_size_to_be_stream", &w.num_val(1048576));
+    }
+    w.blank();
+
     write_field_comment(w, fi, "pool", "cleanup_server_connections");
     w.kv(
         fi,
@@ -1385,6 +1764,158 @@ fn write_single_pool(w: &mut ConfigWriter, pool_name: &str, pool: &Pool) {
     );
     w.blank();
 
+    write_field_desc(w, fi, "pool", "server_cleanup_mode");
+    if let Some(mode) = pool.server_cleanup_mode {
+        w.kv(fi, "server_cleanup_mode", &w.str_val(&mode.to_string()));
+    } else {
+        w.commented_kv(fi, "server_cleanup_mode", &w.str_val("reset_all"));
+    }
+    w.blank();
+
+    write_field_desc(w, fi, "pool", "server_cleanup_query");
+    if let Some(ref query) = pool.server_cleanup_query {
+        w.kv(fi, "server_cleanup_query", &w.str_val(query));
+    } else {
+        w.commented_kv(
+            fi,
+            "server_cleanup_query",
+            &w.str_val("RESET ALL; CLOSE ALL; UNLISTEN *"),
+        );
+    }
+    w.blank();
+
+    write_field_comment(w, fi, "pool", "connect_queries");
+    w.commented_kv(
+        fi,
+        "connect_queries",
+        "[\"SET statement_timeout = '30s'\", \"SELECT set_config('app.tenant', 'main', false)\"]",
+    );
+    w.blank();
+
+    for (name, value, example) in [
+        (
+            "server_statement_timeout_ms",
+            pool.server_statement_timeout_ms,
+            "30000",
+        ),
+        (
+            "server_lock_timeout_ms",
+            pool.server_lock_timeout_ms,
+            "5000",
+        ),
+        (
+            "default_client_statement_timeout_ms",
+            pool.default_client_statement_timeout_ms,
+            "60000",
+        ),
+    ] {
+        write_field_comment(w, fi, "pool", name);
+        if let Some(ms) = value {
+            w.kv(fi, name, &ms.to_string());
+        } else {
+            w.commented_kv(fi, name, example);
+        }
+        w.blank();
+    }
+
+    for (name, value, example) in [
+        (
+            "server_tcp_keepalives_idle",
+            pool.server_tcp_keepalives_idle,
+            "10",
+        ),
+        (
+            "server_tcp_keepalives_interval",
+            pool.server_tcp_keepalives_interval,
+            "3",
+        ),
+        (
+            "server_tcp_keepalives_count",
+            pool.server_tcp_keepalives_count.map(u64::from),
+            "3",
+        ),
+        (
+            "server_tcp_user_timeout",
+            pool.server_tcp_user_timeout,
+            "20",
+        ),
+    ] {
+        write_field_desc(w, fi, "pool", name);
+        if let Some(secs) = value {
+            w.kv(fi, name, &secs.to_string());
+        } else {
+            w.commented_kv(fi, name, example);
+        }
+        w.blank();
+    }
+
+    write_field_desc(w, fi, "pool", "max_concurrent_creates");
+    if let Some(limit) = pool.max_concurrent_creates {
+        w.kv(fi, "max_concurrent_creates", &w.num_val(limit));
+    } else {
+        w.commented_kv(fi, "max_concurrent_creates", "2");
+    }
+    w.blank();
+
+    write_field_desc(w, fi, "pool", "max_concurrent_queries");
+    if let Some(limit) = pool.max_concurrent_queries {
+        w.kv(fi, "max_concurrent_queries", &w.num_val(limit));
+    } else {
+        w.commented_kv(fi, "max_concurrent_queries", "50");
+    }
+    w.blank();
+
+    write_field_comment(w, fi, "pool", "auto_pause_on_unhealthy");
+    if pool.auto_pause_on_unhealthy {
+        w.kv(fi, "auto_pause_on_unhealthy", &w.bool_val(true));
+    } else {
+        w.commented_kv(fi, "auto_pause_on_unhealthy", &w.bool_val(true));
+    }
+    w.blank();
+
+    for (name, value, default) in [
+        (
+            "auto_pause_failure_threshold",
+            pool.auto_pause_failure_threshold,
+            Pool::default_auto_pause_failure_threshold(),
+        ),
+        (
+            "auto_pause_success_threshold",
+            pool.auto_pause_success_threshold,
+            Pool::default_auto_pause_success_threshold(),
+        ),
+    ] {
+        write_field_comment(w, fi, "pool", name);
+        if value != default {
+            w.kv(fi, name, &w.num_val(value));
+        } else {
+            w.commented_kv(fi, name, &w.num_val(value));
+        }
+        w.blank();
+    }
+
+    write_field_desc(w, fi, "pool", "server_tls_mode");
+    if let Some(ref mode) = pool.server_tls_mode {
+        w.kv(fi, "server_tls_mode", &w.str_val(mode));
+    } else {
+        w.commented_kv(fi, "server_tls_mode", &w.str_val("verify-full"));
+    }
+    w.blank();
+
+    for (name, value) in [
+        ("server_tls_ca_cert", &pool.server_tls_ca_cert),
+        ("server_tls_certificate", &pool.server_tls_certificate),
+        ("server_tls_private_key", &pool.server_tls_private_key),
+    ] {
+        write_field_desc(w, fi, "pool", name);
+        if let Some(v) = value {
+            w.kv(fi, name, &w.str_val(v));
+        } else {
+            w.commented_kv(fi, name, &w.str_val(""));
+        }
+        w.blank();
+    }
+
     write_field_desc(w, fi, "pool", "prepared_statements_cache_size");
     if let Some(val) = pool.prepared_statements_cache_size {
         w.kv(fi, "prepared_statements_cache_size", &w.num_val(val));
@@ -1421,6 +1952,14 @@ fn write_single_pool(w: &mut ConfigWriter, pool_name: &str, pool: &Pool) {
     }
     w.blank();
 
+    write_field_desc(w, fi, "pool", "scaling_shrink_step");
+    if let Some(val) = pool.scaling_shrink_step {
+        w.kv(fi, "scaling_shrink_step", &w.num_val(val));
+    } else {
+        w.commented_kv(fi, "scaling_shrink_step", "2");
+    }
+    w.blank();
+
     // --- Pool Coordinator ---
     w.separator(fi, f.section_title("pool_coordinator").get(w.russian));
     w.blank();
@@ -1522,6 +2061,73 @@ fn write_single_pool(w: &mut ConfigWriter, pool_name: &str, pool: &Pool) {
     );
     w.blank();
 
+    write_field_comment(w, fi, "pool", "log_startup_parameters");
+    w.kv(
+        fi,
+        "log_startup_parameters",
+        &w.bool_val(pool.log_startup_parameters),
+    );
+    w.blank();
+
+    write_field_comment(w, fi, "pool", "share_server_connections");
+    w.commented_kv(
+        fi,
+        "share_server_connections",
+        &w.bool_val(pool.share_server_connections),
+    );
+    w.blank();
+
+    write_field_comment(w, fi, "pool", "read_only");
+    w.commented_kv(fi, "read_only", &w.bool_val(pool.read_only));
+    w.blank();
+
+    write_field_comment(w, fi, "pool", "reject_write_queries");
+    w.commented_kv(
+        fi,
+        "reject_write_queries",
+        &w.bool_val(pool.reject_write_queries),
+    );
+    w.blank();
+
+    write_field_comment(w, fi, "pool", "retry_readonly_on_disconnect");
+    w.commented_kv(
+        fi,
+        "retry_readonly_on_disconnect",
+        &w.bool_val(pool.retry_readonly_on_disconnect),
+    );
+    w.blank();
+
+    write_field_comment(w, fi, "pool", "prefer_last_server");
+    w.commented_kv(
+        fi,
+        "prefer_last_server",
+        &w.bool_val(pool.prefer_last_server),
+    );
+    w.blank();
+
+    write_field_comment(w, fi, "pool", "allow_session_override");
+    w.commented_kv(
+        fi,
+        "allow_session_override",
+        &w.bool_val(pool.allow_session_override),
+    );
+    w.blank();
+
+    write_field_comment(w, fi, "pool", "scram_relay");
+    w.commented_kv(fi, "scram_relay", &w.bool_val(pool.scram_relay));
+    w.blank();
+
+    write_field_comment(w, fi, "pool", "client_label_gucs");
+    w.commented_kv(fi, "client_label_gucs", &w.bool_val(pool.client_label_gucs));
+    w.blank();
+
+    write_field_comment(w, fi, "pool", "prepared_statements");
+    match pool.prepared_statements {
+        Some(val) => w.kv(fi, "prepared_statements", &w.bool_val(val)),
+        None => w.commented_kv(fi, "prepared_statements", &w.bool_val(false)),
+    }
+    w.blank();
+
     // --- Per-pool Startup Parameters ---
     write_field_comment(w, fi, "pool", "startup_parameters");
     match w.format {
@@ -1538,6 +2144,61 @@ fn write_single_pool(w: &mut ConfigWriter, pool_name: &str, pool: &Pool) {
     }
     w.blank();
 
+    write_field_desc(w, fi, "pool", "ignore_startup_parameters");
+    w.commented_kv(fi, "ignore_startup_parameters", "[\"extra_float_digits\"]");
+    w.blank();
+
+    write_field_desc(w, fi, "pool", "parameter_status_overrides");
+    match w.format {
+        ConfigFormat::Toml => {
+            w.comment(
+                fi,
+                "parameter_status_overrides = { is_superuser = \"off\" }",
+            );
+        }
+        ConfigFormat::Yaml => {
+            w.comment(fi, "parameter_status_overrides:");
+            w.comment(fi, "  is_superuser: \"off\"");
+        }
+    }
+    w.blank();
+
+    write_field_comment(w, fi, "pool", "shards");
+    match w.format {
+        ConfigFormat::Toml => {
+            w.comment(
+                fi,
+                "shards = { \"1\" = { server_host = \"10.0.1.1\" }, \"2\" = { server_host = \"10.0.2.1\", server_database = \"app_2\" } }",
+            );
+        }
+        ConfigFormat::Yaml => {
+            w.comment(fi, "shards:");
+            w.comment(fi, "  \"1\":");
+            w.comment(fi, "    server_host: \"10.0.1.1\"");
+            w.comment(fi, "  \"2\":");
+            w.comment(fi, "    server_host: \"10.0.2.1\"");
+            w.comment(fi, "    server_database: \"app_2\"");
+        }
+    }
+    w.blank();
+
+    write_field_comment(w, fi, "pool", "hosts");
+    match w.format {
+        ConfigFormat::Toml => {
+            w.comment(
+                fi,
+                "hosts = [{ server_host = \"10.0.0.2\" }, { server_host = \"10.0.0.3\", server_port = 6432 }]",
+            );
+        }
+        ConfigFormat::Yaml => {
+            w.comment(fi, "hosts:");
+            w.comment(fi, "  - server_host: \"10.0.0.2\"");
+            w.comment(fi, "  - server_host: \"10.0.0.3\"");
+            w.comment(fi, "    server_port: 6432");
+        }
+    }
+    w.blank();
+
     write_pool_users(w, pool_name, &pool.users);
     write_auth_query_commented_example(w);
 }
@@ -1637,6 +2298,34 @@ fn write_user_fields_toml(w: &mut ConfigWriter, user: &User, fi: usize) {
     } else {
         w.commented_kv(fi, "auth_pam_service", "\"pg_doorman\"");
     }
+    w.blank();
+
+    write_field_desc(w, fi, "user", "connect_queries");
+    w.commented_kv(fi, "connect_queries", "[\"SET ROLE app_reader\"]");
+    w.blank();
+
+    write_field_comment(w, fi, "user", "priority");
+    if let Some(val) = user.priority {
+        w.kv(fi, "priority", &w.num_val(val));
+    } else {
+        w.commented_kv(fi, "priority", "10");
+    }
+    w.blank();
+
+    write_field_desc(w, fi, "user", "server_role");
+    if let Some(ref role) = user.server_role {
+        w.kv(fi, "server_role", &w.str_val(role));
+    } else {
+        w.commented_kv(fi, "server_role", "\"tenant_a\"");
+    }
+    w.blank();
+
+    write_field_desc(w, fi, "user", "max_concurrent_transactions");
+    if let Some(limit) = user.max_concurrent_transactions {
+        w.kv(fi, "max_concurrent_transactions", &w.num_val(limit));
+    } else {
+        w.commented_kv(fi, "max_concurrent_transactions", "10");
+    }
 }
 
 fn write_user_fields_yaml(w: &mut ConfigWriter, user: &User) {
@@ -1710,6 +2399,37 @@ fn write_user_fields_yaml(w: &mut ConfigWriter, user: &User) {
     } else {
         let _ = writeln!(w.output, "{indent}  # auth_pam_service: \"pg_doorman\"");
     }
+    w.blank();
+
+    write_field_desc(w, 3, "user", "connect_queries");
+    let _ = writeln!(
+        w.output,
+        "{indent}  # connect_queries: [\"SET ROLE app_reader\"]"
+    );
+    w.blank();
+
+    write_field_comment(w, 3, "user", "priority");
+    if let Some(val) = user.priority {
+        let _ = writeln!(w.output, "{indent}  priority: {val}");
+    } else {
+        let _ = writeln!(w.output, "{indent}  # priority: 10");
+    }
+    w.blank();
+
+    write_field_desc(w, 3, "user", "server_role");
+    if let Some(ref role) = user.server_role {
+        let _ = writeln!(w.output, "{indent}  server_role: \"{role}\"");
+    } else {
+        let _ = writeln!(w.output, "{indent}  # server_role: \"tenant_a\"");
+    }
+    w.blank();
+
+    write_field_desc(w, 3, "user", "max_concurrent_transactions");
+    if let Some(limit) = user.max_concurrent_transactions {
+        let _ = writeln!(w.output, "{indent}  max_concurrent_transactions: {limit}");
+    } else {
+        let _ = writeln!(w.output, "{indent}  # max_concurrent_transactions: 10");
+    }
 }
 
 /// Write documentation about server_username/server_password passthrough.
@@ -2061,7 +2781,10 @@ fn write_byte_size_value(
                     indent,
                     &format!(
                         "Supports human-readable format: \"{human_readable}\", \"{}\", or {bytes} (bytes)",
-                        human_readable.replace("MB", "M").replace("GB", "G"),
+                        human_readable
+                            .replace("KB", "K")
+                            .replace("MB", "M")
+                            .replace("GB", "G"),
                     ),
                 );
             }
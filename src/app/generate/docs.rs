@@ -135,6 +135,11 @@ fn write_include_files(out: &mut String) {
         out,
         "```yaml\ninclude:\n  files:\n    - \"pools.yaml\"\n    - \"users.toml\"\n```\n"
     );
+    let _ = writeln!(out, "`dirs` merges every `.toml`, `.yaml` and `.yml` file of a directory after `files`, in lexicographic order of file name. Other files and subdirectories are skipped. This suits one partial config (pools, users) per team:\n");
+    let _ = writeln!(
+        out,
+        "```yaml\ninclude:\n  dirs:\n    - \"/etc/pg_doorman/pools.d\"\n```\n"
+    );
 }
 
 fn write_human_readable_section(out: &mut String) {
@@ -203,18 +208,34 @@ fn write_general_fields(out: &mut String, f: &FieldsData) {
     let fields = [
         "host",
         "port",
+        "listeners",
+        "admin_listen_host",
+        "admin_listen_port",
         "backlog",
+        "accept_proxy_protocol",
         "max_connections",
+        "max_connections_per_tag",
+        "max_connections_overflow_behavior",
+        "max_connections_queue_timeout",
+        "auth_fail_ban_threshold",
+        "auth_fail_ban_window",
+        "auth_fail_ban_duration",
+        "startup_timeout",
         "max_concurrent_creates",
+        "max_concurrent_queries",
         "tls_mode",
         "tls_ca_cert",
         "tls_private_key",
         "tls_certificate",
         "tls_rate_limit_per_second",
+        "tls_min_protocol_version",
+        "tls_ciphers",
         "daemon_pid_file",
         "syslog_prog_name",
         "log_client_connections",
         "log_client_disconnections",
+        "auth_failures_by_subnet",
+        "stats_application_names",
         "worker_threads",
         "worker_cpu_affinity_pinning",
         "tokio_global_queue_interval",
@@ -222,6 +243,9 @@ fn write_general_fields(out: &mut String, f: &FieldsData) {
         "worker_stack_size",
         "max_blocking_threads",
         "connect_timeout",
+        "server_connect_backoff_base",
+        "server_connect_backoff_max",
+        "server_connect_hold",
         "query_wait_timeout",
         "idle_timeout",
         "server_lifetime",
@@ -230,6 +254,7 @@ fn write_general_fields(out: &mut String, f: &FieldsData) {
         "server_idle_check_timeout",
         "server_round_robin",
         "sync_server_parameters",
+        "track_application_name",
         "tcp_so_linger",
         "tcp_no_delay",
         "tcp_keepalives_count",
@@ -242,19 +267,27 @@ fn write_general_fields(out: &mut String, f: &FieldsData) {
         "unix_socket_mode",
         "admin_username",
         "admin_password",
+        "admin_users",
+        "admin_database_name",
         "prepared_statements",
         "prepared_statements_cache_size",
         "server_prepared_statements_cache_size",
         "client_anonymous_prepared_cache_size",
+        "log_prepared_cache_evictions",
         "query_interner_gc_interval_seconds",
         "query_interner_anon_idle_ttl_seconds",
         "message_size_to_be_stream",
         "scaling_warm_pool_ratio",
         "scaling_fast_retries",
         "scaling_max_parallel_creates",
+        "scaling_shrink_step",
         "max_memory_usage",
+        "buffer_pool_initial_capacity",
+        "buffer_pool_max_retained",
+        "buffer_pool_max_buffer_size",
         "shutdown_timeout",
         "proxy_copy_data_timeout",
+        "unknown_message_behavior",
         "server_tls_mode",
         "server_tls_ca_cert",
         "server_tls_certificate",
@@ -263,6 +296,8 @@ fn write_general_fields(out: &mut String, f: &FieldsData) {
         "pg_hba",
         "pooler_check_query",
         "startup_parameters",
+        "ignore_startup_parameters",
+        "parameter_status_overrides",
     ];
 
     for name in &fields {
@@ -279,21 +314,62 @@ fn write_pool_fields(out: &mut String, f: &FieldsData) {
         "server_host",
         "server_port",
         "server_database",
+        "server_username",
+        "server_password",
         "application_name",
         "connect_timeout",
         "idle_timeout",
         "server_lifetime",
+        "client_idle_timeout",
+        "max_query_duration",
+        "max_result_rows",
+        "server_max_age",
+        "message_size_to_be_stream",
         "pool_mode",
         "log_client_parameter_status_changes",
+        "log_startup_parameters",
+        "share_server_connections",
+        "read_only",
+        "reject_write_queries",
+        "retry_readonly_on_disconnect",
+        "prefer_last_server",
+        "allow_session_override",
+        "scram_relay",
+        "client_label_gucs",
+        "prepared_statements",
         "cleanup_server_connections",
+        "server_cleanup_mode",
+        "server_cleanup_query",
+        "connect_queries",
+        "server_statement_timeout_ms",
+        "server_lock_timeout_ms",
+        "default_client_statement_timeout_ms",
+        "server_tcp_keepalives_idle",
+        "server_tcp_keepalives_interval",
+        "server_tcp_keepalives_count",
+        "server_tcp_user_timeout",
+        "max_concurrent_creates",
+        "max_concurrent_queries",
+        "auto_pause_on_unhealthy",
+        "auto_pause_failure_threshold",
+        "auto_pause_success_threshold",
+        "server_tls_mode",
+        "server_tls_ca_cert",
+        "server_tls_certificate",
+        "server_tls_private_key",
         "scaling_warm_pool_ratio",
         "scaling_fast_retries",
+        "scaling_shrink_step",
         "max_db_connections",
         "min_connection_lifetime",
         "reserve_pool_size",
         "reserve_pool_timeout",
         "min_guaranteed_pool_size",
         "startup_parameters",
+        "ignore_startup_parameters",
+        "parameter_status_overrides",
+        "shards",
+        "hosts",
     ];
 
     for name in &fields {
@@ -352,6 +428,10 @@ fn write_user_fields(out: &mut String, f: &FieldsData) {
         "pool_size",
         "min_pool_size",
         "server_lifetime",
+        "connect_queries",
+        "priority",
+        "server_role",
+        "max_concurrent_transactions",
     ];
 
     for name in &fields {
@@ -392,10 +472,11 @@ fn write_prometheus_fields(out: &mut String, f: &FieldsData) {
         "| `host` | {} | `\"0.0.0.0\"` |",
         field_doc(f, "web", "host")
     );
+    let _ = writeln!(out, "| `port` | {} | `9127` |", field_doc(f, "web", "port"));
     let _ = writeln!(
         out,
-        "| `port` | {} | `9127` |\n",
-        field_doc(f, "web", "port")
+        "| `unix_socket` | {} | `null` |\n",
+        field_doc(f, "web", "unix_socket")
     );
 }
 
@@ -412,14 +493,18 @@ fn write_prometheus_metrics_section(out: &mut String) {
     let _ = writeln!(out, "### System Metrics\n");
     let _ = writeln!(out, "| Metric | Description |");
     let _ = writeln!(out, "|--------|-------------|");
-    let _ = writeln!(out, "| `pg_doorman_total_memory` | Total memory allocated to the pg_doorman process in bytes. Monitors the memory footprint of the application. |\n");
+    let _ = writeln!(out, "| `pg_doorman_total_memory` | Total memory allocated to the pg_doorman process in bytes. Monitors the memory footprint of the application. |");
+    let _ = writeln!(out, "| `pg_doorman_message_memory_peak_bytes` | Peak bytes held in in-flight client message buffers since process start. Compare with `general.max_memory_usage` to size the limit. |");
+    let _ = writeln!(out, "| `pg_doorman_memory_limit_hits_total` | Client messages rejected with SQLSTATE 53400 because in-flight buffer memory would exceed `general.max_memory_usage`. |");
+    let _ = writeln!(out, "| `pg_doorman_buffer_pool_buffers_total` | Client buffer pool activity by `event`: `allocated` (new buffer, nothing to reuse), `reused` (taken from a free list), `dropped_oversized` (dropped on release for exceeding `general.buffer_pool_max_buffer_size`). Tune the `general.buffer_pool_*` settings against it. |\n");
 
     // Connection Metrics
     let _ = writeln!(out, "### Connection Metrics\n");
     let _ = writeln!(out, "| Metric | Description |");
     let _ = writeln!(out, "|--------|-------------|");
     let _ = writeln!(out, "| `pg_doorman_connections_total` | Cumulative count of accepted client connections by type. Types include: 'plain' (unencrypted), 'tls' (encrypted), 'cancel' (cancel-query startup), and 'total' (sum of all). Counter form; use `rate(pg_doorman_connections_total[5m])` for connection rate. |");
-    let _ = writeln!(out, "| `pg_doorman_connection_count` | DEPRECATED, removed in 3.10. Gauge mirror of `pg_doorman_connections_total` kept for one minor release. New rules and dashboards must consume the counter form. |\n");
+    let _ = writeln!(out, "| `pg_doorman_connection_count` | DEPRECATED, removed in 3.10. Gauge mirror of `pg_doorman_connections_total` kept for one minor release. New rules and dashboards must consume the counter form. |");
+    let _ = writeln!(out, "| `pg_doorman_client_phase_duration_seconds` | Histogram by `phase`: `tls_handshake` (client TLS handshake), `auth` (StartupMessage to AuthenticationOK), `checkout` (waiting for a backend, including query limits), `active` (holding a backend: one transaction in transaction mode, the whole session in session mode). Splits connect-to-first-query latency into TLS, authentication and pool wait. Aggregated across pools; failed phases are not observed. |\n");
 
     // Socket Metrics
     let _ = writeln!(out, "### Socket Metrics (Linux only)\n");
@@ -432,10 +517,14 @@ fn write_prometheus_metrics_section(out: &mut String) {
     let _ = writeln!(out, "| Metric | Description |");
     let _ = writeln!(out, "|--------|-------------|");
     let _ = writeln!(out, "| `pg_doorman_pools_clients` | Number of clients in connection pools by status, user, and database. Status values include: 'idle' (connected but not executing queries), 'waiting' (waiting for a server connection), and 'active' (currently executing queries). Helps monitor connection pool utilization and client distribution. |");
+    let _ = writeln!(out, "| `pg_doorman_pools_clients_backpressure` | Active clients pg_doorman is blocked writing a reply to, per user and database. Replies are forwarded in chunks of about 8 KB and the next chunk is read from the backend only after the previous one was written, so a client that reads slowly holds its server connection but never makes pg_doorman buffer the rest of the result. A sustained non-zero value points at slow consumers. |");
     let _ = writeln!(out, "| `pg_doorman_pools_servers` | Number of servers in connection pools by status, user, and database. Status values include: 'active' (actively serving clients) and 'idle' (available for new connections). Helps monitor server availability and load distribution. |");
     let _ = writeln!(out, "| `pg_doorman_pools_bytes_total` | Cumulative bytes transferred per pool and direction. Direction values include: 'received' (data from client) and 'sent' (data to client). Counter form; use `rate(pg_doorman_pools_bytes_total[5m])` for throughput. |");
     let _ = writeln!(out, "| `pg_doorman_pools_bytes` | DEPRECATED, removed in 3.10. Gauge mirror of `pg_doorman_pools_bytes_total`. |\n");
-    let _ = writeln!(out, "| `pg_doorman_pool_size` | Configured maximum pool size per user and database. Useful for calculating remaining pool capacity together with pg_doorman_pools_servers. |\n");
+    let _ = writeln!(out, "| `pg_doorman_pool_size` | Configured maximum pool size per user and database. Useful for calculating remaining pool capacity together with pg_doorman_pools_servers. |");
+    let _ = writeln!(out, "| `pg_doorman_pools_auto_pause_transitions_total` | Counter by user, database and `transition` (`pause`, `resume`). Counts pools entering and leaving auto-pause under `auto_pause_on_unhealthy`. A `pause` without a matching `resume` means the pool is still turning clients away. |");
+    let _ = writeln!(out, "| `pg_doorman_pools_affinity_checkouts_total` | Counter by user, database and `result` (`hit`, `miss`). Checkouts in pools with `prefer_last_server` by clients that used a server before: `hit` when the client got the same server back. `rate(...{result=\"hit\"}[5m])` divided by the rate of both results is the affinity hit rate. No series for other pools. |");
+    let _ = writeln!(out, "| `pg_doorman_pool_current_size` | Current number of server connections per user and database, in any state. Moves between min_pool_size and pg_doorman_pool_size as the pool grows on demand and shrinks after idle_timeout. |\n");
 
     // Query and Transaction Metrics
     let _ = writeln!(out, "### Query and Transaction Metrics\n");
@@ -452,7 +541,19 @@ fn write_prometheus_metrics_section(out: &mut String) {
     let _ = writeln!(out, "| `pg_doorman_pools_queries_total` | Cumulative query count per pool. Counter form; use `rate(pg_doorman_pools_queries_total[5m])` for QPS. |");
     let _ = writeln!(out, "| `pg_doorman_pools_queries_count` | DEPRECATED, removed in 3.10. Gauge mirror of `pg_doorman_pools_queries_total`. |");
     let _ = writeln!(out, "| `pg_doorman_pools_queries_total_time` | Total time spent executing queries in connection pools by user and database. Values are in milliseconds. Helps monitor overall query performance and identify users or databases with high query execution times. |");
-    let _ = writeln!(out, "| `pg_doorman_pools_avg_wait_time` | DEPRECATED, removed in 3.10. Running mean that drowns tail wait spikes. Use `pg_doorman_pools_wait_duration_seconds_bucket` with `histogram_quantile()`. |\n");
+    let _ = writeln!(out, "| `pg_doorman_pools_application_queries_total` | Counter by `user`, `database` and `application_name`. Queries per client application, for the names listed in `stats_application_names`; other names are reported as `other`. No series while that setting is empty. |");
+    let _ = writeln!(out, "| `pg_doorman_pools_application_transactions_total` | Transactions per client application. Same labels as `pg_doorman_pools_application_queries_total`. |");
+    let _ = writeln!(out, "| `pg_doorman_pools_application_query_time_microseconds_total` | Query time per client application, in microseconds. `rate()` of it divided by `rate(pg_doorman_pools_application_queries_total[5m])` gives the mean query time of one application. |");
+    let _ = writeln!(out, "| `pg_doorman_pools_avg_wait_time` | DEPRECATED, removed in 3.10. Running mean that drowns tail wait spikes. Use `pg_doorman_pools_wait_duration_seconds_bucket` with `histogram_quantile()`. |");
+    let _ = writeln!(out, "| `pg_doorman_session_feature_in_txn_total` | Counter by user, database and `feature` (`temp_table`, `with_hold_cursor`, `advisory_lock`, `listen`). Statements on transaction-mode connections that rely on session state lost when the server connection is released. Detection is a keyword match and never blocks the statement; the first hit per client is also logged as a warning. |\n");
+
+    // Authentication Metrics
+    let _ = writeln!(out, "### Authentication Metrics\n");
+    let _ = writeln!(out, "| Metric | Description |");
+    let _ = writeln!(out, "|--------|-------------|");
+    let _ = writeln!(out, "| `pg_doorman_auth_failures_total` | Counter by `reason` and `subnet`. Rejected client logins. Reasons: `bad_password` (wrong password, unknown auth_query user, invalid JWT or Talos token), `hba_denied`, `no_pool` (no pool or shard for the database and user), `unsupported_method` (password type pg_doorman cannot check), `protocol_error` (malformed SCRAM, PAM or JWT message). `subnet` is the client's /24 or /64 network when `auth_failures_by_subnet` is on and empty otherwise; the username is never a label. A rising `rate(pg_doorman_auth_failures_total{reason=\"bad_password\"}[5m])` is the credential-stuffing signal. |");
+    let _ = writeln!(out, "| `pg_doorman_tls_handshake_failures_total` | Counter by `cause`. Failed client TLS handshakes: `protocol` (no common protocol version or cipher, or a non-TLS client), `cert_expired` (expired client certificate), `cert_unknown_ca` (a certificate that does not chain to a trusted CA, reported by either side), `alert` (the client aborted with another TLS alert), `other` (e.g. the client disconnected mid-handshake). Each failure is logged with its cause and also counted in `pg_doorman_listener_rejections_total{reason=\"tls_handshake_fail\"}`. |");
+    let _ = writeln!(out, "| `pg_doorman_protocol_errors_total` | Counter by `phase`: `startup` (startup packet of a new client), `client` (message from an authenticated client), `server` (message from a PostgreSQL backend). Connections closed because a message header had a type byte that is not an ASCII letter or digit, or a length below 4 or above 256 MiB. Each desync is logged with the offending header bytes and, for clients, the last message types received. A non-zero rate usually points to a buggy driver or to a non-PostgreSQL client on the port. |\n");
 
     // Auth Query Metrics
     let _ = writeln!(out, "### Auth Query Metrics\n");
@@ -489,8 +590,20 @@ fn write_prometheus_metrics_section(out: &mut String) {
     let _ = writeln!(out, "| `pg_doorman_servers_prepared_hits` | Live aggregate of prepared-statement cache hits across currently active backends of each pool, by user and database. This gauge can decrease when backends rotate; use `pg_doorman_servers_prepared_hits_total` for rates. |");
     let _ = writeln!(out, "| `pg_doorman_servers_prepared_misses` | Live aggregate of prepared-statement cache misses across currently active backends of each pool, by user and database. This gauge can decrease when backends rotate; use `pg_doorman_servers_prepared_misses_total` for rates. |");
     let _ = writeln!(out, "| `pg_doorman_servers_prepared_hits_total` | Counter form of prepared-statement cache hits across all backends of each pool, by user and database. Use `rate()` over this metric for hit throughput. |");
+    let _ = writeln!(out, "| `pg_doorman_servers_max_age_recycles_total` | Counter by user and database. Server connections closed at a transaction boundary because they were older than `server_max_age`. Zero while `server_max_age` is disabled. |");
+    let _ = writeln!(out, "| `pg_doorman_server_connection_lifetime_seconds` | Histogram by `reason`. How long each server connection lived, observed when it closes. Reasons: `idle` (`idle_timeout`, or an idle reserve connection), `lifetime` (`server_lifetime`), `age` (`server_max_age`), `bad` (broken connection or failed alive check), `reconnect` (`RECONNECT`), `evicted` (freed for another pool by the coordinator), `shutdown`, `other`. Aggregated across pools. Use it to tune `server_lifetime` and `idle_timeout`. |");
+    let _ = writeln!(out, "| `pg_doorman_server_recycles_total` | Counter by user, database and `reason` (same values as above). Every closed server connection counts once; `rate()` over it is the connection churn of the pool. |");
     let _ = writeln!(out, "| `pg_doorman_servers_prepared_misses_total` | Counter form of prepared-statement cache misses across all backends of each pool, by user and database. A sustained non-zero rate signals queries that could benefit from being prepared, or from a larger `server_prepared_statements_cache_size`. |\n");
 
+    // Pool Prepared Statement Cache Metrics
+    let _ = writeln!(out, "### Pool Prepared Statement Cache Metrics\n");
+    let _ = writeln!(out, "| Metric | Description |");
+    let _ = writeln!(out, "|--------|-------------|");
+    let _ = writeln!(out, "| `pg_doorman_pool_prepared_cache_entries` | Gauge by user and database. Number of entries in the pool-level prepared statement cache. |");
+    let _ = writeln!(out, "| `pg_doorman_pool_prepared_cache_bytes` | Gauge by user and database. Approximate memory used by the pool-level prepared statement cache. |");
+    let _ = writeln!(out, "| `pg_doorman_pool_prepared_cache_fill_ratio` | Gauge by user and database. Entries divided by `prepared_statements_cache_size`. Reaches 1 once the cache is full; from then on every new statement evicts the least recently used one. |");
+    let _ = writeln!(out, "| `pg_doorman_prepared_cache_evictions_total` | Counter by user and database. Cumulative count of entries evicted from the pool-level cache because it reached `prepared_statements_cache_size`. A sustained non-zero rate means the working set of distinct statements is larger than the cache and `prepared_statements_cache_size` should be raised. The info-level eviction log names the evicted statements; `log_prepared_cache_evictions` adds their hit and miss counts. |\n");
+
     // Per-Client Prepared Statement Cache Metrics
     let _ = writeln!(out, "### Per-Client Prepared Statement Cache Metrics\n");
     let _ = writeln!(out, "The per-client prepared statement cache is split into a Named map (unbounded) and an Anonymous LRU bounded by `client_anonymous_prepared_cache_size` (defaults to the resolved `prepared_statements_cache_size` when unset). The three metrics below expose the size of each part and the eviction rate on the bounded part.\n");
@@ -498,7 +611,8 @@ fn write_prometheus_metrics_section(out: &mut String) {
     let _ = writeln!(out, "|--------|-------------|");
     let _ = writeln!(out, "| `pg_doorman_clients_prepared_named_entries` | Gauge by user and database. Sum of Named entries across every connected client's cache. Named statements have no upper bound and are kept until the client disconnects or sends `DEALLOCATE`. Sustained growth here indicates drivers that mint per-query named statements (some pgjdbc / Hibernate flows, some .NET Npgsql configurations) and may justify capping per-client memory at the application layer. |");
     let _ = writeln!(out, "| `pg_doorman_clients_prepared_anonymous_entries` | Gauge by user and database. Sum of Anonymous entries across every connected client's cache. Each client's Anonymous part is capped at `client_anonymous_prepared_cache_size`, so this gauge approaches at most `connected_clients * cache_size`. |");
-    let _ = writeln!(out, "| `pg_doorman_clients_prepared_anonymous_evictions_total` | Counter by user and database. Cumulative count of Anonymous LRU evictions across all clients of the pool. A sustained non-zero rate signals that `client_anonymous_prepared_cache_size` is too small for the workload and the LRU is recycling entries faster than the application reuses them. The counter is monotonic per pool; an upgrade restarts it from zero. |\n");
+    let _ = writeln!(out, "| `pg_doorman_clients_prepared_anonymous_evictions_total` | Counter by user and database. Cumulative count of Anonymous LRU evictions across all clients of the pool. A sustained non-zero rate signals that `client_anonymous_prepared_cache_size` is too small for the workload and the LRU is recycling entries faster than the application reuses them. The counter is monotonic per pool; an upgrade restarts it from zero. |");
+    let _ = writeln!(out, "| `pg_doorman_clients_prepared_remaps_total` | Counter by user and database. Named Parses that reused a client statement name for a different query, after `Close` or `DEALLOCATE`. Legal, but the usual suspect behind `prepared statement does not exist` errors: compare with `SHOW PREPARED_STATEMENTS CLIENTS` to see which names map to which server statements. |\n");
 
     // Query Interner Metrics
     let _ = writeln!(out, "### Query Interner Metrics\n");
@@ -546,6 +660,12 @@ fn write_prometheus_metrics_section(out: &mut String) {
         "```\nhistogram_quantile(0.99, sum by (le, user, database) (rate(pg_doorman_pools_wait_duration_seconds_bucket[5m])))\n```\n"
     );
 
+    let _ = writeln!(out, "### Client Phase Latency (p99)\n");
+    let _ = writeln!(
+        out,
+        "```\nhistogram_quantile(0.99, sum by (le, phase) (rate(pg_doorman_client_phase_duration_seconds_bucket[5m])))\n```\n"
+    );
+
     let _ = writeln!(out, "### Auth Query Cache Hit Rate\n");
     let _ = writeln!(out, "```\nrate(pg_doorman_auth_query_cache_total{{type=\"hits\"}}[5m]) / clamp_min(rate(pg_doorman_auth_query_cache_total{{type=\"hits\"}}[5m]) + rate(pg_doorman_auth_query_cache_total{{type=\"misses\"}}[5m]), 0.001)\n```\n");
 
@@ -1,5 +1,6 @@
 pub mod annotated;
 pub mod docs;
+pub mod pgbouncer;
 
 use std::error::Error;
 
@@ -112,6 +113,10 @@ pub fn generate_config_with_client(
                 server_username: None,
                 server_password: None,
                 auth_pam_service: None,
+                connect_queries: None,
+                priority: None,
+                server_role: None,
+                max_concurrent_transactions: None,
             };
             users.push(user);
         }
@@ -139,8 +144,43 @@ pub fn generate_config_with_client(
                     connect_timeout: None,
                     idle_timeout: None,
                     server_lifetime: None,
+                    client_idle_timeout: None,
+                    max_query_duration: None,
+                    max_result_rows: None,
+                    server_max_age: None,
+                    ignore_startup_parameters: None,
+                    parameter_status_overrides: std::collections::BTreeMap::new(),
+                    shards: std::collections::BTreeMap::new(),
+                    hosts: Vec::new(),
+                    message_size_to_be_stream: None,
                     cleanup_server_connections: false,
+                    server_cleanup_mode: None,
+                    server_cleanup_query: None,
+                    connect_queries: Vec::new(),
+                    server_statement_timeout_ms: None,
+                    server_lock_timeout_ms: None,
+                    default_client_statement_timeout_ms: None,
+                    server_tcp_keepalives_idle: None,
+                    server_tcp_keepalives_interval: None,
+                    server_tcp_keepalives_count: None,
+                    server_tcp_user_timeout: None,
+                    max_concurrent_creates: None,
+                    max_concurrent_queries: None,
+                    auto_pause_on_unhealthy: false,
+                    auto_pause_failure_threshold:
+                        crate::config::Pool::default_auto_pause_failure_threshold(),
+                    auto_pause_success_threshold:
+                        crate::config::Pool::default_auto_pause_success_threshold(),
                     log_client_parameter_status_changes: false,
+                    log_startup_parameters: false,
+                    share_server_connections: false,
+                    read_only: false,
+                    reject_write_queries: false,
+                    retry_readonly_on_disconnect: false,
+                    prefer_last_server: false,
+                    allow_session_override: false,
+                    scram_relay: false,
+                    client_label_gucs: false,
                     application_name: None,
                     server_host: config
                         .server_host
@@ -149,10 +189,14 @@ pub fn generate_config_with_client(
                         .to_string(),
                     server_port: config.port,
                     server_database: Some(datname.to_string()),
+                    server_username: None,
+                    server_password: None,
+                    prepared_statements: None,
                     prepared_statements_cache_size: None,
                     server_prepared_statements_cache_size: None,
                     scaling_warm_pool_ratio: None,
                     scaling_fast_retries: None,
+                    scaling_shrink_step: None,
                     max_db_connections: None,
                     min_connection_lifetime: None,
                     reserve_pool_size: None,
@@ -0,0 +1,444 @@
+//! `generate --from-pgbouncer`: converts a `pgbouncer.ini` and its
+//! `userlist.txt` into a pg_doorman config.
+//!
+//! The mapping is deliberately conservative. Settings with a direct
+//! equivalent are carried over; everything else becomes a warning that the
+//! caller prints as a comment at the top of the generated file, so nothing
+//! from the original config is dropped silently.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::Path;
+
+use crate::auth::hba::PgHba;
+use crate::config::{Config, Duration, Pool, PoolMode, User};
+
+/// Pool size PgBouncer uses when `default_pool_size` is not set.
+const PGBOUNCER_DEFAULT_POOL_SIZE: u32 = 20;
+
+/// Result of an import: the config plus everything that could not be mapped.
+#[derive(Debug)]
+pub struct PgBouncerImport {
+    pub config: Config,
+    pub warnings: Vec<String>,
+}
+
+/// One `[section]` of an ini file, keys in file order.
+type IniSection = Vec<(String, String)>;
+
+/// Reads `ini_path` and the userlist (`userlist_path`, or `auth_file` from
+/// the ini resolved against the ini's directory) and converts them.
+pub fn import_pgbouncer(
+    ini_path: &str,
+    userlist_path: Option<&str>,
+) -> Result<PgBouncerImport, Box<dyn Error>> {
+    let ini = std::fs::read_to_string(ini_path)
+        .map_err(|err| format!("failed to read {ini_path}: {err}"))?;
+    let sections = parse_ini(&ini);
+
+    let userlist_path = userlist_path.map(str::to_string).or_else(|| {
+        let auth_file = setting(&sections, "pgbouncer", "auth_file")?;
+        let base = Path::new(ini_path).parent().unwrap_or(Path::new(""));
+        Some(base.join(auth_file).to_string_lossy().into_owned())
+    });
+    let userlist = match userlist_path {
+        Some(path) => {
+            let content = std::fs::read_to_string(&path)
+                .map_err(|err| format!("failed to read {path}: {err}"))?;
+            parse_userlist(&content)
+        }
+        None => Vec::new(),
+    };
+
+    Ok(convert(&sections, &userlist))
+}
+
+/// Splits ini content into sections. Comments start with `;` or `#`; keys
+/// are lowercased, as PgBouncer treats them case-insensitively.
+pub fn parse_ini(content: &str) -> BTreeMap<String, IniSection> {
+    let mut sections: BTreeMap<String, IniSection> = BTreeMap::new();
+    let mut current = String::new();
+    for raw in content.lines() {
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            current = name.trim().to_ascii_lowercase();
+            sections.entry(current.clone()).or_default();
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            let key = key.trim();
+            // Database names are case-sensitive; setting names are not.
+            let key = if current == "databases" || current == "users" {
+                key.to_string()
+            } else {
+                key.to_ascii_lowercase()
+            };
+            sections
+                .entry(current.clone())
+                .or_default()
+                .push((key, value.trim().to_string()));
+        }
+    }
+    sections
+}
+
+/// Parses `"user" "password"` lines of a PgBouncer `auth_file`.
+pub fn parse_userlist(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+                return None;
+            }
+            let mut fields = quoted_fields(line).into_iter();
+            Some((fields.next()?, fields.next().unwrap_or_default()))
+        })
+        .collect()
+}
+
+/// Double-quoted fields of a userlist line; `""` inside a field is a quote.
+fn quoted_fields(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '"' {
+            continue;
+        }
+        let mut field = String::new();
+        while let Some(c) = chars.next() {
+            if c == '"' {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                    continue;
+                }
+                break;
+            }
+            field.push(c);
+        }
+        fields.push(field);
+    }
+    fields
+}
+
+/// Parses a `[databases]` connection string: `key=value` pairs separated by
+/// spaces, values optionally in single quotes.
+pub fn parse_connstr(value: &str) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    let mut chars = value.chars().peekable();
+    loop {
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+        let mut key = String::new();
+        for c in chars.by_ref() {
+            if c == '=' {
+                break;
+            }
+            key.push(c);
+        }
+        if key.is_empty() {
+            break;
+        }
+        let mut val = String::new();
+        if chars.peek() == Some(&'\'') {
+            chars.next();
+            while let Some(c) = chars.next() {
+                if c == '\'' {
+                    if chars.peek() == Some(&'\'') {
+                        chars.next();
+                        val.push('\'');
+                        continue;
+                    }
+                    break;
+                }
+                val.push(c);
+            }
+        } else {
+            while let Some(c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                val.push(*c);
+                chars.next();
+            }
+        }
+        pairs.push((key.trim().to_ascii_lowercase(), val));
+    }
+    pairs
+}
+
+fn setting<'a>(
+    sections: &'a BTreeMap<String, IniSection>,
+    section: &str,
+    key: &str,
+) -> Option<&'a str> {
+    sections
+        .get(section)?
+        .iter()
+        .rev()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.as_str())
+}
+
+fn parse_pool_mode(value: &str) -> Option<PoolMode> {
+    match value.to_ascii_lowercase().as_str() {
+        "session" => Some(PoolMode::Session),
+        "transaction" => Some(PoolMode::Transaction),
+        _ => None,
+    }
+}
+
+/// PgBouncer durations are seconds, possibly fractional.
+fn parse_seconds(value: &str) -> Option<Duration> {
+    let seconds: f64 = value.parse().ok()?;
+    (seconds >= 0.0).then(|| Duration::from_millis((seconds * 1000.0) as u64))
+}
+
+/// Converts parsed ini sections and userlist entries into a pg_doorman
+/// config.
+pub fn convert(
+    sections: &BTreeMap<String, IniSection>,
+    userlist: &[(String, String)],
+) -> PgBouncerImport {
+    let mut config = Config::default();
+    let mut warnings = Vec::new();
+    config.path = String::new();
+
+    let mut default_pool_mode = PoolMode::Session;
+    let mut default_pool_size = PGBOUNCER_DEFAULT_POOL_SIZE;
+    let mut hba_rules: Option<String> = None;
+
+    for (key, value) in sections.get("pgbouncer").into_iter().flatten() {
+        let mut unsupported = false;
+        match key.as_str() {
+            "listen_addr" => {
+                config.general.host = match value.as_str() {
+                    "*" => "0.0.0.0".to_string(),
+                    other => other.split(',').next().unwrap_or(other).trim().to_string(),
+                };
+                if value.contains(',') {
+                    warnings.push(format!(
+                        "listen_addr = {value}: pg_doorman listens on one address, using {}",
+                        config.general.host
+                    ));
+                }
+            }
+            "listen_port" => match value.parse() {
+                Ok(port) => config.general.port = port,
+                Err(_) => unsupported = true,
+            },
+            "pool_mode" => match parse_pool_mode(value) {
+                Some(mode) => default_pool_mode = mode,
+                None => unsupported = true,
+            },
+            "default_pool_size" => match value.parse() {
+                Ok(size) => default_pool_size = size,
+                Err(_) => unsupported = true,
+            },
+            "max_client_conn" => match value.parse() {
+                Ok(max) => config.general.max_connections = max,
+                Err(_) => unsupported = true,
+            },
+            "server_lifetime" => match parse_seconds(value) {
+                Some(d) => config.general.server_lifetime = d,
+                None => unsupported = true,
+            },
+            "server_idle_timeout" => match parse_seconds(value) {
+                Some(d) => config.general.idle_timeout = d,
+                None => unsupported = true,
+            },
+            "query_wait_timeout" => match parse_seconds(value) {
+                Some(d) => config.general.query_wait_timeout = d,
+                None => unsupported = true,
+            },
+            "server_connect_timeout" => match parse_seconds(value) {
+                Some(d) => config.general.connect_timeout = d,
+                None => unsupported = true,
+            },
+            "unix_socket_dir" => {
+                config.general.unix_socket_dir = (!value.is_empty()).then(|| value.clone());
+            }
+            "client_tls_sslmode" => config.general.tls_mode = Some(value.clone()),
+            "client_tls_cert_file" => config.general.tls_certificate = Some(value.clone()),
+            "client_tls_key_file" => config.general.tls_private_key = Some(value.clone()),
+            "server_tls_sslmode" => config.general.server_tls_mode = value.clone(),
+            "auth_file" => {}
+            "auth_hba_file" => match PgHba::from_path(value) {
+                Ok(hba) => config.general.pg_hba = Some(hba),
+                Err(err) => warnings.push(format!(
+                    "auth_hba_file = {value}: could not read it ({err}); set general.pg_hba by hand"
+                )),
+            },
+            "auth_type" => match value.as_str() {
+                // pg_doorman picks MD5 or SCRAM from the stored password.
+                "md5" | "scram-sha-256" | "hba" => {}
+                "trust" | "any" => {
+                    hba_rules = Some(
+                        "host all all 0.0.0.0/0 trust\nhost all all ::/0 trust\nlocal all all trust"
+                            .to_string(),
+                    );
+                }
+                _ => unsupported = true,
+            },
+            "admin_users" => {
+                if let Some(admin) = value.split(',').map(str::trim).find(|a| !a.is_empty()) {
+                    config.general.admin_username = admin.to_string();
+                    match userlist.iter().find(|(user, _)| user == admin) {
+                        Some((_, password)) if !password.is_empty() => {
+                            config.general.admin_password = password.clone()
+                        }
+                        _ => warnings.push(format!(
+                            "admin_users = {value}: no password for {admin} in the userlist; set general.admin_password"
+                        )),
+                    }
+                    if value.contains(',') {
+                        warnings.push(format!(
+                            "admin_users = {value}: pg_doorman has a single admin user, using {admin}"
+                        ));
+                    }
+                }
+            }
+            _ => unsupported = true,
+        }
+        if unsupported {
+            warnings.push(format!(
+                "[pgbouncer] {key} = {value}: no pg_doorman equivalent, not imported"
+            ));
+        }
+    }
+    if let Some(rules) = hba_rules {
+        if config.general.pg_hba.is_none() {
+            config.general.pg_hba = Some(PgHba::from_content(&rules));
+        }
+    }
+
+    // `[users]` only carries per-user pool_mode for us.
+    let mut user_pool_modes: BTreeMap<&str, PoolMode> = BTreeMap::new();
+    for (user, value) in sections.get("users").into_iter().flatten() {
+        for (key, val) in parse_connstr(value) {
+            match (key.as_str(), parse_pool_mode(&val)) {
+                ("pool_mode", Some(mode)) => {
+                    user_pool_modes.insert(user, mode);
+                }
+                _ => warnings.push(format!(
+                    "[users] {user}: {key}={val} has no pg_doorman equivalent, not imported"
+                )),
+            }
+        }
+    }
+
+    if userlist.is_empty() {
+        warnings.push(
+            "no userlist entries found: pools have no users; add them or use auth_query"
+                .to_string(),
+        );
+    }
+
+    for (name, value) in sections.get("databases").into_iter().flatten() {
+        if name == "*" {
+            warnings.push(format!(
+                "[databases] * = {value}: fallback databases are not supported, declare each database"
+            ));
+            continue;
+        }
+        let mut pool = Pool {
+            pool_mode: default_pool_mode,
+            ..Default::default()
+        };
+        let mut pool_size = default_pool_size;
+        let mut forced_user: Option<(String, Option<String>)> = None;
+        for (key, val) in parse_connstr(value) {
+            let mut unsupported = false;
+            match key.as_str() {
+                "host" => pool.server_host = val,
+                "port" => match val.parse() {
+                    Ok(port) => pool.server_port = port,
+                    Err(_) => unsupported = true,
+                },
+                "dbname" => {
+                    if val != *name {
+                        pool.server_database = Some(val);
+                    }
+                }
+                "pool_size" => match val.parse() {
+                    Ok(size) => pool_size = size,
+                    Err(_) => unsupported = true,
+                },
+                "pool_mode" => match parse_pool_mode(&val) {
+                    Some(mode) => pool.pool_mode = mode,
+                    None => unsupported = true,
+                },
+                "max_db_connections" => match val.parse() {
+                    Ok(max) => pool.max_db_connections = Some(max),
+                    Err(_) => unsupported = true,
+                },
+                "reserve_pool" => match val.parse() {
+                    Ok(size) => pool.reserve_pool_size = Some(size),
+                    Err(_) => unsupported = true,
+                },
+                "application_name" => pool.application_name = Some(val),
+                "user" => {
+                    let password = forced_user.take().and_then(|(_, p)| p);
+                    forced_user = Some((val, password));
+                }
+                "password" => {
+                    let user = forced_user.take().map(|(u, _)| u).unwrap_or_default();
+                    forced_user = Some((user, Some(val)));
+                }
+                "auth_user" => warnings.push(format!(
+                    "[databases] {name}: auth_user={val} is not imported; configure auth_query for this pool"
+                )),
+                _ => unsupported = true,
+            }
+            if unsupported {
+                warnings.push(format!(
+                    "[databases] {name}: {key}={val} has no pg_doorman equivalent, not imported"
+                ));
+            }
+        }
+        if let Some((ref user, None)) = forced_user {
+            warnings.push(format!(
+                "[databases] {name}: user={user} has no password; set server_password for its users"
+            ));
+        }
+        pool.users = userlist
+            .iter()
+            .map(|(username, password)| User {
+                username: username.clone(),
+                password: password.clone(),
+                pool_size,
+                pool_mode: user_pool_modes.get(username.as_str()).copied(),
+                server_username: forced_user
+                    .as_ref()
+                    .map(|(u, _)| u.clone())
+                    .filter(|u| !u.is_empty()),
+                server_password: forced_user.as_ref().and_then(|(_, p)| p.clone()),
+                ..Default::default()
+            })
+            .collect();
+        config.pools.insert(name.clone(), pool);
+    }
+
+    PgBouncerImport { config, warnings }
+}
+
+/// Prefixes generated config text with the import warnings as comments.
+pub fn with_warnings(data: String, warnings: &[String]) -> String {
+    if warnings.is_empty() {
+        return data;
+    }
+    let mut out = String::from("# Imported from PgBouncer. Settings that were not carried over:\n");
+    for warning in warnings {
+        out.push_str("# WARNING: ");
+        out.push_str(warning);
+        out.push('\n');
+    }
+    out.push('\n');
+    out.push_str(&data);
+    out
+}
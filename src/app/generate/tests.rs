@@ -36,6 +36,10 @@ pub fn generate_config_with_client<
                     server_username: None,
                     server_password: None,
                     auth_pam_service: None,
+                    connect_queries: None,
+                    priority: None,
+                    server_role: None,
+                    max_concurrent_transactions: None,
                 };
                 users_vec.push(user);
             }
@@ -62,8 +66,43 @@ pub fn generate_config_with_client<
                         connect_timeout: None,
                         idle_timeout: None,
                         server_lifetime: None,
+                        client_idle_timeout: None,
+                        max_query_duration: None,
+                        max_result_rows: None,
+                        server_max_age: None,
+                        ignore_startup_parameters: None,
+                        parameter_status_overrides: std::collections::BTreeMap::new(),
+                        shards: std::collections::BTreeMap::new(),
+                        hosts: Vec::new(),
+                        message_size_to_be_stream: None,
                         cleanup_server_connections: false,
+                        server_cleanup_mode: None,
+                        server_cleanup_query: None,
+                        connect_queries: Vec::new(),
+                        server_statement_timeout_ms: None,
+                        server_lock_timeout_ms: None,
+                        default_client_statement_timeout_ms: None,
+                        server_tcp_keepalives_idle: None,
+                        server_tcp_keepalives_interval: None,
+                        server_tcp_keepalives_count: None,
+                        server_tcp_user_timeout: None,
+                        max_concurrent_creates: None,
+                        max_concurrent_queries: None,
+                        auto_pause_on_unhealthy: false,
+                        auto_pause_failure_threshold:
+                            crate::config::Pool::default_auto_pause_failure_threshold(),
+                        auto_pause_success_threshold:
+                            crate::config::Pool::default_auto_pause_success_threshold(),
                         log_client_parameter_status_changes: false,
+                        log_startup_parameters: false,
+                        share_server_connections: false,
+                        read_only: false,
+                        reject_write_queries: false,
+                        retry_readonly_on_disconnect: false,
+                        prefer_last_server: false,
+                        allow_session_override: false,
+                        scram_relay: false,
+                        client_label_gucs: false,
                         application_name: None,
                         server_host: config
                             .server_host
@@ -72,10 +111,14 @@ pub fn generate_config_with_client<
                             .to_string(),
                         server_port: config.port,
                         server_database: Some(db_name.to_string()),
+                        server_username: None,
+                        server_password: None,
+                        prepared_statements: None,
                         prepared_statements_cache_size: None,
                         server_prepared_statements_cache_size: None,
                         scaling_warm_pool_ratio: None,
                         scaling_fast_retries: None,
+                        scaling_shrink_step: None,
                         max_db_connections: None,
                         min_connection_lifetime: None,
                         reserve_pool_size: None,
@@ -122,6 +165,8 @@ fn test_generate_config_with_default_parameters() {
         reference: false,
         russian_comments: false,
         format: None,
+        from_pgbouncer: None,
+        userlist: None,
     };
 
     // Create mock data
@@ -187,6 +232,8 @@ fn test_generate_config_with_custom_parameters() {
         reference: false,
         russian_comments: false,
         format: None,
+        from_pgbouncer: None,
+        userlist: None,
     };
 
     // Create mock data
@@ -248,6 +295,8 @@ fn test_generate_config_with_ssl_enabled() {
         reference: false,
         russian_comments: false,
         format: None,
+        from_pgbouncer: None,
+        userlist: None,
     };
 
     // Create mock data
@@ -290,6 +339,8 @@ fn test_generate_config_with_database_error() {
         reference: false,
         russian_comments: false,
         format: None,
+        from_pgbouncer: None,
+        userlist: None,
     };
 
     // Create a simple error type for testing
@@ -318,3 +369,80 @@ fn test_generate_config_with_database_error() {
 
     assert!(result.is_err());
 }
+
+#[test]
+fn test_import_pgbouncer_maps_settings_and_warns_on_the_rest() {
+    use super::pgbouncer::{convert, parse_connstr, parse_ini, parse_userlist};
+
+    let ini = parse_ini(
+        r#"
+[databases]
+app = host=10.0.0.5 port=5433 dbname=app_prod pool_size=15
+reports = host=10.0.0.6 user=reporter password='s3cr''et' connect_query='SELECT 1'
+* = host=10.0.0.9
+
+[users]
+batch = pool_mode=session
+
+[pgbouncer]
+listen_addr = *
+listen_port = 6543
+pool_mode = transaction
+default_pool_size = 30
+max_client_conn = 2000
+server_lifetime = 1800
+query_wait_timeout = 2.5
+auth_type = md5
+auth_file = userlist.txt
+admin_users = pgb_admin
+stats_period = 60
+"#,
+    );
+    let userlist = parse_userlist(
+        "\"batch\" \"md5aaaa\"\n; comment\n\"web\" \"SCRAM-SHA-256$4096:x\"\n\"pgb_admin\" \"adminpw\"\n",
+    );
+    assert_eq!(userlist.len(), 3);
+    assert_eq!(
+        parse_connstr("user=a password='x y'"),
+        vec![
+            ("user".to_string(), "a".to_string()),
+            ("password".to_string(), "x y".to_string())
+        ]
+    );
+
+    let import = convert(&ini, &userlist);
+    let general = &import.config.general;
+    assert_eq!(general.host, "0.0.0.0");
+    assert_eq!(general.port, 6543);
+    assert_eq!(general.max_connections, 2000);
+    assert_eq!(general.server_lifetime.as_millis(), 1_800_000);
+    assert_eq!(general.query_wait_timeout.as_millis(), 2_500);
+    assert_eq!(general.admin_username, "pgb_admin");
+    assert_eq!(general.admin_password, "adminpw");
+
+    let app = &import.config.pools["app"];
+    assert_eq!(app.pool_mode, PoolMode::Transaction);
+    assert_eq!(app.server_host, "10.0.0.5");
+    assert_eq!(app.server_port, 5433);
+    assert_eq!(app.server_database.as_deref(), Some("app_prod"));
+    assert_eq!(app.users.len(), 3);
+    assert!(app.users.iter().all(|u| u.pool_size == 15));
+    let batch = app.users.iter().find(|u| u.username == "batch").unwrap();
+    assert_eq!(batch.pool_mode, Some(PoolMode::Session));
+
+    let reports = &import.config.pools["reports"];
+    assert_eq!(reports.users[0].pool_size, 30);
+    assert_eq!(
+        reports.users[0].server_username.as_deref(),
+        Some("reporter")
+    );
+    assert_eq!(reports.users[0].server_password.as_deref(), Some("s3cr'et"));
+    assert!(!import.config.pools.contains_key("*"));
+
+    let warned = |needle: &str| import.warnings.iter().any(|w| w.contains(needle));
+    assert!(warned("stats_period"));
+    assert!(warned("connect_query"));
+    assert!(warned("[databases] *"));
+    assert!(!warned("auth_type"));
+    assert!(!warned("auth_file"));
+}
@@ -1,5 +1,8 @@
+pub mod admission;
 pub mod args;
+pub mod client_notice;
 pub mod config;
+pub mod drain;
 pub mod errors;
 pub mod generate;
 pub mod log_level;
@@ -41,6 +44,19 @@ pub fn parse_args() -> Result<Args, Box<dyn std::error::Error>> {
             let data = if config.reference {
                 // --reference: generate reference config with example data, no PG connection needed
                 generate::annotated::generate_reference_config(format, russian)
+            } else if let Some(ref ini_path) = config.from_pgbouncer {
+                // --from-pgbouncer: convert pgbouncer.ini + userlist.txt, no PG connection needed
+                let import =
+                    generate::pgbouncer::import_pgbouncer(ini_path, config.userlist.as_deref())?;
+                let data = if config.no_comments {
+                    match format {
+                        ConfigFormat::Yaml => serde_yaml::to_string(&import.config)?,
+                        ConfigFormat::Toml => toml::to_string_pretty(&import.config)?,
+                    }
+                } else {
+                    generate::annotated::generate_annotated_config(&import.config, format, russian)
+                };
+                generate::pgbouncer::with_warnings(data, &import.warnings)
             } else {
                 // Connect to PG and generate config
                 let pg_doorman_config = generate::generate_config(config)?;
@@ -91,6 +107,25 @@ pub fn parse_args() -> Result<Args, Box<dyn std::error::Error>> {
             }
             std::process::exit(0);
         }
+        Some(Commands::Validate { config }) => {
+            let path = config.as_deref().unwrap_or(&cli.config_file);
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?;
+            match runtime.block_on(crate::config::check(path)) {
+                Ok(config) => {
+                    println!(
+                        "pg_doorman: configuration file {path} is valid ({} pools)",
+                        config.pools.len()
+                    );
+                    std::process::exit(0);
+                }
+                Err(err) => {
+                    eprintln!("pg_doorman: configuration file {path} is invalid: {err}");
+                    std::process::exit(1);
+                }
+            }
+        }
         None => (),
     }
 
@@ -1,10 +1,11 @@
-use std::net::ToSocketAddrs;
+use std::net::{SocketAddr, ToSocketAddrs};
 use std::process;
 use std::sync::atomic::{AtomicBool, AtomicI64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
 use chrono::Utc;
+use futures::FutureExt;
 use log::{debug, error, info, warn};
 use tokio::io::AsyncWriteExt;
 use tokio::net::TcpSocket;
@@ -15,6 +16,7 @@ use tokio::signal::windows as win_signal;
 use tokio::{runtime::Builder, sync::mpsc};
 
 use crate::app::args::Args;
+use crate::app::{admission, drain};
 use crate::config::{get_config, reload_config, Config};
 use crate::daemon;
 use crate::messages::{configure_tcp_socket, configure_unix_socket};
@@ -45,6 +47,10 @@ static ACCEPT_RESOURCE_LOG_LAST: AtomicI64 = AtomicI64::new(0);
 /// Global flag indicating graceful shutdown is in progress
 pub static SHUTDOWN_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
 
+/// Whether tokio workers were pinned to cores. `worker_cpu_affinity_pinning`
+/// is ignored on hosts with fewer than 3 cores; `SHOW WORKERS` reports this.
+pub static WORKER_CPU_AFFINITY_PINNED: AtomicBool = AtomicBool::new(false);
+
 /// Global counter for clients currently in transactions (holding server connections)
 pub static CLIENTS_IN_TRANSACTIONS: AtomicI64 = AtomicI64::new(0);
 
@@ -241,6 +247,140 @@ fn fd_cleanup_upper_bound() -> libc::c_int {
     65_536
 }
 
+/// Listen backlog: `backlog` when set, otherwise `max_connections`.
+fn listen_backlog(config: &Config) -> u32 {
+    if config.general.backlog > 0 {
+        config.general.backlog
+    } else {
+        config.general.max_connections as u32
+    }
+}
+
+/// Creates, configures and binds the client TCP listener. Used at startup
+/// and again by UNDRAIN.
+fn bind_listener(addr: SocketAddr, backlog: u32) -> std::io::Result<tokio::net::TcpListener> {
+    let listen_socket = if addr.is_ipv4() {
+        TcpSocket::new_v4()?
+    } else {
+        TcpSocket::new_v6()?
+    };
+    listen_socket.set_reuseaddr(true)?;
+    listen_socket.set_reuseport(true)?;
+    listen_socket.set_nodelay(true)?;
+    #[cfg(not(windows))]
+    SockRef::from(&listen_socket).set_linger(Some(Duration::from_secs(0)))?;
+    #[cfg(windows)]
+    listen_socket.set_linger(Some(Duration::from_secs(0)))?;
+    // IPTOS_LOWDELAY: u8 = 0x10;
+    #[cfg(not(windows))]
+    if addr.is_ipv4() {
+        if let Err(err) = listen_socket.set_tos_v4(0x10) {
+            warn!("Failed to set IPTOS_LOWDELAY on listener socket: {err}");
+        }
+    }
+    listen_socket.bind(addr)?;
+    listen_socket.listen(backlog)
+}
+
+fn bind_listener_or_exit(addr: SocketAddr, config: &Config) -> tokio::net::TcpListener {
+    match bind_listener(addr, listen_backlog(config)) {
+        Ok(listener) => listener,
+        Err(err) => {
+            error!("Listener socket error: {err}");
+            std::process::exit(exitcode::CONFIG);
+        }
+    }
+}
+
+fn resolve_listen_addr_or_exit(host: &str, port: u16) -> SocketAddr {
+    match format!("{host}:{port}")
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+    {
+        Some(addr) => addr,
+        None => {
+            error!("Listener address {host}:{port} cannot be resolved");
+            std::process::exit(exitcode::CONFIG);
+        }
+    }
+}
+
+/// Binds `general.admin_listen_port`, the listener that serves only the
+/// admin console.
+fn bind_admin_listener(config: &Config) -> Option<tokio::net::TcpListener> {
+    let port = config.general.admin_listen_port?;
+    let host = config
+        .general
+        .admin_listen_host
+        .as_deref()
+        .unwrap_or(&config.general.host);
+    let addr = resolve_listen_addr_or_exit(host, port);
+    let listener = bind_listener_or_exit(addr, config);
+    info!("Running admin console on {addr}");
+    Some(listener)
+}
+
+/// One of `general.listeners`. `listener` is `None` while drained.
+struct ExtraListener {
+    addr: SocketAddr,
+    default_database: Option<String>,
+    listener: Option<tokio::net::TcpListener>,
+}
+
+/// Binds every `general.listeners` entry at startup; a bind failure exits
+/// like one of the main listener.
+fn bind_extra_listeners(config: &Config) -> Vec<ExtraListener> {
+    config
+        .general
+        .listeners
+        .iter()
+        .map(|extra| {
+            let host = extra.host.as_deref().unwrap_or(&config.general.host);
+            let addr = resolve_listen_addr_or_exit(host, extra.port);
+            let listener = bind_listener_or_exit(addr, config);
+            match &extra.default_database {
+                Some(database) => info!("Running on {addr} (default database \"{database}\")"),
+                None => info!("Running on {addr}"),
+            }
+            ExtraListener {
+                addr,
+                default_database: extra.default_database.clone(),
+                listener: Some(listener),
+            }
+        })
+        .collect()
+}
+
+/// Accepts on the main listener and every open extra listener. Returns the
+/// accepting listener's default database along with the accept result.
+async fn accept_any(
+    listener: &Option<tokio::net::TcpListener>,
+    extra_listeners: &[ExtraListener],
+) -> (
+    std::io::Result<(tokio::net::TcpStream, SocketAddr)>,
+    Option<String>,
+) {
+    let mut accepts: Vec<_> = extra_listeners
+        .iter()
+        .filter_map(|extra| {
+            let l = extra.listener.as_ref()?;
+            Some(async move { (l.accept().await, extra.default_database.clone()) }.boxed())
+        })
+        .collect();
+    if let Some(l) = listener {
+        if accepts.is_empty() {
+            return (l.accept().await, None);
+        }
+        accepts.push(async move { (l.accept().await, None) }.boxed());
+    }
+    if accepts.is_empty() {
+        // Every listener was dropped (DRAIN or foreground binary upgrade).
+        return std::future::pending().await;
+    }
+    futures::future::select_all(accepts).await.0
+}
+
 /// Rate-limit accept-loop fd-exhaustion logs without moving the window
 /// on suppressed attempts.
 fn should_log_accept_resource_now() -> bool {
@@ -285,6 +425,7 @@ pub fn run_server(args: Args, config: Config) -> Result<(), Box<dyn std::error::
     if core_ids.len() < 3 {
         worker_cpu_affinity_pinning = false
     }
+    WORKER_CPU_AFFINITY_PINNED.store(worker_cpu_affinity_pinning, Ordering::Relaxed);
     if worker_cpu_affinity_pinning {
         core_affinity::set_for_current(core_ids[thread_id.fetch_add(1, Ordering::SeqCst)]);
     }
@@ -347,84 +488,11 @@ pub fn run_server(args: Args, config: Config) -> Result<(), Box<dyn std::error::
             std_listener.set_nonblocking(true).expect("can't set nonblocking");
             tokio::net::TcpListener::from_std(std_listener).expect("can't create TcpListener from inherited fd")
         } else {
-            // Create new listener
-            let listen_socket = if addr.is_ipv4() {
-                TcpSocket::new_v4().unwrap()
-            } else {
-                TcpSocket::new_v6().unwrap()
-            };
-            listen_socket
-                .set_reuseaddr(true)
-                .expect("can't set reuseaddr");
-            listen_socket
-                .set_reuseport(true)
-                .expect("can't set reuseport");
-            listen_socket
-                .set_nodelay(true)
-                .expect("can't set nodelay");
-            {
-                let sock_ref = SockRef::from(&listen_socket);
-                sock_ref.set_linger(Some(Duration::from_secs(0)))
-                    .expect("could not configure tcp_so_linger for socket");
-            }
-            // IPTOS_LOWDELAY: u8 = 0x10;
-            if addr.is_ipv4() {
-                match listen_socket.set_tos_v4(0x10) {
-                    Ok(_) => (),
-                    Err(err) => {
-                        warn!("Failed to set IPTOS_LOWDELAY on listener socket: {err}");
-                    }
-                };
-            };
-            listen_socket.bind(addr).expect("can't bind");
-            // end configure listener.
-            let backlog = if config.general.backlog > 0 {
-                config.general.backlog
-            } else {
-                config.general.max_connections as u32
-            };
-            match listen_socket.listen(backlog) {
-                Ok(sock) => sock,
-                Err(err) => {
-                    error!("Listener socket error: {err}");
-                    std::process::exit(exitcode::CONFIG);
-                }
-            }
+            bind_listener_or_exit(addr, &config)
         };
 
         #[cfg(windows)]
-        let listener = {
-            let listen_socket = if addr.is_ipv4() {
-                TcpSocket::new_v4().unwrap()
-            } else {
-                TcpSocket::new_v6().unwrap()
-            };
-            listen_socket
-                .set_reuseaddr(true)
-                .expect("can't set reuseaddr");
-            listen_socket
-                .set_reuseport(true)
-                .expect("can't set reuseport");
-            listen_socket
-                .set_nodelay(true)
-                .expect("can't set nodelay");
-            listen_socket
-                .set_linger(Some(Duration::from_secs(0)))
-                .expect("can't set linger 0");
-            listen_socket.bind(addr).expect("can't bind");
-            let backlog = if config.general.backlog > 0 {
-                config.general.backlog
-            } else {
-                config.general.max_connections as u32
-            };
-            match listen_socket.listen(backlog) {
-                Ok(sock) => sock,
-                Err(err) => {
-                    error!("Listener socket error: {err}");
-                    std::process::exit(exitcode::CONFIG);
-                }
-            }
-        };
+        let listener = bind_listener_or_exit(addr, &config);
 
         info!("Running on {addr}");
 
@@ -608,6 +676,7 @@ pub fn run_server(args: Args, config: Config) -> Result<(), Box<dyn std::error::
         let shutdown_timeout = config.general.shutdown_timeout.as_std();
 
         // Web listener (Prometheus exporter + optional UI)
+        let mut web_unix_socket_ownership = None;
         if config.web.enabled {
             // Build the snapshot through `from_config` so the SSO runtime
             // (and any future config-derived fields) populate on cold
@@ -633,6 +702,29 @@ pub fn run_server(args: Args, config: Config) -> Result<(), Box<dyn std::error::
                     std::process::exit(exitcode::OSERR);
                 }
             };
+            // Optional Unix socket for sidecar scrapers. It shares the
+            // pooler socket's bring-up (stale-file check, umask clamp,
+            // `unix_socket_mode`) and its inode-checked cleanup on shutdown.
+            if let Some(ref socket_path) = config.web.unix_socket {
+                let path = socket_path.display().to_string();
+                let mode =
+                    crate::config::General::parse_unix_socket_mode(&config.general.unix_socket_mode)
+                        .expect("unix_socket_mode validated at config load");
+                match create_unix_listener(&path, mode) {
+                    Ok((listener, ownership)) => {
+                        info!("web listener bound on Unix socket {path} (mode={mode:#o})");
+                        web_unix_socket_ownership = Some(ownership);
+                        let opts = opts.clone();
+                        tokio::task::spawn(async move {
+                            crate::web::serve_unix_on(listener, opts).await;
+                        });
+                    }
+                    Err(err) => {
+                        error!("web listener: {err}");
+                        std::process::exit(exitcode::OSERR);
+                    }
+                }
+            }
             tokio::task::spawn(async move {
                 crate::web::serve_on(web_listener, opts).await;
             });
@@ -722,6 +814,9 @@ pub fn run_server(args: Args, config: Config) -> Result<(), Box<dyn std::error::
         // Wrap listener in Option to allow dropping it during foreground binary upgrade
         // while still continuing the graceful shutdown process
         let mut listener = Some(listener);
+        let mut extra_listeners = bind_extra_listeners(&config);
+        // Stays open through DRAIN so the node can be undrained remotely.
+        let mut admin_listener = bind_admin_listener(&config);
 
         info!("Accepting connections");
 
@@ -743,15 +838,7 @@ pub fn run_server(args: Args, config: Config) -> Result<(), Box<dyn std::error::
                 }
             };
 
-            // Create accept future only if listener is available
-            let accept_future = async {
-                if let Some(ref l) = listener {
-                    l.accept().await
-                } else {
-                    // Listener was dropped (foreground binary upgrade), wait forever
-                    std::future::pending().await
-                }
-            };
+            let accept_future = accept_any(&listener, &extra_listeners);
 
             tokio::select! {
 
@@ -760,10 +847,10 @@ pub fn run_server(args: Args, config: Config) -> Result<(), Box<dyn std::error::
                 _ = sighup_signal.recv() => {
                     info!("Reloading config");
                     match reload_config(client_server_map.clone()).await {
-                        Ok(true) => {
+                        Ok(diff) if !diff.is_empty() => {
                             crate::admin::events::push_event("RELOAD", "config reloaded (SIGHUP)".to_string());
                         }
-                        Ok(false) => {
+                        Ok(_) => {
                             // No-op reload — file re-parsed identically. Still
                             // emit a RELOAD entry with "config unchanged" so
                             // audit-driven SIGHUP'ing leaves a trace; one
@@ -789,6 +876,9 @@ pub fn run_server(args: Args, config: Config) -> Result<(), Box<dyn std::error::
                         // Foreground + TTY: graceful shutdown only (no binary upgrade)
                         info!("Got SIGINT (Ctrl+C), starting graceful shutdown");
                         SHUTDOWN_IN_PROGRESS.store(true, Ordering::SeqCst);
+                        crate::app::client_notice::announce(
+                            crate::app::client_notice::SHUTDOWN_NOTICE,
+                        );
                         retain::drain_all_pools();
                         if admin_only { continue; }
                         admin_only = true;
@@ -801,12 +891,15 @@ pub fn run_server(args: Args, config: Config) -> Result<(), Box<dyn std::error::
                     {
                         info!("Got SIGINT, starting binary upgrade and graceful shutdown");
                         match binary_upgrade_and_shutdown(
-                            &args, admin_only, &mut listener, shutdown_timeout, &exit_tx,
+                            &args, admin_only, &mut listener, addr, shutdown_timeout, &exit_tx,
                         ).await {
                             None => continue,
                             handles => { _migration_handles = handles; }
                         }
                         admin_only = true;
+                        // The successor binds general.listeners itself.
+                        extra_listeners.clear();
+                        admin_listener = None;
                     }
                 },
 
@@ -817,23 +910,65 @@ pub fn run_server(args: Args, config: Config) -> Result<(), Box<dyn std::error::
                     {
                         info!("Got SIGUSR2, starting binary upgrade and graceful shutdown");
                         match binary_upgrade_and_shutdown(
-                            &args, admin_only, &mut listener, shutdown_timeout, &exit_tx,
+                            &args, admin_only, &mut listener, addr, shutdown_timeout, &exit_tx,
                         ).await {
                             None => continue,
                             handles => { _migration_handles = handles; }
                         }
                         admin_only = true;
+                        // The successor binds general.listeners itself.
+                        extra_listeners.clear();
+                        admin_listener = None;
+                    }
+                },
+
+                // DRAIN / UNDRAIN from the admin console or the REST API.
+                _ = drain::changed() => {
+                    if drain::is_draining() {
+                        if listener.take().is_some() {
+                            info!("Listener on {addr} closed (DRAIN)");
+                        }
+                        for extra in &mut extra_listeners {
+                            if extra.listener.take().is_some() {
+                                info!("Listener on {} closed (DRAIN)", extra.addr);
+                            }
+                        }
+                    } else if listener.is_none() && !admin_only {
+                        match bind_listener(addr, listen_backlog(&get_config())) {
+                            Ok(l) => {
+                                info!("Listener on {addr} reopened (UNDRAIN)");
+                                listener = Some(l);
+                                for extra in &mut extra_listeners {
+                                    match bind_listener(extra.addr, listen_backlog(&get_config())) {
+                                        Ok(l) => {
+                                            info!("Listener on {} reopened (UNDRAIN)", extra.addr);
+                                            extra.listener = Some(l);
+                                        }
+                                        Err(err) => {
+                                            error!("UNDRAIN: failed to reopen listener on {}: {err}", extra.addr);
+                                        }
+                                    }
+                                }
+                            }
+                            Err(err) => {
+                                error!("UNDRAIN: failed to reopen listener on {addr}: {err}");
+                                drain::undrain_failed(&err.to_string());
+                            }
+                        }
                     }
                 },
 
                 _ = term_signal.recv() => {
                     let clients_in_tx = CLIENTS_IN_TRANSACTIONS.load(Ordering::Relaxed);
                     info!("Got SIGTERM, closing with {} clients in transactions", clients_in_tx);
+                    crate::app::client_notice::announce(
+                        crate::app::client_notice::SHUTDOWN_NOTICE,
+                    );
                     break;
                 },
 
                 // new client.
-                new_client = accept_future => {
+                (new_client, default_database) = accept_future => {
                     let (mut socket, addr) = match new_client {
                         Ok((socket, addr)) => (socket, addr),
                         Err(err) => {
@@ -874,28 +1009,126 @@ pub fn run_server(args: Args, config: Config) -> Result<(), Box<dyn std::error::
 
                     let log_client_disconnections = config.general.log_client_connections;
                     let max_connections = config.general.max_connections;
+                    let max_connections_queue = config.general.max_connections_queue();
+                    let accept_proxy_protocol = config.general.accept_proxy_protocol;
 
                     configure_tcp_socket(&socket);
                     tokio::task::spawn(async move {
+                        let mut addr = addr;
+                        if accept_proxy_protocol {
+                            let header = tokio::time::timeout(
+                                crate::client::proxy_protocol::PROXY_HEADER_TIMEOUT,
+                                crate::client::proxy_protocol::read_proxy_header(&mut socket),
+                            )
+                            .await;
+                            match header {
+                                Ok(Ok(Some(source))) => addr = source,
+                                Ok(Ok(None)) => {}
+                                Ok(Err(err)) => {
+                                    warn!("Rejecting connection from {addr}: {err}");
+                                    crate::web::metrics::record_listener_rejection("proxy_protocol");
+                                    let _ = socket.shutdown().await;
+                                    return;
+                                }
+                                Err(_) => {
+                                    warn!("Rejecting connection from {addr}: timed out waiting for PROXY protocol header");
+                                    crate::web::metrics::record_listener_rejection("proxy_protocol");
+                                    let _ = socket.shutdown().await;
+                                    return;
+                                }
+                            }
+                        }
                         let connection_id = TOTAL_CONNECTION_COUNTER.fetch_add(1, Ordering::Relaxed) as u64 + 1;
-                        let current_clients = CURRENT_CLIENT_COUNT.fetch_add(1, Ordering::SeqCst);
-                        if current_clients as u64 > max_connections {
+                        if let Err(current_clients) = admission::admit(max_connections, max_connections_queue).await {
                             warn!("[#c{connection_id}] client {addr} rejected: too many clients (current={current_clients}, max={max_connections})");
                             if let Err(err) = crate::client::client_entrypoint_too_many_clients_already(
-                                socket, client_server_map).await {
+                                socket, addr, client_server_map).await {
                                 error!("[#c{connection_id}] client {addr} disconnected with error: {err}");
                             }
-                            CURRENT_CLIENT_COUNT.fetch_add(-1, Ordering::SeqCst);
                             return;
                         }
                         let start = Utc::now().naive_utc();
                         let result = crate::client::client_entrypoint(
                             socket,
+                            addr,
                             client_server_map,
                             admin_only,
                             tls_acceptor,
                             tls_rate_limiter,
                             connection_id,
+                            default_database,
+                        )
+                        .await;
+                        log_session_end(
+                            result,
+                            connection_id,
+                            &addr.to_string(),
+                            start,
+                            log_client_disconnections,
+                        );
+                        admission::release();
+                    });
+                }
+
+                // Admin console client
+                new_admin = async {
+                    if let Some(ref l) = admin_listener {
+                        l.accept().await
+                    } else {
+                        std::future::pending().await
+                    }
+                } => {
+                    let (mut socket, addr) = match new_admin {
+                        Ok(pair) => pair,
+                        Err(err) => {
+                            if is_fd_exhaustion_io(&err) {
+                                if should_log_accept_resource_now() {
+                                    error!(
+                                        "Failed to accept admin connection: {err} \
+                                         (process fd table exhausted; backing off)"
+                                    );
+                                }
+                                tokio::time::sleep(Duration::from_millis(10)).await;
+                            } else {
+                                error!("Failed to accept admin connection: {err}");
+                            }
+                            continue;
+                        }
+                    };
+                    if admin_only {
+                        warn!("Rejecting admin connection from {addr}: pooler shutting down");
+                        let _ = socket.shutdown().await;
+                        continue;
+                    }
+                    let tls_rate_limiter = tls_rate_limiter.clone();
+                    let tls_acceptor = tls_acceptor.clone();
+                    let client_server_map = client_server_map.clone();
+                    let config = get_config();
+                    let log_client_disconnections = config.general.log_client_disconnections;
+                    let max_connections = config.general.max_connections;
+                    let max_connections_queue = config.general.max_connections_queue();
+
+                    configure_tcp_socket(&socket);
+                    tokio::task::spawn(async move {
+                        let connection_id = TOTAL_CONNECTION_COUNTER.fetch_add(1, Ordering::Relaxed) as u64 + 1;
+                        if let Err(current_clients) = admission::admit(max_connections, max_connections_queue).await {
+                            warn!("[#c{connection_id}] admin client {addr} rejected: too many clients (current={current_clients}, max={max_connections})");
+                            if let Err(err) = crate::client::client_entrypoint_too_many_clients_already(
+                                socket, addr, client_server_map).await {
+                                error!("[#c{connection_id}] client {addr} disconnected with error: {err}");
+                            }
+                            return;
+                        }
+                        let start = Utc::now().naive_utc();
+                        let result = crate::client::client_entrypoint(
+                            socket,
+                            addr,
+                            client_server_map,
+                            true,
+                            tls_acceptor,
+                            tls_rate_limiter,
+                            connection_id,
+                            None,
                         )
                         .await;
                         log_session_end(
@@ -905,7 +1138,7 @@ pub fn run_server(args: Args, config: Config) -> Result<(), Box<dyn std::error::
                             start,
                             log_client_disconnections,
                         );
-                        CURRENT_CLIENT_COUNT.fetch_add(-1, Ordering::SeqCst);
+                        admission::release();
                     });
                 }
 
@@ -941,16 +1174,19 @@ pub fn run_server(args: Args, config: Config) -> Result<(), Box<dyn std::error::
                         drop(socket);
                         continue;
                     }
+                    // While drained the Unix socket only admits the admin
+                    // console, so the node can still be undrained locally.
+                    let admin_only = drain::is_draining();
                     configure_unix_socket(&socket);
                     let client_server_map = client_server_map.clone();
                     let config = get_config();
                     let log_client_disconnections = config.general.log_client_disconnections;
                     let max_connections = config.general.max_connections;
+                    let max_connections_queue = config.general.max_connections_queue();
 
                     tokio::task::spawn(async move {
                         let connection_id = TOTAL_CONNECTION_COUNTER.fetch_add(1, Ordering::Relaxed) as u64 + 1;
-                        let current_clients = CURRENT_CLIENT_COUNT.fetch_add(1, Ordering::SeqCst);
-                        if current_clients as u64 > max_connections {
+                        if let Err(current_clients) = admission::admit(max_connections, max_connections_queue).await {
                             warn!("[#c{connection_id}] unix client rejected: too many clients (current={current_clients}, max={max_connections})");
                             if let Err(err) = crate::client::client_entrypoint_too_many_clients_already_unix(
                                 socket,
@@ -960,7 +1196,6 @@ pub fn run_server(args: Args, config: Config) -> Result<(), Box<dyn std::error::
                             {
                                 warn!("[#c{connection_id}] unix client rejection response failed: {err}");
                             }
-                            CURRENT_CLIENT_COUNT.fetch_add(-1, Ordering::SeqCst);
                             return;
                         }
                         let start = Utc::now().naive_utc();
@@ -978,7 +1213,7 @@ pub fn run_server(args: Args, config: Config) -> Result<(), Box<dyn std::error::
                             start,
                             log_client_disconnections,
                         );
-                        CURRENT_CLIENT_COUNT.fetch_add(-1, Ordering::SeqCst);
+                        admission::release();
                     });
                 }
 
@@ -988,11 +1223,14 @@ pub fn run_server(args: Args, config: Config) -> Result<(), Box<dyn std::error::
 
             }
         }
-        // Cleanup Unix socket file only if the inode on disk is still the
+        // Cleanup Unix socket files only if the inode on disk is still the
         // one this process created. During a SIGUSR2 binary upgrade the
         // successor rebinds the same path before we reach this point, so
         // an unconditional unlink here would wipe out the new listener.
-        if let Some(ref ownership) = unix_socket_ownership {
+        for ownership in unix_socket_ownership
+            .iter()
+            .chain(web_unix_socket_ownership.iter())
+        {
             match ownership.cleanup_if_ours() {
                 UnixSocketCleanup::Removed => {}
                 UnixSocketCleanup::Missing => {}
@@ -1047,6 +1285,7 @@ async fn binary_upgrade_and_shutdown(
     args: &Args,
     admin_only: bool,
     listener: &mut Option<tokio::net::TcpListener>,
+    addr: SocketAddr,
     shutdown_timeout: Duration,
     exit_tx: &mpsc::Sender<()>,
 ) -> Option<MigrationHandles> {
@@ -1168,11 +1407,32 @@ async fn binary_upgrade_and_shutdown(
         }
     }
 
+    // A drained node has no listener to hand over. The new process needs
+    // one, so the upgrade ends DRAIN first.
+    if !admin_only && listener.is_none() {
+        match bind_listener(addr, listen_backlog(&get_config())) {
+            Ok(l) => {
+                info!("Reopening drained listener on {addr} for binary upgrade");
+                drain::undrain();
+                *listener = Some(l);
+            }
+            Err(err) => {
+                error!(
+                    "Binary upgrade aborted: failed to reopen drained listener on {addr}: {err}"
+                );
+                return None;
+            }
+        }
+    }
+
     // Mark migration before shutdown so idle clients choose the migration path.
     if !admin_only {
         MIGRATION_IN_PROGRESS.store(true, Ordering::Relaxed);
     }
     SHUTDOWN_IN_PROGRESS.store(true, Ordering::SeqCst);
+    if !admin_only {
+        crate::app::client_notice::announce(crate::app::client_notice::UPGRADE_NOTICE);
+    }
 
     let mut migration_handles: Option<MigrationHandles> = None;
 
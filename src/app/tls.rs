@@ -32,6 +32,8 @@ pub fn init_tls(config: &Config) -> TlsState {
                 Path::new(&config.general.tls_private_key.clone().unwrap()),
                 config.general.tls_ca_cert.clone(),
                 config.general.tls_mode.clone(),
+                config.general.tls_min_protocol_version.as_deref(),
+                config.general.tls_ciphers.as_deref(),
             ) {
                 Ok(acceptor) => Some(acceptor),
                 Err(err) => {
@@ -132,6 +132,8 @@ impl PasswordFetcher for AuthQueryExecutor {
 pub struct AuthQueryExecutor {
     config: AuthQueryConfig,
     pool_name: String,
+    /// `auth_query.database`, or the pool's server database.
+    database: String,
     server_host: String,
     server_port: u16,
     tx: mpsc::Sender<Client>,
@@ -145,13 +147,14 @@ impl AuthQueryExecutor {
     pub async fn new(
         config: &AuthQueryConfig,
         pool_name: &str,
+        server_database: &str,
         server_host: &str,
         server_port: u16,
     ) -> Result<Self, Error> {
         let database = config
             .database
             .clone()
-            .unwrap_or_else(|| pool_name.to_string());
+            .unwrap_or_else(|| server_database.to_string());
 
         let pg_config = Self::build_pg_config(config, server_host, server_port, &database);
 
@@ -175,6 +178,9 @@ impl AuthQueryExecutor {
                 &config.user,
             )
             .await?;
+            if i == 0 {
+                Self::warn_if_privileged(&client, pool_name, &config.user).await;
+            }
             tx.send(client).await.map_err(|_| {
                 Error::AuthQueryConnectionError("failed to initialize executor pool".into())
             })?;
@@ -189,6 +195,7 @@ impl AuthQueryExecutor {
         Ok(Self {
             config: config.clone(),
             pool_name: pool_name.to_string(),
+            database,
             server_host: server_host.to_string(),
             server_port,
             tx,
@@ -252,6 +259,45 @@ impl AuthQueryExecutor {
         Ok(client)
     }
 
+    /// Warn when the executor role carries privileges the lookup does not
+    /// need. The executor only has to read password hashes, ideally through
+    /// a `SECURITY DEFINER` function; a superuser or `BYPASSRLS` role turns
+    /// a leaked `auth_query.password` into full cluster access. The probe is
+    /// advisory: failures are logged and never block startup.
+    async fn warn_if_privileged(client: &Client, pool_name: &str, user: &str) {
+        let row = match client
+            .query_opt(
+                "SELECT rolsuper, rolcreaterole, rolreplication, rolbypassrls \
+                 FROM pg_catalog.pg_roles WHERE rolname = current_user",
+                &[],
+            )
+            .await
+        {
+            Ok(Some(row)) => row,
+            Ok(None) => return,
+            Err(e) => {
+                debug!("[pool: {pool_name}] auth_query: privilege probe for '{user}' failed: {e}");
+                return;
+            }
+        };
+
+        let attributes = ["SUPERUSER", "CREATEROLE", "REPLICATION", "BYPASSRLS"];
+        let granted: Vec<&str> = attributes
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| row.try_get::<_, bool>(*idx).unwrap_or(false))
+            .map(|(_, name)| *name)
+            .collect();
+        if !granted.is_empty() {
+            warn!(
+                "[pool: {pool_name}] auth_query: executor role '{user}' has {}; \
+                 use a least-privileged role that can only execute the lookup \
+                 (see the SECURITY DEFINER example in the auth_query docs)",
+                granted.join(", ")
+            );
+        }
+    }
+
     /// Fetch credentials (password hash plus the optional per-user
     /// startup_parameters map) for a username from PostgreSQL.
     /// Returns `Some((password_hash, params))` or `None` if user not found.
@@ -319,20 +365,16 @@ impl AuthQueryExecutor {
     }
 
     async fn try_reconnect(&self) {
-        let database = self
-            .config
-            .database
-            .clone()
-            .unwrap_or_else(|| self.pool_name.clone());
+        let database = &self.database;
         let pg_config =
-            Self::build_pg_config(&self.config, &self.server_host, self.server_port, &database);
+            Self::build_pg_config(&self.config, &self.server_host, self.server_port, database);
         match Self::connect(
             &pg_config,
             0,
             &self.pool_name,
             &self.server_host,
             self.server_port,
-            &database,
+            database,
             &self.config.user,
         )
         .await
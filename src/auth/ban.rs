@@ -0,0 +1,218 @@
+//! Temporary bans for client addresses that keep failing authentication.
+//!
+//! Every failed login recorded through `record_auth_failure` lands here.
+//! Once an address collects `auth_fail_ban_threshold` failures within
+//! `auth_fail_ban_window`, it is banned for `auth_fail_ban_duration`:
+//! `client_entrypoint` rejects its connections before the startup message
+//! reaches pool lookup or authentication. `SHOW BANS` lists the table and
+//! `RESET BANS [ip]` lifts bans early. Unix socket clients have no address
+//! and are never banned.
+
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+use log::warn;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+
+use crate::config::General;
+
+/// Ban settings taken from `general`.
+#[derive(Debug, Clone, Copy)]
+pub struct BanPolicy {
+    pub threshold: u32,
+    pub window: Duration,
+    pub duration: Duration,
+}
+
+impl BanPolicy {
+    /// `None` while `auth_fail_ban_threshold` is 0 (bans disabled).
+    pub fn from_general(general: &General) -> Option<BanPolicy> {
+        if general.auth_fail_ban_threshold == 0 {
+            return None;
+        }
+        Some(BanPolicy {
+            threshold: general.auth_fail_ban_threshold,
+            window: general.auth_fail_ban_window.into(),
+            duration: general.auth_fail_ban_duration.into(),
+        })
+    }
+}
+
+#[derive(Default)]
+struct Tracker {
+    /// Most recent failures, at most `threshold` of them.
+    failures: VecDeque<Instant>,
+    banned_until: Option<Instant>,
+}
+
+/// One row of `SHOW BANS`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BanInfo {
+    pub ip: IpAddr,
+    /// Failures inside the current window.
+    pub failures: usize,
+    /// Time left on the ban; `None` when the address is only being tracked.
+    pub remaining: Option<Duration>,
+}
+
+static TRACKERS: Lazy<Mutex<HashMap<IpAddr, Tracker>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Records one failed login from `ip`. Returns `true` when this failure
+/// started a ban.
+pub fn record_failure(ip: IpAddr, policy: &BanPolicy, now: Instant) -> bool {
+    let mut trackers = TRACKERS.lock();
+    // Drop addresses whose failures aged out and whose ban is over, so the
+    // table only holds what the window still needs.
+    trackers.retain(|_, tracker| is_live(tracker, policy.window, now));
+
+    let tracker = trackers.entry(ip).or_default();
+    if tracker.banned_until.is_some_and(|until| until > now) {
+        return false;
+    }
+    tracker.banned_until = None;
+    tracker.failures.push_back(now);
+    while tracker.failures.len() > policy.threshold as usize {
+        tracker.failures.pop_front();
+    }
+    let window_full = tracker.failures.len() >= policy.threshold as usize
+        && tracker
+            .failures
+            .front()
+            .is_some_and(|first| now.duration_since(*first) <= policy.window);
+    if !window_full {
+        return false;
+    }
+    tracker.failures.clear();
+    tracker.banned_until = Some(now + policy.duration);
+    warn!(
+        "auth ban: {ip} banned for {}s after {} failed logins within {}s",
+        policy.duration.as_secs(),
+        policy.threshold,
+        policy.window.as_secs()
+    );
+    crate::admin::events::push_event(
+        "AUTH_BAN",
+        format!(
+            "{ip} banned for {}s after {} failed logins",
+            policy.duration.as_secs(),
+            policy.threshold
+        ),
+    );
+    true
+}
+
+/// Time left on the ban of `ip`, if it is banned.
+pub fn banned_for(ip: IpAddr, now: Instant) -> Option<Duration> {
+    let trackers = TRACKERS.lock();
+    let until = trackers.get(&ip)?.banned_until?;
+    until.checked_duration_since(now).filter(|d| !d.is_zero())
+}
+
+/// Banned and tracked addresses, banned ones first.
+pub fn bans(window: Duration, now: Instant) -> Vec<BanInfo> {
+    let trackers = TRACKERS.lock();
+    let mut rows: Vec<BanInfo> = trackers
+        .iter()
+        .filter(|(_, tracker)| is_live(tracker, window, now))
+        .map(|(ip, tracker)| BanInfo {
+            ip: *ip,
+            failures: tracker
+                .failures
+                .iter()
+                .filter(|at| now.duration_since(**at) <= window)
+                .count(),
+            remaining: tracker
+                .banned_until
+                .and_then(|until| until.checked_duration_since(now)),
+        })
+        .collect();
+    rows.sort_by(|a, b| b.remaining.cmp(&a.remaining).then(a.ip.cmp(&b.ip)));
+    rows
+}
+
+/// Forgets `ip`, or every address when `ip` is `None`. Returns how many
+/// entries were removed.
+pub fn clear(ip: Option<IpAddr>) -> usize {
+    let mut trackers = TRACKERS.lock();
+    match ip {
+        Some(ip) => usize::from(trackers.remove(&ip).is_some()),
+        None => {
+            let removed = trackers.len();
+            trackers.clear();
+            removed
+        }
+    }
+}
+
+fn is_live(tracker: &Tracker, window: Duration, now: Instant) -> bool {
+    tracker.banned_until.is_some_and(|until| until > now)
+        || tracker
+            .failures
+            .back()
+            .is_some_and(|last| now.duration_since(*last) <= window)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    fn policy() -> BanPolicy {
+        BanPolicy {
+            threshold: 3,
+            window: Duration::from_secs(60),
+            duration: Duration::from_secs(300),
+        }
+    }
+
+    #[test]
+    #[serial(auth_ban)]
+    fn ban_starts_at_threshold_within_window() {
+        clear(None);
+        let ip: IpAddr = "192.0.2.10".parse().unwrap();
+        let start = Instant::now();
+
+        assert!(!record_failure(ip, &policy(), start));
+        assert!(!record_failure(
+            ip,
+            &policy(),
+            start + Duration::from_secs(10)
+        ));
+        assert_eq!(banned_for(ip, start + Duration::from_secs(10)), None);
+        assert!(record_failure(
+            ip,
+            &policy(),
+            start + Duration::from_secs(20)
+        ));
+
+        let banned_at = start + Duration::from_secs(20);
+        assert_eq!(banned_for(ip, banned_at), Some(Duration::from_secs(300)));
+        assert_eq!(banned_for(ip, banned_at + Duration::from_secs(300)), None);
+
+        let rows = bans(policy().window, banned_at);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].ip, ip);
+
+        assert_eq!(clear(Some(ip)), 1);
+        assert_eq!(banned_for(ip, banned_at), None);
+    }
+
+    #[test]
+    #[serial(auth_ban)]
+    fn failures_outside_window_do_not_ban() {
+        clear(None);
+        let ip: IpAddr = "2001:db8::10".parse().unwrap();
+        let start = Instant::now();
+
+        record_failure(ip, &policy(), start);
+        record_failure(ip, &policy(), start + Duration::from_secs(50));
+        assert!(!record_failure(
+            ip,
+            &policy(),
+            start + Duration::from_secs(100)
+        ));
+        assert_eq!(banned_for(ip, start + Duration::from_secs(100)), None);
+    }
+}
@@ -5,6 +5,9 @@ use ipnet::IpNet;
 
 use crate::transport::ClientTransport;
 
+/// Longest `reject message="..."` text sent to clients, in characters.
+const MAX_REJECT_MESSAGE_CHARS: usize = 256;
+
 /// Authentication method supported by our checker.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AuthMethod {
@@ -58,6 +61,8 @@ pub struct HbaRule {
     pub user: NameMatcher,
     pub address: Option<IpNet>,
     pub method: AuthMethod,
+    /// `message="..."` option of a `reject` rule, already sanitized.
+    pub reject_message: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -102,6 +107,18 @@ pub enum CheckResult {
     Trust,
 }
 
+impl std::fmt::Display for CheckResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            CheckResult::NotMatched => "not_matched",
+            CheckResult::Deny => "deny",
+            CheckResult::Allow => "allow",
+            CheckResult::Trust => "trust",
+        };
+        f.write_str(s)
+    }
+}
+
 /// Parsed pg_hba set of rules, in order.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct PgHba {
@@ -144,6 +161,16 @@ impl std::fmt::Display for AuthMethod {
 
 impl std::fmt::Display for HbaRule {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_match(f)?;
+        if let Some(message) = &self.reject_message {
+            write!(f, " message=\"{message}\"")?;
+        }
+        Ok(())
+    }
+}
+
+impl HbaRule {
+    fn fmt_match(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self.host_type {
             HostType::Local => {
                 write!(
@@ -334,12 +361,22 @@ impl PgHba {
 
             let method = AuthMethod::from_token(tokens[method_idx].as_str());
 
+            // Options follow the method; only `message=` on `reject` is used.
+            let reject_message = match method {
+                AuthMethod::Reject => tokens[method_idx + 1..]
+                    .iter()
+                    .find_map(|opt| opt.strip_prefix("message="))
+                    .and_then(sanitize_reject_message),
+                _ => None,
+            };
+
             rules.push(HbaRule {
                 host_type: ht,
                 database,
                 user,
                 address: address_opt,
                 method,
+                reject_message,
             });
         }
         PgHba { rules }
@@ -364,13 +401,27 @@ impl PgHba {
         username: &str,
         database: &str,
     ) -> CheckResult {
+        self.matching_rule(transport, type_auth, username, database)
+            .0
+    }
+
+    /// Same as [`check_hba`](Self::check_hba), but also returns the index in
+    /// `rules` of the rule that made the decision (`None` for `NotMatched`).
+    /// Used by the admin `TEST HBA` command.
+    pub fn matching_rule(
+        &self,
+        transport: &ClientTransport,
+        type_auth: &str,
+        username: &str,
+        database: &str,
+    ) -> (CheckResult, Option<usize>) {
         let want = match type_auth.to_ascii_lowercase().as_str() {
             "md5" => AuthMethod::Md5,
             "scram-sha-256" | "scram_sha_256" | "scramsha256" => AuthMethod::ScramSha256,
             _ => AuthMethod::Other(type_auth.to_string()),
         };
 
-        for rule in &self.rules {
+        for (idx, rule) in self.rules.iter().enumerate() {
             match rule.host_type {
                 HostType::Local => {
                     // local rules match only Unix socket connections
@@ -400,13 +451,45 @@ impl PgHba {
 
             // First matching rule that applies decides.
             match rule.method {
-                AuthMethod::Trust => return CheckResult::Trust,
-                ref m if *m == want => return CheckResult::Allow,
-                AuthMethod::Reject => return CheckResult::Deny,
+                AuthMethod::Trust => return (CheckResult::Trust, Some(idx)),
+                ref m if *m == want => return (CheckResult::Allow, Some(idx)),
+                AuthMethod::Reject => return (CheckResult::Deny, Some(idx)),
                 _ => continue, // different method: not a decision, keep searching
             }
         }
-        CheckResult::NotMatched
+        (CheckResult::NotMatched, None)
+    }
+
+    /// Custom message of the `reject` rule that denies this connection for
+    /// either password method, if that rule has one.
+    pub fn reject_message(
+        &self,
+        transport: &ClientTransport,
+        username: &str,
+        database: &str,
+    ) -> Option<&str> {
+        ["md5", "scram-sha-256"].iter().find_map(|type_auth| {
+            match self.matching_rule(transport, type_auth, username, database) {
+                (CheckResult::Deny, Some(idx)) => self.rules[idx].reject_message.as_deref(),
+                _ => None,
+            }
+        })
+    }
+}
+
+/// Makes an operator-supplied message safe for an ErrorResponse: drops
+/// control characters (including NUL) and caps the length.
+fn sanitize_reject_message(raw: &str) -> Option<String> {
+    let cleaned: String = raw
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_REJECT_MESSAGE_CHARS)
+        .collect();
+    let trimmed = cleaned.trim();
+    if trimmed.is_empty() {
+        None
+    } else {
+        Some(trimmed.to_string())
     }
 }
 
@@ -474,6 +557,46 @@ hostnossl all all 127.0.0.1/32 trust
         ClientTransport::Unix
     }
 
+    #[test]
+    fn reject_message_is_parsed_and_sanitized() {
+        let hba = PgHba::from_content(
+            "host all all 10.0.0.0/8 reject message=\"access from this network requires VPN\"\n\
+             host all all 0.0.0.0/0 reject message=\"\u{7}   \"\n\
+             host all all 10.0.0.0/8 md5 message=\"ignored\"",
+        );
+        assert_eq!(
+            hba.rules[0].reject_message.as_deref(),
+            Some("access from this network requires VPN")
+        );
+        assert_eq!(hba.rules[1].reject_message, None);
+        assert_eq!(hba.rules[2].reject_message, None);
+        assert_eq!(
+            hba.rules[0].to_string(),
+            "host all all 10.0.0.0/8 reject message=\"access from this network requires VPN\""
+        );
+
+        let ip = IpAddr::V4(Ipv4Addr::new(10, 1, 2, 3));
+        assert_eq!(
+            hba.reject_message(&tcp(ip, false), "alice", "app"),
+            Some("access from this network requires VPN")
+        );
+        let other = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1));
+        assert_eq!(hba.reject_message(&tcp(other, false), "alice", "app"), None);
+
+        let long = format!(
+            "host all all 0.0.0.0/0 reject message=\"{}\"",
+            "x".repeat(1000)
+        );
+        let hba = PgHba::from_content(&long);
+        assert_eq!(
+            hba.rules[0]
+                .reject_message
+                .as_ref()
+                .map(|m| m.chars().count()),
+            Some(MAX_REJECT_MESSAGE_CHARS)
+        );
+    }
+
     #[test]
     fn parse_and_check() {
         let hba = PgHba::from_content(SAMPLE);
@@ -663,6 +786,48 @@ hostnossl all all 127.0.0.1/32 trust
         );
     }
 
+    #[test]
+    fn hostssl_and_hostnossl_split_tcp_clients_by_tls() {
+        // With both kinds of rules for the same network, the client's TLS
+        // state alone picks the rule: TLS clients skip hostnossl, plaintext
+        // clients skip hostssl, regardless of rule order.
+        let ip = IpAddr::V4(Ipv4Addr::new(10, 1, 2, 3));
+        for content in [
+            "hostnossl all all 10.0.0.0/8 reject\nhostssl all all 10.0.0.0/8 trust",
+            "hostssl all all 10.0.0.0/8 trust\nhostnossl all all 10.0.0.0/8 reject",
+        ] {
+            let hba = PgHba::from_content(content);
+            assert_eq!(
+                hba.check_hba(&tcp(ip, true), "md5", "alice", "app"),
+                CheckResult::Trust,
+                "{content}"
+            );
+            assert_eq!(
+                hba.check_hba(&tcp(ip, false), "md5", "alice", "app"),
+                CheckResult::Deny,
+                "{content}"
+            );
+        }
+    }
+
+    #[test]
+    fn hostnossl_rule_ignored_for_tls_and_unix() {
+        let hba = PgHba::from_content("hostnossl all all 0.0.0.0/0 trust");
+        let ip = IpAddr::V4(Ipv4Addr::new(10, 1, 2, 3));
+        assert_eq!(
+            hba.check_hba(&tcp(ip, true), "md5", "alice", "app"),
+            CheckResult::NotMatched
+        );
+        assert_eq!(
+            hba.check_hba(&unix_transport(), "md5", "alice", "app"),
+            CheckResult::NotMatched
+        );
+        assert_eq!(
+            hba.check_hba(&tcp(ip, false), "md5", "alice", "app"),
+            CheckResult::Trust
+        );
+    }
+
     #[test]
     fn hostssl_rule_ignored_for_unix() {
         // The ClientTransport enum makes it impossible to build a
@@ -751,4 +916,29 @@ hostnossl all all 127.0.0.1/32 trust
             CheckResult::NotMatched
         );
     }
+
+    #[test]
+    fn matching_rule_reports_deciding_index() {
+        let hba = PgHba::from_content(
+            "hostssl all all 10.0.0.0/8 scram-sha-256\nhost all bob 0.0.0.0/0 reject\nhost all all 10.0.0.0/8 md5\n",
+        );
+        let ip = IpAddr::V4(Ipv4Addr::new(10, 1, 2, 3));
+        // scram rule at 0 does not decide an md5 request; md5 rule at 2 does.
+        assert_eq!(
+            hba.matching_rule(&tcp(ip, true), "md5", "alice", "app"),
+            (CheckResult::Allow, Some(2))
+        );
+        assert_eq!(
+            hba.matching_rule(&tcp(ip, true), "scram-sha-256", "alice", "app"),
+            (CheckResult::Allow, Some(0))
+        );
+        assert_eq!(
+            hba.matching_rule(&tcp(ip, false), "md5", "bob", "app"),
+            (CheckResult::Deny, Some(1))
+        );
+        assert_eq!(
+            hba.matching_rule(&unix_transport(), "md5", "alice", "app"),
+            (CheckResult::NotMatched, None)
+        );
+    }
 }
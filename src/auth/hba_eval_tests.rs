@@ -13,6 +13,8 @@ fn base_ci() -> ClientIdentifier {
         is_talos: false,
         hba_scram: CheckResult::NotMatched,
         hba_md5: CheckResult::NotMatched,
+        hba_reject_message: None,
+        requested_pool_mode: None,
     }
 }
 
@@ -1,4 +1,5 @@
 pub mod auth_query;
+pub mod ban;
 pub mod hba;
 #[cfg(test)]
 mod hba_eval_tests;
@@ -6,9 +7,11 @@ pub mod jwt;
 pub mod pam;
 pub mod scram;
 pub mod scram_client;
+pub mod scram_relay;
 pub mod talos;
 
 use std::marker::Unpin;
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
@@ -23,7 +26,7 @@ use crate::auth::scram::{
     prepare_server_final_message, prepare_server_first_response,
 };
 use crate::config::BackendAuthMethod;
-use crate::config::{get_config, PoolMode};
+use crate::config::{config_arc, get_config, AdminPermission, PoolMode};
 use crate::errors::{ClientIdentifier, Error};
 use crate::messages::constants::{
     JWT_PUB_KEY_PASSWORD_PREFIX, MD5_PASSWORD_PREFIX, SASL_CONTINUE, SASL_FINAL, SCRAM_SHA_256,
@@ -37,7 +40,7 @@ use crate::pool::{
     create_dynamic_pool, get_auth_query_state, get_pool, get_pool_config, is_dynamic_pool,
     ConnectionPool, PoolIdentifier,
 };
-use crate::server::ServerParameters;
+use crate::server::{Server, ServerParameters};
 
 /// Canonicalised set of GUC names the operator put under
 /// `general.startup_parameters` / `pool.startup_parameters` /
@@ -59,6 +62,11 @@ pub struct AuthOutcome {
     pub server_parameters: ServerParameters,
     pub prepared_statements_enabled: bool,
     pub operator_managed_keys: Option<OperatorManagedKeys>,
+    /// Admin console login limited to read-only commands.
+    pub admin_readonly: bool,
+    /// Backend connection a `scram_relay` client logged in through; it
+    /// serves the client's whole session.
+    pub relayed_server: Option<Server>,
 }
 
 /// Authenticate a user based on the provided parameters
@@ -75,6 +83,8 @@ where
     T: AsyncWriteExt + Unpin,
 {
     let mut prepared_statements_enabled = false;
+    let mut admin_readonly = false;
+    let mut relayed_server = None;
 
     // Authenticate admin user.
     let (transaction_mode, server_parameters, operator_managed_keys) = if admin {
@@ -85,16 +95,23 @@ where
                 "HBA trust: admin user={username_from_parameters}, addr={}",
                 client_identifier.addr
             );
+            let permission = get_config()
+                .general
+                .admin_login(username_from_parameters)
+                .map_or(AdminPermission::Full, |(_, permission)| permission);
             return Ok(AuthOutcome {
                 transaction_mode: false,
                 server_parameters: ServerParameters::admin(),
                 prepared_statements_enabled: false,
                 operator_managed_keys: None,
+                admin_readonly: permission == AdminPermission::Readonly,
+                relayed_server: None,
             });
         }
         if client_identifier.hba_md5 == CheckResult::Deny
             || client_identifier.hba_scram == CheckResult::Deny
         {
+            record_auth_failure("hba_denied", &client_identifier.addr);
             let error = Error::AuthError(format!(
                 "HBA failed for admin user: {username_from_parameters}"
             ));
@@ -102,8 +119,16 @@ where
             wrong_password(write, username_from_parameters).await?;
             return Err(error);
         }
-        let (tx, sp) = authenticate_admin(read, write, username_from_parameters).await?;
-        (tx, sp, None)
+        let permission = authenticate_admin(
+            read,
+            write,
+            username_from_parameters,
+            pool_name,
+            &client_identifier.addr,
+        )
+        .await?;
+        admin_readonly = permission == AdminPermission::Readonly;
+        (false, ServerParameters::admin(), None)
     }
     // Authenticate normal user.
     else {
@@ -114,6 +139,7 @@ where
             pool_name,
             username_from_parameters,
             &mut prepared_statements_enabled,
+            &mut relayed_server,
         )
         .await?
     };
@@ -123,32 +149,88 @@ where
         server_parameters,
         prepared_statements_enabled,
         operator_managed_keys,
+        admin_readonly,
+        relayed_server,
     })
 }
 
-/// Authenticate an admin user with MD5
+/// Authenticate an admin user with SCRAM-SHA-256 or MD5 against
+/// `admin_username` or one of `admin_users`, and return its permission.
 async fn authenticate_admin<S, T>(
     read: &mut S,
     write: &mut T,
     username_from_parameters: &str,
-) -> Result<(bool, ServerParameters), Error>
+    pool_name: &str,
+    client_addr: &str,
+) -> Result<AdminPermission, Error>
 where
     S: AsyncReadExt + Unpin,
     T: AsyncWriteExt + Unpin,
 {
+    let config = get_config();
+    // Unknown names are checked against the main admin login, so they
+    // fail the same way a wrong password does.
+    let (admin_username, admin_password, permission) =
+        match config.general.admin_login(username_from_parameters) {
+            Some((password, permission)) => (username_from_parameters, password, permission),
+            None => (
+                config.general.admin_username.as_str(),
+                config.general.admin_password.as_str(),
+                AdminPermission::Full,
+            ),
+        };
+    verify_admin_password(
+        read,
+        write,
+        admin_username,
+        admin_password,
+        username_from_parameters,
+        pool_name,
+        client_addr,
+    )
+    .await?;
+
+    Ok(permission)
+}
+
+/// Checks the admin password. A `SCRAM-SHA-256$...` secret in
+/// `admin_password` is verified with SCRAM, so MD5 can be disabled
+/// everywhere; a plain password keeps the MD5 challenge.
+async fn verify_admin_password<S, T>(
+    read: &mut S,
+    write: &mut T,
+    admin_username: &str,
+    admin_password: &str,
+    username_from_parameters: &str,
+    pool_name: &str,
+    client_addr: &str,
+) -> Result<(), Error>
+where
+    S: AsyncReadExt + Unpin,
+    T: AsyncWriteExt + Unpin,
+{
+    if admin_password.starts_with(SCRAM_SHA_256) {
+        authenticate_with_scram(
+            read,
+            write,
+            admin_password,
+            username_from_parameters,
+            pool_name,
+            client_addr,
+        )
+        .await?;
+        return Ok(());
+    }
+
     // Authenticate admin user with md5.
     let salt = md5_challenge(write).await?;
     let password_response = read_password(read).await?;
-    let config = get_config();
 
     // Compare server and client hashes.
-    let password_hash = md5_hash_password(
-        &config.general.admin_username,
-        &config.general.admin_password,
-        &salt,
-    );
+    let password_hash = md5_hash_password(admin_username, admin_password, &salt);
 
     if password_hash != password_response {
+        record_auth_failure("bad_password", client_addr);
         let error = Error::AuthError(format!(
             "Invalid password for admin user: {username_from_parameters}"
         ));
@@ -159,7 +241,7 @@ where
         return Err(error);
     }
 
-    Ok((false, ServerParameters::admin()))
+    Ok(())
 }
 
 /// Authenticate a normal user with various methods
@@ -211,6 +293,113 @@ fn eval_hba_for_pool_password(pool_password: &str, ci: &ClientIdentifier) -> Che
     CheckResult::Allow
 }
 
+/// Text sent to a client denied by HBA: the matching `reject` rule's
+/// `message=` if it has one, otherwise `fallback`.
+/// Counts one rejected login in `pg_doorman_auth_failures_total` and logs
+/// it with the reason and client address. `reason` is one of
+/// `bad_password`, `hba_denied`, `no_pool`, `unsupported_method` or
+/// `protocol_error`; the username never becomes a label. With
+/// `auth_fail_ban_threshold` set, the failure also counts towards a
+/// temporary ban of the client IP.
+pub(crate) fn record_auth_failure(reason: &'static str, client_addr: &str) {
+    let config = config_arc();
+    let subnet = if config.general.auth_failures_by_subnet {
+        client_subnet(client_addr)
+    } else {
+        String::new()
+    };
+    crate::web::metrics::record_auth_failure(reason, &subnet);
+    info!("auth failure: reason={reason} client={client_addr}");
+
+    if let (Some(policy), Ok(addr)) = (
+        ban::BanPolicy::from_general(&config.general),
+        client_addr.parse::<SocketAddr>(),
+    ) {
+        ban::record_failure(addr.ip(), &policy, std::time::Instant::now());
+    }
+}
+
+/// The /24 (IPv4) or /64 (IPv6) network of an `ip:port` client address;
+/// `local` for Unix socket clients.
+fn client_subnet(client_addr: &str) -> String {
+    match client_addr.parse::<SocketAddr>().map(|addr| addr.ip()) {
+        Ok(IpAddr::V4(ip)) => {
+            let [a, b, c, _] = ip.octets();
+            format!("{a}.{b}.{c}.0/24")
+        }
+        Ok(IpAddr::V6(ip)) => {
+            let s = ip.segments();
+            format!("{}/64", Ipv6Addr::new(s[0], s[1], s[2], s[3], 0, 0, 0, 0))
+        }
+        Err(_) => "local".to_string(),
+    }
+}
+
+pub(crate) fn hba_denied_text(client_identifier: &ClientIdentifier, fallback: String) -> String {
+    client_identifier
+        .hba_reject_message
+        .clone()
+        .unwrap_or(fallback)
+}
+
+/// Pool mode of one client connection. A client may ask for session mode
+/// with `pg_doorman.pool_mode=session` in a transaction-mode pool that sets
+/// `allow_session_override`; asking for the pool's own mode is a no-op.
+/// Anything else is refused with the message returned in `Err`.
+pub(crate) fn resolve_connection_pool_mode(
+    pool_mode: PoolMode,
+    allow_session_override: bool,
+    requested: Option<PoolMode>,
+) -> Result<PoolMode, String> {
+    match requested {
+        None => Ok(pool_mode),
+        Some(requested) if requested == pool_mode => Ok(pool_mode),
+        Some(PoolMode::Session) if allow_session_override => Ok(PoolMode::Session),
+        Some(PoolMode::Session) => Err(
+            "session pool mode is not allowed for this pool (allow_session_override is off)".into(),
+        ),
+        Some(PoolMode::Transaction) => {
+            Err("transaction pool mode cannot be requested in a session-mode pool".into())
+        }
+    }
+}
+
+/// Applies [`resolve_connection_pool_mode`] to an authenticated client and
+/// returns whether the connection runs in transaction mode. A refused
+/// request ends the connection with SQLSTATE 0A000.
+async fn connection_transaction_mode<T>(
+    write: &mut T,
+    pool: &ConnectionPool,
+    client_identifier: &ClientIdentifier,
+) -> Result<bool, Error>
+where
+    T: AsyncWriteExt + Unpin,
+{
+    match resolve_connection_pool_mode(
+        pool.settings.pool_mode,
+        pool.settings.allow_session_override,
+        client_identifier.requested_pool_mode,
+    ) {
+        Ok(mode) => {
+            if mode != pool.settings.pool_mode {
+                info!(
+                    "[{}@{}] client {} switched to {mode} pool mode",
+                    client_identifier.username, client_identifier.pool_name, client_identifier.addr
+                );
+            }
+            Ok(mode == PoolMode::Transaction)
+        }
+        Err(message) => {
+            warn!(
+                "[{}@{}] client {} rejected: {message}",
+                client_identifier.username, client_identifier.pool_name, client_identifier.addr
+            );
+            error_response_terminal(write, &message, "0A000").await?;
+            Err(Error::ClientError(message))
+        }
+    }
+}
+
 async fn authenticate_normal_user<S, T>(
     read: &mut S,
     write: &mut T,
@@ -218,6 +407,7 @@ async fn authenticate_normal_user<S, T>(
     pool_name: &str,
     username_from_parameters: &str,
     prepared_statements_enabled: &mut bool,
+    relayed_server: &mut Option<Server>,
 ) -> Result<(bool, ServerParameters, Option<OperatorManagedKeys>), Error>
 where
     S: AsyncReadExt + Unpin,
@@ -257,25 +447,51 @@ where
 
     let pool_password = pool.settings.user.password.clone();
 
-    // Evaluate HBA once for this connection
-    let hba_decision = eval_hba_for_pool_password(&pool_password, client_identifier);
+    // Evaluate HBA once for this connection. A relayed login is a SCRAM
+    // exchange, so the scram rules apply to it.
+    let hba_password = if pool.settings.scram_relay {
+        SCRAM_SHA_256
+    } else {
+        pool_password.as_str()
+    };
+    let hba_decision = eval_hba_for_pool_password(hba_password, client_identifier);
     if hba_decision == CheckResult::Deny {
-        error_response_terminal(
-        write,
-        format!(
-            "Connection with scram password from IP address {} to {}@{} is not permitted by HBA configuration. Please contact your database administrator.",
-            client_identifier.addr, username_from_parameters, pool_name
-        )
-            .as_str(),
-        "28000",
-    )
-        .await?;
+        record_auth_failure("hba_denied", &client_identifier.addr);
+        let message = hba_denied_text(
+            client_identifier,
+            format!(
+                "Connection with scram password from IP address {} to {}@{} is not permitted by HBA configuration. Please contact your database administrator.",
+                client_identifier.addr, username_from_parameters, pool_name
+            ),
+        );
+        error_response_terminal(write, &message, "28000").await?;
         return Err(Error::HbaForbiddenError(format!(
         "Connection with scram not permitted by HBA configuration for client: {} from address: {:?}",
         client_identifier, client_identifier.addr,
     )));
     }
 
+    // The backend has the last word on a relayed login, so it runs even
+    // when HBA trusts the client.
+    if pool.settings.scram_relay {
+        let transaction_mode = connection_transaction_mode(write, &pool, client_identifier).await?;
+        let server = authenticate_with_scram_relay(
+            read,
+            write,
+            &pool,
+            username_from_parameters,
+            pool_name,
+            &client_identifier.addr,
+        )
+        .await?;
+        let mut server_parameters = ServerParameters::new();
+        server_parameters.set_from_hashmap(&server.server_parameters_as_hashmap(), true);
+        *relayed_server = Some(server);
+        let operator_managed_keys =
+            Some(pool.database.server_pool().operator_managed_startup_keys());
+        return Ok((transaction_mode, server_parameters, operator_managed_keys));
+    }
+
     if client_identifier.is_talos || hba_decision == CheckResult::Trust {
         // Pass, client already authenticated (talos) or HBA Trust
     } else if pool.settings.user.auth_pam_service.is_some() {
@@ -335,6 +551,7 @@ where
         )
         .await?;
     } else {
+        record_auth_failure("unsupported_method", &client_identifier.addr);
         warn!("[{username_from_parameters}@{pool_name}] unsupported password type");
         error_response_terminal(
             write,
@@ -347,7 +564,7 @@ where
         )));
     }
 
-    let transaction_mode = pool.settings.pool_mode == PoolMode::Transaction;
+    let transaction_mode = connection_transaction_mode(write, &pool, client_identifier).await?;
     *prepared_statements_enabled = transaction_mode && pool.prepared_statement_cache.is_some();
 
     let server_parameters = match pool.get_server_parameters().await {
@@ -414,6 +631,7 @@ where
     let password_response = match vec_to_string(password_response) {
         Ok(p) => p,
         Err(err) => {
+            record_auth_failure("protocol_error", client_addr);
             error!("[{username_from_parameters}@{pool_name}] PAM: failed to read password from {client_addr}: {err}");
             error_response_terminal(
                 write,
@@ -432,6 +650,7 @@ where
     ) {
         Ok(_) => (),
         Err(err) => {
+            record_auth_failure("bad_password", client_addr);
             error!(
                 "[{username_from_parameters}@{pool_name}] PAM authentication failed from {client_addr} (service={service}): {err}"
             );
@@ -486,6 +705,7 @@ where
     )) {
         Ok(client_first_message) => client_first_message,
         Err(err) => {
+            record_auth_failure("protocol_error", client_addr);
             warn!("[{username_from_parameters}@{pool_name}] SCRAM: client first message parse error from {client_addr}: {err}");
             error_response_terminal(
                     write,
@@ -515,6 +735,7 @@ where
     )) {
         Ok(client_final_message) => client_final_message,
         Err(err) => {
+            record_auth_failure("protocol_error", client_addr);
             warn!(
                 "[{username_from_parameters}@{pool_name}] SCRAM: client final message parse error from {client_addr}: {err}"
             );
@@ -538,6 +759,7 @@ where
     ) {
         Ok(result) => result,
         Err(err) => {
+            record_auth_failure("bad_password", client_addr);
             warn!(
                 "[{username_from_parameters}@{pool_name}] SCRAM: server final message error from {client_addr}: {err}"
             );
@@ -557,6 +779,50 @@ where
     Ok(Some(client_key))
 }
 
+/// Relays the client's SCRAM exchange to a new backend connection of a
+/// `scram_relay` pool and returns that connection. PostgreSQL checks the
+/// password; a refusal reaches the client as PostgreSQL sent it.
+async fn authenticate_with_scram_relay<S, T>(
+    read: &mut S,
+    write: &mut T,
+    pool: &ConnectionPool,
+    username_from_parameters: &str,
+    pool_name: &str,
+    client_addr: &str,
+) -> Result<Server, Error>
+where
+    S: AsyncReadExt + Unpin,
+    T: AsyncWriteExt + Unpin,
+{
+    let (relay, client_half) = scram_relay::channel();
+    let (server, relayed) = tokio::join!(
+        pool.database.server_pool().create_relayed_connection(relay),
+        scram_relay::relay_to_client(read, write, client_half),
+    );
+    if let Err(err) = relayed {
+        let reason = match err {
+            Error::AuthError(_) => "bad_password",
+            _ => "protocol_error",
+        };
+        record_auth_failure(reason, client_addr);
+        warn!("[{username_from_parameters}@{pool_name}] relayed SCRAM login from {client_addr} failed: {err}");
+        return Err(err);
+    }
+    match server {
+        Ok(server) => Ok(server),
+        Err(err) => {
+            error!("[{username_from_parameters}@{pool_name}] relayed SCRAM connection for {client_addr} failed: {err}");
+            error_response_terminal(
+                write,
+                &format!("could not open a server connection for {username_from_parameters}@{pool_name}. Please try again later."),
+                "08006",
+            )
+            .await?;
+            Err(err)
+        }
+    }
+}
+
 /// Authenticate a user with MD5
 async fn authenticate_with_md5<S, T>(
     read: &mut S,
@@ -575,6 +841,7 @@ where
     let password_response = read_password(read).await?;
     let except_md5_hash = md5_hash_second_pass(pool_password.strip_prefix("md5").unwrap(), &salt);
     if except_md5_hash != password_response {
+        record_auth_failure("bad_password", client_addr);
         error!(
             "[{username_from_parameters}@{}] MD5 authentication failed from {client_addr}",
             pool.address.pool_name
@@ -612,6 +879,7 @@ where
     let jwt_token = match vec_to_string(jwt_token_response) {
         Ok(p) => p,
         Err(err) => {
+            record_auth_failure("protocol_error", client_addr);
             error!("[{username_from_parameters}@{pool_name}] JWT: failed to parse token from {client_addr}: {err}");
             error_response_terminal(
                 write,
@@ -627,6 +895,7 @@ where
     let jwt_user_name = match get_user_name_from_jwt(jwt_pub_key, jwt_token).await {
         Ok(u) => u,
         Err(err) => {
+            record_auth_failure("bad_password", client_addr);
             error!("[{username_from_parameters}@{pool_name}] JWT: validation failed from {client_addr}: {err}");
             error_response_terminal(
                 write,
@@ -640,6 +909,7 @@ where
         }
     };
     if !jwt_user_name.eq(username_from_parameters) {
+        record_auth_failure("bad_password", client_addr);
         error!("[{username_from_parameters}@{pool_name}] JWT: username mismatch from {client_addr} (token={jwt_user_name})");
         error_response_terminal(
             write,
@@ -693,6 +963,7 @@ where
                      Please try again later."
                 )
             } else {
+                record_auth_failure("no_pool", &client_identifier.addr);
                 format!(
                     "No connection pool configured for database: {pool_name}, \
                      user: {username}. Please check your connection parameters."
@@ -727,6 +998,7 @@ where
         Ok(None) => {
             // User not found
             auth_fail!(aq_state);
+            record_auth_failure("bad_password", &client_identifier.addr);
             warn!("[{username}@{pool_name}] auth_query: user not found");
             wrong_password(write, username).await?;
             return Err(Error::AuthError(format!(
@@ -748,15 +1020,15 @@ where
     // 4. HBA check
     let hba_decision = eval_hba_for_pool_password(&cache_entry.password_hash, client_identifier);
     if hba_decision == CheckResult::Deny {
-        error_response_terminal(
-            write,
-            &format!(
+        record_auth_failure("hba_denied", &client_identifier.addr);
+        let message = hba_denied_text(
+            client_identifier,
+            format!(
                 "Connection from IP address {} to {}@{} is not permitted by HBA configuration.",
                 client_identifier.addr, username, pool_name
             ),
-            "28000",
-        )
-        .await?;
+        );
+        error_response_terminal(write, &message, "28000").await?;
         return Err(Error::HbaForbiddenError(format!(
             "HBA denied auth_query user '{username}' from {:?}",
             client_identifier.addr,
@@ -815,6 +1087,7 @@ where
             }
             if !auth_ok {
                 auth_fail!(aq_state);
+                record_auth_failure("bad_password", &client_identifier.addr);
                 warn!(
                     "[{username}@{pool_name}] auth_query: MD5 authentication failed (refetch did not match or was rate-limited)"
                 );
@@ -856,6 +1129,7 @@ where
         let client_first = match parse_client_first_message(String::from_utf8_lossy(&first_msg)) {
             Ok(msg) => msg,
             Err(err) => {
+                record_auth_failure("protocol_error", &client_identifier.addr);
                 warn!("[{username}@{pool_name}] auth_query: SCRAM client first message parse error: {err}");
                 error_response_terminal(
                     write,
@@ -881,6 +1155,7 @@ where
         let client_final = match parse_client_final_message(String::from_utf8_lossy(&final_msg)) {
             Ok(msg) => msg,
             Err(err) => {
+                record_auth_failure("protocol_error", &client_identifier.addr);
                 warn!("[{username}@{pool_name}] auth_query: SCRAM client final message parse error: {err}");
                 error_response_terminal(
                     write,
@@ -913,6 +1188,7 @@ where
                 // so we can't retry with a re-fetched verifier using the same proof.
                 // Invalidate cache so next reconnect gets fresh verifier.
                 auth_fail!(aq_state);
+                record_auth_failure("bad_password", &client_identifier.addr);
                 cache.invalidate(username);
                 error!(
                     "[{username}@{pool_name}] auth_query: SCRAM authentication failed, cache invalidated"
@@ -924,6 +1200,7 @@ where
             }
         }
     } else {
+        record_auth_failure("unsupported_method", &client_identifier.addr);
         error_response_terminal(
             write,
             "Unsupported authentication method for auth_query user.",
@@ -956,7 +1233,8 @@ where
                 }
             };
 
-            let transaction_mode = pool.settings.pool_mode == PoolMode::Transaction;
+            let transaction_mode =
+                connection_transaction_mode(write, &pool, client_identifier).await?;
             *prepared_statements_enabled =
                 transaction_mode && pool.prepared_statement_cache.is_some();
 
@@ -1033,7 +1311,8 @@ where
             // Do NOT change client_identifier.username — stay as the dynamic user
             // so that Client.username matches the pool's user for get_pool() lookups.
 
-            let transaction_mode = pool.settings.pool_mode == PoolMode::Transaction;
+            let transaction_mode =
+                connection_transaction_mode(write, &pool, client_identifier).await?;
             *prepared_statements_enabled =
                 transaction_mode && pool.prepared_statement_cache.is_some();
 
@@ -0,0 +1,110 @@
+//! SCRAM relay for `scram_relay` pools.
+//!
+//! The client's SCRAM-SHA-256 exchange is passed through to a backend
+//! connection opened for that client alone, so PostgreSQL checks the real
+//! credentials and pg_doorman holds neither a password nor a verifier.
+//! The backend half runs inside `Server::startup`, the client half in
+//! [`relay_to_client`]; the two talk over a pair of channels, so neither
+//! side needs the other's stream.
+
+use bytes::BytesMut;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::mpsc;
+
+use crate::errors::Error;
+use crate::messages::constants::{SASL, SASL_FINAL};
+use crate::messages::{
+    error_response_terminal, read_password, scram_server_response, scram_start_challenge,
+};
+
+/// A message from the backend that the client has to see during login.
+#[derive(Debug)]
+pub enum RelayMessage {
+    /// `AuthenticationSASL`, `AuthenticationSASLContinue` or
+    /// `AuthenticationSASLFinal` with its data.
+    Sasl { code: i32, data: BytesMut },
+    /// The backend refused the login.
+    Error { sqlstate: String, message: String },
+}
+
+/// Backend half of the relay, driven by `Server::startup`.
+pub struct ScramRelay {
+    messages: mpsc::Sender<RelayMessage>,
+    responses: mpsc::Receiver<Vec<u8>>,
+}
+
+/// Client half of the relay, driven by [`relay_to_client`].
+pub struct ScramRelayClient {
+    messages: mpsc::Receiver<RelayMessage>,
+    responses: mpsc::Sender<Vec<u8>>,
+}
+
+/// Creates the two connected halves of a relay.
+pub fn channel() -> (ScramRelay, ScramRelayClient) {
+    let (messages_tx, messages_rx) = mpsc::channel(1);
+    let (responses_tx, responses_rx) = mpsc::channel(1);
+    (
+        ScramRelay {
+            messages: messages_tx,
+            responses: responses_rx,
+        },
+        ScramRelayClient {
+            messages: messages_rx,
+            responses: responses_tx,
+        },
+    )
+}
+
+impl ScramRelay {
+    /// Passes one backend message to the client. Returns `false` once the
+    /// client has gone.
+    pub async fn send(&mut self, message: RelayMessage) -> bool {
+        self.messages.send(message).await.is_ok()
+    }
+
+    /// The client's `SASLInitialResponse` or `SASLResponse` body, or `None`
+    /// once the client has gone.
+    pub async fn response(&mut self) -> Option<Vec<u8>> {
+        self.responses.recv().await
+    }
+}
+
+/// Plays the backend's SASL messages to the client and sends its answers
+/// back until the backend half is dropped. Only `SCRAM-SHA-256` is offered
+/// to the client: the client's TLS session is not the backend's, so
+/// channel binding cannot survive the relay. A refused login reaches the
+/// client with the backend's own SQLSTATE and message, and is returned as
+/// `Error::AuthError`.
+pub async fn relay_to_client<S, T>(
+    read: &mut S,
+    write: &mut T,
+    mut relay: ScramRelayClient,
+) -> Result<(), Error>
+where
+    S: AsyncReadExt + Unpin,
+    T: AsyncWriteExt + Unpin,
+{
+    while let Some(message) = relay.messages.recv().await {
+        match message {
+            RelayMessage::Sasl { code, data } => {
+                if code == SASL {
+                    scram_start_challenge(write).await?;
+                } else {
+                    scram_server_response(write, code, &String::from_utf8_lossy(&data)).await?;
+                }
+                if code == SASL_FINAL {
+                    continue;
+                }
+                let response = read_password(read).await?;
+                if relay.responses.send(response).await.is_err() {
+                    break;
+                }
+            }
+            RelayMessage::Error { sqlstate, message } => {
+                error_response_terminal(write, &message, &sqlstate).await?;
+                return Err(Error::AuthError(format!("{sqlstate}: {message}")));
+            }
+        }
+    }
+    Ok(())
+}
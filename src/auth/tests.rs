@@ -107,10 +107,90 @@ fn test_admin_authentication() {
         let mut reader = MockReader::new(vec![password_hash]);
         let mut writer = MockWriter::new();
 
-        let result = authenticate_admin(&mut reader, &mut writer, "admin").await;
+        let result =
+            authenticate_admin(&mut reader, &mut writer, "admin", "pgdoorman", "127.0.0.1").await;
 
         // This test might fail due to the need for more sophisticated mocking
         // of the get_config function
         assert!(result.is_ok());
     });
 }
+
+#[tokio::test]
+async fn test_admin_scram_secret_uses_sasl() {
+    let secret = "SCRAM-SHA-256$4096:p2j/1lMdQF6r1dD9I9f7PQ==$H3xt5yh7lwSq9zUPYwHovRu3FyUCCXchG/skydJRa9o=:5xU6Wj/GNg3UnN2uQIx3ezx7uZyzGeM5NrvSJRIxnlw=";
+    // Client answers with a garbage SASLInitialResponse.
+    let mut reader = std::io::Cursor::new(b"p\0\0\0\x08junk".to_vec());
+    let mut writer: Vec<u8> = Vec::new();
+
+    let result = verify_admin_password(
+        &mut reader,
+        &mut writer,
+        "admin",
+        secret,
+        "admin",
+        "pgdoorman",
+        "127.0.0.1",
+    )
+    .await;
+
+    assert!(matches!(result, Err(Error::ScramClientError(_))));
+    // AuthenticationSASL (R, code 10), never AuthenticationMD5Password (code 5).
+    assert_eq!(writer[0], b'R');
+    assert_eq!(&writer[5..9], &10i32.to_be_bytes());
+}
+
+#[tokio::test]
+async fn test_admin_plain_password_uses_md5() {
+    let mut reader = std::io::Cursor::new(Vec::new());
+    let mut writer: Vec<u8> = Vec::new();
+
+    let result = verify_admin_password(
+        &mut reader,
+        &mut writer,
+        "admin",
+        "secret",
+        "admin",
+        "pgdoorman",
+        "127.0.0.1",
+    )
+    .await;
+
+    assert!(result.is_err());
+    assert_eq!(writer[0], b'R');
+    assert_eq!(&writer[5..9], &5i32.to_be_bytes());
+}
+
+#[test]
+fn test_client_subnet_groups_addresses_by_network() {
+    assert_eq!(client_subnet("10.1.2.3:5432"), "10.1.2.0/24");
+    assert_eq!(
+        client_subnet("[2001:db8:1:2:3:4:5:6]:5432"),
+        "2001:db8:1:2::/64"
+    );
+    assert_eq!(client_subnet("unix:"), "local");
+}
+
+#[test]
+fn test_resolve_connection_pool_mode() {
+    use crate::config::PoolMode::{Session, Transaction};
+
+    assert_eq!(
+        resolve_connection_pool_mode(Transaction, false, None),
+        Ok(Transaction)
+    );
+    assert_eq!(
+        resolve_connection_pool_mode(Transaction, false, Some(Transaction)),
+        Ok(Transaction)
+    );
+    assert_eq!(
+        resolve_connection_pool_mode(Transaction, true, Some(Session)),
+        Ok(Session)
+    );
+    assert!(resolve_connection_pool_mode(Transaction, false, Some(Session)).is_err());
+    assert_eq!(
+        resolve_connection_pool_mode(Session, false, Some(Session)),
+        Ok(Session)
+    );
+    assert!(resolve_connection_pool_mode(Session, true, Some(Transaction)).is_err());
+}
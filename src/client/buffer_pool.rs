@@ -1,31 +1,105 @@
 use bytes::BytesMut;
 use std::cell::RefCell;
 use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicI64, AtomicU64, AtomicUsize, Ordering};
 
 const DEFAULT_BUFFER_CAPACITY: usize = 8192;
-const BUFFER_SHRINK_THRESHOLD: usize = 4 * DEFAULT_BUFFER_CAPACITY; // 32KB
-const MAX_POOL_SIZE: usize = 512; // ~5MB per thread
+const DEFAULT_MAX_BUFFER_SIZE: usize = 4 * DEFAULT_BUFFER_CAPACITY; // 32KB
+const DEFAULT_MAX_RETAINED: usize = 512; // ~5MB per thread
+
+// Sizing from `general.buffer_pool_*`, set by `configure` on load and RELOAD.
+static INITIAL_CAPACITY: AtomicUsize = AtomicUsize::new(DEFAULT_BUFFER_CAPACITY);
+static MAX_BUFFER_SIZE: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_BUFFER_SIZE);
+static MAX_RETAINED: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_RETAINED);
 
 thread_local! {
-    static LOCAL_POOL: RefCell<Vec<BytesMut>> = RefCell::new(Vec::with_capacity(MAX_POOL_SIZE));
+    static LOCAL_POOL: RefCell<Vec<BytesMut>> = const { RefCell::new(Vec::new()) };
+}
+
+// Process-wide counters across all thread-local pools, read by SHOW MEM.
+static CHECKED_OUT: AtomicI64 = AtomicI64::new(0);
+static POOLED: AtomicI64 = AtomicI64::new(0);
+static HITS: AtomicU64 = AtomicU64::new(0);
+static MISSES: AtomicU64 = AtomicU64::new(0);
+static DROPPED_OVERSIZED: AtomicU64 = AtomicU64::new(0);
+
+/// Applies `general.buffer_pool_*`. Buffers already parked keep their
+/// capacity; free lists over the new `max_retained` drain as buffers are
+/// taken out.
+pub fn configure(initial_capacity: usize, max_retained: usize, max_buffer_size: usize) {
+    INITIAL_CAPACITY.store(initial_capacity, Ordering::Relaxed);
+    MAX_RETAINED.store(max_retained, Ordering::Relaxed);
+    MAX_BUFFER_SIZE.store(max_buffer_size, Ordering::Relaxed);
+}
+
+/// Snapshot of the buffer pool counters summed over all worker threads.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BufferPoolStats {
+    /// Buffers held by a `PooledBuffer` right now.
+    pub checked_out: i64,
+    /// Buffers parked in thread-local free lists.
+    pub pooled: i64,
+    /// Acquisitions served from a free list.
+    pub hits: u64,
+    /// Acquisitions that had to allocate a new buffer.
+    pub misses: u64,
+    /// Buffers dropped on release for growing past `max_buffer_size`.
+    pub dropped_oversized: u64,
+}
+
+impl BufferPoolStats {
+    /// Buffers currently allocated by the pool, in use or parked.
+    pub fn allocated(&self) -> i64 {
+        self.checked_out + self.pooled
+    }
+
+    /// Share of acquisitions served without allocating, in percent.
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 * 100.0 / total as f64
+        }
+    }
+}
+
+pub fn buffer_pool_stats() -> BufferPoolStats {
+    BufferPoolStats {
+        checked_out: CHECKED_OUT.load(Ordering::Relaxed),
+        pooled: POOLED.load(Ordering::Relaxed),
+        hits: HITS.load(Ordering::Relaxed),
+        misses: MISSES.load(Ordering::Relaxed),
+        dropped_oversized: DROPPED_OVERSIZED.load(Ordering::Relaxed),
+    }
 }
 
 /// Acquire a buffer from the thread-local pool or create a new one.
 #[inline]
 fn acquire_buffer() -> BytesMut {
-    LOCAL_POOL.with(|pool| {
-        pool.borrow_mut()
-            .pop()
-            .unwrap_or_else(|| BytesMut::with_capacity(DEFAULT_BUFFER_CAPACITY))
-    })
+    CHECKED_OUT.fetch_add(1, Ordering::Relaxed);
+    let pooled = LOCAL_POOL.with(|pool| pool.borrow_mut().pop());
+    match pooled {
+        Some(buffer) => {
+            POOLED.fetch_sub(1, Ordering::Relaxed);
+            HITS.fetch_add(1, Ordering::Relaxed);
+            buffer
+        }
+        None => {
+            MISSES.fetch_add(1, Ordering::Relaxed);
+            BytesMut::with_capacity(INITIAL_CAPACITY.load(Ordering::Relaxed))
+        }
+    }
 }
 
 /// Return a buffer to the thread-local pool.
 /// If the buffer is too large, it is dropped instead to reclaim memory.
 #[inline]
 fn release_buffer(mut buffer: BytesMut) {
-    if buffer.capacity() > BUFFER_SHRINK_THRESHOLD {
+    CHECKED_OUT.fetch_sub(1, Ordering::Relaxed);
+    if buffer.capacity() > MAX_BUFFER_SIZE.load(Ordering::Relaxed) {
         // Drop it, don't pollute the pool with huge buffers
+        DROPPED_OVERSIZED.fetch_add(1, Ordering::Relaxed);
         return;
     }
 
@@ -34,8 +108,9 @@ fn release_buffer(mut buffer: BytesMut) {
 
     LOCAL_POOL.with(|pool| {
         if let Ok(mut pool) = pool.try_borrow_mut() {
-            if pool.len() < MAX_POOL_SIZE {
+            if pool.len() < MAX_RETAINED.load(Ordering::Relaxed) {
                 pool.push(buffer);
+                POOLED.fetch_add(1, Ordering::Relaxed);
             }
         }
         // If borrow fails or pool is full, just drop the buffer
@@ -58,8 +133,10 @@ impl PooledBuffer {
     #[inline]
     pub fn shrink_if_needed(&mut self) {
         // self.0 is always Some during normal usage
-        if self.capacity() > BUFFER_SHRINK_THRESHOLD {
-            self.0 = Some(acquire_buffer());
+        if self.capacity() > MAX_BUFFER_SIZE.load(Ordering::Relaxed) {
+            if let Some(old) = self.0.replace(acquire_buffer()) {
+                release_buffer(old);
+            }
         }
     }
 }
@@ -95,3 +172,55 @@ impl Drop for PooledBuffer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn acquisitions_are_counted_as_hit_or_miss() {
+        let before = buffer_pool_stats();
+        drop(PooledBuffer::new());
+        let _reused = PooledBuffer::new();
+        let after = buffer_pool_stats();
+        assert!(after.hits + after.misses >= before.hits + before.misses + 2);
+    }
+
+    #[test]
+    fn oversized_buffers_are_dropped_not_reused() {
+        let before = buffer_pool_stats();
+        let mut buffer = PooledBuffer::new();
+        buffer.reserve(4 * DEFAULT_MAX_BUFFER_SIZE);
+        drop(buffer);
+        assert!(buffer_pool_stats().dropped_oversized > before.dropped_oversized);
+    }
+
+    #[test]
+    fn configure_sets_capacity_of_new_buffers() {
+        configure(1024, DEFAULT_MAX_RETAINED, DEFAULT_MAX_BUFFER_SIZE);
+        // A fresh thread has an empty free list, so the buffer is allocated.
+        let capacity = std::thread::spawn(|| PooledBuffer::new().capacity())
+            .join()
+            .unwrap();
+        configure(
+            DEFAULT_BUFFER_CAPACITY,
+            DEFAULT_MAX_RETAINED,
+            DEFAULT_MAX_BUFFER_SIZE,
+        );
+        assert!(
+            (1024..DEFAULT_BUFFER_CAPACITY).contains(&capacity),
+            "{capacity}"
+        );
+    }
+
+    #[test]
+    fn hit_rate_handles_no_acquisitions() {
+        assert_eq!(BufferPoolStats::default().hit_rate(), 0.0);
+        let stats = BufferPoolStats {
+            hits: 3,
+            misses: 1,
+            ..Default::default()
+        };
+        assert_eq!(stats.hit_rate(), 75.0);
+    }
+}
@@ -9,9 +9,13 @@ use tokio::io::BufReader;
 
 use crate::client::buffer_pool::PooledBuffer;
 use crate::messages::{error_response, Parse};
+use crate::pool::target_session::{
+    backend_role, classify_in_background, select_backend, TargetSessionAttrs,
+};
 use crate::pool::{get_pool, ClientServerMap, ConnectionPool};
-use crate::server::ServerParameters;
+use crate::server::{Server, ServerParameters};
 use crate::stats::{ClientStats, PreparedCacheSnapshot, ServerStats};
+use crate::utils::debug_messages::RecentMessageTypes;
 
 /// Key for prepared statement cache - avoids string allocations for anonymous statements
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -218,6 +222,17 @@ impl PreparedStatementCache {
         }
     }
 
+    /// `(client name, server name)` of every Named entry, sorted by client name.
+    pub fn named_mappings(&self) -> Vec<(String, String)> {
+        let mut mappings: Vec<(String, String)> = self
+            .named
+            .iter()
+            .map(|(name, cached)| (name.clone(), cached.server_name().to_string()))
+            .collect();
+        mappings.sort_unstable();
+        mappings
+    }
+
     /// Clears both Named and Anonymous maps.
     #[inline]
     pub fn clear(&mut self) {
@@ -558,6 +573,13 @@ pub struct Client<S, T> {
     /// Clients want to talk to admin database.
     pub(crate) admin: bool,
 
+    /// Admin login limited to `SHOW` and `TEST HBA`.
+    pub(crate) admin_readonly: bool,
+
+    /// A session-only feature in transaction mode was already logged for
+    /// this client; later hits only bump the metric.
+    pub(crate) session_feature_warned: bool,
+
     /// Last server process stats we talked to.
     pub(crate) last_server_stats: Option<Arc<ServerStats>>,
 
@@ -587,11 +609,57 @@ pub struct Client<S, T> {
 
     pub(crate) client_last_messages_in_tx: PooledBuffer,
 
+    /// Types of the last messages read from the client, logged when the
+    /// stream desyncs.
+    pub(crate) recent_messages: RecentMessageTypes,
+
+    /// `server_id` of the server used for the previous transaction, asked
+    /// for again at checkout in `prefer_last_server` pools.
+    pub(crate) last_server_id: Option<i32>,
+
     /// Pending BEGIN message for deferred connection optimization.
     /// When client sends standalone "begin;", we synthesize response
     /// and defer actual BEGIN until next query arrives.
     pub(crate) client_pending_begin: Option<BytesMut>,
 
+    /// Per-pool `max_query_duration` in milliseconds (0 = disabled).
+    pub(crate) max_query_duration_ms: u64,
+
+    /// Per-pool `max_result_rows` (0 = disabled).
+    pub(crate) max_result_rows: u64,
+
+    /// The current simple query may be re-run on another server if its
+    /// connection breaks before anything reaches the client
+    /// (`retry_readonly_on_disconnect`). Cleared once a response arrives.
+    pub(crate) retry_readonly: bool,
+
+    /// `pg_doorman.query_timeout_ms` asked for by the client, capped by
+    /// `max_query_duration_ms`.
+    pub(crate) query_timeout_ms: Option<u64>,
+
+    /// Per-pool `server_max_age` in milliseconds (0 = disabled).
+    pub(crate) server_max_age_ms: u64,
+
+    /// `pg_doorman.shard` from the StartupMessage; picks the shard's pool at
+    /// checkout.
+    pub(crate) shard_key: Option<String>,
+
+    /// `target_session_attrs` from the StartupMessage; picks the member of a
+    /// multi-host pool at checkout.
+    pub(crate) target_session_attrs: Option<TargetSessionAttrs>,
+
+    /// `replication` from the StartupMessage. Such a client gets its own
+    /// walsender connection and is relayed byte for byte.
+    pub(crate) replication: Option<String>,
+
+    /// Backend connection of a `scram_relay` client, opened during its
+    /// login. Every checkout of the session gets this server instead of
+    /// one from the pool.
+    pub(crate) relayed_server: Option<Box<Server>>,
+
+    /// Slot in the `max_connections_per_tag` budget; released when the client drops.
+    pub(crate) _tag_guard: Option<super::tags::TagGuard>,
+
     /// Raw fd of the client TCP socket. Stored before tokio::io::split()
     /// because ReadHalf/WriteHalf do not expose as_raw_fd().
     /// Used for client migration during graceful reload.
@@ -644,11 +712,88 @@ where
             ));
     }
 
+    /// Records every cached Named statement for
+    /// `SHOW PREPARED_STATEMENTS CLIENTS`; a migrated client arrives with
+    /// them already cached. Later changes are recorded one by one.
+    pub(crate) fn publish_prepared_names(&self) {
+        for (client_name, server_name) in self.prepared.cache.named_mappings() {
+            self.stats.add_prepared_name(&client_name, &server_name);
+        }
+    }
+
+    /// Picks the member of a multi-host pool that matches
+    /// `target_session_attrs` among the members whose role is known. The
+    /// others are classified in the background for later clients. `None`
+    /// when the pool has no `hosts`, so the client stays on `server_host`.
+    async fn get_target_pool(
+        &mut self,
+        attrs: TargetSessionAttrs,
+    ) -> Option<Result<ConnectionPool, Error>> {
+        let members: Vec<(String, String, u16)> = {
+            let config = crate::config::config_arc();
+            let pool_config = config.pools.get(&self.pool_name)?;
+            if pool_config.hosts.is_empty() {
+                return None;
+            }
+            pool_config
+                .backends(&self.pool_name)
+                .into_iter()
+                .map(|backend| (backend.pool_key, backend.host.to_string(), backend.port))
+                .collect()
+        };
+
+        let mut pools = Vec::with_capacity(members.len());
+        let mut roles = Vec::with_capacity(members.len());
+        for (pool_key, host, port) in members {
+            let Some(pool) = get_pool(&pool_key, &self.username) else {
+                continue;
+            };
+            let role = backend_role(&host, port);
+            if role.is_none() {
+                classify_in_background(&pool, &host, port);
+            }
+            pools.push(pool);
+            roles.push(role);
+        }
+
+        match select_backend(attrs, &roles) {
+            Ok(idx) => Some(Ok(pools.swap_remove(idx))),
+            Err(message) => {
+                if let Err(err) = error_response(&mut self.write, &message, "08000").await {
+                    return Some(Err(err));
+                }
+                Some(Err(Error::ClientError(message)))
+            }
+        }
+    }
+
     /// Retrieve connection pool, if it exists.
     /// Return an error to the client otherwise.
     pub(crate) async fn get_pool(&mut self) -> Result<ConnectionPool, Error> {
-        match get_pool(&self.pool_name, &self.username) {
+        if let Some(attrs) = self.target_session_attrs {
+            if let Some(result) = self.get_target_pool(attrs).await {
+                return result;
+            }
+        }
+        let pool = match self.shard_key.as_deref() {
+            Some(shard) => get_pool(
+                &crate::config::Pool::shard_pool_name(&self.pool_name, shard),
+                &self.username,
+            ),
+            None => get_pool(&self.pool_name, &self.username),
+        };
+        match pool {
             Some(pool) => Ok(pool),
+            // RELOAD dropped the shard after this client connected.
+            None if self.shard_key.is_some() => {
+                let shard = self.shard_key.as_deref().unwrap_or_default();
+                let message = format!(
+                    "unknown shard key \"{shard}\" for database \"{}\"",
+                    self.pool_name
+                );
+                error_response(&mut self.write, &message, "3D000").await?;
+                Err(Error::ClientError(message))
+            }
             None => {
                 error_response(
                     &mut self.write,
@@ -959,4 +1104,27 @@ mod cache_split_tests {
         assert_eq!(named, 50);
         assert_eq!(anon, 0);
     }
+
+    #[test]
+    fn named_mappings_list_server_names_of_named_entries_only() {
+        let mut cache = PreparedStatementCache::new(0);
+        let _ = cache.put(
+            PreparedStatementKey::Named("b_stmt".into()),
+            make_cached("DOORMAN_7", "SELECT 2"),
+        );
+        let mut async_entry = make_cached("DOORMAN_3", "SELECT 1");
+        async_entry.async_name = Some("DOORMAN_async_9".into());
+        let _ = cache.put(PreparedStatementKey::Named("a_stmt".into()), async_entry);
+        let _ = cache.put(
+            PreparedStatementKey::Anonymous(1),
+            make_cached("DOORMAN_1", "SELECT 3"),
+        );
+        assert_eq!(
+            cache.named_mappings(),
+            vec![
+                ("a_stmt".to_string(), "DOORMAN_async_9".to_string()),
+                ("b_stmt".to_string(), "DOORMAN_7".to_string()),
+            ]
+        );
+    }
 }
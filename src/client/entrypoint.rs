@@ -1,4 +1,5 @@
-use log::{error, info, warn};
+use log::{debug, error, info, warn};
+use std::net::SocketAddr;
 #[cfg(unix)]
 use std::os::unix::io::AsRawFd;
 use std::sync::atomic::Ordering;
@@ -16,7 +17,9 @@ use crate::utils::rate_limit::RateLimiter;
 use crate::transport::ClientTransport;
 
 use super::core::Client;
-use super::startup::{get_startup, startup_tls, ClientConnectionType};
+use super::startup::{
+    get_startup, startup_deadline, startup_tls, within_startup_deadline, ClientConnectionType,
+};
 
 /// Identity info returned from client_entrypoint for disconnect logging.
 pub struct ClientSessionInfo {
@@ -42,17 +45,19 @@ async fn drive_authenticated_client<S, T>(
     client_server_map: ClientServerMap,
     admin_only: bool,
     connection_id: u64,
+    default_database: Option<String>,
     #[cfg(unix)] raw_fd: Option<std::os::unix::io::RawFd>,
     #[cfg(all(unix, feature = "tls-migration"))] ssl_ptr: Option<crate::client::core::SslRawPtr>,
     log_client_connections: bool,
     log_label: &'static str,
+    deadline: Option<tokio::time::Instant>,
 ) -> Result<Option<ClientSessionInfo>, Error>
 where
     S: tokio::io::AsyncRead + Unpin + Send + 'static,
     T: tokio::io::AsyncWrite + Unpin + Send + 'static,
 {
     let peer = transport.peer_display();
-    match Client::startup(
+    let startup = Client::startup(
         read,
         write,
         transport,
@@ -60,13 +65,13 @@ where
         client_server_map,
         admin_only,
         connection_id,
+        default_database,
         #[cfg(unix)]
         raw_fd,
         #[cfg(all(unix, feature = "tls-migration"))]
         ssl_ptr,
-    )
-    .await
-    {
+    );
+    match within_startup_deadline(deadline, startup).await {
         Ok(mut client) => {
             if log_client_connections {
                 info!(
@@ -89,21 +94,84 @@ where
     }
 }
 
+/// Content type of a TLS handshake record, the first byte of a ClientHello.
+const TLS_HANDSHAKE_RECORD: u8 = 0x16;
+
+/// Negotiate TLS on a TCP client and run it. `direct` is set when the
+/// client skipped SSLRequest (`sslnegotiation=direct`); otherwise the
+/// caller has already answered the SSLRequest with `S`.
+#[allow(clippy::too_many_arguments)]
+async fn drive_tls_client(
+    stream: TcpStream,
+    addr: SocketAddr,
+    client_server_map: ClientServerMap,
+    admin_only: bool,
+    tls_acceptor: tokio_native_tls::TlsAcceptor,
+    tls_rate_limiter: Option<RateLimiter>,
+    connection_id: u64,
+    default_database: Option<String>,
+    log_client_connections: bool,
+    direct: bool,
+    deadline: Option<tokio::time::Instant>,
+) -> Result<Option<ClientSessionInfo>, Error> {
+    TLS_CONNECTION_COUNTER.fetch_add(1, Ordering::Relaxed);
+
+    if let Some(tls_rate_limiter) = tls_rate_limiter {
+        tls_rate_limiter.wait().await;
+    }
+
+    // Negotiate TLS.
+    let startup = startup_tls(
+        stream,
+        addr,
+        client_server_map,
+        admin_only,
+        tls_acceptor,
+        connection_id,
+        default_database,
+        direct,
+    );
+    match within_startup_deadline(deadline, startup).await {
+        Ok(mut client) => {
+            if log_client_connections {
+                info!(
+                    "[{}@{} #c{}] client connected from {addr} ({})",
+                    client.username,
+                    client.pool_name,
+                    client.connection_id,
+                    if direct { "direct TLS" } else { "TLS" }
+                );
+            }
+            let session_info = ClientSessionInfo {
+                username: client.username.clone(),
+                pool_name: client.pool_name.clone(),
+                connection_id: client.connection_id,
+            };
+            let result = client.handle().await;
+            if !client.is_admin() && result.is_err() {
+                warn!(
+                    "[{}@{} #c{}] client {} disconnected with error: {}",
+                    client.username,
+                    client.pool_name,
+                    client.connection_id,
+                    addr,
+                    result.as_ref().unwrap_err()
+                );
+                client.disconnect_stats();
+            }
+            result.map(|_| Some(session_info))
+        }
+        Err(err) => Err(err),
+    }
+}
+
 pub async fn client_entrypoint_too_many_clients_already(
     mut stream: TcpStream,
+    addr: SocketAddr,
     client_server_map: ClientServerMap,
 ) -> Result<(), Error> {
     crate::web::metrics::record_listener_rejection("too_many_clients");
-    let addr = match stream.peer_addr() {
-        Ok(addr) => addr,
-        Err(err) => {
-            return Err(Error::SocketError(format!(
-                "Failed to get peer address: {err:?}"
-            )));
-        }
-    };
-
-    match get_startup::<TcpStream>(&mut stream).await {
+    match within_startup_deadline(startup_deadline(), get_startup(&mut stream)).await {
         Ok((ClientConnectionType::Tls, _)) => {
             write_all_flush(&mut stream, b"N").await?;
             // здесь может быть ошибка SSL is not enabled on the server,
@@ -140,6 +208,36 @@ pub async fn client_entrypoint_too_many_clients_already(
     Ok(())
 }
 
+/// Reject a client whose IP is banned for repeated authentication failures.
+/// The startup packet is read only to answer it: no pool lookup, no
+/// authentication, and cancel requests are dropped.
+async fn client_entrypoint_banned(
+    mut stream: TcpStream,
+    addr: SocketAddr,
+    remaining: std::time::Duration,
+) -> Result<(), Error> {
+    crate::web::metrics::record_listener_rejection("auth_ban");
+    debug!(
+        "client {addr} rejected: banned for another {}s",
+        remaining.as_secs()
+    );
+    match within_startup_deadline(startup_deadline(), get_startup(&mut stream)).await {
+        Ok((ClientConnectionType::Tls, _)) => write_all_flush(&mut stream, b"N").await?,
+        Ok((ClientConnectionType::Startup, _)) => (),
+        Ok((ClientConnectionType::CancelQuery, _)) => return Ok(()),
+        Err(err) => return Err(err),
+    }
+    error_response_terminal(
+        &mut stream,
+        &format!(
+            "too many failed authentication attempts from this address, try again in {}s",
+            remaining.as_secs().max(1)
+        ),
+        "28000",
+    )
+    .await
+}
+
 /// Reject an inbound Unix socket client with a proper PostgreSQL ErrorResponse.
 ///
 /// The TCP rejection path above also handles TLS and cancel-request startups,
@@ -154,7 +252,7 @@ pub async fn client_entrypoint_too_many_clients_already_unix(
     connection_id: u64,
 ) -> Result<(), Error> {
     crate::web::metrics::record_listener_rejection("too_many_clients");
-    match get_startup::<UnixStream>(&mut stream).await {
+    match within_startup_deadline(startup_deadline(), get_startup(&mut stream)).await {
         Ok((ClientConnectionType::Tls, _)) => {
             // Unix sockets never negotiate TLS; mirror the main Unix entrypoint
             // and refuse the SSL request with the same error message.
@@ -183,78 +281,79 @@ pub async fn client_entrypoint_too_many_clients_already_unix(
 }
 
 /// Client entrypoint. Returns session identity on success for disconnect logging.
+///
+/// `addr` is the client address: the socket peer, or the source carried in
+/// the PROXY protocol header when `accept_proxy_protocol` is on.
+/// `default_database` is the accepting listener's default database, if any.
+#[allow(clippy::too_many_arguments)]
 pub async fn client_entrypoint(
     mut stream: TcpStream,
+    addr: SocketAddr,
     client_server_map: ClientServerMap,
     admin_only: bool,
     tls_acceptor: Option<tokio_native_tls::TlsAcceptor>,
     tls_rate_limiter: Option<RateLimiter>,
     connection_id: u64,
+    default_database: Option<String>,
 ) -> Result<Option<ClientSessionInfo>, Error> {
+    if let Some(remaining) = crate::auth::ban::banned_for(addr.ip(), std::time::Instant::now()) {
+        return client_entrypoint_banned(stream, addr, remaining)
+            .await
+            .map(|_| None);
+    }
+
     let config = get_config();
     let log_client_connections = config.general.log_client_connections;
     let tls_mode = config.general.tls_mode.clone();
+    let deadline = startup_deadline();
 
-    // Figure out if the client wants TLS or not.
-    let addr = match stream.peer_addr() {
-        Ok(addr) => addr,
-        Err(err) => {
-            return Err(Error::SocketError(format!(
-                "Failed to get peer address: {err:?}"
-            )));
+    // `sslnegotiation=direct` (PostgreSQL 17): the client starts the TLS
+    // handshake without an SSLRequest. A StartupMessage or SSLRequest
+    // begins with its length, whose first byte is 0, so a TLS handshake
+    // record is unambiguous.
+    if let Some(tls_acceptor) = tls_acceptor.as_ref() {
+        let mut first = [0u8; 1];
+        let peek = async { Ok::<_, Error>(stream.peek(&mut first).await) };
+        let peeked = within_startup_deadline(deadline, peek).await?;
+        if matches!(peeked, Ok(1) if first[0] == TLS_HANDSHAKE_RECORD) {
+            return drive_tls_client(
+                stream,
+                addr,
+                client_server_map,
+                admin_only,
+                tls_acceptor.clone(),
+                tls_rate_limiter,
+                connection_id,
+                default_database,
+                log_client_connections,
+                true,
+                deadline,
+            )
+            .await;
         }
-    };
+    }
 
-    match get_startup::<TcpStream>(&mut stream).await {
+    // Figure out if the client wants TLS or not.
+    match within_startup_deadline(deadline, get_startup(&mut stream)).await {
         // Client requested a TLS connection.
         Ok((ClientConnectionType::Tls, _)) => {
             // TLS settings are configured, will setup TLS now.
             if let Some(tls_acceptor) = tls_acceptor {
-                TLS_CONNECTION_COUNTER.fetch_add(1, Ordering::Relaxed);
                 write_all_flush(&mut stream, b"S").await?;
-
-                if let Some(tls_rate_limiter) = tls_rate_limiter {
-                    tls_rate_limiter.wait().await;
-                }
-
-                // Negotiate TLS.
-                match startup_tls(
+                drive_tls_client(
                     stream,
+                    addr,
                     client_server_map,
                     admin_only,
                     tls_acceptor,
+                    tls_rate_limiter,
                     connection_id,
+                    default_database,
+                    log_client_connections,
+                    false,
+                    deadline,
                 )
                 .await
-                {
-                    Ok(mut client) => {
-                        if log_client_connections {
-                            info!(
-                                "[{}@{} #c{}] client connected from {addr} (TLS)",
-                                client.username, client.pool_name, client.connection_id
-                            );
-                        }
-                        let session_info = ClientSessionInfo {
-                            username: client.username.clone(),
-                            pool_name: client.pool_name.clone(),
-                            connection_id: client.connection_id,
-                        };
-                        let result = client.handle().await;
-                        if !client.is_admin() && result.is_err() {
-                            warn!(
-                                "[{}@{} #c{}] client {} disconnected with error: {}",
-                                client.username,
-                                client.pool_name,
-                                client.connection_id,
-                                addr,
-                                result.as_ref().unwrap_err()
-                            );
-                            client.disconnect_stats();
-                        }
-                        result.map(|_| Some(session_info))
-                    }
-                    Err(err) => Err(err),
-                }
             }
             // TLS is not configured, we cannot offer it.
             else {
@@ -264,7 +363,7 @@ pub async fn client_entrypoint(
 
                 // Attempting regular startup. Client can disconnect now
                 // if they choose.
-                match get_startup::<TcpStream>(&mut stream).await {
+                match within_startup_deadline(deadline, get_startup(&mut stream)).await {
                     // Client accepted unencrypted connection.
                     Ok((ClientConnectionType::Startup, bytes)) => {
                         #[cfg(unix)]
@@ -281,12 +380,14 @@ pub async fn client_entrypoint(
                             client_server_map,
                             admin_only,
                             connection_id,
+                            default_database,
                             #[cfg(unix)]
                             raw_fd,
                             #[cfg(all(unix, feature = "tls-migration"))]
                             None, // no SSL for plain TCP
                             log_client_connections,
                             "plain",
+                            deadline,
                         )
                         .await
                     }
@@ -302,7 +403,9 @@ pub async fn client_entrypoint(
                     }
 
                     Err(err) => {
-                        crate::web::metrics::record_listener_rejection("invalid_startup");
+                        if err != Error::ClientStartupTimeout {
+                            crate::web::metrics::record_listener_rejection("invalid_startup");
+                        }
                         Err(err)
                     }
                 }
@@ -336,12 +439,14 @@ pub async fn client_entrypoint(
                 client_server_map,
                 admin_only,
                 connection_id,
+                default_database,
                 #[cfg(unix)]
                 raw_fd,
                 #[cfg(all(unix, feature = "tls-migration"))]
                 None, // no SSL for plain TCP
                 log_client_connections,
                 "plain",
+                deadline,
             )
             .await
         }
@@ -374,7 +479,9 @@ pub async fn client_entrypoint(
 
         // Something failed, probably the socket.
         Err(err) => {
-            crate::web::metrics::record_listener_rejection("invalid_startup");
+            if err != Error::ClientStartupTimeout {
+                crate::web::metrics::record_listener_rejection("invalid_startup");
+            }
             error!("#c{connection_id} client {addr} startup failed: {err}");
             Err(err)
         }
@@ -390,8 +497,9 @@ pub async fn client_entrypoint_unix(
 ) -> Result<Option<ClientSessionInfo>, Error> {
     let config = get_config();
     let log_client_connections = config.general.log_client_connections;
+    let deadline = startup_deadline();
 
-    match get_startup::<UnixStream>(&mut stream).await {
+    match within_startup_deadline(deadline, get_startup(&mut stream)).await {
         Ok((ClientConnectionType::Startup, bytes)) => {
             PLAIN_CONNECTION_COUNTER.fetch_add(1, Ordering::Relaxed);
             let raw_fd = Some(stream.as_raw_fd());
@@ -404,12 +512,14 @@ pub async fn client_entrypoint_unix(
                 client_server_map,
                 admin_only,
                 connection_id,
+                None,
                 #[cfg(unix)]
                 raw_fd,
                 #[cfg(all(unix, feature = "tls-migration"))]
                 None, // no SSL on Unix socket
                 log_client_connections,
                 "unix",
+                deadline,
             )
             .await
         }
@@ -452,7 +562,9 @@ pub async fn client_entrypoint_unix(
         }
 
         Err(err) => {
-            crate::web::metrics::record_listener_rejection("invalid_startup");
+            if err != Error::ClientStartupTimeout {
+                crate::web::metrics::record_listener_rejection("invalid_startup");
+            }
             error!("#c{connection_id} unix client startup failed: {err}");
             Err(err)
         }
@@ -1,6 +1,8 @@
+use log::warn;
+
 use crate::client::core::Client;
 use crate::errors::Error;
-use crate::messages::error_response;
+use crate::messages::{error_response, error_response_terminal};
 
 impl<S, T> Client<S, T>
 where
@@ -28,9 +30,12 @@ where
                 ).await
             }
             Error::CurrentMemoryUsage => {
+                // 53400 keeps a pooler-side rejection apart from a backend
+                // out_of_memory (53200) in client logs and error metrics.
+                crate::web::metrics::record_memory_limit_hit();
                 self.send_error_response(
-                    "Server is temporarily out of memory. Please try again later or reduce the size of your query.",
-                    "53200",
+                    "pooler memory limit exceeded (max_memory_usage), try again",
+                    "53400",
                     err,
                 ).await
             }
@@ -80,6 +85,38 @@ where
                     err,
                 ).await
             }
+            Error::ServerConnectionLost {
+                ref sqlstate,
+                ref message,
+            } => {
+                // Terminal, as from the backend itself: the client
+                // connection closes right after.
+                let message = format!(
+                    "server connection lost: {message}. The current transaction was aborted; please retry"
+                );
+                error_response_terminal(&mut self.write, &message, sqlstate).await?;
+                Err(err)
+            }
+            Error::ProtocolSyncError(ref msg) => {
+                // The stream is out of sync: nothing after this point can be
+                // parsed, so say why and close instead of forwarding garbage.
+                crate::web::metrics::record_protocol_error("client");
+                warn!(
+                    "[{}@{} #c{}] protocol desync from client {}: {msg}; last messages: {}",
+                    self.username,
+                    self.pool_name,
+                    self.connection_id,
+                    self.addr,
+                    self.recent_messages,
+                );
+                let _ = error_response_terminal(
+                    &mut self.write,
+                    "invalid frontend message: protocol out of sync",
+                    "08P01",
+                )
+                .await;
+                Err(err)
+            }
             Error::ProxyTimeout => {
                 self.send_error_response(
                     "Proxy operation timed out. Please try again later.",
@@ -16,9 +16,11 @@ use crate::config::{get_config, BackendAuthMethod};
 use crate::errors::Error;
 use crate::messages::config_socket::configure_tcp_socket;
 use crate::messages::Parse;
+use crate::pool::target_session::TargetSessionAttrs;
 use crate::pool::{get_pool, resolve_client_anon_cache_size, ClientServerMap, ConnectionPool};
 use crate::server::ServerParameters;
 use crate::stats::ClientStats;
+use crate::utils::debug_messages::RecentMessageTypes;
 
 use super::core::PreparedStatementState;
 
@@ -267,6 +269,25 @@ where
             buf.put_u8(0);
         }
 
+        // Shard key, target_session_attrs and query timeout: optional
+        // trailers, so older processes simply ignore them. A trailer is
+        // written empty when only a later one is set.
+        let has_query_timeout = self.query_timeout_ms.is_some();
+        if self.shard_key.is_some() || self.target_session_attrs.is_some() || has_query_timeout {
+            put_str(&mut buf, self.shard_key.as_deref().unwrap_or_default());
+        }
+        if self.target_session_attrs.is_some() || has_query_timeout {
+            put_str(
+                &mut buf,
+                self.target_session_attrs
+                    .map(|attrs| attrs.as_str())
+                    .unwrap_or_default(),
+            );
+        }
+        if let Some(ms) = self.query_timeout_ms {
+            buf.put_u64(ms);
+        }
+
         buf
     }
 }
@@ -318,6 +339,9 @@ struct DeserializedState {
     #[allow(dead_code)]
     use_tls: bool,
     backend_auth: Option<BackendAuthMethod>,
+    shard_key: Option<String>,
+    target_session_attrs: Option<TargetSessionAttrs>,
+    query_timeout_ms: Option<u64>,
 }
 
 struct PreparedEntry {
@@ -450,6 +474,24 @@ fn deserialize_state(mut buf: BytesMut) -> Result<DeserializedState, Error> {
         None
     };
 
+    let shard_key = if buf.remaining() > 0 {
+        Some(get_str(&mut buf)?).filter(|shard| !shard.is_empty())
+    } else {
+        None
+    };
+
+    let target_session_attrs = if buf.remaining() > 0 {
+        TargetSessionAttrs::parse(&get_str(&mut buf)?)
+    } else {
+        None
+    };
+
+    let query_timeout_ms = if buf.remaining() >= 8 {
+        Some(buf.get_u64())
+    } else {
+        None
+    };
+
     Ok(DeserializedState {
         connection_id,
         secret_key,
@@ -463,6 +505,9 @@ fn deserialize_state(mut buf: BytesMut) -> Result<DeserializedState, Error> {
         prepared_entries,
         use_tls,
         backend_auth,
+        shard_key,
+        target_session_attrs,
+        query_timeout_ms,
     })
 }
 
@@ -540,6 +585,8 @@ pub async fn reconstruct_client(
         client_server_map,
         stats,
         admin: false,
+        admin_readonly: false,
+        session_feature_warned: false,
         last_server_stats: None,
         connected_to_server: false,
         session_xact_start: None,
@@ -548,8 +595,20 @@ pub async fn reconstruct_client(
         server_parameters: state.server_parameters,
         prepared,
         client_last_messages_in_tx: PooledBuffer::new(),
+        recent_messages: RecentMessageTypes::default(),
+        last_server_id: None,
         max_memory_usage: config.general.max_memory_usage.as_bytes(),
         client_pending_begin: None,
+        max_query_duration_ms: 0,
+        max_result_rows: 0,
+        retry_readonly: false,
+        query_timeout_ms: state.query_timeout_ms,
+        server_max_age_ms: 0,
+        shard_key: state.shard_key,
+        target_session_attrs: state.target_session_attrs,
+        replication: None,
+        relayed_server: None,
+        _tag_guard: None,
         #[cfg(unix)]
         raw_fd,
         #[cfg(all(unix, feature = "tls-migration"))]
@@ -646,6 +705,8 @@ pub async fn reconstruct_tls_client(
         client_server_map,
         stats,
         admin: false,
+        admin_readonly: false,
+        session_feature_warned: false,
         last_server_stats: None,
         connected_to_server: false,
         session_xact_start: None,
@@ -654,8 +715,20 @@ pub async fn reconstruct_tls_client(
         server_parameters: state.server_parameters,
         prepared,
         client_last_messages_in_tx: PooledBuffer::new(),
+        recent_messages: RecentMessageTypes::default(),
+        last_server_id: None,
         max_memory_usage: config.general.max_memory_usage.as_bytes(),
         client_pending_begin: None,
+        max_query_duration_ms: 0,
+        max_result_rows: 0,
+        retry_readonly: false,
+        query_timeout_ms: state.query_timeout_ms,
+        server_max_age_ms: 0,
+        shard_key: state.shard_key,
+        target_session_attrs: state.target_session_attrs,
+        replication: None,
+        relayed_server: None,
+        _tag_guard: None,
         #[cfg(unix)]
         raw_fd,
         #[cfg(all(unix, feature = "tls-migration"))]
@@ -1038,7 +1111,7 @@ pub async fn migration_receiver_task(
                                     if !client.is_admin() && result.is_err() {
                                         client.disconnect_stats();
                                     }
-                                    CURRENT_CLIENT_COUNT.fetch_add(-1, Ordering::SeqCst);
+                                    crate::app::admission::release();
                                 }
                                 Err(e) => {
                                     error!("failed to reconstruct migrated TLS client: {e}");
@@ -1083,7 +1156,7 @@ pub async fn migration_receiver_task(
                                 );
                                 client.disconnect_stats();
                             }
-                            CURRENT_CLIENT_COUNT.fetch_add(-1, Ordering::SeqCst);
+                            crate::app::admission::release();
                         }
                         Err(e) => {
                             error!("failed to reconstruct migrated client: {e}");
@@ -1412,6 +1485,58 @@ mod tests {
         );
     }
 
+    #[test]
+    fn deserialize_shard_key_trailer() {
+        let header = || {
+            let mut buf = BytesMut::new();
+            buf.put_u32(MIGRATION_MAGIC);
+            buf.put_u16(MIGRATION_VERSION);
+            buf.put_u64(1);
+            buf.put_i32(1);
+            buf.put_u8(0);
+            put_str(&mut buf, "db");
+            put_str(&mut buf, "u");
+            buf.put_u16(5432);
+            buf.put_u8(9);
+            buf.put_slice(b"127.0.0.1");
+            buf.put_u16(0); // no params
+            buf.put_u8(0); // prepared disabled
+            buf.put_u8(0); // not async
+            buf.put_u32(0); // no entries
+            buf.put_u8(0); // no tls
+            buf.put_u8(0); // no backend auth
+            buf
+        };
+
+        assert_eq!(deserialize_state(header()).unwrap().shard_key, None);
+
+        let mut buf = header();
+        put_str(&mut buf, "eu-1");
+        assert_eq!(
+            deserialize_state(buf).unwrap().shard_key.as_deref(),
+            Some("eu-1")
+        );
+
+        let mut buf = header();
+        put_str(&mut buf, "");
+        put_str(&mut buf, "read-only");
+        let state = deserialize_state(buf).unwrap();
+        assert_eq!(state.shard_key, None);
+        assert_eq!(
+            state.target_session_attrs,
+            Some(TargetSessionAttrs::ReadOnly)
+        );
+        assert_eq!(state.query_timeout_ms, None);
+
+        let mut buf = header();
+        put_str(&mut buf, "");
+        put_str(&mut buf, "");
+        buf.put_u64(500);
+        let state = deserialize_state(buf).unwrap();
+        assert_eq!(state.target_session_attrs, None);
+        assert_eq!(state.query_timeout_ms, Some(500));
+    }
+
     #[test]
     fn serialize_deserialize_anonymous_prepared() {
         let mut buf = BytesMut::new();
@@ -6,7 +6,11 @@ mod error_handling;
 #[cfg(unix)]
 pub mod migration;
 mod protocol;
+pub mod proxy_protocol;
+mod query_deadline;
+mod replication;
 mod startup;
+pub mod tags;
 mod transaction;
 mod util;
 
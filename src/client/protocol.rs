@@ -86,6 +86,9 @@ where
                         Error::PreparedStatementError => {
                             warn!("[{}@{} #c{}] server rejected prepared statement {:?}, evicting from client cache", self.username, self.pool_name, self.connection_id, key);
                             self.prepared.cache.pop(&key);
+                            if let PreparedStatementKey::Named(name) = &key {
+                                self.stats.remove_prepared_name(name);
+                            }
                         }
 
                         _ => {
@@ -235,6 +238,30 @@ where
             hash,
             async_name: async_name.clone(),
         };
+        // A client reusing a statement name for a different query is legal
+        // (after Close or DEALLOCATE, or across transactions), but it is the
+        // usual suspect behind "prepared statement does not exist" reports.
+        if let PreparedStatementKey::Named(name) = &cache_key {
+            if let Some(prev) = self
+                .prepared
+                .cache
+                .get(&cache_key)
+                .filter(|prev| prev.hash != hash)
+            {
+                debug!(
+                    "[{}@{} #c{}] statement `{}` remapped: `{}` -> `{}`",
+                    self.username,
+                    self.pool_name,
+                    self.connection_id,
+                    name,
+                    prev.server_name(),
+                    cached.server_name(),
+                );
+                self.stats.prepared_remap();
+                crate::web::metrics::observe_prepared_remap(&self.username, &self.pool_name);
+            }
+            self.stats.add_prepared_name(name, cached.server_name());
+        }
         // Only Evicted is a real LRU eviction. Replaced (steady-state
         // re-Parse of the same anonymous hash) and Inserted must not bump
         // the operator counter — that was the bug behind a non-zero
@@ -484,6 +511,7 @@ where
                 let is_anonymous = client_given_name.is_empty();
                 crate::server::record_query_count(cached.hash, is_anonymous);
                 self.prepared.last_bound_for_top = Some((cached.hash, is_anonymous));
+                server.stats.set_current_query(cached.parse.query_arc());
 
                 Ok(())
             }
@@ -712,7 +740,9 @@ where
         // same hash. Leave the entry to expire via the per-client LRU.
         if self.prepared.enabled && close.is_prepared_statement() && !close.anonymous() {
             let key = PreparedStatementKey::Named(close.name.clone());
-            self.prepared.cache.pop(&key);
+            if self.prepared.cache.pop(&key).is_some() {
+                self.stats.remove_prepared_name(&close.name);
+            }
         }
 
         Ok(())
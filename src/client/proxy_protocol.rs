@@ -0,0 +1,255 @@
+//! PROXY protocol (HAProxy spec, v1 text and v2 binary) header parsing.
+//!
+//! When `accept_proxy_protocol` is enabled, every TCP connection must start
+//! with a PROXY header written by the load balancer. The header carries the
+//! original client address, which then replaces the socket peer for HBA
+//! checks and logging. The parser reads exactly the header bytes and nothing
+//! more, so the Postgres startup packet that follows is left in the socket.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::errors::Error;
+
+/// Signature that opens every v2 header.
+const V2_SIGNATURE: [u8; 12] = *b"\r\n\r\n\0\r\nQUIT\n";
+
+/// v1 headers are at most 107 bytes including the trailing CRLF.
+const V1_MAX_LEN: usize = 107;
+
+/// v2 payloads above this size are not produced by any sane balancer
+/// (addresses plus a handful of TLVs); reject them instead of buffering.
+const V2_MAX_PAYLOAD: usize = 4096;
+
+/// How long a connection may take to deliver its PROXY header.
+pub const PROXY_HEADER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Reads a PROXY header from `stream`.
+///
+/// Returns `Some(addr)` with the original source address, or `None` when the
+/// header is valid but carries no address (v1 `UNKNOWN`, v2 `LOCAL` command,
+/// or a non-inet family such as AF_UNIX). Callers fall back to the socket
+/// peer in the `None` case. A missing or malformed header is an error.
+pub async fn read_proxy_header<S>(stream: &mut S) -> Result<Option<SocketAddr>, Error>
+where
+    S: AsyncRead + Unpin,
+{
+    // Both versions are at least 12 bytes long (the shortest v1 header,
+    // "PROXY UNKNOWN\r\n", is 15), so this read never eats startup bytes.
+    let mut prefix = [0u8; 12];
+    read_exact(stream, &mut prefix).await?;
+
+    if prefix == V2_SIGNATURE {
+        read_v2(stream).await
+    } else if prefix.starts_with(b"PROXY ") {
+        read_v1(stream, &prefix).await
+    } else {
+        Err(Error::ProtocolSyncError(
+            "expected PROXY protocol header".to_string(),
+        ))
+    }
+}
+
+async fn read_exact<S>(stream: &mut S, buf: &mut [u8]) -> Result<(), Error>
+where
+    S: AsyncRead + Unpin,
+{
+    stream
+        .read_exact(buf)
+        .await
+        .map(|_| ())
+        .map_err(|err| Error::SocketError(format!("failed to read PROXY header: {err}")))
+}
+
+async fn read_v1<S>(stream: &mut S, prefix: &[u8]) -> Result<Option<SocketAddr>, Error>
+where
+    S: AsyncRead + Unpin,
+{
+    let mut line = prefix.to_vec();
+    while !line.ends_with(b"\r\n") {
+        if line.len() >= V1_MAX_LEN {
+            return Err(Error::ProtocolSyncError(
+                "PROXY v1 header is too long".to_string(),
+            ));
+        }
+        let mut byte = [0u8; 1];
+        read_exact(stream, &mut byte).await?;
+        line.push(byte[0]);
+    }
+    line.truncate(line.len() - 2);
+    let line = std::str::from_utf8(&line)
+        .map_err(|_| Error::ProtocolSyncError("PROXY v1 header is not valid ASCII".to_string()))?;
+    parse_v1(line)
+}
+
+/// Parses a v1 header line without the trailing CRLF.
+fn parse_v1(line: &str) -> Result<Option<SocketAddr>, Error> {
+    let bad = || Error::ProtocolSyncError(format!("malformed PROXY v1 header: {line:?}"));
+    let mut parts = line.split(' ');
+    if parts.next() != Some("PROXY") {
+        return Err(bad());
+    }
+    let family = parts.next().ok_or_else(bad)?;
+    if family == "UNKNOWN" {
+        // Anything may follow UNKNOWN; the receiver must ignore it.
+        return Ok(None);
+    }
+    let fields: Vec<&str> = parts.collect();
+    if fields.len() != 4 {
+        return Err(bad());
+    }
+    let src_ip: IpAddr = match family {
+        "TCP4" => IpAddr::V4(fields[0].parse::<Ipv4Addr>().map_err(|_| bad())?),
+        "TCP6" => IpAddr::V6(fields[0].parse::<Ipv6Addr>().map_err(|_| bad())?),
+        _ => return Err(bad()),
+    };
+    let src_port: u16 = fields[2].parse().map_err(|_| bad())?;
+    Ok(Some(SocketAddr::new(src_ip, src_port)))
+}
+
+async fn read_v2<S>(stream: &mut S) -> Result<Option<SocketAddr>, Error>
+where
+    S: AsyncRead + Unpin,
+{
+    let mut head = [0u8; 4];
+    read_exact(stream, &mut head).await?;
+    let len = u16::from_be_bytes([head[2], head[3]]) as usize;
+    if len > V2_MAX_PAYLOAD {
+        return Err(Error::ProtocolSyncError(format!(
+            "PROXY v2 payload too large: {len} bytes"
+        )));
+    }
+    let mut payload = vec![0u8; len];
+    read_exact(stream, &mut payload).await?;
+    parse_v2(head[0], head[1], &payload)
+}
+
+/// Parses the v2 version/command byte, family/protocol byte and payload.
+fn parse_v2(ver_cmd: u8, fam: u8, payload: &[u8]) -> Result<Option<SocketAddr>, Error> {
+    if ver_cmd >> 4 != 2 {
+        return Err(Error::ProtocolSyncError(format!(
+            "unsupported PROXY protocol version {}",
+            ver_cmd >> 4
+        )));
+    }
+    match ver_cmd & 0x0f {
+        // LOCAL: health check from the balancer itself, keep the real peer.
+        0x0 => return Ok(None),
+        // PROXY
+        0x1 => {}
+        cmd => {
+            return Err(Error::ProtocolSyncError(format!(
+                "unsupported PROXY v2 command {cmd}"
+            )))
+        }
+    }
+    let short = || Error::ProtocolSyncError("PROXY v2 address block is truncated".to_string());
+    match fam >> 4 {
+        // AF_INET: src(4) dst(4) src_port(2) dst_port(2)
+        0x1 => {
+            let b = payload.get(..12).ok_or_else(short)?;
+            let ip = Ipv4Addr::new(b[0], b[1], b[2], b[3]);
+            let port = u16::from_be_bytes([b[8], b[9]]);
+            Ok(Some(SocketAddr::new(IpAddr::V4(ip), port)))
+        }
+        // AF_INET6: src(16) dst(16) src_port(2) dst_port(2)
+        0x2 => {
+            let b = payload.get(..36).ok_or_else(short)?;
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(&b[..16]);
+            let port = u16::from_be_bytes([b[32], b[33]]);
+            Ok(Some(SocketAddr::new(
+                IpAddr::V6(Ipv6Addr::from(octets)),
+                port,
+            )))
+        }
+        // AF_UNSPEC, AF_UNIX: no inet address to report.
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn read(bytes: &[u8]) -> (Result<Option<SocketAddr>, Error>, Vec<u8>) {
+        let mut cursor = std::io::Cursor::new(bytes.to_vec());
+        let result = read_proxy_header(&mut cursor).await;
+        let pos = cursor.position() as usize;
+        (result, bytes[pos..].to_vec())
+    }
+
+    #[tokio::test]
+    async fn v1_tcp4_and_tcp6() {
+        let (res, rest) = read(b"PROXY TCP4 192.0.2.10 10.0.0.1 51234 6432\r\nSTARTUP").await;
+        assert_eq!(res.unwrap(), Some("192.0.2.10:51234".parse().unwrap()));
+        assert_eq!(rest, b"STARTUP");
+
+        let (res, _) = read(b"PROXY TCP6 2001:db8::1 2001:db8::2 4000 6432\r\n").await;
+        assert_eq!(res.unwrap(), Some("[2001:db8::1]:4000".parse().unwrap()));
+
+        let (res, rest) = read(b"PROXY UNKNOWN\r\nX").await;
+        assert_eq!(res.unwrap(), None);
+        assert_eq!(rest, b"X");
+    }
+
+    #[tokio::test]
+    async fn v1_rejects_garbage() {
+        assert!(read(b"PROXY TCP4 nope 10.0.0.1 1 2\r\n").await.0.is_err());
+        assert!(read(b"PROXY TCP4 1.2.3.4 5.6.7.8 1\r\n").await.0.is_err());
+        assert!(read(b"PROXY UDP4 1.2.3.4 5.6.7.8 1 2\r\n").await.0.is_err());
+        let mut long = b"PROXY TCP4 ".to_vec();
+        long.extend_from_slice(&[b'1'; 200]);
+        assert!(read(&long).await.0.is_err());
+    }
+
+    #[tokio::test]
+    async fn missing_header_is_rejected() {
+        // A plain Postgres SSLRequest must not be accepted as a header.
+        let ssl_request = [0u8, 0, 0, 8, 4, 210, 22, 47, 0, 0, 0, 8, 0, 3, 0, 0];
+        assert!(read(&ssl_request).await.0.is_err());
+        assert!(read(b"PROX").await.0.is_err());
+    }
+
+    #[tokio::test]
+    async fn v2_inet_and_local() {
+        let mut hdr = V2_SIGNATURE.to_vec();
+        hdr.extend_from_slice(&[0x21, 0x11, 0, 12]);
+        hdr.extend_from_slice(&[198, 51, 100, 7, 10, 0, 0, 1]);
+        hdr.extend_from_slice(&40000u16.to_be_bytes());
+        hdr.extend_from_slice(&6432u16.to_be_bytes());
+        hdr.extend_from_slice(b"TAIL");
+        let (res, rest) = read(&hdr).await;
+        assert_eq!(res.unwrap(), Some("198.51.100.7:40000".parse().unwrap()));
+        assert_eq!(rest, b"TAIL");
+
+        let mut hdr = V2_SIGNATURE.to_vec();
+        hdr.extend_from_slice(&[0x21, 0x21, 0, 36]);
+        let src: Ipv6Addr = "2001:db8::7".parse().unwrap();
+        let dst: Ipv6Addr = "2001:db8::1".parse().unwrap();
+        hdr.extend_from_slice(&src.octets());
+        hdr.extend_from_slice(&dst.octets());
+        hdr.extend_from_slice(&1234u16.to_be_bytes());
+        hdr.extend_from_slice(&6432u16.to_be_bytes());
+        let (res, _) = read(&hdr).await;
+        assert_eq!(res.unwrap(), Some("[2001:db8::7]:1234".parse().unwrap()));
+
+        let mut local = V2_SIGNATURE.to_vec();
+        local.extend_from_slice(&[0x20, 0x00, 0, 0]);
+        assert_eq!(read(&local).await.0.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn v2_rejects_bad_version_and_truncation() {
+        let mut hdr = V2_SIGNATURE.to_vec();
+        hdr.extend_from_slice(&[0x11, 0x11, 0, 12]);
+        hdr.extend_from_slice(&[0; 12]);
+        assert!(read(&hdr).await.0.is_err());
+
+        let mut hdr = V2_SIGNATURE.to_vec();
+        hdr.extend_from_slice(&[0x21, 0x11, 0, 4, 1, 2, 3, 4]);
+        assert!(read(&hdr).await.0.is_err());
+    }
+}
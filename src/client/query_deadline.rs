@@ -0,0 +1,373 @@
+//! Hard ceiling on a single query's duration (`max_query_duration`).
+//!
+//! A watchdog task is armed for every server round trip. If the round trip
+//! outlives the limit, the task marks the backend in `CANCELED_PIDS` and
+//! sends a CancelRequest, exactly like a client-issued cancel. PostgreSQL
+//! then answers the running query with `57014` followed by ReadyForQuery,
+//! so the round trip ends normally and the client sees the error.
+//!
+//! Unlike a client cancel, the pooler knows when its own cancel has been
+//! consumed: if the round trip ended with `57014` after the watchdog fired,
+//! the pid is taken back out of `CANCELED_PIDS` and the connection stays in
+//! the pool. Otherwise the query finished on its own while the cancel was in
+//! flight, and the connection is discarded at the next checkout so the late
+//! cancel cannot hit somebody else's query.
+//!
+//! `max_result_rows` reuses the same machinery: once a round trip has
+//! returned more DataRow messages than the limit, the cancel is sent right
+//! away instead of after a delay, and is settled the same way.
+//!
+//! A client may ask for a shorter limit with `pg_doorman.query_timeout_ms`,
+//! in the StartupMessage or with `SET` outside a transaction. The pool's
+//! `max_query_duration` stays the ceiling: larger values are clamped with a
+//! warning, so a client cannot lift the operator's limit.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::BytesMut;
+use log::warn;
+use tokio::task::JoinHandle;
+
+use crate::messages::notice_message;
+use crate::pool::{CancelTarget, CANCELED_PIDS};
+use crate::server::Server;
+use crate::utils::format_duration_ms;
+
+/// SQLSTATE `query_canceled`.
+const QUERY_CANCELED: &str = "57014";
+
+/// How long `finish` waits for an in-flight CancelRequest to be delivered.
+const CANCEL_DELIVERY_WAIT: Duration = Duration::from_secs(1);
+
+/// Client parameter asking for its own query limit, in milliseconds.
+pub(crate) const QUERY_TIMEOUT_PARAMETER: &str = "pg_doorman.query_timeout_ms";
+
+/// Pool setting that caps [`QUERY_TIMEOUT_PARAMETER`].
+const POOL_LIMIT_SETTING: &str = "max_query_duration";
+
+/// Pool setting that caps the rows of one round trip.
+pub(crate) const RESULT_ROWS_SETTING: &str = "max_result_rows";
+
+/// Whether a round trip that has returned `rows` DataRows is over the
+/// `max_result_rows` limit; 0 disables the limit.
+pub(crate) fn result_rows_exceeded(rows: u64, limit: u64) -> bool {
+    limit > 0 && rows > limit
+}
+
+/// Parses a `pg_doorman.query_timeout_ms` value; 0 means no client limit.
+pub(crate) fn parse_query_timeout(raw: &str) -> Option<u64> {
+    raw.trim().trim_matches('\'').trim().parse().ok()
+}
+
+/// A `SET` or `RESET` of [`QUERY_TIMEOUT_PARAMETER`].
+#[derive(Debug, PartialEq)]
+pub(crate) enum QueryTimeoutStatement {
+    /// `SET ... = <ms>`; `None` for `SET ... TO DEFAULT`.
+    Set(Option<u64>),
+    Reset,
+    /// `SET` with a value that is not a number of milliseconds.
+    Invalid(String),
+}
+
+/// Recognizes `SET [SESSION] pg_doorman.query_timeout_ms {=|TO} <ms>` and
+/// `RESET pg_doorman.query_timeout_ms`; `None` for any other statement.
+pub(crate) fn parse_query_timeout_statement(query: &[u8]) -> Option<QueryTimeoutStatement> {
+    let query = std::str::from_utf8(query).ok()?;
+    let query = query.trim().trim_end_matches(';').replacen('=', " = ", 1);
+    let mut words = query.split_whitespace();
+    let command = words.next()?;
+    if command.eq_ignore_ascii_case("RESET") {
+        let name = words.next()?;
+        return (name.eq_ignore_ascii_case(QUERY_TIMEOUT_PARAMETER) && words.next().is_none())
+            .then_some(QueryTimeoutStatement::Reset);
+    }
+    if !command.eq_ignore_ascii_case("SET") {
+        return None;
+    }
+    let mut name = words.next()?;
+    if name.eq_ignore_ascii_case("SESSION") {
+        name = words.next()?;
+    }
+    if !name.eq_ignore_ascii_case(QUERY_TIMEOUT_PARAMETER) {
+        return None;
+    }
+    let operator = words.next()?;
+    if operator != "=" && !operator.eq_ignore_ascii_case("TO") {
+        return None;
+    }
+    let raw = words.collect::<Vec<_>>().join(" ");
+    if raw.eq_ignore_ascii_case("DEFAULT") {
+        return Some(QueryTimeoutStatement::Set(None));
+    }
+    Some(match parse_query_timeout(&raw) {
+        Some(ms) => QueryTimeoutStatement::Set(Some(ms)),
+        None => QueryTimeoutStatement::Invalid(raw),
+    })
+}
+
+/// Limit for one round trip and the setting it comes from: the client's
+/// request when it is shorter than the pool's `max_query_duration`,
+/// otherwise the pool limit. 0 disables the watchdog.
+pub(crate) fn effective_limit(requested_ms: Option<u64>, pool_max_ms: u64) -> (u64, &'static str) {
+    match requested_ms {
+        Some(ms) if ms > 0 && (pool_max_ms == 0 || ms < pool_max_ms) => {
+            (ms, QUERY_TIMEOUT_PARAMETER)
+        }
+        _ => (pool_max_ms, POOL_LIMIT_SETTING),
+    }
+}
+
+/// Warning for a client request above the pool's `max_query_duration`.
+pub(crate) fn clamp_notice(requested_ms: Option<u64>, pool_max_ms: u64) -> Option<BytesMut> {
+    let ms = requested_ms?;
+    (pool_max_ms > 0 && ms > pool_max_ms).then(|| {
+        notice_message(
+            &format!(
+                "{QUERY_TIMEOUT_PARAMETER} = {ms} exceeds the pool's {POOL_LIMIT_SETTING} ({}), clamped to it",
+                format_duration_ms(pool_max_ms)
+            ),
+            "01000",
+        )
+    })
+}
+
+/// Watchdog for one server round trip; aborted on drop.
+pub(crate) struct QueryDeadline {
+    /// The limit as shown to people: a duration or a row count.
+    limit: String,
+    /// Setting the limit comes from, named in logs and the notice.
+    setting: &'static str,
+    fired: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+/// Marks the backend as canceled by the pooler; the CancelRequest follows.
+fn mark_fired(fired: &AtomicBool, target: &CancelTarget, setting: &str, limit: &str) {
+    fired.store(true, Ordering::Release);
+    CANCELED_PIDS.lock().insert(target.process_id);
+    warn!(
+        "[{}] query exceeded {setting} ({limit}), canceling pid={}",
+        target.pool_name, target.process_id
+    );
+}
+
+impl QueryDeadline {
+    /// Starts the watchdog for the backend described by `target`.
+    pub(crate) fn arm(target: CancelTarget, limit_ms: u64, setting: &'static str) -> Self {
+        Self::spawn(
+            target,
+            Some(Duration::from_millis(limit_ms)),
+            setting,
+            format_duration_ms(limit_ms),
+        )
+    }
+
+    /// Cancels the backend right away, for a limit the round trip has
+    /// already crossed.
+    pub(crate) fn cancel_now(target: CancelTarget, setting: &'static str, limit: String) -> Self {
+        Self::spawn(target, None, setting, limit)
+    }
+
+    fn spawn(
+        target: CancelTarget,
+        delay: Option<Duration>,
+        setting: &'static str,
+        limit: String,
+    ) -> Self {
+        let fired = Arc::new(AtomicBool::new(false));
+        if delay.is_none() {
+            mark_fired(&fired, &target, setting, &limit);
+        }
+        let task_fired = fired.clone();
+        let task_limit = limit.clone();
+        let handle = tokio::spawn(async move {
+            if let Some(delay) = delay {
+                tokio::time::sleep(delay).await;
+                mark_fired(&task_fired, &target, setting, &task_limit);
+            }
+            if let Err(err) = Server::cancel(
+                &target.host,
+                target.port,
+                target.process_id,
+                target.secret_key,
+                &target.server_tls,
+                target.connected_with_tls,
+                &target.pool_name,
+            )
+            .await
+            {
+                warn!(
+                    "[{}] failed to cancel pid={} after {setting}: {err}",
+                    target.pool_name, target.process_id
+                );
+            }
+        });
+        Self {
+            limit,
+            setting,
+            fired,
+            handle: Some(handle),
+        }
+    }
+
+    /// Whether the limit was reached and a cancel was issued.
+    pub(crate) fn fired(&self) -> bool {
+        self.fired.load(Ordering::Acquire)
+    }
+
+    /// NoticeResponse sent ahead of the backend's `57014` so the client can
+    /// tell a pooler-enforced limit from a manual cancel.
+    pub(crate) fn notice(&self) -> BytesMut {
+        notice_message(
+            &format!(
+                "query canceled by pg_doorman: exceeded {} ({})",
+                self.setting, self.limit
+            ),
+            QUERY_CANCELED,
+        )
+    }
+
+    /// Settles the watchdog after the round trip. If the cancel fired and the
+    /// backend reported `57014`, the cancel is known to be consumed and the
+    /// connection is released from `CANCELED_PIDS`.
+    pub(crate) async fn finish(mut self, server: &Server) {
+        let Some(handle) = self.handle.take() else {
+            return;
+        };
+        if !self.fired() {
+            handle.abort();
+            return;
+        }
+        // Let the CancelRequest reach the postmaster before deciding.
+        let delivered = tokio::time::timeout(CANCEL_DELIVERY_WAIT, handle)
+            .await
+            .is_ok();
+        let consumed = matches!(
+            &server.last_sql_error,
+            Some((code, _)) if code == QUERY_CANCELED
+        );
+        if delivered && consumed {
+            CANCELED_PIDS.lock().remove(&server.get_process_id());
+        }
+    }
+}
+
+impl Drop for QueryDeadline {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::tls::{ServerTlsConfig, ServerTlsMode};
+
+    fn target(process_id: i32) -> CancelTarget {
+        CancelTarget {
+            process_id,
+            secret_key: 0,
+            // Nothing listens on port 1; the cancel attempt fails fast.
+            host: "127.0.0.1".to_string(),
+            port: 1,
+            server_tls: Arc::new(
+                ServerTlsConfig::new(ServerTlsMode::Disable, None, None, None).unwrap(),
+            ),
+            connected_with_tls: false,
+            pool_name: "deadline-test".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn fires_after_limit_and_marks_pid() {
+        let deadline = QueryDeadline::arm(target(-7001), 10, POOL_LIMIT_SETTING);
+        assert!(!deadline.fired());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(deadline.fired());
+        assert!(CANCELED_PIDS.lock().remove(&-7001));
+    }
+
+    #[tokio::test]
+    async fn dropped_before_limit_does_nothing() {
+        let deadline = QueryDeadline::arm(target(-7002), 50, POOL_LIMIT_SETTING);
+        drop(deadline);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!CANCELED_PIDS.lock().contains(&-7002));
+    }
+
+    #[tokio::test]
+    async fn cancel_now_fires_immediately() {
+        let canceled =
+            QueryDeadline::cancel_now(target(-7003), RESULT_ROWS_SETTING, "1000 rows".to_string());
+        assert!(canceled.fired());
+        assert!(CANCELED_PIDS.lock().remove(&-7003));
+        let notice = canceled.notice();
+        let text = String::from_utf8_lossy(&notice);
+        assert!(text.contains("exceeded max_result_rows (1000 rows)"));
+    }
+
+    #[test]
+    fn result_rows_limit() {
+        assert!(!result_rows_exceeded(1_000_000, 0));
+        assert!(!result_rows_exceeded(1000, 1000));
+        assert!(result_rows_exceeded(1001, 1000));
+    }
+
+    #[test]
+    fn query_timeout_statements() {
+        use QueryTimeoutStatement::*;
+        let parse = |q: &str| parse_query_timeout_statement(q.as_bytes());
+        assert_eq!(
+            parse("SET pg_doorman.query_timeout_ms = 500"),
+            Some(Set(Some(500)))
+        );
+        assert_eq!(
+            parse("set session pg_doorman.query_timeout_ms to '250';"),
+            Some(Set(Some(250)))
+        );
+        assert_eq!(
+            parse("SET pg_doorman.query_timeout_ms=100"),
+            Some(Set(Some(100)))
+        );
+        assert_eq!(
+            parse("SET pg_doorman.query_timeout_ms TO DEFAULT"),
+            Some(Set(None))
+        );
+        assert_eq!(parse("RESET pg_doorman.query_timeout_ms"), Some(Reset));
+        assert_eq!(
+            parse("SET pg_doorman.query_timeout_ms = '5s'"),
+            Some(Invalid("'5s'".to_string()))
+        );
+        assert_eq!(parse("SET statement_timeout = 500"), None);
+        assert_eq!(parse("SET LOCAL pg_doorman.query_timeout_ms = 500"), None);
+        assert_eq!(parse("SELECT 1"), None);
+    }
+
+    #[test]
+    fn client_limit_is_capped_by_pool_limit() {
+        assert_eq!(effective_limit(None, 0), (0, POOL_LIMIT_SETTING));
+        assert_eq!(
+            effective_limit(Some(500), 0),
+            (500, QUERY_TIMEOUT_PARAMETER)
+        );
+        assert_eq!(
+            effective_limit(Some(500), 1000),
+            (500, QUERY_TIMEOUT_PARAMETER)
+        );
+        assert_eq!(
+            effective_limit(Some(5000), 1000),
+            (1000, POOL_LIMIT_SETTING)
+        );
+        // 0 drops the client's own limit but never the pool's.
+        assert_eq!(effective_limit(Some(0), 1000), (1000, POOL_LIMIT_SETTING));
+
+        assert!(clamp_notice(Some(5000), 1000).is_some());
+        assert!(clamp_notice(Some(500), 1000).is_none());
+        assert!(clamp_notice(Some(5000), 0).is_none());
+        assert!(clamp_notice(None, 1000).is_none());
+    }
+}
@@ -0,0 +1,90 @@
+//! Replication passthrough (`replication=true` / `replication=database`).
+//!
+//! Walsender sessions (`pg_basebackup`, `pg_receivewal`, logical
+//! replication subscribers) speak the replication sub-protocol: CopyBoth
+//! streams, replication commands such as `IDENTIFY_SYSTEM`, and no
+//! transaction boundaries to pool on. Such a client gets a dedicated
+//! backend opened with the same `replication` value and is then relayed
+//! byte for byte until either side closes. The transaction-pooling,
+//! prepared-statement and query-limit machinery never sees these bytes.
+
+use log::{info, warn};
+
+use crate::errors::Error;
+use crate::messages::error_response;
+use crate::pool::ConnectionPool;
+
+use super::core::Client;
+
+impl<S, T> Client<S, T>
+where
+    S: tokio::io::AsyncRead + std::marker::Unpin,
+    T: tokio::io::AsyncWrite + std::marker::Unpin,
+{
+    /// Opens a walsender connection for this client and relays both
+    /// directions until one side disconnects.
+    pub(crate) async fn handle_replication(
+        &mut self,
+        pool: &ConnectionPool,
+        replication: &str,
+    ) -> Result<(), Error> {
+        let application_name = self.server_parameters.get_application_name().to_string();
+        let mut server = match pool
+            .database
+            .server_pool()
+            .create_replication_connection(replication, &application_name)
+            .await
+        {
+            Ok(server) => server,
+            Err(err) => {
+                warn!(
+                    "[{}@{} #c{}] replication connection failed: {err}",
+                    self.username, self.pool_name, self.connection_id
+                );
+                match &err {
+                    Error::ServerStartupParameterRejection {
+                        sqlstate, message, ..
+                    } => error_response(&mut self.write, message, sqlstate).await?,
+                    _ => {
+                        error_response(
+                            &mut self.write,
+                            &format!("could not open replication connection: {err}"),
+                            "08006",
+                        )
+                        .await?
+                    }
+                }
+                return Err(err);
+            }
+        };
+
+        // Cancel requests carrying this client's key reach the walsender.
+        server.claim(self.connection_id as i32, self.secret_key);
+        server.stats.active(application_name);
+        self.stats.active_idle();
+        self.connected_to_server = true;
+        self.last_server_stats = Some(server.stats.clone());
+        info!(
+            "[{}@{} #c{}] client {} relayed to replication connection pid={}",
+            self.username,
+            self.pool_name,
+            self.connection_id,
+            self.addr,
+            server.get_process_id()
+        );
+
+        let (mut server_read, mut server_write) = tokio::io::split(&mut server.stream);
+        let result = tokio::select! {
+            result = tokio::io::copy(&mut self.read, &mut server_write) => result,
+            result = tokio::io::copy(&mut server_read, &mut self.write) => result,
+        };
+        self.release();
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(Error::SocketError(format!(
+                "replication relay for {}@{} closed: {err}",
+                self.username, self.pool_name
+            ))),
+        }
+    }
+}
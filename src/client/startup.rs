@@ -1,5 +1,8 @@
 use bytes::{Buf, BufMut, BytesMut};
-use log::error;
+use log::{debug, error, info, warn};
+use rand::rngs::OsRng;
+use rand::TryRngCore;
+use std::collections::HashMap;
 use std::ffi::CStr;
 use std::str;
 use std::sync::atomic::Ordering;
@@ -7,23 +10,39 @@ use std::sync::Arc;
 use tokio::io::{split, AsyncReadExt, BufReader, ReadHalf, WriteHalf};
 use tokio::net::TcpStream;
 
-use crate::auth::authenticate;
 use crate::auth::hba::CheckResult;
 use crate::auth::talos::{extract_talos_token, talos_role_to_string};
-use crate::config::{check_hba, get_config};
+use crate::auth::{authenticate, hba_denied_text, record_auth_failure};
+use crate::config::tls::POSTGRESQL_ALPN;
+use crate::config::{check_hba, get_config, hba_reject_message, listener_database, PoolMode};
 use crate::errors::{ClientIdentifier, Error};
 use crate::messages::constants::*;
 use crate::messages::{
     error_response_terminal, parse_startup, plain_password_challenge, read_password,
-    ready_for_query, write_all_flush,
+    ready_for_query, replication_mode, startup_negotiation, write_all_flush,
 };
+use crate::pool::target_session::TargetSessionAttrs;
 use crate::pool::ClientServerMap;
 use crate::server::ServerParameters;
+use crate::stats::wait_events::{WaitGuard, CLIENTS_IN_AUTH, CLIENTS_IN_TLS_HANDSHAKE};
 use crate::stats::{ClientStats, CANCEL_CONNECTION_COUNTER};
 use crate::transport::ClientTransport;
+use crate::utils::debug_messages::{hex_preview, RecentMessageTypes};
 
 use super::buffer_pool::PooledBuffer;
 use super::core::{Client, PreparedStatementState};
+use super::query_deadline::{parse_query_timeout, QUERY_TIMEOUT_PARAMETER};
+use super::tags::{normalize_tag, try_acquire, CLIENT_TAG_PARAMETER};
+
+/// StartupMessage parameter naming the shard of a pool with `shards`.
+pub(crate) const CLIENT_SHARD_PARAMETER: &str = "pg_doorman.shard";
+
+/// StartupMessage parameter asking for a per-connection pool mode.
+pub(crate) const CLIENT_POOL_MODE_PARAMETER: &str = "pg_doorman.pool_mode";
+
+/// StartupMessage parameter picking the member of a multi-host pool, with
+/// libpq's `target_session_attrs` values.
+pub(crate) const CLIENT_TARGET_SESSION_ATTRS_PARAMETER: &str = "target_session_attrs";
 
 /// Type of connection received from client.
 pub(crate) enum ClientConnectionType {
@@ -32,6 +51,61 @@ pub(crate) enum ClientConnectionType {
     CancelQuery,
 }
 
+/// Secret half of the cancel key sent in BackendKeyData.
+///
+/// Drawn from the OS CSPRNG so one client cannot predict another's key from
+/// the keys it has seen itself. Zero is rerolled: it is never issued, so a
+/// CancelRequest carrying it can never match.
+pub(crate) fn generate_secret_key() -> i32 {
+    loop {
+        let key = match OsRng.try_next_u32() {
+            Ok(key) => key as i32,
+            // ThreadRng is a reseeding CSPRNG as well; only used when the
+            // OS source is unavailable.
+            Err(_) => rand::random::<i32>(),
+        };
+        if key != 0 {
+            return key;
+        }
+    }
+}
+
+/// Largest startup packet accepted from a client. PostgreSQL caps it at
+/// 10000 bytes; real StartupMessages are a few hundred.
+pub(crate) const MAX_STARTUP_PACKET_LEN: i32 = 8 * 1024;
+
+/// Instant by which a new client must finish startup and authentication,
+/// or `None` when `general.startup_timeout` is 0.
+pub(crate) fn startup_deadline() -> Option<tokio::time::Instant> {
+    let timeout = get_config().general.startup_timeout.as_std();
+    if timeout.is_zero() {
+        None
+    } else {
+        Some(tokio::time::Instant::now() + timeout)
+    }
+}
+
+/// Run one step of the client handshake, failing with
+/// `ClientStartupTimeout` once `deadline` passes.
+pub(crate) async fn within_startup_deadline<T, F>(
+    deadline: Option<tokio::time::Instant>,
+    step: F,
+) -> Result<T, Error>
+where
+    F: std::future::Future<Output = Result<T, Error>>,
+{
+    let Some(deadline) = deadline else {
+        return step.await;
+    };
+    match tokio::time::timeout_at(deadline, step).await {
+        Ok(result) => result,
+        Err(_) => {
+            crate::web::metrics::record_listener_rejection("startup_timeout");
+            Err(Error::ClientStartupTimeout)
+        }
+    }
+}
+
 /// Handle the first message the client sends.
 pub(crate) async fn get_startup<S>(
     stream: &mut S,
@@ -47,7 +121,14 @@ where
 
     // Validate message length: minimum is 8 bytes (4 for length field + 4 for protocol code).
     // Also reject negative or excessively large lengths to prevent overflow/DoS.
-    if !(8..=8 * 1024).contains(&len) {
+    if !(8..=MAX_STARTUP_PACKET_LEN).contains(&len) {
+        crate::web::metrics::record_protocol_error("startup");
+        warn!(
+            "rejecting startup packet with invalid length {len} (bytes [{}])",
+            hex_preview(&len.to_be_bytes(), 4)
+        );
+        // Best effort: the client may not be speaking the protocol at all.
+        let _ = error_response_terminal(stream, "invalid length of startup packet", "08P01").await;
         return Err(Error::ClientBadStartup);
     }
 
@@ -65,9 +146,6 @@ where
         // Client is requesting SSL (TLS).
         SSL_REQUEST_CODE => Ok((ClientConnectionType::Tls, bytes)),
 
-        // Client wants to use plain text, requesting regular startup.
-        PROTOCOL_VERSION_NUMBER => Ok((ClientConnectionType::Startup, bytes)),
-
         // Client is requesting to cancel a running query (plain text connection).
         CANCEL_REQUEST_CODE => Ok((ClientConnectionType::CancelQuery, bytes)),
 
@@ -79,21 +157,80 @@ where
             Err(Error::AuthError("GSSENCMODE is unsupported".to_string()))
         }
 
+        // Client wants to use plain text, requesting regular startup. Newer
+        // minor versions (3.2 from libpq 18 and up) and `_pq_.*` protocol
+        // options are declined with NegotiateProtocolVersion, so the client
+        // carries on with plain 3.0 instead of dropping the connection.
+        // The options are never forwarded to backends.
+        code if code >> 16 == PROTOCOL_VERSION_MAJOR => {
+            let requested_minor = code & 0xFFFF;
+            if let Some(negotiation) = startup_negotiation(requested_minor, &bytes) {
+                if requested_minor > 0 {
+                    debug!(
+                        "Client requested protocol 3.{requested_minor}, negotiating down to 3.0"
+                    );
+                }
+                write_all_flush(stream, &negotiation).await?;
+            }
+            Ok((ClientConnectionType::Startup, bytes))
+        }
+
         // Something else, probably something is wrong, and it's not our fault,
         // e.g. badly implemented Postgres client.
-        _ => Err(Error::ProtocolSyncError(format!(
-            "Unexpected startup code: {code}"
-        ))),
+        _ => {
+            crate::web::metrics::record_protocol_error("startup");
+            Err(Error::ProtocolSyncError(format!(
+                "Unexpected startup code: {code} (protocol {}.{}, only 3.x is supported)",
+                code >> 16,
+                code & 0xFFFF
+            )))
+        }
     }
 }
 
-/// Handle TLS connection negotiation.
+/// Label for `pg_doorman_tls_handshake_failures_total`, derived from the
+/// OpenSSL error text of a failed handshake. Checked in this order because
+/// an alert message can also name the certificate problem
+/// (`tlsv1 alert unknown ca`).
+fn tls_handshake_failure_cause(message: &str) -> &'static str {
+    let message = message.to_ascii_lowercase();
+    if message.contains("certificate has expired") || message.contains("certificate expired") {
+        "cert_expired"
+    } else if message.contains("unknown ca")
+        || message.contains("unable to get local issuer certificate")
+        || message.contains("self-signed certificate")
+        || message.contains("self signed certificate")
+        || message.contains("certificate verify failed")
+    {
+        "cert_unknown_ca"
+    } else if message.contains("alert") {
+        "alert"
+    } else if message.contains("wrong version number")
+        || message.contains("unsupported protocol")
+        || message.contains("no protocols available")
+        || message.contains("no shared cipher")
+        || message.contains("version too low")
+        || message.contains("http request")
+        || message.contains("packet length too long")
+    {
+        "protocol"
+    } else {
+        "other"
+    }
+}
+
+/// Handle TLS connection negotiation. `direct` means the client started
+/// the handshake without an SSLRequest, which requires ALPN `postgresql`.
+#[allow(clippy::too_many_arguments)]
 pub async fn startup_tls(
     stream: TcpStream,
+    addr: std::net::SocketAddr,
     client_server_map: ClientServerMap,
     admin_only: bool,
     tls_acceptor: tokio_native_tls::TlsAcceptor,
     connection_id: u64,
+    default_database: Option<String>,
+    direct: bool,
 ) -> Result<
     Client<
         ReadHalf<tokio_native_tls::TlsStream<TcpStream>>,
@@ -102,14 +239,6 @@ pub async fn startup_tls(
     Error,
 > {
     // Negotiate TLS.
-    let addr = match stream.peer_addr() {
-        Ok(addr) => addr,
-        Err(err) => {
-            return Err(Error::SocketError(format!(
-                "Failed to get peer address: {err:?}"
-            )));
-        }
-    };
 
     // Capture TCP fd before TLS wrapping — needed for migration
     #[cfg(unix)]
@@ -118,17 +247,49 @@ pub async fn startup_tls(
         stream.as_raw_fd()
     };
 
-    let mut stream = match tls_acceptor.accept(stream).await {
-        Ok(stream) => stream,
+    let handshake_started = std::time::Instant::now();
+    let handshake = {
+        let _waiting = WaitGuard::enter(&CLIENTS_IN_TLS_HANDSHAKE);
+        tls_acceptor.accept(stream).await
+    };
+    let mut stream = match handshake {
+        Ok(stream) => {
+            crate::web::metrics::observe_client_phase(
+                "tls_handshake",
+                handshake_started.elapsed().as_secs_f64(),
+            );
+            stream
+        }
 
-        // TLS negotiation failed.
+        // TLS negotiation failed. The attempt already took its
+        // tls_rate_limit_per_second slot before the handshake started.
         Err(err) => {
+            let cause = tls_handshake_failure_cause(&err.to_string());
             crate::web::metrics::record_listener_rejection("tls_handshake_fail");
-            error!("TLS negotiation failed: {err}");
+            crate::web::metrics::record_tls_handshake_failure(cause);
+            error!("TLS negotiation with {addr} failed ({cause}): {err}");
             return Err(Error::TlsError);
         }
     };
 
+    // Like PostgreSQL, accept direct TLS only from clients that negotiated
+    // the postgresql protocol, so other TLS clients cannot be pointed at us.
+    if direct {
+        let alpn = stream.get_ref().negotiated_alpn().ok().flatten();
+        if alpn.as_deref() != Some(POSTGRESQL_ALPN.as_bytes()) {
+            crate::web::metrics::record_listener_rejection("protocol_error");
+            error_response_terminal(
+                &mut stream,
+                "direct SSL connection was established without ALPN protocol negotiation extension",
+                "08P01",
+            )
+            .await?;
+            return Err(Error::ProtocolSyncError(format!(
+                "direct TLS from {addr} without ALPN \"{POSTGRESQL_ALPN}\""
+            )));
+        }
+    }
+
     // TLS negotiation successful.
     // Continue with regular startup using encrypted connection.
     match get_startup::<tokio_native_tls::TlsStream<TcpStream>>(&mut stream).await {
@@ -155,6 +316,7 @@ pub async fn startup_tls(
                 client_server_map,
                 admin_only,
                 connection_id,
+                default_database,
                 #[cfg(unix)]
                 raw_fd,
                 #[cfg(all(unix, feature = "tls-migration"))]
@@ -198,6 +360,7 @@ where
         client_server_map: ClientServerMap,
         admin_only: bool,
         connection_id: u64,
+        default_database: Option<String>,
         #[cfg(unix)] raw_fd: Option<std::os::unix::io::RawFd>,
         #[cfg(all(unix, feature = "tls-migration"))] ssl_ptr: Option<super::core::SslRawPtr>,
     ) -> Result<Client<S, T>, Error> {
@@ -224,22 +387,105 @@ where
             }
         };
 
-        let pool_name = parameters
-            .get("database")
-            .unwrap_or(username_from_parameters)
-            .to_string();
+        let pool_name = {
+            let config = get_config();
+            listener_database(
+                parameters.get("database").map(String::as_str),
+                username_from_parameters,
+                default_database.as_deref(),
+                |name| config.pools.contains_key(name),
+            )
+        };
+
+        if get_config()
+            .pools
+            .get(&pool_name)
+            .is_some_and(|pool| pool.log_startup_parameters)
+        {
+            info!(
+                "[{username_from_parameters}@{pool_name} #c{connection_id}] startup parameters from {}: {}",
+                transport.peer_display(),
+                format_startup_parameters(&parameters)
+            );
+        }
 
         let application_name = match parameters.get("application_name") {
             Some(application_name) => application_name,
             None => "pg_doorman",
         };
 
+        // libpq rejects unknown connection keywords, so the tag and shard key
+        // may also arrive as `options=-c pg_doorman.tag=...`.
+        let startup_value = |name: &str| {
+            parameters.get(name).cloned().or_else(|| {
+                parameters.get("options").and_then(|options| {
+                    crate::server::parameters::parse_startup_options(options)
+                        .into_iter()
+                        .find(|(key, _)| key == name)
+                        .map(|(_, value)| value)
+                })
+            })
+        };
+        let tag = startup_value(CLIENT_TAG_PARAMETER).and_then(|raw| normalize_tag(&raw));
+        let shard_key = startup_value(CLIENT_SHARD_PARAMETER)
+            .map(|raw| raw.trim().to_string())
+            .filter(|shard| !shard.is_empty());
+        let requested_pool_mode = match startup_value(CLIENT_POOL_MODE_PARAMETER) {
+            None => None,
+            Some(raw) => match raw.trim().to_ascii_lowercase().as_str() {
+                "session" => Some(PoolMode::Session),
+                "transaction" => Some(PoolMode::Transaction),
+                _ => {
+                    let message = format!(
+                        "invalid value for parameter \"{CLIENT_POOL_MODE_PARAMETER}\": \"{raw}\""
+                    );
+                    error_response_terminal(&mut write, &message, "22023").await?;
+                    return Err(Error::ClientError(format!(
+                        "invalid {CLIENT_POOL_MODE_PARAMETER} value '{raw}'"
+                    )));
+                }
+            },
+        };
+
+        let target_session_attrs = match startup_value(CLIENT_TARGET_SESSION_ATTRS_PARAMETER) {
+            None => None,
+            Some(raw) => match TargetSessionAttrs::parse(&raw) {
+                Some(attrs) => Some(attrs),
+                None => {
+                    let message = format!(
+                        "invalid value for parameter \"{CLIENT_TARGET_SESSION_ATTRS_PARAMETER}\": \"{raw}\""
+                    );
+                    error_response_terminal(&mut write, &message, "22023").await?;
+                    return Err(Error::ClientError(format!(
+                        "invalid {CLIENT_TARGET_SESSION_ATTRS_PARAMETER} value '{raw}'"
+                    )));
+                }
+            },
+        };
+
+        let query_timeout_ms = match startup_value(QUERY_TIMEOUT_PARAMETER) {
+            None => None,
+            Some(raw) => match parse_query_timeout(&raw) {
+                Some(ms) => Some(ms),
+                None => {
+                    let message = format!(
+                        "invalid value for parameter \"{QUERY_TIMEOUT_PARAMETER}\": \"{raw}\""
+                    );
+                    error_response_terminal(&mut write, &message, "22023").await?;
+                    return Err(Error::ClientError(format!(
+                        "invalid {QUERY_TIMEOUT_PARAMETER} value '{raw}'"
+                    )));
+                }
+            },
+        };
+
         let mut client_identifier = ClientIdentifier::new(
             application_name,
             username_from_parameters,
             &pool_name,
             transport.peer_display().as_str(),
         );
+        client_identifier.requested_pool_mode = requested_pool_mode;
         client_identifier.hba_md5 =
             check_hba(&transport, "md5", username_from_parameters, &pool_name);
         client_identifier.hba_scram = check_hba(
@@ -248,6 +494,12 @@ where
             username_from_parameters,
             &pool_name,
         );
+        if client_identifier.hba_md5 == CheckResult::Deny
+            || client_identifier.hba_scram == CheckResult::Deny
+        {
+            client_identifier.hba_reject_message =
+                hba_reject_message(&transport, username_from_parameters, &pool_name);
+        }
         {
             // If md5 or scram is allowed, we can try to authenticate with Talos.
             let hba_ok = client_identifier.hba_md5 == CheckResult::Allow
@@ -300,6 +552,7 @@ where
                 let token = match extract_talos_token(talos_token, talos_databases).await {
                     Ok(token) => token,
                     Err(err) => {
+                        record_auth_failure("bad_password", &client_identifier.addr);
                         error_response_terminal(
                             &mut write,
                             format!("Invalid Talos token: {err:?}").as_str(),
@@ -315,23 +568,61 @@ where
             }
         }
 
-        let admin = ["pgdoorman", "pgbouncer"]
-            .iter()
-            .filter(|db| **db == pool_name)
-            .count()
-            == 1;
-
-        // Kick any client that's not admin while we're in admin-only mode.
-        if !admin && admin_only {
+        let admin = get_config().general.is_admin_database(&pool_name);
+        let replication = replication_mode(&parameters).map(str::to_string);
+        if admin && replication.is_some() {
             error_response_terminal(
                 &mut write,
-                "is admin only mode: pooler is shut down now",
-                "58006",
+                "replication connections are not supported by the admin console",
+                "0A000",
             )
             .await?;
+            return Err(Error::ClientError(
+                "replication connection to the admin console".into(),
+            ));
+        }
+
+        // With a dedicated admin listener the console is not served on the
+        // data ports; the Unix socket keeps it for local access.
+        let tcp = matches!(transport, ClientTransport::Tcp { .. });
+        if admin && tcp && !admin_only && get_config().general.admin_listen_port.is_some() {
+            let message = format!("database \"{pool_name}\" is only served on the admin port");
+            error_response_terminal(&mut write, &message, "3D000").await?;
+            return Err(Error::ClientError(message));
+        }
+
+        // The admin listener is the only TCP listener that hands out
+        // admin-only connections.
+        if !admin && admin_only && tcp {
+            let message = format!("database \"{pool_name}\" is not served on the admin port");
+            error_response_terminal(&mut write, &message, "3D000").await?;
+            return Err(Error::ClientError(message));
+        }
+
+        // Kick any client that's not admin while we're in admin-only mode.
+        if !admin && admin_only {
+            let message = if crate::app::drain::is_draining() {
+                "is admin only mode: pooler is draining and does not accept new connections"
+            } else {
+                "is admin only mode: pooler is shut down now"
+            };
+            error_response_terminal(&mut write, message, "58006").await?;
             return Err(Error::ShuttingDown);
         }
 
+        if let (false, Some(shard)) = (admin, shard_key.as_deref()) {
+            let known = get_config()
+                .pools
+                .get(&pool_name)
+                .is_some_and(|pool| pool.shards.contains_key(shard));
+            if !known {
+                record_auth_failure("no_pool", &client_identifier.addr);
+                let message = format!("unknown shard key \"{shard}\" for database \"{pool_name}\"");
+                error_response_terminal(&mut write, &message, "3D000").await?;
+                return Err(Error::ClientError(message));
+            }
+        }
+
         // Final HBA decision: if neither md5 nor scram is explicitly allowed or trusted,
         // the connection is not permitted by HBA. `Deny` indicates explicit `reject` rule,
         // while `NotMatched` means no rule matched.
@@ -343,14 +634,14 @@ where
             CheckResult::Allow | CheckResult::Trust
         );
         if !hba_ok_final {
-            error_response_terminal(
-                &mut write,
+            let message = hba_denied_text(
+                &client_identifier,
                 format!("Connection from {} to {}@{} (TLS: {}) is not permitted by HBA configuration. Please contact your database administrator.",
-                        transport.peer_display(), username_from_parameters, pool_name, use_tls).as_str(),
-                "28000"
-            )
-                .await?;
+                        transport.peer_display(), username_from_parameters, pool_name, use_tls),
+            );
+            error_response_terminal(&mut write, &message, "28000").await?;
             crate::web::metrics::record_listener_rejection("hba");
+            record_auth_failure("hba_denied", &client_identifier.addr);
             return Err(Error::HbaForbiddenError(format!(
                 "Connection not permitted by HBA configuration for client: {} from {}",
                 client_identifier,
@@ -362,20 +653,26 @@ where
         // Wrapping is intentional: PostgreSQL uses 32-bit PIDs with the same
         // wrapping behavior. Sequential values give fewer collisions than random
         // at <50K concurrent clients. The random secret_key (below) provides
-        // collision resistance after wrap-around (~2^31 connections).
+        // collision resistance after wrap-around (~2^31 connections) and is
+        // what actually authorizes a CancelRequest: the pid alone is guessable.
         let process_id: i32 = connection_id as i32;
-        let secret_key: i32 = rand::random();
+        let secret_key = generate_secret_key();
 
         // Authenticate user
-        let auth_outcome = authenticate(
-            &mut read,
-            &mut write,
-            admin,
-            &mut client_identifier,
-            &pool_name,
-            username_from_parameters,
-        )
-        .await?;
+        let auth_started = std::time::Instant::now();
+        let auth_outcome = {
+            let _waiting = WaitGuard::enter(&CLIENTS_IN_AUTH);
+            authenticate(
+                &mut read,
+                &mut write,
+                admin,
+                &mut client_identifier,
+                &pool_name,
+                username_from_parameters,
+            )
+            .await?
+        };
+        crate::web::metrics::observe_client_phase("auth", auth_started.elapsed().as_secs_f64());
         let transaction_mode = auth_outcome.transaction_mode;
         let mut server_parameters = auth_outcome.server_parameters;
         let prepared_statements_enabled = auth_outcome.prepared_statements_enabled;
@@ -384,19 +681,98 @@ where
         // snapshot. Configured startup_parameters win, because
         // the backend will run with those values. `startup = true`
         // keeps non-ParameterStatus GUCs such as search_path and role
-        // available for checkout sync.
-        for (key, value) in &parameters {
-            if !crate::server::parameters::is_safe_client_startup_key(key) {
+        // available for checkout sync. GUCs passed via `options=-c ...`
+        // come first so explicit StartupMessage keys override them, matching
+        // the backend's own ordering. Keys in ignore_startup_parameters are
+        // dropped, so the client runs with the pool's value and checkout
+        // never has to SET them.
+        let ignored_keys: std::collections::HashSet<String> = {
+            let config = get_config();
+            match config.pools.get(&pool_name) {
+                Some(pool) => pool.resolve_ignore_startup_parameters(&config.general),
+                None => &config.general.ignore_startup_parameters,
+            }
+            .iter()
+            .map(|name| crate::server::parameters::canonicalize_param_name(name.clone()))
+            .collect()
+        };
+        let option_params = parameters
+            .get("options")
+            .map(|options| crate::server::parameters::parse_startup_options(options))
+            .unwrap_or_default();
+        for (key, value) in option_params.iter().map(|(k, v)| (k, v)).chain(
+            parameters
+                .iter()
+                .filter(|(key, _)| key.as_str() != "options"),
+        ) {
+            if key == CLIENT_TAG_PARAMETER
+                || key == CLIENT_SHARD_PARAMETER
+                || key == CLIENT_POOL_MODE_PARAMETER
+                || key == CLIENT_TARGET_SESSION_ATTRS_PARAMETER
+                || key == QUERY_TIMEOUT_PARAMETER
+                || !crate::server::parameters::is_safe_client_startup_key(key)
+            {
+                continue;
+            }
+            let canonical = crate::server::parameters::canonicalize_param_name(key.clone());
+            if ignored_keys.contains(&canonical) {
                 continue;
             }
             if let Some(keys) = auth_outcome.operator_managed_keys.as_ref() {
-                let canonical = crate::server::parameters::canonicalize_param_name(key.clone());
                 if keys.contains(&canonical) {
                     continue;
                 }
             }
             let _ = server_parameters.set_param(key.clone(), value.clone(), true);
         }
+        // A pool's default_client_statement_timeout_ms stands in for a
+        // statement_timeout the client did not send: it goes out in the
+        // ParameterStatus below and checkout sync sets it on the server.
+        let default_statement_timeout = get_config()
+            .pools
+            .get(&pool_name)
+            .and_then(|pool| pool.default_client_statement_timeout_ms)
+            .filter(|ms| *ms > 0);
+        if let Some(ms) = default_statement_timeout {
+            let operator_managed = auth_outcome
+                .operator_managed_keys
+                .as_ref()
+                .is_some_and(|keys| keys.contains("statement_timeout"));
+            if !operator_managed
+                && !server_parameters
+                    .parameters
+                    .contains_key("statement_timeout")
+            {
+                server_parameters.set_param("statement_timeout", ms.to_string(), true);
+            }
+        }
+        // Per-tag cap is checked after authentication so only clients that
+        // could have connected anyway are counted against their tag.
+        let tag_guard = match tag.as_deref() {
+            Some(tag) if !admin => {
+                let limit = get_config().general.max_connections_per_tag;
+                match try_acquire(tag, limit) {
+                    Some(guard) => Some(guard),
+                    None => {
+                        warn!(
+                            "[{username_from_parameters}@{pool_name} #c{connection_id}] client {} rejected: tag '{tag}' reached max_connections_per_tag={limit}",
+                            transport.peer_display()
+                        );
+                        error_response_terminal(
+                            &mut write,
+                            &format!("too many connections for client tag \"{tag}\""),
+                            "53300",
+                        )
+                        .await?;
+                        return Err(Error::ClientError(format!(
+                            "max_connections_per_tag reached for tag '{tag}'"
+                        )));
+                    }
+                }
+            }
+            _ => None,
+        };
+
         let mut buf = BytesMut::new();
         {
             let mut auth_ok = BytesMut::with_capacity(9);
@@ -404,8 +780,16 @@ where
             auth_ok.put_i32(8);
             auth_ok.put_i32(0);
             buf.put(auth_ok);
-            let server_params_buf: BytesMut = (&server_parameters).into();
-            buf.put(server_params_buf);
+            let overrides = {
+                let config = get_config();
+                match config.pools.get(&pool_name) {
+                    Some(pool) => pool.resolve_parameter_status_overrides(&config.general),
+                    None => crate::config::startup_parameters::cascade_canonical_keys(&[&config
+                        .general
+                        .parameter_status_overrides]),
+                }
+            };
+            buf.put(server_parameters.to_bytes_with_overrides(&overrides));
             let mut key_data = BytesMut::from(&b"K"[..]);
             key_data.put_i32(12);
             key_data.put_i32(process_id);
@@ -415,15 +799,18 @@ where
         }
         write_all_flush(&mut write, &buf).await?;
 
-        let stats = Arc::new(ClientStats::new(
-            connection_id,
-            client_identifier.application_name.as_str(),
-            client_identifier.username.as_str(),
-            &pool_name,
-            addr.to_string().as_str(),
-            crate::utils::clock::now(),
-            use_tls,
-        ));
+        let stats = Arc::new(
+            ClientStats::new(
+                connection_id,
+                client_identifier.application_name.as_str(),
+                client_identifier.username.as_str(),
+                &pool_name,
+                addr.to_string().as_str(),
+                crate::utils::clock::now(),
+                use_tls,
+            )
+            .with_tag(tag.as_deref().unwrap_or_default()),
+        );
 
         let config = get_config();
         let anon_cache_size =
@@ -442,6 +829,8 @@ where
             client_server_map,
             stats,
             admin,
+            admin_readonly: auth_outcome.admin_readonly,
+            session_feature_warned: false,
             last_server_stats: None,
             connected_to_server: false,
             session_xact_start: None,
@@ -450,8 +839,20 @@ where
             server_parameters,
             prepared: PreparedStatementState::new(prepared_statements_enabled, anon_cache_size),
             client_last_messages_in_tx: PooledBuffer::new(),
+            recent_messages: RecentMessageTypes::default(),
+            last_server_id: None,
             max_memory_usage: config.general.max_memory_usage.as_bytes(),
             client_pending_begin: None,
+            max_query_duration_ms: 0,
+            max_result_rows: 0,
+            retry_readonly: false,
+            query_timeout_ms,
+            server_max_age_ms: 0,
+            shard_key,
+            target_session_attrs,
+            replication,
+            relayed_server: auth_outcome.relayed_server.map(Box::new),
+            _tag_guard: tag_guard,
             #[cfg(unix)]
             raw_fd,
             #[cfg(all(unix, feature = "tls-migration"))]
@@ -467,6 +868,11 @@ where
         mut bytes: BytesMut, // The rest of the startup message.
         client_server_map: ClientServerMap,
     ) -> Result<Client<S, T>, Error> {
+        // A CancelRequest is exactly pid + secret; anything shorter is a
+        // malformed packet, not a key to look up.
+        if bytes.remaining() < 8 {
+            return Err(Error::ClientBadStartup);
+        }
         let target_process_id = bytes.get_i32();
         let target_secret_key = bytes.get_i32();
         // In cancel mode, connection_id stores the target's process_id for lookup.
@@ -484,6 +890,8 @@ where
             client_server_map,
             stats: Arc::new(ClientStats::default()),
             admin: false,
+            admin_readonly: false,
+            session_feature_warned: false,
             last_server_stats: None,
             pool_name: String::from("undefined"),
             username: String::from("undefined"),
@@ -492,8 +900,20 @@ where
             connected_to_server: false,
             session_xact_start: None,
             client_last_messages_in_tx: PooledBuffer::new(),
+            recent_messages: RecentMessageTypes::default(),
+            last_server_id: None,
             max_memory_usage: 128 * 1024 * 1024,
             client_pending_begin: None,
+            max_query_duration_ms: 0,
+            max_result_rows: 0,
+            retry_readonly: false,
+            query_timeout_ms: None,
+            server_max_age_ms: 0,
+            shard_key: None,
+            target_session_attrs: None,
+            replication: None,
+            relayed_server: None,
+            _tag_guard: None,
             #[cfg(unix)]
             raw_fd: None,
             #[cfg(all(unix, feature = "tls-migration"))]
@@ -501,3 +921,120 @@ where
         })
     }
 }
+
+/// Renders startup parameters as `key=value` pairs sorted by key, with
+/// values quoted so empty strings and spaces stay visible.
+fn format_startup_parameters(parameters: &HashMap<String, String>) -> String {
+    let mut pairs: Vec<_> = parameters.iter().collect();
+    pairs.sort_unstable();
+    pairs
+        .into_iter()
+        .map(|(key, value)| format!("{key}={value:?}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        format_startup_parameters, generate_secret_key, get_startup, tls_handshake_failure_cause,
+        within_startup_deadline, MAX_STARTUP_PACKET_LEN,
+    };
+    use crate::errors::Error;
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn startup_parameters_are_logged_sorted_and_quoted() {
+        let parameters = [
+            ("user", "app"),
+            ("options", "-c search_path=a,b"),
+            ("application_name", ""),
+            ("database", "db"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        assert_eq!(
+            format_startup_parameters(&parameters),
+            r#"application_name="" database="db" options="-c search_path=a,b" user="app""#
+        );
+    }
+
+    #[test]
+    fn tls_handshake_failures_are_classified_by_openssl_reason() {
+        assert_eq!(
+            tls_handshake_failure_cause(
+                "error:0A000086:SSL routines:tls_post_process_client_certificate:certificate verify failed:ssl/statem/statem_srvr.c:3513: (certificate has expired)"
+            ),
+            "cert_expired"
+        );
+        assert_eq!(
+            tls_handshake_failure_cause(
+                "error:0A000418:SSL routines:ssl3_read_bytes:tlsv1 alert unknown ca:ssl/record/rec_layer_s3.c:1605:SSL alert number 48"
+            ),
+            "cert_unknown_ca"
+        );
+        assert_eq!(
+            tls_handshake_failure_cause(
+                "error:0A00042E:SSL routines:ssl3_read_bytes:tlsv1 alert protocol version:ssl/record/rec_layer_s3.c:1605:SSL alert number 70"
+            ),
+            "alert"
+        );
+        assert_eq!(
+            tls_handshake_failure_cause(
+                "error:0A0000C1:SSL routines:tls_post_process_client_hello:no shared cipher:ssl/statem/statem_srvr.c:2220:"
+            ),
+            "protocol"
+        );
+        assert_eq!(
+            tls_handshake_failure_cause("unexpected EOF during handshake"),
+            "other"
+        );
+    }
+
+    #[test]
+    fn secret_keys_are_nonzero_and_not_repeated() {
+        let keys: std::collections::HashSet<i32> =
+            (0..100).map(|_| generate_secret_key()).collect();
+        assert!(!keys.contains(&0));
+        // 100 draws from 2^32 values: a repeat means the source is broken.
+        assert_eq!(keys.len(), 100);
+    }
+
+    #[tokio::test]
+    async fn oversized_startup_packet_is_answered_with_08p01() {
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        client
+            .write_all(&(MAX_STARTUP_PACKET_LEN + 1).to_be_bytes())
+            .await
+            .unwrap();
+        assert_eq!(
+            get_startup(&mut server).await.unwrap_err(),
+            Error::ClientBadStartup
+        );
+        drop(server);
+        let mut reply = Vec::new();
+        client.read_to_end(&mut reply).await.unwrap();
+        assert_eq!(reply[0], b'E');
+        let reply = String::from_utf8_lossy(&reply);
+        assert!(reply.contains("08P01"));
+        assert!(reply.contains("invalid length of startup packet"));
+    }
+
+    #[tokio::test]
+    async fn startup_steps_fail_once_the_deadline_passes() {
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(20);
+        let stalled = within_startup_deadline(deadline.into(), async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        })
+        .await;
+        assert_eq!(stalled, Err(Error::ClientStartupTimeout));
+
+        let quick = within_startup_deadline(deadline.into(), async { Ok(7) }).await;
+        assert_eq!(quick, Ok(7));
+        let unbounded = within_startup_deadline(None, async { Ok(7) }).await;
+        assert_eq!(unbounded, Ok(7));
+    }
+}
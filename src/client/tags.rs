@@ -0,0 +1,160 @@
+//! Client tags.
+//!
+//! A client may send `pg_doorman.tag` in its StartupMessage to label the
+//! connection (for example with a deployment name). The tag shows up in
+//! SHOW CLIENTS and, with `max_connections_per_tag`, caps how many clients
+//! sharing the tag may be connected at once across all pools.
+
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+
+/// StartupMessage parameter carrying the client tag.
+pub const CLIENT_TAG_PARAMETER: &str = "pg_doorman.tag";
+
+/// Longest tag kept; longer values are truncated so a client cannot blow up
+/// SHOW CLIENTS output or the counter map.
+const MAX_TAG_LEN: usize = 64;
+
+static TAG_COUNTS: Lazy<Mutex<HashMap<String, u64>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Normalizes a raw startup value into a tag. Empty values mean "no tag".
+pub fn normalize_tag(raw: &str) -> Option<String> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    let mut end = trimmed.len().min(MAX_TAG_LEN);
+    while !trimmed.is_char_boundary(end) {
+        end -= 1;
+    }
+    Some(trimmed[..end].to_string())
+}
+
+/// Holds one slot of a tag's connection budget; released on drop.
+#[derive(Debug)]
+pub struct TagGuard {
+    tag: String,
+}
+
+impl TagGuard {
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+}
+
+impl Drop for TagGuard {
+    fn drop(&mut self) {
+        let mut counts = TAG_COUNTS.lock();
+        if let Some(count) = counts.get_mut(&self.tag) {
+            *count -= 1;
+            if *count == 0 {
+                counts.remove(&self.tag);
+            }
+        }
+    }
+}
+
+/// Takes a slot for `tag`. Returns `None` when `limit` clients with this tag
+/// are already connected; `limit == 0` means unlimited.
+pub fn try_acquire(tag: &str, limit: u64) -> Option<TagGuard> {
+    let mut counts = TAG_COUNTS.lock();
+    let count = counts.entry(tag.to_string()).or_insert(0);
+    if limit > 0 && *count >= limit {
+        if *count == 0 {
+            counts.remove(tag);
+        }
+        return None;
+    }
+    *count += 1;
+    Some(TagGuard {
+        tag: tag.to_string(),
+    })
+}
+
+/// Number of connected clients carrying `tag`.
+pub fn tag_connections(tag: &str) -> u64 {
+    TAG_COUNTS.lock().get(tag).copied().unwrap_or(0)
+}
+
+/// GUC carrying the client address on the server (`client_label_gucs`).
+pub const CLIENT_ADDR_GUC: &str = "pg_doorman.client_addr";
+
+/// GUC carrying the client tag on the server, empty without a tag.
+pub const CLIENT_TAG_GUC: &str = "pg_doorman.client_tag";
+
+/// `SET` statements labelling a server connection with its current client.
+/// Values go into `E''` literals so quotes and backslashes from the client
+/// tag cannot escape them whatever `standard_conforming_strings` says.
+pub fn client_labels_query(addr: &str, tag: &str) -> String {
+    fn literal(value: &str) -> String {
+        let escaped: String = value
+            .chars()
+            .filter(|&c| c != '\0')
+            .flat_map(|c| match c {
+                '\'' => vec!['\'', '\''],
+                '\\' => vec!['\\', '\\'],
+                c => vec![c],
+            })
+            .collect();
+        format!("E'{escaped}'")
+    }
+    format!(
+        "SET {CLIENT_ADDR_GUC} = {}; SET {CLIENT_TAG_GUC} = {}",
+        literal(addr),
+        literal(tag)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_trims_and_truncates() {
+        assert_eq!(normalize_tag("  "), None);
+        assert_eq!(normalize_tag(" web-blue "), Some("web-blue".to_string()));
+        let long = "x".repeat(200);
+        assert_eq!(normalize_tag(&long).unwrap().len(), MAX_TAG_LEN);
+        let multibyte = "ж".repeat(40);
+        assert!(normalize_tag(&multibyte).unwrap().len() <= MAX_TAG_LEN);
+    }
+
+    #[test]
+    fn limit_is_enforced_and_released_on_drop() {
+        let tag = "tags-test-limit";
+        let first = try_acquire(tag, 2).unwrap();
+        let second = try_acquire(tag, 2).unwrap();
+        assert!(try_acquire(tag, 2).is_none());
+        assert_eq!(tag_connections(tag), 2);
+
+        drop(first);
+        let third = try_acquire(tag, 2).unwrap();
+        assert_eq!(third.tag(), tag);
+        drop(second);
+        drop(third);
+        assert_eq!(tag_connections(tag), 0);
+    }
+
+    #[test]
+    fn client_labels_query_escapes_values() {
+        assert_eq!(
+            client_labels_query("10.0.0.7", ""),
+            "SET pg_doorman.client_addr = E'10.0.0.7'; SET pg_doorman.client_tag = E''"
+        );
+        assert_eq!(
+            client_labels_query("10.0.0.7", "it's\\x"),
+            "SET pg_doorman.client_addr = E'10.0.0.7'; SET pg_doorman.client_tag = E'it''s\\\\x'"
+        );
+    }
+
+    #[test]
+    fn zero_limit_is_unlimited() {
+        let tag = "tags-test-unlimited";
+        let guards: Vec<_> = (0..10).map(|_| try_acquire(tag, 0).unwrap()).collect();
+        assert_eq!(tag_connections(tag), 10);
+        drop(guards);
+        assert_eq!(tag_connections(tag), 0);
+    }
+}
@@ -3,29 +3,47 @@ use log::{debug, error, info, warn};
 use std::future::{poll_fn, Future};
 use std::ops::DerefMut;
 use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use std::task::Poll;
 use std::time::Duration;
+use tokio::io::AsyncBufReadExt;
 
 use crate::utils::clock::now;
+use crate::utils::format_duration_ms;
 
 use crate::admin::handle_admin;
+use crate::app::client_notice;
 use crate::app::server::{
     CLIENTS_IN_TRANSACTIONS, MIGRATION_IN_PROGRESS, MIGRATION_TX, SHUTDOWN_IN_PROGRESS,
 };
 use crate::client::batch_handling::PARSE_COMPLETE_MSG;
 use crate::client::core::{BatchOperation, Client, PreparedStatementKey};
-use crate::client::util::{is_standalone_begin, QUERY_DEALLOCATE};
+use crate::client::query_deadline::{
+    clamp_notice, effective_limit, parse_query_timeout_statement, result_rows_exceeded,
+    QueryDeadline, QueryTimeoutStatement, QUERY_TIMEOUT_PARAMETER, RESULT_ROWS_SETTING,
+};
+use crate::client::tags::client_labels_query;
+use crate::client::util::{
+    client_gone, is_retryable_read, is_standalone_begin, is_write_statement, parse_deallocate,
+    session_only_feature, DeallocateTarget, MAX_DEALLOCATE_QUERY_LEN,
+};
+use crate::config::UnknownMessageBehavior;
 use crate::errors::Error;
 use crate::messages::{
-    deallocate_response, ends_with_idle_ready_for_query, error_response, error_response_terminal,
-    has_error_response, insert_close_complete_after_last_close_complete, read_message_reuse,
-    write_all_flush,
+    command_complete, deallocate_response, ends_with_idle_ready_for_query, error_response,
+    error_response_terminal, has_error_response, insert_close_complete_after_last_close_complete,
+    notice_message, read_message_reuse, ready_for_query, write_all_flush,
 };
+use crate::pool::query_limit::{self, QueryLimitExceeded};
 use crate::pool::CANCELED_PIDS;
-use crate::server::Server;
+use crate::server::{Server, ServerCloseCause};
 use crate::utils::buffering_writer::BufferingWriter;
 use crate::utils::debug_messages::{log_client_to_server, log_server_to_client};
-use crate::web::metrics::{POOLER_CHECK_QUERY_BACKEND_TOTAL, POOLER_CHECK_QUERY_CACHE_TOTAL};
+use crate::web::metrics::{
+    observe_client_phase, record_affinity_checkout, record_server_max_age_recycle,
+    record_session_feature_in_txn, POOLER_CHECK_QUERY_BACKEND_TOTAL,
+    POOLER_CHECK_QUERY_CACHE_TOTAL,
+};
 
 // =============================================================================
 // PostgreSQL Extended Query Protocol - Documentation
@@ -158,6 +176,45 @@ impl Drop for TransactionGuard {
 enum NextClientMessage {
     Message(BytesMut),
     ServerDead,
+    /// Session-mode client stayed idle outside a transaction past `client_idle_timeout`.
+    IdleTimeout,
+}
+
+/// Server a client runs its transaction (or session) on.
+enum CheckedOut {
+    Pooled(crate::pool::Object),
+    /// The client's own backend connection in a `scram_relay` pool. It
+    /// goes back to the client, not to the pool, when the block ends.
+    Relayed(Box<Server>),
+}
+
+impl CheckedOut {
+    fn created_at(&self) -> quanta::Instant {
+        match self {
+            CheckedOut::Pooled(conn) => conn.created_at(),
+            // `server_max_age` is off for relayed servers.
+            CheckedOut::Relayed(_) => now(),
+        }
+    }
+}
+
+impl std::ops::Deref for CheckedOut {
+    type Target = Server;
+    fn deref(&self) -> &Server {
+        match self {
+            CheckedOut::Pooled(conn) => conn,
+            CheckedOut::Relayed(server) => server,
+        }
+    }
+}
+
+impl DerefMut for CheckedOut {
+    fn deref_mut(&mut self) -> &mut Server {
+        match self {
+            CheckedOut::Pooled(conn) => conn,
+            CheckedOut::Relayed(server) => server,
+        }
+    }
 }
 
 /// Action to take after processing a message in the transaction loop
@@ -168,6 +225,20 @@ enum TransactionAction {
     Break,
 }
 
+/// Bytes of a simple query kept for `SHOW ACTIVE_QUERIES`. The preview
+/// shows far fewer characters; the margin covers whitespace that gets
+/// collapsed and multi-byte UTF-8.
+const ACTIVE_QUERY_TEXT_MAX_BYTES: usize = 1024;
+
+/// Query text of a simple Query ('Q') message, capped at
+/// `ACTIVE_QUERY_TEXT_MAX_BYTES`.
+fn simple_query_text(message: &BytesMut) -> Arc<str> {
+    let body = message.get(5..).unwrap_or_default();
+    let body = body.strip_suffix(&[0]).unwrap_or(body);
+    let body = &body[..body.len().min(ACTIVE_QUERY_TEXT_MAX_BYTES)];
+    Arc::from(String::from_utf8_lossy(body))
+}
+
 impl<S, T> Client<S, T>
 where
     S: tokio::io::AsyncRead + std::marker::Unpin,
@@ -202,6 +273,27 @@ where
         false
     }
 
+    /// Marks the server for closing at this transaction boundary if it is
+    /// older than `server_max_age`. Unlike `server_lifetime`, this also
+    /// catches connections that never sit idle in the pool.
+    fn retire_if_over_max_age(&self, server: &mut Server, created_at: quanta::Instant) -> bool {
+        if self.server_max_age_ms == 0 || server.is_bad() {
+            return false;
+        }
+        let age_ms = created_at.elapsed().as_millis() as u64;
+        if age_ms < self.server_max_age_ms {
+            return false;
+        }
+        server.set_close_cause(ServerCloseCause::Age);
+        server.mark_bad(&format!(
+            "server_max_age exceeded (age={}, limit={})",
+            format_duration_ms(age_ms),
+            format_duration_ms(self.server_max_age_ms),
+        ));
+        record_server_max_age_recycle(&self.username, &self.pool_name);
+        true
+    }
+
     /// Ensure server is in copy mode, return error if not
     #[inline(always)]
     fn ensure_copy_mode(&mut self, server: &mut Server) -> Result<(), Error> {
@@ -240,7 +332,18 @@ where
     ///    against `server_readable()`.  Detects dead servers (e.g.
     ///    `pg_terminate_backend`, `idle_in_transaction_session_timeout`) and
     ///    releases the pool slot early instead of holding it indefinitely.
-    async fn wait_for_next_message(&mut self, server: &Server) -> Result<NextClientMessage, Error> {
+    ///
+    /// `idle_timeout` and `notice_seen` are only passed in session mode while
+    /// the server sits outside a transaction and COPY: the first bounds the
+    /// full monitor stage, the second lets it pass on the DRAIN or shutdown
+    /// notice the way `wait_for_input` does.
+    async fn wait_for_next_message(
+        &mut self,
+        server: &Server,
+        idle_timeout: Option<Duration>,
+        mut notice_seen: Option<&mut u64>,
+    ) -> Result<NextClientMessage, Error> {
+        let idle_deadline = idle_timeout.map(|timeout| tokio::time::Instant::now() + timeout);
         let mut read_fut = std::pin::pin!(read_message_reuse(
             &mut self.read,
             &mut self.read_buf,
@@ -262,6 +365,20 @@ where
         }
 
         loop {
+            let announced = client_notice::announced();
+            tokio::pin!(announced);
+            announced.as_mut().enable();
+            if let Some(message) = notice_seen.as_deref_mut().and_then(client_notice::pending) {
+                info!(
+                    "[{}@{} #c{}] notifying client {}: {message}",
+                    self.username, self.pool_name, self.connection_id, self.addr
+                );
+                write_all_flush(
+                    &mut self.write,
+                    &notice_message(&message, client_notice::NOTICE_SQLSTATE),
+                )
+                .await?;
+            }
             tokio::select! {
                 biased;
                 result = &mut read_fut => {
@@ -273,10 +390,40 @@ where
                     }
                     return Ok(NextClientMessage::ServerDead);
                 }
+                _ = &mut announced, if notice_seen.is_some() => {}
+                _ = async {
+                    match idle_deadline {
+                        Some(deadline) => tokio::time::sleep_until(deadline).await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    return Ok(NextClientMessage::IdleTimeout);
+                }
             }
         }
     }
 
+    /// Close a client that exceeded `client_idle_timeout` while idle outside
+    /// a transaction. The caller must have released any server first.
+    async fn close_idle_client(&mut self, idle_timeout_ms: u64) -> Result<(), Error> {
+        info!(
+            "[{}@{} #c{}] closing client {}: idle longer than client_idle_timeout ({})",
+            self.username,
+            self.pool_name,
+            self.connection_id,
+            self.addr,
+            format_duration_ms(idle_timeout_ms)
+        );
+        error_response_terminal(
+            &mut self.write,
+            "terminating connection due to client_idle_timeout",
+            "57P05",
+        )
+        .await?;
+        self.stats.disconnect();
+        Ok(())
+    }
+
     /// Handle cancel mode - when client wants to cancel a previously issued query.
     /// Opens a new separate connection to the server, sends the backend_id
     /// and secret_key and then closes it for security reasons.
@@ -296,8 +443,8 @@ where
                 t.clone()
             }
 
-            // The client doesn't know / got the wrong server,
-            // we're closing the connection for security reasons.
+            // Unknown pid or a secret that does not match it. Close without
+            // a reply so the request cannot be used to probe for live keys.
             None => return Ok(()),
         };
 
@@ -313,7 +460,31 @@ where
         .await
     }
 
-    /// Check for pooler health check and DEALLOCATE queries, handle them without server.
+    /// Counts a statement relying on session state that a transaction-mode
+    /// pool drops when the server connection goes back, and warns once per
+    /// client. The statement is still forwarded.
+    fn note_session_only_feature(&mut self, query: &[u8]) {
+        let Some(feature) = session_only_feature(query) else {
+            return;
+        };
+        record_session_feature_in_txn(&self.username, &self.pool_name, feature);
+        if self.session_feature_warned {
+            return;
+        }
+        self.session_feature_warned = true;
+        warn!(
+            "[{}@{} #c{}] client {} (application_name={}) uses {feature} in transaction pool mode; \
+             its session state is lost once the server connection is released",
+            self.username,
+            self.pool_name,
+            self.connection_id,
+            self.addr,
+            self.server_parameters.get_application_name()
+        );
+    }
+
+    /// Check for pooler health check, DEALLOCATE and (on `reject_write_queries`
+    /// pools) write queries, handle them without server.
     /// Returns `Ok(true)` if query was handled (caller should continue to next iteration),
     /// `Ok(false)` if query needs normal processing.
     #[inline]
@@ -331,8 +502,11 @@ where
         // used as the cache key in `handle_pooler_check_query`, so a
         // RELOAD that races with an in-flight probe can never mix
         // request bytes from one config with a cache key from another.
+        // A `scram_relay` pool has no credentials to open a probe
+        // connection of its own, so the client's server answers.
         let snapshot = crate::config::POOLER_CHECK_QUERY_SNAPSHOT.load_full();
-        if message.len() == snapshot.request_bytes.len()
+        if !pool.settings.scram_relay
+            && message.len() == snapshot.request_bytes.len()
             && snapshot.request_bytes.as_ref() == &message[..]
         {
             self.handle_pooler_check_query(message, pool, &snapshot)
@@ -340,49 +514,121 @@ where
             return Ok(true);
         }
 
-        // Check for DEALLOCATE query and clear client prepared statements cache
-        // Format: Q message = [Q:1][length:4][query][null:1]
-        // QUERY_DEALLOCATE = "deallocate " (11 bytes)
-        if message.len() < 60 && message.len() > QUERY_DEALLOCATE.len() + 6 {
-            let query_bytes = &message[5..message.len() - 1]; // exclude null terminator
-
-            // Case-insensitive check for "deallocate " prefix
-            if query_bytes
-                .get(..QUERY_DEALLOCATE.len())
-                .map(|s| s.eq_ignore_ascii_case(QUERY_DEALLOCATE))
-                .unwrap_or(false)
-            {
-                // Extract statement name after "deallocate "
-                let statement_part = std::str::from_utf8(&query_bytes[QUERY_DEALLOCATE.len()..])
-                    .unwrap_or("")
-                    .trim()
-                    .trim_end_matches(';');
-
-                if statement_part.eq_ignore_ascii_case("all") {
-                    // DEALLOCATE ALL - clear entire client cache
+        // Read-only pool: refuse obvious writes here with a clear message
+        // instead of the server's "cannot execute ... in a read-only
+        // transaction". Only outside a deferred BEGIN, where the client
+        // expects ReadyForQuery('I').
+        if pool.settings.reject_write_queries
+            && self.client_pending_begin.is_none()
+            && message.len() > 5
+            && is_write_statement(&message[5..message.len() - 1])
+        {
+            debug!(
+                "[{}@{} #c{}] rejected write query on read-only pool",
+                self.username, self.pool_name, self.connection_id
+            );
+            error_response(
+                &mut self.write,
+                &format!("pool \"{}\" is read-only", self.pool_name),
+                "25006",
+            )
+            .await?;
+            return Ok(true);
+        }
+
+        // `SET pg_doorman.query_timeout_ms` is pooler state: answer it here
+        // so the value survives server handoffs.
+        if self.client_pending_begin.is_none() && self.try_handle_query_timeout_set(message).await?
+        {
+            return Ok(true);
+        }
+
+        // DEALLOCATE names the client's statements: with prepared statements
+        // enabled they only exist in the pooler's cache, and the server
+        // copies are shared under pooler names and must stay. In transaction
+        // mode the next server is not the one that ran PREPARE either, so
+        // answer it here in both cases. Only a session-mode client without
+        // the cache owns its server-side names, and its query goes through.
+        if (self.prepared.enabled || self.transaction_mode)
+            && message.len() <= MAX_DEALLOCATE_QUERY_LEN + 6
+        {
+            if let Some(target) = parse_deallocate(&message[5..message.len() - 1]) {
+                self.handle_deallocate(target).await?;
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Drops `target` from the client's prepared statement cache, when
+    /// there is one, and answers the `DEALLOCATE`. Server-side copies are
+    /// left alone: other clients share them, and a re-PREPARE under the
+    /// same name goes through the cache again. An unknown name is still
+    /// answered with success, as pgx deallocates statements it never
+    /// prepared here.
+    async fn handle_deallocate(&mut self, target: DeallocateTarget) -> Result<(), Error> {
+        let tag = match &target {
+            DeallocateTarget::All => "DEALLOCATE ALL",
+            DeallocateTarget::Name(_) => "DEALLOCATE",
+        };
+        if self.prepared.enabled {
+            match target {
+                DeallocateTarget::All => {
                     let count = self.prepared.cache.len();
                     self.prepared.cache.clear();
+                    self.stats.clear_prepared_names();
                     info!(
                         "[{}@{} #c{}] DEALLOCATE ALL: cleared {} entries from client prepared statement cache",
                         self.username, self.pool_name, self.connection_id, count
                     );
-                } else if !statement_part.is_empty() {
-                    // DEALLOCATE <name> - remove specific statement from cache
-                    let key = PreparedStatementKey::Named(statement_part.to_string());
+                }
+                DeallocateTarget::Name(name) => {
+                    let key = PreparedStatementKey::Named(name.clone());
                     if self.prepared.cache.pop(&key).is_some() {
+                        self.stats.remove_prepared_name(&name);
                         debug!(
                             "[{}@{} #c{}] DEALLOCATE {}: removed from client cache",
-                            self.username, self.pool_name, self.connection_id, statement_part
+                            self.username, self.pool_name, self.connection_id, name
                         );
                     }
                 }
-
-                write_all_flush(&mut self.write, &deallocate_response()).await?;
-                return Ok(true);
             }
         }
+        let response = deallocate_response(tag, self.client_pending_begin.is_some());
+        write_all_flush(&mut self.write, &response).await
+    }
 
-        Ok(false)
+    /// Answers `SET`/`RESET pg_doorman.query_timeout_ms` outside a
+    /// transaction. Returns `Ok(false)` for any other message.
+    async fn try_handle_query_timeout_set(&mut self, message: &BytesMut) -> Result<bool, Error> {
+        if message[0] != b'Q' || message.len() <= 5 {
+            return Ok(false);
+        }
+        let Some(statement) = parse_query_timeout_statement(&message[5..message.len() - 1]) else {
+            return Ok(false);
+        };
+        let (requested, tag) = match statement {
+            QueryTimeoutStatement::Set(ms) => (ms, "SET"),
+            QueryTimeoutStatement::Reset => (None, "RESET"),
+            QueryTimeoutStatement::Invalid(raw) => {
+                error_response(
+                    &mut self.write,
+                    &format!(
+                        "invalid value for parameter \"{QUERY_TIMEOUT_PARAMETER}\": \"{raw}\""
+                    ),
+                    "22023",
+                )
+                .await?;
+                return Ok(true);
+            }
+        };
+        self.query_timeout_ms = requested;
+        let mut response = clamp_notice(requested, self.max_query_duration_ms).unwrap_or_default();
+        response.put(command_complete(tag));
+        response.put(ready_for_query(false));
+        write_all_flush(&mut self.write, &response).await?;
+        Ok(true)
     }
 
     /// Serve a `general.pooler_check_query` SimpleQuery. The first probe in
@@ -478,6 +724,7 @@ where
         // hash into the next Sync.
         self.prepared.last_bound_for_top = None;
 
+        server.stats.set_current_query(simple_query_text(message));
         self.execute_server_roundtrip(Some(message), server).await?;
         self.stats.query();
         server.stats.query(
@@ -667,25 +914,33 @@ where
         // Clear the buffer
         self.buffer.clear();
 
-        let response = server
-            .recv(&mut self.write, Some(&mut self.server_parameters))
-            .await?;
+        // The reply arrives in chunks of at most the flush threshold; drain
+        // it all before the transaction can complete.
+        loop {
+            let response = server
+                .recv(&mut self.write, Some(&mut self.server_parameters))
+                .await?;
 
-        self.stats.active_write();
-        match write_all_flush(&mut self.write, &response).await {
-            Ok(_) => self.stats.active_idle(),
-            Err(err) => {
-                server.wait_available().await;
-                server.mark_bad(
-                    format!(
-                        "failed to flush CopyDone response to client {}: {:?}",
-                        self.addr, err
-                    )
-                    .as_str(),
-                );
-                return Err(err);
+            self.stats.active_write();
+            match write_all_flush(&mut self.write, &response).await {
+                Ok(_) => self.stats.active_idle(),
+                Err(err) => {
+                    server.wait_available().await;
+                    server.mark_bad(
+                        format!(
+                            "failed to flush CopyDone response to client {}: {:?}",
+                            self.addr, err
+                        )
+                        .as_str(),
+                    );
+                    return Err(err);
+                }
+            };
+
+            if !server.is_data_available() {
+                break;
             }
-        };
+        }
 
         if self.complete_transaction_if_needed(server, false) {
             return Ok(TransactionAction::Break);
@@ -701,12 +956,45 @@ where
             return self.handle_cancel_mode().await;
         }
         self.stats.register(self.stats.clone());
+        // A migrated client arrives with its Named statements already cached.
+        self.publish_prepared_names();
         let pool = match self.admin {
             true => None,
             false => Some(self.get_pool().await?),
         };
+        if let (Some(pool), Some(replication)) = (pool.as_ref(), self.replication.clone()) {
+            return self.handle_replication(pool, &replication).await;
+        }
+
+        let client_idle_timeout_ms = pool
+            .as_ref()
+            .map(|pool| pool.settings.client_idle_timeout_ms)
+            .unwrap_or(0);
+        self.max_query_duration_ms = pool
+            .as_ref()
+            .map(|pool| pool.settings.max_query_duration_ms)
+            .unwrap_or(0);
+        self.max_result_rows = pool
+            .as_ref()
+            .map(|pool| pool.settings.max_result_rows)
+            .unwrap_or(0);
+        if let Some(notice) = clamp_notice(self.query_timeout_ms, self.max_query_duration_ms) {
+            write_all_flush(&mut self.write, &notice).await?;
+        }
+        // A relayed server is the client's only connection, so it is never
+        // retired by age.
+        self.server_max_age_ms = pool
+            .as_ref()
+            .filter(|pool| !pool.settings.scram_relay)
+            .map(|pool| pool.settings.server_max_age_ms)
+            .unwrap_or(0);
 
         let mut query_start_at: quanta::Instant;
+        // Last DRAIN/shutdown announcement passed on to this client.
+        let mut notice_seen = 0u64;
+        // A read-only query whose server broke before answering, to be sent
+        // again without reading from the client.
+        let mut retry_message: Option<BytesMut> = None;
         loop {
             self.stats.idle_read();
 
@@ -716,7 +1004,11 @@ where
             // Single atomic load — reused for both the deferred-log branch
             // and the actual migration branch to avoid redundant reads.
             #[cfg(unix)]
-            if MIGRATION_IN_PROGRESS.load(Ordering::Relaxed) && !self.admin {
+            if MIGRATION_IN_PROGRESS.load(Ordering::Relaxed)
+                && !self.admin
+                && retry_message.is_none()
+                && self.relayed_server.is_none()
+            {
                 if self.client_pending_begin.is_some() || !self.read.buffer().is_empty() {
                     debug!(
                         "[{}@{} #c{}] migration deferred: pending_begin={} read_buf={}",
@@ -786,13 +1078,43 @@ where
                 }
             }
 
-            let message =
-                match read_message_reuse(&mut self.read, &mut self.read_buf, self.max_memory_usage)
-                    .await
-                {
-                    Ok(message) => message,
-                    Err(err) => return self.process_error(err).await,
+            let retrying = retry_message.is_some();
+            let message = if let Some(message) = retry_message.take() {
+                message
+            } else {
+                // A pending deferred BEGIN means the client already saw
+                // ReadyForQuery('T'), so it is inside a transaction.
+                let read_fut = async {
+                    // A deferred BEGIN means the client is inside a transaction;
+                    // it gets the notice once that transaction ends.
+                    if !self.admin && self.client_pending_begin.is_none() {
+                        self.wait_for_input(&mut notice_seen).await?;
+                    }
+                    read_message_reuse(&mut self.read, &mut self.read_buf, self.max_memory_usage)
+                        .await
                 };
+                let read_result =
+                    if client_idle_timeout_ms > 0 && self.client_pending_begin.is_none() {
+                        match tokio::time::timeout(
+                            Duration::from_millis(client_idle_timeout_ms),
+                            read_fut,
+                        )
+                        .await
+                        {
+                            Ok(result) => result,
+                            Err(_) => return self.close_idle_client(client_idle_timeout_ms).await,
+                        }
+                    } else {
+                        read_fut.await
+                    };
+                match read_result {
+                    Ok(message) => {
+                        self.recent_messages.push(message[0]);
+                        message
+                    }
+                    Err(err) => return self.process_error(err).await,
+                }
+            };
             if message[0] as char == 'X' {
                 debug!(
                     "[{}@{} #c{}] client {} sent Terminate",
@@ -816,9 +1138,14 @@ where
             }
             // Handle admin database queries.
             if self.admin {
-                handle_admin(&mut self.write, message, self.client_server_map.clone())
-                    .await
-                    .inspect_err(|_| self.stats.disconnect())?;
+                handle_admin(
+                    &mut self.write,
+                    message,
+                    self.client_server_map.clone(),
+                    self.admin_readonly,
+                )
+                .await
+                .inspect_err(|_| self.stats.disconnect())?;
                 continue;
             }
 
@@ -860,13 +1187,123 @@ where
             // Check if we have a pending BEGIN to send with this query
             let pending_begin = self.client_pending_begin.take();
 
+            // Only a standalone read, once: a query inside a transaction
+            // depends on what ran before it on the lost server.
+            let mut retry_armed = current_pool.settings.retry_readonly_on_disconnect
+                && self.transaction_mode
+                && !retrying
+                && pending_begin.is_none()
+                && message[0] == b'Q'
+                && message.len() > 5
+                && is_retryable_read(&message[5..message.len() - 1]);
+
             let shutdown_in_progress = {
                 // start server.
                 // Grab a server from the pool.
                 let connecting_at = now();
                 self.stats.waiting();
+                // One query_wait_timeout covers the limiters and the
+                // checkout together.
+                let mut timeouts = current_pool.database.timeouts();
+                let wait_deadline = tokio::time::Instant::now()
+                    + crate::config::config_arc()
+                        .general
+                        .query_wait_timeout
+                        .as_std();
+                // Held with the server until the end of this block, so
+                // max_concurrent_transactions and max_concurrent_queries
+                // count transactions on the backend.
+                let query_permits = tokio::select! {
+                    biased;
+                    permits = query_limit::acquire(
+                        &current_pool.address.pool_name,
+                        current_pool.transaction_quota.as_deref(),
+                        wait_deadline,
+                    ) => permits,
+                    _ = client_gone(&mut self.read) => {
+                        info!(
+                            "[{}@{} #c{}] client {} disconnected while waiting for a query slot",
+                            self.username, self.pool_name, self.connection_id, self.addr
+                        );
+                        return Ok(());
+                    }
+                };
+                let _query_permits = match query_permits {
+                    Ok(permits) => permits,
+                    Err(exceeded) => {
+                        self.stats.idle_read();
+                        current_pool.address.stats.error_with_sqlstate("53300");
+                        self.stats.checkout_error();
+
+                        if message[0] as char == 'S' {
+                            self.reset_buffered_state();
+                        }
+
+                        let limit = match exceeded {
+                            QueryLimitExceeded::User(limit) => {
+                                format!("user max_concurrent_transactions ({limit})")
+                            }
+                            QueryLimitExceeded::Pool(limit) => {
+                                format!("pool max_concurrent_queries ({limit})")
+                            }
+                            QueryLimitExceeded::Global(limit) => {
+                                format!("general max_concurrent_queries ({limit})")
+                            }
+                        };
+                        error_response(
+                            &mut self.write,
+                            &format!("Too many concurrent queries: {limit} reached and query_wait_timeout expired. Please try again later."),
+                            "53300",
+                        )
+                        .await?;
+
+                        error!(
+                            "[{}@{} #c{}] query_wait_timeout expired waiting for {limit}",
+                            self.username, self.pool_name, self.connection_id,
+                        );
+                        return Err(Error::AllServersDown);
+                    }
+                };
                 let mut conn = loop {
-                    match current_pool.database.get().await {
+                    if current_pool.settings.scram_relay {
+                        match self.relayed_server.take() {
+                            Some(server) if !server.is_bad() => break CheckedOut::Relayed(server),
+                            _ => {
+                                self.stats.idle_read();
+                                error_response_terminal(
+                                    &mut self.write,
+                                    "the server connection of this session is closed",
+                                    "08006",
+                                )
+                                .await?;
+                                self.stats.disconnect();
+                                return Ok(());
+                            }
+                        }
+                    }
+                    // Dropping the checkout when the client goes away gives
+                    // back its place in the queue and any permit at once,
+                    // instead of opening a server nobody will use.
+                    let priority = current_pool.settings.user.priority.unwrap_or(0);
+                    let preferred_server = if current_pool.settings.prefer_last_server {
+                        self.last_server_id
+                    } else {
+                        None
+                    };
+                    timeouts.wait =
+                        Some(wait_deadline.saturating_duration_since(tokio::time::Instant::now()));
+                    let checkout = tokio::select! {
+                        biased;
+                        checkout = current_pool.database.timeout_get(&timeouts, priority, preferred_server) => checkout,
+                        _ = client_gone(&mut self.read) => {
+                            info!(
+                                "[{}@{} #c{}] client {} disconnected while waiting for a server",
+                                self.username, self.pool_name, self.connection_id, self.addr
+                            );
+                            return Ok(());
+                        }
+                    };
+                    match checkout {
                         Ok(mut conn) => {
                             // check server candidate in canceled pids.
                             {
@@ -879,7 +1316,7 @@ where
                             }
                             // checkin_cleanup before give server to client.
                             match conn.checkin_cleanup().await {
-                                Ok(()) => break conn,
+                                Ok(()) => break CheckedOut::Pooled(conn),
                                 Err(err) => {
                                     warn!(
                                         "[{}@{} #c{}] server cleanup error: {err}",
@@ -986,14 +1423,24 @@ where
                         }
                     };
                 };
+                let server_created_at = conn.created_at();
                 let server = conn.deref_mut();
-                server
-                    .stats
-                    .active(self.stats.application_name().to_string());
+                let server_id = server.stats.server_id();
+                if current_pool.settings.prefer_last_server {
+                    if let Some(last) = self.last_server_id {
+                        record_affinity_checkout(
+                            &self.username,
+                            &self.pool_name,
+                            last == server_id,
+                        );
+                    }
+                }
+                self.last_server_id = Some(server_id);
+                let application_name = self.stats.application_name();
+                server.stats.active(application_name.clone());
                 let checkout_us = connecting_at.elapsed().as_micros() as u64;
-                server
-                    .stats
-                    .checkout_time(checkout_us, self.stats.application_name().to_string());
+                observe_client_phase("checkout", checkout_us as f64 / 1_000_000.0);
+                server.stats.checkout_time(checkout_us, application_name);
                 // Update client-side wait tracking so SHOW POOLS maxwait
                 // reflects real checkout peaks, not the zero from init.
                 self.stats
@@ -1050,6 +1497,19 @@ where
 
                 if current_pool.settings.sync_server_parameters {
                     server.sync_parameters(&self.server_parameters).await?;
+                } else if current_pool.settings.default_client_statement_timeout_ms > 0 {
+                    server
+                        .sync_statement_timeout(&self.server_parameters)
+                        .await?;
+                }
+                if let Some(role) = current_pool.settings.user.server_role.as_deref() {
+                    server.set_role(role).await?;
+                }
+                if current_pool.settings.client_label_gucs {
+                    server.queue_client_labels(&client_labels_query(
+                        &self.addr.ip().to_string(),
+                        self.stats.tag(),
+                    ));
                 }
                 server.set_async_mode(false);
 
@@ -1115,8 +1575,26 @@ where
                     let message = match initial_message {
                         None => {
                             self.stats.active_read();
-                            match self.wait_for_next_message(server).await {
-                                Ok(NextClientMessage::Message(msg)) => msg,
+                            let session_idle = !self.transaction_mode
+                                && !server.in_transaction()
+                                && !server.in_copy_mode();
+                            let idle_timeout = (session_idle && client_idle_timeout_ms > 0)
+                                .then(|| Duration::from_millis(client_idle_timeout_ms));
+                            let notice_seen = session_idle.then_some(&mut notice_seen);
+                            match self
+                                .wait_for_next_message(server, idle_timeout, notice_seen)
+                                .await
+                            {
+                                Ok(NextClientMessage::Message(msg)) => {
+                                    self.recent_messages.push(msg[0]);
+                                    msg
+                                }
+                                Ok(NextClientMessage::IdleTimeout) => {
+                                    server.checkin_cleanup().await?;
+                                    self.connected_to_server = false;
+                                    self.release();
+                                    return self.close_idle_client(client_idle_timeout_ms).await;
+                                }
                                 Ok(NextClientMessage::ServerDead) => {
                                     warn!(
                                         "[{}@{} #c{}] server died while idle in transaction pid={}",
@@ -1172,8 +1650,40 @@ where
                     let action = match code {
                         // Query
                         'Q' => {
-                            self.handle_simple_query(&message, server, query_start_at)
-                                .await?
+                            if !server.in_transaction()
+                                && self.try_handle_query_timeout_set(&message).await?
+                            {
+                                TransactionAction::Continue
+                            } else {
+                                if self.transaction_mode && message.len() > 5 {
+                                    self.note_session_only_feature(&message[5..message.len() - 1]);
+                                }
+                                self.retry_readonly =
+                                    std::mem::take(&mut retry_armed) && !server.in_transaction();
+                                let result = self
+                                    .handle_simple_query(&message, server, query_start_at)
+                                    .await;
+                                match result {
+                                    Err(Error::ServerConnectionLost { .. })
+                                        if self.retry_readonly =>
+                                    {
+                                        self.retry_readonly = false;
+                                        warn!(
+                                            "[{}@{} #c{}] server pid={} lost before answering a read-only query, retrying on another server",
+                                            self.username,
+                                            self.pool_name,
+                                            self.connection_id,
+                                            server.get_process_id()
+                                        );
+                                        retry_message = Some(message);
+                                        TransactionAction::Break
+                                    }
+                                    result => {
+                                        self.retry_readonly = false;
+                                        result?
+                                    }
+                                }
+                            }
                         }
 
                         // FunctionCall
@@ -1184,6 +1694,11 @@ where
 
                         // Terminate
                         'X' => {
+                            observe_client_phase(
+                                "active",
+                                server_active_at.elapsed().as_secs_f64(),
+                            );
+                            self.retire_if_over_max_age(server, server_created_at);
                             server.checkin_cleanup().await?;
                             self.stats.disconnect();
                             self.connected_to_server = false;
@@ -1193,6 +1708,17 @@ where
 
                         // Parse
                         'P' => {
+                            // Parse body: statement name, then query text, both
+                            // NUL-terminated.
+                            if self.transaction_mode {
+                                let body = message.get(5..).unwrap_or_default();
+                                if let Some(name_end) = body.iter().position(|&b| b == 0) {
+                                    let text = &body[name_end + 1..];
+                                    let text_end =
+                                        text.iter().position(|&b| b == 0).unwrap_or(text.len());
+                                    self.note_session_only_feature(&text[..text_end]);
+                                }
+                            }
                             self.process_parse_immediate(message, current_pool, server)
                                 .await?;
                             TransactionAction::Continue
@@ -1243,14 +1769,50 @@ where
                         // Copy is done, successfully or not.
                         'c' | 'f' => self.handle_copy_done_fail(&message, server).await?,
 
-                        // Some unexpected message. We either did not implement the protocol correctly
-                        // or this is not a Postgres client we're talking to.
+                        // A message type we do not know: a newer protocol
+                        // version, or not a Postgres client at all.
                         _ => {
-                            error!(
-                                "[{}@{} #c{}] unexpected message code '{}' (ASCII: {}) from client {}",
-                                self.username, self.pool_name, self.connection_id, code, code as u8, self.addr
+                            debug!(
+                                "[{}@{} #c{}] unknown message type '{}' (ASCII: {}) from client {}",
+                                self.username,
+                                self.pool_name,
+                                self.connection_id,
+                                code,
+                                code as u8,
+                                self.addr
                             );
-                            TransactionAction::Continue
+                            let behavior =
+                                crate::config::config_arc().general.unknown_message_behavior;
+                            if behavior == UnknownMessageBehavior::Passthrough
+                                && !self.transaction_mode
+                            {
+                                // Sent in order with the next Sync or Flush.
+                                self.buffer.put(&message[..]);
+                                TransactionAction::Continue
+                            } else {
+                                error!(
+                                    "[{}@{} #c{}] unexpected message code '{}' (ASCII: {}) from client {}",
+                                    self.username, self.pool_name, self.connection_id, code, code as u8, self.addr
+                                );
+                                if behavior != UnknownMessageBehavior::Ignore {
+                                    self.reset_buffered_state();
+                                    error_response_terminal(
+                                        &mut self.write,
+                                        &format!(
+                                            "unsupported frontend message type {}",
+                                            code as u8
+                                        ),
+                                        "08P01",
+                                    )
+                                    .await?;
+                                    return Err(Error::ProtocolSyncError(format!(
+                                        "unsupported frontend message type {} from client {}",
+                                        code as u8, self.addr
+                                    )));
+                                }
+                                // Dropped; the client stays connected.
+                                TransactionAction::Continue
+                            }
                         }
                     };
 
@@ -1265,7 +1827,10 @@ where
                 let shutdown_in_progress = SHUTDOWN_IN_PROGRESS.load(Ordering::Relaxed);
                 if shutdown_in_progress {
                     server.mark_bad("graceful shutdown - releasing server connection");
-                } else if !server.is_async() {
+                } else if retry_message.is_none()
+                    && !self.retire_if_over_max_age(server, server_created_at)
+                    && !server.is_async()
+                {
                     server.checkin_cleanup().await?;
                 }
                 if self.transaction_mode {
@@ -1273,9 +1838,13 @@ where
                         .stats
                         .add_xact_time_and_idle(server_active_at.elapsed().as_micros() as u64);
                 }
+                observe_client_phase("active", server_active_at.elapsed().as_secs_f64());
                 // The server is no longer bound to us, we can't cancel it's queries anymore.
                 self.release();
                 server.stats.wait_idle();
+                if let CheckedOut::Relayed(server) = conn {
+                    self.relayed_server = Some(server);
+                }
                 shutdown_in_progress
             }; // release server.
 
@@ -1305,6 +1874,38 @@ where
         }
     }
 
+    /// Wait until the client has bytes to read, passing on the DRAIN or
+    /// shutdown notice if one is announced while it sits idle. Only waits
+    /// for readability, so `read_message_reuse` still reads the message in
+    /// one uncancelled piece.
+    async fn wait_for_input(&mut self, notice_seen: &mut u64) -> Result<(), Error> {
+        loop {
+            let announced = client_notice::announced();
+            tokio::pin!(announced);
+            announced.as_mut().enable();
+            if let Some(message) = client_notice::pending(notice_seen) {
+                info!(
+                    "[{}@{} #c{}] notifying client {}: {message}",
+                    self.username, self.pool_name, self.connection_id, self.addr
+                );
+                write_all_flush(
+                    &mut self.write,
+                    &notice_message(&message, client_notice::NOTICE_SQLSTATE),
+                )
+                .await?;
+            }
+            if !self.read.buffer().is_empty() {
+                return Ok(());
+            }
+            tokio::select! {
+                biased;
+                // Errors and EOF surface from the read that follows.
+                _ = self.read.fill_buf() => return Ok(()),
+                _ = &mut announced => {}
+            }
+        }
+    }
+
     pub(crate) async fn execute_server_roundtrip(
         &mut self,
         message: Option<&BytesMut>,
@@ -1333,12 +1934,32 @@ where
                 )
                 .await;
             }
+            if matches!(err, Error::ServerConnectionLost { .. }) {
+                // The caller runs the query again on another server.
+                if self.retry_readonly {
+                    return Err(err);
+                }
+                return self.process_error(err).await;
+            }
             return Err(err);
         }
 
         // Debug log: client -> server
         log_client_to_server(&self.addr_str, server.get_process_id(), message);
 
+        // Cancel the backend if this round trip outlives max_query_duration
+        // or the shorter limit the client asked for.
+        let (limit_ms, setting) =
+            effective_limit(self.query_timeout_ms, self.max_query_duration_ms);
+        let mut deadline = (limit_ms > 0).then(|| {
+            server.last_sql_error = None;
+            QueryDeadline::arm(server.cancel_target(), limit_ms, setting)
+        });
+        let mut deadline_notice_sent = false;
+        // Cancel issued once the round trip returns more than max_result_rows.
+        let mut rows_canceled: Option<QueryDeadline> = None;
+        let rows_at_start = server.data_rows_received;
+
         // Pre-calculate fast release conditions (avoids repeated checks)
         let can_fast_release = self.transaction_mode;
 
@@ -1363,9 +1984,39 @@ where
                         self.addr, err
                     );
                     server.mark_bad(&msg);
+                    if matches!(err, Error::ServerConnectionLost { .. }) {
+                        // A large DataRow is streamed to the client from
+                        // inside recv, so rows count as an answer too.
+                        if server.data_rows_received != rows_at_start {
+                            self.retry_readonly = false;
+                        }
+                        if self.retry_readonly {
+                            return Err(err);
+                        }
+                        return self.process_error(err).await;
+                    }
                     return Err(err);
                 }
             };
+            // Part of the answer is on its way to the client.
+            self.retry_readonly = false;
+
+            if rows_canceled.is_none()
+                && result_rows_exceeded(
+                    server.data_rows_received - rows_at_start,
+                    self.max_result_rows,
+                )
+                && !deadline.as_ref().is_some_and(|d| d.fired())
+            {
+                // One cancel per round trip: the row limit replaces the timer.
+                deadline = None;
+                server.last_sql_error = None;
+                rows_canceled = Some(QueryDeadline::cancel_now(
+                    server.cancel_target(),
+                    RESULT_ROWS_SETTING,
+                    format!("{} rows", self.max_result_rows),
+                ));
+            }
 
             // Insert pending ParseComplete messages based on batch_operations order
             // This ensures ParseComplete messages are inserted in the correct position
@@ -1386,6 +2037,21 @@ where
                 self.prepared.pending_close_complete -= inserted;
             }
 
+            // The cancel error follows once the watchdog fired; tell the
+            // client why before forwarding it.
+            if !deadline_notice_sent {
+                if let Some(deadline) = deadline
+                    .as_ref()
+                    .or(rows_canceled.as_ref())
+                    .filter(|d| d.fired())
+                {
+                    let mut with_notice = deadline.notice();
+                    with_notice.put(response);
+                    response = with_notice;
+                    deadline_notice_sent = true;
+                }
+            }
+
             // Debug log: server -> client (after all modifications to show what client actually receives)
             log_server_to_client(&self.addr_str, server.get_process_id(), &response);
 
@@ -1436,6 +2102,20 @@ where
             }
         }
 
+        if let Some(deadline) = deadline.or(rows_canceled) {
+            deadline.finish(server).await;
+        }
+
+        // The client ran SET application_name (or a transaction reverted
+        // one): show the new name in SHOW CLIENTS.
+        if server.application_name_reported {
+            server.application_name_reported = false;
+            if crate::config::config_arc().general.track_application_name {
+                self.stats
+                    .set_application_name(self.server_parameters.get_application_name());
+            }
+        }
+
         Ok(())
     }
 }
@@ -1,14 +1,60 @@
 use bytes::BytesMut;
 use once_cell::sync::Lazy;
 use std::sync::{atomic::AtomicUsize, Arc};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
 
 /// Incrementally count prepared statements
 /// to avoid random conflicts in places where the random number generator is weak.
 pub static PREPARED_STATEMENT_COUNTER: Lazy<Arc<AtomicUsize>> =
     Lazy::new(|| Arc::new(AtomicUsize::new(0)));
 
-// Ignore deallocate queries from pgx.
-pub(crate) static QUERY_DEALLOCATE: &[u8] = "deallocate ".as_bytes();
+/// Longest `DEALLOCATE` query worth parsing: the keywords plus a quoted
+/// identifier of the maximum length, with room for whitespace.
+pub(crate) const MAX_DEALLOCATE_QUERY_LEN: usize = 256;
+
+/// What a `DEALLOCATE [PREPARE] { name | ALL }` statement drops.
+#[derive(Debug, PartialEq)]
+pub(crate) enum DeallocateTarget {
+    All,
+    Name(String),
+}
+
+/// Recognizes `DEALLOCATE [PREPARE] { name | ALL } [;]`. Unquoted names
+/// fold to lower case like PostgreSQL does; quoted names keep their case
+/// and may contain `""`. `None` for any other statement.
+pub(crate) fn parse_deallocate(query: &[u8]) -> Option<DeallocateTarget> {
+    let query = std::str::from_utf8(query).ok()?.trim();
+    let query = query.strip_suffix(';').unwrap_or(query).trim_end();
+    let rest = strip_keyword(query, "DEALLOCATE")?;
+    let rest = strip_keyword(rest, "PREPARE").unwrap_or(rest);
+    if let Some(quoted) = rest.strip_prefix('"') {
+        let name = quoted.strip_suffix('"')?;
+        // Every quote left inside must be a doubled one.
+        if name.replace("\"\"", "").contains('"') || name.is_empty() {
+            return None;
+        }
+        return Some(DeallocateTarget::Name(name.replace("\"\"", "\"")));
+    }
+    if rest.is_empty()
+        || !rest
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+    {
+        return None;
+    }
+    if rest.eq_ignore_ascii_case("ALL") {
+        return Some(DeallocateTarget::All);
+    }
+    Some(DeallocateTarget::Name(rest.to_lowercase()))
+}
+
+/// Strips a leading case-insensitive `keyword` followed by whitespace.
+fn strip_keyword<'a>(query: &'a str, keyword: &str) -> Option<&'a str> {
+    let head = query.get(..keyword.len())?;
+    let rest = &query[keyword.len()..];
+    (head.eq_ignore_ascii_case(keyword) && rest.starts_with(char::is_whitespace))
+        .then(|| rest.trim_start())
+}
 
 /// Size of Q message containing "begin;" or "BEGIN;"
 /// Format: [Q:1][length:4][query:6][null:1] = 12 bytes
@@ -33,3 +79,351 @@ pub(crate) fn is_standalone_begin(message: &BytesMut) -> bool {
     let query = &message[5..11];
     query.eq_ignore_ascii_case(b"begin;")
 }
+
+/// Leading keywords of statements a `reject_write_queries` pool refuses.
+const WRITE_KEYWORDS: &[&[u8]] = &[
+    b"insert",
+    b"update",
+    b"delete",
+    b"merge",
+    b"truncate",
+    b"create",
+    b"alter",
+    b"drop",
+    b"grant",
+    b"revoke",
+];
+
+/// Data-modifying statements a `WITH` query can hold.
+const CTE_WRITE_KEYWORDS: &[&[u8]] = &[b"insert", b"update", b"delete", b"merge"];
+
+/// Checks whether a simple-query text starts with a write keyword, is a
+/// `COPY ... FROM` or is a `WITH` query holding a data-modifying statement.
+/// Words are matched, not parsed, so a keyword inside a literal in a `WITH`
+/// query counts; writes behind `EXECUTE` or a function call are left to
+/// `default_transaction_read_only` on the server.
+pub(crate) fn is_write_statement(query: &[u8]) -> bool {
+    let query = skip_leading_comments(query);
+    let word_len = query.iter().take_while(|b| b.is_ascii_alphabetic()).count();
+    let word = &query[..word_len];
+    if word.eq_ignore_ascii_case(b"copy") {
+        // `COPY (query) TO` only reads; otherwise the table is followed by
+        // FROM or TO.
+        let rest = query[word_len..].trim_ascii_start();
+        return !rest.starts_with(b"(")
+            && sql_words(rest)
+                .find(|w| w.eq_ignore_ascii_case(b"from") || w.eq_ignore_ascii_case(b"to"))
+                .is_some_and(|w| w.eq_ignore_ascii_case(b"from"));
+    }
+    if word.eq_ignore_ascii_case(b"with") {
+        // `FOR UPDATE` and `FOR NO KEY UPDATE` lock rows, they do not write.
+        let mut previous: &[u8] = b"";
+        return sql_words(&query[word_len..]).any(|w| {
+            let lock = w.eq_ignore_ascii_case(b"update")
+                && (previous.eq_ignore_ascii_case(b"for") || previous.eq_ignore_ascii_case(b"key"));
+            previous = w;
+            !lock
+                && CTE_WRITE_KEYWORDS
+                    .iter()
+                    .any(|keyword| w.eq_ignore_ascii_case(keyword))
+        });
+    }
+    WRITE_KEYWORDS
+        .iter()
+        .any(|keyword| word.eq_ignore_ascii_case(keyword))
+}
+
+/// Leading keywords of statements `retry_readonly_on_disconnect` may
+/// run a second time.
+const READ_KEYWORDS: &[&[u8]] = &[b"select", b"values", b"table", b"show"];
+
+/// Checks whether a simple-query text is a single read statement that is
+/// safe to re-run on another server: it starts with `SELECT`, `VALUES`,
+/// `TABLE` or `SHOW`, has no `;` before its end and no `INTO`. Like
+/// `is_write_statement` it only looks at words, so a `;` or `into` inside a
+/// literal rules a statement out; functions with side effects still pass.
+pub(crate) fn is_retryable_read(query: &[u8]) -> bool {
+    let query = skip_leading_comments(query).trim_ascii_end();
+    let query = query.strip_suffix(b";").unwrap_or(query);
+    if query.contains(&b';') {
+        return false;
+    }
+    let mut words = sql_words(query);
+    words
+        .next()
+        .is_some_and(|first| READ_KEYWORDS.iter().any(|k| first.eq_ignore_ascii_case(k)))
+        && !words.any(|word| word.eq_ignore_ascii_case(b"into"))
+}
+
+/// Functions taking a session-level advisory lock; the `_xact_` variants
+/// are released at commit and are fine in transaction mode.
+const SESSION_ADVISORY_LOCKS: &[&[u8]] = &[
+    b"pg_advisory_lock",
+    b"pg_advisory_lock_shared",
+    b"pg_try_advisory_lock",
+    b"pg_try_advisory_lock_shared",
+];
+
+/// Best-effort detection of statements whose effect outlives the
+/// transaction and is lost once a transaction-mode pool hands the server
+/// connection to another client. Returns the feature label:
+/// `temp_table`, `with_hold_cursor`, `advisory_lock` or `listen`.
+/// Keywords inside string literals can match; callers only report.
+pub(crate) fn session_only_feature(query: &[u8]) -> Option<&'static str> {
+    // Cheap filter first: this runs on every statement of a
+    // transaction-mode client.
+    let query = skip_leading_comments(query);
+    let first = sql_words(query).next().unwrap_or_default();
+    let candidate = [b"listen".as_slice(), b"create", b"declare"]
+        .iter()
+        .any(|keyword| first.eq_ignore_ascii_case(keyword))
+        || query
+            .windows(b"advisory_lock".len())
+            .any(|window| window.eq_ignore_ascii_case(b"advisory_lock"));
+    if !candidate {
+        return None;
+    }
+
+    let words: Vec<&[u8]> = sql_words(query).collect();
+    let is = |idx: usize, keywords: &[&[u8]]| {
+        words
+            .get(idx)
+            .is_some_and(|word| keywords.iter().any(|k| word.eq_ignore_ascii_case(k)))
+    };
+    let has_sequence = |sequence: &[&[u8]]| {
+        words.windows(sequence.len()).any(|window| {
+            window
+                .iter()
+                .zip(sequence)
+                .all(|(word, keyword)| word.eq_ignore_ascii_case(keyword))
+        })
+    };
+
+    if is(0, &[b"listen"]) {
+        return Some("listen");
+    }
+    if is(0, &[b"create"]) {
+        // CREATE [GLOBAL | LOCAL] {TEMP | TEMPORARY} ...
+        let kind = if is(1, &[b"global", b"local"]) { 2 } else { 1 };
+        if is(kind, &[b"temp", b"temporary"]) && !has_sequence(&[b"on", b"commit", b"drop"]) {
+            return Some("temp_table");
+        }
+    }
+    if is(0, &[b"declare"]) && has_sequence(&[b"with", b"hold"]) {
+        return Some("with_hold_cursor");
+    }
+    (0..words.len())
+        .any(|idx| is(idx, SESSION_ADVISORY_LOCKS))
+        .then_some("advisory_lock")
+}
+
+/// Identifier-like words of a statement, split on everything else.
+fn sql_words(query: &[u8]) -> impl Iterator<Item = &[u8]> {
+    query
+        .split(|b| !(b.is_ascii_alphanumeric() || *b == b'_'))
+        .filter(|word| !word.is_empty())
+}
+
+fn skip_leading_comments(mut query: &[u8]) -> &[u8] {
+    loop {
+        query = query.trim_ascii_start();
+        if let Some(rest) = query.strip_prefix(b"--") {
+            query = match rest.iter().position(|&b| b == b'\n') {
+                Some(end) => &rest[end + 1..],
+                None => &[],
+            };
+        } else if let Some(rest) = query.strip_prefix(b"/*") {
+            query = match rest.windows(2).position(|w| w == b"*/") {
+                Some(end) => &rest[end + 2..],
+                None => &[],
+            };
+        } else {
+            return query;
+        }
+    }
+}
+
+/// Resolves once the client closes its socket or the socket fails. Data
+/// the client sends meanwhile stays buffered in `read` and the future never
+/// resolves: a client that is still talking has not gone away.
+pub(crate) async fn client_gone<R: AsyncBufRead + Unpin>(read: &mut R) {
+    if let Ok(buf) = read.fill_buf().await {
+        if !buf.is_empty() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        client_gone, is_retryable_read, is_write_statement, parse_deallocate, session_only_feature,
+        DeallocateTarget,
+    };
+    use std::time::Duration;
+    use tokio::io::{AsyncWriteExt, BufReader};
+
+    #[tokio::test]
+    async fn client_gone_resolves_on_close_only() {
+        let (client, server) = tokio::io::duplex(64);
+        let mut read = BufReader::new(server);
+        drop(client);
+        tokio::time::timeout(Duration::from_secs(1), client_gone(&mut read))
+            .await
+            .expect("closed client must be reported");
+
+        let (mut client, server) = tokio::io::duplex(64);
+        let mut read = BufReader::new(server);
+        client.write_all(b"Q").await.unwrap();
+        drop(client);
+        let waited = tokio::time::timeout(Duration::from_millis(50), client_gone(&mut read)).await;
+        assert!(waited.is_err(), "pending input must keep the client alive");
+        assert_eq!(read.buffer(), b"Q");
+    }
+
+    #[test]
+    fn deallocate_statements_are_parsed() {
+        let name = |n: &str| Some(DeallocateTarget::Name(n.to_string()));
+        assert_eq!(
+            parse_deallocate(b"DEALLOCATE ALL"),
+            Some(DeallocateTarget::All)
+        );
+        assert_eq!(
+            parse_deallocate(b"  deallocate prepare all ;"),
+            Some(DeallocateTarget::All)
+        );
+        assert_eq!(parse_deallocate(b"DEALLOCATE stmt_1;"), name("stmt_1"));
+        assert_eq!(parse_deallocate(b"DEALLOCATE Stmt_1"), name("stmt_1"));
+        assert_eq!(
+            parse_deallocate(b"DEALLOCATE PREPARE\tlsx_2"),
+            name("lsx_2")
+        );
+        assert_eq!(
+            parse_deallocate(b"DEALLOCATE \"My\"\"Stmt\""),
+            name("My\"Stmt")
+        );
+        // A quoted ALL is a statement named "ALL", not every statement.
+        assert_eq!(parse_deallocate(b"DEALLOCATE \"ALL\""), name("ALL"));
+
+        assert_eq!(parse_deallocate(b"DEALLOCATE"), None);
+        // PREPARE is an unreserved keyword, so alone it is a name.
+        assert_eq!(parse_deallocate(b"DEALLOCATE PREPARE"), name("prepare"));
+        assert_eq!(parse_deallocate(b"DEALLOCATEALL"), None);
+        assert_eq!(parse_deallocate(b"DEALLOCATE a, b"), None);
+        assert_eq!(parse_deallocate(b"DEALLOCATE \"open"), None);
+        assert_eq!(parse_deallocate(b"DEALLOCATE \"\""), None);
+        assert_eq!(parse_deallocate(b"SELECT 'DEALLOCATE ALL'"), None);
+    }
+
+    #[test]
+    fn write_statements_are_detected_by_leading_keyword() {
+        assert!(is_write_statement(b"INSERT INTO t VALUES (1)"));
+        assert!(is_write_statement(b"  update t set a = 1"));
+        assert!(is_write_statement(b"-- note\n/* hint */ Delete from t"));
+        assert!(is_write_statement(b"truncate t;"));
+
+        assert!(!is_write_statement(b"select * from inserts"));
+        assert!(!is_write_statement(b"insertx"));
+        assert!(!is_write_statement(b"-- insert into t"));
+        assert!(!is_write_statement(b"/* unterminated insert"));
+        assert!(!is_write_statement(b""));
+    }
+
+    #[test]
+    fn copy_from_is_a_write() {
+        assert!(is_write_statement(b"COPY t FROM STDIN"));
+        assert!(is_write_statement(
+            b"copy t (a, b) from '/tmp/t.csv' with (format csv)"
+        ));
+        assert!(is_write_statement(
+            b"/* load */ COPY public.t FROM PROGRAM 'cat t'"
+        ));
+
+        assert!(!is_write_statement(b"COPY t TO STDOUT"));
+        assert!(!is_write_statement(b"COPY t (a, b) TO STDOUT"));
+        assert!(!is_write_statement(b"COPY (SELECT a FROM t) TO STDOUT"));
+        assert!(!is_write_statement(b"copyx t from stdin"));
+    }
+
+    #[test]
+    fn writable_cte_is_a_write() {
+        assert!(is_write_statement(
+            b"WITH moved AS (DELETE FROM a RETURNING *) INSERT INTO b SELECT * FROM moved"
+        ));
+        assert!(is_write_statement(
+            b"with x as (select 1) update t set a = 1"
+        ));
+        assert!(is_write_statement(
+            b"WITH ins AS (INSERT INTO t VALUES (1) RETURNING id) SELECT id FROM ins"
+        ));
+
+        assert!(!is_write_statement(b"WITH x AS (SELECT 1) SELECT * FROM x"));
+        assert!(!is_write_statement(
+            b"WITH x AS (SELECT * FROM t FOR UPDATE) SELECT * FROM x"
+        ));
+        assert!(!is_write_statement(
+            b"WITH x AS (SELECT * FROM t) SELECT * FROM x FOR NO KEY UPDATE"
+        ));
+        assert!(!is_write_statement(
+            b"WITH updates AS (SELECT 1) SELECT * FROM updates"
+        ));
+    }
+
+    #[test]
+    fn retryable_reads_are_single_read_statements() {
+        assert!(is_retryable_read(b"SELECT * FROM t WHERE id = 1"));
+        assert!(is_retryable_read(b"-- report\n select count(*) from t;  "));
+        assert!(is_retryable_read(b"values (1), (2)"));
+        assert!(is_retryable_read(b"TABLE t"));
+        assert!(is_retryable_read(b"show server_version"));
+
+        assert!(!is_retryable_read(b"select 1; select 2"));
+        assert!(!is_retryable_read(b"SELECT * INTO copy_t FROM t"));
+        assert!(!is_retryable_read(
+            b"with d as (delete from t returning *) select * from d"
+        ));
+        assert!(!is_retryable_read(b"update t set a = 1"));
+        assert!(!is_retryable_read(b"selectx"));
+        assert!(!is_retryable_read(b";"));
+        assert!(!is_retryable_read(b""));
+    }
+
+    #[test]
+    fn session_only_features_are_detected() {
+        assert_eq!(
+            session_only_feature(b"CREATE TEMP TABLE t (a int)"),
+            Some("temp_table")
+        );
+        assert_eq!(
+            session_only_feature(b"create global temporary table t (a int)"),
+            Some("temp_table")
+        );
+        assert_eq!(
+            session_only_feature(b"DECLARE c CURSOR WITH HOLD FOR SELECT 1"),
+            Some("with_hold_cursor")
+        );
+        assert_eq!(
+            session_only_feature(b"select pg_advisory_lock(42)"),
+            Some("advisory_lock")
+        );
+        assert_eq!(
+            session_only_feature(b"-- wait\nLISTEN jobs"),
+            Some("listen")
+        );
+
+        assert_eq!(
+            session_only_feature(b"create temp table t (a int) on commit drop"),
+            None
+        );
+        assert_eq!(
+            session_only_feature(b"DECLARE c CURSOR WITHOUT HOLD FOR SELECT 1"),
+            None
+        );
+        assert_eq!(
+            session_only_feature(b"select pg_advisory_xact_lock(42)"),
+            None
+        );
+        assert_eq!(session_only_feature(b"create table temp (a int)"), None);
+        assert_eq!(session_only_feature(b"select 1"), None);
+    }
+}
@@ -128,3 +128,18 @@ impl Address {
         self.pool_name.clone()
     }
 }
+
+/// One backend that gets a connection pool per user: the pool's own server,
+/// one `shards` entry or one `hosts` entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolBackend<'a> {
+    /// Key in the pool map; `pool/shard` for shards.
+    pub pool_key: String,
+    pub host: &'a str,
+    pub port: u16,
+    pub server_database: &'a str,
+    /// `Some` for a `shards` entry.
+    pub shard: Option<&'a str>,
+    /// `true` for a `hosts` entry.
+    pub cluster_host: bool,
+}
@@ -0,0 +1,34 @@
+//! Additional admin console logins.
+
+use serde_derive::{Deserialize, Serialize};
+
+/// Additional admin console login.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AdminUser {
+    pub username: String,
+    /// Plain password (MD5) or a `SCRAM-SHA-256$...` secret.
+    pub password: String,
+    #[serde(default)]
+    pub permission: AdminPermission,
+}
+
+/// What an admin login may do.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminPermission {
+    /// `SHOW` and `TEST HBA` only.
+    #[default]
+    Readonly,
+    /// Every admin command.
+    Full,
+}
+
+impl std::fmt::Display for AdminPermission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            AdminPermission::Readonly => "readonly",
+            AdminPermission::Full => "full",
+        };
+        write!(f, "{s}")
+    }
+}
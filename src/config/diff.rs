@@ -0,0 +1,286 @@
+//! What a config reload changed, for the log and the `RELOAD` reply.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use serde_json::{Map, Value};
+
+use super::{Config, Pool};
+
+/// Longest rendering of a changed value; longer ones are cut with `...`.
+const MAX_VALUE_LEN: usize = 64;
+
+/// One difference between the running config and the reloaded one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigChange {
+    PoolAdded(String),
+    PoolRemoved(String),
+    UserAdded {
+        pool: String,
+        user: String,
+    },
+    UserRemoved {
+        pool: String,
+        user: String,
+    },
+    /// A setting at a dotted path such as `pools.app.pool_size`; unset
+    /// values render as `unset`.
+    Setting {
+        path: String,
+        old: String,
+        new: String,
+    },
+    /// A password or key changed; the values are not shown.
+    SecretChanged {
+        path: String,
+    },
+}
+
+impl fmt::Display for ConfigChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigChange::PoolAdded(pool) => write!(f, "pool \"{pool}\" added"),
+            ConfigChange::PoolRemoved(pool) => write!(f, "pool \"{pool}\" removed"),
+            ConfigChange::UserAdded { pool, user } => {
+                write!(f, "user \"{user}\" added to pool \"{pool}\"")
+            }
+            ConfigChange::UserRemoved { pool, user } => {
+                write!(f, "user \"{user}\" removed from pool \"{pool}\"")
+            }
+            ConfigChange::Setting { path, old, new } => write!(f, "{path}: {old} -> {new}"),
+            ConfigChange::SecretChanged { path } => write!(f, "{path} changed"),
+        }
+    }
+}
+
+/// Changes between two configs, in a stable order: top-level sections,
+/// then pools by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigDiff {
+    pub changes: Vec<ConfigChange>,
+}
+
+impl ConfigDiff {
+    pub fn compute(old: &Config, new: &Config) -> Self {
+        let mut changes = Vec::new();
+
+        diff_values(
+            "general",
+            &to_value(&old.general),
+            &to_value(&new.general),
+            &mut changes,
+        );
+        // `pg_hba` is not serialized, so it is compared on its own.
+        if old.general.pg_hba != new.general.pg_hba {
+            changes.push(ConfigChange::Setting {
+                path: "general.pg_hba".to_string(),
+                old: "previous rules".to_string(),
+                new: "new rules".to_string(),
+            });
+        }
+        diff_values(
+            "web",
+            &to_value(&old.web),
+            &to_value(&new.web),
+            &mut changes,
+        );
+        diff_values(
+            "talos",
+            &to_value(&old.talos),
+            &to_value(&new.talos),
+            &mut changes,
+        );
+        diff_values(
+            "include",
+            &to_value(&old.include),
+            &to_value(&new.include),
+            &mut changes,
+        );
+
+        let names: BTreeSet<&String> = old.pools.keys().chain(new.pools.keys()).collect();
+        for name in names {
+            match (old.pools.get(name), new.pools.get(name)) {
+                (Some(_), None) => changes.push(ConfigChange::PoolRemoved(name.clone())),
+                (None, Some(_)) => changes.push(ConfigChange::PoolAdded(name.clone())),
+                (Some(old_pool), Some(new_pool)) => {
+                    diff_pool(name, old_pool, new_pool, &mut changes)
+                }
+                (None, None) => {}
+            }
+        }
+
+        ConfigDiff { changes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+fn diff_pool(name: &str, old: &Pool, new: &Pool, changes: &mut Vec<ConfigChange>) {
+    let prefix = format!("pools.{name}");
+    let mut old_value = to_value(old);
+    let mut new_value = to_value(new);
+    // Users are matched by name below rather than by list position.
+    for value in [&mut old_value, &mut new_value] {
+        if let Value::Object(map) = value {
+            map.remove("users");
+        }
+    }
+    diff_values(&prefix, &old_value, &new_value, changes);
+
+    for user in &old.users {
+        match new.users.iter().find(|u| u.username == user.username) {
+            None => changes.push(ConfigChange::UserRemoved {
+                pool: name.to_string(),
+                user: user.username.clone(),
+            }),
+            Some(new_user) => diff_values(
+                &format!("{prefix}.users.{}", user.username),
+                &to_value(user),
+                &to_value(new_user),
+                changes,
+            ),
+        }
+    }
+    for user in &new.users {
+        if !old.users.iter().any(|u| u.username == user.username) {
+            changes.push(ConfigChange::UserAdded {
+                pool: name.to_string(),
+                user: user.username.clone(),
+            });
+        }
+    }
+}
+
+fn to_value<T: serde::Serialize>(section: &T) -> Value {
+    serde_json::to_value(section).unwrap_or(Value::Null)
+}
+
+/// Walk nested tables down to the settings that differ. A missing key and
+/// `null` both mean the setting is unset.
+fn diff_values(path: &str, old: &Value, new: &Value, changes: &mut Vec<ConfigChange>) {
+    if let (Value::Object(old_map), Value::Object(new_map)) = (old, new) {
+        let keys: BTreeSet<&String> = old_map.keys().chain(new_map.keys()).collect();
+        for key in keys {
+            diff_values(
+                &format!("{path}.{key}"),
+                field(old_map, key),
+                field(new_map, key),
+                changes,
+            );
+        }
+        return;
+    }
+    if old == new {
+        return;
+    }
+    if is_secret(path) {
+        changes.push(ConfigChange::SecretChanged {
+            path: path.to_string(),
+        });
+    } else {
+        changes.push(ConfigChange::Setting {
+            path: path.to_string(),
+            old: render(old),
+            new: render(new),
+        });
+    }
+}
+
+fn field<'a>(map: &'a Map<String, Value>, key: &str) -> &'a Value {
+    map.get(key).unwrap_or(&Value::Null)
+}
+
+fn is_secret(path: &str) -> bool {
+    let name = path.rsplit('.').next().unwrap_or(path);
+    name.contains("password") || name.contains("secret")
+}
+
+fn render(value: &Value) -> String {
+    let text = match value {
+        Value::Null => return "unset".to_string(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    if text.chars().count() > MAX_VALUE_LEN {
+        let cut: String = text.chars().take(MAX_VALUE_LEN).collect();
+        format!("{cut}...")
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{PoolMode, User};
+
+    fn user(name: &str) -> User {
+        User {
+            username: name.to_string(),
+            ..User::default()
+        }
+    }
+
+    fn config_with_pool(pool: Pool) -> Config {
+        let mut config = Config::default();
+        config.pools.insert("app".to_string(), pool);
+        config
+    }
+
+    #[test]
+    fn identical_configs_have_no_changes() {
+        let config = config_with_pool(Pool::default());
+        assert!(ConfigDiff::compute(&config, &config.clone()).is_empty());
+    }
+
+    #[test]
+    fn reports_pools_users_and_settings() {
+        let mut old_pool = Pool::default();
+        old_pool.users = vec![user("alice"), user("bob")];
+        let old = config_with_pool(old_pool.clone());
+
+        let mut new_pool = old_pool;
+        new_pool.pool_mode = PoolMode::Session;
+        new_pool.users[0].pool_size = 80;
+        new_pool.users[0].password = "md5changed".to_string();
+        new_pool.users.remove(1);
+        new_pool.users.push(user("carol"));
+        let mut new = config_with_pool(new_pool);
+        new.pools.insert("reports".to_string(), Pool::default());
+        new.general.max_connections += 1;
+
+        let changes: Vec<String> = ConfigDiff::compute(&old, &new)
+            .changes
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                format!(
+                    "general.max_connections: {} -> {}",
+                    old.general.max_connections, new.general.max_connections
+                ),
+                "pools.app.pool_mode: Transaction -> Session".to_string(),
+                "pools.app.users.alice.password changed".to_string(),
+                "pools.app.users.alice.pool_size: 40 -> 80".to_string(),
+                "user \"bob\" removed from pool \"app\"".to_string(),
+                "user \"carol\" added to pool \"app\"".to_string(),
+                "pool \"reports\" added".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn unset_values_and_long_values_render_readably() {
+        assert_eq!(render(&Value::Null), "unset");
+        assert_eq!(render(&Value::from(5)), "5");
+        let long = "x".repeat(MAX_VALUE_LEN + 10);
+        assert_eq!(
+            render(&Value::from(long)),
+            format!("{}...", "x".repeat(MAX_VALUE_LEN))
+        );
+    }
+}
@@ -4,9 +4,22 @@ use ipnet::IpNet;
 use serde_derive::{Deserialize, Serialize};
 
 use super::tls;
-use super::{ByteSize, Duration, Include};
+use super::{AdminPermission, AdminUser, ByteSize, Duration, Include, Listener};
 use crate::auth::hba::PgHba;
 
+/// Keepalive and `TCP_USER_TIMEOUT` values applied to a TCP socket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TcpKeepaliveSettings {
+    /// Seconds of idle before the first probe.
+    pub idle: u64,
+    /// Seconds between probes.
+    pub interval: u64,
+    /// Unanswered probes before the connection is dropped.
+    pub count: u32,
+    /// `TCP_USER_TIMEOUT` in seconds, 0 = OS default. Linux only.
+    pub user_timeout: u64,
+}
+
 /// General configuration.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct General {
@@ -16,6 +29,19 @@ pub struct General {
     #[serde(default = "General::default_port")]
     pub port: u16,
 
+    /// Extra TCP listeners, each with an optional default database.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub listeners: Vec<Listener>,
+
+    /// Bind address of the admin listener; `host` when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_listen_host: Option<String>,
+
+    /// Port of a TCP listener that serves only the admin console. When set,
+    /// the other TCP listeners refuse the admin database.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_listen_port: Option<u16>,
+
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub tokio_global_queue_interval: Option<u32>,
 
@@ -25,6 +51,19 @@ pub struct General {
     #[serde(default = "General::default_connect_timeout")]
     pub connect_timeout: Duration,
 
+    /// First pause after a failed server connect; doubles per consecutive failure.
+    #[serde(default = "General::default_server_connect_backoff_base")]
+    pub server_connect_backoff_base: Duration,
+
+    /// Upper bound of the pause after failed server connects.
+    #[serde(default = "General::default_server_connect_backoff_max")]
+    pub server_connect_backoff_max: Duration,
+
+    /// How long a checkout keeps retrying a refused or unavailable backend
+    /// before the error reaches the client (0 = no retry).
+    #[serde(default = "General::default_server_connect_hold")]
+    pub server_connect_hold: Duration,
+
     #[serde(default = "General::default_query_wait_timeout")]
     pub query_wait_timeout: Duration,
 
@@ -82,6 +121,17 @@ pub struct General {
     #[serde(default)] // True
     pub log_client_disconnections: bool,
 
+    /// Label `pg_doorman_auth_failures_total` with the client's /24 (IPv4)
+    /// or /64 (IPv6) network.
+    #[serde(default)] // False
+    pub auth_failures_by_subnet: bool,
+
+    /// Application names that get their own row in `SHOW STATS_BY_APP`
+    /// and their own `application_name` label; others count as `other`.
+    /// Empty turns the per-application breakdown off.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stats_application_names: Vec<String>,
+
     #[serde(default = "General::default_shutdown_timeout")] // 10_000
     pub shutdown_timeout: Duration,
 
@@ -91,14 +141,61 @@ pub struct General {
     #[serde(default = "General::default_max_memory_usage")] // 256m
     pub max_memory_usage: ByteSize,
 
+    /// Capacity of a freshly allocated client buffer.
+    #[serde(default = "General::default_buffer_pool_initial_capacity")] // 8k
+    pub buffer_pool_initial_capacity: ByteSize,
+
+    /// Free client buffers kept for reuse per worker thread.
+    #[serde(default = "General::default_buffer_pool_max_retained")]
+    pub buffer_pool_max_retained: usize,
+
+    /// Buffers that grew past this size are dropped instead of reused.
+    #[serde(default = "General::default_buffer_pool_max_buffer_size")] // 32k
+    pub buffer_pool_max_buffer_size: ByteSize,
+
     #[serde(default = "General::default_max_connections")]
     pub max_connections: u64,
 
+    /// Maximum number of clients sharing one `pg_doorman.tag` startup
+    /// parameter value. 0 means unlimited.
+    #[serde(default)]
+    pub max_connections_per_tag: u64,
+
+    /// What a client gets once `max_connections` is reached: an immediate
+    /// 53300 (`reject`), or a bounded wait for a free slot (`queue`).
+    #[serde(default)]
+    pub max_connections_overflow_behavior: MaxConnectionsOverflow,
+
+    /// How long a queued client waits for a free slot before it is rejected.
+    #[serde(default = "General::default_max_connections_queue_timeout")]
+    pub max_connections_queue_timeout: Duration,
+
+    /// Failed logins from one IP within `auth_fail_ban_window` that ban it
+    /// for `auth_fail_ban_duration`. 0 disables bans.
+    #[serde(default)]
+    pub auth_fail_ban_threshold: u32,
+
+    #[serde(default = "General::default_auth_fail_ban_window")]
+    pub auth_fail_ban_window: Duration,
+
+    #[serde(default = "General::default_auth_fail_ban_duration")]
+    pub auth_fail_ban_duration: Duration,
+
+    /// How long a new client may take to send its startup packet, finish
+    /// TLS and authenticate before it is disconnected. 0 disables the limit.
+    #[serde(default = "General::default_startup_timeout")]
+    pub startup_timeout: Duration,
+
     /// Maximum number of server connections that can be created concurrently.
     /// Uses a semaphore to limit parallel connection creation instead of serializing with mutex.
     #[serde(default = "General::default_max_concurrent_creates")]
     pub max_concurrent_creates: usize,
 
+    /// Maximum number of transactions running on the backends of all pools
+    /// at once; 0 means unlimited.
+    #[serde(default)]
+    pub max_concurrent_queries: usize,
+
     /// Warm pool ratio for connection scaling (0-100, percentage).
     /// Connections below this threshold of max_size are created immediately.
     #[serde(default = "General::default_scaling_warm_pool_ratio")]
@@ -115,6 +212,11 @@ pub struct General {
     #[serde(default = "General::default_scaling_max_parallel_creates")]
     pub scaling_max_parallel_creates: u32,
 
+    /// Maximum idle-expired server connections closed per pool per retain cycle,
+    /// so a pool shrinks gradually after a burst. 0 = unlimited.
+    #[serde(default = "General::default_scaling_shrink_step")]
+    pub scaling_shrink_step: u32,
+
     #[serde(default = "General::default_server_lifetime")]
     pub server_lifetime: Duration,
 
@@ -141,12 +243,24 @@ pub struct General {
     #[serde(default = "General::default_sync_server_parameters")] // False
     pub sync_server_parameters: bool,
 
+    /// Follow `SET application_name` in the client label shown by
+    /// SHOW CLIENTS and the web UI.
+    #[serde(default = "General::default_track_application_name")] // True
+    pub track_application_name: bool,
+
     #[serde(default = "General::default_worker_threads")]
     pub worker_threads: usize,
 
     #[serde(default = "General::default_proxy_copy_data_timeout")] // 15_000
     pub proxy_copy_data_timeout: Duration,
 
+    /// What happens to a frontend message type pg_doorman does not know:
+    /// it is answered with a protocol error (`error`), logged and dropped
+    /// (`ignore`), or, in session mode, forwarded to the backend as is
+    /// (`passthrough`).
+    #[serde(default)]
+    pub unknown_message_behavior: UnknownMessageBehavior,
+
     // worker_cpu_affinity_pinning: пытаемся пинить каждый worker на CPU, начиная со второго CPU.
     #[serde(default = "General::default_worker_cpu_affinity_pinning")]
     pub worker_cpu_affinity_pinning: bool,
@@ -159,6 +273,10 @@ pub struct General {
     // tcp backlog.
     #[serde(default = "General::default_backlog")]
     pub backlog: u32,
+    /// Expect a PROXY protocol (v1 or v2) header on every TCP connection and
+    /// use the client address it carries for HBA and logging.
+    #[serde(default)]
+    pub accept_proxy_protocol: bool,
 
     // pooler_check_query: ping pooler with simple query like '/* ping pooler */;'.
     #[serde(default = "General::default_pooler_check_query")]
@@ -174,6 +292,12 @@ pub struct General {
     pub tls_mode: Option<String>,
     #[serde(default = "General::default_tls_rate_limit_per_second")]
     pub tls_rate_limit_per_second: usize,
+    /// Lowest TLS version accepted from clients: "1.2" (default) or "1.3".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls_min_protocol_version: Option<String>,
+    /// OpenSSL cipher list for client TLS 1.2 connections.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls_ciphers: Option<String>,
 
     #[serde(default = "General::default_server_tls_mode")]
     pub server_tls_mode: String,
@@ -211,6 +335,15 @@ pub struct General {
     pub admin_username: String,
     pub admin_password: String,
 
+    /// Extra admin console logins, each `readonly` or `full`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub admin_users: Vec<AdminUser>,
+
+    /// Virtual database name that routes a connection to the admin console.
+    /// `pgbouncer` is always accepted as an alias.
+    #[serde(default = "General::default_admin_database_name")]
+    pub admin_database_name: String,
+
     #[serde(default = "General::default_prepared_statements")]
     pub prepared_statements: bool,
 
@@ -244,6 +377,11 @@ pub struct General {
     #[serde(default, alias = "client_prepared_statements_cache_size")]
     pub client_anonymous_prepared_cache_size: Option<usize>,
 
+    /// Add the evicted statement's hit and miss counts to the log line of
+    /// every pool-level prepared cache eviction.
+    #[serde(default)]
+    pub log_prepared_cache_evictions: bool,
+
     /// How often (seconds) the query interner runs its mark-and-sweep GC.
     /// The actual sweep ticks at `gc_interval / 4` so an entry marked on
     /// one cycle has a quarter-interval to be touched (and unmarked)
@@ -289,9 +427,33 @@ pub struct General {
     /// retry, fallback, or per-key quarantine for the backend's verdict.
     #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
     pub startup_parameters: std::collections::BTreeMap<String, String>,
+
+    /// Client StartupMessage parameters pg_doorman drops instead of
+    /// carrying them into the client's session (e.g. `extra_float_digits`).
+    /// The client then runs with the pool's value — the backend default or
+    /// `startup_parameters` — so drivers with different defaults no longer
+    /// trigger SET churn on checkout. A pool-level list replaces this one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore_startup_parameters: Vec<String>,
+
+    /// `ParameterStatus` values reported to clients at login in place of
+    /// the backend's (e.g. a masked `server_version`). Names the backend
+    /// does not report are added. The backend session is not changed.
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub parameter_status_overrides: std::collections::BTreeMap<String, String>,
 }
 
 impl General {
+    /// Keepalive settings for client sockets and pools without overrides.
+    pub fn tcp_keepalive_settings(&self) -> TcpKeepaliveSettings {
+        TcpKeepaliveSettings {
+            idle: self.tcp_keepalives_idle,
+            interval: self.tcp_keepalives_interval,
+            count: self.tcp_keepalives_count,
+            user_timeout: self.tcp_user_timeout,
+        }
+    }
+
     pub fn default_host() -> String {
         "0.0.0.0".into()
     }
@@ -327,6 +489,19 @@ impl General {
         Duration::from_millis(3_000)
     }
 
+    pub fn default_server_connect_backoff_base() -> Duration {
+        Duration::from_millis(10)
+    }
+
+    pub fn default_server_connect_backoff_max() -> Duration {
+        Duration::from_millis(1_000)
+    }
+
+    /// Same as the `connect_timeout` default.
+    pub fn default_server_connect_hold() -> Duration {
+        Self::default_connect_timeout()
+    }
+
     pub fn default_query_wait_timeout() -> Duration {
         Duration::from_millis(5000)
     }
@@ -383,10 +558,47 @@ impl General {
         ByteSize::from_mb(256) // 256mb
     }
 
+    pub fn default_buffer_pool_initial_capacity() -> ByteSize {
+        ByteSize::from_kb(8)
+    }
+
+    pub fn default_buffer_pool_max_retained() -> usize {
+        512
+    }
+
+    pub fn default_buffer_pool_max_buffer_size() -> ByteSize {
+        ByteSize::from_kb(32)
+    }
+
     pub fn default_max_connections() -> u64 {
         8 * 1024
     }
 
+    pub fn default_max_connections_queue_timeout() -> Duration {
+        Duration::from_millis(1000)
+    }
+
+    pub fn default_auth_fail_ban_window() -> Duration {
+        Duration::from_millis(60_000)
+    }
+
+    pub fn default_auth_fail_ban_duration() -> Duration {
+        Duration::from_millis(300_000)
+    }
+
+    pub fn default_startup_timeout() -> Duration {
+        Duration::from_millis(60_000)
+    }
+
+    /// Queue timeout for clients over `max_connections`; `None` rejects them
+    /// straight away.
+    pub fn max_connections_queue(&self) -> Option<std::time::Duration> {
+        match self.max_connections_overflow_behavior {
+            MaxConnectionsOverflow::Reject => None,
+            MaxConnectionsOverflow::Queue => Some(self.max_connections_queue_timeout.into()),
+        }
+    }
+
     /// Default maximum number of concurrent server connection creates.
     /// Allows up to 4 connections to be created in parallel per pool.
     pub fn default_max_concurrent_creates() -> usize {
@@ -408,6 +620,11 @@ impl General {
         2
     }
 
+    /// Default shrink step: 0, unlimited (matches ScalingConfig::DEFAULT_SHRINK_STEP).
+    pub fn default_scaling_shrink_step() -> u32 {
+        0
+    }
+
     pub fn default_backlog() -> u32 {
         0
     }
@@ -420,6 +637,10 @@ impl General {
         false
     }
 
+    pub fn default_track_application_name() -> bool {
+        true
+    }
+
     // These keepalive defaults should detect a dead connection within 30 seconds.
     // Tokio defaults to disabling keepalives which keeps dead connections around indefinitely.
     // This can lead to permanent server pool exhaustion
@@ -467,6 +688,27 @@ impl General {
     pub fn default_prepared_statements_cache_size() -> usize {
         8 * 1024
     }
+    pub fn default_admin_database_name() -> String {
+        String::from("pgdoorman")
+    }
+
+    /// Whether a startup `database` targets the admin console.
+    pub fn is_admin_database(&self, database: &str) -> bool {
+        database == self.admin_database_name || database == "pgbouncer"
+    }
+
+    /// Password and permission of the admin login `username`: the main
+    /// `admin_username` has full access, `admin_users` entries their own.
+    pub fn admin_login(&self, username: &str) -> Option<(&str, AdminPermission)> {
+        if username == self.admin_username {
+            return Some((self.admin_password.as_str(), AdminPermission::Full));
+        }
+        self.admin_users
+            .iter()
+            .find(|user| user.username == username)
+            .map(|user| (user.password.as_str(), user.permission))
+    }
+
     pub fn default_prepared_statements() -> bool {
         true
     }
@@ -515,6 +757,7 @@ impl General {
     pub fn default_include() -> Include {
         Include {
             files: Self::default_include_files(),
+            dirs: Vec::new(),
         }
     }
 
@@ -525,13 +768,61 @@ impl General {
             .is_some_and(|result| match result {
                 Ok(tls_mode) => {
                     match tls_mode {
-                        tls::TLSMode::VerifyFull | tls::TLSMode::Require => true,
+                        tls::TLSMode::VerifyFull
+                        | tls::TLSMode::VerifyCa
+                        | tls::TLSMode::Require => true,
                         _ => false, // allow non-ssl connections
                     }
                 }
                 Err(_) => false,
             })
     }
+
+    /// Names of settings that differ between `self` and `new` but are only
+    /// read at process start (listeners, runtime, client TLS). RELOAD
+    /// refuses a config with any of these changed instead of silently
+    /// ignoring them.
+    pub fn restart_required_changes(&self, new: &General) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        macro_rules! check {
+            ($($field:ident),* $(,)?) => {
+                $(
+                    if self.$field != new.$field {
+                        changed.push(stringify!($field));
+                    }
+                )*
+            };
+        }
+        check!(
+            host,
+            port,
+            listeners,
+            admin_listen_host,
+            admin_listen_port,
+            backlog,
+            unix_socket_dir,
+            unix_socket_mode,
+            worker_threads,
+            worker_cpu_affinity_pinning,
+            tokio_global_queue_interval,
+            tokio_event_interval,
+            worker_stack_size,
+            max_blocking_threads,
+            daemon_pid_file,
+            syslog_prog_name,
+            shutdown_timeout,
+            retain_connections_time,
+            query_interner_gc_interval_seconds,
+            tls_certificate,
+            tls_private_key,
+            tls_ca_cert,
+            tls_mode,
+            tls_min_protocol_version,
+            tls_ciphers,
+            tls_rate_limit_per_second,
+        );
+        changed
+    }
 }
 
 impl Default for General {
@@ -539,20 +830,39 @@ impl Default for General {
         General {
             host: Self::default_host(),
             port: Self::default_port(),
+            listeners: Vec::new(),
+            admin_listen_host: None,
+            admin_listen_port: None,
             tokio_global_queue_interval: None,
             tokio_event_interval: None,
             connect_timeout: General::default_connect_timeout(),
+            server_connect_backoff_base: General::default_server_connect_backoff_base(),
+            server_connect_backoff_max: General::default_server_connect_backoff_max(),
+            server_connect_hold: General::default_server_connect_hold(),
             query_wait_timeout: General::default_query_wait_timeout(),
             idle_timeout: General::default_idle_timeout(),
             shutdown_timeout: Self::default_shutdown_timeout(),
             proxy_copy_data_timeout: Self::default_proxy_copy_data_timeout(),
+            unknown_message_behavior: UnknownMessageBehavior::default(),
             message_size_to_be_stream: Self::default_message_size_to_be_stream(),
             max_memory_usage: Self::default_max_memory_usage(),
+            buffer_pool_initial_capacity: Self::default_buffer_pool_initial_capacity(),
+            buffer_pool_max_retained: Self::default_buffer_pool_max_retained(),
+            buffer_pool_max_buffer_size: Self::default_buffer_pool_max_buffer_size(),
             max_connections: Self::default_max_connections(),
+            max_connections_per_tag: 0,
+            max_connections_overflow_behavior: MaxConnectionsOverflow::default(),
+            max_connections_queue_timeout: Self::default_max_connections_queue_timeout(),
+            auth_fail_ban_threshold: 0,
+            auth_fail_ban_window: Self::default_auth_fail_ban_window(),
+            auth_fail_ban_duration: Self::default_auth_fail_ban_duration(),
+            startup_timeout: Self::default_startup_timeout(),
             max_concurrent_creates: Self::default_max_concurrent_creates(),
+            max_concurrent_queries: 0,
             scaling_warm_pool_ratio: Self::default_scaling_warm_pool_ratio(),
             scaling_fast_retries: Self::default_scaling_fast_retries(),
             scaling_max_parallel_creates: Self::default_scaling_max_parallel_creates(),
+            scaling_shrink_step: Self::default_scaling_shrink_step(),
             worker_threads: Self::default_worker_threads(),
             worker_cpu_affinity_pinning: Self::default_worker_cpu_affinity_pinning(),
             worker_stack_size: None,
@@ -569,12 +879,17 @@ impl Default for General {
             unix_socket_mode: Self::default_unix_socket_mode(),
             log_client_connections: true,
             log_client_disconnections: true,
+            auth_failures_by_subnet: false,
+            stats_application_names: Vec::new(),
             sync_server_parameters: Self::default_sync_server_parameters(),
+            track_application_name: Self::default_track_application_name(),
             tls_certificate: None,
             tls_private_key: None,
             tls_ca_cert: None,
             tls_mode: None,
             tls_rate_limit_per_second: Self::default_tls_rate_limit_per_second(),
+            tls_min_protocol_version: None,
+            tls_ciphers: None,
             server_tls_mode: Self::default_server_tls_mode(),
             server_tls_ca_cert: None,
             server_tls_certificate: None,
@@ -586,6 +901,8 @@ impl Default for General {
             fallback_lifetime: None,
             admin_username: String::from("admin"),
             admin_password: String::from("admin"),
+            admin_users: Vec::new(),
+            admin_database_name: Self::default_admin_database_name(),
             server_lifetime: Self::default_server_lifetime(),
             retain_connections_time: Self::default_retain_connections_time(),
             retain_connections_max: Self::default_retain_connections_max(),
@@ -595,6 +912,7 @@ impl Default for General {
             prepared_statements_cache_size: Self::default_prepared_statements_cache_size(),
             server_prepared_statements_cache_size: None,
             client_anonymous_prepared_cache_size: None,
+            log_prepared_cache_evictions: false,
             query_interner_gc_interval_seconds: Self::default_query_interner_gc_interval_seconds(),
             query_interner_anon_idle_ttl_seconds:
                 Self::default_query_interner_anon_idle_ttl_seconds(),
@@ -605,10 +923,58 @@ impl Default for General {
             syslog_prog_name: None,
             pooler_check_query: Self::default_pooler_check_query(),
             backlog: Self::default_backlog(),
+            accept_proxy_protocol: false,
+            ignore_startup_parameters: Vec::new(),
+            parameter_status_overrides: std::collections::BTreeMap::new(),
         }
     }
 }
 
+/// Behaviour once `max_connections` clients are connected.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MaxConnectionsOverflow {
+    /// Reject with 53300 `sorry, too many clients already`.
+    #[default]
+    Reject,
+    /// Wait up to `max_connections_queue_timeout`, then reject.
+    Queue,
+}
+
+impl std::fmt::Display for MaxConnectionsOverflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            MaxConnectionsOverflow::Reject => "reject",
+            MaxConnectionsOverflow::Queue => "queue",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Handling of frontend message types pg_doorman does not recognize.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum UnknownMessageBehavior {
+    /// Answer with 08P01 and close the client, as PostgreSQL does.
+    #[default]
+    Error,
+    /// Log the message and drop it; the client stays connected.
+    Ignore,
+    /// Forward to the backend in session mode; transaction mode errors.
+    Passthrough,
+}
+
+impl std::fmt::Display for UnknownMessageBehavior {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            UnknownMessageBehavior::Error => "error",
+            UnknownMessageBehavior::Ignore => "ignore",
+            UnknownMessageBehavior::Passthrough => "passthrough",
+        };
+        write!(f, "{s}")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -14,11 +14,16 @@ pub struct ServerConfig {
 pub struct Include {
     #[serde(default = "General::default_include_files")]
     pub files: Vec<String>,
+
+    /// Directories whose `.toml`, `.yaml` and `.yml` files are merged after
+    /// `files`, in lexicographic order of file name.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dirs: Vec<String>,
 }
 
 impl Include {
     pub fn is_empty(&self) -> bool {
-        self.files.is_empty()
+        self.files.is_empty() && self.dirs.is_empty()
     }
 }
 
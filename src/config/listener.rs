@@ -0,0 +1,79 @@
+//! Extra client listeners.
+
+use serde_derive::{Deserialize, Serialize};
+
+/// Database name a client of a listener with `default_database` may send
+/// to ask for that default explicitly.
+pub const LISTENER_DEFAULT_DATABASE: &str = "default";
+
+/// Additional TCP listener, bound next to `general.host`:`general.port`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Listener {
+    /// Bind address; `general.host` when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    pub port: u16,
+    /// Pool a client of this listener lands in when it names no database.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_database: Option<String>,
+}
+
+/// Database a client connects to. The requested name wins whenever it is
+/// a configured pool. Otherwise the listener's `default_database` replaces
+/// a missing name, the user name libpq sends in its place, or
+/// [`LISTENER_DEFAULT_DATABASE`].
+pub fn listener_database(
+    requested: Option<&str>,
+    username: &str,
+    default_database: Option<&str>,
+    is_pool: impl Fn(&str) -> bool,
+) -> String {
+    let name = requested
+        .filter(|name| !name.is_empty())
+        .unwrap_or(username);
+    match default_database {
+        Some(default)
+            if (name == username || name == LISTENER_DEFAULT_DATABASE) && !is_pool(name) =>
+        {
+            default.to_string()
+        }
+        _ => name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(requested: Option<&str>, default_database: Option<&str>) -> String {
+        listener_database(requested, "app", default_database, |name| {
+            matches!(name, "prod" | "staging")
+        })
+    }
+
+    #[test]
+    fn requested_pool_takes_precedence() {
+        assert_eq!(resolve(Some("prod"), Some("staging")), "prod");
+        assert_eq!(resolve(Some("other"), Some("staging")), "other");
+    }
+
+    #[test]
+    fn default_replaces_missing_user_and_sentinel_names() {
+        assert_eq!(resolve(None, Some("staging")), "staging");
+        assert_eq!(resolve(Some(""), Some("staging")), "staging");
+        assert_eq!(resolve(Some("app"), Some("staging")), "staging");
+        assert_eq!(resolve(Some("default"), Some("staging")), "staging");
+    }
+
+    #[test]
+    fn without_default_the_name_is_kept() {
+        assert_eq!(resolve(None, None), "app");
+        assert_eq!(resolve(Some("default"), None), "default");
+    }
+
+    #[test]
+    fn user_named_pool_is_not_replaced() {
+        let database = listener_database(None, "prod", Some("staging"), |name| name == "prod");
+        assert_eq!(database, "prod");
+    }
+}
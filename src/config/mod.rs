@@ -14,7 +14,7 @@ use tokio::fs::File;
 use tokio::io::AsyncReadExt;
 
 use self::tls::{load_identity, TLSMode};
-use crate::auth::hba::CheckResult;
+use crate::auth::hba::{CheckResult, HostType};
 use crate::errors::Error;
 use crate::pool::{ClientServerMap, ConnectionPool};
 use crate::transport::ClientTransport;
@@ -22,10 +22,13 @@ use crate::utils::format_duration_ms;
 
 // Sub-modules
 mod address;
+mod admin;
 mod byte_size;
+mod diff;
 mod duration;
 mod general;
 mod include;
+mod listener;
 mod pool;
 mod pooler_check_query;
 pub mod startup_parameters;
@@ -38,12 +41,15 @@ pub mod web;
 mod tests;
 
 // Re-exports
-pub use address::{Address, BackendAuthMethod, PoolMode};
+pub use address::{Address, BackendAuthMethod, PoolBackend, PoolMode};
+pub use admin::{AdminPermission, AdminUser};
 pub use byte_size::ByteSize;
+pub use diff::{ConfigChange, ConfigDiff};
 pub use duration::Duration;
-pub use general::General;
+pub use general::{General, MaxConnectionsOverflow, TcpKeepaliveSettings, UnknownMessageBehavior};
 pub use include::{GeneralWithInclude, Include, ServerConfig};
-pub use pool::{AuthQueryConfig, Pool};
+pub use listener::{listener_database, Listener, LISTENER_DEFAULT_DATABASE};
+pub use pool::{AuthQueryConfig, HostTarget, Pool, ServerCleanupMode, ShardTarget};
 pub use pooler_check_query::{
     update_pooler_check_query_snapshot, PoolerCheckQuerySnapshot, POOLER_CHECK_QUERY_SNAPSHOT,
 };
@@ -54,6 +60,13 @@ pub use web::Web;
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Build information stamped by `build.rs`.
+pub const GIT_COMMIT: &str = env!("PG_DOORMAN_GIT_COMMIT");
+pub const BUILD_DATE: &str = env!("PG_DOORMAN_BUILD_DATE");
+pub const RUSTC_VERSION: &str = env!("PG_DOORMAN_RUSTC_VERSION");
+/// Enabled cargo features, comma-separated; empty for a default build.
+pub const FEATURES: &str = env!("PG_DOORMAN_FEATURES");
+
 /// Configuration file format.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ConfigFormat {
@@ -79,7 +92,7 @@ fn parse_config_content<T: serde::de::DeserializeOwned>(
     contents: &str,
     format: ConfigFormat,
 ) -> Result<T, Error> {
-    warn_on_deprecated_general_keys(contents, format);
+    warn_on_deprecated_keys(contents, format);
     match format {
         ConfigFormat::Toml => toml::from_str(contents)
             .map_err(|err| Error::BadConfig(format!("TOML parse error: {err}"))),
@@ -125,17 +138,66 @@ fn find_deprecated_general_keys_toml(value: &toml::Value) -> Vec<&'static str> {
 /// through; this list only exists to drive the parser-level warning.
 const DEPRECATED_GENERAL_KEYS: &[&str] = &["client_prepared_statements_cache_size"];
 
+/// Pure helper: returns the deprecated keys present in any pool of the
+/// parsed YAML value, each once.
+fn find_deprecated_pool_keys_yaml(value: &serde_yaml::Value) -> Vec<&'static str> {
+    let Some(pools) = value.get("pools").and_then(|pools| pools.as_mapping()) else {
+        return Vec::new();
+    };
+    DEPRECATED_POOL_KEYS
+        .iter()
+        .copied()
+        .filter(|key| {
+            pools.values().any(|pool| {
+                pool.as_mapping().is_some_and(|map| {
+                    map.contains_key(serde_yaml::Value::String((*key).to_string()))
+                })
+            })
+        })
+        .collect()
+}
+
+/// Pure helper: returns the deprecated keys present in any pool of the
+/// parsed TOML value, each once.
+fn find_deprecated_pool_keys_toml(value: &toml::Value) -> Vec<&'static str> {
+    let Some(pools) = value.get("pools").and_then(|pools| pools.as_table()) else {
+        return Vec::new();
+    };
+    DEPRECATED_POOL_KEYS
+        .iter()
+        .copied()
+        .filter(|key| {
+            pools.values().any(|pool| {
+                pool.as_table()
+                    .is_some_and(|table| table.contains_key(*key))
+            })
+        })
+        .collect()
+}
+
+/// Deprecated keys inside a pool section, with the same `alias` contract
+/// as `DEPRECATED_GENERAL_KEYS`.
+const DEPRECATED_POOL_KEYS: &[&str] = &["server_reset_query"];
+
 /// Detect deprecated keys in raw config content and emit a `log::warn!`
 /// for each one found. Failures to parse the raw value are silent —
 /// the main parser produces the user-facing error.
-fn warn_on_deprecated_general_keys(contents: &str, format: ConfigFormat) {
+fn warn_on_deprecated_keys(contents: &str, format: ConfigFormat) {
     let deprecated = match format {
         ConfigFormat::Yaml => match serde_yaml::from_str::<serde_yaml::Value>(contents) {
-            Ok(value) => find_deprecated_general_keys_yaml(&value),
+            Ok(value) => {
+                let mut keys = find_deprecated_general_keys_yaml(&value);
+                keys.extend(find_deprecated_pool_keys_yaml(&value));
+                keys
+            }
             Err(_) => return,
         },
         ConfigFormat::Toml => match contents.parse::<toml::Value>() {
-            Ok(value) => find_deprecated_general_keys_toml(&value),
+            Ok(value) => {
+                let mut keys = find_deprecated_general_keys_toml(&value);
+                keys.extend(find_deprecated_pool_keys_toml(&value));
+                keys
+            }
             Err(_) => return,
         },
     };
@@ -147,6 +209,12 @@ fn warn_on_deprecated_general_keys(contents: &str, format: ConfigFormat) {
                  backward compatibility. Update your config; the alias may be removed in a \
                  future release."
             ),
+            "server_reset_query" => warn!(
+                "configuration uses deprecated pool field 'server_reset_query'; the value has \
+                 been mapped to 'server_cleanup_query' with server_cleanup_mode = \"custom\" \
+                 (\"discard_all\" for DISCARD ALL) and runs on dirty connections only. Update \
+                 your config; the alias may be removed in a future release."
+            ),
             other => warn!(
                 "configuration uses deprecated field '{other}'; \
                  update your config — the alias may be removed in a future release."
@@ -174,6 +242,29 @@ fn remove_json_nulls(value: &mut serde_json::Value) {
     }
 }
 
+/// Replace every non-empty `password` or `*_password` string with `***`.
+fn redact_passwords(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                let secret = key == "password" || key.ends_with("_password");
+                match v {
+                    serde_json::Value::String(s) if secret && !s.is_empty() => {
+                        *s = "***".to_string();
+                    }
+                    _ => redact_passwords(v),
+                }
+            }
+        }
+        serde_json::Value::Array(arr) => {
+            for item in arr.iter_mut() {
+                redact_passwords(item);
+            }
+        }
+        _ => {}
+    }
+}
+
 /// Convert configuration content to TOML string for merging.
 /// This allows mixing YAML and TOML files in include.files.
 fn content_to_toml_string(contents: &str, format: ConfigFormat) -> Result<String, Error> {
@@ -254,7 +345,10 @@ impl Default for Config {
                 keys: vec![],
                 databases: vec![],
             },
-            include: Include { files: Vec::new() },
+            include: Include {
+                files: Vec::new(),
+                dirs: Vec::new(),
+            },
         }
     }
 }
@@ -286,6 +380,10 @@ impl From<&Config> for std::collections::HashMap<String, String> {
         let mut static_settings = vec![
             ("host".to_string(), config.general.host.to_string()),
             ("port".to_string(), config.general.port.to_string()),
+            (
+                "worker_threads".to_string(),
+                config.general.worker_threads.to_string(),
+            ),
             (
                 "connect_timeout".to_string(),
                 config.general.connect_timeout.to_string(),
@@ -306,6 +404,17 @@ impl From<&Config> for std::collections::HashMap<String, String> {
 }
 
 impl Config {
+    /// The configuration pg_doorman runs with, after includes are merged
+    /// and validated, as TOML with passwords replaced by `***`.
+    pub fn effective_toml(&self) -> Result<String, Error> {
+        let mut value = serde_json::to_value(self)
+            .map_err(|err| Error::BadConfig(format!("config serialization error: {err}")))?;
+        remove_json_nulls(&mut value);
+        redact_passwords(&mut value);
+        toml::to_string_pretty(&value)
+            .map_err(|err| Error::BadConfig(format!("config serialization error: {err}")))
+    }
+
     /// Print current configuration.
     pub fn show(&self) {
         info!("Worker threads: {}", self.general.worker_threads);
@@ -343,6 +452,12 @@ impl Config {
         );
         info!("Backlog: {}", self.general.backlog);
         info!("Max connections: {}", self.general.max_connections);
+        if let Some(timeout) = self.general.max_connections_queue() {
+            info!(
+                "Clients over max connections queue for up to {}",
+                format_duration_ms(timeout.as_millis() as u64)
+            );
+        }
         info!("Server round robin: {}", self.general.server_round_robin);
         if self.general.hba.is_empty() {
             if let Some(pg_hba) = &self.general.pg_hba {
@@ -432,6 +547,14 @@ impl Config {
             &self.general.startup_parameters,
             "general.startup_parameters",
         )?;
+        startup_parameters::validate_ignore_list(
+            &self.general.ignore_startup_parameters,
+            "general.ignore_startup_parameters",
+        )?;
+        startup_parameters::validate_parameter_status_overrides(
+            &self.general.parameter_status_overrides,
+            "general.parameter_status_overrides",
+        )?;
         // Reject deterministic `general + pool` overflows at config load.
         // For each configured user, mirror the runtime full-packet size
         // check so `pg_doorman -t` fails even when the parameter body fits
@@ -446,10 +569,7 @@ impl Config {
             // pool that overrides `timezone` with `TimeZone` would
             // serialise two rows during validation and disagree with
             // the runtime byte count.
-            let merged = startup_parameters::cascade_canonical_keys(&[
-                &self.general.startup_parameters,
-                &pool_config.startup_parameters,
-            ]);
+            let merged = pool_config.resolve_startup_parameters(&self.general);
             let merged_size = startup_parameters::serialized_bytes(&merged);
             if merged_size > startup_parameters::MAX_OPERATOR_BUDGET {
                 return Err(Error::BadConfig(format!(
@@ -460,10 +580,7 @@ impl Config {
                     startup_parameters::MAX_STARTUP_PACKET_SIZE,
                 )));
             }
-            let server_database = pool_config
-                .server_database
-                .as_deref()
-                .unwrap_or(pool_name.as_str());
+            let server_database = pool_config.resolve_server_database(pool_name);
             // Runtime resolves the StartupMessage application_name as
             // pool override → `"pg_doorman"`. Mirror that default so
             // `pg_doorman -t` doesn't accept a config whose only safe
@@ -494,9 +611,9 @@ impl Config {
                 Ok(())
             };
             for user in &pool_config.users {
-                let server_username = user
-                    .server_username
-                    .as_deref()
+                let server_username = pool_config
+                    .server_credentials(user)
+                    .0
                     .unwrap_or(user.username.as_str());
                 validate_user_identity("user", &user.username, server_username)?;
             }
@@ -525,6 +642,125 @@ impl Config {
             ));
         }
 
+        if self.general.buffer_pool_initial_capacity.as_bytes() == 0
+            || self.general.buffer_pool_initial_capacity.as_bytes()
+                > self.general.buffer_pool_max_buffer_size.as_bytes()
+        {
+            return Err(Error::BadConfig(
+                "general.buffer_pool_initial_capacity must be > 0 and not larger than \
+                 buffer_pool_max_buffer_size"
+                    .to_string(),
+            ));
+        }
+
+        if self.general.max_connections_overflow_behavior == MaxConnectionsOverflow::Queue
+            && self.general.max_connections_queue_timeout.as_millis() == 0
+        {
+            return Err(Error::BadConfig(
+                "general.max_connections_queue_timeout must be > 0 when \
+                 max_connections_overflow_behavior is \"queue\""
+                    .to_string(),
+            ));
+        }
+
+        if self.general.auth_fail_ban_threshold > 0
+            && (self.general.auth_fail_ban_window.as_millis() == 0
+                || self.general.auth_fail_ban_duration.as_millis() == 0)
+        {
+            return Err(Error::BadConfig(
+                "general.auth_fail_ban_window and general.auth_fail_ban_duration must be > 0 \
+                 when auth_fail_ban_threshold is set"
+                    .to_string(),
+            ));
+        }
+
+        let mut listen_ports = std::collections::HashSet::new();
+        listen_ports.insert(self.general.port);
+        for listener in &self.general.listeners {
+            if !listen_ports.insert(listener.port) {
+                return Err(Error::BadConfig(format!(
+                    "general.listeners: port {} is already used by another listener",
+                    listener.port
+                )));
+            }
+            if listener
+                .default_database
+                .as_ref()
+                .is_some_and(|name| name.is_empty())
+            {
+                return Err(Error::BadConfig(format!(
+                    "general.listeners: default_database of the listener on port {} cannot be empty",
+                    listener.port
+                )));
+            }
+        }
+        match self.general.admin_listen_port {
+            Some(port) if !listen_ports.insert(port) => {
+                return Err(Error::BadConfig(format!(
+                    "general.admin_listen_port: port {port} is already used by another listener"
+                )));
+            }
+            None if self.general.admin_listen_host.is_some() => {
+                return Err(Error::BadConfig(
+                    "general.admin_listen_host requires general.admin_listen_port".to_string(),
+                ));
+            }
+            _ => {}
+        }
+
+        let mut admin_names = std::collections::HashSet::new();
+        admin_names.insert(self.general.admin_username.as_str());
+        for user in &self.general.admin_users {
+            if user.username.is_empty() || user.password.is_empty() {
+                return Err(Error::BadConfig(
+                    "general.admin_users: username and password cannot be empty".to_string(),
+                ));
+            }
+            if !admin_names.insert(user.username.as_str()) {
+                return Err(Error::BadConfig(format!(
+                    "general.admin_users: admin user \"{}\" is defined more than once",
+                    user.username
+                )));
+            }
+        }
+
+        if self.general.admin_database_name.is_empty() {
+            return Err(Error::BadConfig(
+                "general.admin_database_name cannot be empty".to_string(),
+            ));
+        }
+        if let Some(pool_name) = self
+            .pools
+            .keys()
+            .find(|name| self.general.is_admin_database(name))
+        {
+            return Err(Error::BadConfig(format!(
+                "pool \"{pool_name}\" clashes with the admin database name; \
+                 rename the pool or change general.admin_database_name"
+            )));
+        }
+        // `pool/shard` names the connection pools of a pool's shards, so it
+        // must not be ambiguous or taken by another pool.
+        for (pool_name, pool) in &self.pools {
+            if pool.shards.is_empty() {
+                continue;
+            }
+            if pool_name.contains('/') {
+                return Err(Error::BadConfig(format!(
+                    "pool \"{pool_name}\" has shards and cannot contain '/', which separates pool and shard names"
+                )));
+            }
+            if let Some(shard) = pool
+                .shards
+                .keys()
+                .find(|shard| self.pools.contains_key(&format!("{pool_name}/{shard}")))
+            {
+                return Err(Error::BadConfig(format!(
+                    "pool \"{pool_name}/{shard}\" clashes with shard \"{shard}\" of pool \"{pool_name}\""
+                )));
+            }
+        }
+
         // Validate scaling_warm_pool_ratio
         if self.general.scaling_warm_pool_ratio > 100 {
             return Err(Error::BadConfig(
@@ -573,10 +809,34 @@ impl Config {
             );
         }
 
+        // hostssl/hostnossl key on the client-to-pooler leg only. Rules the
+        // listener's TLS settings make unreachable are a configuration
+        // mistake, usually a tightening that silently does nothing.
+        for rule in unreachable_ssl_hba_rules(&self.general) {
+            warn!(
+                "pg_hba rule `{rule}` can never match: {}",
+                if rule.starts_with("hostssl") {
+                    "tls_certificate is not set, so no client connects over TLS"
+                } else {
+                    "tls_mode rejects plaintext TCP clients before pg_hba is evaluated"
+                }
+            );
+        }
+
         // Validate prepared_statements
         if self.general.prepared_statements && self.general.prepared_statements_cache_size == 0 {
             return Err(Error::BadConfig("The value of prepared_statements_cache should be greater than 0 if prepared_statements are enabled".to_string()));
         }
+        for (name, pool) in &self.pools {
+            let cache_size = pool
+                .prepared_statements_cache_size
+                .unwrap_or(self.general.prepared_statements_cache_size);
+            if pool.resolve_prepared_statements(&self.general) && cache_size == 0 {
+                return Err(Error::BadConfig(format!(
+                    "pool {name}: prepared_statements_cache_size should be greater than 0 if prepared_statements are enabled"
+                )));
+            }
+        }
 
         // Validate query interner GC interval. The spawn divides this by 4 to
         // get the sweep tick, so 0 would deadlock the timer.
@@ -626,15 +886,38 @@ impl Config {
                         "tls_mode is {mode} but tls_certificate or tls_private_key is not"
                     )));
                 }
-                if mode == tls::TLSMode::VerifyFull && self.general.tls_ca_cert.is_none() {
+                if mode.requires_ca() && self.general.tls_ca_cert.is_none() {
                     return Err(Error::BadConfig(format!(
                         "tls_mode is {mode} but tls_ca_cert is not set"
                     )));
                 }
                 #[cfg(not(target_os = "linux"))]
-                if mode == tls::TLSMode::VerifyFull {
+                if mode.requires_ca() {
+                    return Err(Error::BadConfig(format!(
+                        "tls_mode {mode} is supported only on linux"
+                    )));
+                }
+            }
+
+            let min_version = match self.general.tls_min_protocol_version.as_deref() {
+                Some(version) => Some(tls::parse_tls_protocol_version(version)?),
+                None => None,
+            };
+            if let Some(ref ciphers) = self.general.tls_ciphers {
+                if ciphers.trim().is_empty() {
+                    return Err(Error::BadConfig("tls_ciphers cannot be empty".to_string()));
+                }
+                let mut ctx = openssl::ssl::SslContext::builder(openssl::ssl::SslMethod::tls())
+                    .map_err(|err| Error::BadConfig(format!("tls_ciphers: {err}")))?;
+                if ctx.set_cipher_list(ciphers).is_err() {
+                    return Err(Error::BadConfig(format!(
+                        "tls_ciphers \"{ciphers}\" does not name any cipher known to OpenSSL"
+                    )));
+                }
+                if matches!(min_version, Some(native_tls::Protocol::Tlsv13)) {
                     return Err(Error::BadConfig(
-                        "tls_mode verify-full is supported only on linux".to_string(),
+                        "tls_ciphers applies to TLS 1.2 only and has no effect with tls_min_protocol_version = \"1.3\""
+                            .to_string(),
                     ));
                 }
             }
@@ -766,6 +1049,18 @@ impl Config {
             pool.validate().await?;
         }
 
+        if self.general.server_connect_backoff_base.as_millis() == 0
+            || self.general.server_connect_backoff_base.as_millis()
+                > self.general.server_connect_backoff_max.as_millis()
+        {
+            return Err(Error::BadConfig(format!(
+                "server_connect_backoff_base ({}ms) must be positive and not exceed \
+                 server_connect_backoff_max ({}ms)",
+                self.general.server_connect_backoff_base.as_millis(),
+                self.general.server_connect_backoff_max.as_millis()
+            )));
+        }
+
         // Cross-config validation: coordinator timeouts vs query_wait_timeout
         let qwt = self.general.query_wait_timeout.as_millis();
         for (pool_name, pool_config) in &self.pools {
@@ -826,10 +1121,62 @@ async fn load_file(path: &str) -> Result<String, Error> {
     Ok(contents)
 }
 
+/// Config files of an `include.dirs` entry, sorted by file name so the merge
+/// order does not depend on the filesystem. Other files and subdirectories
+/// are skipped.
+fn include_dir_files(dir: &str) -> Result<Vec<String>, Error> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|err| Error::BadConfig(format!("Could not read include dir {dir}: {err}")))?;
+    let mut files = Vec::new();
+    for entry in entries {
+        let entry = entry
+            .map_err(|err| Error::BadConfig(format!("Could not read include dir {dir}: {err}")))?;
+        let path = entry.path();
+        let is_config = matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("toml" | "yaml" | "yml")
+        );
+        if is_config && path.is_file() {
+            files.push(path.to_string_lossy().into_owned());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
 /// Parse the configuration file located at the path.
 /// Supports both TOML (.toml) and YAML (.yaml, .yml) formats.
 /// Format is auto-detected based on file extension.
 pub async fn parse(path: &str) -> Result<(), Error> {
+    let config = load(path).await?;
+    install(config);
+    Ok(())
+}
+
+/// Check the config at `path` the way startup does, without making it
+/// active, binding listeners or connecting to backends. On top of
+/// `load` it builds the client TLS acceptor, which startup otherwise
+/// only does after the config is installed.
+pub async fn check(path: &str) -> Result<Config, Error> {
+    let config = load(path).await?;
+    if let (Some(cert), Some(key)) = (
+        config.general.tls_certificate.as_deref(),
+        config.general.tls_private_key.as_deref(),
+    ) {
+        tls::build_acceptor(
+            Path::new(cert),
+            Path::new(key),
+            config.general.tls_ca_cert.as_deref(),
+            config.general.tls_mode.clone(),
+            config.general.tls_min_protocol_version.as_deref(),
+            config.general.tls_ciphers.as_deref(),
+        )?;
+    }
+    Ok(config)
+}
+
+/// Read, merge and validate the config at `path` without making it active.
+async fn load(path: &str) -> Result<Config, Error> {
     let format = ConfigFormat::detect(path);
 
     // parse only include.files = ["./path/to/file",...]
@@ -844,7 +1191,12 @@ pub async fn parse(path: &str) -> Result<(), Error> {
         .parse()
         .map_err(|err| Error::BadConfig(format!("Could not parse config file {path}: {err:?}")))?;
 
-    for file in include_config.include.files {
+    let mut include_files = include_config.include.files;
+    for dir in &include_config.include.dirs {
+        include_files.extend(include_dir_files(dir)?);
+    }
+
+    for file in include_files {
         info!("Merge config with include file: {file}");
         let include_file_content = load_file(file.as_str()).await?;
         let include_format = ConfigFormat::detect(&file);
@@ -874,24 +1226,55 @@ pub async fn parse(path: &str) -> Result<(), Error> {
 
     config.path = path.to_string();
 
-    // Update the configuration globally.
-    CONFIG.store(Arc::new(config.clone()));
-    update_pooler_check_query_snapshot(&config.general.pooler_check_query);
+    Ok(config)
+}
 
-    Ok(())
+/// Update the configuration globally.
+fn install(config: Config) {
+    let config = Arc::new(config);
+    CONFIG.store(config.clone());
+    crate::client::buffer_pool::configure(
+        config.general.buffer_pool_initial_capacity.as_usize(),
+        config.general.buffer_pool_max_retained,
+        config.general.buffer_pool_max_buffer_size.as_usize(),
+    );
+    update_pooler_check_query_snapshot(&config.general.pooler_check_query);
+    crate::stats::application::update_application_allowlist(
+        &config.general.stats_application_names,
+    );
 }
 
-pub async fn reload_config(client_server_map: ClientServerMap) -> Result<bool, Error> {
+/// Reload the config file and reconcile pools. Returns what changed; an
+/// empty diff means the file re-parsed identically to the running config.
+pub async fn reload_config(client_server_map: ClientServerMap) -> Result<ConfigDiff, Error> {
     let old_config = get_config();
 
-    match parse(&old_config.path).await {
-        Ok(()) => (),
+    let candidate = match load(&old_config.path).await {
+        Ok(config) => config,
         Err(err) => {
             error!("Config reload error: {err}");
             return Err(Error::BadConfig(format!("Config reload error: {err:?}")));
         }
     };
 
+    // Reject the whole file rather than apply the pool/user/hba part and
+    // quietly keep the old listener or runtime settings.
+    let restart_required = old_config
+        .general
+        .restart_required_changes(&candidate.general);
+    if !restart_required.is_empty() {
+        let fields = restart_required.join(", ");
+        error!(
+            "Config reload rejected: changed settings require a restart: {fields}; \
+             UPGRADE (SIGUSR2) applies them without dropping clients"
+        );
+        return Err(Error::BadConfig(format!(
+            "changed settings require a restart and cannot be reloaded: {fields}; \
+             UPGRADE (SIGUSR2) applies them without dropping clients"
+        )));
+    }
+    install(candidate);
+
     let new_config = get_config();
     // Refresh the web listener's reload-aware options whether or not
     // pools changed: `[web]` and `[general].admin_*` updates can land
@@ -907,11 +1290,15 @@ pub async fn reload_config(client_server_map: ClientServerMap) -> Result<bool, E
     crate::web::metrics::refresh_static_info_metrics();
 
     if old_config != new_config {
-        info!("Config changed, reloading");
+        let diff = ConfigDiff::compute(&old_config, &new_config);
+        info!("Config changed, reloading ({} changes)", diff.changes.len());
+        for change in &diff.changes {
+            info!("Config change: {change}");
+        }
         ConnectionPool::from_config(client_server_map).await?;
-        Ok(true)
+        Ok(diff)
     } else {
-        Ok(false)
+        Ok(ConfigDiff::default())
     }
 }
 
@@ -925,6 +1312,21 @@ pub fn check_hba(
     check_hba_with_general(&config.general, transport, type_auth, username, database)
 }
 
+/// Custom message of the `pg_hba` `reject` rule denying this connection.
+pub fn hba_reject_message(
+    transport: &ClientTransport,
+    username: &str,
+    database: &str,
+) -> Option<String> {
+    let config = get_config();
+    config
+        .general
+        .pg_hba
+        .as_ref()?
+        .reject_message(transport, username, database)
+        .map(str::to_string)
+}
+
 /// True when the operator enabled a Unix listener alongside the legacy
 /// IP-based `general.hba` whitelist, without a `pg_hba` snippet to cover
 /// the `local` transport. In this shape Unix clients bypass the CIDR
@@ -933,6 +1335,27 @@ pub(crate) fn legacy_hba_bypassed_by_unix_socket(general: &General) -> bool {
     general.unix_socket_dir.is_some() && general.pg_hba.is_none() && !general.hba.is_empty()
 }
 
+/// `pg_hba` rules that can never match under the client-facing TLS settings:
+/// `hostssl` when no `tls_certificate` is configured (the listener answers
+/// every SSLRequest with `N`), and `hostnossl` when `tls_mode` is `require`,
+/// `verify-ca` or `verify-full` (plaintext TCP is rejected before HBA runs).
+pub(crate) fn unreachable_ssl_hba_rules(general: &General) -> Vec<String> {
+    let Some(ref pg) = general.pg_hba else {
+        return Vec::new();
+    };
+    let tls_available = general.tls_certificate.is_some();
+    let plain_available = !general.only_ssl_connections();
+    pg.rules
+        .iter()
+        .filter(|rule| match rule.host_type {
+            HostType::HostSSL => !tls_available,
+            HostType::HostNoSSL => !plain_available,
+            HostType::Host | HostType::Local => false,
+        })
+        .map(|rule| rule.to_string())
+        .collect()
+}
+
 /// Pure evaluation of HBA rules against an explicit [`General`] snapshot.
 ///
 /// Split out of [`check_hba`] so that unit tests can exercise the legacy
@@ -945,20 +1368,33 @@ pub(crate) fn check_hba_with_general(
     username: &str,
     database: &str,
 ) -> CheckResult {
+    explain_hba_with_general(general, transport, type_auth, username, database).0
+}
+
+/// [`check_hba_with_general`] plus the index of the deciding rule: a
+/// position in `pg_hba.rules`, or in the legacy `general.hba` list when
+/// `pg_hba` is not configured. `None` when no rule decided, including the
+/// legacy "allow everything" cases (empty list, Unix socket).
+pub(crate) fn explain_hba_with_general(
+    general: &General,
+    transport: &ClientTransport,
+    type_auth: &str,
+    username: &str,
+    database: &str,
+) -> (CheckResult, Option<usize>) {
     if let Some(ref pg) = general.pg_hba {
-        return pg.check_hba(transport, type_auth, username, database);
+        return pg.matching_rule(transport, type_auth, username, database);
     }
     // Legacy hba list has no unix concept — allow all unix connections
     if transport.is_unix() {
-        return CheckResult::Allow;
+        return (CheckResult::Allow, None);
     }
     if general.hba.is_empty() {
-        return CheckResult::Allow;
+        return (CheckResult::Allow, None);
     }
     let ip = transport.hba_ip();
-    if general.hba.iter().any(|net| net.contains(&ip)) {
-        CheckResult::Allow
-    } else {
-        CheckResult::NotMatched
+    match general.hba.iter().position(|net| net.contains(&ip)) {
+        Some(idx) => (CheckResult::Allow, Some(idx)),
+        None => (CheckResult::NotMatched, None),
     }
 }
@@ -9,7 +9,8 @@ use std::collections::HashSet;
 use std::fmt;
 use std::hash::{Hash, Hasher};
 
-use super::{Duration, PoolMode, User};
+use super::user::validate_connect_queries;
+use super::{ByteSize, Duration, PoolBackend, PoolMode, TcpKeepaliveSettings, User};
 
 /// Custom deserializer for users field that supports both formats:
 /// - Array format (recommended): `users: [{ username: "user1", ... }]`
@@ -79,12 +80,158 @@ pub struct Pool {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub server_lifetime: Option<u64>,
 
+    /// Close client connections that stay idle outside a transaction for
+    /// longer than this (milliseconds). In session mode this also releases
+    /// the server the client was holding. 0 or None = disabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_idle_timeout: Option<u64>,
+
+    /// Cancel a query that runs longer than this (milliseconds) with a
+    /// CancelRequest to the backend. Independent of the server-side
+    /// `statement_timeout`, which roles can override. 0 or None = disabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_query_duration: Option<u64>,
+
+    /// Cancel a query once one round trip has returned more DataRow
+    /// messages than this. COPY output is not counted. 0 or None = disabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_result_rows: Option<u64>,
+
+    /// Close a server connection at the first transaction boundary after it
+    /// has been alive this long (milliseconds), even if it was never idle.
+    /// `server_lifetime` only ages out idle connections. 0 or None = disabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_max_age: Option<u64>,
+
+    /// Override of `general.message_size_to_be_stream` for this pool.
+    /// Server messages larger than this are streamed to the client.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_size_to_be_stream: Option<ByteSize>,
+
     #[serde(default = "Pool::default_cleanup_server_connections")]
     pub cleanup_server_connections: bool,
 
+    /// What a dirty server connection runs at checkin when
+    /// `cleanup_server_connections` is on. Default: `reset_all`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_cleanup_mode: Option<ServerCleanupMode>,
+
+    /// Statements run at checkin when `server_cleanup_mode` is `custom`.
+    ///
+    /// The `server_reset_query` alias accepts PgBouncer configs; without a
+    /// `server_cleanup_mode` the value selects the mode in `validate`.
+    #[serde(
+        default,
+        alias = "server_reset_query",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub server_cleanup_query: Option<String>,
+
+    /// Statements run in order on every new server connection, right after
+    /// startup. Users may override the list with their own.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub connect_queries: Vec<String>,
+
+    /// `statement_timeout` (ms) set on every server connection of the pool.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_statement_timeout_ms: Option<u64>,
+
+    /// `lock_timeout` (ms) set on every server connection of the pool.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_lock_timeout_ms: Option<u64>,
+
+    /// `statement_timeout` (ms) advertised to clients that send none and
+    /// applied on checkout. 0 or None = disabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_client_statement_timeout_ms: Option<u64>,
+
+    /// Backend socket keepalive idle time (seconds); overrides
+    /// `general.tcp_keepalives_idle` for this pool's server connections.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_tcp_keepalives_idle: Option<u64>,
+
+    /// Backend socket keepalive probe interval (seconds).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_tcp_keepalives_interval: Option<u64>,
+
+    /// Backend socket keepalive probe count.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_tcp_keepalives_count: Option<u32>,
+
+    /// Backend socket `TCP_USER_TIMEOUT` (seconds, 0 = OS default).
+    /// Only supported on Linux.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_tcp_user_timeout: Option<u64>,
+
+    /// Override of `general.max_concurrent_creates` for this pool's
+    /// server pools.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_creates: Option<usize>,
+
+    /// Maximum number of this pool's transactions running on the backend
+    /// at once, independent of `pool_size`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_queries: Option<usize>,
+
+    /// Pause the pool while its backend keeps failing: checkouts fail fast
+    /// with "backend unavailable" instead of waiting `query_wait_timeout`.
+    #[serde(default)] // False
+    pub auto_pause_on_unhealthy: bool,
+
+    /// Consecutive connect or alive-check failures that auto-pause the pool.
+    #[serde(default = "Pool::default_auto_pause_failure_threshold")]
+    pub auto_pause_failure_threshold: u32,
+
+    /// Consecutive successful connects that resume an auto-paused pool.
+    #[serde(default = "Pool::default_auto_pause_success_threshold")]
+    pub auto_pause_success_threshold: u32,
+
     #[serde(default)] // False
     pub log_client_parameter_status_changes: bool,
 
+    /// Log every startup parameter a client of this pool sends.
+    #[serde(default)] // False
+    pub log_startup_parameters: bool,
+
+    /// Let transaction-mode users with identical `server_username` and
+    /// `server_password` draw from one server connection pool.
+    #[serde(default)] // False
+    pub share_server_connections: bool,
+
+    /// Start every server connection with `default_transaction_read_only = on`.
+    #[serde(default)] // False
+    pub read_only: bool,
+
+    /// With `read_only`, answer obvious write statements with an error
+    /// instead of forwarding them to the server.
+    #[serde(default)] // False
+    pub reject_write_queries: bool,
+
+    /// In transaction mode, re-run a single read-only simple query on a new
+    /// server connection once when the first one breaks before answering.
+    #[serde(default)] // False
+    pub retry_readonly_on_disconnect: bool,
+
+    /// In transaction mode, give a client the server connection it used for
+    /// its previous transaction when that connection is idle.
+    #[serde(default)] // False
+    pub prefer_last_server: bool,
+
+    /// Let clients of a transaction-mode pool switch their own connection
+    /// to session mode with `options=-c pg_doorman.pool_mode=session`.
+    #[serde(default)] // False
+    pub allow_session_override: bool,
+
+    /// Relay each client's SCRAM exchange to a backend connection of its
+    /// own, so PostgreSQL checks the credentials. Session mode only.
+    #[serde(default)] // False
+    pub scram_relay: bool,
+
+    /// Label every checked-out server connection with the client's address
+    /// and tag in `pg_doorman.client_addr` / `pg_doorman.client_tag`.
+    #[serde(default)] // False
+    pub client_label_gucs: bool,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub application_name: Option<String>,
 
@@ -94,10 +241,25 @@ pub struct Pool {
     #[serde(default = "Pool::default_server_port")]
     pub server_port: u16,
 
-    // The real name of the database on the server. If it is not specified, the pool name is used.
+    /// Database name sent to PostgreSQL. The pool name stays the name
+    /// clients connect to; this only renames the database on the backend
+    /// side. Several pools may point at the same server database.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub server_database: Option<String>,
 
+    /// Backend credentials for users of this pool that set no
+    /// `server_username` of their own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_username: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_password: Option<String>,
+
+    /// Override `general.prepared_statements` for this pool. `false` turns
+    /// the pool-level cache off, so Parse/Bind pass through unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prepared_statements: Option<bool>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub prepared_statements_cache_size: Option<usize>,
 
@@ -116,6 +278,10 @@ pub struct Pool {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub scaling_fast_retries: Option<u32>,
 
+    /// Override global scaling_shrink_step for this pool.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scaling_shrink_step: Option<u32>,
+
     /// Maximum total server connections to this database across all users.
     /// 0 or None = disabled (default), each user pool works independently.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -185,6 +351,29 @@ pub struct Pool {
     #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
     pub startup_parameters: std::collections::BTreeMap<String, String>,
 
+    /// Per-pool replacement for `general.ignore_startup_parameters`.
+    /// An empty list stops ignoring anything for this pool.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ignore_startup_parameters: Option<Vec<String>>,
+
+    /// Pool-level `ParameterStatus` overrides; they win over
+    /// `general.parameter_status_overrides` per name.
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub parameter_status_overrides: std::collections::BTreeMap<String, String>,
+
+    /// Backends picked by the client's `pg_doorman.shard` startup parameter.
+    /// Each key gets its own connection pool per user; clients without a
+    /// shard key use `server_host`/`server_port`.
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub shards: std::collections::BTreeMap<String, ShardTarget>,
+
+    /// Other members of the `server_host` cluster. Clients that send
+    /// `target_session_attrs` are routed among `server_host` and these by
+    /// the role `pg_is_in_recovery()` reports; other clients stay on
+    /// `server_host`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hosts: Vec<HostTarget>,
+
     #[serde(
         default = "Pool::default_users",
         deserialize_with = "deserialize_users"
@@ -221,6 +410,14 @@ impl Pool {
         true
     }
 
+    pub fn default_auto_pause_failure_threshold() -> u32 {
+        5
+    }
+
+    pub fn default_auto_pause_success_threshold() -> u32 {
+        2
+    }
+
     /// Resolve scaling config by merging pool-level overrides with general defaults.
     /// Anticipation/burst params are global-only by design (no per-pool override).
     pub fn resolve_scaling_config(
@@ -233,11 +430,219 @@ impl Pool {
         let retries = self
             .scaling_fast_retries
             .unwrap_or(general.scaling_fast_retries);
+        let shrink_step = self
+            .scaling_shrink_step
+            .unwrap_or(general.scaling_shrink_step);
         crate::pool::ScalingConfig {
             warm_pool_ratio: ratio as f32 / 100.0,
             fast_retries: retries,
             max_parallel_creates: general.scaling_max_parallel_creates,
+            shrink_step,
+        }
+    }
+
+    /// Statements run on each new server connection of `user`: the user's own
+    /// `connect_queries` if set, otherwise the pool's.
+    pub fn resolve_connect_queries<'a>(&'a self, user: &'a User) -> &'a [String] {
+        user.connect_queries
+            .as_deref()
+            .unwrap_or(self.connect_queries.as_slice())
+    }
+
+    /// `SET` statements for `server_statement_timeout_ms` and
+    /// `server_lock_timeout_ms`, or `None` when neither is set.
+    pub fn server_timeouts_query(&self) -> Option<String> {
+        let mut query = String::new();
+        if let Some(ms) = self.server_statement_timeout_ms {
+            query.push_str(&format!("SET statement_timeout = {ms};"));
+        }
+        if let Some(ms) = self.server_lock_timeout_ms {
+            query.push_str(&format!("SET lock_timeout = {ms};"));
+        }
+        (!query.is_empty()).then_some(query)
+    }
+
+    /// Keepalive settings for this pool's server sockets: each
+    /// `server_tcp_*` field if set, otherwise the general one.
+    pub fn resolve_server_tcp_keepalive(
+        &self,
+        general: &crate::config::General,
+    ) -> TcpKeepaliveSettings {
+        let global = general.tcp_keepalive_settings();
+        TcpKeepaliveSettings {
+            idle: self.server_tcp_keepalives_idle.unwrap_or(global.idle),
+            interval: self
+                .server_tcp_keepalives_interval
+                .unwrap_or(global.interval),
+            count: self.server_tcp_keepalives_count.unwrap_or(global.count),
+            user_timeout: self.server_tcp_user_timeout.unwrap_or(global.user_timeout),
+        }
+    }
+
+    /// Database to open on the server: `server_database` if set, otherwise
+    /// the pool name.
+    pub fn resolve_server_database<'a>(&'a self, pool_name: &'a str) -> &'a str {
+        self.server_database.as_deref().unwrap_or(pool_name)
+    }
+
+    /// Pool map key of a shard's connection pool. Validation keeps `/` out
+    /// of pool names and shard keys, so it cannot match another pool.
+    pub fn shard_pool_name(pool_name: &str, shard: &str) -> String {
+        format!("{pool_name}/{shard}")
+    }
+
+    /// Pool map key of the connection pool of a `hosts` entry.
+    pub fn host_pool_name(pool_name: &str, host: &str, port: u16) -> String {
+        format!("{pool_name}@{host}:{port}")
+    }
+
+    /// Backends the pool's users get connection pools for: the pool's own
+    /// server first, then each shard in key order, then each `hosts` entry.
+    pub fn backends<'a>(&'a self, pool_name: &'a str) -> Vec<PoolBackend<'a>> {
+        let server_database = self.resolve_server_database(pool_name);
+        let mut backends = Vec::with_capacity(1 + self.shards.len() + self.hosts.len());
+        backends.push(PoolBackend {
+            pool_key: pool_name.to_string(),
+            host: &self.server_host,
+            port: self.server_port,
+            server_database,
+            shard: None,
+            cluster_host: false,
+        });
+        for (shard, target) in &self.shards {
+            backends.push(PoolBackend {
+                pool_key: Self::shard_pool_name(pool_name, shard),
+                host: &target.server_host,
+                port: target.server_port.unwrap_or(self.server_port),
+                server_database: target.server_database.as_deref().unwrap_or(server_database),
+                shard: Some(shard),
+                cluster_host: false,
+            });
+        }
+        for target in &self.hosts {
+            let port = target.server_port.unwrap_or(self.server_port);
+            backends.push(PoolBackend {
+                pool_key: Self::host_pool_name(pool_name, &target.server_host, port),
+                host: &target.server_host,
+                port,
+                server_database,
+                shard: None,
+                cluster_host: true,
+            });
         }
+        backends
+    }
+
+    /// Backend `server_username` and `server_password` of `user`: its own
+    /// when it sets `server_username`, the pool's otherwise.
+    pub fn server_credentials<'a>(&'a self, user: &'a User) -> (Option<&'a str>, Option<&'a str>) {
+        match user.server_username {
+            Some(_) => (
+                user.server_username.as_deref(),
+                user.server_password.as_deref(),
+            ),
+            None => (
+                self.server_username.as_deref(),
+                self.server_password.as_deref(),
+            ),
+        }
+    }
+
+    /// `user` with the pool's `server_username`/`server_password` filled
+    /// in when it has no backend credentials of its own.
+    pub fn resolve_user(&self, user: &User) -> User {
+        let (server_username, server_password) = self.server_credentials(user);
+        User {
+            server_username: server_username.map(str::to_string),
+            server_password: server_password.map(str::to_string),
+            ..user.clone()
+        }
+    }
+
+    /// Server credentials `user` shares a server connection pool under when
+    /// `share_server_connections` is on. Only transaction-mode users with
+    /// both `server_username` and `server_password` resolved can share.
+    pub fn shared_server_key<'a>(&'a self, user: &'a User) -> Option<(&'a str, &'a str)> {
+        if !self.share_server_connections
+            || user.pool_mode.unwrap_or(self.pool_mode) != PoolMode::Transaction
+        {
+            return None;
+        }
+        match self.server_credentials(user) {
+            (Some(server_username), Some(server_password)) => {
+                Some((server_username, server_password))
+            }
+            _ => None,
+        }
+    }
+
+    /// Size of a shared server pool: the largest `pool_size` among the users
+    /// sharing it.
+    pub fn shared_pool_size(&self, key: (&str, &str)) -> u32 {
+        self.users
+            .iter()
+            .filter(|user| self.shared_server_key(user) == Some(key))
+            .map(|user| user.pool_size)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Whether a user sharing the server pool under `key` has a non-zero
+    /// `priority`, so its waiting clients need the priority queue.
+    pub fn shared_pool_prioritized(&self, key: (&str, &str)) -> bool {
+        self.users
+            .iter()
+            .any(|user| user.priority.unwrap_or(0) > 0 && self.shared_server_key(user) == Some(key))
+    }
+
+    /// Startup parameters sent to every server of this pool: the general
+    /// map overlaid with the pool map, keys canonicalised. `read_only`
+    /// forces `default_transaction_read_only = on` on top of both.
+    pub fn resolve_startup_parameters(
+        &self,
+        general: &crate::config::General,
+    ) -> std::collections::BTreeMap<String, String> {
+        let mut merged = crate::config::startup_parameters::cascade_canonical_keys(&[
+            &general.startup_parameters,
+            &self.startup_parameters,
+        ]);
+        if self.read_only {
+            merged.insert(
+                "default_transaction_read_only".to_string(),
+                "on".to_string(),
+            );
+        }
+        merged
+    }
+
+    /// Whether this pool caches prepared statements: the pool setting if
+    /// set, otherwise the general one.
+    pub fn resolve_prepared_statements(&self, general: &crate::config::General) -> bool {
+        self.prepared_statements
+            .unwrap_or(general.prepared_statements)
+    }
+
+    /// Startup parameters to ignore for this pool: the pool list if set,
+    /// otherwise the general one.
+    pub fn resolve_ignore_startup_parameters<'a>(
+        &'a self,
+        general: &'a crate::config::General,
+    ) -> &'a [String] {
+        self.ignore_startup_parameters
+            .as_deref()
+            .unwrap_or(&general.ignore_startup_parameters)
+    }
+
+    /// `ParameterStatus` overrides for clients of this pool: the general
+    /// map overlaid with the pool map, names canonicalised.
+    pub fn resolve_parameter_status_overrides(
+        &self,
+        general: &crate::config::General,
+    ) -> std::collections::BTreeMap<String, String> {
+        crate::config::startup_parameters::cascade_canonical_keys(&[
+            &general.parameter_status_overrides,
+            &self.parameter_status_overrides,
+        ])
     }
 
     pub async fn validate(&mut self) -> Result<(), Error> {
@@ -245,6 +650,207 @@ impl Pool {
             &self.startup_parameters,
             "pool.startup_parameters",
         )?;
+        crate::config::startup_parameters::validate_parameter_status_overrides(
+            &self.parameter_status_overrides,
+            "pool.parameter_status_overrides",
+        )?;
+        if let Some(database) = &self.server_database {
+            if database.is_empty() || database.contains('\0') {
+                return Err(Error::BadConfig(format!(
+                    "server_database {database:?} is not a valid database name"
+                )));
+            }
+        }
+        for (shard, target) in &self.shards {
+            if shard.is_empty() {
+                return Err(Error::BadConfig("shards: shard key cannot be empty".into()));
+            }
+            if shard.contains('/') {
+                return Err(Error::BadConfig(format!(
+                    "shards.{shard}: shard key cannot contain '/'"
+                )));
+            }
+            if target.server_host.is_empty() {
+                return Err(Error::BadConfig(format!(
+                    "shards.{shard}: server_host cannot be empty"
+                )));
+            }
+            if let Some(database) = &target.server_database {
+                if database.is_empty() || database.contains('\0') {
+                    return Err(Error::BadConfig(format!(
+                        "shards.{shard}: server_database {database:?} is not a valid database name"
+                    )));
+                }
+            }
+        }
+        if self.reject_write_queries && !self.read_only {
+            return Err(Error::BadConfig(
+                "reject_write_queries requires read_only = true".into(),
+            ));
+        }
+        if !self.shards.is_empty() && self.auth_query.is_some() {
+            return Err(Error::BadConfig(
+                "shards cannot be combined with auth_query".into(),
+            ));
+        }
+        let mut members = std::collections::HashSet::new();
+        members.insert((self.server_host.as_str(), self.server_port));
+        for target in &self.hosts {
+            if target.server_host.is_empty() {
+                return Err(Error::BadConfig(
+                    "hosts: server_host cannot be empty".into(),
+                ));
+            }
+            let port = target.server_port.unwrap_or(self.server_port);
+            if !members.insert((target.server_host.as_str(), port)) {
+                return Err(Error::BadConfig(format!(
+                    "hosts: {}:{port} is listed twice",
+                    target.server_host
+                )));
+            }
+        }
+        if !self.hosts.is_empty() && (self.auth_query.is_some() || !self.shards.is_empty()) {
+            return Err(Error::BadConfig(
+                "hosts cannot be combined with auth_query or shards".into(),
+            ));
+        }
+        if self.scram_relay {
+            if self.auth_query.is_some() || !self.shards.is_empty() || !self.hosts.is_empty() {
+                return Err(Error::BadConfig(
+                    "scram_relay cannot be combined with auth_query, shards or hosts".into(),
+                ));
+            }
+            if self.server_username.is_some() {
+                return Err(Error::BadConfig(
+                    "scram_relay: PostgreSQL checks each client's own credentials, remove server_username".into(),
+                ));
+            }
+            for user in &self.users {
+                if user.pool_mode.unwrap_or(self.pool_mode) != PoolMode::Session {
+                    return Err(Error::BadConfig(format!(
+                        "scram_relay requires session pool mode (user '{}')",
+                        user.username
+                    )));
+                }
+                if !user.password.is_empty()
+                    || user.server_username.is_some()
+                    || user.auth_pam_service.is_some()
+                {
+                    return Err(Error::BadConfig(format!(
+                        "scram_relay: user '{}' cannot set password, server_username or auth_pam_service; PostgreSQL checks the client's own credentials",
+                        user.username
+                    )));
+                }
+                if user.min_pool_size.unwrap_or(0) > 0 {
+                    return Err(Error::BadConfig(format!(
+                        "scram_relay: user '{}' cannot set min_pool_size, connections are opened at client login",
+                        user.username
+                    )));
+                }
+            }
+        }
+        if let Some(names) = &self.ignore_startup_parameters {
+            crate::config::startup_parameters::validate_ignore_list(
+                names,
+                "pool.ignore_startup_parameters",
+            )?;
+        }
+
+        validate_connect_queries(&self.connect_queries, "connect_queries")?;
+
+        // A query without a mode comes from `server_reset_query`: `DISCARD
+        // ALL` maps to its mode, an empty one keeps the default.
+        if self.server_cleanup_mode.is_none() {
+            if let Some(query) = self.server_cleanup_query.take() {
+                if crate::server::cleanup::is_discard_all(&query) {
+                    self.server_cleanup_mode = Some(ServerCleanupMode::DiscardAll);
+                } else if !query.trim().is_empty() {
+                    self.server_cleanup_mode = Some(ServerCleanupMode::Custom);
+                    self.server_cleanup_query = Some(query);
+                }
+            }
+        }
+        match (
+            self.server_cleanup_mode,
+            self.server_cleanup_query.as_deref(),
+        ) {
+            (Some(ServerCleanupMode::Custom), None) => {
+                return Err(Error::BadConfig(
+                    "server_cleanup_mode = custom requires server_cleanup_query".into(),
+                ));
+            }
+            (Some(ServerCleanupMode::Custom), Some(query)) if query.trim().is_empty() => {
+                return Err(Error::BadConfig(
+                    "server_cleanup_query cannot be empty".into(),
+                ));
+            }
+            (Some(ServerCleanupMode::Custom), Some(query)) => {
+                validate_server_cleanup_query(query)?;
+            }
+            (_, Some(_)) => {
+                return Err(Error::BadConfig(
+                    "server_cleanup_query is only used with server_cleanup_mode = custom".into(),
+                ));
+            }
+            _ => {}
+        }
+
+        for (name, value) in [
+            (
+                "server_tcp_keepalives_idle",
+                self.server_tcp_keepalives_idle,
+            ),
+            (
+                "server_tcp_keepalives_interval",
+                self.server_tcp_keepalives_interval,
+            ),
+            (
+                "server_tcp_keepalives_count",
+                self.server_tcp_keepalives_count.map(u64::from),
+            ),
+        ] {
+            if value == Some(0) {
+                return Err(Error::BadConfig(format!("{name} must be > 0")));
+            }
+        }
+        if self.default_client_statement_timeout_ms.unwrap_or(0) > 0
+            && self.server_statement_timeout_ms.is_some()
+        {
+            return Err(Error::BadConfig(
+                "default_client_statement_timeout_ms and server_statement_timeout_ms both set statement_timeout; set only one".into(),
+            ));
+        }
+        if self.server_password.is_some() && self.server_username.is_none() {
+            return Err(Error::BadConfig(
+                "server_password requires server_username to be set".into(),
+            ));
+        }
+        if self.max_concurrent_creates == Some(0) {
+            return Err(Error::BadConfig(
+                "max_concurrent_creates must be > 0".into(),
+            ));
+        }
+        if self.max_concurrent_queries == Some(0) {
+            return Err(Error::BadConfig(
+                "max_concurrent_queries must be > 0".into(),
+            ));
+        }
+        if self.auto_pause_failure_threshold == 0 {
+            return Err(Error::BadConfig(
+                "auto_pause_failure_threshold must be > 0".into(),
+            ));
+        }
+        if self.auto_pause_success_threshold == 0 {
+            return Err(Error::BadConfig(
+                "auto_pause_success_threshold must be > 0".into(),
+            ));
+        }
+
+        // TCP_USER_TIMEOUT is only supported on Linux
+        #[cfg(not(target_os = "linux"))]
+        if self.server_tcp_user_timeout.is_some_and(|secs| secs > 0) {
+            warn!("server_tcp_user_timeout is only supported on Linux and will be ignored");
+        }
 
         // Validate scaling_warm_pool_ratio
         if let Some(ratio) = self.scaling_warm_pool_ratio {
@@ -414,6 +1020,14 @@ impl Pool {
             if aq.query.is_empty() {
                 return Err(Error::BadConfig("auth_query.query cannot be empty".into()));
             }
+            // The username is always bound as $1. A query without the
+            // placeholder either fails at runtime or returns the same row
+            // for every user.
+            if !references_first_parameter(&aq.query) {
+                return Err(Error::BadConfig(
+                    "auth_query.query must reference the username as $1".into(),
+                ));
+            }
             if aq.user.is_empty() {
                 return Err(Error::BadConfig("auth_query.user cannot be empty".into()));
             }
@@ -446,16 +1060,49 @@ impl Default for Pool {
             server_port: 5432,
             server_host: String::from("127.0.0.1"),
             server_database: None,
+            server_username: None,
+            server_password: None,
             connect_timeout: None,
             idle_timeout: None,
             server_lifetime: None,
+            client_idle_timeout: None,
+            max_query_duration: None,
+            max_result_rows: None,
+            server_max_age: None,
+            message_size_to_be_stream: None,
             cleanup_server_connections: true,
+            server_cleanup_mode: None,
+            server_cleanup_query: None,
+            connect_queries: Vec::new(),
+            server_statement_timeout_ms: None,
+            server_lock_timeout_ms: None,
+            default_client_statement_timeout_ms: None,
+            server_tcp_keepalives_idle: None,
+            server_tcp_keepalives_interval: None,
+            server_tcp_keepalives_count: None,
+            server_tcp_user_timeout: None,
+            max_concurrent_creates: None,
+            max_concurrent_queries: None,
+            auto_pause_on_unhealthy: false,
+            auto_pause_failure_threshold: Pool::default_auto_pause_failure_threshold(),
+            auto_pause_success_threshold: Pool::default_auto_pause_success_threshold(),
             log_client_parameter_status_changes: false,
+            log_startup_parameters: false,
+            share_server_connections: false,
+            read_only: false,
+            reject_write_queries: false,
+            retry_readonly_on_disconnect: false,
+            prefer_last_server: false,
+            allow_session_override: false,
+            scram_relay: false,
+            client_label_gucs: false,
             application_name: None,
+            prepared_statements: None,
             prepared_statements_cache_size: None,
             server_prepared_statements_cache_size: None,
             scaling_warm_pool_ratio: None,
             scaling_fast_retries: None,
+            scaling_shrink_step: None,
             max_db_connections: None,
             min_connection_lifetime: None,
             reserve_pool_size: None,
@@ -472,8 +1119,205 @@ impl Default for Pool {
             server_tls_private_key: None,
             auth_query: None,
             startup_parameters: std::collections::BTreeMap::new(),
+            ignore_startup_parameters: None,
+            parameter_status_overrides: std::collections::BTreeMap::new(),
+            shards: std::collections::BTreeMap::new(),
+            hosts: Vec::new(),
+        }
+    }
+}
+
+/// Backend a shard key routes to. Port and database default to the pool's.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ShardTarget {
+    pub server_host: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_port: Option<u16>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_database: Option<String>,
+}
+
+/// Another member of the pool's cluster. The port defaults to the pool's.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HostTarget {
+    pub server_host: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_port: Option<u16>,
+}
+
+/// Statements a dirty server connection runs at checkin:
+/// - reset_all: `RESET ALL`, `DEALLOCATE ALL`, `CLOSE ALL`, each only when
+///   the session tracker saw the matching state change,
+/// - discard_all: the `DISCARD ALL` set on every checkin, keeping cached
+///   prepared statements when the prepared statement cache is on,
+/// - custom: `server_cleanup_query` on dirty connections.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ServerCleanupMode {
+    DiscardAll,
+    #[default]
+    ResetAll,
+    Custom,
+}
+
+impl fmt::Display for ServerCleanupMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            ServerCleanupMode::DiscardAll => "discard_all",
+            ServerCleanupMode::ResetAll => "reset_all",
+            ServerCleanupMode::Custom => "custom",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Split `query` on semicolons outside quotes, identifiers and
+/// dollar-quoted bodies, dropping empty statements.
+fn split_statements(query: &str) -> Vec<&str> {
+    let bytes = query.as_bytes();
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'\'' | b'"') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    i += 1;
+                }
+            }
+            b'$' => {
+                let tag_len = bytes[i + 1..].iter().position(|&b| b == b'$').filter(|&n| {
+                    bytes[i + 1..i + 1 + n]
+                        .iter()
+                        .all(|&b| b.is_ascii_alphanumeric() || b == b'_')
+                });
+                if let Some(n) = tag_len {
+                    let tag = &query[i..i + n + 2];
+                    match query[i + tag.len()..].find(tag) {
+                        Some(end) => i += tag.len() + end + tag.len() - 1,
+                        None => i = bytes.len(),
+                    }
+                }
+            }
+            b';' => {
+                statements.push(&query[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    statements.push(&query[start.min(query.len())..]);
+    statements
+        .into_iter()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Whether `query` has a `$1` placeholder token outside quotes, identifiers,
+/// dollar-quoted bodies and comments. `$10` and up are other parameters.
+fn references_first_parameter(query: &str) -> bool {
+    let bytes = query.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'\'' | b'"') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    i += 1;
+                }
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => match query[i + 2..].find("*/") {
+                Some(end) => i += end + 3,
+                None => i = bytes.len(),
+            },
+            b'$' if bytes.get(i + 1).is_some_and(u8::is_ascii_digit) => {
+                let digits = bytes[i + 1..]
+                    .iter()
+                    .take_while(|b| b.is_ascii_digit())
+                    .count();
+                if query[i + 1..i + 1 + digits].parse() == Ok(1u32) {
+                    return true;
+                }
+                i += digits;
+            }
+            // An identifier may hold `$`, as in `a$1`; that is no placeholder.
+            c if c.is_ascii_alphabetic() || c == b'_' || c >= 0x80 => {
+                while bytes.get(i + 1).is_some_and(|&b| {
+                    b.is_ascii_alphanumeric() || b == b'_' || b == b'$' || b >= 0x80
+                }) {
+                    i += 1;
+                }
+            }
+            b'$' => {
+                let tag_len = bytes[i + 1..].iter().position(|&b| b == b'$').filter(|&n| {
+                    bytes[i + 1..i + 1 + n]
+                        .iter()
+                        .all(|&b| b.is_ascii_alphanumeric() || b == b'_')
+                });
+                if let Some(n) = tag_len {
+                    let tag = &query[i..i + n + 2];
+                    match query[i + tag.len()..].find(tag) {
+                        Some(end) => i += tag.len() + end + tag.len() - 1,
+                        None => i = bytes.len(),
+                    }
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    false
+}
+
+/// `server_cleanup_query` goes out as one simple query on a connection
+/// that must come back idle, so it cannot control the transaction, start
+/// COPY, or contain `DISCARD ALL`, which refuses to run in a
+/// multi-statement query (`DEALLOCATE ALL` may be appended to it).
+fn validate_server_cleanup_query(query: &str) -> Result<(), Error> {
+    if query.contains('\0') {
+        return Err(Error::BadConfig(
+            "server_cleanup_query cannot contain NUL bytes".into(),
+        ));
+    }
+    let statements = split_statements(query);
+    for statement in &statements {
+        let keyword = statement
+            .split(|c: char| !c.is_ascii_alphabetic())
+            .next()
+            .unwrap_or_default()
+            .to_ascii_uppercase();
+        match keyword.as_str() {
+            "BEGIN" | "START" | "COMMIT" | "END" | "ROLLBACK" | "ABORT" | "SAVEPOINT"
+            | "RELEASE" => {
+                return Err(Error::BadConfig(format!(
+                    "server_cleanup_query cannot contain transaction control: {statement}"
+                )));
+            }
+            "COPY" => {
+                return Err(Error::BadConfig(format!(
+                    "server_cleanup_query cannot contain COPY: {statement}"
+                )));
+            }
+            _ => {}
+        }
+        if crate::server::cleanup::is_discard_all(statement) {
+            return Err(Error::BadConfig(
+                "server_cleanup_query cannot contain DISCARD ALL; use server_cleanup_mode = discard_all".into(),
+            ));
         }
     }
+    Ok(())
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
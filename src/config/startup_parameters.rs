@@ -81,6 +81,36 @@ pub fn validate(map: &BTreeMap<String, String>, scope: &str) -> Result<(), Error
     validate_total_size(map, scope)
 }
 
+/// Validate an `ignore_startup_parameters` list: every entry must be a
+/// GUC name the client could send in its StartupMessage.
+pub fn validate_ignore_list(names: &[String], scope: &str) -> Result<(), Error> {
+    for name in names {
+        if !is_valid_guc_name(name) {
+            return Err(Error::BadConfig(format!(
+                "{scope}: '{name}' is not a valid parameter name"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Validate a `parameter_status_overrides` map. Names only need to look
+/// like GUC names: they are reported to clients, never sent to a backend.
+pub fn validate_parameter_status_overrides(
+    map: &BTreeMap<String, String>,
+    scope: &str,
+) -> Result<(), Error> {
+    for (key, value) in map {
+        if !is_valid_guc_name(key) {
+            return Err(Error::BadConfig(format!(
+                "{scope}: '{key}' is not a valid parameter name"
+            )));
+        }
+        validate_value(key, value, scope)?;
+    }
+    Ok(())
+}
+
 /// Validate a single borrowed `(key, value)` pair the same way [`validate`]
 /// would. Used by the auth_query JSON parser to check entries inline
 /// without building a one-element `BTreeMap` for each one. The total-size
@@ -235,6 +265,18 @@ mod tests {
             .collect()
     }
 
+    #[test]
+    fn ignore_list_accepts_guc_names_only() {
+        let names = vec!["extra_float_digits".to_string(), "DateStyle".to_string()];
+        assert!(validate_ignore_list(&names, "general.ignore_startup_parameters").is_ok());
+
+        let bad = vec!["extra_float_digits; SET x".to_string()];
+        let err = validate_ignore_list(&bad, "general.ignore_startup_parameters").unwrap_err();
+        assert!(
+            matches!(err, Error::BadConfig(ref msg) if msg.contains("not a valid parameter name"))
+        );
+    }
+
     #[test]
     fn empty_map_is_valid() {
         assert!(validate(&BTreeMap::new(), "general.startup_parameters").is_ok());
@@ -50,6 +50,64 @@ server_port = 5432
     temp_file
 }
 
+#[tokio::test]
+async fn check_accepts_valid_and_reports_invalid_config() {
+    let temp_file = create_temp_config();
+    let config = check(temp_file.path().to_str().unwrap()).await.unwrap();
+    assert_eq!(config.pools.len(), 4);
+
+    let mut broken = NamedTempFile::with_suffix(".toml").unwrap();
+    broken
+        .write_all(
+            br#"
+[general]
+host = "127.0.0.1"
+port = 6432
+admin_username = "admin"
+admin_password = "admin_password"
+tls_private_key = "key.pem"
+"#,
+        )
+        .unwrap();
+    broken.flush().unwrap();
+    match check(broken.path().to_str().unwrap()).await {
+        Err(Error::BadConfig(msg)) => assert!(msg.contains("tls_private_key"), "{msg}"),
+        other => panic!("expected BadConfig, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn effective_toml_redacts_passwords() {
+    let temp_file = create_temp_config();
+    let mut config = check(temp_file.path().to_str().unwrap()).await.unwrap();
+    config.pools.get_mut("test_db1").unwrap().server_password = Some("pool_secret".into());
+
+    let effective = config.effective_toml().unwrap();
+    for secret in [
+        "admin_password\"",
+        "password1",
+        "SCRAM-SHA-256",
+        "pool_secret",
+    ] {
+        assert!(!effective.contains(secret), "{secret} leaked:\n{effective}");
+    }
+
+    let value: toml::Value = toml::from_str(&effective).unwrap();
+    assert_eq!(value["general"]["admin_password"].as_str(), Some("***"));
+    assert_eq!(
+        value["pools"]["example_db"]["users"][0]["password"].as_str(),
+        Some("***")
+    );
+    assert_eq!(
+        value["pools"]["test_db1"]["server_password"].as_str(),
+        Some("***")
+    );
+    assert_eq!(
+        value["pools"]["example_db"]["users"][0]["username"].as_str(),
+        Some("example_user_1")
+    );
+}
+
 #[tokio::test]
 #[serial]
 async fn test_config() {
@@ -189,6 +247,39 @@ async fn test_validate_prepared_statements_no_cache() {
     }
 }
 
+#[tokio::test]
+async fn test_validate_pool_prepared_statements_override() {
+    let mut config = Config::default();
+    config.general.prepared_statements = false;
+    config.general.prepared_statements_cache_size = 0;
+    config.pools.insert("disabled".to_string(), Pool::default());
+    assert!(config.validate().await.is_ok());
+
+    let pool = Pool {
+        prepared_statements: Some(true),
+        ..Pool::default()
+    };
+    assert!(pool.resolve_prepared_statements(&config.general));
+    config.pools.insert("enabled".to_string(), pool);
+    let result = config.validate().await;
+    assert!(matches!(result, Err(Error::BadConfig(msg)) if msg.contains("pool enabled")));
+
+    config
+        .pools
+        .get_mut("enabled")
+        .unwrap()
+        .prepared_statements_cache_size = Some(512);
+    assert!(config.validate().await.is_ok());
+
+    config.general.prepared_statements = true;
+    config.general.prepared_statements_cache_size = 1024;
+    let pool = Pool {
+        prepared_statements: Some(false),
+        ..Pool::default()
+    };
+    assert!(!pool.resolve_prepared_statements(&config.general));
+}
+
 // Test tls_certificate set but tls_private_key not set
 #[tokio::test]
 async fn test_validate_tls_certificate_without_private_key() {
@@ -268,6 +359,58 @@ async fn test_validate_tls_mode_verify_full_without_ca_cert() {
     }
 }
 
+// verify-ca checks the client certificate too, so it needs the CA as well
+#[tokio::test]
+async fn test_validate_tls_mode_verify_ca_without_ca_cert() {
+    let mut config = Config::default();
+    config.general.tls_mode = Some("verify-ca".to_string());
+    config.general.tls_certificate = Some("cert.pem".to_string());
+    config.general.tls_private_key = Some("key.pem".to_string());
+    config.general.tls_ca_cert = None;
+
+    match config.validate().await {
+        Err(Error::BadConfig(msg)) => {
+            assert!(
+                msg.contains("tls_mode is verify-ca but tls_ca_cert is not set"),
+                "{msg}"
+            )
+        }
+        other => panic!("expected BadConfig, got {other:?}"),
+    }
+}
+
+// Test tls_ciphers combined with a TLS 1.3 floor
+#[tokio::test]
+async fn test_validate_tls_ciphers_with_tls13_floor() {
+    let mut config = Config::default();
+
+    config.general.tls_min_protocol_version = Some("1.3".to_string());
+    config.general.tls_ciphers = Some("ECDHE-RSA-AES256-GCM-SHA384".to_string());
+
+    let result = config.validate().await;
+    if let Err(Error::BadConfig(msg)) = result {
+        assert!(msg.contains("tls_ciphers applies to TLS 1.2 only"));
+    } else {
+        panic!("Expected BadConfig error about tls_ciphers with TLS 1.3");
+    }
+}
+
+// Test unknown tls_min_protocol_version and unknown cipher names
+#[tokio::test]
+async fn test_validate_tls_min_protocol_version_and_ciphers() {
+    let mut config = Config::default();
+
+    config.general.tls_min_protocol_version = Some("1.0".to_string());
+    assert!(config.validate().await.is_err());
+
+    config.general.tls_min_protocol_version = Some("TLSv1.2".to_string());
+    config.general.tls_ciphers = Some("NOT-A-CIPHER".to_string());
+    assert!(config.validate().await.is_err());
+
+    config.general.tls_ciphers = Some("ECDHE-RSA-AES256-GCM-SHA384".to_string());
+    assert!(config.validate().await.is_ok());
+}
+
 // Test valid TLS configuration with mode "allow"
 #[tokio::test]
 async fn test_validate_valid_tls_mode_allow() {
@@ -463,6 +606,53 @@ include:
     assert!(result.include.files.is_empty());
 }
 
+#[tokio::test]
+async fn test_include_dirs_merge_sorted_config_files() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("20-billing.toml"),
+        "[pools.billing]\nserver_host = \"localhost\"\nserver_port = 5432\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("10-orders.yaml"),
+        "pools:\n  orders:\n    server_host: \"localhost\"\n    server_port: 5432\n",
+    )
+    .unwrap();
+    std::fs::write(dir.path().join("README.md"), "not a config").unwrap();
+    std::fs::create_dir(dir.path().join("disabled.toml")).unwrap();
+
+    let dir_path = dir.path().to_str().unwrap();
+    let files = include_dir_files(dir_path).unwrap();
+    let names: Vec<&str> = files
+        .iter()
+        .map(|file| file.rsplit('/').next().unwrap())
+        .collect();
+    assert_eq!(names, vec!["10-orders.yaml", "20-billing.toml"]);
+
+    let main = format!(
+        r#"
+[include]
+dirs = ["{dir_path}"]
+
+[general]
+host = "127.0.0.1"
+port = 6432
+admin_username = "admin"
+admin_password = "admin_password"
+"#
+    );
+    let mut main_file = NamedTempFile::with_suffix(".toml").unwrap();
+    main_file.write_all(main.as_bytes()).unwrap();
+    main_file.flush().unwrap();
+
+    let config = load(main_file.path().to_str().unwrap()).await.unwrap();
+    assert!(config.pools.contains_key("orders"));
+    assert!(config.pools.contains_key("billing"));
+
+    assert!(include_dir_files("/nonexistent/pg_doorman/pools.d").is_err());
+}
+
 // ============================================================================
 // TOML Backward Compatibility Tests
 // ============================================================================
@@ -1024,6 +1214,71 @@ async fn test_auth_query_validate_empty_query() {
     }
 }
 
+/// Validation: query without the $1 username placeholder produces error
+#[tokio::test]
+async fn test_auth_query_validate_query_without_placeholder() {
+    let mut pool = Pool::default();
+    pool.auth_query = Some(pool::AuthQueryConfig {
+        query: "SELECT usename, passwd FROM pg_shadow".to_string(),
+        user: "pg_doorman_auth".to_string(),
+        password: "secret".to_string(),
+        database: None,
+        workers: 2,
+        server_user: None,
+        server_password: None,
+        pool_size: 40,
+        min_pool_size: 0,
+        cache_ttl: Duration::from_hours(1),
+        cache_failure_ttl: Duration::from_secs(30),
+        min_interval: Duration::from_secs(1),
+    });
+
+    let result = pool.validate().await;
+    assert!(result.is_err());
+    if let Err(Error::BadConfig(msg)) = result {
+        assert!(msg.contains("must reference the username as $1"));
+    }
+}
+
+/// Validation: `$10`, quoted `$1` and `$1` in comments are not the placeholder
+#[tokio::test]
+async fn test_auth_query_validate_placeholder_tokens() {
+    for (query, valid) in [
+        ("SELECT * FROM pg_shadow WHERE usename = $1", true),
+        ("SELECT * FROM pg_shadow WHERE usename = $1::text", true),
+        ("SELECT * FROM pg_shadow WHERE usename = $10", false),
+        ("SELECT * FROM pg_shadow WHERE usename = '$1'", false),
+        ("SELECT $$ $1 $$ FROM pg_shadow", false),
+        ("SELECT * FROM pg_shadow -- WHERE usename = $1", false),
+        ("SELECT /* $1 */ * FROM pg_shadow", false),
+    ] {
+        let mut pool = Pool::default();
+        pool.auth_query = Some(pool::AuthQueryConfig {
+            query: query.to_string(),
+            user: "pg_doorman_auth".to_string(),
+            password: "secret".to_string(),
+            database: None,
+            workers: 2,
+            server_user: None,
+            server_password: None,
+            pool_size: 40,
+            min_pool_size: 0,
+            cache_ttl: Duration::from_hours(1),
+            cache_failure_ttl: Duration::from_secs(30),
+            min_interval: Duration::from_secs(1),
+        });
+        let result = pool.validate().await;
+        match result {
+            Err(Error::BadConfig(msg)) => {
+                assert!(!valid, "{query}: {msg}");
+                assert!(msg.contains("must reference the username as $1"), "{msg}");
+            }
+            Err(e) => panic!("{query}: {e:?}"),
+            Ok(()) => assert!(valid, "{query}"),
+        }
+    }
+}
+
 /// Validation: empty user produces error
 #[tokio::test]
 async fn test_auth_query_validate_empty_user() {
@@ -1249,10 +1504,12 @@ pools:
     assert_eq!(config.general.scaling_warm_pool_ratio, 20);
     assert_eq!(config.general.scaling_fast_retries, 10);
     assert_eq!(config.general.scaling_max_parallel_creates, 2);
+    assert_eq!(config.general.scaling_shrink_step, 0);
     // Pool-level should be None
     let pool = &config.pools["mydb"];
     assert_eq!(pool.scaling_warm_pool_ratio, None);
     assert_eq!(pool.scaling_fast_retries, None);
+    assert_eq!(pool.scaling_shrink_step, None);
 }
 
 /// Test 3: Pool-level override parsing
@@ -1308,9 +1565,11 @@ async fn test_resolve_scaling_config_pool_override() {
     general.scaling_warm_pool_ratio = 20;
     general.scaling_fast_retries = 10;
     general.scaling_max_parallel_creates = 2;
+    general.scaling_shrink_step = 0;
 
     let pool = Pool {
         scaling_warm_pool_ratio: Some(50),
+        scaling_shrink_step: Some(3),
         ..Pool::default()
     };
 
@@ -1318,6 +1577,7 @@ async fn test_resolve_scaling_config_pool_override() {
     assert!((scaling.warm_pool_ratio - 0.5).abs() < f32::EPSILON);
     assert_eq!(scaling.fast_retries, 10); // general default
     assert_eq!(scaling.max_parallel_creates, 2); // global only
+    assert_eq!(scaling.shrink_step, 3);
 }
 
 /// Test 5: resolve_scaling_config() — general fallback
@@ -1327,6 +1587,7 @@ async fn test_resolve_scaling_config_general_fallback() {
     general.scaling_warm_pool_ratio = 30;
     general.scaling_fast_retries = 15;
     general.scaling_max_parallel_creates = 3;
+    general.scaling_shrink_step = 4;
 
     let pool = Pool::default(); // all scaling fields are None
 
@@ -1334,6 +1595,7 @@ async fn test_resolve_scaling_config_general_fallback() {
     assert!((scaling.warm_pool_ratio - 0.3).abs() < f32::EPSILON);
     assert_eq!(scaling.fast_retries, 15);
     assert_eq!(scaling.max_parallel_creates, 3);
+    assert_eq!(scaling.shrink_step, 4);
 }
 
 /// Test 5b: Validation rejects max_parallel_creates = 0 (would deadlock create path)
@@ -1364,6 +1626,26 @@ async fn test_validate_scaling_warm_pool_ratio_general_out_of_range() {
     }
 }
 
+#[tokio::test]
+async fn buffer_pool_initial_capacity_must_fit_max_buffer_size() {
+    let general: General = serde_yaml::from_str(
+        "admin_username: admin\nadmin_password: admin\nbuffer_pool_initial_capacity: 16KB\n",
+    )
+    .unwrap();
+    assert_eq!(general.buffer_pool_initial_capacity, ByteSize::from_kb(16));
+    assert_eq!(general.buffer_pool_max_retained, 512);
+    assert_eq!(general.buffer_pool_max_buffer_size, ByteSize::from_kb(32));
+
+    let mut config = Config::default();
+    config.general.buffer_pool_initial_capacity = ByteSize::from_kb(64);
+    match config.validate().await {
+        Err(Error::BadConfig(msg)) => {
+            assert!(msg.contains("buffer_pool_initial_capacity"), "{msg}")
+        }
+        other => panic!("expected BadConfig, got {other:?}"),
+    }
+}
+
 /// Test 7: Validation — pool warm_pool_ratio > 100
 #[tokio::test]
 async fn test_validate_scaling_warm_pool_ratio_pool_out_of_range() {
@@ -1764,6 +2046,82 @@ fn legacy_hba_bypass_quiet_when_pg_hba_present() {
     assert!(!legacy_hba_bypassed_by_unix_socket(&general));
 }
 
+// ---- hostssl/hostnossl against the client-facing TLS settings ----
+
+fn tls_transport(ip: &str) -> ClientTransport {
+    let peer = std::net::SocketAddr::new(ip.parse().unwrap(), 12345);
+    ClientTransport::Tcp { peer, ssl: true }
+}
+
+#[test]
+fn check_hba_ssl_rules_follow_client_transport() {
+    // The ssl flag describes the client-to-pooler leg; server_tls_mode
+    // (the pooler-to-backend leg) must not influence the decision.
+    use crate::auth::hba::PgHba;
+    let mut general = General::default();
+    general.server_tls_mode = "require".to_string();
+    general.pg_hba = Some(PgHba::from_content(
+        "hostssl all all 10.0.0.0/8 trust\nhostnossl all all 10.0.0.0/8 reject",
+    ));
+
+    assert_eq!(
+        check_hba_with_general(&general, &tls_transport("10.0.0.5"), "md5", "alice", "app"),
+        CheckResult::Trust
+    );
+    assert_eq!(
+        check_hba_with_general(&general, &tcp_transport("10.0.0.5"), "md5", "alice", "app"),
+        CheckResult::Deny
+    );
+    assert_eq!(
+        check_hba_with_general(&general, &ClientTransport::Unix, "md5", "alice", "app"),
+        CheckResult::NotMatched
+    );
+}
+
+#[test]
+fn unreachable_ssl_rules_without_tls_certificate() {
+    use crate::auth::hba::PgHba;
+    let mut general = General::default();
+    general.pg_hba = Some(PgHba::from_content(
+        "hostssl all all 0.0.0.0/0 scram-sha-256\nhostnossl all all 0.0.0.0/0 md5\nhost all all 0.0.0.0/0 reject",
+    ));
+    assert_eq!(
+        unreachable_ssl_hba_rules(&general),
+        vec!["hostssl all all 0.0.0.0/0 scram-sha-256".to_string()]
+    );
+
+    // tls_mode = allow with a certificate: both kinds of clients reach HBA.
+    general.tls_mode = Some("allow".to_string());
+    general.tls_certificate = Some("server.crt".to_string());
+    assert!(unreachable_ssl_hba_rules(&general).is_empty());
+}
+
+#[test]
+fn unreachable_nossl_rules_when_tls_required() {
+    use crate::auth::hba::PgHba;
+    let mut general = General::default();
+    general.tls_certificate = Some("server.crt".to_string());
+    general.pg_hba = Some(PgHba::from_content(
+        "hostssl all all 0.0.0.0/0 scram-sha-256\nhostnossl all all 0.0.0.0/0 reject",
+    ));
+    for mode in ["require", "verify-ca", "verify-full"] {
+        general.tls_mode = Some(mode.to_string());
+        assert_eq!(
+            unreachable_ssl_hba_rules(&general),
+            vec!["hostnossl all all 0.0.0.0/0 reject".to_string()],
+            "tls_mode = {mode}"
+        );
+    }
+}
+
+#[test]
+fn unreachable_ssl_rules_ignore_legacy_hba() {
+    let mut general = General::default();
+    general.hba = vec!["10.0.0.0/8".parse().unwrap()];
+    general.tls_mode = Some("require".to_string());
+    assert!(unreachable_ssl_hba_rules(&general).is_empty());
+}
+
 #[test]
 fn deprecated_general_keys_yaml_detects_old_field_under_general() {
     let yaml = r#"
@@ -1825,6 +2183,34 @@ client_anonymous_prepared_cache_size = 2048
     assert!(found.is_empty());
 }
 
+#[test]
+fn deprecated_pool_keys_are_detected_in_any_pool() {
+    let yaml = r#"
+pools:
+  app:
+    server_host: "127.0.0.1"
+  legacy:
+    server_host: "127.0.0.1"
+    server_reset_query: "DISCARD ALL"
+"#;
+    let value: serde_yaml::Value = serde_yaml::from_str(yaml).unwrap();
+    assert_eq!(
+        find_deprecated_pool_keys_yaml(&value),
+        vec!["server_reset_query"]
+    );
+
+    let toml_input = r#"
+[pools.legacy]
+server_host = "127.0.0.1"
+server_reset_query = ""
+"#;
+    let value: toml::Value = toml_input.parse().unwrap();
+    assert_eq!(
+        find_deprecated_pool_keys_toml(&value),
+        vec!["server_reset_query"]
+    );
+}
+
 #[tokio::test]
 #[serial]
 async fn test_config_web_section() {
@@ -1881,6 +2267,7 @@ admin_password = "admin_password"
 enabled = true
 host = "127.0.0.1"
 port = 9128
+unix_socket = "/var/run/pg_doorman/web.sock"
 
 [pools.example_db]
 server_host = "localhost"
@@ -1901,6 +2288,10 @@ pool_size = 5
     assert!(cfg.web.enabled);
     assert_eq!(cfg.web.host, "127.0.0.1");
     assert_eq!(cfg.web.port, 9128);
+    assert_eq!(
+        cfg.web.unix_socket.as_deref(),
+        Some(std::path::Path::new("/var/run/pg_doorman/web.sock"))
+    );
     // New-field defaults are preserved when the legacy [prometheus] alias is used.
     assert!(!cfg.web.ui);
     assert!(!cfg.web.ui_anonymous);
@@ -2102,3 +2493,927 @@ async fn reject_merged_general_pool_startup_parameters_overflow() {
         other => panic!("expected BadConfig, got {other:?}"),
     }
 }
+
+#[test]
+fn pool_client_idle_timeout_defaults_to_disabled() {
+    let pool: Pool = serde_yaml::from_str("server_host: \"localhost\"\n").unwrap();
+    assert_eq!(pool.client_idle_timeout, None);
+
+    let pool: Pool =
+        serde_yaml::from_str("server_host: \"localhost\"\nclient_idle_timeout: 60000\n").unwrap();
+    assert_eq!(pool.client_idle_timeout, Some(60000));
+}
+
+#[test]
+fn pool_max_query_duration_defaults_to_disabled() {
+    let pool: Pool = serde_yaml::from_str("server_host: \"localhost\"\n").unwrap();
+    assert_eq!(pool.max_query_duration, None);
+
+    let pool: Pool =
+        serde_yaml::from_str("server_host: \"localhost\"\nmax_query_duration: 5000\n").unwrap();
+    assert_eq!(pool.max_query_duration, Some(5000));
+}
+
+#[test]
+fn pool_max_result_rows_defaults_to_disabled() {
+    let pool: Pool = serde_yaml::from_str("server_host: \"localhost\"\n").unwrap();
+    assert_eq!(pool.max_result_rows, None);
+
+    let pool: Pool =
+        serde_yaml::from_str("server_host: \"localhost\"\nmax_result_rows: 100000\n").unwrap();
+    assert_eq!(pool.max_result_rows, Some(100000));
+}
+
+#[test]
+fn pool_retry_readonly_on_disconnect_defaults_to_off() {
+    let pool: Pool = serde_yaml::from_str("server_host: \"localhost\"\n").unwrap();
+    assert!(!pool.retry_readonly_on_disconnect);
+
+    let pool: Pool =
+        serde_yaml::from_str("server_host: \"localhost\"\nretry_readonly_on_disconnect: true\n")
+            .unwrap();
+    assert!(pool.retry_readonly_on_disconnect);
+}
+
+#[test]
+fn pool_prefer_last_server_defaults_to_off() {
+    let pool: Pool = serde_yaml::from_str("server_host: \"localhost\"\n").unwrap();
+    assert!(!pool.prefer_last_server);
+
+    let pool: Pool =
+        serde_yaml::from_str("server_host: \"localhost\"\nprefer_last_server: true\n").unwrap();
+    assert!(pool.prefer_last_server);
+}
+
+#[test]
+fn pool_server_max_age_defaults_to_disabled() {
+    let pool: Pool = serde_yaml::from_str("server_host: \"localhost\"\n").unwrap();
+    assert_eq!(pool.server_max_age, None);
+
+    let pool: Pool =
+        serde_yaml::from_str("server_host: \"localhost\"\nserver_max_age: 3600000\n").unwrap();
+    assert_eq!(pool.server_max_age, Some(3600000));
+}
+
+#[test]
+fn pool_ignore_startup_parameters_replaces_general_list() {
+    let mut general = General::default();
+    general.ignore_startup_parameters = vec!["extra_float_digits".to_string()];
+
+    let mut pool = Pool::default();
+    assert_eq!(
+        pool.resolve_ignore_startup_parameters(&general),
+        ["extra_float_digits".to_string()]
+    );
+
+    pool.ignore_startup_parameters = Some(vec!["DateStyle".to_string()]);
+    assert_eq!(
+        pool.resolve_ignore_startup_parameters(&general),
+        ["DateStyle".to_string()]
+    );
+
+    pool.ignore_startup_parameters = Some(Vec::new());
+    assert!(pool.resolve_ignore_startup_parameters(&general).is_empty());
+}
+
+#[tokio::test]
+async fn pool_server_database_renames_backend_database() {
+    let mut pool = Pool::default();
+    assert_eq!(pool.resolve_server_database("reports"), "reports");
+
+    pool.server_database = Some("analytics_ro".to_string());
+    assert_eq!(pool.resolve_server_database("reports"), "analytics_ro");
+    assert!(pool.validate().await.is_ok());
+
+    pool.server_database = Some(String::new());
+    assert!(pool.validate().await.is_err());
+}
+
+#[tokio::test]
+async fn test_admin_database_name() {
+    let mut config = Config::default();
+    assert!(config.general.is_admin_database("pgdoorman"));
+    assert!(config.general.is_admin_database("pgbouncer"));
+
+    config.general.admin_database_name = "console".to_string();
+    assert!(config.general.is_admin_database("console"));
+    assert!(config.general.is_admin_database("pgbouncer"));
+    assert!(!config.general.is_admin_database("pgdoorman"));
+    assert!(config.validate().await.is_ok());
+
+    config.pools.insert("console".to_string(), Pool::default());
+    match config.validate().await {
+        Err(Error::BadConfig(msg)) => assert!(msg.contains("admin_database_name")),
+        other => panic!("Expected BadConfig about admin database name, got {other:?}"),
+    }
+
+    config.pools.clear();
+    config.general.admin_database_name = String::new();
+    assert!(config.validate().await.is_err());
+}
+
+#[tokio::test]
+async fn pool_shards_get_their_own_backends() {
+    let mut pool: Pool = serde_yaml::from_str(
+        "server_host: \"10.0.0.1\"\nserver_database: \"app\"\nshards:\n  \"1\":\n    server_host: \"10.0.1.1\"\n  \"2\":\n    server_host: \"10.0.2.1\"\n    server_port: 6543\n    server_database: \"app_2\"\n",
+    )
+    .unwrap();
+    assert!(pool.validate().await.is_ok());
+
+    let backends = pool.backends("orders");
+    assert_eq!(backends.len(), 3);
+    assert_eq!(backends[0].pool_key, "orders");
+    assert_eq!(backends[0].shard, None);
+    assert_eq!(
+        (
+            backends[1].pool_key.as_str(),
+            backends[1].host,
+            backends[1].port
+        ),
+        ("orders/1", "10.0.1.1", 5432)
+    );
+    assert_eq!(backends[1].server_database, "app");
+    assert_eq!(
+        (
+            backends[2].host,
+            backends[2].port,
+            backends[2].server_database
+        ),
+        ("10.0.2.1", 6543, "app_2")
+    );
+
+    pool.shards.get_mut("1").unwrap().server_host = String::new();
+    assert!(pool.validate().await.is_err());
+}
+
+#[tokio::test]
+async fn slash_in_pool_name_or_shard_key_is_rejected() {
+    let mut pool: Pool = serde_yaml::from_str(
+        "server_host: \"10.0.0.1\"\nshards:\n  \"1/a\":\n    server_host: \"10.0.1.1\"\n",
+    )
+    .unwrap();
+    match pool.validate().await {
+        Err(Error::BadConfig(msg)) => assert!(msg.contains("cannot contain '/'"), "{msg}"),
+        other => panic!("Expected BadConfig about the shard key, got {other:?}"),
+    }
+
+    let sharded: Pool = serde_yaml::from_str(
+        "server_host: \"10.0.0.1\"\nshards:\n  \"1\":\n    server_host: \"10.0.1.1\"\n",
+    )
+    .unwrap();
+    let mut config = Config::default();
+    config
+        .pools
+        .insert("orders/eu".to_string(), sharded.clone());
+    match config.validate().await {
+        Err(Error::BadConfig(msg)) => assert!(msg.contains("\"orders/eu\""), "{msg}"),
+        other => panic!("Expected BadConfig about the pool name, got {other:?}"),
+    }
+
+    let mut config = Config::default();
+    config.pools.insert("orders".to_string(), sharded);
+    config.pools.insert("orders/1".to_string(), Pool::default());
+    match config.validate().await {
+        Err(Error::BadConfig(msg)) => assert!(msg.contains("clashes with shard"), "{msg}"),
+        other => panic!("Expected BadConfig about the clashing pool, got {other:?}"),
+    }
+
+    let mut config = Config::default();
+    config
+        .pools
+        .insert("reports/eu".to_string(), Pool::default());
+    assert!(config.validate().await.is_ok());
+}
+
+#[tokio::test]
+async fn scram_relay_requires_session_mode_and_no_credentials() {
+    let relay_pool = |user: User| Pool {
+        pool_mode: PoolMode::Session,
+        scram_relay: true,
+        users: vec![user],
+        ..Default::default()
+    };
+    let user = User {
+        username: "app".to_string(),
+        pool_size: 10,
+        ..Default::default()
+    };
+    relay_pool(user.clone()).validate().await.unwrap();
+
+    let mut pool = relay_pool(user.clone());
+    pool.pool_mode = PoolMode::Transaction;
+    match pool.validate().await {
+        Err(Error::BadConfig(msg)) => assert!(msg.contains("session pool mode"), "{msg}"),
+        other => panic!("Expected BadConfig about the pool mode, got {other:?}"),
+    }
+
+    for user in [
+        User {
+            password: "md5abc".to_string(),
+            ..user.clone()
+        },
+        User {
+            server_username: Some("app".to_string()),
+            server_password: Some("secret".to_string()),
+            ..user.clone()
+        },
+    ] {
+        match relay_pool(user).validate().await {
+            Err(Error::BadConfig(msg)) => assert!(msg.contains("cannot set password"), "{msg}"),
+            other => panic!("Expected BadConfig about credentials, got {other:?}"),
+        }
+    }
+
+    match relay_pool(User {
+        min_pool_size: Some(1),
+        ..user
+    })
+    .validate()
+    .await
+    {
+        Err(Error::BadConfig(msg)) => assert!(msg.contains("min_pool_size"), "{msg}"),
+        other => panic!("Expected BadConfig about min_pool_size, got {other:?}"),
+    }
+}
+
+#[test]
+fn pool_shared_server_key_requires_transaction_mode_and_server_creds() {
+    let user = |name: &str, server: Option<&str>, pool_size: u32| User {
+        username: name.to_string(),
+        pool_size,
+        server_username: server.map(str::to_string),
+        server_password: server.map(|_| "secret".to_string()),
+        ..Default::default()
+    };
+    let mut pool = Pool {
+        users: vec![
+            user("app_1", Some("app"), 10),
+            user("app_2", Some("app"), 25),
+            user("reporting", Some("reports"), 5),
+            user("passthrough", None, 40),
+        ],
+        ..Default::default()
+    };
+    assert_eq!(pool.shared_server_key(&pool.users[0]), None);
+
+    pool.share_server_connections = true;
+    assert_eq!(
+        pool.shared_server_key(&pool.users[0]),
+        Some(("app", "secret"))
+    );
+    assert_eq!(pool.shared_server_key(&pool.users[3]), None);
+    assert_eq!(pool.shared_pool_size(("app", "secret")), 25);
+    assert_eq!(pool.shared_pool_size(("reports", "secret")), 5);
+    assert!(!pool.shared_pool_prioritized(("app", "secret")));
+    pool.users[1].priority = Some(10);
+    assert!(pool.shared_pool_prioritized(("app", "secret")));
+    assert!(!pool.shared_pool_prioritized(("reports", "secret")));
+
+    pool.users[1].pool_mode = Some(PoolMode::Session);
+    assert!(!pool.shared_pool_prioritized(("app", "secret")));
+    assert_eq!(pool.shared_server_key(&pool.users[1]), None);
+    assert_eq!(pool.shared_pool_size(("app", "secret")), 10);
+}
+
+#[tokio::test]
+async fn pool_server_credentials_fill_in_users_without_their_own() {
+    let mut pool = Pool {
+        server_username: Some("app_service".to_string()),
+        server_password: Some("service_secret".to_string()),
+        users: vec![
+            User {
+                username: "alice".to_string(),
+                ..Default::default()
+            },
+            User {
+                username: "bob".to_string(),
+                server_username: Some("bob_backend".to_string()),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+
+    let alice = pool.resolve_user(&pool.users[0]);
+    assert_eq!(alice.server_username.as_deref(), Some("app_service"));
+    assert_eq!(alice.server_password.as_deref(), Some("service_secret"));
+    // A user with its own server_username keeps it, trust auth included.
+    let bob = pool.resolve_user(&pool.users[1]);
+    assert_eq!(bob.server_username.as_deref(), Some("bob_backend"));
+    assert_eq!(bob.server_password, None);
+
+    pool.share_server_connections = true;
+    assert_eq!(
+        pool.shared_server_key(&pool.users[0]),
+        Some(("app_service", "service_secret"))
+    );
+    assert_eq!(pool.shared_server_key(&pool.users[1]), None);
+
+    pool.server_username = None;
+    assert!(pool.validate().await.is_err());
+}
+
+#[tokio::test]
+async fn pool_read_only_forces_default_transaction_read_only() {
+    let mut general = General::default();
+    general.startup_parameters.insert(
+        "default_transaction_read_only".to_string(),
+        "off".to_string(),
+    );
+    let mut pool = Pool::default();
+    assert_eq!(
+        pool.resolve_startup_parameters(&general)
+            .get("default_transaction_read_only")
+            .map(String::as_str),
+        Some("off")
+    );
+
+    pool.read_only = true;
+    assert_eq!(
+        pool.resolve_startup_parameters(&general)
+            .get("default_transaction_read_only")
+            .map(String::as_str),
+        Some("on")
+    );
+
+    pool.read_only = false;
+    pool.reject_write_queries = true;
+    match pool.validate().await {
+        Err(Error::BadConfig(msg)) => assert!(msg.contains("read_only"), "{msg}"),
+        other => panic!("expected BadConfig, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn pool_hosts_get_their_own_backends() {
+    let mut pool: Pool = serde_yaml::from_str(
+        "server_host: \"10.0.0.1\"\nhosts:\n  - server_host: \"10.0.0.2\"\n  - server_host: \"10.0.0.3\"\n    server_port: 6432\n",
+    )
+    .unwrap();
+    assert!(pool.validate().await.is_ok());
+
+    let backends = pool.backends("orders");
+    assert_eq!(backends.len(), 3);
+    assert!(!backends[0].cluster_host);
+    assert_eq!(
+        (
+            backends[2].pool_key.as_str(),
+            backends[2].host,
+            backends[2].port,
+            backends[2].cluster_host
+        ),
+        ("orders@10.0.0.3:6432", "10.0.0.3", 6432, true)
+    );
+    assert_eq!(backends[1].server_database, "orders");
+
+    pool.hosts[1].server_host = "10.0.0.1".to_string();
+    pool.hosts[1].server_port = None;
+    assert!(pool.validate().await.is_err());
+}
+
+#[test]
+fn pool_message_size_to_be_stream_override() {
+    let pool: Pool = serde_yaml::from_str("server_host: \"localhost\"\n").unwrap();
+    assert_eq!(pool.message_size_to_be_stream, None);
+
+    let pool: Pool =
+        serde_yaml::from_str("server_host: \"localhost\"\nmessage_size_to_be_stream: \"64KB\"\n")
+            .unwrap();
+    assert_eq!(pool.message_size_to_be_stream, Some(ByteSize(64 * 1024)));
+}
+
+/// Validation: custom cleanup mode needs a query, and a query needs custom mode
+#[tokio::test]
+async fn test_server_cleanup_mode_validation() {
+    let mut pool = Pool {
+        server_cleanup_mode: Some(ServerCleanupMode::Custom),
+        ..Pool::default()
+    };
+    assert!(pool.validate().await.is_err());
+
+    pool.server_cleanup_query = Some("RESET ALL; CLOSE ALL".to_string());
+    assert!(pool.validate().await.is_ok());
+
+    pool.server_cleanup_mode = Some(ServerCleanupMode::DiscardAll);
+    assert!(pool.validate().await.is_err());
+
+    pool.server_cleanup_query = None;
+    assert!(pool.validate().await.is_ok());
+
+    let parsed: Pool =
+        serde_yaml::from_str("server_host: \"localhost\"\nserver_cleanup_mode: discard_all\n")
+            .unwrap();
+    assert_eq!(
+        parsed.server_cleanup_mode,
+        Some(ServerCleanupMode::DiscardAll)
+    );
+}
+
+#[tokio::test]
+async fn test_server_reset_query_alias() {
+    let parse = |query: &str| -> Pool {
+        serde_yaml::from_str(&format!(
+            "server_host: \"localhost\"\nserver_reset_query: {query:?}\n"
+        ))
+        .unwrap()
+    };
+
+    let mut pool = parse("");
+    assert!(pool.validate().await.is_ok());
+    assert_eq!(pool.server_cleanup_mode, None);
+    assert_eq!(pool.server_cleanup_query, None);
+
+    let mut pool = parse("discard all;");
+    assert!(pool.validate().await.is_ok());
+    assert_eq!(
+        pool.server_cleanup_mode,
+        Some(ServerCleanupMode::DiscardAll)
+    );
+    assert_eq!(pool.server_cleanup_query, None);
+
+    for ok in [
+        "RESET ALL; SELECT pg_advisory_unlock_all(); CLOSE ALL;",
+        "SELECT set_config('app.note', 'a;b', false); DO $$BEGIN PERFORM 1; END$$",
+    ] {
+        let mut pool = parse(ok);
+        assert!(pool.validate().await.is_ok(), "{ok} should be accepted");
+        assert_eq!(pool.server_cleanup_mode, Some(ServerCleanupMode::Custom));
+        assert_eq!(pool.server_cleanup_query.as_deref(), Some(ok));
+    }
+    for bad in [
+        "RESET ALL; DISCARD ALL",
+        "ROLLBACK; RESET ALL",
+        "begin; RESET ALL; commit",
+        "COPY t TO STDOUT",
+    ] {
+        assert!(
+            parse(bad).validate().await.is_err(),
+            "{bad} should be rejected"
+        );
+    }
+
+    let mut pool = parse("RESET ALL");
+    pool.server_cleanup_mode = Some(ServerCleanupMode::DiscardAll);
+    assert!(pool.validate().await.is_err());
+
+    let both = "server_host: \"localhost\"\nserver_cleanup_query: \"RESET ALL\"\nserver_reset_query: \"RESET ALL\"\n";
+    assert!(serde_yaml::from_str::<Pool>(both).is_err());
+}
+
+// Per-pool server TLS overrides: verification needs a CA from the pool or general
+#[tokio::test]
+async fn test_validate_pool_server_tls_overrides() {
+    let mut config = Config::default();
+    config.general.server_tls_mode = "disable".to_string();
+    config.pools.insert(
+        "cloud".to_string(),
+        Pool {
+            server_tls_mode: Some("verify-full".to_string()),
+            users: vec![User {
+                username: "u1".to_string(),
+                password: "p1".to_string(),
+                pool_size: 10,
+                ..Default::default()
+            }],
+            ..Pool::default()
+        },
+    );
+    match config.validate().await {
+        Err(Error::BadConfig(msg)) => {
+            assert!(msg.contains("pool 'cloud'") && msg.contains("server_tls_ca_cert"))
+        }
+        other => panic!("Expected BadConfig about missing CA, got {other:?}"),
+    }
+
+    config.pools.get_mut("cloud").unwrap().server_tls_ca_cert =
+        Some("/etc/ssl/cloud-ca.pem".to_string());
+    config.pools.insert(
+        "local".to_string(),
+        Pool {
+            server_tls_mode: Some("disable".to_string()),
+            users: vec![User {
+                username: "u2".to_string(),
+                password: "p2".to_string(),
+                pool_size: 10,
+                ..Default::default()
+            }],
+            ..Pool::default()
+        },
+    );
+    assert!(config.validate().await.is_ok());
+
+    config.pools.get_mut("local").unwrap().server_tls_mode = Some("sometimes".to_string());
+    assert!(config.validate().await.is_err());
+}
+
+// server_connect_backoff_base must be positive and within server_connect_backoff_max
+#[tokio::test]
+async fn test_validate_server_connect_backoff() {
+    let mut config = Config::default();
+    assert!(config.validate().await.is_ok());
+
+    config.general.server_connect_backoff_base = Duration::from_millis(2000);
+    config.general.server_connect_backoff_max = Duration::from_millis(1000);
+    match config.validate().await {
+        Err(Error::BadConfig(msg)) => assert!(msg.contains("server_connect_backoff_base")),
+        other => panic!("Expected BadConfig about backoff, got {other:?}"),
+    }
+
+    config.general.server_connect_backoff_base = Duration::from_millis(0);
+    assert!(config.validate().await.is_err());
+}
+
+#[test]
+fn server_connect_hold_defaults_to_connect_timeout() {
+    let general = General::default();
+    assert_eq!(general.server_connect_hold, general.connect_timeout);
+
+    let general: General = serde_yaml::from_str(
+        "admin_username: admin\nadmin_password: admin\nserver_connect_hold: 0\n",
+    )
+    .unwrap();
+    assert_eq!(general.server_connect_hold.as_millis(), 0);
+}
+
+#[tokio::test]
+async fn test_max_connections_queue_requires_timeout() {
+    let mut config = Config::default();
+    assert_eq!(config.general.max_connections_queue(), None);
+
+    config.general.max_connections_overflow_behavior = MaxConnectionsOverflow::Queue;
+    assert_eq!(
+        config.general.max_connections_queue(),
+        Some(std::time::Duration::from_millis(1000))
+    );
+    assert!(config.validate().await.is_ok());
+
+    config.general.max_connections_queue_timeout = Duration::from_millis(0);
+    match config.validate().await {
+        Err(Error::BadConfig(msg)) => assert!(msg.contains("max_connections_queue_timeout")),
+        other => panic!("Expected BadConfig about queue timeout, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_unknown_message_behavior() {
+    assert_eq!(
+        General::default().unknown_message_behavior,
+        UnknownMessageBehavior::Error
+    );
+    let general: General = serde_yaml::from_str(
+        "admin_username: admin\nadmin_password: admin\nunknown_message_behavior: ignore\n",
+    )
+    .unwrap();
+    assert_eq!(
+        general.unknown_message_behavior,
+        UnknownMessageBehavior::Ignore
+    );
+    let general: General = serde_yaml::from_str(
+        "admin_username: admin\nadmin_password: admin\nunknown_message_behavior: passthrough\n",
+    )
+    .unwrap();
+    assert_eq!(
+        general.unknown_message_behavior,
+        UnknownMessageBehavior::Passthrough
+    );
+    assert!(serde_yaml::from_str::<General>(
+        "admin_username: admin\nadmin_password: admin\nunknown_message_behavior: drop\n",
+    )
+    .is_err());
+}
+
+// RELOAD refuses settings that are only read at process start
+#[test]
+fn test_restart_required_changes() {
+    let old = General::default();
+    let mut new = old.clone();
+    new.max_connections_per_tag = 10;
+    new.server_connect_backoff_max = Duration::from_millis(5000);
+    assert!(old.restart_required_changes(&new).is_empty());
+
+    new.port = 6433;
+    new.worker_threads += 1;
+    new.tls_ciphers = Some("ECDHE-RSA-AES256-GCM-SHA384".to_string());
+    assert_eq!(
+        old.restart_required_changes(&new),
+        vec!["port", "worker_threads", "tls_ciphers"]
+    );
+
+    // Extra listeners are bound once, with their default database.
+    let mut new = old.clone();
+    new.listeners = vec![crate::config::Listener {
+        host: None,
+        port: 6434,
+        default_database: Some("app".to_string()),
+    }];
+    assert_eq!(old.restart_required_changes(&new), vec!["listeners"]);
+}
+
+#[tokio::test]
+async fn connect_queries_user_override_and_blank_statements() {
+    let mut pool = Pool::default();
+    pool.connect_queries = vec!["SET search_path = app".to_string()];
+    let mut user = User::default();
+    assert_eq!(
+        pool.resolve_connect_queries(&user),
+        ["SET search_path = app"]
+    );
+    user.connect_queries = Some(Vec::new());
+    assert!(pool.resolve_connect_queries(&user).is_empty());
+
+    pool.connect_queries.push("  ".to_string());
+    match pool.validate().await {
+        Err(Error::BadConfig(msg)) => assert!(msg.contains("connect_queries[1]"), "{msg}"),
+        other => panic!("expected BadConfig, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn user_server_role_must_not_be_empty() {
+    let mut user = User {
+        server_role: Some("tenant_a".to_string()),
+        ..User::default()
+    };
+    assert!(user.validate().await.is_ok());
+
+    user.server_role = Some(String::new());
+    match user.validate().await {
+        Err(Error::BadConfig(msg)) => assert!(msg.contains("server_role"), "{msg}"),
+        other => panic!("expected BadConfig, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn user_max_concurrent_transactions_must_be_positive() {
+    let mut user: User = serde_yaml::from_str(
+        "username: app\npassword: secret\npool_size: 40\nmax_concurrent_transactions: 10\n",
+    )
+    .unwrap();
+    assert_eq!(user.max_concurrent_transactions, Some(10));
+    assert!(user.validate().await.is_ok());
+
+    user.max_concurrent_transactions = Some(0);
+    match user.validate().await {
+        Err(Error::BadConfig(msg)) => {
+            assert!(msg.contains("max_concurrent_transactions"), "{msg}")
+        }
+        other => panic!("expected BadConfig, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn pool_default_client_statement_timeout_excludes_server_statement_timeout() {
+    let mut pool = Pool {
+        default_client_statement_timeout_ms: Some(60_000),
+        ..Default::default()
+    };
+    pool.validate().await.unwrap();
+
+    pool.server_statement_timeout_ms = Some(30_000);
+    match pool.validate().await {
+        Err(Error::BadConfig(msg)) => {
+            assert!(msg.contains("default_client_statement_timeout_ms"), "{msg}")
+        }
+        other => panic!("expected BadConfig, got {other:?}"),
+    }
+
+    // 0 disables the default, so it does not conflict.
+    pool.default_client_statement_timeout_ms = Some(0);
+    pool.validate().await.unwrap();
+}
+
+#[test]
+fn server_timeouts_query_sets_only_configured_timeouts() {
+    let mut pool = Pool::default();
+    assert_eq!(pool.server_timeouts_query(), None);
+
+    pool.server_lock_timeout_ms = Some(5000);
+    assert_eq!(
+        pool.server_timeouts_query().as_deref(),
+        Some("SET lock_timeout = 5000;")
+    );
+
+    pool.server_statement_timeout_ms = Some(30000);
+    assert_eq!(
+        pool.server_timeouts_query().as_deref(),
+        Some("SET statement_timeout = 30000;SET lock_timeout = 5000;")
+    );
+}
+
+#[test]
+fn server_tcp_keepalive_falls_back_to_general_per_field() {
+    let general = General::default();
+    let mut pool = Pool::default();
+    assert_eq!(
+        pool.resolve_server_tcp_keepalive(&general),
+        general.tcp_keepalive_settings()
+    );
+
+    pool.server_tcp_keepalives_idle = Some(10);
+    pool.server_tcp_user_timeout = Some(20);
+    let resolved = pool.resolve_server_tcp_keepalive(&general);
+    assert_eq!(resolved.idle, 10);
+    assert_eq!(resolved.user_timeout, 20);
+    assert_eq!(resolved.interval, general.tcp_keepalives_interval);
+    assert_eq!(resolved.count, general.tcp_keepalives_count);
+}
+
+#[tokio::test]
+async fn server_tcp_keepalives_reject_zero() {
+    for field in [
+        "server_tcp_keepalives_idle",
+        "server_tcp_keepalives_interval",
+        "server_tcp_keepalives_count",
+    ] {
+        let mut pool: Pool =
+            serde_yaml::from_str(&format!("server_host: \"localhost\"\n{field}: 0\n")).unwrap();
+        match pool.validate().await {
+            Err(Error::BadConfig(msg)) => assert!(msg.contains(field), "{msg}"),
+            other => panic!("expected BadConfig for {field}, got {other:?}"),
+        }
+    }
+
+    let mut pool: Pool =
+        serde_yaml::from_str("server_host: \"localhost\"\nserver_tcp_user_timeout: 0\n").unwrap();
+    assert!(pool.validate().await.is_ok());
+}
+
+#[tokio::test]
+async fn pool_max_concurrent_creates_overrides_general() {
+    let mut pool: Pool =
+        serde_yaml::from_str("server_host: \"localhost\"\nmax_concurrent_creates: 2\n").unwrap();
+    assert_eq!(pool.max_concurrent_creates, Some(2));
+    assert!(pool.validate().await.is_ok());
+
+    pool.max_concurrent_creates = Some(0);
+    match pool.validate().await {
+        Err(Error::BadConfig(msg)) => assert!(msg.contains("max_concurrent_creates"), "{msg}"),
+        other => panic!("expected BadConfig, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn max_concurrent_queries_defaults_to_unlimited() {
+    let mut pool: Pool =
+        serde_yaml::from_str("server_host: \"localhost\"\nmax_concurrent_queries: 50\n").unwrap();
+    assert_eq!(pool.max_concurrent_queries, Some(50));
+    assert!(pool.validate().await.is_ok());
+    assert_eq!(General::default().max_concurrent_queries, 0);
+
+    pool.max_concurrent_queries = Some(0);
+    match pool.validate().await {
+        Err(Error::BadConfig(msg)) => assert!(msg.contains("max_concurrent_queries"), "{msg}"),
+        other => panic!("expected BadConfig, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn auto_pause_defaults_and_thresholds() {
+    let pool: Pool = serde_yaml::from_str("server_host: \"localhost\"\n").unwrap();
+    assert!(!pool.auto_pause_on_unhealthy);
+    assert_eq!(pool.auto_pause_failure_threshold, 5);
+    assert_eq!(pool.auto_pause_success_threshold, 2);
+
+    for field in [
+        "auto_pause_failure_threshold",
+        "auto_pause_success_threshold",
+    ] {
+        let mut pool: Pool = serde_yaml::from_str(&format!(
+            "server_host: \"localhost\"\nauto_pause_on_unhealthy: true\n{field}: 0\n"
+        ))
+        .unwrap();
+        match pool.validate().await {
+            Err(Error::BadConfig(msg)) => assert!(msg.contains(field), "{msg}"),
+            other => panic!("expected BadConfig for {field}, got {other:?}"),
+        }
+    }
+}
+
+#[tokio::test]
+async fn admin_users_reject_duplicate_names() {
+    let mut config = Config::default();
+    config.general.admin_users = vec![crate::config::AdminUser {
+        username: config.general.admin_username.clone(),
+        password: "monitor".to_string(),
+        permission: crate::config::AdminPermission::Readonly,
+    }];
+    match config.validate().await {
+        Err(Error::BadConfig(msg)) => assert!(msg.contains("more than once")),
+        other => panic!("expected BadConfig, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn listeners_parse_and_reject_reused_ports() {
+    let general: General = serde_yaml::from_str(
+        "admin_username: admin\nadmin_password: admin\nport: 6432\nlisteners:\n  - port: 6433\n    default_database: staging\n  - port: 6434\n    host: 127.0.0.1\n",
+    )
+    .unwrap();
+    assert_eq!(general.listeners.len(), 2);
+    assert_eq!(
+        general.listeners[0].default_database.as_deref(),
+        Some("staging")
+    );
+    assert_eq!(general.listeners[1].host.as_deref(), Some("127.0.0.1"));
+    assert_eq!(general.listeners[1].default_database, None);
+
+    let mut config = Config::default();
+    config.general.listeners = vec![crate::config::Listener {
+        host: None,
+        port: config.general.port,
+        default_database: Some("staging".to_string()),
+    }];
+    match config.validate().await {
+        Err(Error::BadConfig(msg)) => assert!(msg.contains("already used"), "{msg}"),
+        other => panic!("expected BadConfig, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn auth_fail_ban_requires_window_and_duration() {
+    let mut config = Config::default();
+    config.general.auth_fail_ban_threshold = 5;
+    config.general.auth_fail_ban_window = Duration::from_millis(0);
+    match config.validate().await {
+        Err(Error::BadConfig(msg)) => assert!(msg.contains("auth_fail_ban_window")),
+        other => panic!("expected BadConfig, got {other:?}"),
+    }
+}
+
+#[test]
+fn startup_timeout_defaults_to_one_minute() {
+    assert_eq!(
+        General::default().startup_timeout,
+        Duration::from_millis(60_000)
+    );
+    let general: General = serde_yaml::from_str(
+        "admin_username: admin\nadmin_password: admin\nstartup_timeout: \"5s\"\n",
+    )
+    .unwrap();
+    assert_eq!(general.startup_timeout, Duration::from_millis(5_000));
+}
+
+#[test]
+fn track_application_name_defaults_to_on() {
+    assert!(General::default().track_application_name);
+    let general: General =
+        serde_yaml::from_str("admin_username: admin\nadmin_password: admin\n").unwrap();
+    assert!(general.track_application_name);
+    let general: General = serde_yaml::from_str(
+        "admin_username: admin\nadmin_password: admin\ntrack_application_name: false\n",
+    )
+    .unwrap();
+    assert!(!general.track_application_name);
+}
+
+#[tokio::test]
+async fn admin_listen_port_must_be_free_and_set_with_host() {
+    let mut config = Config::default();
+    config.general.admin_listen_port = Some(config.general.port);
+    match config.validate().await {
+        Err(Error::BadConfig(msg)) => assert!(msg.contains("admin_listen_port"), "{msg}"),
+        other => panic!("expected BadConfig, got {other:?}"),
+    }
+
+    let mut config = Config::default();
+    config.general.admin_listen_host = Some("127.0.0.1".to_string());
+    match config.validate().await {
+        Err(Error::BadConfig(msg)) => assert!(msg.contains("admin_listen_host"), "{msg}"),
+        other => panic!("expected BadConfig, got {other:?}"),
+    }
+
+    config.general.admin_listen_port = Some(6431);
+    assert!(config.validate().await.is_ok());
+}
+
+#[tokio::test]
+async fn parameter_status_overrides_merge_and_validate() {
+    let mut config = Config::default();
+    config.general.parameter_status_overrides = std::collections::BTreeMap::from([
+        ("server_version".to_string(), "14.0".to_string()),
+        ("TimeZone".to_string(), "UTC".to_string()),
+    ]);
+    let pool = Pool {
+        parameter_status_overrides: std::collections::BTreeMap::from([(
+            "Server_Version".to_string(),
+            "15.0".to_string(),
+        )]),
+        ..Pool::default()
+    };
+    let merged = pool.resolve_parameter_status_overrides(&config.general);
+    assert_eq!(
+        merged.get("server_version").map(String::as_str),
+        Some("15.0")
+    );
+    assert_eq!(merged.get("TimeZone").map(String::as_str), Some("UTC"));
+    assert!(config.validate().await.is_ok());
+
+    config
+        .general
+        .parameter_status_overrides
+        .insert("bad name".to_string(), "x".to_string());
+    match config.validate().await {
+        Err(Error::BadConfig(msg)) => assert!(msg.contains("parameter_status_overrides"), "{msg}"),
+        other => panic!("expected BadConfig, got {other:?}"),
+    }
+}
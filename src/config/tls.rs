@@ -106,6 +106,8 @@ pub enum TLSMode {
     Disable,
     /// Require TLS but don't verify certificates
     Require,
+    /// Require TLS and a client certificate signed by `tls_ca_cert`
+    VerifyCa,
     /// Require TLS and verify certificates
     VerifyFull,
 }
@@ -116,6 +118,7 @@ impl std::fmt::Display for TLSMode {
             TLSMode::Allow => write!(f, "allow"),
             TLSMode::Disable => write!(f, "disable"),
             TLSMode::Require => write!(f, "require"),
+            TLSMode::VerifyCa => write!(f, "verify-ca"),
             TLSMode::VerifyFull => write!(f, "verify-full"),
         }
     }
@@ -128,10 +131,31 @@ impl TLSMode {
             "allow" => Ok(TLSMode::Allow),
             "disable" => Ok(TLSMode::Disable),
             "require" => Ok(TLSMode::Require),
+            "verify-ca" => Ok(TLSMode::VerifyCa),
             "verify-full" => Ok(TLSMode::VerifyFull),
             _ => Err(Error::BadConfig(format!("Invalid tls_mode: {s}"))),
         }
     }
+
+    /// Whether clients must present a certificate signed by `tls_ca_cert`.
+    /// A client has no hostname to match, so `verify-ca` and `verify-full`
+    /// check the same thing on this side.
+    pub fn requires_ca(&self) -> bool {
+        matches!(self, TLSMode::VerifyCa | TLSMode::VerifyFull)
+    }
+}
+
+/// Parse `tls_min_protocol_version`. Accepts "1.2"/"1.3" and the
+/// "TLSv1.2"/"TLSv1.3" spelling used by PostgreSQL's ssl_min_protocol_version.
+pub fn parse_tls_protocol_version(s: &str) -> Result<Protocol, Error> {
+    let version = s.strip_prefix("TLSv").unwrap_or(s);
+    match version {
+        "1.2" => Ok(Protocol::Tlsv12),
+        "1.3" => Ok(Protocol::Tlsv13),
+        _ => Err(Error::BadConfig(format!(
+            "Invalid tls_min_protocol_version: {s} (expected \"1.2\" or \"1.3\")"
+        ))),
+    }
 }
 
 /// Convert TLSMode to native_tls TlsClientCertificateVerification
@@ -140,7 +164,7 @@ fn tls_mode_to_verification(mode: &str) -> Result<TlsClientCertificateVerificati
     let tls_mode = TLSMode::from_string(mode)?;
     match tls_mode {
         TLSMode::Require | TLSMode::Allow => Ok(DoNotRequestCertificate),
-        TLSMode::VerifyFull => Ok(RequireCertificate),
+        TLSMode::VerifyCa | TLSMode::VerifyFull => Ok(RequireCertificate),
         TLSMode::Disable => Err(Error::BadConfig(
             "TLS mode 'disable' cannot be used when TLS is enabled".to_string(),
         )),
@@ -326,13 +350,20 @@ impl ServerTlsConfig {
     }
 }
 
-/// Build a TLS acceptor from certificate, key, and optional CA certificate
+/// ALPN protocol name PostgreSQL registered for its wire protocol.
+pub const POSTGRESQL_ALPN: &str = "postgresql";
+
+/// Build a TLS acceptor from certificate, key, and optional CA certificate.
+/// `min_version` and `ciphers` come from `tls_min_protocol_version` and
+/// `tls_ciphers`; `None` keeps TLS 1.2 as the floor and the OpenSSL defaults.
 #[allow(unused_variables)]
 pub fn build_acceptor(
     cert: &Path,
     key: &Path,
     ca_path: Option<impl AsRef<Path>>,
     mode: Option<String>,
+    min_version: Option<&str>,
+    ciphers: Option<&str>,
 ) -> Result<tokio_native_tls::TlsAcceptor, Error> {
     // Load identity from certificate and key
     let identity = load_identity(cert, key).map_err(|err| {
@@ -358,9 +389,21 @@ pub fn build_acceptor(
     let mut builder = native_tls::TlsAcceptor::builder(identity);
 
     // Set protocol versions
-    builder.min_protocol_version(Some(Protocol::Tlsv12)); // Upgraded from Tlsv10 for better security
+    let min_protocol = match min_version {
+        Some(version) => parse_tls_protocol_version(version)?,
+        None => Protocol::Tlsv12, // Upgraded from Tlsv10 for better security
+    };
+    builder.min_protocol_version(Some(min_protocol));
     builder.max_protocol_version(None);
 
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "ios")))]
+    builder.cipher_list(ciphers.map(str::to_string));
+
+    // PostgreSQL 17 clients offer ALPN "postgresql" and require it for
+    // `sslnegotiation=direct`.
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "ios")))]
+    builder.accept_alpns(&[POSTGRESQL_ALPN]);
+
     // Configure client certificate verification
     #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "ios")))]
     if let Some(ca_cert) = ca {
@@ -440,6 +483,10 @@ mod tests {
         assert_eq!(TLSMode::from_string("allow").unwrap(), TLSMode::Allow);
         assert_eq!(TLSMode::from_string("disable").unwrap(), TLSMode::Disable);
         assert_eq!(TLSMode::from_string("require").unwrap(), TLSMode::Require);
+        assert_eq!(
+            TLSMode::from_string("verify-ca").unwrap(),
+            TLSMode::VerifyCa
+        );
         assert_eq!(
             TLSMode::from_string("verify-full").unwrap(),
             TLSMode::VerifyFull
@@ -454,6 +501,7 @@ mod tests {
         assert_eq!(TLSMode::Allow.to_string(), "allow");
         assert_eq!(TLSMode::Disable.to_string(), "disable");
         assert_eq!(TLSMode::Require.to_string(), "require");
+        assert_eq!(TLSMode::VerifyCa.to_string(), "verify-ca");
         assert_eq!(TLSMode::VerifyFull.to_string(), "verify-full");
     }
 
@@ -468,6 +516,10 @@ mod tests {
             tls_mode_to_verification("require").unwrap(),
             TlsClientCertificateVerification::DoNotRequestCertificate
         ));
+        assert!(matches!(
+            tls_mode_to_verification("verify-ca").unwrap(),
+            TlsClientCertificateVerification::RequireCertificate
+        ));
         assert!(matches!(
             tls_mode_to_verification("verify-full").unwrap(),
             TlsClientCertificateVerification::RequireCertificate
@@ -571,6 +623,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_tls_protocol_version() {
+        assert!(matches!(
+            parse_tls_protocol_version("1.2"),
+            Ok(Protocol::Tlsv12)
+        ));
+        assert!(matches!(
+            parse_tls_protocol_version("TLSv1.3"),
+            Ok(Protocol::Tlsv13)
+        ));
+        assert!(parse_tls_protocol_version("1.1").is_err());
+        assert!(parse_tls_protocol_version("").is_err());
+    }
+
     #[test]
     fn test_build_acceptor() {
         // These paths are relative to the project root
@@ -585,6 +651,8 @@ mod tests {
                 &key_path,
                 Some(&ca_path),
                 Some("require".to_string()),
+                None,
+                None,
             );
             assert!(
                 result.is_ok(),
@@ -598,6 +666,8 @@ mod tests {
                 &key_path,
                 None::<&Path>,
                 Some("require".to_string()),
+                None,
+                None,
             );
             assert!(
                 result.is_ok(),
@@ -606,12 +676,39 @@ mod tests {
             );
 
             // Test without mode
-            let result = build_acceptor(&cert_path, &key_path, Some(&ca_path), None);
+            let result = build_acceptor(&cert_path, &key_path, Some(&ca_path), None, None, None);
             assert!(
                 result.is_ok(),
                 "Failed to build acceptor without mode: {:?}",
                 result.err()
             );
+
+            // TLS 1.3 floor
+            let result = build_acceptor(
+                &cert_path,
+                &key_path,
+                None::<&Path>,
+                None,
+                Some("1.3"),
+                None,
+            );
+            assert!(
+                result.is_ok(),
+                "Failed to build TLS 1.3-only acceptor: {:?}",
+                result.err()
+            );
+
+            // Unknown cipher names are rejected when the acceptor is built
+            let result = build_acceptor(
+                &cert_path,
+                &key_path,
+                None::<&Path>,
+                None,
+                None,
+                Some("NOT-A-CIPHER"),
+            );
+            #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "ios")))]
+            assert!(result.is_err());
         }
     }
 }
@@ -30,6 +30,19 @@ pub struct User {
     // Pam auth
     #[serde(skip_serializing_if = "Option::is_none")]
     pub auth_pam_service: Option<String>,
+    /// Replaces the pool's `connect_queries` for this user's connections.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_queries: Option<Vec<String>>,
+    /// Order among waiters of a saturated shared server pool; 0 when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u8>,
+    /// Role set with `SET ROLE` for the duration of each server checkout.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_role: Option<String>,
+    /// Server connections this user's clients may hold at once; caps one
+    /// user's share of a shared server pool.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_transactions: Option<usize>,
 }
 
 impl Default for User {
@@ -44,6 +57,10 @@ impl Default for User {
             server_username: None,
             server_password: None,
             auth_pam_service: None,
+            connect_queries: None,
+            priority: None,
+            server_role: None,
+            max_concurrent_transactions: None,
         }
     }
 }
@@ -63,6 +80,17 @@ impl User {
                 "server_password requires server_username to be set".to_string(),
             ));
         }
+        if self.server_role.as_deref().is_some_and(str::is_empty) {
+            return Err(Error::BadConfig("server_role cannot be empty".to_string()));
+        }
+        if self.max_concurrent_transactions == Some(0) {
+            return Err(Error::BadConfig(
+                "max_concurrent_transactions must be > 0".to_string(),
+            ));
+        }
+        if let Some(queries) = &self.connect_queries {
+            validate_connect_queries(queries, "user connect_queries")?;
+        }
         if let Some(min_pool_size) = self.min_pool_size {
             if min_pool_size > self.pool_size {
                 return Err(Error::BadConfig(format!(
@@ -75,3 +103,11 @@ impl User {
         Ok(())
     }
 }
+
+/// Rejects blank statements in a `connect_queries` list.
+pub(crate) fn validate_connect_queries(queries: &[String], field: &str) -> Result<(), Error> {
+    if let Some(idx) = queries.iter().position(|query| query.trim().is_empty()) {
+        return Err(Error::BadConfig(format!("{field}[{idx}] cannot be empty")));
+    }
+    Ok(())
+}
@@ -10,6 +10,11 @@ pub struct Web {
     pub host: String,
     #[serde(default = "Web::default_port")]
     pub port: u16,
+    /// Unix socket path the listener also accepts on, so `/metrics` can be
+    /// scraped through a sidecar without a network port. Served in addition
+    /// to `host`/`port`; the socket gets `general.unix_socket_mode`.
+    #[serde(default)]
+    pub unix_socket: Option<PathBuf>,
     #[serde(default = "Web::default_enabled")]
     pub enabled: bool,
     #[serde(default = "Web::default_ui")]
@@ -93,6 +98,7 @@ impl Web {
         Web {
             host: Self::default_host(),
             port: Self::default_port(),
+            unix_socket: None,
             enabled: Self::default_enabled(),
             ui: Self::default_ui(),
             ui_anonymous: Self::default_ui_anonymous(),
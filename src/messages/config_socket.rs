@@ -4,7 +4,7 @@ use log::{debug, error, log_enabled, Level};
 use socket2::{SockRef, TcpKeepalive};
 use tokio::net::{TcpStream, UnixStream};
 
-use crate::config::{get_config, Config};
+use crate::config::{get_config, Config, TcpKeepaliveSettings};
 
 /// Configure Unix socket parameters.
 pub fn configure_unix_socket(stream: &UnixStream) {
@@ -39,8 +39,28 @@ pub fn configure_tcp_socket_for_cancel(stream: &TcpStream) {
 
 /// Configure TCP socket parameters.
 pub fn configure_tcp_socket(stream: &TcpStream) {
-    let sock_ref = SockRef::from(stream);
     let conf = get_config();
+    let keepalive = conf.general.tcp_keepalive_settings();
+    configure_tcp_socket_with_keepalive(stream, &conf, &keepalive);
+}
+
+/// Configure a backend TCP socket: the pool's `server_tcp_*` overrides,
+/// falling back to the general keepalive settings.
+pub fn configure_server_tcp_socket(stream: &TcpStream, pool_name: &str) {
+    let conf = get_config();
+    let keepalive = match conf.pools.get(pool_name) {
+        Some(pool) => pool.resolve_server_tcp_keepalive(&conf.general),
+        None => conf.general.tcp_keepalive_settings(),
+    };
+    configure_tcp_socket_with_keepalive(stream, &conf, &keepalive);
+}
+
+fn configure_tcp_socket_with_keepalive(
+    stream: &TcpStream,
+    conf: &Config,
+    keepalive: &TcpKeepaliveSettings,
+) {
+    let sock_ref = SockRef::from(stream);
 
     match sock_ref.set_linger(Some(Duration::from_secs(conf.general.tcp_so_linger))) {
         Ok(_) => {}
@@ -52,7 +72,7 @@ pub fn configure_tcp_socket(stream: &TcpStream) {
         Err(err) => error!("failed to set TCP_NODELAY on TCP socket: {err}"),
     }
 
-    configure_tcp_socket_without_linger(&sock_ref, &conf, "TCP socket");
+    configure_tcp_socket_without_linger(&sock_ref, conf, keepalive, "TCP socket");
 }
 
 /// Configure accepted web TCP socket parameters.
@@ -68,10 +88,16 @@ pub fn configure_web_tcp_socket(stream: &TcpStream) {
         Err(err) => error!("failed to set TCP_NODELAY on web TCP socket: {err}"),
     }
 
-    configure_tcp_socket_without_linger(&sock_ref, &conf, "web TCP socket");
+    let keepalive = conf.general.tcp_keepalive_settings();
+    configure_tcp_socket_without_linger(&sock_ref, &conf, &keepalive, "web TCP socket");
 }
 
-fn configure_tcp_socket_without_linger(sock_ref: &SockRef<'_>, conf: &Config, label: &str) {
+fn configure_tcp_socket_without_linger(
+    sock_ref: &SockRef<'_>,
+    conf: &Config,
+    keepalive: &TcpKeepaliveSettings,
+    label: &str,
+) {
     // Opt-in SO_RCVBUF/SO_SNDBUF. A non-zero value disables Linux TCP
     // autotuning for this socket and sets fixed send/receive buffer
     // limits. Linux doubles the requested values internally and may
@@ -116,9 +142,9 @@ fn configure_tcp_socket_without_linger(sock_ref: &SockRef<'_>, conf: &Config, la
         Ok(_) => {
             match sock_ref.set_tcp_keepalive(
                 &TcpKeepalive::new()
-                    .with_interval(Duration::from_secs(conf.general.tcp_keepalives_interval))
-                    .with_retries(conf.general.tcp_keepalives_count)
-                    .with_time(Duration::from_secs(conf.general.tcp_keepalives_idle)),
+                    .with_interval(Duration::from_secs(keepalive.interval))
+                    .with_retries(keepalive.count)
+                    .with_time(Duration::from_secs(keepalive.idle)),
             ) {
                 Ok(_) => (),
                 Err(err) => error!("failed to set TCP keepalive parameters on {label}: {err}"),
@@ -129,10 +155,8 @@ fn configure_tcp_socket_without_linger(sock_ref: &SockRef<'_>, conf: &Config, la
 
     // TCP_USER_TIMEOUT is only supported on Linux
     #[cfg(target_os = "linux")]
-    if conf.general.tcp_user_timeout > 0 {
-        match sock_ref
-            .set_tcp_user_timeout(Some(Duration::from_secs(conf.general.tcp_user_timeout)))
-        {
+    if keepalive.user_timeout > 0 {
+        match sock_ref.set_tcp_user_timeout(Some(Duration::from_secs(keepalive.user_timeout))) {
             Ok(_) => (),
             Err(err) => error!("failed to set TCP_USER_TIMEOUT on {label}: {err}"),
         }
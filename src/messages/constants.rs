@@ -24,6 +24,10 @@ pub const AUTHENTICATION_CLEAR_PASSWORD: i32 = 3;
 // Used in the StartupMessage to indicate regular handshake.
 pub const PROTOCOL_VERSION_NUMBER: i32 = 196608;
 
+// Major protocol version in the upper 16 bits of the StartupMessage code.
+// Any minor version of it is accepted and negotiated down to 3.0.
+pub const PROTOCOL_VERSION_MAJOR: i32 = 3;
+
 // SSLRequest: used to indicate we want an SSL connection.
 pub const SSL_REQUEST_CODE: i32 = 80877103;
 
@@ -225,6 +225,11 @@ impl Parse {
         &self.query
     }
 
+    /// Shared handle to the query text.
+    pub fn query_arc(&self) -> Arc<str> {
+        self.query.clone()
+    }
+
     pub fn param_types(&self) -> &[i32] {
         &self.param_types
     }
@@ -302,6 +302,17 @@ pub fn parse_startup(bytes: BytesMut) -> Result<HashMap<String, String>, Error>
     Ok(result)
 }
 
+/// The `replication` StartupMessage parameter when the client asks for a
+/// walsender connection (`true`, `database`, ...), `None` for a regular
+/// connection. Any other value is left for PostgreSQL to validate.
+pub fn replication_mode(params: &HashMap<String, String>) -> Option<&str> {
+    let value = params.get("replication")?;
+    match value.trim().to_ascii_lowercase().as_str() {
+        "false" | "off" | "no" | "0" => None,
+        _ => Some(value.as_str()),
+    }
+}
+
 /// Create md5 password hash given a salt.
 pub fn md5_hash_password(user: &str, password: &str, salt: &[u8]) -> Vec<u8> {
     let mut md5 = Md5::new();
@@ -405,6 +416,65 @@ pub fn error_message(message: &str, code: &str) -> BytesMut {
     res
 }
 
+/// NegotiateProtocolVersion: the newest minor protocol version we speak and
+/// the `_pq_.*` startup options we did not recognise. PostgreSQL sends it
+/// instead of failing the startup, and clients fall back accordingly.
+pub fn negotiate_protocol_version(minor_version: i32, unrecognized: &[&str]) -> BytesMut {
+    let mut body = BytesMut::new();
+    body.put_i32(minor_version);
+    body.put_i32(unrecognized.len() as i32);
+    for option in unrecognized {
+        body.put_slice(option.as_bytes());
+        body.put_u8(0);
+    }
+
+    let mut res = BytesMut::with_capacity(body.len() + 5);
+    res.put_u8(b'v');
+    res.put_i32(body.len() as i32 + 4);
+    res.put(body);
+    res
+}
+
+/// NegotiateProtocolVersion to send after a StartupMessage, if any: the
+/// client asked for a minor version newer than 3.0, or passed `_pq_.*`
+/// protocol options. Both are declined in a single message, the way
+/// PostgreSQL does it. Returns `None` for a plain 3.0 startup, and also
+/// for a malformed one, which `parse_startup` rejects afterwards.
+pub fn startup_negotiation(requested_minor: i32, bytes: &BytesMut) -> Option<BytesMut> {
+    let params = parse_params(bytes.clone()).ok()?;
+    let mut unrecognized: Vec<&str> = params
+        .keys()
+        .map(String::as_str)
+        .filter(|key| key.starts_with(crate::config::startup_parameters::RESERVED_PREFIX))
+        .collect();
+    if requested_minor == 0 && unrecognized.is_empty() {
+        return None;
+    }
+    unrecognized.sort_unstable();
+    Some(negotiate_protocol_version(0, &unrecognized))
+}
+
+/// NoticeResponse with WARNING severity. Clients may receive it at any
+/// point, so it can be slipped in front of a server response.
+pub fn notice_message(message: &str, code: &str) -> BytesMut {
+    let mut notice = BytesMut::new();
+    notice.put_u8(b'S');
+    notice.put_slice(&b"WARNING\0"[..]);
+    notice.put_u8(b'V');
+    notice.put_slice(&b"WARNING\0"[..]);
+    notice.put_u8(b'C');
+    notice.put_slice(format!("{code}\0").as_bytes());
+    notice.put_u8(b'M');
+    notice.put_slice(format!("{message}\0").as_bytes());
+    notice.put_u8(0);
+
+    let mut res = BytesMut::with_capacity(notice.len() + 5);
+    res.put_u8(b'N');
+    res.put_i32(notice.len() as i32 + 4);
+    res.put(notice);
+    res
+}
+
 pub async fn error_response_terminal<S>(
     stream: &mut S,
     message: &str,
@@ -657,14 +727,14 @@ pub fn has_error_response(bytes: &[u8]) -> bool {
     false
 }
 
-/// Create a deallocate response message.
+/// Create the response to a simple-query `DEALLOCATE` answered by the
+/// pooler: CommandComplete with `tag` (`DEALLOCATE` or `DEALLOCATE ALL`)
+/// and ReadyForQuery.
 #[inline]
-pub fn deallocate_response() -> BytesMut {
-    // ParseComplete(5) + CommandComplete("DEALLOCATE" = 10 + 1 + 4 + 1 = 16) + ReadyForQuery(6) = 27
-    let mut bytes = BytesMut::with_capacity(27);
-    bytes.put(parse_complete());
-    bytes.put(command_complete("DEALLOCATE"));
-    bytes.put(ready_for_query(false));
+pub fn deallocate_response(tag: &str, in_transaction: bool) -> BytesMut {
+    let mut bytes = BytesMut::with_capacity(tag.len() + 6 + 6);
+    bytes.put(command_complete(tag));
+    bytes.put(ready_for_query(in_transaction));
     bytes
 }
 
@@ -6,7 +6,8 @@ use tokio::time::timeout;
 
 use crate::errors::Error;
 use crate::errors::Error::ProxyTimeout;
-use crate::messages::{CURRENT_MEMORY, MAX_MESSAGE_SIZE};
+use crate::messages::{CURRENT_MEMORY, MAX_MESSAGE_SIZE, PEAK_MEMORY};
+use crate::utils::debug_messages::hex_preview;
 
 /// Default capacity for a freshly allocated reusable read buffer.
 const REUSE_BUF_DEFAULT_CAPACITY: usize = 16 * 1024;
@@ -98,22 +99,37 @@ where
     Ok((code, len))
 }
 
+/// Validates a message header before its body is read. Every message type
+/// of the frontend and backend protocol is an ASCII letter or digit, and the
+/// length counts itself, so anything else means the stream is out of sync:
+/// reading on would interpret payload bytes as headers. The error carries
+/// the header bytes so the log shows what was actually on the wire.
+fn check_message_header(code: u8, len: i32) -> Result<(), Error> {
+    let problem = if !code.is_ascii_alphanumeric() {
+        "invalid message type"
+    } else if len < 4 {
+        "message length is too small"
+    } else if len > MAX_MESSAGE_SIZE {
+        "message length is too large"
+    } else {
+        return Ok(());
+    };
+    let mut header = [0u8; 5];
+    header[0] = code;
+    header[1..].copy_from_slice(&len.to_be_bytes());
+    Err(Error::ProtocolSyncError(format!(
+        "{problem}: type={:?} len={len} header=[{}]",
+        code as char,
+        hex_preview(&header, header.len()),
+    )))
+}
+
 /// Read message data.
 pub async fn read_message_data<S>(stream: &mut S, code: u8, len: i32) -> Result<BytesMut, Error>
 where
     S: tokio::io::AsyncRead + std::marker::Unpin,
 {
-    if len < 4 {
-        return Err(Error::ProtocolSyncError(format!(
-            "Message length is too small: {len}"
-        )));
-    }
-
-    if len > MAX_MESSAGE_SIZE {
-        return Err(Error::ProtocolSyncError(format!(
-            "Message length is too large: {len}"
-        )));
-    }
+    check_message_header(code, len)?;
 
     let total_len = len as usize + 1; // code(1) + len(4) + data
     let mut buf = BytesMut::with_capacity(total_len);
@@ -140,6 +156,7 @@ where
         CURRENT_MEMORY.fetch_sub(len as i64, Ordering::Relaxed);
         return Err(Error::CurrentMemoryUsage);
     }
+    PEAK_MEMORY.fetch_max(prev + len as i64, Ordering::Relaxed);
     let result = read_message_data(stream, code, len).await;
     CURRENT_MEMORY.fetch_sub(len as i64, Ordering::Relaxed);
     result
@@ -165,22 +182,14 @@ where
 {
     let (code, len) = read_message_header(stream).await?;
 
-    if len < 4 {
-        return Err(Error::ProtocolSyncError(format!(
-            "Message length is too small: {len}"
-        )));
-    }
-    if len > MAX_MESSAGE_SIZE {
-        return Err(Error::ProtocolSyncError(format!(
-            "Message length is too large: {len}"
-        )));
-    }
+    check_message_header(code, len)?;
 
     let prev = CURRENT_MEMORY.fetch_add(len as i64, Ordering::Relaxed);
     if (prev + len as i64) as u64 > max_memory_usage {
         CURRENT_MEMORY.fetch_sub(len as i64, Ordering::Relaxed);
         return Err(Error::CurrentMemoryUsage);
     }
+    PEAK_MEMORY.fetch_max(prev + len as i64, Ordering::Relaxed);
 
     let total_len = len as usize + 1;
     shrink_reuse_buf(buf);
@@ -215,11 +224,7 @@ pub async fn read_message_body_reuse<S>(
 where
     S: tokio::io::AsyncRead + std::marker::Unpin,
 {
-    if len < 4 {
-        return Err(Error::ProtocolSyncError(format!(
-            "Message length is too small: {len}"
-        )));
-    }
+    check_message_header(code, len)?;
 
     let total_len = len as usize + 1;
     shrink_reuse_buf(buf);
@@ -460,6 +465,26 @@ mod tests {
         // Note: CURRENT_MEMORY is a global shared by all tests — don't assert absolute value
     }
 
+    /// A type byte that is no protocol message (here: payload bytes read as
+    /// a header after a desync) is rejected before its length is trusted,
+    /// and the error shows the header bytes.
+    #[tokio::test]
+    async fn reuse_invalid_type_byte_returns_sync_error() {
+        let data = wire_msg(0x00, b"SELECT 1\0");
+        let mut stream = Cursor::new(data);
+        let mut buf = BytesMut::with_capacity(READ_BUF_DEFAULT_CAPACITY);
+
+        let result = read_message_reuse(&mut stream, &mut buf, u64::MAX).await;
+
+        match result {
+            Err(Error::ProtocolSyncError(msg)) => {
+                assert!(msg.starts_with("invalid message type"), "{msg}");
+                assert!(msg.contains("header=[00 00 00 00 0d]"), "{msg}");
+            }
+            other => panic!("expected ProtocolSyncError, got {other:?}"),
+        }
+    }
+
     // =========================================================================
     // read_message_reuse — memory pressure
     // =========================================================================
@@ -512,6 +537,21 @@ mod tests {
         );
     }
 
+    /// An accepted read raises the peak watermark to at least its own size.
+    #[tokio::test]
+    async fn reuse_memory_peak_tracks_accepted_read() {
+        let body = vec![0u8; 4092]; // len = 4096
+        let data = wire_msg(b'D', &body);
+        let mut stream = Cursor::new(data);
+        let mut buf = BytesMut::with_capacity(READ_BUF_DEFAULT_CAPACITY);
+
+        let _ = read_message_reuse(&mut stream, &mut buf, u64::MAX)
+            .await
+            .unwrap();
+
+        assert!(PEAK_MEMORY.load(Ordering::SeqCst) >= 4096);
+    }
+
     /// Memory counter delta must be 0 even on read failure (EOF mid-body).
     /// Uses delta instead of absolute value to avoid races with parallel tests.
     #[tokio::test]
@@ -9,8 +9,8 @@ use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 use crate::errors::Error;
 use crate::messages::protocol::row_description;
 use crate::messages::{
-    data_row, data_row_nullable, error_message, parse_startup, ready_for_query, DataType,
-    PgErrorMsg,
+    data_row, data_row_nullable, error_message, negotiate_protocol_version, parse_startup,
+    ready_for_query, replication_mode, startup_negotiation, DataType, PgErrorMsg,
 };
 
 #[allow(dead_code)]
@@ -88,6 +88,30 @@ fn test_parse_startup_success() {
     assert_eq!(params.get("application_name"), Some(&"testapp".to_string()));
 }
 
+#[test]
+fn test_replication_mode() {
+    let mut bytes = BytesMut::new();
+    bytes.put_slice(b"user\0replicator\0");
+    bytes.put_slice(b"replication\0database\0");
+    bytes.put_u8(0);
+    let params = parse_startup(bytes).unwrap();
+    assert_eq!(replication_mode(&params), Some("database"));
+
+    let mut params = params;
+    for (value, expected) in [
+        ("true", Some("true")),
+        ("on", Some("on")),
+        ("false", None),
+        ("OFF", None),
+        ("0", None),
+    ] {
+        params.insert("replication".to_string(), value.to_string());
+        assert_eq!(replication_mode(&params), expected, "replication={value}");
+    }
+    params.remove("replication");
+    assert_eq!(replication_mode(&params), None);
+}
+
 #[test]
 fn test_parse_startup_missing_user() {
     let mut bytes = BytesMut::new();
@@ -105,6 +129,41 @@ fn test_parse_startup_missing_user() {
     }
 }
 
+#[test]
+fn test_negotiate_protocol_version_lists_unrecognized_options() {
+    let msg = negotiate_protocol_version(0, &["_pq_.compression"]);
+    let mut expected = BytesMut::new();
+    expected.put_u8(b'v');
+    expected.put_i32(4 + 4 + 4 + "_pq_.compression".len() as i32 + 1);
+    expected.put_i32(0);
+    expected.put_i32(1);
+    expected.put_slice(b"_pq_.compression\0");
+    assert_eq!(msg, expected);
+}
+
+#[test]
+fn test_startup_negotiation_declines_newer_minor_and_pq_options() {
+    let mut params = BytesMut::new();
+    params.put_slice(b"user\0alice\0_pq_.b\0on\0_pq_.a\0on\0\0");
+
+    assert_eq!(
+        startup_negotiation(2, &params),
+        Some(negotiate_protocol_version(0, &["_pq_.a", "_pq_.b"]))
+    );
+    assert_eq!(
+        startup_negotiation(0, &params),
+        Some(negotiate_protocol_version(0, &["_pq_.a", "_pq_.b"]))
+    );
+
+    let mut plain = BytesMut::new();
+    plain.put_slice(b"user\0alice\0\0");
+    assert_eq!(startup_negotiation(0, &plain), None);
+    assert_eq!(
+        startup_negotiation(2, &plain),
+        Some(negotiate_protocol_version(0, &[]))
+    );
+}
+
 #[test]
 fn test_error_message_detailed() {
     let result = error_message("Test error message", "28000");
@@ -37,6 +37,9 @@ pub struct AuthQueryState {
     /// against the new parent config.
     pub(crate) parent_fingerprint: u64,
     pool_name: String,
+    /// Default database for executor connections (`server_database`, or
+    /// the pool name).
+    server_database: String,
     server_host: String,
     server_port: u16,
     /// Pool identifier for the shared server_user pool (None = passthrough mode).
@@ -53,6 +56,7 @@ impl AuthQueryState {
         pool_startup_hash: u64,
         parent_fingerprint: u64,
         pool_name: String,
+        server_database: String,
         server_host: String,
         server_port: u16,
         shared_pool_id: Option<PoolIdentifier>,
@@ -64,6 +68,7 @@ impl AuthQueryState {
             pool_startup_hash,
             parent_fingerprint,
             pool_name,
+            server_database,
             server_host,
             server_port,
             shared_pool_id,
@@ -90,6 +95,7 @@ impl AuthQueryState {
                 let executor = AuthQueryExecutor::new(
                     &self.config,
                     &self.pool_name,
+                    &self.server_database,
                     &self.server_host,
                     self.server_port,
                 )
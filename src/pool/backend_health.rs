@@ -0,0 +1,134 @@
+//! Automatic pause of a pool whose backend keeps failing.
+//!
+//! With `auto_pause_on_unhealthy`, `auto_pause_failure_threshold`
+//! consecutive connect or alive-check failures put the pool into
+//! auto-pause: checkouts fail fast instead of waiting out
+//! `query_wait_timeout`. The retain loop then probes the backend with one
+//! connect per cycle, and `auto_pause_success_threshold` consecutive
+//! successes resume the pool. Admin RESUME clears the state at once.
+//!
+//! Auto-pause is tracked apart from the admin PAUSE bit: an admin PAUSE
+//! parks clients until RESUME, an auto-pause turns them away.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+/// Health streaks of one pool's backend.
+#[derive(Debug, Default)]
+pub(crate) struct BackendHealth {
+    failures: AtomicU32,
+    successes: AtomicU32,
+    paused: AtomicBool,
+    probing: AtomicBool,
+}
+
+impl BackendHealth {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the pool is auto-paused.
+    pub(crate) fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Acquire)
+    }
+
+    /// Current failure streak, for the pause log line.
+    pub(crate) fn failures(&self) -> u32 {
+        self.failures.load(Ordering::Relaxed)
+    }
+
+    /// Records a backend failure. Returns `true` when this failure
+    /// reached `threshold` and auto-paused the pool.
+    pub(crate) fn on_failure(&self, threshold: u32) -> bool {
+        self.successes.store(0, Ordering::Relaxed);
+        let streak = self.failures.fetch_add(1, Ordering::Relaxed) + 1;
+        streak >= threshold && !self.paused.swap(true, Ordering::AcqRel)
+    }
+
+    /// Records a successful connect or alive check. Returns `true` when
+    /// this success reached `threshold` and resumed an auto-paused pool.
+    pub(crate) fn on_success(&self, threshold: u32) -> bool {
+        self.failures.store(0, Ordering::Relaxed);
+        if !self.is_paused() {
+            return false;
+        }
+        let streak = self.successes.fetch_add(1, Ordering::Relaxed) + 1;
+        streak >= threshold && self.clear()
+    }
+
+    /// Leaves auto-pause and resets both streaks. Returns `true` if the
+    /// pool was auto-paused.
+    pub(crate) fn clear(&self) -> bool {
+        self.failures.store(0, Ordering::Relaxed);
+        self.successes.store(0, Ordering::Relaxed);
+        self.paused.swap(false, Ordering::AcqRel)
+    }
+
+    /// Claims the single probe slot. Returns `false` while another probe
+    /// is still connecting.
+    pub(crate) fn try_start_probe(&self) -> bool {
+        !self.probing.swap(true, Ordering::AcqRel)
+    }
+
+    /// Releases the probe slot taken by [`Self::try_start_probe`].
+    pub(crate) fn finish_probe(&self) {
+        self.probing.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pauses_once_at_failure_threshold() {
+        let health = BackendHealth::new();
+        assert!(!health.on_failure(3));
+        assert!(!health.on_failure(3));
+        assert!(health.on_failure(3));
+        assert!(health.is_paused());
+        // Further failures do not report a second transition.
+        assert!(!health.on_failure(3));
+        assert_eq!(health.failures(), 4);
+    }
+
+    #[test]
+    fn success_resets_failure_streak() {
+        let health = BackendHealth::new();
+        health.on_failure(3);
+        health.on_failure(3);
+        assert!(!health.on_success(2));
+        assert!(!health.on_failure(3));
+        assert!(!health.is_paused());
+    }
+
+    #[test]
+    fn resumes_after_consecutive_successes() {
+        let health = BackendHealth::new();
+        assert!(health.on_failure(1));
+        assert!(!health.on_success(2));
+        // A failure in between restarts the success streak.
+        assert!(!health.on_failure(1));
+        assert!(!health.on_success(2));
+        assert!(health.on_success(2));
+        assert!(!health.is_paused());
+        assert_eq!(health.failures(), 0);
+    }
+
+    #[test]
+    fn clear_reports_whether_paused() {
+        let health = BackendHealth::new();
+        assert!(!health.clear());
+        health.on_failure(1);
+        assert!(health.clear());
+        assert!(!health.is_paused());
+    }
+
+    #[test]
+    fn single_probe_slot() {
+        let health = BackendHealth::new();
+        assert!(health.try_start_probe());
+        assert!(!health.try_start_probe());
+        health.finish_probe();
+        assert!(health.try_start_probe());
+    }
+}
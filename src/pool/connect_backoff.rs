@@ -0,0 +1,90 @@
+//! Backoff between failed server connection attempts.
+//!
+//! Every `ServerPool::create` failure pauses the caller before the error is
+//! returned. The pause doubles with each consecutive failure of the pool, up
+//! to `server_connect_backoff_max`, and resets after the first successful
+//! connect. Half of each pause is random so that workers which failed
+//! together do not retry together when the backend comes back.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+/// Growth is capped at 2^16 * base; `max` normally kicks in long before.
+const MAX_EXPONENT: u32 = 16;
+
+/// Consecutive connect failures of one pool.
+#[derive(Debug, Default)]
+pub(crate) struct ConnectBackoff {
+    failures: AtomicU32,
+}
+
+impl ConnectBackoff {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Clears the failure streak after a successful connect.
+    pub(crate) fn on_success(&self) {
+        self.failures.store(0, Ordering::Relaxed);
+    }
+
+    /// Records a failure and returns how long the caller should sleep.
+    pub(crate) fn on_failure(&self, base: Duration, max: Duration) -> Duration {
+        let streak = self.failures.fetch_add(1, Ordering::Relaxed);
+        let ceiling = Self::ceiling(streak, base, max);
+        let half = ceiling / 2;
+        half + half.mul_f64(rand::random::<f64>())
+    }
+
+    /// Upper bound of the pause after `streak` earlier consecutive failures.
+    fn ceiling(streak: u32, base: Duration, max: Duration) -> Duration {
+        base.saturating_mul(1 << streak.min(MAX_EXPONENT)).min(max)
+    }
+
+    /// Current failure streak, for tests and diagnostics.
+    #[cfg(test)]
+    fn failures(&self) -> u32 {
+        self.failures.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: Duration = Duration::from_millis(10);
+    const MAX: Duration = Duration::from_millis(1000);
+
+    #[test]
+    fn ceiling_doubles_and_caps() {
+        assert_eq!(ConnectBackoff::ceiling(0, BASE, MAX), BASE);
+        assert_eq!(
+            ConnectBackoff::ceiling(3, BASE, MAX),
+            Duration::from_millis(80)
+        );
+        assert_eq!(ConnectBackoff::ceiling(10, BASE, MAX), MAX);
+        assert_eq!(ConnectBackoff::ceiling(u32::MAX, BASE, MAX), MAX);
+    }
+
+    #[test]
+    fn delay_stays_within_jitter_window() {
+        let backoff = ConnectBackoff::new();
+        for streak in 0..12 {
+            let ceiling = ConnectBackoff::ceiling(streak, BASE, MAX);
+            let delay = backoff.on_failure(BASE, MAX);
+            assert!(delay >= ceiling / 2 && delay <= ceiling, "{delay:?}");
+        }
+        assert_eq!(backoff.failures(), 12);
+    }
+
+    #[test]
+    fn success_resets_streak() {
+        let backoff = ConnectBackoff::new();
+        for _ in 0..5 {
+            backoff.on_failure(BASE, MAX);
+        }
+        backoff.on_success();
+        assert_eq!(backoff.failures(), 0);
+        assert!(backoff.on_failure(BASE, MAX) <= BASE);
+    }
+}
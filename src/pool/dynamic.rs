@@ -14,6 +14,7 @@ use crate::config::{get_config, BackendAuthMethod, PoolMode, User};
 use crate::errors::Error;
 use crate::server::ServerParameters;
 use crate::stats::AddressStats;
+use crate::web::metrics::pool_prepared_cache_eviction_counter;
 
 use super::types::{PoolConfig, QueueMode, Timeouts};
 use super::{
@@ -91,10 +92,7 @@ pub fn create_dynamic_pool(
     let client_server_map = super::get_client_server_map()
         .ok_or_else(|| Error::AuthError("auth_query: client_server_map not initialized".into()))?;
 
-    let server_database = pool_config
-        .server_database
-        .clone()
-        .unwrap_or_else(|| pool_name.to_string());
+    let server_database = pool_config.resolve_server_database(pool_name).to_string();
 
     let ba_arc = backend_auth.map(|ba| Arc::new(parking_lot::RwLock::new(ba)));
     debug!(
@@ -132,12 +130,13 @@ pub fn create_dynamic_pool(
         ..Default::default()
     };
 
-    let prepared_statements_cache_size = match config.general.prepared_statements {
-        true => pool_config
-            .prepared_statements_cache_size
-            .unwrap_or(config.general.prepared_statements_cache_size),
-        false => 0,
-    };
+    let prepared_statements_cache_size =
+        match pool_config.resolve_prepared_statements(&config.general) {
+            true => pool_config
+                .prepared_statements_cache_size
+                .unwrap_or(config.general.prepared_statements_cache_size),
+            false => 0,
+        };
 
     let server_prepared_statements_cache_size = resolve_server_cache_size(
         prepared_statements_cache_size,
@@ -158,12 +157,8 @@ pub fn create_dynamic_pool(
     // snapshot. Dynamic auth_query pools follow the same lifecycle as
     // static pools: rebuilt on RELOAD when the underlying base changes
     // (see `general_startup_parameters_changed` in pool/mod.rs).
-    let base_startup_parameters = std::sync::Arc::new(
-        crate::config::startup_parameters::cascade_canonical_keys(&[
-            &config.general.startup_parameters,
-            &pool_config.startup_parameters,
-        ]),
-    );
+    let base_startup_parameters =
+        std::sync::Arc::new(pool_config.resolve_startup_parameters(&config.general));
 
     // Convert the caller's HashMap snapshot into the BTreeMap shape
     // ServerPool stores. The snapshot comes from the auth_query row used
@@ -195,7 +190,9 @@ pub fn create_dynamic_pool(
         pool_config.log_client_parameter_status_changes,
         server_prepared_statements_cache_size,
         application_name,
-        config.general.max_concurrent_creates,
+        pool_config
+            .max_concurrent_creates
+            .unwrap_or(config.general.max_concurrent_creates),
         pool_config
             .server_lifetime
             .unwrap_or(config.general.server_lifetime.as_millis()),
@@ -255,20 +252,38 @@ pub fn create_dynamic_pool(
             life_time_ms: pool_config
                 .server_lifetime
                 .unwrap_or(config.general.server_lifetime.as_millis()),
+            client_idle_timeout_ms: pool_config.client_idle_timeout.unwrap_or(0),
+            max_query_duration_ms: pool_config.max_query_duration.unwrap_or(0),
+            max_result_rows: pool_config.max_result_rows.unwrap_or(0),
+            server_max_age_ms: pool_config.server_max_age.unwrap_or(0),
             sync_server_parameters: config.general.sync_server_parameters,
             min_guaranteed_pool_size: pool_config.min_guaranteed_pool_size.unwrap_or(0),
+            reject_write_queries: pool_config.reject_write_queries,
+            retry_readonly_on_disconnect: pool_config.retry_readonly_on_disconnect,
+            prefer_last_server: pool_config.prefer_last_server,
+            allow_session_override: pool_config.allow_session_override,
+            scram_relay: pool_config.scram_relay,
+            client_label_gucs: pool_config.client_label_gucs,
+            default_client_statement_timeout_ms: pool_config
+                .default_client_statement_timeout_ms
+                .unwrap_or(0),
         },
-        prepared_statement_cache: match config.general.prepared_statements {
+        prepared_statement_cache: match pool_config.resolve_prepared_statements(&config.general) {
             false => None,
-            true => Some(Arc::new(PreparedStatementCache::new(
-                prepared_statements_cache_size,
-                config.general.worker_threads,
-            ))),
+            true => Some(Arc::new(
+                PreparedStatementCache::new(
+                    prepared_statements_cache_size,
+                    config.general.worker_threads,
+                )
+                .with_eviction_counter(pool_prepared_cache_eviction_counter(username, pool_name))
+                .with_eviction_logging(config.general.log_prepared_cache_evictions),
+            )),
         },
         check_query_cache: Arc::new(CheckQueryCache::new()),
         coordinator: get_coordinator(pool_name),
         replenish_failures: Arc::new(AtomicU32::new(0)),
         init_complete: Arc::new(AtomicBool::new(false)),
+        transaction_quota: None,
     };
 
     // Atomic insert into POOLS
@@ -170,8 +170,19 @@ mod tests {
                 db: "test_db".to_string(),
                 idle_timeout_ms: 60_000,
                 life_time_ms: 60_000,
+                client_idle_timeout_ms: 0,
+                max_query_duration_ms: 0,
+                max_result_rows: 0,
+                server_max_age_ms: 0,
                 sync_server_parameters: false,
                 min_guaranteed_pool_size: 0,
+                reject_write_queries: false,
+                retry_readonly_on_disconnect: false,
+                prefer_last_server: false,
+                allow_session_override: false,
+                scram_relay: false,
+                client_label_gucs: false,
+                default_client_statement_timeout_ms: 0,
             },
             config_hash: 0,
             per_user_startup_overlay_hash: crate::pool::empty_overlay_hash(),
@@ -180,6 +191,7 @@ mod tests {
             coordinator: None,
             replenish_failures: Arc::new(AtomicU32::new(0)),
             init_complete: Arc::new(AtomicBool::new(init_complete)),
+            transaction_quota: None,
         }
     }
 
@@ -20,9 +20,12 @@ use tokio::sync::{oneshot, Notify, Semaphore, SemaphorePermit, TryAcquireError};
 
 use super::errors::{PoolError, RecycleError, TimeoutType};
 use super::pool_coordinator;
-use super::types::{Metrics, PoolConfig, QueueMode, Status, Timeouts};
+use super::types::{Metrics, PoolConfig, QueueMode, ScalingConfig, Status, Timeouts};
+use super::wait_queue::WaitQueue;
 use super::ServerPool;
-use crate::server::Server;
+use crate::config::config_arc;
+use crate::errors::{Error, ServerIdentifier};
+use crate::server::{Server, ServerCloseCause};
 
 const MAX_FAST_RETRY: i32 = 10;
 
@@ -91,6 +94,13 @@ impl Drop for Object {
     }
 }
 
+impl Object {
+    /// When the underlying server connection was opened.
+    pub fn created_at(&self) -> quanta::Instant {
+        self.inner.as_ref().unwrap().metrics.created
+    }
+}
+
 impl Deref for Object {
     type Target = Server;
     fn deref(&self) -> &Self::Target {
@@ -215,6 +225,12 @@ struct PoolInner {
     /// Number of users currently holding or waiting for objects.
     users: AtomicUsize,
     semaphore: Semaphore,
+    /// Clients that missed the semaphore fast path, in user priority order.
+    /// Only used when `prioritized`.
+    wait_queue: WaitQueue,
+    /// Some user of this pool has a non-zero `priority`. Otherwise waiting
+    /// clients queue on the semaphore alone, which is FIFO already.
+    prioritized: bool,
     config: PoolConfig,
     /// Database-level coordinator (None when max_db_connections = 0).
     coordinator: Option<Arc<pool_coordinator::PoolCoordinator>>,
@@ -316,6 +332,16 @@ fn burst_gate_budget(xact_p99_us: u64) -> Duration {
     Duration::from_millis(budget_ms)
 }
 
+/// Whether a checkout-time connect failure looks like a short backend
+/// blip (restart, failover) worth retrying under `server_connect_hold`.
+/// A create timeout is not: a backend that hangs is not coming back soon.
+fn holds_through_blip(err: &PoolError) -> bool {
+    matches!(
+        err,
+        PoolError::Backend(Error::ConnectError(_) | Error::ServerUnavailableError(_, _))
+    )
+}
+
 /// Push a connection into the idle queue respecting the configured
 /// queue mode (FIFO/LIFO). Caller must hold the slots lock.
 #[inline(always)]
@@ -470,19 +496,47 @@ impl PoolInner {
         timeouts: &Timeouts,
         coordinator_permit: Option<pool_coordinator::CoordinatorPermit>,
     ) -> Result<ObjectInner, PoolError> {
-        let obj = match timeouts.create {
-            Some(duration) => {
-                match tokio::time::timeout(duration, self.server_pool.create()).await {
-                    Ok(Ok(obj)) => obj,
-                    Ok(Err(e)) => return Err(PoolError::Backend(e)),
-                    Err(_) => return Err(PoolError::Timeout(TimeoutType::Create)),
+        // Refused or unavailable connects are retried until
+        // `server_connect_hold` has passed since the first failure, so a
+        // restart or failover shorter than that never reaches the client.
+        // `ServerPool::create` backs off before returning each failure.
+        let connect_hold = config_arc().general.server_connect_hold.as_std();
+        let mut held_since = None;
+        let obj = loop {
+            let result = match timeouts.create {
+                Some(duration) => {
+                    match tokio::time::timeout(duration, self.server_pool.create()).await {
+                        Ok(result) => result.map_err(PoolError::Backend),
+                        Err(_) => Err(PoolError::Timeout(TimeoutType::Create)),
+                    }
                 }
+                None => self.server_pool.create().await.map_err(PoolError::Backend),
+            };
+            let err = match result {
+                Ok(obj) => {
+                    if let Some(since) = held_since {
+                        log::info!(
+                            "[{}@{}] backend reachable again, checkout held for {}ms",
+                            self.username,
+                            self.pool_name,
+                            since.elapsed().as_millis(),
+                        );
+                    }
+                    break obj;
+                }
+                Err(err) => err,
+            };
+            if !holds_through_blip(&err) || self.server_pool.is_auto_paused() {
+                return Err(err);
             }
-            None => self
-                .server_pool
-                .create()
-                .await
-                .map_err(PoolError::Backend)?,
+            let since = *held_since.get_or_insert_with(tokio::time::Instant::now);
+            if since.elapsed() >= connect_hold {
+                return Err(err);
+            }
+            debug!(
+                "[{}@{}] holding checkout through backend failure: {}",
+                self.username, self.pool_name, err
+            );
         };
 
         {
@@ -508,7 +562,34 @@ impl PoolInner {
             let mut slots = self.slots.lock();
             slots.vec.pop_front()
         };
+        self.recycle_idle(obj_inner, timeouts).await
+    }
+
+    /// Like [`Self::try_recycle_one`], but takes the idle connection with
+    /// `server_id` when it is in the queue (`prefer_last_server`). A busy
+    /// or closed preferred server is not waited for: the head of the queue
+    /// is taken instead.
+    async fn try_recycle_preferred(&self, timeouts: &Timeouts, server_id: i32) -> RecycleOutcome {
+        let obj_inner = {
+            let mut slots = self.slots.lock();
+            match slots
+                .vec
+                .iter()
+                .position(|inner| inner.obj.stats.server_id() == server_id)
+            {
+                Some(index) => slots.vec.remove(index),
+                None => slots.vec.pop_front(),
+            }
+        };
+        self.recycle_idle(obj_inner, timeouts).await
+    }
 
+    /// Recycles a connection just taken off the idle queue.
+    async fn recycle_idle(
+        &self,
+        obj_inner: Option<ObjectInner>,
+        timeouts: &Timeouts,
+    ) -> RecycleOutcome {
         let Some(mut inner) = obj_inner else {
             return RecycleOutcome::Empty;
         };
@@ -828,6 +909,23 @@ impl Pool {
         }
     }
 
+    /// Turn the checkout away while `auto_pause_on_unhealthy` has paused
+    /// the pool: waiting would only end in `query_wait_timeout`.
+    fn fail_if_auto_paused(&self) -> Result<(), PoolError> {
+        if !self.inner.server_pool.is_auto_paused() {
+            return Ok(());
+        }
+        let address = self.inner.server_pool.address();
+        Err(PoolError::Backend(Error::ServerUnavailableError(
+            "pool auto-paused after consecutive backend failures".into(),
+            ServerIdentifier::new(
+                address.username.clone(),
+                &address.database,
+                &address.pool_name,
+            ),
+        )))
+    }
+
     /// Block if the pool is paused, waiting for resume or timeout.
     ///
     /// IMPORTANT: `resume_notified()` must be called BEFORE `is_paused()`
@@ -848,10 +946,12 @@ impl Pool {
         Ok(())
     }
 
-    /// Acquire a semaphore permit: fast spin path, then blocking fallback.
+    /// Acquire a semaphore permit: fast spin path, then blocking fallback
+    /// through the priority wait queue on prioritized pools.
     async fn acquire_semaphore(
         &self,
         timeouts: &Timeouts,
+        priority: u8,
     ) -> Result<SemaphorePermit<'_>, PoolError> {
         let mut try_fast = 0;
         loop {
@@ -876,23 +976,52 @@ impl Pool {
             } else {
                 match timeouts.wait {
                     Some(duration) => {
-                        match tokio::time::timeout(duration, self.inner.semaphore.acquire()).await {
-                            Ok(Ok(p)) => Ok(p),
-                            Ok(Err(_)) => Err(PoolError::Closed),
+                        match tokio::time::timeout(duration, self.acquire_waiting(priority)).await {
+                            Ok(result) => result,
                             Err(_) => Err(PoolError::Timeout(TimeoutType::Wait)),
                         }
                     }
-                    None => self
-                        .inner
-                        .semaphore
-                        .acquire()
-                        .await
-                        .map_err(|_| PoolError::Closed),
+                    None => self.acquire_waiting(priority).await,
                 }
             };
         }
     }
 
+    /// Blocking part of [`Self::acquire_semaphore`].
+    async fn acquire_waiting(&self, priority: u8) -> Result<SemaphorePermit<'_>, PoolError> {
+        if !self.inner.prioritized {
+            return self
+                .inner
+                .semaphore
+                .acquire()
+                .await
+                .map_err(|_| PoolError::Closed);
+        }
+        self.acquire_queued(priority).await
+    }
+
+    /// Waits in the priority queue until this client is its head, then for
+    /// the next freed permit. Only the head waits on the semaphore, so a
+    /// higher-priority arrival takes over the head position and the permit.
+    async fn acquire_queued(&self, priority: u8) -> Result<SemaphorePermit<'_>, PoolError> {
+        let ticket = self.inner.wait_queue.enter(priority);
+        loop {
+            if ticket.is_head() {
+                tokio::select! {
+                    // A permit granted after losing the head position goes
+                    // back to the semaphore for the new head.
+                    biased;
+                    _ = ticket.changed() => {}
+                    permit = self.inner.semaphore.acquire() => {
+                        return permit.map_err(|_| PoolError::Closed);
+                    }
+                }
+            } else {
+                ticket.changed().await;
+            }
+        }
+    }
+
     /// Anticipation zone: warm threshold gate, fast spin, and direct
     /// handoff via oneshot channel. Returns `Some(ObjectInner)` if a
     /// recycled connection was obtained, `None` to proceed to the create
@@ -1012,6 +1141,8 @@ impl Pool {
                 }),
                 users: AtomicUsize::new(0),
                 semaphore: Semaphore::new(builder.config.max_size),
+                wait_queue: WaitQueue::new(),
+                prioritized: builder.prioritized,
                 config: builder.config,
                 coordinator: builder.coordinator,
                 pool_name: builder.pool_name,
@@ -1027,11 +1158,37 @@ impl Pool {
     /// Retrieves an Object from this Pool or waits for one to become available.
     #[inline(always)]
     pub async fn get(&self) -> Result<Object, PoolError> {
-        self.timeout_get(&self.timeouts()).await
+        self.timeout_get(&self.timeouts(), 0, None).await
+    }
+
+    /// Like [`Self::get`], but while the pool is saturated a higher
+    /// `priority` is served ahead of waiting lower-priority clients.
+    #[inline(always)]
+    pub async fn get_with_priority(&self, priority: u8) -> Result<Object, PoolError> {
+        self.timeout_get(&self.timeouts(), priority, None).await
+    }
+
+    /// Like [`Self::get_with_priority`], but hands out the idle connection
+    /// with `server_id` if there is one. Soft affinity: when that server is
+    /// checked out or gone, any other connection is returned as usual.
+    #[inline(always)]
+    pub async fn get_preferring(
+        &self,
+        priority: u8,
+        server_id: Option<i32>,
+    ) -> Result<Object, PoolError> {
+        self.timeout_get(&self.timeouts(), priority, server_id)
+            .await
     }
 
     /// Retrieves an Object from this Pool using a different timeout than the configured one.
-    pub async fn timeout_get(&self, timeouts: &Timeouts) -> Result<Object, PoolError> {
+    /// `preferred_server` is tried first among the idle connections.
+    pub async fn timeout_get(
+        &self,
+        timeouts: &Timeouts,
+        priority: u8,
+        preferred_server: Option<i32>,
+    ) -> Result<Object, PoolError> {
         self.inner.users.fetch_add(1, Ordering::Relaxed);
         scopeguard::defer! {
             self.inner.users.fetch_sub(1, Ordering::Relaxed);
@@ -1039,18 +1196,24 @@ impl Pool {
 
         let start = tokio::time::Instant::now();
 
+        self.fail_if_auto_paused()?;
         self.wait_if_paused(timeouts).await?;
-        let permit = self.acquire_semaphore(timeouts).await.inspect_err(|_e| {
+        let permit = self.acquire_semaphore(timeouts, priority).await.inspect_err(|_e| {
             let slots = self.inner.slots.lock();
             warn!(
-                "[{}@{}] checkout timeout at phase=semaphore elapsed={}ms size={} max={} waiters={} semaphore_avail={}",
+                "[{}@{}] checkout timeout at phase=semaphore elapsed={}ms size={} max={} waiters={} queued={} semaphore_avail={}",
                 self.inner.pool_name, self.inner.username,
                 start.elapsed().as_millis(), slots.size, slots.max_size,
-                slots.waiters.len(), self.inner.semaphore.available_permits(),
+                slots.waiters.len(), self.inner.wait_queue.len(),
+                self.inner.semaphore.available_permits(),
             );
         })?;
 
-        if let RecycleOutcome::Reused(inner) = self.inner.try_recycle_one(timeouts).await {
+        let first = match preferred_server {
+            Some(server_id) => self.inner.try_recycle_preferred(timeouts, server_id).await,
+            None => self.inner.try_recycle_one(timeouts).await,
+        };
+        if let RecycleOutcome::Reused(inner) = first {
             self.maybe_trigger_pre_replacement(&inner.metrics);
             return Ok(self.wrap_checkout(*inner, permit));
         }
@@ -1161,7 +1324,9 @@ impl Pool {
     /// `CoordinatorPermit::drop` (a tokio `Notify::notify_one` that itself
     /// briefly takes an internal mutex). Holding `slots.lock()` across these
     /// blocks any peer caller trying to recycle from the same pool.
-    pub fn retain(&self, f: impl Fn(&Server, Metrics) -> bool) {
+    ///
+    /// `cause` is recorded on every evicted connection.
+    pub fn retain(&self, cause: ServerCloseCause, f: impl Fn(&Server, Metrics) -> bool) {
         let evicted: Vec<ObjectInner> = {
             let mut guard = self.inner.slots.lock();
             // Common case on a healthy retain cycle: nothing to evict.
@@ -1171,10 +1336,11 @@ impl Pool {
             }
             let mut keep = VecDeque::with_capacity(guard.vec.capacity());
             let mut evicted = Vec::new();
-            for obj in guard.vec.drain(..) {
+            for mut obj in guard.vec.drain(..) {
                 if f(&obj.obj, obj.metrics) {
                     keep.push_back(obj);
                 } else {
+                    obj.obj.set_close_cause(cause);
                     evicted.push(obj);
                 }
             }
@@ -1194,9 +1360,12 @@ impl Pool {
     /// As with [`retain`], evicted objects are extracted under the lock and
     /// dropped only after the lock is released, so peer callers do not block
     /// on PG `Terminate` syscalls or coordinator wake-ups.
+    ///
+    /// `should_close` returns the close cause for connections to evict and
+    /// `None` for connections to keep.
     pub fn retain_oldest_first(
         &self,
-        should_close: impl Fn(&Server, &Metrics) -> bool,
+        should_close: impl Fn(&Server, &Metrics) -> Option<ServerCloseCause>,
         max_to_close: usize,
     ) -> usize {
         let evicted: Vec<ObjectInner> = {
@@ -1209,15 +1378,16 @@ impl Pool {
                 if !guard
                     .vec
                     .iter()
-                    .any(|obj| should_close(&obj.obj, &obj.metrics))
+                    .any(|obj| should_close(&obj.obj, &obj.metrics).is_some())
                 {
                     return 0;
                 }
                 // Unlimited — partition every matching object out of the vec.
                 let mut keep = VecDeque::with_capacity(guard.vec.capacity());
                 let mut evicted = Vec::new();
-                for obj in guard.vec.drain(..) {
-                    if should_close(&obj.obj, &obj.metrics) {
+                for mut obj in guard.vec.drain(..) {
+                    if let Some(cause) = should_close(&obj.obj, &obj.metrics) {
+                        obj.obj.set_close_cause(cause);
                         evicted.push(obj);
                     } else {
                         keep.push_back(obj);
@@ -1228,13 +1398,15 @@ impl Pool {
                 evicted
             } else {
                 // Pre-walk to identify the oldest `max_to_close` candidates.
-                // We do not extract here — only collect (index, age) pairs.
-                let mut candidates: Vec<(usize, u128)> = guard
+                // We do not extract here — only collect (index, age, cause).
+                let mut candidates: Vec<(usize, u128, ServerCloseCause)> = guard
                     .vec
                     .iter()
                     .enumerate()
-                    .filter(|(_, obj)| should_close(&obj.obj, &obj.metrics))
-                    .map(|(idx, obj)| (idx, obj.metrics.age().as_millis()))
+                    .filter_map(|(idx, obj)| {
+                        should_close(&obj.obj, &obj.metrics)
+                            .map(|cause| (idx, obj.metrics.age().as_millis(), cause))
+                    })
                     .collect();
 
                 if candidates.is_empty() {
@@ -1244,16 +1416,17 @@ impl Pool {
                 // Sort by age descending (oldest first — highest age value)
                 candidates.sort_by(|a, b| b.1.cmp(&a.1));
 
-                let to_close: std::collections::HashSet<usize> = candidates
+                let to_close: std::collections::HashMap<usize, ServerCloseCause> = candidates
                     .into_iter()
                     .take(max_to_close)
-                    .map(|(idx, _)| idx)
+                    .map(|(idx, _, cause)| (idx, cause))
                     .collect();
 
                 let mut keep = VecDeque::with_capacity(guard.vec.capacity());
                 let mut evicted = Vec::with_capacity(to_close.len());
-                for (idx, obj) in guard.vec.drain(..).enumerate() {
-                    if to_close.contains(&idx) {
+                for (idx, mut obj) in guard.vec.drain(..).enumerate() {
+                    if let Some(cause) = to_close.get(&idx) {
+                        obj.obj.set_close_cause(*cause);
                         evicted.push(obj);
                     } else {
                         keep.push_back(obj);
@@ -1279,7 +1452,10 @@ impl Pool {
     /// Returns `true` if a connection was evicted.
     pub fn evict_one_idle(&self, min_lifetime_ms: u64) -> bool {
         self.retain_oldest_first(
-            |_, metrics| metrics.age().as_millis() >= u128::from(min_lifetime_ms),
+            |_, metrics| {
+                (metrics.age().as_millis() >= u128::from(min_lifetime_ms))
+                    .then_some(ServerCloseCause::Evicted)
+            },
             1,
         ) > 0
     }
@@ -1362,7 +1538,7 @@ impl Pool {
             }
             let mut keep = VecDeque::with_capacity(guard.vec.capacity());
             let mut evicted = Vec::new();
-            for obj in guard.vec.drain(..) {
+            for mut obj in guard.vec.drain(..) {
                 let is_reserve = obj
                     .coordinator_permit
                     .as_ref()
@@ -1376,6 +1552,7 @@ impl Pool {
                 if idle < u128::from(min_lifetime_ms) {
                     keep.push_back(obj);
                 } else {
+                    obj.obj.set_close_cause(ServerCloseCause::Idle);
                     evicted.push(obj);
                 }
             }
@@ -1395,6 +1572,12 @@ impl Pool {
         self.inner.config.timeouts
     }
 
+    /// Get the scaling configuration this pool was built with.
+    #[inline(always)]
+    pub fn scaling(&self) -> ScalingConfig {
+        self.inner.config.scaling
+    }
+
     /// Creates new connections to bring the pool up to the desired count.
     /// Returns the number of connections successfully created.
     /// Stops on the first creation failure to avoid hammering a failing server.
@@ -1535,9 +1718,28 @@ impl Pool {
         self.inner.server_pool.pause();
     }
 
-    /// Resumes the pool — unblocks waiting clients.
+    /// Resumes the pool — unblocks waiting clients and ends an auto-pause.
     pub fn resume(&self) {
         self.inner.server_pool.resume();
+        self.inner.server_pool.clear_auto_pause();
+    }
+
+    /// Returns whether `auto_pause_on_unhealthy` has paused the pool.
+    pub fn is_auto_paused(&self) -> bool {
+        self.inner.server_pool.is_auto_paused()
+    }
+
+    /// One connect attempt against the backend of an auto-paused pool. Its
+    /// outcome feeds the health streak that resumes the pool; a connection
+    /// that succeeds joins the pool. At most one probe runs per pool.
+    pub async fn probe_backend(&self) {
+        if !self.inner.server_pool.try_start_backend_probe() {
+            return;
+        }
+        scopeguard::defer! {
+            self.inner.server_pool.finish_backend_probe();
+        }
+        self.replenish(1).await;
     }
 
     /// Returns whether the pool is paused.
@@ -1569,7 +1771,7 @@ impl Pool {
     pub fn reconnect(&self) -> u32 {
         let new_epoch = self.inner.server_pool.bump_epoch();
         // Drain all idle connections — they have the old epoch
-        self.retain(|_, _| false);
+        self.retain(ServerCloseCause::Reconnect, |_, _| false);
         new_epoch
     }
 
@@ -1709,6 +1911,7 @@ pub struct PoolBuilder {
     coordinator: Option<Arc<pool_coordinator::PoolCoordinator>>,
     pool_name: String,
     username: String,
+    prioritized: bool,
 }
 
 impl PoolBuilder {
@@ -1719,6 +1922,7 @@ impl PoolBuilder {
             coordinator: None,
             pool_name: String::new(),
             username: String::new(),
+            prioritized: false,
         }
     }
 
@@ -1749,6 +1953,13 @@ impl PoolBuilder {
         self
     }
 
+    /// Orders waiting clients by user `priority`. Leave it off when every
+    /// user of the pool has the same priority.
+    pub fn prioritized(mut self, prioritized: bool) -> Self {
+        self.prioritized = prioritized;
+        self
+    }
+
     /// Builds the Pool.
     pub fn build(self) -> Pool {
         Pool::from_builder(self)
@@ -1803,6 +2014,23 @@ mod tests {
     use super::*;
     use std::time::Duration;
 
+    #[test]
+    fn connect_hold_retries_only_backend_blips() {
+        let id = ServerIdentifier::new("u".to_string(), "d", "p");
+        assert!(holds_through_blip(&PoolError::Backend(
+            Error::ConnectError("connection refused".into())
+        )));
+        assert!(holds_through_blip(&PoolError::Backend(
+            Error::ServerUnavailableError("the database system is starting up".into(), id.clone())
+        )));
+        assert!(!holds_through_blip(&PoolError::Timeout(
+            TimeoutType::Create
+        )));
+        assert!(!holds_through_blip(&PoolError::Backend(
+            Error::ServerAuthError("password authentication failed".into(), id)
+        )));
+    }
+
     // ------------------------------------------------------------------
     // BurstGateGuard — RAII burst gate slot
     // ------------------------------------------------------------------
@@ -2080,6 +2308,10 @@ mod tests {
     /// real backend connection — it only exercises the in-memory notify
     /// machinery on the resulting `PoolInner`.
     fn test_pool_with_coordinator(coord: Arc<pool_coordinator::PoolCoordinator>) -> Pool {
+        test_pool_builder(coord).build()
+    }
+
+    fn test_pool_builder(coord: Arc<pool_coordinator::PoolCoordinator>) -> PoolBuilder {
         use crate::config::{Address, User};
         use dashmap::DashMap;
 
@@ -2107,7 +2339,6 @@ mod tests {
             .coordinator(Some(coord))
             .pool_name("test_db".to_string())
             .username("test_user".to_string())
-            .build()
     }
 
     /// `notify_return_observers` wakes the peer-pool coordinator Phase C
@@ -2318,6 +2549,131 @@ mod tests {
         );
     }
 
+    /// With every permit taken, a priority client that queued after a
+    /// default client still gets the next freed permit first; the default
+    /// client follows once the priority client is done.
+    #[tokio::test]
+    async fn priority_waiter_gets_next_freed_permit() {
+        let coord = pool_coordinator::PoolCoordinator::new(
+            "test_db".to_string(),
+            pool_coordinator::CoordinatorConfig {
+                max_db_connections: 0,
+                min_connection_lifetime_ms: 0,
+                reserve_pool_size: 0,
+                reserve_pool_timeout_ms: 0,
+            },
+        );
+        let pool = test_pool_builder(coord).prioritized(true).build();
+        let total_permits = pool.inner.semaphore.available_permits();
+        let mut held = Vec::with_capacity(total_permits);
+        for _ in 0..total_permits {
+            held.push(pool.inner.semaphore.acquire().await.unwrap());
+        }
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let timeouts = Timeouts {
+            wait: Some(Duration::from_secs(5)),
+            ..Timeouts::default()
+        };
+        for (queued, (name, priority)) in [("default", 0u8), ("priority", 10u8)]
+            .into_iter()
+            .enumerate()
+        {
+            let waiter_pool = pool.clone();
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                let _permit = waiter_pool
+                    .acquire_semaphore(&timeouts, priority)
+                    .await
+                    .unwrap();
+                tx.send(name).unwrap();
+            });
+            // Let the waiter get through the fast path and into the queue.
+            while pool.inner.wait_queue.len() <= queued {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        }
+
+        held.pop();
+        assert_eq!(rx.recv().await, Some("priority"));
+        assert_eq!(rx.recv().await, Some("default"));
+    }
+
+    /// A client that disconnects while queued drops its checkout; the
+    /// pool must stop counting it as waiting and forget its queue ticket.
+    #[tokio::test]
+    async fn dropped_checkout_leaves_wait_count_and_queue() {
+        let coord = pool_coordinator::PoolCoordinator::new(
+            "test_db".to_string(),
+            pool_coordinator::CoordinatorConfig {
+                max_db_connections: 0,
+                min_connection_lifetime_ms: 0,
+                reserve_pool_size: 0,
+                reserve_pool_timeout_ms: 0,
+            },
+        );
+        let pool = test_pool_builder(coord).prioritized(true).build();
+        let total_permits = pool.inner.semaphore.available_permits();
+        let mut held = Vec::with_capacity(total_permits);
+        for _ in 0..total_permits {
+            held.push(pool.inner.semaphore.acquire().await.unwrap());
+        }
+
+        let waiter_pool = pool.clone();
+        let waiter = tokio::spawn(async move { waiter_pool.get_with_priority(0).await });
+        while pool.inner.wait_queue.len() == 0 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        assert_eq!(pool.status().waiting, 1);
+
+        waiter.abort();
+        let _ = waiter.await;
+        assert_eq!(pool.status().waiting, 0);
+        assert_eq!(pool.inner.wait_queue.len(), 0);
+        assert_eq!(pool.inner.semaphore.available_permits(), 0);
+
+        drop(held);
+        assert_eq!(pool.inner.semaphore.available_permits(), total_permits);
+    }
+
+    /// Without a prioritized user, a waiting client skips the priority
+    /// queue and waits on the semaphore directly.
+    #[tokio::test]
+    async fn unprioritized_pool_waits_on_semaphore() {
+        let coord = pool_coordinator::PoolCoordinator::new(
+            "test_db".to_string(),
+            pool_coordinator::CoordinatorConfig {
+                max_db_connections: 0,
+                min_connection_lifetime_ms: 0,
+                reserve_pool_size: 0,
+                reserve_pool_timeout_ms: 0,
+            },
+        );
+        let pool = test_pool_with_coordinator(coord);
+        let total_permits = pool.inner.semaphore.available_permits();
+        let mut held = Vec::with_capacity(total_permits);
+        for _ in 0..total_permits {
+            held.push(pool.inner.semaphore.acquire().await.unwrap());
+        }
+
+        let timeouts = Timeouts {
+            wait: Some(Duration::from_secs(5)),
+            ..Timeouts::default()
+        };
+        let waiter_pool = pool.clone();
+        let waiter = tokio::spawn(async move {
+            waiter_pool
+                .acquire_semaphore(&timeouts, 0)
+                .await
+                .map(|permit| permit.forget())
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(pool.inner.wait_queue.len(), 0);
+
+        held.pop();
+        assert!(waiter.await.unwrap().is_ok());
+    }
+
     // ------------------------------------------------------------------
     // Direct handoff — oneshot channel mechanics
     // ------------------------------------------------------------------
@@ -17,6 +17,7 @@ use crate::messages::Parse;
 use crate::server::ServerParameters;
 use crate::stats::auth_query::AuthQueryStats;
 use crate::stats::AddressStats;
+use crate::web::metrics::pool_prepared_cache_eviction_counter;
 
 mod errors;
 mod inner;
@@ -29,15 +30,20 @@ pub use types::{Metrics, PoolConfig, QueueMode, ScalingConfig, Status, Timeouts}
 pub use crate::server::PreparedStatementCache;
 
 mod auth_query_state;
+mod backend_health;
 mod check_query_cache;
+mod connect_backoff;
 mod dynamic;
 mod eviction;
 pub mod gc;
 mod init_guard;
 pub mod pool_coordinator;
+pub mod query_limit;
 pub mod retain;
 mod server_pool;
 pub mod startup_resolver;
+pub mod target_session;
+mod wait_queue;
 
 pub mod fallback;
 
@@ -258,7 +264,8 @@ impl Display for PoolIdentifier {
 
 impl From<&Address> for PoolIdentifier {
     fn from(address: &Address) -> PoolIdentifier {
-        PoolIdentifier::new(&address.database, &address.username)
+        // Pools are keyed by the client-facing name, not `server_database`.
+        PoolIdentifier::new(&address.pool_name, &address.username)
     }
 }
 
@@ -278,9 +285,47 @@ pub struct PoolSettings {
     idle_timeout_ms: u64,
     life_time_ms: u64,
 
+    /// Close clients idle outside a transaction longer than this (0 = disabled).
+    pub client_idle_timeout_ms: u64,
+
+    /// Cancel a single query running longer than this (0 = disabled).
+    pub max_query_duration_ms: u64,
+
+    /// Cancel a query after this many result rows in one round trip (0 = disabled).
+    pub max_result_rows: u64,
+
+    /// Close a server connection older than this at the next transaction
+    /// boundary, busy or not (0 = disabled).
+    pub server_max_age_ms: u64,
+
     /// Pool-level minimum connections protected from coordinator eviction.
     /// Effective protection = max(user.min_pool_size, this value).
     pub min_guaranteed_pool_size: u32,
+
+    /// Refuse obvious write statements before checkout (`read_only` pools).
+    pub reject_write_queries: bool,
+
+    /// Re-run a single read-only simple query once on a new server
+    /// connection when the first one breaks before answering.
+    pub retry_readonly_on_disconnect: bool,
+
+    /// Hand a client the server connection of its previous transaction
+    /// when that one is idle (soft affinity).
+    pub prefer_last_server: bool,
+
+    /// Clients may ask for session mode in a transaction-mode pool.
+    pub allow_session_override: bool,
+
+    /// Each client logs in through its own backend connection, which
+    /// serves its whole session.
+    pub scram_relay: bool,
+
+    /// Set `pg_doorman.client_addr` / `pg_doorman.client_tag` on checkout.
+    pub client_label_gucs: bool,
+
+    /// `statement_timeout` given to clients without their own; checkout
+    /// syncs it even without `sync_server_parameters` (0 = disabled).
+    pub default_client_statement_timeout_ms: u64,
 }
 
 impl Default for PoolSettings {
@@ -291,8 +336,19 @@ impl Default for PoolSettings {
             db: String::default(),
             idle_timeout_ms: General::default_idle_timeout().as_millis(),
             life_time_ms: General::default_server_lifetime().as_millis(),
+            client_idle_timeout_ms: 0,
+            max_query_duration_ms: 0,
+            max_result_rows: 0,
+            server_max_age_ms: 0,
             sync_server_parameters: General::default_sync_server_parameters(),
             min_guaranteed_pool_size: 0,
+            reject_write_queries: false,
+            retry_readonly_on_disconnect: false,
+            prefer_last_server: false,
+            allow_session_override: false,
+            scram_relay: false,
+            client_label_gucs: false,
+            default_client_statement_timeout_ms: 0,
         }
     }
 }
@@ -352,6 +408,11 @@ pub struct ConnectionPool {
     /// still establishing its first connection cannot be reaped while
     /// `pool_state().size` is still zero.
     pub(crate) init_complete: Arc<AtomicBool>,
+
+    /// The user's `max_concurrent_transactions` quota. Each user has its
+    /// own, also when `share_server_connections` puts several users on one
+    /// server pool.
+    pub(crate) transaction_quota: Option<Arc<query_limit::QueryLimiter>>,
 }
 
 impl ConnectionPool {
@@ -440,211 +501,288 @@ impl ConnectionPool {
             };
             let server_tls_config = build_server_tls_for_pool(pool_config, &config.general)?;
 
-            // There is one pool per database/user pair.
+            // There is one pool per database/user pair, plus one per shard.
+            // With `share_server_connections`, users with the same server
+            // credentials get their own entry but one underlying server pool.
+            let backends = pool_config.backends(pool_name);
+            let mut shared_pools: HashMap<(String, &str, &str), ConnectionPool> = HashMap::new();
             for user in &pool_config.users {
-                let old_pool_ref = get_pool(pool_name, &user.username);
-                let identifier = PoolIdentifier::new(pool_name, &user.username);
-
-                if let Some(pool) = old_pool_ref {
-                    // If the pool hasn't changed, get existing reference and insert it into the new_pools.
-                    // We replace all pools at the end, but if the reference is kept, the pool won't get re-created (bb8).
-                    if pool.config_hash == new_pool_hash_value
-                        && pool.address.server_tls.as_ref() == server_tls_config.as_ref()
-                    {
-                        info!("[{}@{}] config unchanged", user.username, pool_name);
-                        new_pools.insert(identifier.clone(), pool.clone());
-                        continue;
-                    }
-                    if pool.config_hash == new_pool_hash_value
-                        && pool.address.server_tls.as_ref() != server_tls_config.as_ref()
-                    {
-                        info!(
-                            "[{}@{}] tls certificates changed on disk, recreating pool",
-                            user.username, pool_name
-                        );
+                // Pool-level server credentials stand in for users without
+                // their own.
+                let resolved_user = pool_config.resolve_user(user);
+                for backend in &backends {
+                    let pool_key = backend.pool_key.as_str();
+                    let old_pool_ref = get_pool(pool_key, &user.username);
+                    let identifier = PoolIdentifier::new(pool_key, &user.username);
+
+                    if let Some(pool) = old_pool_ref {
+                        // If the pool hasn't changed, get existing reference and insert it into the new_pools.
+                        // We replace all pools at the end, but if the reference is kept, the pool won't get re-created (bb8).
+                        if pool.config_hash == new_pool_hash_value
+                            && pool.address.server_tls.as_ref() == server_tls_config.as_ref()
+                        {
+                            info!("[{}@{}] config unchanged", user.username, pool_key);
+                            new_pools.insert(identifier.clone(), pool.clone());
+                            continue;
+                        }
+                        if pool.config_hash == new_pool_hash_value
+                            && pool.address.server_tls.as_ref() != server_tls_config.as_ref()
+                        {
+                            info!(
+                                "[{}@{}] tls certificates changed on disk, recreating pool",
+                                user.username, pool_key
+                            );
+                        }
                     }
-                }
 
-                info!("[{}@{}] creating pool", user.username, pool_name);
-
-                // real database name on postgresql server.
-                let server_database = pool_config
-                    .server_database
-                    .clone()
-                    .unwrap_or(pool_name.clone().to_string());
-
-                // Detect passthrough-eligible static users:
-                // server_password is None AND (server_username is None OR equals username)
-                let backend_auth = if user.server_password.is_none()
-                    && (user.server_username.is_none()
-                        || user.server_username.as_deref() == Some(&user.username))
-                {
-                    if user
-                        .password
-                        .starts_with(crate::messages::constants::MD5_PASSWORD_PREFIX)
-                    {
-                        info!(
-                            "[{}@{}] static passthrough: MD5 pass-the-hash",
-                            user.username, pool_name
-                        );
-                        Some(Arc::new(RwLock::new(BackendAuthMethod::Md5PassTheHash(
-                            user.password.clone(),
-                        ))))
-                    } else if user
-                        .password
-                        .starts_with(crate::messages::constants::SCRAM_SHA_256)
-                    {
+                    let share_key = pool_config.shared_server_key(user).map(
+                        |(server_user, server_password)| {
+                            (pool_key.to_string(), server_user, server_password)
+                        },
+                    );
+                    if let Some(shared) = share_key.as_ref().and_then(|key| shared_pools.get(key)) {
                         info!(
-                            "[{}@{}] static passthrough: SCRAM pending",
-                            user.username, pool_name
+                            "[{}@{}] sharing server connections with {}",
+                            user.username, pool_key, shared.settings.user.username
                         );
-                        Some(Arc::new(RwLock::new(BackendAuthMethod::ScramPending)))
-                    } else {
-                        None
+                        let mut pool = shared.clone();
+                        pool.settings.user = resolved_user.clone();
+                        pool.transaction_quota = user
+                            .max_concurrent_transactions
+                            .map(query_limit::QueryLimiter::new);
+                        new_pools.insert(identifier, pool);
+                        continue;
                     }
-                } else {
-                    None
-                };
 
-                let address = Address {
-                    database: pool_name.clone(),
-                    host: pool_config.server_host.clone(),
-                    port: pool_config.server_port,
-                    username: user.username.clone(),
-                    password: user.password.clone(),
-                    pool_name: pool_name.clone(),
-                    stats: Arc::new(AddressStats::default()),
-                    backend_auth,
-                    server_tls: server_tls_config.clone(),
-                };
+                    info!("[{}@{}] creating pool", user.username, pool_key);
 
-                let prepared_statements_cache_size = match config.general.prepared_statements {
-                    true => pool_config
-                        .prepared_statements_cache_size
-                        .unwrap_or(config.general.prepared_statements_cache_size),
-                    false => 0,
-                };
+                    // real database name on postgresql server.
+                    let server_database = backend.server_database.to_string();
 
-                let server_prepared_statements_cache_size = resolve_server_cache_size(
-                    prepared_statements_cache_size,
-                    pool_config.server_prepared_statements_cache_size,
-                    config.general.server_prepared_statements_cache_size,
-                );
+                    // Detect passthrough-eligible static users:
+                    // server_password is None AND (server_username is None OR equals username)
+                    let backend_auth = if resolved_user.server_password.is_none()
+                        && (resolved_user.server_username.is_none()
+                            || resolved_user.server_username.as_deref() == Some(&user.username))
+                    {
+                        if user
+                            .password
+                            .starts_with(crate::messages::constants::MD5_PASSWORD_PREFIX)
+                        {
+                            info!(
+                                "[{}@{}] static passthrough: MD5 pass-the-hash",
+                                user.username, pool_name
+                            );
+                            Some(Arc::new(RwLock::new(BackendAuthMethod::Md5PassTheHash(
+                                user.password.clone(),
+                            ))))
+                        } else if user
+                            .password
+                            .starts_with(crate::messages::constants::SCRAM_SHA_256)
+                        {
+                            info!(
+                                "[{}@{}] static passthrough: SCRAM pending",
+                                user.username, pool_name
+                            );
+                            Some(Arc::new(RwLock::new(BackendAuthMethod::ScramPending)))
+                        } else {
+                            None
+                        }
+                    } else {
+                        None
+                    };
+
+                    let address = Address {
+                        database: pool_name.clone(),
+                        host: backend.host.to_string(),
+                        port: backend.port,
+                        username: user.username.clone(),
+                        password: user.password.clone(),
+                        pool_name: pool_name.clone(),
+                        stats: Arc::new(AddressStats::default()),
+                        backend_auth,
+                        server_tls: server_tls_config.clone(),
+                    };
+
+                    let prepared_statements_cache_size =
+                        match pool_config.resolve_prepared_statements(&config.general) {
+                            true => pool_config
+                                .prepared_statements_cache_size
+                                .unwrap_or(config.general.prepared_statements_cache_size),
+                            false => 0,
+                        };
 
-                let application_name = pool_config
-                    .application_name
-                    .clone()
-                    .unwrap_or_else(|| "pg_doorman".to_string());
-
-                let pool_mode = user.pool_mode.unwrap_or(pool_config.pool_mode);
-
-                let fallback_state = build_fallback_state(pool_name, pool_config, &config.general);
-
-                // Merge general+pool startup_parameters from the same
-                // `config` snapshot we hashed above. ServerPool keeps this
-                // as Arc<BTreeMap> for the rest of its life — the reload
-                // path rebuilds the pool whenever either layer's hash
-                // changes, so the snapshot stays valid until then. Passing
-                // it in explicitly (rather than letting ServerPool::new
-                // call config_arc() again) closes a narrow race where a
-                // second reload between this iteration and constructor
-                // execution would write a different baseline to the pool
-                // than the one the reuse hash captured.
-                let base_startup_parameters = Arc::new(
-                    crate::config::startup_parameters::cascade_canonical_keys(&[
-                        &config.general.startup_parameters,
-                        &pool_config.startup_parameters,
-                    ]),
-                );
+                    let server_prepared_statements_cache_size = resolve_server_cache_size(
+                        prepared_statements_cache_size,
+                        pool_config.server_prepared_statements_cache_size,
+                        config.general.server_prepared_statements_cache_size,
+                    );
 
-                let manager = ServerPool::new(
-                    address.clone(),
-                    user.clone(),
-                    server_database.as_str(),
-                    client_server_map.clone(),
-                    pool_config.cleanup_server_connections,
-                    pool_config.log_client_parameter_status_changes,
-                    server_prepared_statements_cache_size,
-                    application_name,
-                    config.general.max_concurrent_creates,
-                    pool_config
-                        .server_lifetime
-                        .unwrap_or(config.general.server_lifetime.as_millis()),
-                    pool_config
-                        .idle_timeout
-                        .unwrap_or(config.general.idle_timeout.as_millis()),
-                    config.general.server_idle_check_timeout.as_millis(),
-                    config.general.connect_timeout.as_std(),
-                    config.general.query_wait_timeout.as_std(),
-                    pool_mode == PoolMode::Session,
-                    fallback_state,
-                    base_startup_parameters,
-                    // Static pools carry no per-user auth_query overlay.
-                    Arc::new(std::collections::BTreeMap::new()),
-                );
+                    let application_name = pool_config
+                        .application_name
+                        .clone()
+                        .unwrap_or_else(|| "pg_doorman".to_string());
 
-                let queue_strategy = match config.general.server_round_robin {
-                    true => QueueMode::Fifo,
-                    false => QueueMode::Lifo,
-                };
+                    let pool_mode = user.pool_mode.unwrap_or(pool_config.pool_mode);
 
-                let mut builder_config = Pool::builder(manager)
-                    .coordinator(coordinators.get(pool_name).cloned())
-                    .pool_name(pool_name.clone())
-                    .username(user.username.clone());
-                builder_config = builder_config.config(PoolConfig {
-                    max_size: user.pool_size as usize,
-                    timeouts: Timeouts {
-                        wait: Some(config.general.query_wait_timeout.as_std()),
-                        create: Some(config.general.connect_timeout.as_std()),
-                        recycle: None,
-                    },
-                    queue_mode: queue_strategy,
-                    scaling: pool_config.resolve_scaling_config(&config.general),
-                });
-
-                let pool = builder_config.build();
-
-                let pool = ConnectionPool {
-                    database: pool,
-                    address,
-                    config_hash: new_pool_hash_value,
-                    // Static and dedicated-mode shared pools carry no
-                    // per-user overlay, so they pin to the empty-map
-                    // hash. Dynamic passthrough pools set this from the
-                    // captured overlay in dynamic.rs.
-                    per_user_startup_overlay_hash: empty_overlay_hash(),
-                    original_server_parameters: Arc::new(tokio::sync::Mutex::new(
-                        ServerParameters::new(),
-                    )),
-                    settings: PoolSettings {
-                        pool_mode,
-                        user: user.clone(),
-                        db: pool_name.clone(),
-                        idle_timeout_ms: pool_config
-                            .idle_timeout
-                            .unwrap_or(config.general.idle_timeout.as_millis()),
-                        life_time_ms: pool_config
+                    // Patroni fallback and the database-wide coordinator track the
+                    // pool's own server; shards are separate clusters and `hosts`
+                    // members are picked by role instead.
+                    let own_server = backend.shard.is_none() && !backend.cluster_host;
+                    let fallback_state = match own_server {
+                        true => build_fallback_state(pool_name, pool_config, &config.general),
+                        false => None,
+                    };
+                    let coordinator = match own_server {
+                        true => coordinators.get(pool_name).cloned(),
+                        false => None,
+                    };
+
+                    // Merge general+pool startup_parameters from the same
+                    // `config` snapshot we hashed above. ServerPool keeps this
+                    // as Arc<BTreeMap> for the rest of its life — the reload
+                    // path rebuilds the pool whenever either layer's hash
+                    // changes, so the snapshot stays valid until then. Passing
+                    // it in explicitly (rather than letting ServerPool::new
+                    // call config_arc() again) closes a narrow race where a
+                    // second reload between this iteration and constructor
+                    // execution would write a different baseline to the pool
+                    // than the one the reuse hash captured.
+                    let base_startup_parameters =
+                        Arc::new(pool_config.resolve_startup_parameters(&config.general));
+
+                    let manager = ServerPool::new(
+                        address.clone(),
+                        resolved_user.clone(),
+                        server_database.as_str(),
+                        client_server_map.clone(),
+                        pool_config.cleanup_server_connections,
+                        pool_config.log_client_parameter_status_changes,
+                        server_prepared_statements_cache_size,
+                        application_name,
+                        pool_config
+                            .max_concurrent_creates
+                            .unwrap_or(config.general.max_concurrent_creates),
+                        pool_config
                             .server_lifetime
                             .unwrap_or(config.general.server_lifetime.as_millis()),
-                        sync_server_parameters: config.general.sync_server_parameters,
-                        min_guaranteed_pool_size: pool_config.min_guaranteed_pool_size.unwrap_or(0),
-                    },
-                    prepared_statement_cache: match config.general.prepared_statements {
-                        false => None,
-                        true => Some(Arc::new(PreparedStatementCache::new(
-                            prepared_statements_cache_size,
-                            config.general.worker_threads,
-                        ))),
-                    },
-                    check_query_cache: Arc::new(CheckQueryCache::new()),
-                    coordinator: coordinators.get(pool_name).cloned(),
-                    replenish_failures: Arc::new(AtomicU32::new(0)),
-                    init_complete: Arc::new(AtomicBool::new(true)),
-                };
+                        pool_config
+                            .idle_timeout
+                            .unwrap_or(config.general.idle_timeout.as_millis()),
+                        config.general.server_idle_check_timeout.as_millis(),
+                        config.general.connect_timeout.as_std(),
+                        config.general.query_wait_timeout.as_std(),
+                        pool_mode == PoolMode::Session,
+                        fallback_state,
+                        base_startup_parameters,
+                        // Static pools carry no per-user auth_query overlay.
+                        Arc::new(std::collections::BTreeMap::new()),
+                    );
 
-                // There is one pool per database/user pair.
-                new_pools.insert(PoolIdentifier::new(pool_name, &user.username), pool);
+                    let queue_strategy = match config.general.server_round_robin {
+                        true => QueueMode::Fifo,
+                        false => QueueMode::Lifo,
+                    };
+
+                    let mut builder_config = Pool::builder(manager)
+                        .coordinator(coordinator.clone())
+                        .pool_name(pool_name.clone())
+                        .username(user.username.clone());
+                    let max_size = match &share_key {
+                        Some((_, server_user, server_password)) => {
+                            pool_config.shared_pool_size((*server_user, *server_password))
+                        }
+                        None => user.pool_size,
+                    };
+                    // Only users sharing one server pool can differ in priority.
+                    builder_config = builder_config.prioritized(share_key.as_ref().is_some_and(
+                        |(_, server_user, server_password)| {
+                            pool_config.shared_pool_prioritized((*server_user, *server_password))
+                        },
+                    ));
+                    builder_config = builder_config.config(PoolConfig {
+                        max_size: max_size as usize,
+                        timeouts: Timeouts {
+                            wait: Some(config.general.query_wait_timeout.as_std()),
+                            create: Some(config.general.connect_timeout.as_std()),
+                            recycle: None,
+                        },
+                        queue_mode: queue_strategy,
+                        scaling: pool_config.resolve_scaling_config(&config.general),
+                    });
+
+                    let pool = builder_config.build();
+
+                    let pool = ConnectionPool {
+                        database: pool,
+                        address,
+                        config_hash: new_pool_hash_value,
+                        // Static and dedicated-mode shared pools carry no
+                        // per-user overlay, so they pin to the empty-map
+                        // hash. Dynamic passthrough pools set this from the
+                        // captured overlay in dynamic.rs.
+                        per_user_startup_overlay_hash: empty_overlay_hash(),
+                        original_server_parameters: Arc::new(tokio::sync::Mutex::new(
+                            ServerParameters::new(),
+                        )),
+                        settings: PoolSettings {
+                            pool_mode,
+                            user: resolved_user.clone(),
+                            db: pool_name.clone(),
+                            idle_timeout_ms: pool_config
+                                .idle_timeout
+                                .unwrap_or(config.general.idle_timeout.as_millis()),
+                            life_time_ms: pool_config
+                                .server_lifetime
+                                .unwrap_or(config.general.server_lifetime.as_millis()),
+                            client_idle_timeout_ms: pool_config.client_idle_timeout.unwrap_or(0),
+                            max_query_duration_ms: pool_config.max_query_duration.unwrap_or(0),
+                            max_result_rows: pool_config.max_result_rows.unwrap_or(0),
+                            server_max_age_ms: pool_config.server_max_age.unwrap_or(0),
+                            sync_server_parameters: config.general.sync_server_parameters,
+                            min_guaranteed_pool_size: pool_config
+                                .min_guaranteed_pool_size
+                                .unwrap_or(0),
+                            reject_write_queries: pool_config.reject_write_queries,
+                            retry_readonly_on_disconnect: pool_config.retry_readonly_on_disconnect,
+                            prefer_last_server: pool_config.prefer_last_server,
+                            allow_session_override: pool_config.allow_session_override,
+                            scram_relay: pool_config.scram_relay,
+                            client_label_gucs: pool_config.client_label_gucs,
+                            default_client_statement_timeout_ms: pool_config
+                                .default_client_statement_timeout_ms
+                                .unwrap_or(0),
+                        },
+                        prepared_statement_cache: match pool_config
+                            .resolve_prepared_statements(&config.general)
+                        {
+                            false => None,
+                            true => Some(Arc::new(
+                                PreparedStatementCache::new(
+                                    prepared_statements_cache_size,
+                                    config.general.worker_threads,
+                                )
+                                .with_eviction_counter(pool_prepared_cache_eviction_counter(
+                                    &user.username,
+                                    pool_name,
+                                ))
+                                .with_eviction_logging(config.general.log_prepared_cache_evictions),
+                            )),
+                        },
+                        check_query_cache: Arc::new(CheckQueryCache::new()),
+                        coordinator,
+                        replenish_failures: Arc::new(AtomicU32::new(0)),
+                        init_complete: Arc::new(AtomicBool::new(true)),
+                        transaction_quota: user
+                            .max_concurrent_transactions
+                            .map(query_limit::QueryLimiter::new),
+                    };
+
+                    if let Some(key) = share_key {
+                        shared_pools.insert(key, pool.clone());
+                    }
+                    new_pools.insert(identifier, pool);
+                }
             }
         }
 
@@ -724,10 +862,8 @@ impl ConnectionPool {
                             ..Default::default()
                         };
 
-                        let server_database = pool_config
-                            .server_database
-                            .clone()
-                            .unwrap_or_else(|| pool_name.to_string());
+                        let server_database =
+                            pool_config.resolve_server_database(pool_name).to_string();
                         let server_tls_config =
                             build_server_tls_for_pool(pool_config, &config.general)?;
 
@@ -744,7 +880,7 @@ impl ConnectionPool {
                         };
 
                         let prepared_statements_cache_size =
-                            match config.general.prepared_statements {
+                            match pool_config.resolve_prepared_statements(&config.general) {
                                 true => pool_config
                                     .prepared_statements_cache_size
                                     .unwrap_or(config.general.prepared_statements_cache_size),
@@ -767,12 +903,8 @@ impl ConnectionPool {
                         let fallback_state =
                             build_fallback_state(pool_name, pool_config, &config.general);
 
-                        let base_startup_parameters = Arc::new(
-                            crate::config::startup_parameters::cascade_canonical_keys(&[
-                                &config.general.startup_parameters,
-                                &pool_config.startup_parameters,
-                            ]),
-                        );
+                        let base_startup_parameters =
+                            Arc::new(pool_config.resolve_startup_parameters(&config.general));
 
                         let manager = ServerPool::new(
                             address.clone(),
@@ -783,7 +915,9 @@ impl ConnectionPool {
                             pool_config.log_client_parameter_status_changes,
                             server_prepared_statements_cache_size,
                             application_name,
-                            config.general.max_concurrent_creates,
+                            pool_config
+                                .max_concurrent_creates
+                                .unwrap_or(config.general.max_concurrent_creates),
                             pool_config
                                 .server_lifetime
                                 .unwrap_or(config.general.server_lifetime.as_millis()),
@@ -850,22 +984,50 @@ impl ConnectionPool {
                                 life_time_ms: pool_config
                                     .server_lifetime
                                     .unwrap_or(config.general.server_lifetime.as_millis()),
+                                client_idle_timeout_ms: pool_config
+                                    .client_idle_timeout
+                                    .unwrap_or(0),
+                                max_query_duration_ms: pool_config.max_query_duration.unwrap_or(0),
+                                max_result_rows: pool_config.max_result_rows.unwrap_or(0),
+                                server_max_age_ms: pool_config.server_max_age.unwrap_or(0),
                                 sync_server_parameters: config.general.sync_server_parameters,
                                 min_guaranteed_pool_size: pool_config
                                     .min_guaranteed_pool_size
                                     .unwrap_or(0),
+                                reject_write_queries: pool_config.reject_write_queries,
+                                retry_readonly_on_disconnect: pool_config
+                                    .retry_readonly_on_disconnect,
+                                prefer_last_server: pool_config.prefer_last_server,
+                                allow_session_override: pool_config.allow_session_override,
+                                scram_relay: pool_config.scram_relay,
+                                client_label_gucs: pool_config.client_label_gucs,
+                                default_client_statement_timeout_ms: pool_config
+                                    .default_client_statement_timeout_ms
+                                    .unwrap_or(0),
                             },
-                            prepared_statement_cache: match config.general.prepared_statements {
+                            prepared_statement_cache: match pool_config
+                                .resolve_prepared_statements(&config.general)
+                            {
                                 false => None,
-                                true => Some(Arc::new(PreparedStatementCache::new(
-                                    prepared_statements_cache_size,
-                                    config.general.worker_threads,
-                                ))),
+                                true => Some(Arc::new(
+                                    PreparedStatementCache::new(
+                                        prepared_statements_cache_size,
+                                        config.general.worker_threads,
+                                    )
+                                    .with_eviction_counter(pool_prepared_cache_eviction_counter(
+                                        &shared_user.username,
+                                        pool_name,
+                                    ))
+                                    .with_eviction_logging(
+                                        config.general.log_prepared_cache_evictions,
+                                    ),
+                                )),
                             },
                             check_query_cache: Arc::new(CheckQueryCache::new()),
                             coordinator: coordinators.get(pool_name).cloned(),
                             replenish_failures: Arc::new(AtomicU32::new(0)),
                             init_complete: Arc::new(AtomicBool::new(true)),
+                            transaction_quota: None,
                         };
 
                         new_pools.insert(identifier.clone(), conn_pool);
@@ -883,6 +1045,7 @@ impl ConnectionPool {
                         pool_startup_hash,
                         parent_fingerprint,
                         pool_name.clone(),
+                        pool_config.resolve_server_database(pool_name).to_string(),
                         pool_config.server_host.clone(),
                         pool_config.server_port,
                         shared_pool_id,
@@ -1003,8 +1166,25 @@ impl ConnectionPool {
         }
 
         COORDINATORS.store(Arc::new(coordinators));
+        query_limit::apply_config(&config);
         AUTH_QUERY_STATE.store(Arc::new(auth_query_states));
         POOLS.store(Arc::new(new_pools.clone()));
+
+        // Classify the members of multi-host pools up front, so the first
+        // target_session_attrs clients find their roles known.
+        for (pool_name, pool_config) in &config.pools {
+            let Some(user) = pool_config.users.first() else {
+                continue;
+            };
+            if pool_config.hosts.is_empty() {
+                continue;
+            }
+            for backend in pool_config.backends(pool_name) {
+                if let Some(pool) = get_pool(&backend.pool_key, &user.username) {
+                    target_session::classify_in_background(&pool, backend.host, backend.port);
+                }
+            }
+        }
         // Advance the recycle-watcher hash only after the new state is
         // published; a failure path above (Err returned via `?`) leaves
         // PREVIOUS_GENERAL_STARTUP_HASH alone so the next reload still
@@ -0,0 +1,214 @@
+//! Admission control on concurrently executing queries.
+//!
+//! `max_concurrent_queries` caps how many transactions of a pool run on
+//! the backend at once, independent of `pool_size`; the general setting
+//! caps all pools together. A client takes its permits before checking
+//! out a server and keeps them until the server is released, so the limit
+//! counts transactions in transaction mode and sessions in session mode.
+//! Clients over the limit wait up to `query_wait_timeout`, which also
+//! covers the server checkout that follows. A user's
+//! `max_concurrent_transactions` quota works the same way and is taken
+//! first; its limiter lives on the user's `ConnectionPool`.
+
+use arc_swap::ArcSwap;
+use log::{debug, info};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+use crate::config::Config;
+
+/// A cap on concurrently running transactions: one `max_concurrent_queries`
+/// limit or one user's `max_concurrent_transactions` quota.
+#[derive(Debug)]
+pub struct QueryLimiter {
+    limit: usize,
+    semaphore: Arc<Semaphore>,
+}
+
+impl QueryLimiter {
+    pub fn new(limit: usize) -> Arc<Self> {
+        Arc::new(Self {
+            limit,
+            semaphore: Arc::new(Semaphore::new(limit)),
+        })
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Queries currently holding a permit.
+    pub fn active(&self) -> usize {
+        self.limit
+            .saturating_sub(self.semaphore.available_permits())
+    }
+
+    /// Waits until `deadline` at the latest for a permit.
+    pub(crate) async fn acquire(&self, deadline: Instant) -> Option<OwnedSemaphorePermit> {
+        tokio::time::timeout_at(deadline, self.semaphore.clone().acquire_owned())
+            .await
+            .ok()?
+            .ok()
+    }
+}
+
+/// Per-pool limiters, keyed by pool name. Pools without
+/// `max_concurrent_queries` have no entry.
+pub static QUERY_LIMITERS: Lazy<ArcSwap<HashMap<String, Arc<QueryLimiter>>>> =
+    Lazy::new(|| ArcSwap::from_pointee(HashMap::new()));
+
+/// Limiter for `general.max_concurrent_queries`, `None` when unlimited.
+pub static GLOBAL_QUERY_LIMITER: Lazy<ArcSwap<Option<Arc<QueryLimiter>>>> =
+    Lazy::new(|| ArcSwap::from_pointee(None));
+
+/// Permits held while a client runs a transaction on the backend.
+/// Dropping them lets the next waiting client in.
+#[derive(Debug, Default)]
+pub struct QueryPermits {
+    _user: Option<OwnedSemaphorePermit>,
+    _pool: Option<OwnedSemaphorePermit>,
+    _global: Option<OwnedSemaphorePermit>,
+}
+
+/// Which limit a client gave up waiting on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryLimitExceeded {
+    User(usize),
+    Pool(usize),
+    Global(usize),
+}
+
+/// Takes a permit from the user's quota, then from the pool limiter and
+/// then from the global one, waiting until `deadline` at the latest for
+/// all of them together.
+pub async fn acquire(
+    pool_name: &str,
+    user_quota: Option<&QueryLimiter>,
+    deadline: Instant,
+) -> Result<QueryPermits, QueryLimitExceeded> {
+    let pool_limiter = QUERY_LIMITERS.load().get(pool_name).cloned();
+    let global_limiter = GLOBAL_QUERY_LIMITER.load_full();
+    let global_limiter = global_limiter.as_ref().as_ref();
+
+    let mut permits = QueryPermits::default();
+    if let Some(limiter) = user_quota {
+        permits._user = Some(
+            limiter
+                .acquire(deadline)
+                .await
+                .ok_or(QueryLimitExceeded::User(limiter.limit()))?,
+        );
+    }
+    if let Some(limiter) = pool_limiter {
+        permits._pool = Some(
+            limiter
+                .acquire(deadline)
+                .await
+                .ok_or(QueryLimitExceeded::Pool(limiter.limit()))?,
+        );
+    }
+    if let Some(limiter) = global_limiter {
+        permits._global = Some(
+            limiter
+                .acquire(deadline)
+                .await
+                .ok_or(QueryLimitExceeded::Global(limiter.limit()))?,
+        );
+    }
+    Ok(permits)
+}
+
+/// Queries of `pool_name` holding a permit; 0 when the pool is unlimited.
+pub fn active_queries(pool_name: &str) -> usize {
+    QUERY_LIMITERS
+        .load()
+        .get(pool_name)
+        .map_or(0, |limiter| limiter.active())
+}
+
+/// Rebuilds the limiters for `config`. A limiter whose limit did not change
+/// is kept, so a RELOAD does not let extra queries through; a replaced one
+/// lives on until the permits taken from it are dropped.
+pub(crate) fn apply_config(config: &Config) {
+    let old_limiters = QUERY_LIMITERS.load();
+    let mut limiters = HashMap::new();
+    for (pool_name, pool_config) in &config.pools {
+        let Some(limit) = pool_config.max_concurrent_queries else {
+            continue;
+        };
+        match old_limiters.get(pool_name) {
+            Some(existing) if existing.limit() == limit => {
+                debug!("[pool: {pool_name}] max_concurrent_queries unchanged, reusing");
+                limiters.insert(pool_name.clone(), existing.clone());
+            }
+            _ => {
+                info!("[pool: {pool_name}] limiting concurrent queries to {limit}");
+                limiters.insert(pool_name.clone(), QueryLimiter::new(limit));
+            }
+        }
+    }
+    QUERY_LIMITERS.store(Arc::new(limiters));
+
+    let limit = config.general.max_concurrent_queries;
+    let global = match GLOBAL_QUERY_LIMITER.load_full().as_ref() {
+        _ if limit == 0 => None,
+        Some(existing) if existing.limit() == limit => Some(existing.clone()),
+        _ => {
+            info!("limiting concurrent queries across all pools to {limit}");
+            Some(QueryLimiter::new(limit))
+        }
+    };
+    GLOBAL_QUERY_LIMITER.store(Arc::new(global));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn after(millis: u64) -> Instant {
+        Instant::now() + Duration::from_millis(millis)
+    }
+
+    #[tokio::test]
+    async fn limiter_counts_and_times_out() {
+        let limiter = QueryLimiter::new(2);
+        let first = limiter.acquire(after(10)).await;
+        let second = limiter.acquire(after(10)).await;
+        assert!(first.is_some() && second.is_some());
+        assert_eq!(limiter.active(), 2);
+
+        assert!(limiter.acquire(after(10)).await.is_none());
+
+        drop(first);
+        assert_eq!(limiter.active(), 1);
+        assert!(limiter.acquire(after(10)).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn user_quota_is_taken_before_pool_limits() {
+        let quota = QueryLimiter::new(1);
+        let held = acquire("query_limit_test_quota", Some(&quota), Instant::now())
+            .await
+            .unwrap();
+        assert_eq!(quota.active(), 1);
+        assert_eq!(
+            acquire("query_limit_test_quota", Some(&quota), after(10))
+                .await
+                .unwrap_err(),
+            QueryLimitExceeded::User(1)
+        );
+        drop(held);
+        assert_eq!(quota.active(), 0);
+    }
+
+    #[tokio::test]
+    async fn unlimited_pool_acquires_without_waiting() {
+        let permits = acquire("query_limit_test_unlimited", None, Instant::now()).await;
+        assert!(permits.is_ok());
+        assert_eq!(active_queries("query_limit_test_unlimited"), 0);
+    }
+}
@@ -5,6 +5,7 @@ use log::{info, warn};
 use rand::seq::SliceRandom;
 
 use crate::config::get_config;
+use crate::server::ServerCloseCause;
 use crate::utils::{format_duration_ms, format_elapsed};
 
 use super::{get_all_pools, ConnectionPool};
@@ -16,6 +17,10 @@ impl ConnectionPool {
     /// If `max` > 0, at most `max` connections will be closed across all pools,
     /// prioritizing the oldest connections first.
     ///
+    /// Lifetime-expired connections are always closed. Idle-expired ones are
+    /// how the pool shrinks after a burst: they are closed only down to
+    /// `min_pool_size` and at most `scaling_shrink_step` per cycle.
+    ///
     /// Pools under client pressure are skipped: closing an idle connection
     /// the moment a client is queued behind it just turns a free recycle
     /// into a fresh connect on the wait path.
@@ -24,22 +29,25 @@ impl ConnectionPool {
             return 0;
         }
 
-        // Closure to determine if a connection should be closed
-        // Uses per-connection timeouts with jitter to prevent mass closures
-        let should_close = |_: &crate::server::Server, metrics: &crate::pool::Metrics| -> bool {
-            // Check idle timeout (per-connection with jitter, 0 = disabled)
-            if metrics.idle_timeout_ms > 0 {
-                if let Some(v) = metrics.recycled {
-                    if (v.elapsed().as_millis() as u64) > metrics.idle_timeout_ms {
-                        return true;
-                    }
-                }
-            }
-            // Check server lifetime (per-connection with jitter, 0 = disabled)
-            if metrics.lifetime_ms > 0 && (metrics.age().as_millis() as u64) > metrics.lifetime_ms {
-                return true;
+        // Per-connection timeouts carry jitter to prevent mass closures.
+        // Check server lifetime (0 = disabled)
+        let lifetime_expired = |_: &crate::server::Server,
+                                metrics: &crate::pool::Metrics|
+         -> Option<ServerCloseCause> {
+            (metrics.lifetime_ms > 0 && (metrics.age().as_millis() as u64) > metrics.lifetime_ms)
+                .then_some(ServerCloseCause::Lifetime)
+        };
+        // Check idle timeout (0 = disabled)
+        let idle_expired = |_: &crate::server::Server,
+                            metrics: &crate::pool::Metrics|
+         -> Option<ServerCloseCause> {
+            if metrics.idle_timeout_ms == 0 {
+                return None;
             }
-            false
+            metrics
+                .recycled
+                .filter(|v| (v.elapsed().as_millis() as u64) > metrics.idle_timeout_ms)
+                .map(|_| ServerCloseCause::Idle)
         };
 
         // Calculate remaining quota for this pool
@@ -54,9 +62,19 @@ impl ConnectionPool {
         };
 
         // Use retain_oldest_first which sorts by age when max > 0
-        let closed = self
+        let mut closed = self
             .database
-            .retain_oldest_first(should_close, max_to_close);
+            .retain_oldest_first(lifetime_expired, max_to_close);
+
+        let idle_budget = idle_close_budget(
+            self.database.status().size,
+            self.settings.user.min_pool_size.unwrap_or(0) as usize,
+            self.database.scaling().shrink_step as usize,
+            (max > 0).then(|| max_to_close.saturating_sub(closed)),
+        );
+        if idle_budget > 0 {
+            closed += self.database.retain_oldest_first(idle_expired, idle_budget);
+        }
         count.fetch_add(closed, Ordering::Relaxed);
 
         if closed > 0 {
@@ -92,7 +110,8 @@ impl ConnectionPool {
         let idle_before = status_before.available;
 
         // Close all idle connections by returning false for all
-        self.database.retain(|_, _| false);
+        self.database
+            .retain(ServerCloseCause::Shutdown, |_, _| false);
 
         let status_after = self.database.status();
         let closed = idle_before.saturating_sub(status_after.available);
@@ -111,6 +130,25 @@ impl ConnectionPool {
     }
 }
 
+/// How many idle-expired connections one retain cycle may close in a pool of
+/// `size` connections: never below `min_pool_size`, at most `shrink_step`
+/// (0 = no cap), and within what is left of the global quota, if any.
+fn idle_close_budget(
+    size: usize,
+    min_pool_size: usize,
+    shrink_step: usize,
+    quota_left: Option<usize>,
+) -> usize {
+    let mut budget = size.saturating_sub(min_pool_size);
+    if shrink_step > 0 {
+        budget = budget.min(shrink_step);
+    }
+    if let Some(quota_left) = quota_left {
+        budget = budget.min(quota_left);
+    }
+    budget
+}
+
 pub async fn retain_connections() {
     let config = get_config();
     let retain_time = config.general.retain_connections_time.as_std();
@@ -222,10 +260,20 @@ pub async fn retain_connections() {
         }
         count.store(0, Ordering::Relaxed);
 
+        // Probe the backend of auto-paused pools off the retain task: a
+        // failing connect sleeps through the connect backoff.
+        for pool in &pool_refs {
+            if pool.database.is_auto_paused() {
+                let pool = (*pool).clone();
+                tokio::spawn(async move { pool.database.probe_backend().await });
+            }
+        }
+
         // Replenish pools below min_pool_size
         for pool in &pool_refs {
-            // Don't replenish paused pools — no new connections during PAUSE
-            if pool.database.is_paused() {
+            // Don't replenish paused pools — no new connections during PAUSE.
+            // Auto-paused pools are left to their probe.
+            if pool.database.is_paused() || pool.database.is_auto_paused() {
                 continue;
             }
             if let Some(min_pool_size) = pool.settings.user.min_pool_size {
@@ -340,8 +388,19 @@ mod tests {
                 db: "test_db".to_string(),
                 idle_timeout_ms: 60_000,
                 life_time_ms: 1, // tiny: any connection would be "expired"
+                client_idle_timeout_ms: 0,
+                max_query_duration_ms: 0,
+                max_result_rows: 0,
+                server_max_age_ms: 0,
                 sync_server_parameters: false,
                 min_guaranteed_pool_size: 0,
+                reject_write_queries: false,
+                retry_readonly_on_disconnect: false,
+                prefer_last_server: false,
+                allow_session_override: false,
+                scram_relay: false,
+                client_label_gucs: false,
+                default_client_statement_timeout_ms: 0,
             },
             config_hash: 0,
             per_user_startup_overlay_hash: crate::pool::empty_overlay_hash(),
@@ -350,6 +409,7 @@ mod tests {
             coordinator: None,
             replenish_failures: Arc::new(AtomicU32::new(0)),
             init_complete: Arc::new(AtomicBool::new(true)),
+            transaction_quota: None,
         }
     }
 
@@ -386,4 +446,19 @@ mod tests {
             "shared retain counter must not advance",
         );
     }
+
+    #[test]
+    fn idle_close_budget_keeps_min_pool_size() {
+        assert_eq!(idle_close_budget(10, 0, 0, None), 10);
+        assert_eq!(idle_close_budget(10, 4, 0, None), 6);
+        assert_eq!(idle_close_budget(3, 4, 0, None), 0);
+    }
+
+    #[test]
+    fn idle_close_budget_honours_shrink_step_and_quota() {
+        assert_eq!(idle_close_budget(10, 4, 2, None), 2);
+        assert_eq!(idle_close_budget(10, 4, 2, Some(1)), 1);
+        assert_eq!(idle_close_budget(10, 4, 0, Some(3)), 3);
+        assert_eq!(idle_close_budget(10, 4, 2, Some(0)), 0);
+    }
 }
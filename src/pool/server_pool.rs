@@ -2,7 +2,8 @@
 //!
 //! `ServerPool` manages the creation and recycling of individual PostgreSQL
 //! server connections. It handles connect timeouts, lifetime checks, alive
-//! checks, pause/resume, and reconnect epoch management.
+//! checks, pause/resume, auto-pause on backend failures, and reconnect
+//! epoch management.
 
 use std::collections::{BTreeMap, HashSet};
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -12,14 +13,17 @@ use std::time::Duration;
 use log::{debug, info, warn};
 use tokio::sync::{Notify, Semaphore};
 
+use crate::auth::scram_relay::ScramRelay;
 use crate::config::startup_parameters as sp;
 use crate::config::{Address, User};
 use crate::errors::Error;
 use crate::patroni::types::Role;
-use crate::server::Server;
+use crate::server::{Server, ServerCloseCause};
 use crate::stats::ServerStats;
 use crate::utils::format_duration_ms;
 
+use super::backend_health::BackendHealth;
+use super::connect_backoff::ConnectBackoff;
 use super::errors::{RecycleError, RecycleResult};
 use super::startup_resolver::ApplicationState;
 use super::types::Metrics;
@@ -191,6 +195,12 @@ pub struct ServerPool {
     /// Counter for total connections created (for logging).
     connection_counter: AtomicU64,
 
+    /// Failure streak shared by all `create` calls; drives the retry pause.
+    connect_backoff: ConnectBackoff,
+
+    /// Backend failure/success streaks behind `auto_pause_on_unhealthy`.
+    backend_health: BackendHealth,
+
     /// Server lifetime in milliseconds (0 = unlimited).
     lifetime_ms: u64,
 
@@ -334,6 +344,8 @@ impl ServerPool {
             prepared_statement_cache_size,
             create_semaphore: Arc::new(Semaphore::new(max_concurrent_creates)),
             connection_counter: AtomicU64::new(0),
+            connect_backoff: ConnectBackoff::new(),
+            backend_health: BackendHealth::new(),
             application_name,
             lifetime_ms,
             idle_timeout_ms,
@@ -448,6 +460,7 @@ impl ServerPool {
                 self.session_mode,
                 &startup_parameters,
                 self.operator_managed_startup_keys.clone(),
+                None,
             ),
         )
         .await;
@@ -507,6 +520,7 @@ impl ServerPool {
                     self.session_mode,
                     &startup_parameters,
                     self.operator_managed_startup_keys.clone(),
+                    None,
                 ),
             )
             .await;
@@ -519,6 +533,8 @@ impl ServerPool {
             Ok(conn) => {
                 // Permit is released automatically when _permit goes out of scope
                 conn.stats.idle(0);
+                self.connect_backoff.on_success();
+                self.record_backend_success();
                 Ok(conn)
             }
             Err(err) => {
@@ -536,14 +552,126 @@ impl ServerPool {
                         );
                         return self.create_fallback_connection().await;
                     }
+                    self.record_backend_failure();
                 }
-                // Brief backoff on error to avoid hammering a failing server
-                tokio::time::sleep(Duration::from_millis(10)).await;
+                // Back off before returning so callers that failed together
+                // don't hammer a recovering server in lockstep.
+                let delay = {
+                    let config = crate::config::get_config();
+                    self.connect_backoff.on_failure(
+                        config.general.server_connect_backoff_base.as_std(),
+                        config.general.server_connect_backoff_max.as_std(),
+                    )
+                };
+                debug!(
+                    "[{}@{}] server connect failed, backing off {}",
+                    self.address.username,
+                    self.address.pool_name,
+                    format_duration_ms(delay.as_millis() as u64),
+                );
+                tokio::time::sleep(delay).await;
                 Err(err)
             }
         }
     }
 
+    /// Opens a walsender connection for a client that sent `replication`
+    /// in its StartupMessage. The connection bypasses the pool: it takes
+    /// no `pool_size` slot or coordinator permit, has no fallback, and
+    /// belongs to the client until it disconnects. The client's
+    /// `application_name` is sent so `synchronous_standby_names` and
+    /// `pg_stat_replication` see the real standby name.
+    pub async fn create_replication_connection(
+        &self,
+        replication: &str,
+        application_name: &str,
+    ) -> Result<Server, Error> {
+        let mut startup_parameters = self.resolved_startup_parameters()?.into_owned();
+        startup_parameters.insert("replication".to_string(), replication.to_string());
+        startup_parameters.insert("application_name".to_string(), application_name.to_string());
+        info!(
+            "[{}@{}] new replication connection (replication={replication}) to {}:{}",
+            self.address.username, self.address.pool_name, self.address.host, self.address.port,
+        );
+
+        let stats = Arc::new(ServerStats::new(
+            self.address.clone(),
+            crate::utils::clock::now(),
+        ));
+        stats.register(stats.clone());
+
+        let result = startup_with_timeout(
+            self.connect_timeout,
+            &self.address.host,
+            self.address.port,
+            Server::startup(
+                &self.address,
+                &self.user,
+                &self.database,
+                self.client_server_map.clone(),
+                stats.clone(),
+                false,
+                self.log_client_parameter_status_changes,
+                0,
+                application_name.to_string(),
+                true,
+                &startup_parameters,
+                self.operator_managed_startup_keys.clone(),
+                None,
+            ),
+        )
+        .await;
+        if result.is_err() {
+            stats.disconnect();
+        }
+        result
+    }
+
+    /// Opens the backend connection of one client of a `scram_relay` pool,
+    /// relaying the client's SCRAM exchange through `relay`. Like a
+    /// replication connection it takes no `pool_size` slot and belongs to
+    /// the client until it disconnects.
+    pub async fn create_relayed_connection(&self, mut relay: ScramRelay) -> Result<Server, Error> {
+        let startup_parameters = self.resolved_startup_parameters()?;
+        info!(
+            "[{}@{}] new relayed SCRAM connection to {}:{}",
+            self.address.username, self.address.pool_name, self.address.host, self.address.port,
+        );
+
+        let stats = Arc::new(ServerStats::new(
+            self.address.clone(),
+            crate::utils::clock::now(),
+        ));
+        stats.register(stats.clone());
+
+        let result = startup_with_timeout(
+            self.connect_timeout,
+            &self.address.host,
+            self.address.port,
+            Server::startup(
+                &self.address,
+                &self.user,
+                &self.database,
+                self.client_server_map.clone(),
+                stats.clone(),
+                self.cleanup_connections,
+                self.log_client_parameter_status_changes,
+                self.prepared_statement_cache_size,
+                self.application_name.clone(),
+                true,
+                &startup_parameters,
+                self.operator_managed_startup_keys.clone(),
+                Some(&mut relay),
+            ),
+        )
+        .await;
+        match &result {
+            Ok(server) => server.stats.idle(0),
+            Err(_) => stats.disconnect(),
+        }
+        result
+    }
+
     /// Returns the address of this pool.
     pub fn address(&self) -> &Address {
         &self.address
@@ -585,11 +713,21 @@ impl ServerPool {
         ),
     > {
         let cfg = crate::config::config_arc();
+        // `read_only` is reported as a pool-level value: the pool config is
+        // where it comes from.
         let pool_params = cfg
             .pools
             .get(&self.address.pool_name)
-            .map(|p| &p.startup_parameters)
-            .cloned()
+            .map(|p| {
+                let mut params = p.startup_parameters.clone();
+                if p.read_only {
+                    params.insert(
+                        "default_transaction_read_only".to_string(),
+                        "on".to_string(),
+                    );
+                }
+                params
+            })
             .unwrap_or_default();
         let auth_query_params: Option<std::collections::HashMap<String, String>> =
             match super::get_auth_query_state(&self.address.pool_name) {
@@ -1081,6 +1219,7 @@ impl ServerPool {
                 self.session_mode,
                 startup_parameters,
                 self.operator_managed_startup_keys.clone(),
+                None,
             ),
         )
         .await;
@@ -1134,6 +1273,7 @@ impl ServerPool {
                     self.session_mode,
                     startup_parameters,
                     self.operator_managed_startup_keys.clone(),
+                    None,
                 ),
             )
             .await;
@@ -1188,6 +1328,95 @@ impl ServerPool {
         self.resume_notify.notify_waiters();
     }
 
+    /// Returns whether the pool is auto-paused by `auto_pause_on_unhealthy`.
+    pub fn is_auto_paused(&self) -> bool {
+        self.backend_health.is_paused()
+    }
+
+    /// Ends an auto-pause on admin RESUME. Returns `true` if one was active.
+    pub fn clear_auto_pause(&self) -> bool {
+        let cleared = self.backend_health.clear();
+        if cleared {
+            self.log_auto_pause_transition(false, "admin RESUME");
+        }
+        cleared
+    }
+
+    /// Claims the single backend probe slot of an auto-paused pool.
+    pub(crate) fn try_start_backend_probe(&self) -> bool {
+        self.backend_health.try_start_probe()
+    }
+
+    /// Releases the probe slot taken by `try_start_backend_probe`.
+    pub(crate) fn finish_backend_probe(&self) {
+        self.backend_health.finish_probe();
+    }
+
+    /// Feeds a connect or alive-check failure into the health streak and
+    /// auto-pauses the pool at the configured threshold.
+    fn record_backend_failure(&self) {
+        let config = crate::config::get_config();
+        let Some(pool) = config.pools.get(&self.address.pool_name) else {
+            return;
+        };
+        if !pool.auto_pause_on_unhealthy {
+            return;
+        }
+        if self
+            .backend_health
+            .on_failure(pool.auto_pause_failure_threshold)
+        {
+            let reason = format!(
+                "{} consecutive backend failures",
+                self.backend_health.failures()
+            );
+            self.log_auto_pause_transition(true, &reason);
+        }
+    }
+
+    /// Resets the failure streak; resumes an auto-paused pool once enough
+    /// successes in a row were seen. The config is read only while paused,
+    /// and a pool whose `auto_pause_on_unhealthy` was switched off by
+    /// RELOAD resumes on the first success.
+    fn record_backend_success(&self) {
+        let threshold = if self.backend_health.is_paused() {
+            crate::config::get_config()
+                .pools
+                .get(&self.address.pool_name)
+                .filter(|pool| pool.auto_pause_on_unhealthy)
+                .map_or(1, |pool| pool.auto_pause_success_threshold)
+        } else {
+            1
+        };
+        if self.backend_health.on_success(threshold) {
+            let reason = format!("{threshold} consecutive successful connects");
+            self.log_auto_pause_transition(false, &reason);
+        }
+    }
+
+    fn log_auto_pause_transition(&self, paused: bool, reason: &str) {
+        let identifier = format!("{}@{}", self.address.username, self.address.pool_name);
+        let (target, transition) = if paused {
+            warn!(
+                "[{identifier}] auto-pause: backend {}:{} unhealthy after {reason}, checkouts fail fast until it recovers",
+                self.address.host, self.address.port,
+            );
+            ("AUTO_PAUSE", "pause")
+        } else {
+            info!(
+                "[{identifier}] auto-pause: backend {}:{} healthy again after {reason}, pool resumed",
+                self.address.host, self.address.port,
+            );
+            ("AUTO_RESUME", "resume")
+        };
+        crate::admin::events::push_event(target, format!("pool {identifier} auto-{transition}d"));
+        crate::web::metrics::record_auto_pause_transition(
+            &self.address.username,
+            &self.address.pool_name,
+            transition,
+        );
+    }
+
     /// Returns a future that completes when the pool is resumed.
     pub fn resume_notified(&self) -> tokio::sync::futures::Notified<'_> {
         self.resume_notify.notified()
@@ -1229,12 +1458,14 @@ impl ServerPool {
     ) -> RecycleResult {
         if conn.is_bad() {
             conn.close_reason = Some("bad connection".to_string());
+            conn.set_close_cause(ServerCloseCause::Bad);
             return Err(RecycleError::StaticMessage("Bad connection"));
         }
 
         // RECONNECT epoch check: reject connections created before current epoch
         if metrics.epoch < self.current_epoch() {
             conn.close_reason = Some("reconnect epoch outdated".to_string());
+            conn.set_close_cause(ServerCloseCause::Reconnect);
             return Err(RecycleError::StaticMessage(
                 "Connection outdated (RECONNECT)",
             ));
@@ -1247,6 +1478,7 @@ impl ServerPool {
                 format_duration_ms(age_ms),
                 format_duration_ms(metrics.lifetime_ms),
             ));
+            conn.set_close_cause(ServerCloseCause::Lifetime);
             return Err(RecycleError::StaticMessage("Connection exceeded lifetime"));
         }
 
@@ -1264,9 +1496,12 @@ impl ServerPool {
                             "failed alive check after {} idle",
                             format_duration_ms(idle_time_ms),
                         ));
+                        conn.set_close_cause(ServerCloseCause::Bad);
+                        self.record_backend_failure();
                         return Err(RecycleError::StaticMessage("Connection failed alive check"));
                     }
                     debug!("Connection {} passed alive check", conn);
+                    self.record_backend_success();
                 }
             }
         }
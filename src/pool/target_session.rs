@@ -0,0 +1,239 @@
+//! `target_session_attrs` routing across the members of a pool's cluster.
+//!
+//! A pool with `hosts` has several backends: `server_host` plus each `hosts`
+//! entry. Every new server connection to such a backend runs
+//! `SELECT pg_is_in_recovery()` and records the answer here, keyed by
+//! `host:port`, so the role follows switchovers as soon as the pool opens a
+//! fresh connection. A client that asked for `target_session_attrs` is then
+//! routed to the first backend whose last known role matches. Members with
+//! no known role are classified in the background, never while a client
+//! waits.
+
+use std::time::{Duration, Instant};
+
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use log::debug;
+use once_cell::sync::Lazy;
+
+use super::ConnectionPool;
+
+/// Role of a backend as last reported by `pg_is_in_recovery()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendRole {
+    Primary,
+    Standby,
+}
+
+/// Values of libpq's `target_session_attrs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetSessionAttrs {
+    Any,
+    ReadWrite,
+    ReadOnly,
+    Primary,
+    Standby,
+    PreferStandby,
+}
+
+impl TargetSessionAttrs {
+    /// Parses a libpq `target_session_attrs` value.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "any" => Some(Self::Any),
+            "read-write" => Some(Self::ReadWrite),
+            "read-only" => Some(Self::ReadOnly),
+            "primary" => Some(Self::Primary),
+            "standby" => Some(Self::Standby),
+            "prefer-standby" => Some(Self::PreferStandby),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Any => "any",
+            Self::ReadWrite => "read-write",
+            Self::ReadOnly => "read-only",
+            Self::Primary => "primary",
+            Self::Standby => "standby",
+            Self::PreferStandby => "prefer-standby",
+        }
+    }
+}
+
+/// Last role seen per `host:port`.
+static BACKEND_ROLES: Lazy<DashMap<String, BackendRole>> = Lazy::new(DashMap::new);
+
+fn role_key(host: &str, port: u16) -> String {
+    format!("{host}:{port}")
+}
+
+/// Records the `pg_is_in_recovery()` answer of a fresh server connection.
+pub fn record_backend_role(host: &str, port: u16, in_recovery: bool) {
+    let role = match in_recovery {
+        true => BackendRole::Standby,
+        false => BackendRole::Primary,
+    };
+    BACKEND_ROLES.insert(role_key(host, port), role);
+}
+
+/// Last known role of a backend; `None` until a connection to it classified it.
+pub fn backend_role(host: &str, port: u16) -> Option<BackendRole> {
+    BACKEND_ROLES.get(&role_key(host, port)).map(|role| *role)
+}
+
+/// How long a member whose classifying connection failed is left alone.
+const PROBE_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Members being classified (`None`) or whose last classifying connection
+/// failed (`Some(when)`), per `host:port`.
+static PROBES: Lazy<DashMap<String, Option<Instant>>> = Lazy::new(DashMap::new);
+
+/// Opens a connection to a member whose role is unknown in the background,
+/// so that later clients find it classified. One probe per member runs at
+/// a time, and a failed one is retried after `PROBE_RETRY_INTERVAL`, so a
+/// member that is down costs no client a connect timeout.
+pub fn classify_in_background(pool: &ConnectionPool, host: &str, port: u16) {
+    if backend_role(host, port).is_some() {
+        return;
+    }
+    let key = role_key(host, port);
+    match PROBES.entry(key.clone()) {
+        Entry::Occupied(mut probe) => match *probe.get() {
+            Some(failed_at) if failed_at.elapsed() >= PROBE_RETRY_INTERVAL => {
+                probe.insert(None);
+            }
+            _ => return,
+        },
+        Entry::Vacant(probe) => {
+            probe.insert(None);
+        }
+    }
+    let pool = pool.clone();
+    tokio::spawn(async move {
+        // A fresh server connection records its backend's role.
+        match pool.database.get().await {
+            Ok(_) => {
+                PROBES.remove(&key);
+            }
+            Err(err) => {
+                debug!("could not classify {key} for target_session_attrs: {err}");
+                PROBES.insert(key, Some(Instant::now()));
+            }
+        }
+    });
+}
+
+/// Picks the backend for `attrs` from candidates in config order, the pool's
+/// own `server_host` first. Backends with an unknown role never match.
+///
+/// - `any`: the first candidate.
+/// - `read-write`, `primary`: the first primary; an error when only
+///   standbys are up.
+/// - `read-only`, `standby`: the first standby; an error when only
+///   primaries are up.
+/// - `prefer-standby`: the first standby, otherwise the first primary.
+pub fn select_backend(
+    attrs: TargetSessionAttrs,
+    roles: &[Option<BackendRole>],
+) -> Result<usize, String> {
+    if attrs == TargetSessionAttrs::Any && !roles.is_empty() {
+        return Ok(0);
+    }
+    let find = |wanted: BackendRole| roles.iter().position(|role| *role == Some(wanted));
+    let no_match = |missing: &str| {
+        format!(
+            "no backend matches target_session_attrs={}: no {missing} is available",
+            attrs.as_str()
+        )
+    };
+    let primary = find(BackendRole::Primary);
+    let standby = find(BackendRole::Standby);
+    match attrs {
+        TargetSessionAttrs::ReadWrite | TargetSessionAttrs::Primary => {
+            primary.ok_or_else(|| no_match("primary"))
+        }
+        TargetSessionAttrs::ReadOnly | TargetSessionAttrs::Standby => {
+            standby.ok_or_else(|| no_match("standby"))
+        }
+        TargetSessionAttrs::Any | TargetSessionAttrs::PreferStandby => {
+            standby.or(primary).ok_or_else(|| no_match("server"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const P: Option<BackendRole> = Some(BackendRole::Primary);
+    const S: Option<BackendRole> = Some(BackendRole::Standby);
+
+    #[test]
+    fn parse_accepts_libpq_values() {
+        assert_eq!(
+            TargetSessionAttrs::parse("Read-Write"),
+            Some(TargetSessionAttrs::ReadWrite)
+        );
+        assert_eq!(
+            TargetSessionAttrs::parse("prefer-standby"),
+            Some(TargetSessionAttrs::PreferStandby)
+        );
+        assert_eq!(TargetSessionAttrs::parse("replica"), None);
+    }
+
+    #[test]
+    fn read_write_picks_primary_and_fails_without_one() {
+        assert_eq!(
+            select_backend(TargetSessionAttrs::ReadWrite, &[S, None, P]),
+            Ok(2)
+        );
+        assert!(select_backend(TargetSessionAttrs::ReadWrite, &[S, S]).is_err());
+        assert!(select_backend(TargetSessionAttrs::Primary, &[None]).is_err());
+    }
+
+    #[test]
+    fn standby_picks_standby_and_fails_without_one() {
+        assert_eq!(select_backend(TargetSessionAttrs::ReadOnly, &[P, S]), Ok(1));
+        assert_eq!(
+            select_backend(TargetSessionAttrs::Standby, &[None, P, S]),
+            Ok(2)
+        );
+        assert!(select_backend(TargetSessionAttrs::Standby, &[P, None]).is_err());
+        assert!(select_backend(TargetSessionAttrs::ReadOnly, &[P]).is_err());
+    }
+
+    #[test]
+    fn prefer_standby_falls_back_to_primary() {
+        assert_eq!(
+            select_backend(TargetSessionAttrs::PreferStandby, &[P, S]),
+            Ok(1)
+        );
+        assert_eq!(
+            select_backend(TargetSessionAttrs::PreferStandby, &[None, P]),
+            Ok(1)
+        );
+        assert!(select_backend(TargetSessionAttrs::PreferStandby, &[None, None]).is_err());
+    }
+
+    #[test]
+    fn any_takes_first_backend() {
+        assert_eq!(select_backend(TargetSessionAttrs::Any, &[None, P]), Ok(0));
+    }
+
+    #[test]
+    fn recorded_role_is_keyed_by_host_and_port() {
+        record_backend_role("tsa-test-host", 6001, true);
+        record_backend_role("tsa-test-host", 6002, false);
+        assert_eq!(
+            backend_role("tsa-test-host", 6001),
+            Some(BackendRole::Standby)
+        );
+        assert_eq!(
+            backend_role("tsa-test-host", 6002),
+            Some(BackendRole::Primary)
+        );
+        assert_eq!(backend_role("tsa-test-host", 6003), None);
+    }
+}
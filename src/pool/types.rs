@@ -16,6 +16,10 @@ pub struct ScalingConfig {
     /// Tasks beyond this limit wait for either an idle return or a create completion.
     /// Must be >= 1.
     pub max_parallel_creates: u32,
+
+    /// Maximum idle-expired connections closed per pool per retain cycle.
+    /// 0 = unlimited. Idle closes never take the pool below `min_pool_size`.
+    pub shrink_step: u32,
 }
 
 impl ScalingConfig {
@@ -23,9 +27,11 @@ impl ScalingConfig {
     /// - 20% warm pool (immediate creation below threshold)
     /// - 10 fast retries (~10-50μs of yield_now spin)
     /// - 2 concurrent creates per pool (anti-thundering-herd)
+    /// - no cap on idle closes per retain cycle
     pub const DEFAULT_WARM_POOL_RATIO: f32 = 0.2;
     pub const DEFAULT_FAST_RETRIES: u32 = 10;
     pub const DEFAULT_MAX_PARALLEL_CREATES: u32 = 2;
+    pub const DEFAULT_SHRINK_STEP: u32 = 0;
 }
 
 impl Default for ScalingConfig {
@@ -34,6 +40,7 @@ impl Default for ScalingConfig {
             warm_pool_ratio: Self::DEFAULT_WARM_POOL_RATIO,
             fast_retries: Self::DEFAULT_FAST_RETRIES,
             max_parallel_creates: Self::DEFAULT_MAX_PARALLEL_CREATES,
+            shrink_step: Self::DEFAULT_SHRINK_STEP,
         }
     }
 }
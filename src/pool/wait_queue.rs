@@ -0,0 +1,162 @@
+//! Priority order for clients waiting on a saturated pool.
+//!
+//! On pools where a user has a non-zero `priority`, clients that miss the
+//! semaphore fast path queue here instead of directly on the semaphore.
+//! Only the head of the queue waits for a permit, so the next freed
+//! connection goes to the head. A client with user `priority` p is ordered
+//! as if it had arrived `p * PRIORITY_STEP` earlier: a higher priority
+//! jumps ahead of recent arrivals, while a default client that has already
+//! waited longer than that head start is still served first, so nobody
+//! starves. With equal priorities the queue is FIFO, same as the semaphore
+//! on its own.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use tokio::sync::Notify;
+
+/// Queue head start bought by one priority level. The `priority` reference
+/// docs quote this value.
+pub(crate) const PRIORITY_STEP: Duration = Duration::from_millis(50);
+
+/// Virtual arrival time in microseconds since the queue was created, then
+/// arrival sequence for ties.
+type WaiterKey = (u64, u64);
+
+pub(crate) struct WaitQueue {
+    created: Instant,
+    next_seq: AtomicU64,
+    waiters: Mutex<BTreeMap<WaiterKey, Arc<Notify>>>,
+}
+
+impl WaitQueue {
+    pub(crate) fn new() -> Self {
+        Self {
+            created: Instant::now(),
+            next_seq: AtomicU64::new(0),
+            waiters: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Queues a waiter. Dropping the ticket leaves the queue.
+    pub(crate) fn enter(&self, priority: u8) -> WaitTicket<'_> {
+        self.enter_at(priority, Instant::now())
+    }
+
+    fn enter_at(&self, priority: u8, now: Instant) -> WaitTicket<'_> {
+        let step = PRIORITY_STEP.as_micros() as u64;
+        // Offset by the largest head start so the virtual arrival never
+        // goes below zero.
+        let arrival = now.saturating_duration_since(self.created).as_micros() as u64
+            + u64::from(u8::MAX) * step;
+        let key = (
+            arrival - u64::from(priority) * step,
+            self.next_seq.fetch_add(1, Ordering::Relaxed),
+        );
+        let notify = Arc::new(Notify::new());
+
+        let mut waiters = self.waiters.lock();
+        let displaced = waiters
+            .first_key_value()
+            .filter(|(head, _)| key < **head)
+            .map(|(_, head)| head.clone());
+        waiters.insert(key, notify.clone());
+        drop(waiters);
+
+        // The old head stops waiting for a permit and yields to this one.
+        if let Some(head) = displaced {
+            head.notify_one();
+        }
+        WaitTicket {
+            queue: self,
+            key,
+            notify,
+        }
+    }
+
+    /// Number of queued waiters.
+    pub(crate) fn len(&self) -> usize {
+        self.waiters.lock().len()
+    }
+}
+
+/// A client's place in the [`WaitQueue`].
+pub(crate) struct WaitTicket<'a> {
+    queue: &'a WaitQueue,
+    key: WaiterKey,
+    notify: Arc<Notify>,
+}
+
+impl WaitTicket<'_> {
+    /// Whether this waiter is next in line for a permit.
+    pub(crate) fn is_head(&self) -> bool {
+        self.queue
+            .waiters
+            .lock()
+            .first_key_value()
+            .is_some_and(|(head, _)| *head == self.key)
+    }
+
+    /// Resolves once this waiter became the head or lost the head position.
+    /// May also resolve spuriously; callers re-check [`Self::is_head`].
+    pub(crate) async fn changed(&self) {
+        self.notify.notified().await;
+    }
+}
+
+impl Drop for WaitTicket<'_> {
+    fn drop(&mut self) {
+        let mut waiters = self.queue.waiters.lock();
+        let was_head = waiters
+            .first_key_value()
+            .is_some_and(|(head, _)| *head == self.key);
+        waiters.remove(&self.key);
+        if was_head {
+            if let Some((_, next)) = waiters.first_key_value() {
+                next.notify_one();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equal_priorities_are_fifo() {
+        let queue = WaitQueue::new();
+        let now = Instant::now();
+        let first = queue.enter_at(0, now);
+        let second = queue.enter_at(0, now);
+        assert!(first.is_head());
+        assert!(!second.is_head());
+        drop(first);
+        assert!(second.is_head());
+        assert_eq!(queue.len(), 1);
+    }
+
+    #[test]
+    fn higher_priority_jumps_recent_arrivals() {
+        let queue = WaitQueue::new();
+        let now = Instant::now();
+        let default = queue.enter_at(0, now);
+        let priority = queue.enter_at(2, now + PRIORITY_STEP);
+        assert!(priority.is_head());
+        assert!(!default.is_head());
+    }
+
+    #[test]
+    fn long_wait_outranks_priority_head_start() {
+        let queue = WaitQueue::new();
+        let now = Instant::now();
+        let default = queue.enter_at(0, now);
+        let priority = queue.enter_at(2, now + PRIORITY_STEP * 3);
+        assert!(default.is_head());
+        drop(default);
+        assert!(priority.is_head());
+    }
+}
@@ -4,6 +4,7 @@ use tokio::io::AsyncReadExt;
 
 use crate::auth::jwt::{new_claims, sign_with_jwt_priv_key};
 use crate::auth::scram_client::ScramSha256;
+use crate::auth::scram_relay::{RelayMessage, ScramRelay};
 use crate::config::{BackendAuthMethod, User};
 use crate::errors::{Error, ServerIdentifier};
 use crate::messages::constants::*;
@@ -13,6 +14,8 @@ use super::stream::StreamInner;
 
 /// Handles authentication during server startup.
 /// Processes various authentication methods: SASL, MD5, clear password.
+/// With `scram_relay` the SASL exchange is the client's, passed through.
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn handle_authentication(
     stream: &mut StreamInner,
     auth_code: i32,
@@ -21,7 +24,11 @@ pub(crate) async fn handle_authentication(
     scram_client_auth: &mut Option<ScramSha256>,
     server_identifier: &ServerIdentifier,
     backend_auth: Option<&BackendAuthMethod>,
+    scram_relay: Option<&mut ScramRelay>,
 ) -> Result<(), Error> {
+    if let Some(relay) = scram_relay {
+        return relay_authentication(stream, auth_code, len, relay, server_identifier).await;
+    }
     match auth_code {
         AUTHENTICATION_SUCCESSFUL => Ok(()),
 
@@ -219,3 +226,77 @@ pub(crate) async fn handle_authentication(
         }
     }
 }
+
+/// Passes the server's SASL messages to the client of a `scram_relay` pool
+/// and the client's answers back. Only SCRAM-SHA-256 can be relayed: any
+/// other method needs a secret pg_doorman does not have.
+async fn relay_authentication(
+    stream: &mut StreamInner,
+    auth_code: i32,
+    len: i32,
+    relay: &mut ScramRelay,
+    server_identifier: &ServerIdentifier,
+) -> Result<(), Error> {
+    match auth_code {
+        AUTHENTICATION_SUCCESSFUL => Ok(()),
+
+        SASL | SASL_CONTINUE | SASL_FINAL => {
+            let mut data = vec![0u8; (len - 8) as usize];
+            stream.read_exact(&mut data).await.map_err(|_| {
+                Error::ServerStartupError(
+                    "Failed to read SASL message from server".into(),
+                    server_identifier.clone(),
+                )
+            })?;
+            if auth_code == SASL
+                && !data
+                    .split(|&b| b == 0)
+                    .any(|mechanism| mechanism == SCRAM_SHA_256.as_bytes())
+            {
+                return Err(Error::ServerAuthError(
+                    format!(
+                        "server does not offer {SCRAM_SHA_256}: {}",
+                        String::from_utf8_lossy(&data)
+                    ),
+                    server_identifier.clone(),
+                ));
+            }
+
+            let message = RelayMessage::Sasl {
+                code: auth_code,
+                data: BytesMut::from(&data[..]),
+            };
+            let client_gone = || {
+                Error::ServerAuthError(
+                    "client disconnected during the relayed SCRAM exchange".into(),
+                    server_identifier.clone(),
+                )
+            };
+            if !relay.send(message).await {
+                return Err(client_gone());
+            }
+            if auth_code == SASL_FINAL {
+                return Ok(());
+            }
+            let response = relay.response().await.ok_or_else(client_gone)?;
+
+            let mut res = BytesMut::with_capacity(5 + response.len());
+            res.put_u8(b'p');
+            res.put_i32(4 + response.len() as i32);
+            res.put_slice(&response);
+            write_all_flush(stream, &res).await?;
+            Ok(())
+        }
+
+        _ => {
+            error!(
+                "[{}@{}] scram_relay: server asked for auth method code={}, only SCRAM-SHA-256 can be relayed",
+                server_identifier.username, server_identifier.pool_name, auth_code
+            );
+            Err(Error::ServerAuthError(
+                "scram_relay: the server must use SCRAM-SHA-256 authentication".into(),
+                server_identifier.clone(),
+            ))
+        }
+    }
+}
@@ -1,3 +1,7 @@
+use std::sync::Arc;
+
+use crate::config::ServerCleanupMode;
+
 #[derive(Copy, Clone, Debug)]
 pub(crate) struct CleanupState {
     /// If server connection requires RESET ALL before checkin because of set statement
@@ -48,3 +52,160 @@ impl std::fmt::Display for CleanupState {
         )
     }
 }
+
+/// The `DISCARD ALL` steps minus `DEALLOCATE ALL`, for connections whose
+/// prepared statements are owned by the prepared statement cache.
+const DISCARD_ALL_KEEP_PREPARED: &str = "CLOSE ALL;SET SESSION AUTHORIZATION DEFAULT;RESET ALL;\
+UNLISTEN *;SELECT pg_advisory_unlock_all();DISCARD PLANS;DISCARD TEMP;DISCARD SEQUENCES;";
+
+/// Per-pool checkin cleanup, resolved from `server_cleanup_mode` and
+/// `server_cleanup_query`.
+#[derive(Clone, Debug, Default)]
+pub(crate) enum CleanupPolicy {
+    #[default]
+    ResetAll,
+    DiscardAll,
+    Custom(Arc<str>),
+}
+
+/// `DISCARD ALL` alone, the one reset that must not be combined with
+/// other statements. Also deallocates every prepared statement.
+pub(crate) fn is_discard_all(query: &str) -> bool {
+    let statement = query.trim().trim_end_matches(';').trim_end();
+    let mut words = statement.split_whitespace();
+    matches!(
+        (words.next(), words.next(), words.next()),
+        (Some(a), Some(b), None) if a.eq_ignore_ascii_case("DISCARD") && b.eq_ignore_ascii_case("ALL")
+    )
+}
+
+impl CleanupPolicy {
+    pub(crate) fn from_config(mode: Option<ServerCleanupMode>, query: Option<&str>) -> Self {
+        match (mode.unwrap_or_default(), query) {
+            (ServerCleanupMode::DiscardAll, _) => CleanupPolicy::DiscardAll,
+            (ServerCleanupMode::Custom, Some(query)) => CleanupPolicy::Custom(Arc::from(query)),
+            _ => CleanupPolicy::ResetAll,
+        }
+    }
+
+    /// `discard_all` covers state the tracker cannot see (temp tables,
+    /// advisory locks, LISTEN), so it runs on every checkin.
+    #[inline(always)]
+    pub(crate) fn runs_on_clean(&self) -> bool {
+        matches!(self, CleanupPolicy::DiscardAll)
+    }
+
+    /// Whether the checkin query resets session settings, dropping the
+    /// pool's server timeouts with them. A custom query is assumed to.
+    pub(crate) fn resets_settings(&self, state: &CleanupState) -> bool {
+        match self {
+            CleanupPolicy::ResetAll => state.needs_cleanup_set,
+            CleanupPolicy::DiscardAll | CleanupPolicy::Custom(_) => true,
+        }
+    }
+
+    /// Query to send at checkin and whether it deallocates every prepared
+    /// statement. `keep_prepared` is set when the prepared statement cache
+    /// owns the server-side statements; they are then deallocated only when
+    /// the tracker says the cache is out of sync.
+    pub(crate) fn reset_query(&self, state: &CleanupState, keep_prepared: bool) -> (String, bool) {
+        match self {
+            CleanupPolicy::ResetAll => {
+                let mut query = String::from("RESET ROLE;");
+                if state.needs_cleanup_set {
+                    query.push_str("RESET ALL;");
+                }
+                if state.needs_cleanup_prepare {
+                    query.push_str("DEALLOCATE ALL;");
+                }
+                if state.needs_cleanup_declare {
+                    query.push_str("CLOSE ALL;");
+                }
+                (query, state.needs_cleanup_prepare)
+            }
+            CleanupPolicy::DiscardAll => {
+                if keep_prepared && !state.needs_cleanup_prepare {
+                    (DISCARD_ALL_KEEP_PREPARED.to_string(), false)
+                } else {
+                    // DISCARD ALL refuses to run inside a multi-statement
+                    // query, so it goes alone.
+                    ("DISCARD ALL".to_string(), true)
+                }
+            }
+            CleanupPolicy::Custom(custom) => {
+                let mut query = custom.to_string();
+                if state.needs_cleanup_prepare {
+                    if !query.trim_end().ends_with(';') {
+                        query.push(';');
+                    }
+                    query.push_str("DEALLOCATE ALL;");
+                }
+                (query, state.needs_cleanup_prepare)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dirty(set: bool, prepare: bool, declare: bool) -> CleanupState {
+        CleanupState {
+            needs_cleanup_set: set,
+            needs_cleanup_prepare: prepare,
+            needs_cleanup_declare: declare,
+        }
+    }
+
+    #[test]
+    fn reset_all_sends_only_tracked_statements() {
+        let (query, deallocates) =
+            CleanupPolicy::ResetAll.reset_query(&dirty(true, false, true), true);
+        assert_eq!(query, "RESET ROLE;RESET ALL;CLOSE ALL;");
+        assert!(!deallocates);
+    }
+
+    #[test]
+    fn discard_all_keeps_cached_prepared_statements() {
+        let policy = CleanupPolicy::DiscardAll;
+        let (query, deallocates) = policy.reset_query(&CleanupState::new(), true);
+        assert!(!query.contains("DEALLOCATE"));
+        assert!(!deallocates);
+
+        let (query, deallocates) = policy.reset_query(&dirty(false, true, false), true);
+        assert_eq!(query, "DISCARD ALL");
+        assert!(deallocates);
+
+        let (query, _) = policy.reset_query(&CleanupState::new(), false);
+        assert_eq!(query, "DISCARD ALL");
+    }
+
+    #[test]
+    fn custom_appends_deallocate_when_cache_out_of_sync() {
+        let policy = CleanupPolicy::from_config(Some(ServerCleanupMode::Custom), Some("RESET ALL"));
+        let (query, deallocates) = policy.reset_query(&dirty(true, false, false), true);
+        assert_eq!(query, "RESET ALL");
+        assert!(!deallocates);
+
+        let (query, deallocates) = policy.reset_query(&dirty(true, true, false), true);
+        assert_eq!(query, "RESET ALL;DEALLOCATE ALL;");
+        assert!(deallocates);
+    }
+
+    #[test]
+    fn resets_settings_only_when_settings_are_reset() {
+        assert!(!CleanupPolicy::ResetAll.resets_settings(&dirty(false, true, true)));
+        assert!(CleanupPolicy::ResetAll.resets_settings(&dirty(true, false, false)));
+        assert!(CleanupPolicy::DiscardAll.resets_settings(&CleanupState::new()));
+        let custom = CleanupPolicy::from_config(Some(ServerCleanupMode::Custom), Some("RESET ALL"));
+        assert!(custom.resets_settings(&CleanupState::new()));
+    }
+
+    #[test]
+    fn discard_all_is_recognized_only_alone() {
+        assert!(is_discard_all("discard all;"));
+        assert!(!is_discard_all("DISCARD ALL; RESET ROLE"));
+        assert!(!is_discard_all("DISCARD PLANS"));
+    }
+}
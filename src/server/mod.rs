@@ -24,5 +24,5 @@ pub use prepared_statement_cache::{
 pub use prepared_statement_cache::{
     anon_entry_for_test, named_entry_for_test, reset_interners_for_test,
 };
-pub use server_backend::Server;
+pub use server_backend::{Server, ServerCloseCause};
 pub use stream::StreamInner;
@@ -1,6 +1,6 @@
 use bytes::{BufMut, BytesMut};
 use once_cell::sync::Lazy;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use crate::config::VERSION;
 
@@ -98,6 +98,62 @@ pub fn is_safe_client_startup_key(key: &str) -> bool {
     !is_set_forbidden(&canonical)
 }
 
+/// Split the StartupMessage `options` value into GUC assignments, the way
+/// the backend does: words are separated by whitespace, a backslash escapes
+/// the next character, and both `-c name=value` and `--name=value` forms are
+/// accepted. Dashes in names become underscores. Other switches are ignored.
+pub fn parse_startup_options(options: &str) -> Vec<(String, String)> {
+    let mut words = Vec::new();
+    let mut chars = options.chars().peekable();
+    loop {
+        while chars.peek().is_some_and(|c| c.is_ascii_whitespace()) {
+            chars.next();
+        }
+        if chars.peek().is_none() {
+            break;
+        }
+        let mut word = String::new();
+        while let Some(c) = chars.next() {
+            if c.is_ascii_whitespace() {
+                break;
+            }
+            if c == '\\' {
+                if let Some(escaped) = chars.next() {
+                    word.push(escaped);
+                }
+                continue;
+            }
+            word.push(c);
+        }
+        words.push(word);
+    }
+
+    let mut result = Vec::new();
+    let mut iter = words.into_iter();
+    while let Some(word) = iter.next() {
+        let assignment = if let Some(rest) = word.strip_prefix("--") {
+            rest.to_string()
+        } else if let Some(rest) = word.strip_prefix("-c") {
+            if rest.is_empty() {
+                match iter.next() {
+                    Some(next) => next,
+                    None => break,
+                }
+            } else {
+                rest.to_string()
+            }
+        } else {
+            continue;
+        };
+        if let Some((name, value)) = assignment.split_once('=') {
+            if !name.is_empty() {
+                result.push((name.replace('-', "_"), value.to_string()));
+            }
+        }
+    }
+    result
+}
+
 /// Canonicalise a PostgreSQL session parameter name. Tracked
 /// ParameterStatus names keep PostgreSQL's spelling; other names are
 /// folded to ASCII lower case for stable comparisons and config merges.
@@ -114,6 +170,11 @@ pub fn canonicalize_param_name(key: String) -> String {
     }
 }
 
+/// Quote a name as a SQL identifier, doubling embedded double quotes.
+pub fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
 /// One action produced by `compare_params` for checkout sync.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ParamAction {
@@ -330,6 +391,22 @@ impl ServerParameters {
         h
     }
 
+    /// ParameterStatus messages for the client's login, with `overrides`
+    /// replacing the reported values and adding names not reported yet.
+    pub fn to_bytes_with_overrides(&self, overrides: &BTreeMap<String, String>) -> BytesMut {
+        let mut bytes = BytesMut::new();
+        for (key, value) in &self.parameters {
+            if PARAMETER_STATUS_SUPPRESSED.contains(key.as_str()) || overrides.contains_key(key) {
+                continue;
+            }
+            ServerParameters::add_parameter_message(key, value, &mut bytes);
+        }
+        for (key, value) in overrides {
+            ServerParameters::add_parameter_message(key, value, &mut bytes);
+        }
+        bytes
+    }
+
     fn add_parameter_message(key: &str, value: &str, buffer: &mut BytesMut) {
         buffer.put_u8(b'S');
 
@@ -376,6 +453,33 @@ static PARAMETER_STATUS_SUPPRESSED: Lazy<HashSet<&'static str>> = Lazy::new(|| {
 mod tests {
     use super::*;
 
+    #[test]
+    fn quote_identifier_keeps_case_and_escapes_quotes() {
+        assert_eq!(quote_identifier("Tenant_A"), "\"Tenant_A\"");
+        assert_eq!(quote_identifier("a\"b"), "\"a\"\"b\"");
+    }
+
+    #[test]
+    fn parse_startup_options_accepts_c_and_long_forms() {
+        assert_eq!(
+            parse_startup_options("-c search_path=myschema --work-mem=64MB -cstatement_timeout=5s"),
+            vec![
+                ("search_path".to_string(), "myschema".to_string()),
+                ("work_mem".to_string(), "64MB".to_string()),
+                ("statement_timeout".to_string(), "5s".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_startup_options_handles_escapes_and_junk() {
+        assert_eq!(
+            parse_startup_options(r"  -c search_path=a,\ b -d 1 -c noequals -c"),
+            vec![("search_path".to_string(), "a, b".to_string())]
+        );
+        assert!(parse_startup_options("").is_empty());
+    }
+
     #[test]
     fn canonicalize_timezone_matches_any_case() {
         assert_eq!(canonicalize_param_name("timezone".to_string()), "TimeZone");
@@ -582,6 +686,24 @@ mod tests {
         assert!(blob.contains("server_version"));
     }
 
+    #[test]
+    fn parameter_status_overrides_replace_and_add_values() {
+        let mut sp = ServerParameters::new();
+        sp.set_param("server_version", "16.4", true);
+        sp.set_param("TimeZone", "UTC", false);
+        let overrides = BTreeMap::from([
+            ("server_version".to_string(), "14.0".to_string()),
+            ("is_superuser".to_string(), "off".to_string()),
+        ]);
+        let bytes = sp.to_bytes_with_overrides(&overrides);
+        let blob = String::from_utf8_lossy(&bytes);
+        assert!(blob.contains("server_version\014.0\0"));
+        assert!(!blob.contains("16.4"));
+        assert!(blob.contains("is_superuser\0off\0"));
+        assert!(blob.contains("TimeZone\0UTC\0"));
+        assert_eq!(sp.parameters["server_version"], "16.4");
+    }
+
     #[test]
     fn remove_param_drops_entry_and_invalidates_planner_cache_for_planner_keys() {
         // RESET removes planner state and invalidates the cached digest.
@@ -1,6 +1,7 @@
 use dashmap::DashMap;
 use log::{info, log_enabled, trace, Level};
 use once_cell::sync::Lazy;
+use prometheus::IntCounter;
 use std::sync::atomic::{AtomicU64, AtomicU8, AtomicUsize, Ordering};
 use std::sync::Arc;
 
@@ -487,6 +488,12 @@ pub struct PreparedStatementCache {
     /// inserts; this counter trades one shape of approximation for one
     /// that is far cheaper to read.
     total_memory_bytes: AtomicU64,
+    /// Per-pool `pg_doorman_prepared_cache_evictions_total` series, bumped
+    /// on every size-limit eviction. `None` for caches built outside a pool.
+    eviction_counter: Option<IntCounter>,
+    /// Add hit and miss counts to the eviction log line
+    /// (`log_prepared_cache_evictions`).
+    log_evictions: bool,
 }
 
 /// Per-entry overhead independent of the Parse content (DashMap key + the
@@ -521,9 +528,23 @@ impl PreparedStatementCache {
             max_size: size,
             counter: AtomicU64::new(0),
             total_memory_bytes: AtomicU64::new(0),
+            eviction_counter: None,
+            log_evictions: false,
         }
     }
 
+    /// Attaches the Prometheus counter incremented on every eviction.
+    pub fn with_eviction_counter(mut self, counter: IntCounter) -> Self {
+        self.eviction_counter = Some(counter);
+        self
+    }
+
+    /// Adds the entry's hit statistics to the per-eviction log line.
+    pub fn with_eviction_logging(mut self, enabled: bool) -> Self {
+        self.log_evictions = enabled;
+        self
+    }
+
     /// Adds the prepared statement to the cache if it doesn't exist with a new name
     /// if it already exists will give you the existing parse
     ///
@@ -593,6 +614,11 @@ impl PreparedStatementCache {
         self.cache.is_empty()
     }
 
+    /// Maximum number of entries before the oldest one is evicted.
+    pub fn capacity(&self) -> usize {
+        self.max_size
+    }
+
     /// Approximate memory usage of the cache in bytes. Single atomic load
     /// — kept in sync with `get_or_insert` and `evict_oldest` so the
     /// dashboard polling path does not pay an O(N) walk on every snapshot.
@@ -673,15 +699,34 @@ impl PreparedStatementCache {
             if let Some((_, entry)) = self.cache.remove(&key) {
                 self.total_memory_bytes
                     .fetch_sub(entry_bytes(&entry.parse), Ordering::Relaxed);
-                info!(
-                    "Pool cache eviction: hash={:#x}, kind={}, name={}, query=\"{}\", size={}/{}",
-                    key,
-                    entry.kind().as_str(),
-                    entry.parse.name,
-                    truncate_query_for_log(entry.parse.query()),
-                    self.cache.len(),
-                    self.max_size,
-                );
+                if let Some(counter) = &self.eviction_counter {
+                    counter.inc();
+                }
+                // Hit and miss counts tell a cold one-off apart from a hot
+                // statement pushed out because the cache is too small.
+                if self.log_evictions {
+                    info!(
+                        "Pool cache eviction: hash={:#x}, kind={}, name={}, hits={}, misses={}, query=\"{}\", size={}/{}",
+                        key,
+                        entry.kind().as_str(),
+                        entry.parse.name,
+                        entry.hit_count.load(Ordering::Relaxed),
+                        entry.miss_count.load(Ordering::Relaxed),
+                        truncate_query_for_log(entry.parse.query()),
+                        self.cache.len(),
+                        self.max_size,
+                    );
+                } else {
+                    info!(
+                        "Pool cache eviction: hash={:#x}, kind={}, name={}, query=\"{}\", size={}/{}",
+                        key,
+                        entry.kind().as_str(),
+                        entry.parse.name,
+                        truncate_query_for_log(entry.parse.query()),
+                        self.cache.len(),
+                        self.max_size,
+                    );
+                }
             }
         }
     }
@@ -816,6 +861,25 @@ mod tests {
         assert_eq!(cache.memory_usage(), 0);
     }
 
+    #[test]
+    fn eviction_counter_counts_size_limit_evictions() {
+        let counter = IntCounter::new("test_prepared_cache_evictions", "test").unwrap();
+        let cache = PreparedStatementCache::new(2, 1).with_eviction_counter(counter.clone());
+        assert_eq!(cache.capacity(), 2);
+
+        for (i, query) in ["SELECT 1", "SELECT 2", "SELECT 3", "SELECT 4"]
+            .iter()
+            .enumerate()
+        {
+            cache.get_or_insert(&make_parse("", query), i as u64, None);
+        }
+        // Re-using a cached hash is a hit, not an eviction.
+        cache.get_or_insert(&make_parse("", "SELECT 4"), 3, None);
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(counter.get(), 2);
+    }
+
     #[test]
     fn memory_usage_tracks_inserts_and_eviction() {
         let cache = PreparedStatementCache::new(2, 1);
@@ -91,8 +91,18 @@ pub(crate) async fn send_and_flush_timeout(
     }
 }
 
-/// Flushes messages and records write stats/activity.
+/// Flushes messages and records write stats/activity. Queued client labels
+/// are prepended to the same write.
 pub(crate) async fn send_and_flush(server: &mut Server, messages: &BytesMut) -> Result<(), Error> {
+    if let Some(mut labelled) = server.pending_labels.take() {
+        labelled.extend_from_slice(messages);
+        server.awaiting_label_reply = true;
+        return write_and_flush(server, &labelled).await;
+    }
+    write_and_flush(server, messages).await
+}
+
+async fn write_and_flush(server: &mut Server, messages: &BytesMut) -> Result<(), Error> {
     server.stats.data_sent(messages.len());
     server.stats.wait_writing();
 
@@ -111,8 +121,7 @@ pub(crate) async fn send_and_flush(server: &mut Server, messages: &BytesMut) ->
                 server.address.pool_name,
                 server.get_process_id(),
             );
-            server.mark_bad("failed to flush data to server");
-            Err(err)
+            Err(server.connection_lost(err))
         }
     }
 }
@@ -121,6 +130,46 @@ pub(crate) async fn send_and_flush(server: &mut Server, messages: &BytesMut) ->
 // Helper functions
 // ============================================================================
 
+/// Reads the reply to the client-label `SET` up to its ReadyForQuery and
+/// drops it; the client never asked for it. A rejected `SET` only costs
+/// the labels, so it is logged and the connection stays usable.
+async fn discard_label_reply(server: &mut Server) -> Result<(), Error> {
+    server.awaiting_label_reply = false;
+    loop {
+        let (code_u8, message_len) = match read_message_header(&mut server.stream).await {
+            Ok(header) => header,
+            Err(err) => return Err(server.connection_lost(err)),
+        };
+        let message = match read_message_body_reuse(
+            &mut server.stream,
+            &mut server.read_buf,
+            code_u8,
+            message_len,
+        )
+        .await
+        {
+            Ok(message) => message,
+            Err(err) => return Err(server.connection_lost(err)),
+        };
+        match code_u8 {
+            b'Z' => return Ok(()),
+            b'E' => {
+                if let Ok(msg) = PgErrorMsg::parse(&message[5..]) {
+                    warn!(
+                        "[{}@{}] client label SET rejected pid={}: code={}, message=\"{}\"",
+                        server.address.username,
+                        server.address.pool_name,
+                        server.get_process_id(),
+                        msg.code,
+                        sanitize_for_log(&msg.message),
+                    );
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
 /// Handles large DataRow ('D') messages that exceed max_message_size.
 /// Streams the message directly to the client without buffering.
 async fn handle_large_data_row<C>(
@@ -132,6 +181,7 @@ async fn handle_large_data_row<C>(
 where
     C: tokio::io::AsyncWrite + std::marker::Unpin,
 {
+    server.data_rows_received += 1;
     // Send current buffer + header
     server.buffer.put_u8(code_u8);
     server.buffer.put_i32(message_len);
@@ -375,6 +425,12 @@ fn handle_error_response(server: &mut Server, message: &mut BytesMut) {
         server.address.stats.error_with_sqlstate(&msg.code);
         // Let `small_simple_query` return SQL-level failures as `Err`.
         server.last_sql_error = Some((msg.code.clone(), msg.message.clone()));
+        // The backend closes the connection right after a FATAL; evict it
+        // now and keep the cause for the client.
+        if msg.severity == "FATAL" || msg.severity == "PANIC" {
+            server.mark_bad(&format!("backend sent {} {}", msg.severity, msg.code));
+            server.fatal_error = Some((msg.code.clone(), msg.message.clone()));
+        }
     } else {
         error!(
             "[{}@{}] server error pid={}: could not parse error details",
@@ -547,6 +603,9 @@ fn handle_parameter_status(
     // Update client parameters if tracking is enabled
     if let Some(client_server_parameters) = client_server_parameters.as_mut() {
         client_server_parameters.set_param(&key, &value, false);
+        if key == "application_name" {
+            server.application_name_reported = true;
+        }
         if server.log_client_parameter_status_changes {
             info!(
                 "[{}@{}] parameter changed pid={}: {key}={value}",
@@ -571,6 +630,10 @@ pub(crate) async fn recv<C>(
 where
     C: tokio::io::AsyncWrite + std::marker::Unpin,
 {
+    if server.awaiting_label_reply {
+        discard_label_reply(server).await?;
+    }
+
     // Handle deferred large message from previous recv() call.
     // When recv() encounters a large backend message but the buffer already has
     // accumulated messages, it returns the buffer first (for response ordering)
@@ -608,7 +671,10 @@ where
             break;
         }
 
-        let (code_u8, message_len) = read_message_header(&mut server.stream).await?;
+        let (code_u8, message_len) = match read_message_header(&mut server.stream).await {
+            Ok(header) => header,
+            Err(err) => return Err(server.connection_lost(err)),
+        };
         // Handle large DataRow messages that exceed max_message_size
         if server.max_message_size > 0
             && message_len > server.max_message_size
@@ -707,8 +773,7 @@ where
                     server.address.pool_name,
                     server.get_process_id(),
                 );
-                server.mark_bad(format!("Failed to read message data: {err}").as_str());
-                return Err(err);
+                return Err(server.connection_lost(err));
             }
         };
 
@@ -750,13 +815,9 @@ where
 
             // DataRow
             'D' => {
+                server.data_rows_received += 1;
                 // More data is available after this message, this is not the end of the reply.
                 server.data_available = true;
-
-                // Don't flush yet, the more we buffer, the faster this goes...up to a limit.
-                if server.buffer.len() >= BUFFER_FLUSH_THRESHOLD {
-                    break;
-                }
             }
 
             // CopyInResponse: copy is starting from client to server.
@@ -773,12 +834,7 @@ where
             }
 
             // CopyData
-            'd' => {
-                // Don't flush yet, buffer until we reach limit
-                if server.buffer.len() >= BUFFER_FLUSH_THRESHOLD {
-                    break;
-                }
-            }
+            'd' => (),
 
             // CopyDone
             // Buffer until ReadyForQuery shows up, so don't exit the loop yet.
@@ -867,6 +923,17 @@ where
             // Keep buffering until ReadyForQuery shows up.
             _ => (),
         };
+
+        // Don't flush yet, the more we buffer, the faster this goes...up to a
+        // limit. Past it the chunk goes to the caller, which writes it to the
+        // client before reading on, so a slow client stalls the backend read
+        // instead of growing the buffer. This covers every message type: a
+        // notice flood or a long multi-statement reply is cut the same way
+        // as rows.
+        if server.buffer.len() >= BUFFER_FLUSH_THRESHOLD {
+            server.data_available = true;
+            break;
+        }
     }
 
     let bytes = server.buffer.clone();
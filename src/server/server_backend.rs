@@ -12,6 +12,7 @@ use lru::LruCache;
 use tokio::io::{AsyncReadExt, BufStream};
 
 use crate::auth::scram_client::ScramSha256;
+use crate::auth::scram_relay::{RelayMessage, ScramRelay};
 use crate::config::{get_config, tls, Address, BackendAuthMethod, User};
 use crate::errors::{Error, ServerIdentifier};
 use crate::messages::PgErrorMsg;
@@ -22,8 +23,8 @@ use crate::pool::{CancelTarget, ClientServerMap, CANCELED_PIDS};
 use crate::stats::ServerStats;
 
 use super::authentication::handle_authentication;
-use super::cleanup::CleanupState;
-use super::parameters::ServerParameters;
+use super::cleanup::{CleanupPolicy, CleanupState};
+use super::parameters::{quote_identifier, ServerParameters};
 use super::stream::{create_tcp_stream_inner, create_unix_stream_inner, StreamInner};
 use super::{prepared_statements, protocol_io, startup_cancel};
 
@@ -111,6 +112,9 @@ pub struct Server {
     /// before returning them to the pool. If false, discard dirty connections instead.
     cleanup_connections: bool,
 
+    /// Statements sent by `checkin_cleanup`, from the pool's `server_cleanup_mode`.
+    cleanup_policy: CleanupPolicy,
+
     /// Configuration flag: if true, log when server parameters change for debugging purposes.
     pub(crate) log_client_parameter_status_changes: bool,
 
@@ -153,10 +157,34 @@ pub struct Server {
     /// The large DataRow/CopyData/FunctionCallResponse will be streamed on the next recv() call.
     pub(crate) pending_large_message: Option<(u8, i32)>,
 
+    /// Client-label `SET` queued at checkout. It goes out in the same write
+    /// as the next message to the server, so labelling costs no round trip.
+    pub(crate) pending_labels: Option<BytesMut>,
+
+    /// The reply to `pending_labels` has not been read yet; `recv` drops it
+    /// before reading the reply the client is waiting for.
+    pub(crate) awaiting_label_reply: bool,
+
+    /// The backend reported a new `application_name` to the client during
+    /// the current round trip; the client picks it up for its label.
+    pub(crate) application_name_reported: bool,
+
+    /// A user's `server_role` was set at checkout; checkin sends `RESET ROLE`
+    /// so the role does not carry over to the next client.
+    pub(crate) role_applied: bool,
+
+    /// DataRow messages received over the life of the connection. The
+    /// client handler compares it across a round trip for `max_result_rows`.
+    pub(crate) data_rows_received: u64,
+
     /// Reason for closing this connection, set before dropping.
     /// Used by Drop to produce a single log line with cause and effect.
     pub(crate) close_reason: Option<String>,
 
+    /// Close reason label for the lifetime histogram and recycle counter.
+    /// The first cause recorded wins.
+    pub(crate) close_cause: Option<ServerCloseCause>,
+
     /// Per-connection lifetime override (ms). Set on fallback connections so
     /// they expire before the local backend recovers.
     pub(crate) override_lifetime_ms: Option<u64>,
@@ -167,11 +195,20 @@ pub struct Server {
     /// same set.
     operator_managed_startup_keys: Arc<HashSet<String>>,
 
+    /// `SET` statements for the pool's `server_statement_timeout_ms` and
+    /// `server_lock_timeout_ms`. Sent after connect and again after a
+    /// checkin cleanup that resets settings.
+    server_timeouts_query: Option<String>,
+
     /// Most recent PostgreSQL ErrorResponse for the current backend
     /// exchange. `small_simple_query` uses it to return SQL failures as
     /// `Err`, so callers do not mirror rejected SET/RESET operations
     /// into the backend snapshot.
     pub(crate) last_sql_error: Option<(String, String)>,
+
+    /// FATAL or PANIC ErrorResponse the backend sent before closing the
+    /// connection. Reported to the client by [`Server::connection_lost`].
+    pub(crate) fatal_error: Option<(String, String)>,
 }
 
 impl std::fmt::Display for Server {
@@ -225,6 +262,66 @@ impl Server {
         Ok(())
     }
 
+    /// Runs `connect_queries` in order on a fresh connection. The set is all
+    /// or nothing: the first failure marks the connection bad so it is
+    /// closed before any client sees a half-prepared session. Returns the
+    /// failure description.
+    async fn run_connect_queries(&mut self, queries: &[String]) -> Result<(), String> {
+        for (idx, query) in queries.iter().enumerate() {
+            let failure = match self.small_simple_query(query).await {
+                Ok(()) if self.in_transaction() => {
+                    "left a transaction open; connect_queries must not end inside BEGIN".to_string()
+                }
+                Ok(()) => continue,
+                Err(err) => err.to_string(),
+            };
+            let reason = format!("connect_queries[{idx}] ({query}) failed: {failure}");
+            self.close_reason = Some(format!("connect_queries[{idx}] failed"));
+            self.mark_bad(&reason);
+            return Err(reason);
+        }
+        Ok(())
+    }
+
+    /// Asks the backend whether it is a standby, for `target_session_attrs`
+    /// routing across a pool's `hosts`.
+    async fn query_in_recovery(&mut self) -> Result<bool, Error> {
+        self.last_sql_error = None;
+        self.send_and_flush(&simple_query("SELECT pg_is_in_recovery()"))
+            .await?;
+
+        let mut reply = BytesMut::new();
+        let mut noop = tokio::io::sink();
+        loop {
+            reply.extend_from_slice(&self.recv(&mut noop, None).await?);
+            if !self.data_available {
+                break;
+            }
+        }
+        if let Some((sqlstate, message)) = self.last_sql_error.take() {
+            return Err(Error::QueryError(format!(
+                "pg_is_in_recovery() failed (SQLSTATE {sqlstate}): {message}"
+            )));
+        }
+
+        // DataRow: code, length, column count, value length, value.
+        while reply.remaining() >= 5 {
+            let code = reply.get_u8();
+            let len = reply.get_i32() as usize;
+            if len < 4 || reply.remaining() < len - 4 {
+                break;
+            }
+            let mut body = reply.split_to(len - 4);
+            if code == b'D' && body.remaining() >= 6 && body.get_i16() == 1 {
+                let value_len = body.get_i32();
+                return Ok(value_len == 1 && body.first() == Some(&b't'));
+            }
+        }
+        Err(Error::QueryError(
+            "pg_is_in_recovery() returned no row".to_string(),
+        ))
+    }
+
     /// Check if the connection is alive by sending a minimal query (`;`).
     /// Uses the provided timeout for the operation.
     /// Returns Ok(()) if connection is alive, Err if dead or timeout exceeded.
@@ -283,6 +380,30 @@ impl Server {
             self.address.username, self.address.pool_name, self.process_id
         );
         self.bad = true;
+        self.close_cause.get_or_insert(ServerCloseCause::Bad);
+    }
+
+    /// Marks the connection bad after a failed read or write and builds the
+    /// error for the client: the backend's FATAL if it sent one before
+    /// closing, otherwise 08006.
+    pub(crate) fn connection_lost(&mut self, err: Error) -> Error {
+        if matches!(err, Error::ProtocolSyncError(_)) {
+            crate::web::metrics::record_protocol_error("server");
+        }
+        self.mark_bad(&format!("connection lost: {err}"));
+        let (sqlstate, message) = self.fatal_error.take().unwrap_or_else(|| {
+            (
+                "08006".to_string(),
+                "server closed the connection unexpectedly".to_string(),
+            )
+        });
+        Error::ServerConnectionLost { sqlstate, message }
+    }
+
+    /// Records why this connection is about to be closed. Keeps an earlier
+    /// cause, so a specific reason is not overwritten by a later `Bad`.
+    pub fn set_close_cause(&mut self, cause: ServerCloseCause) {
+        self.close_cause.get_or_insert(cause);
     }
 
     /// Returns a future that completes when the server socket becomes readable.
@@ -374,6 +495,12 @@ impl Server {
         protocol_io::send_and_flush(self, messages).await
     }
 
+    /// Label this connection for the current client: `query` is sent ahead
+    /// of the next message and its reply is discarded.
+    pub fn queue_client_labels(&mut self, query: &str) {
+        self.pending_labels = Some(simple_query(query));
+    }
+
     /// If the server is still inside a transaction.
     /// If the client disconnects while the server is in a transaction, we will clean it up.
     #[inline(always)]
@@ -398,6 +525,7 @@ impl Server {
     /// connection back in the pool
     pub async fn checkin_cleanup(&mut self) -> Result<(), Error> {
         self.pending_large_message = None;
+        self.pending_labels = None;
         if self.in_copy_mode() {
             warn!(
                 "[{}@{}] server returned in copy-mode pid={}",
@@ -443,6 +571,12 @@ impl Server {
             self.small_simple_query("ROLLBACK").await?;
         }
 
+        // `RESET ALL` and the other reset queries leave the role alone.
+        if self.role_applied {
+            self.run_preserving_set_flag("RESET ROLE").await?;
+            self.role_applied = false;
+        }
+
         // If the client added prepared statements to the cache but disconnected
         // before Sync/Flush, the cache contains entries that were never sent to
         // PostgreSQL. Force DEALLOCATE ALL to re-synchronize.
@@ -464,27 +598,34 @@ impl Server {
         // to avoid leaking state between clients. For performance reasons we only
         // send `RESET ALL` if we think the session is altered instead of just sending
         // it before each checkin.
-        if self.cleanup_state.needs_cleanup() && self.cleanup_connections {
-            info!(
-                "[{}@{}] session state cleanup pid={}: {}",
-                self.address.username, self.address.pool_name, self.process_id, self.cleanup_state
-            );
-            let mut reset_string = String::from("RESET ROLE;");
-
-            if self.cleanup_state.needs_cleanup_set {
-                reset_string.push_str("RESET ALL;");
-            };
-
-            if self.cleanup_state.needs_cleanup_prepare {
-                reset_string.push_str("DEALLOCATE ALL;");
-            };
-
-            if self.cleanup_state.needs_cleanup_declare {
-                reset_string.push_str("CLOSE ALL;");
-            };
+        let dirty = self.cleanup_state.needs_cleanup();
+        if (dirty || self.cleanup_policy.runs_on_clean()) && self.cleanup_connections {
+            if dirty {
+                info!(
+                    "[{}@{}] session state cleanup pid={}: {}",
+                    self.address.username,
+                    self.address.pool_name,
+                    self.process_id,
+                    self.cleanup_state
+                );
+            }
+            let (reset_string, deallocates) = self
+                .cleanup_policy
+                .reset_query(&self.cleanup_state, self.prepared_statement_cache.is_some());
+            let resets_settings = self.cleanup_policy.resets_settings(&self.cleanup_state);
 
             self.small_simple_query(&reset_string).await?;
-            if self.cleanup_state.needs_cleanup_prepare {
+            // The reset dropped the pool's timeouts along with the client's
+            // settings. Sent separately because DISCARD ALL must run alone.
+            if resets_settings {
+                // A statement_timeout applied by checkout sync is gone too;
+                // forget it so the next checkout sets it again.
+                self.server_parameters.remove_param("statement_timeout");
+                if let Some(query) = self.server_timeouts_query.clone() {
+                    self.small_simple_query(&query).await?;
+                }
+            }
+            if deallocates {
                 // flush prepared.
                 self.registering_prepared_statement.clear();
                 if self.prepared_statement_cache.is_some() {
@@ -642,18 +783,21 @@ impl Server {
 
     /// Claim this server as mine for the purposes of query cancellation.
     pub fn claim(&mut self, process_id: i32, secret_key: i32) {
-        self.client_server_map.insert(
-            (process_id, secret_key),
-            CancelTarget {
-                process_id: self.process_id,
-                secret_key: self.secret_key,
-                host: self.address.host.clone(),
-                port: self.address.port,
-                server_tls: self.address.server_tls.clone(),
-                connected_with_tls: self.connected_with_tls,
-                pool_name: self.address.pool_name.clone(),
-            },
-        );
+        self.client_server_map
+            .insert((process_id, secret_key), self.cancel_target());
+    }
+
+    /// Everything needed to send a CancelRequest for this backend.
+    pub fn cancel_target(&self) -> CancelTarget {
+        CancelTarget {
+            process_id: self.process_id,
+            secret_key: self.secret_key,
+            host: self.address.host.clone(),
+            port: self.address.port,
+            server_tls: self.address.server_tls.clone(),
+            connected_with_tls: self.connected_with_tls,
+            pool_name: self.address.pool_name.clone(),
+        }
     }
 
     /// Determines if the server already has a prepared statement with the given name.
@@ -708,7 +852,26 @@ impl Server {
     }
 
     pub async fn sync_parameters(&mut self, parameters: &ServerParameters) -> Result<(), Error> {
+        self.sync_parameters_matching(parameters, |_| true).await
+    }
+
+    /// Checkout sync of `statement_timeout` alone, for pools with
+    /// `default_client_statement_timeout_ms` but no `sync_server_parameters`.
+    pub async fn sync_statement_timeout(
+        &mut self,
+        parameters: &ServerParameters,
+    ) -> Result<(), Error> {
+        self.sync_parameters_matching(parameters, |key| key == "statement_timeout")
+            .await
+    }
+
+    async fn sync_parameters_matching(
+        &mut self,
+        parameters: &ServerParameters,
+        matches: impl Fn(&str) -> bool,
+    ) -> Result<(), Error> {
         let mut parameter_diff = self.server_parameters.compare_params(parameters);
+        parameter_diff.retain(|k, _| matches(k));
 
         // Configured startup_parameters win over client StartupMessage values.
         if !self.operator_managed_startup_keys.is_empty() {
@@ -761,6 +924,23 @@ impl Server {
         res
     }
 
+    /// Switch the session to the user's `server_role` for this checkout.
+    pub async fn set_role(&mut self, role: &str) -> Result<(), Error> {
+        // Marked before sending so a failed attempt is still reset at checkin.
+        self.role_applied = true;
+        self.run_preserving_set_flag(&format!("SET ROLE {}", quote_identifier(role)))
+            .await
+    }
+
+    /// `SET ROLE` and `RESET ROLE` would otherwise arm or disarm the cleanup
+    /// of settings the client changed itself.
+    async fn run_preserving_set_flag(&mut self, query: &str) -> Result<(), Error> {
+        let needs_cleanup_set = self.cleanup_state.needs_cleanup_set;
+        let res = self.small_simple_query(query).await;
+        self.cleanup_state.needs_cleanup_set = needs_cleanup_set;
+        res
+    }
+
     /// Issue a query cancellation request to the server.
     /// Uses a separate connection that's not part of the connection pool.
     pub async fn cancel(
@@ -796,6 +976,10 @@ impl Server {
     /// (`general` -> pool -> auth_query). It is sent in the backend
     /// `StartupMessage`. If PostgreSQL rejects a value, pg_doorman forwards
     /// the `ErrorResponse` unchanged.
+    ///
+    /// With `scram_relay` the connection belongs to one client of a
+    /// `scram_relay` pool: its SCRAM exchange is relayed to the backend
+    /// and a startup error is passed on to it.
     #[allow(clippy::too_many_arguments)]
     pub async fn startup(
         address: &Address,
@@ -810,6 +994,7 @@ impl Server {
         session_mode: bool,
         startup_parameters: &std::collections::BTreeMap<String, String>,
         operator_managed_startup_keys: Arc<HashSet<String>>,
+        mut scram_relay: Option<&mut ScramRelay>,
     ) -> Result<Server, Error> {
         let config = get_config();
 
@@ -944,6 +1129,7 @@ impl Server {
                         &mut scram_client_auth,
                         &server_identifier,
                         backend_auth_snapshot.as_ref(),
+                        scram_relay.as_deref_mut(),
                     )
                     .await?;
 
@@ -975,6 +1161,15 @@ impl Server {
                         ));
                     };
 
+                    if let Some(relay) = scram_relay.as_deref_mut() {
+                        relay
+                            .send(RelayMessage::Error {
+                                sqlstate: msg.code.clone(),
+                                message: msg.message.clone(),
+                            })
+                            .await;
+                    }
+
                     if msg.code.starts_with("57P") {
                         return Err(Error::ServerUnavailableError(
                             msg.message,
@@ -1087,7 +1282,7 @@ impl Server {
                         phase_started.elapsed().as_secs_f64(),
                     );
 
-                    let server = Server {
+                    let mut server = Server {
                         address: address.to_owned(),
                         stream: BufStream::new(stream),
                         buffer: BytesMut::with_capacity(BUFFER_FLUSH_THRESHOLD),
@@ -1108,6 +1303,16 @@ impl Server {
                         application_name,
                         last_activity: SystemTime::now(),
                         cleanup_connections,
+                        cleanup_policy: config
+                            .pools
+                            .get(&address.pool_name)
+                            .map(|pool| {
+                                CleanupPolicy::from_config(
+                                    pool.server_cleanup_mode,
+                                    pool.server_cleanup_query.as_deref(),
+                                )
+                            })
+                            .unwrap_or_default(),
                         log_client_parameter_status_changes,
                         prepared_statement_cache: match server_prepared_statement_cache_size {
                             0 => None,
@@ -1120,17 +1325,73 @@ impl Server {
                         deferred_eviction_closes: Vec::new(),
                         connected_with_tls,
                         session_mode,
-                        max_message_size: config.general.message_size_to_be_stream.as_bytes()
-                            as i32,
+                        max_message_size: config
+                            .pools
+                            .get(&address.pool_name)
+                            .and_then(|pool| pool.message_size_to_be_stream)
+                            .unwrap_or(config.general.message_size_to_be_stream)
+                            .as_bytes() as i32,
                         pending_large_message: None,
+                        pending_labels: None,
+                        awaiting_label_reply: false,
+                        application_name_reported: false,
+                        role_applied: false,
+                        data_rows_received: 0,
                         close_reason: None,
+                        close_cause: None,
                         override_lifetime_ms: None,
                         operator_managed_startup_keys,
+                        server_timeouts_query: config
+                            .pools
+                            .get(&address.pool_name)
+                            .and_then(|pool| pool.server_timeouts_query()),
                         last_sql_error: None,
+                        fatal_error: None,
                     };
                     server.stats.update_process_id(process_id);
                     server.stats.set_tls(connected_with_tls);
 
+                    let connect_queries = config
+                        .pools
+                        .get(&address.pool_name)
+                        .map(|pool| pool.resolve_connect_queries(user))
+                        .unwrap_or_default();
+                    if let Err(err) = server.run_connect_queries(connect_queries).await {
+                        return Err(Error::ServerStartupError(err, server_identifier));
+                    }
+                    // After connect_queries, so the configured timeouts win.
+                    if let Some(query) = server.server_timeouts_query.clone() {
+                        if let Err(err) = server.small_simple_query(&query).await {
+                            let reason = format!("server timeouts ({query}) failed: {err}");
+                            server.close_reason = Some("server timeouts failed".to_string());
+                            server.mark_bad(&reason);
+                            return Err(Error::ServerStartupError(reason, server_identifier));
+                        }
+                    }
+
+                    // Members of a multi-host pool are routed by role, so every
+                    // fresh connection refreshes its backend's role.
+                    let multi_host = config
+                        .pools
+                        .get(&address.pool_name)
+                        .is_some_and(|pool| !pool.hosts.is_empty());
+                    if multi_host {
+                        match server.query_in_recovery().await {
+                            Ok(in_recovery) => crate::pool::target_session::record_backend_role(
+                                &address.host,
+                                address.port,
+                                in_recovery,
+                            ),
+                            Err(err) => warn!(
+                                "[{}@{}] could not classify {}:{} for target_session_attrs: {err}",
+                                server_identifier.username,
+                                server_identifier.pool_name,
+                                address.host,
+                                address.port
+                            ),
+                        }
+                    }
+
                     return Ok(server);
                 }
 
@@ -1147,6 +1408,44 @@ impl Server {
     }
 }
 
+/// Why a server connection was closed. Used as the `reason` label of
+/// `pg_doorman_server_connection_lifetime_seconds` and
+/// `pg_doorman_server_recycles_total`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerCloseCause {
+    /// Idle longer than `idle_timeout`, or an idle reserve connection.
+    Idle,
+    /// Older than `server_lifetime`.
+    Lifetime,
+    /// Older than `server_max_age`, closed at a transaction boundary.
+    Age,
+    /// Broken connection, protocol error or failed alive check.
+    Bad,
+    /// Closed by RECONNECT or an outdated reconnect epoch.
+    Reconnect,
+    /// Evicted by the pool coordinator to free a slot for another pool.
+    Evicted,
+    /// Closed by graceful shutdown or binary upgrade.
+    Shutdown,
+    /// Anything else, e.g. the pool itself being dropped on RELOAD.
+    Other,
+}
+
+impl ServerCloseCause {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ServerCloseCause::Idle => "idle",
+            ServerCloseCause::Lifetime => "lifetime",
+            ServerCloseCause::Age => "age",
+            ServerCloseCause::Bad => "bad",
+            ServerCloseCause::Reconnect => "reconnect",
+            ServerCloseCause::Evicted => "evicted",
+            ServerCloseCause::Shutdown => "shutdown",
+            ServerCloseCause::Other => "other",
+        }
+    }
+}
+
 impl Drop for Server {
     /// Try to do a clean shut down. Best effort because
     /// the socket is in non-blocking mode, so it may not be ready
@@ -1180,6 +1479,20 @@ impl Drop for Server {
         let duration = now - self.connected_at;
         let session = crate::utils::format_duration(&duration);
 
+        let cause = self.close_cause.unwrap_or(
+            if crate::app::server::SHUTDOWN_IN_PROGRESS.load(std::sync::atomic::Ordering::Relaxed) {
+                ServerCloseCause::Shutdown
+            } else {
+                ServerCloseCause::Other
+            },
+        );
+        crate::web::metrics::record_server_close(
+            &self.address.username,
+            &self.address.pool_name,
+            cause.as_str(),
+            duration.num_milliseconds().max(0) as f64 / 1000.0,
+        );
+
         match (&self.close_reason, self.bad) {
             (Some(reason), _) => info!(
                 "[{}@{}] server closed pid={}: {}, session={}",
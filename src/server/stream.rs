@@ -3,7 +3,7 @@ use std::time::Instant;
 
 use crate::config::tls::ServerTlsConfig;
 use crate::errors::Error;
-use crate::messages::{configure_tcp_socket, configure_unix_socket, ssl_request};
+use crate::messages::{configure_server_tcp_socket, configure_unix_socket, ssl_request};
 
 use pin_project_lite::pin_project;
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite};
@@ -180,7 +180,7 @@ pub(crate) async fn create_tcp_stream_inner(
         }
     };
 
-    configure_tcp_socket(&stream);
+    configure_server_tcp_socket(&stream, pool_name);
 
     crate::web::metrics::observe_backend_create_phase(
         "tcp_connect",
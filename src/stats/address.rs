@@ -4,6 +4,10 @@ use parking_lot::Mutex;
 use std::collections::HashMap;
 use std::sync::atomic::*;
 
+use super::application::{
+    with_application_bucket, ApplicationStatFields, ApplicationStatsSnapshot,
+};
+
 /// Fields for tracking various statistics related to PostgreSQL connections by address.
 ///
 /// Each field is an atomic counter allowing safe sharing and updating
@@ -104,6 +108,12 @@ pub struct AddressStats {
     /// new shard entry under a brief write lock.
     pub errors_by_sqlstate: DashMap<String, AtomicU64>,
 
+    /// Cumulative query and transaction counters keyed by client
+    /// `application_name`. Only filled while
+    /// `general.stats_application_names` is set; the keys are limited to
+    /// that allowlist plus `other`.
+    pub by_application: DashMap<String, ApplicationStatFields>,
+
     /// Process-unique identifier for this `AddressStats` instance.
     /// Every `Default::default()` mints a fresh value from a static
     /// monotonic counter. The Prometheus scrape path passes this into
@@ -140,6 +150,7 @@ impl Default for AddressStats {
             wait_histogram: Mutex::new(new_histogram()),
             p95_xact_time_us: AtomicU64::new(0),
             errors_by_sqlstate: DashMap::new(),
+            by_application: DashMap::new(),
             generation: next_address_stats_generation(),
         }
     }
@@ -402,6 +413,53 @@ impl AddressStats {
             .collect()
     }
 
+    /// Counts one transaction under the application's bucket. No-op while
+    /// the per-application breakdown is off.
+    #[inline(always)]
+    pub fn application_xact_add(&self, application_name: &str) {
+        with_application_bucket(application_name, |bucket| {
+            self.application_fields(bucket, |fields| {
+                fields.xact_count.fetch_add(1, Ordering::Relaxed);
+            })
+        });
+    }
+
+    /// Counts one query and its time under the application's bucket.
+    /// No-op while the per-application breakdown is off.
+    #[inline(always)]
+    pub fn application_query_add(&self, application_name: &str, microseconds: u64) {
+        with_application_bucket(application_name, |bucket| {
+            self.application_fields(bucket, |fields| {
+                fields.query_count.fetch_add(1, Ordering::Relaxed);
+                fields
+                    .query_time_microseconds
+                    .fetch_add(microseconds, Ordering::Relaxed);
+            })
+        });
+    }
+
+    /// Same locking shape as `error_with_sqlstate`: a shard read lock once
+    /// the bucket exists, a brief write lock on its first use.
+    #[inline(always)]
+    fn application_fields(&self, bucket: &str, f: impl FnOnce(&ApplicationStatFields)) {
+        if let Some(fields) = self.by_application.get(bucket) {
+            f(&fields);
+            return;
+        }
+        f(&self.by_application.entry(bucket.to_string()).or_default());
+    }
+
+    /// Per-application counters sorted by application name.
+    pub fn by_application_snapshot(&self) -> Vec<ApplicationStatsSnapshot> {
+        let mut rows: Vec<ApplicationStatsSnapshot> = self
+            .by_application
+            .iter()
+            .map(|kv| ApplicationStatsSnapshot::new(kv.key(), kv.value()))
+            .collect();
+        rows.sort_by(|a, b| a.application_name.cmp(&b.application_name));
+        rows
+    }
+
     /// Returns transaction time percentiles (p50, p90, p95, p99) in microseconds.
     ///
     /// Uses HDR histogram for O(1) percentile calculation.
@@ -428,6 +486,18 @@ impl AddressStats {
         )
     }
 
+    /// Returns the longest transaction time recorded in the current stats
+    /// period, in microseconds. Zero when no transaction finished yet.
+    pub fn get_xact_max(&self) -> u64 {
+        self.xact_histogram.lock().max()
+    }
+
+    /// Returns the longest query time recorded in the current stats
+    /// period, in microseconds. Zero when no query finished yet.
+    pub fn get_query_max(&self) -> u64 {
+        self.query_histogram.lock().max()
+    }
+
     /// Returns client checkout (wait) time percentiles (p50, p90, p95, p99)
     /// in microseconds.
     ///
@@ -682,6 +752,35 @@ mod tests {
         assert_eq!(snap.len(), 3);
     }
 
+    #[test]
+    #[serial_test::serial(application_allowlist)]
+    fn test_application_breakdown_folds_unlisted_names() {
+        let stats = AddressStats::default();
+
+        // Off by default: nothing is tracked.
+        crate::stats::application::update_application_allowlist(&[]);
+        stats.application_query_add("billing", 100);
+        assert!(stats.by_application_snapshot().is_empty());
+
+        crate::stats::application::update_application_allowlist(&["billing".to_string()]);
+        stats.application_xact_add("billing");
+        stats.application_query_add("billing", 100);
+        stats.application_query_add("billing", 300);
+        stats.application_query_add("psql", 50);
+        stats.application_query_add("", 50);
+        crate::stats::application::update_application_allowlist(&[]);
+
+        let rows = stats.by_application_snapshot();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].application_name, "billing");
+        assert_eq!(rows[0].xact_count, 1);
+        assert_eq!(rows[0].query_count, 2);
+        assert_eq!(rows[0].avg_query_time(), 200);
+        assert_eq!(rows[1].application_name, "other");
+        assert_eq!(rows[1].query_count, 2);
+        assert_eq!(rows[1].query_time_microseconds, 100);
+    }
+
     #[test]
     fn test_error_with_sqlstate_snapshot_empty_by_default() {
         let stats = AddressStats::default();
@@ -829,6 +928,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_xact_and_query_max() {
+        let stats = AddressStats::default();
+        assert_eq!(stats.get_xact_max(), 0);
+        assert_eq!(stats.get_query_max(), 0);
+
+        stats.xact_time_add(1_500);
+        stats.xact_time_add(42_000);
+        stats.xact_time_add(700);
+        stats.query_time_add_microseconds(250);
+        stats.query_time_add_microseconds(9_000);
+
+        // HDR histogram with 3 significant digits: allow 0.1% error.
+        assert!((41_958..=42_042).contains(&stats.get_xact_max()));
+        assert!((8_991..=9_009).contains(&stats.get_query_max()));
+
+        stats.reset_histograms();
+        assert_eq!(stats.get_xact_max(), 0);
+        assert_eq!(stats.get_query_max(), 0);
+    }
+
     #[test]
     fn test_histogram_reset() {
         let stats = AddressStats::default();
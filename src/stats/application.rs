@@ -0,0 +1,83 @@
+//! Per-application query statistics.
+//!
+//! Pools are shared by many services that only differ by the
+//! `application_name` they send at startup. When
+//! `general.stats_application_names` lists some of those names, every
+//! query and transaction is also counted under its application inside the
+//! pool's `AddressStats`; names outside the list fold into `other`, so the
+//! number of buckets per pool never exceeds the list length plus one. An
+//! empty list (the default) turns the breakdown off and costs one atomic
+//! load per query.
+
+use arc_swap::ArcSwap;
+use once_cell::sync::Lazy;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Bucket for application names that are not in the allowlist.
+pub const OTHER_APPLICATION: &str = "other";
+
+static ALLOWLIST: Lazy<ArcSwap<HashSet<String>>> =
+    Lazy::new(|| ArcSwap::from_pointee(HashSet::new()));
+
+/// Replaces the allowlist. Called whenever a config is installed.
+pub fn update_application_allowlist(names: &[String]) {
+    ALLOWLIST.store(Arc::new(names.iter().cloned().collect()));
+}
+
+/// Runs `f` with the bucket `application_name` is counted under, or does
+/// nothing while the breakdown is off.
+#[inline(always)]
+pub(crate) fn with_application_bucket(application_name: &str, f: impl FnOnce(&str)) {
+    let allowlist = ALLOWLIST.load();
+    if allowlist.is_empty() {
+        return;
+    }
+    if allowlist.contains(application_name) {
+        f(application_name)
+    } else {
+        f(OTHER_APPLICATION)
+    }
+}
+
+/// Cumulative counters of one application bucket in one pool.
+#[derive(Debug, Default)]
+pub struct ApplicationStatFields {
+    /// Number of transactions processed
+    pub xact_count: AtomicU64,
+
+    /// Number of queries processed
+    pub query_count: AtomicU64,
+
+    /// Total query processing time in microseconds
+    pub query_time_microseconds: AtomicU64,
+}
+
+/// Plain copy of `ApplicationStatFields` for SHOW STATS_BY_APP and
+/// the Prometheus exporter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApplicationStatsSnapshot {
+    pub application_name: String,
+    pub xact_count: u64,
+    pub query_count: u64,
+    pub query_time_microseconds: u64,
+}
+
+impl ApplicationStatsSnapshot {
+    pub(crate) fn new(application_name: &str, fields: &ApplicationStatFields) -> Self {
+        Self {
+            application_name: application_name.to_string(),
+            xact_count: fields.xact_count.load(Ordering::Relaxed),
+            query_count: fields.query_count.load(Ordering::Relaxed),
+            query_time_microseconds: fields.query_time_microseconds.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Mean query time in microseconds, 0 before the first query.
+    pub fn avg_query_time(&self) -> u64 {
+        self.query_time_microseconds
+            .checked_div(self.query_count)
+            .unwrap_or(0)
+    }
+}
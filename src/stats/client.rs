@@ -1,5 +1,7 @@
 use super::{get_reporter, Reporter};
 use iota::iota;
+use parking_lot::Mutex;
+use std::collections::HashMap;
 use std::sync::atomic::*;
 use std::sync::Arc;
 
@@ -80,15 +82,19 @@ pub struct ClientStats {
     connection_id: u64,
 
     /// Client metadata - these fields are set when the ClientStats is constructed and not modified after
+    /// (except `application_name`, see `track_application_name`)
     /// ------------------------------------------------------------------------------------------
-    /// Name of the application that established the connection
-    application_name: String,
+    /// Name of the application that established the connection, or the one
+    /// it set later in the session
+    application_name: Mutex<String>,
     /// PostgreSQL username used for the connection
     username: String,
     /// Name of the connection pool this client is using
     pool_name: String,
     /// IP address of the client
     ipaddr: String,
+    /// Client tag from the `pg_doorman.tag` startup parameter, empty if none
+    tag: String,
     /// Timestamp when the client connected
     connect_time: quanta::Instant,
     /// Whether the client is using TLS/SSL encryption
@@ -135,6 +141,12 @@ pub struct ClientStats {
     pub prepared_anonymous_count: AtomicU64,
     /// Cumulative count of Anonymous LRU evictions in client's prepared statement cache
     pub prepared_anonymous_evictions: AtomicU64,
+    /// Server name of each Named prepared statement, by client name, for
+    /// `SHOW PREPARED_STATEMENTS CLIENTS`.
+    prepared_names: Mutex<HashMap<String, String>>,
+    /// Named Parses that reused a name already bound to a different query.
+    pub prepared_remaps: AtomicU64,
+
     /// Whether this client is async (uses Flush instead of Sync)
     pub is_async_client: AtomicBool,
 }
@@ -154,10 +166,11 @@ impl Default for ClientStats {
         ClientStats {
             connection_id: 0,
             connect_time: clock::now(),
-            application_name: String::new(),
+            application_name: Mutex::new(String::new()),
             username: String::new(),
             pool_name: String::new(),
             ipaddr: String::new(),
+            tag: String::new(),
             total_wait_time: AtomicU64::new(0),
             max_wait_time: AtomicU64::new(0),
             state_wait: AtomicU8::new(Self::pack(CLIENT_STATE_IDLE, CLIENT_WAIT_IDLE)),
@@ -170,6 +183,8 @@ impl Default for ClientStats {
             prepared_named_count: AtomicU64::new(0),
             prepared_anonymous_count: AtomicU64::new(0),
             prepared_anonymous_evictions: AtomicU64::new(0),
+            prepared_names: Mutex::new(HashMap::new()),
+            prepared_remaps: AtomicU64::new(0),
             is_async_client: AtomicBool::new(false),
             reporter: get_reporter(),
             use_tls: false,
@@ -277,7 +292,7 @@ impl ClientStats {
         Self {
             connection_id,
             connect_time,
-            application_name: application_name.to_string(),
+            application_name: Mutex::new(application_name.to_string()),
             username: username.to_string(),
             pool_name: pool_name.to_string(),
             ipaddr: ipaddr.to_string(),
@@ -286,6 +301,12 @@ impl ClientStats {
         }
     }
 
+    /// Attaches the client tag shown in SHOW CLIENTS.
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tag = tag.to_string();
+        self
+    }
+
     //
     // Client lifecycle management
     // ------------------------------------------------------------------------------------------
@@ -429,10 +450,19 @@ impl ClientStats {
         self.connection_id
     }
 
-    /// Returns the name of the application that established the connection.
+    /// Returns the current application name of the client.
     #[inline(always)]
-    pub fn application_name(&self) -> &str {
-        &self.application_name
+    pub fn application_name(&self) -> String {
+        self.application_name.lock().clone()
+    }
+
+    /// Replaces the application name after the client changed it with
+    /// `SET application_name`.
+    pub fn set_application_name(&self, name: &str) {
+        let mut application_name = self.application_name.lock();
+        if *application_name != name {
+            *application_name = name.to_string();
+        }
     }
 
     /// Returns whether the client is using TLS/SSL encryption.
@@ -459,6 +489,12 @@ impl ClientStats {
         &self.ipaddr
     }
 
+    /// Returns the client tag, or an empty string when the client sent none.
+    #[inline(always)]
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
     //
     // Prepared statement cache metrics
     // ------------------------------------------------------------------------------------------
@@ -521,6 +557,48 @@ impl ClientStats {
         self.prepared_anonymous_evictions.load(Ordering::Relaxed)
     }
 
+    /// Records a Named statement the client cached.
+    pub fn add_prepared_name(&self, client_name: &str, server_name: &str) {
+        self.prepared_names
+            .lock()
+            .insert(client_name.to_string(), server_name.to_string());
+    }
+
+    /// Forgets a Named statement the client closed or deallocated.
+    pub fn remove_prepared_name(&self, client_name: &str) {
+        self.prepared_names.lock().remove(client_name);
+    }
+
+    /// Forgets every Named statement of the client.
+    pub fn clear_prepared_names(&self) {
+        self.prepared_names.lock().clear();
+    }
+
+    /// Named statements as `(client name, server name)`, sorted by client
+    /// name. Built on each call, for the admin console.
+    pub fn prepared_names(&self) -> Vec<(String, String)> {
+        let mut names: Vec<(String, String)> = self
+            .prepared_names
+            .lock()
+            .iter()
+            .map(|(client_name, server_name)| (client_name.clone(), server_name.clone()))
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Counts a Named Parse that rebound an existing name to a different query.
+    #[inline(always)]
+    pub fn prepared_remap(&self) {
+        self.prepared_remaps.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Named statement names this client rebound to a different query.
+    #[inline(always)]
+    pub fn prepared_remaps(&self) -> u64 {
+        self.prepared_remaps.load(Ordering::Relaxed)
+    }
+
     /// Marks this client as an async client (uses Flush instead of Sync).
     #[inline(always)]
     pub fn set_async_client(&self) {
@@ -595,6 +673,23 @@ mod tests {
         assert_eq!(stats.error_count.load(Ordering::Relaxed), 0);
     }
 
+    #[test]
+    fn test_client_stats_set_application_name() {
+        let stats = ClientStats::new(
+            7,
+            "worker",
+            "user",
+            "pool",
+            "127.0.0.1",
+            clock::now(),
+            false,
+        );
+        assert_eq!(stats.application_name(), "worker");
+
+        stats.set_application_name("worker:reindex");
+        assert_eq!(stats.application_name(), "worker:reindex");
+    }
+
     #[test]
     fn test_client_stats_new() {
         // Test that ClientStats::new initializes with the provided values
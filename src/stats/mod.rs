@@ -23,6 +23,8 @@ use std::sync::Arc;
 // -----------------------------------------------------------------------------
 /// Statistics for connections grouped by address
 pub mod address;
+/// Per-application breakdown of address statistics
+pub mod application;
 /// Statistics for auth_query cache and authentication
 pub mod auth_query;
 /// Statistics for client connections
@@ -33,11 +35,15 @@ mod connections;
 pub mod pool;
 /// Utilities for printing statistics (internal)
 pub mod print_all_stats;
+/// Sliding-window throughput rates per pool
+pub mod rates;
 /// Statistics for server connections
 pub mod server;
 /// Socket-related statistics (Linux only)
 #[cfg(target_os = "linux")]
 pub mod socket;
+/// Gauges of clients blocked before they reach a pool
+pub mod wait_events;
 
 // Public exports for commonly used types and functions
 // -----------------------------------------------------------------------------
@@ -153,12 +159,14 @@ impl Collector {
     /// 3. Prints all statistics for monitoring purposes
     ///
     /// The collection happens every STAT_PERIOD milliseconds (15 seconds by default).
+    /// It also starts the once-per-second sampler behind `SHOW RATES`.
     ///
     /// # Returns
     ///
     /// This method returns immediately after spawning the background task.
     pub async fn collect(&mut self) {
         info!("Stats reporter started");
+        rates::spawn_rates_sampler();
 
         tokio::task::spawn(async move {
             // Create a periodic interval for statistics collection
@@ -21,11 +21,19 @@ use std::sync::atomic::*;
 use std::sync::Arc;
 
 use crate::pool::get_all_pools;
-use crate::stats::client::{CLIENT_STATE_ACTIVE, CLIENT_STATE_IDLE, CLIENT_STATE_WAITING};
+use crate::stats::client::{
+    CLIENT_STATE_ACTIVE, CLIENT_STATE_IDLE, CLIENT_STATE_WAITING, CLIENT_WAIT_WRITE,
+};
 use crate::stats::server::{SERVER_STATE_ACTIVE, SERVER_STATE_IDLE, SERVER_STATE_LOGIN};
 use crate::stats::ClientStats;
 use crate::stats::ServerStats;
 
+/// Formats a microsecond duration as milliseconds with three decimals,
+/// so sub-millisecond transactions do not collapse to zero.
+fn micros_to_ms(us: u64) -> String {
+    format!("{:.3}", us as f64 / 1_000f64)
+}
+
 #[derive(Debug, Clone)]
 /// Comprehensive statistics for a PostgreSQL connection pool.
 ///
@@ -51,6 +59,10 @@ pub struct PoolStats {
     /// Number of client connections waiting for a server connection
     pub cl_waiting: u64,
 
+    /// Number of active clients pg_doorman is blocked writing a reply to.
+    /// Their backend reads are paused until the client drains its socket.
+    pub cl_backpressure: u64,
+
     /// Number of cancel requests from clients
     pub cl_cancel_req: u64,
 
@@ -74,6 +86,20 @@ pub struct PoolStats {
     /// indicate stuck checkouts.
     pub oldest_active_age_ms: u64,
 
+    /// Milliseconds the longest-waiting client of this pool has spent in
+    /// the WAITING state, taken at snapshot time. Zero when no client is
+    /// queued for a server.
+    pub oldest_wait_ms: u64,
+
+    /// Transactions of this pool holding a `max_concurrent_queries`
+    /// permit. The limit is shared by all users of the pool; zero when
+    /// the pool has no limit.
+    pub queries_active: u64,
+
+    /// Server connections this user holds against its
+    /// `max_concurrent_transactions` quota; zero without a quota.
+    pub quota_in_use: u64,
+
     //
     // Performance metrics
     // ------------------------------------------------------------------------------------------
@@ -151,6 +177,9 @@ pub struct PoolStats {
     /// Approximate memory usage of the pool-level prepared statement cache in bytes
     pub prepared_statements_bytes: u64,
 
+    /// Configured capacity of the pool-level prepared statement cache (0 when disabled)
+    pub prepared_statements_capacity: u64,
+
     /// Total number of entries in all clients' prepared statement caches
     pub client_prepared_count: u64,
 
@@ -186,6 +215,14 @@ pub struct PoolStats {
     /// Average query processing time (microseconds)
     pub avg_query_time_microseconds: u64,
 
+    /// Longest transaction in the current stats period (microseconds,
+    /// from HDR histogram)
+    pub max_xact_time_microseconds: u64,
+
+    /// Longest query in the current stats period (microseconds, from HDR
+    /// histogram)
+    pub max_query_time_microseconds: u64,
+
     /// Whether the pool is paused (PAUSE command)
     pub paused: bool,
 
@@ -255,12 +292,16 @@ impl PoolStats {
             cl_idle: 0,
             cl_active: 0,
             cl_waiting: 0,
+            cl_backpressure: 0,
             cl_cancel_req: 0,
             sv_active: 0,
             sv_idle: 0,
             sv_used: 0,
             sv_login: 0,
             oldest_active_age_ms: 0,
+            oldest_wait_ms: 0,
+            queries_active: 0,
+            quota_in_use: 0,
             maxwait: 0,
             avg_query_count: 0,
             avg_xact_count: 0,
@@ -284,6 +325,7 @@ impl PoolStats {
             total_query_time_microseconds: 0,
             prepared_statements_count: 0,
             prepared_statements_bytes: 0,
+            prepared_statements_capacity: 0,
             client_prepared_count: 0,
             client_prepared_bytes: 0,
             client_named_count: 0,
@@ -294,6 +336,8 @@ impl PoolStats {
             avg_sent: 0,
             avg_xact_time_microsecons: 0,
             avg_query_time_microseconds: 0,
+            max_xact_time_microseconds: 0,
+            max_query_time_microseconds: 0,
             paused: false,
             fallback_active: false,
             source_generation: 0,
@@ -371,6 +415,9 @@ impl PoolStats {
             ("paused", DataType::Text),
             ("fallback_active", DataType::Text),
             ("oldest_active_age_ms", DataType::Numeric),
+            ("oldest_wait_ms", DataType::Numeric),
+            ("queries_active", DataType::Numeric),
+            ("quota_in_use", DataType::Numeric),
         ]
     }
 
@@ -397,6 +444,10 @@ impl PoolStats {
             ("transaction_0.95", DataType::Numeric),
             ("query_0.5", DataType::Numeric),
             ("transaction_0.5", DataType::Numeric),
+            ("avg_xact_ms", DataType::Numeric),
+            ("max_xact_ms", DataType::Numeric),
+            ("avg_query_ms", DataType::Numeric),
+            ("max_query_ms", DataType::Numeric),
         ]
     }
 
@@ -422,6 +473,10 @@ impl PoolStats {
             Cow::Owned(self.xact_percentile.p95.to_string()),
             Cow::Owned(self.query_percentile.p50.to_string()),
             Cow::Owned(self.xact_percentile.p50.to_string()),
+            Cow::Owned(micros_to_ms(self.avg_xact_time_microsecons)),
+            Cow::Owned(micros_to_ms(self.max_xact_time_microseconds)),
+            Cow::Owned(micros_to_ms(self.avg_query_time_microseconds)),
+            Cow::Owned(micros_to_ms(self.max_query_time_microseconds)),
         ]
     }
 
@@ -445,6 +500,9 @@ impl PoolStats {
             Cow::Borrowed(if self.paused { "1" } else { "0" }),
             Cow::Borrowed(if self.fallback_active { "1" } else { "0" }),
             Cow::Owned(self.oldest_active_age_ms.to_string()),
+            Cow::Owned(self.oldest_wait_ms.to_string()),
+            Cow::Owned(self.queries_active.to_string()),
+            Cow::Owned(self.quota_in_use.to_string()),
         ]
     }
 
@@ -586,6 +644,13 @@ impl PoolStats {
                 .get()
                 > 0.5;
 
+            current.queries_active =
+                crate::pool::query_limit::active_queries(&pool.address().pool_name) as u64;
+            current.quota_in_use = pool
+                .transaction_quota
+                .as_ref()
+                .map_or(0, |quota| quota.active()) as u64;
+
             // Load average statistics
             current.avg_xact_count = address.averages.xact_count.load(Ordering::Relaxed);
             current.avg_query_count = address.averages.query_count.load(Ordering::Relaxed);
@@ -600,6 +665,8 @@ impl PoolStats {
                 .query_time_microseconds
                 .load(Ordering::Relaxed);
             current.avg_errors = address.averages.errors.load(Ordering::Relaxed);
+            current.max_xact_time_microseconds = address.get_xact_max();
+            current.max_query_time_microseconds = address.get_query_max();
 
             // Load total statistics
             current.total_errors = address.total.errors.load(Ordering::Relaxed);
@@ -616,6 +683,7 @@ impl PoolStats {
             if let Some(cache) = pool.prepared_statement_cache.as_ref() {
                 current.prepared_statements_count = cache.len() as u64;
                 current.prepared_statements_bytes = cache.memory_usage() as u64;
+                current.prepared_statements_capacity = cache.capacity() as u64;
             }
 
             // Load statistics for SHOW STATS command
@@ -667,9 +735,19 @@ impl PoolStats {
                 Some(pool_stats) => {
                     // Update client state counter based on client state
                     match client.state() {
-                        CLIENT_STATE_ACTIVE => pool_stats.cl_active += 1,
+                        CLIENT_STATE_ACTIVE => {
+                            pool_stats.cl_active += 1;
+                            if client.wait() == CLIENT_WAIT_WRITE {
+                                pool_stats.cl_backpressure += 1;
+                            }
+                        }
                         CLIENT_STATE_IDLE => pool_stats.cl_idle += 1,
-                        CLIENT_STATE_WAITING => pool_stats.cl_waiting += 1,
+                        CLIENT_STATE_WAITING => {
+                            pool_stats.cl_waiting += 1;
+                            if let Some(wait) = client.wait_ms() {
+                                pool_stats.oldest_wait_ms = pool_stats.oldest_wait_ms.max(wait);
+                            }
+                        }
                         _ => error!(
                             "[{}@{}] unknown client state: {}",
                             client.username(),
@@ -795,6 +873,7 @@ mod tests {
             assert_eq!(stats.cl_active, 0, "{id} cl_active should be 0");
             assert_eq!(stats.cl_idle, 0, "{id} cl_idle should be 0");
             assert_eq!(stats.cl_waiting, 0, "{id} cl_waiting should be 0");
+            assert_eq!(stats.cl_backpressure, 0, "{id} cl_backpressure should be 0");
             assert_eq!(stats.sv_active, 0, "{id} sv_active should be 0");
             assert_eq!(stats.sv_idle, 0, "{id} sv_idle should be 0");
         }
@@ -853,6 +932,42 @@ mod tests {
         assert_eq!(header.len(), row.len(), "header/row width mismatch");
     }
 
+    /// SHOW POOLS_EXTENDED reports transaction and query durations in
+    /// milliseconds; the underlying counters are microseconds.
+    #[test]
+    fn show_pools_extended_reports_durations_in_ms() {
+        let percentile = Percentile {
+            p99: 0,
+            p95: 0,
+            p90: 0,
+            p50: 0,
+        };
+        let mut stats = PoolStats::new_with_percentiles(
+            PoolIdentifier::new("shop", "alice"),
+            PoolMode::Transaction,
+            percentile.clone(),
+            percentile.clone(),
+            percentile,
+        );
+        stats.avg_xact_time_microsecons = 1_500;
+        stats.max_xact_time_microseconds = 2_345_678;
+        stats.avg_query_time_microseconds = 250;
+        stats.max_query_time_microseconds = 0;
+
+        let header = PoolStats::generate_show_pools_extended_header();
+        let row = stats.generate_show_pools_extended_row();
+        assert_eq!(header.len(), row.len(), "header/row width mismatch");
+
+        let column = |name: &str| {
+            let idx = header.iter().position(|(h, _)| *h == name).unwrap();
+            row[idx].as_ref().to_string()
+        };
+        assert_eq!(column("avg_xact_ms"), "1.500");
+        assert_eq!(column("max_xact_ms"), "2345.678");
+        assert_eq!(column("avg_query_ms"), "0.250");
+        assert_eq!(column("max_query_ms"), "0.000");
+    }
+
     /// Both entry points must agree on shape when fed the same global
     /// POOLS state and equivalent client/server maps. Validates that
     /// `construct_pool_lookup_from` is a structural extract of
@@ -0,0 +1,200 @@
+//! Per-pool throughput over short sliding windows, served by `SHOW RATES`.
+//!
+//! A background sampler copies the cumulative address counters of every
+//! pool into a small ring buffer once per second. Rates are the counter
+//! delta between the newest sample and the one a window ago, divided by
+//! the real time between them, so a late tick does not inflate the result.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+
+use crate::pool::{get_all_pools, PoolIdentifier};
+
+use super::AddressStats;
+
+/// Windows reported by `SHOW RATES`, in seconds.
+pub const RATE_WINDOWS_SECS: [u64; 3] = [1, 5, 60];
+
+/// How often the sampler snapshots the pool counters. The stats collector
+/// ticks every 15 s, too coarse for a 1 s window, so rates keep their own tick.
+const RATE_SAMPLE_PERIOD: Duration = Duration::from_secs(1);
+
+/// Samples kept per pool: enough to span the longest window plus one.
+const RATE_HISTORY_LEN: usize = 61;
+
+/// Cumulative counters copied from `AddressStats::total` at one instant.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RateCounters {
+    pub xact_count: u64,
+    pub query_count: u64,
+    pub bytes_received: u64,
+    pub bytes_sent: u64,
+}
+
+impl RateCounters {
+    fn from_address_stats(stats: &AddressStats) -> Self {
+        RateCounters {
+            xact_count: stats.total.xact_count.load(Ordering::Relaxed),
+            query_count: stats.total.query_count.load(Ordering::Relaxed),
+            bytes_received: stats.total.bytes_received.load(Ordering::Relaxed),
+            bytes_sent: stats.total.bytes_sent.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Per-second rates over one window.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Rates {
+    pub queries_per_sec: f64,
+    pub xacts_per_sec: f64,
+    pub bytes_received_per_sec: f64,
+    pub bytes_sent_per_sec: f64,
+}
+
+/// Ring buffer of recent counter samples for one pool.
+#[derive(Debug, Default)]
+pub struct RateHistory {
+    samples: VecDeque<(Instant, RateCounters)>,
+}
+
+impl RateHistory {
+    pub fn push(&mut self, at: Instant, counters: RateCounters) {
+        if self.samples.len() == RATE_HISTORY_LEN {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((at, counters));
+    }
+
+    /// Rates over the last `window`. While the history is still shorter
+    /// than the window the oldest sample is used instead; `None` until
+    /// there are two samples to compare.
+    pub fn rates(&self, window: Duration) -> Option<Rates> {
+        let &(newest_at, newest) = self.samples.back()?;
+        let &(base_at, base) = self
+            .samples
+            .iter()
+            .rev()
+            .skip(1)
+            .find(|(at, _)| newest_at.duration_since(*at) >= window)
+            .or_else(|| self.samples.front())?;
+        let elapsed = newest_at.duration_since(base_at).as_secs_f64();
+        if elapsed <= 0.0 {
+            return None;
+        }
+        // Pools recreated by RELOAD start their counters from zero again;
+        // report zero for that window rather than wrapping around.
+        let per_sec = |new: u64, old: u64| new.saturating_sub(old) as f64 / elapsed;
+        Some(Rates {
+            queries_per_sec: per_sec(newest.query_count, base.query_count),
+            xacts_per_sec: per_sec(newest.xact_count, base.xact_count),
+            bytes_received_per_sec: per_sec(newest.bytes_received, base.bytes_received),
+            bytes_sent_per_sec: per_sec(newest.bytes_sent, base.bytes_sent),
+        })
+    }
+}
+
+static RATE_HISTORY: Lazy<Mutex<HashMap<PoolIdentifier, RateHistory>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Take one sample of every pool and forget pools that no longer exist.
+fn sample_rates(now: Instant) {
+    let pools = get_all_pools();
+    let mut history = RATE_HISTORY.lock();
+    history.retain(|id, _| pools.contains_key(id));
+    for (id, pool) in pools.iter() {
+        let counters = RateCounters::from_address_stats(&pool.address().stats);
+        history.entry(id.clone()).or_default().push(now, counters);
+    }
+}
+
+/// Spawn the once-per-second sampler. Must be called once, after a tokio
+/// runtime is available.
+pub fn spawn_rates_sampler() {
+    tokio::task::spawn(async move {
+        let mut interval = tokio::time::interval(RATE_SAMPLE_PERIOD);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            sample_rates(Instant::now());
+        }
+    });
+}
+
+/// Rates for every sampled pool over each of `RATE_WINDOWS_SECS`,
+/// sorted by database and user.
+pub fn rates_snapshot() -> Vec<(PoolIdentifier, [Option<Rates>; 3])> {
+    let history = RATE_HISTORY.lock();
+    let mut rows: Vec<_> = history
+        .iter()
+        .map(|(id, h)| {
+            (
+                id.clone(),
+                RATE_WINDOWS_SECS.map(|secs| h.rates(Duration::from_secs(secs))),
+            )
+        })
+        .collect();
+    rows.sort_by(|a, b| (&a.0.db, &a.0.user).cmp(&(&b.0.db, &b.0.user)));
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counters(queries: u64, bytes: u64) -> RateCounters {
+        RateCounters {
+            xact_count: queries / 2,
+            query_count: queries,
+            bytes_received: bytes,
+            bytes_sent: bytes * 2,
+        }
+    }
+
+    #[test]
+    fn rates_need_two_samples() {
+        let mut h = RateHistory::default();
+        assert_eq!(h.rates(Duration::from_secs(1)), None);
+        h.push(Instant::now(), counters(10, 100));
+        assert_eq!(h.rates(Duration::from_secs(1)), None);
+    }
+
+    #[test]
+    fn rates_use_the_sample_one_window_back() {
+        let start = Instant::now();
+        let mut h = RateHistory::default();
+        for sec in 0..=10u64 {
+            // 100 queries/s for the first five seconds, 10 queries/s after.
+            let queries = if sec <= 5 {
+                sec * 100
+            } else {
+                500 + (sec - 5) * 10
+            };
+            h.push(start + Duration::from_secs(sec), counters(queries, queries));
+        }
+        let one = h.rates(Duration::from_secs(1)).unwrap();
+        assert_eq!(one.queries_per_sec, 10.0);
+        assert_eq!(one.xacts_per_sec, 5.0);
+        assert_eq!(one.bytes_sent_per_sec, 20.0);
+        let five = h.rates(Duration::from_secs(5)).unwrap();
+        assert_eq!(five.queries_per_sec, 10.0);
+        // Only 10 s of history: the 60 s window falls back to the oldest sample.
+        let sixty = h.rates(Duration::from_secs(60)).unwrap();
+        assert_eq!(sixty.queries_per_sec, 55.0);
+    }
+
+    #[test]
+    fn history_is_bounded_and_counter_resets_read_as_zero() {
+        let start = Instant::now();
+        let mut h = RateHistory::default();
+        for sec in 0..200u64 {
+            h.push(start + Duration::from_secs(sec), counters(sec, sec));
+        }
+        assert_eq!(h.samples.len(), RATE_HISTORY_LEN);
+        h.push(start + Duration::from_secs(200), RateCounters::default());
+        assert_eq!(h.rates(Duration::from_secs(1)).unwrap(), Rates::default());
+    }
+}
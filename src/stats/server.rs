@@ -91,6 +91,15 @@ pub struct ServerStats {
     /// Nanoseconds elapsed from `connect_time` at the moment this server
     /// last entered ACTIVE. `NEVER_ACTIVE` means not activated yet.
     active_since_nanos_from_connect: AtomicU64,
+
+    /// Text of the statement sent in the current round trip, for
+    /// `SHOW ACTIVE_QUERIES`. Only meaningful while
+    /// `query_since_nanos_from_connect` is set; clearing that atomic is
+    /// enough to retire it, so the hot path never takes this lock twice.
+    current_query: Mutex<Option<Arc<str>>>,
+    /// Nanoseconds from `connect_time` when `current_query` was sent.
+    /// `NEVER_ACTIVE` means no statement is running.
+    query_since_nanos_from_connect: AtomicU64,
 }
 
 /// Sentinel for `active_since_nanos_from_connect` meaning "not activated yet".
@@ -129,6 +138,8 @@ impl Default for ServerStats {
             prepared_cache_size: AtomicU64::new(0),
             use_tls: AtomicBool::new(false),
             active_since_nanos_from_connect: AtomicU64::new(NEVER_ACTIVE),
+            current_query: Mutex::new(None),
+            query_since_nanos_from_connect: AtomicU64::new(NEVER_ACTIVE),
         }
     }
 }
@@ -280,6 +291,32 @@ impl ServerStats {
         Some(now_nanos.saturating_sub(since_nanos) / 1_000_000)
     }
 
+    /// Records the statement this server is about to run.
+    pub fn set_current_query(&self, query: Arc<str>) {
+        *self.current_query.lock() = Some(query);
+        self.query_since_nanos_from_connect
+            .store(self.nanos_from_connect().max(1), Ordering::Relaxed);
+    }
+
+    /// The round trip finished; the server no longer runs a statement.
+    #[inline(always)]
+    pub fn clear_current_query(&self) {
+        self.query_since_nanos_from_connect
+            .store(NEVER_ACTIVE, Ordering::Relaxed);
+    }
+
+    /// The running statement and how long it has been running, in
+    /// milliseconds. `None` between round trips.
+    pub fn current_query(&self) -> Option<(Arc<str>, u64)> {
+        let since_nanos = self.query_since_nanos_from_connect.load(Ordering::Relaxed);
+        if since_nanos == NEVER_ACTIVE {
+            return None;
+        }
+        let query = self.current_query.lock().clone()?;
+        let age_ms = self.nanos_from_connect().saturating_sub(since_nanos) / 1_000_000;
+        Some((query, age_ms))
+    }
+
     #[inline]
     fn nanos_from_connect(&self) -> u64 {
         clock::now()
@@ -291,6 +328,7 @@ impl ServerStats {
     /// Server is idle and available for the next client.
     #[inline(always)]
     pub fn idle(&self, microseconds: u64) {
+        self.clear_current_query();
         self.address.stats.xact_time_add(microseconds);
         crate::web::metrics::observe_pool_transaction_microseconds(
             &self.address.username,
@@ -303,6 +341,7 @@ impl ServerStats {
     /// Records transaction time and sets the server state to IDLE.
     #[inline(always)]
     pub fn add_xact_time_and_idle(&self, microseconds: u64) {
+        self.clear_current_query();
         self.set_state(SERVER_STATE_IDLE);
         self.address.stats.xact_time_add(microseconds);
         crate::web::metrics::observe_pool_transaction_microseconds(
@@ -454,9 +493,13 @@ impl ServerStats {
     /// * `application_name` - Name of the application executing the query
     #[inline(always)]
     pub fn query(&self, microseconds: u64, application_name: &str) {
+        self.clear_current_query();
         self.set_application(application_name.to_string());
         self.address.stats.query_count_add();
         self.address.stats.query_time_add_microseconds(microseconds);
+        self.address
+            .stats
+            .application_query_add(application_name, microseconds);
         self.query_count.fetch_add(1, Ordering::Relaxed);
         crate::web::metrics::observe_pool_query_microseconds(
             &self.address.username,
@@ -479,6 +522,7 @@ impl ServerStats {
         self.set_application(application_name.to_string());
         self.transaction_count.fetch_add(1, Ordering::Relaxed);
         self.address.stats.xact_count_add();
+        self.address.stats.application_xact_add(application_name);
     }
 
     /// Records data sent to the server and updates related statistics.
@@ -588,6 +632,19 @@ mod tests {
     use super::*;
     use crate::stats::get_server_stats;
 
+    #[test]
+    fn current_query_is_cleared_when_the_round_trip_ends() {
+        let stats = ServerStats::default();
+        assert!(stats.current_query().is_none());
+
+        stats.set_current_query(Arc::from("select pg_sleep(1)"));
+        let (query, _age_ms) = stats.current_query().unwrap();
+        assert_eq!(&*query, "select pg_sleep(1)");
+
+        stats.clear_current_query();
+        assert!(stats.current_query().is_none());
+    }
+
     #[test]
     fn test_server_stats_default() {
         // Test that ServerStats::default initializes with expected default values
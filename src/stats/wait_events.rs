@@ -0,0 +1,49 @@
+/// Gauges of clients that are blocked before they have a `ClientStats`
+/// entry: queued for a `max_connections` slot, in the TLS handshake, or
+/// authenticating. `SHOW WAIT_EVENTS` reports them next to the per-client
+/// states.
+use std::sync::atomic::{AtomicI64, Ordering};
+
+/// Clients queued for a `max_connections` slot.
+pub static CLIENTS_IN_ADMISSION_QUEUE: AtomicI64 = AtomicI64::new(0);
+
+/// Clients in the TLS handshake.
+pub static CLIENTS_IN_TLS_HANDSHAKE: AtomicI64 = AtomicI64::new(0);
+
+/// Clients in the authentication exchange, including `auth_query` lookups.
+pub static CLIENTS_IN_AUTH: AtomicI64 = AtomicI64::new(0);
+
+/// Holds one of the gauges above raised until dropped, so every exit
+/// path of the wait lowers it again.
+pub struct WaitGuard(&'static AtomicI64);
+
+impl WaitGuard {
+    pub fn enter(gauge: &'static AtomicI64) -> Self {
+        gauge.fetch_add(1, Ordering::Relaxed);
+        Self(gauge)
+    }
+}
+
+impl Drop for WaitGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static GAUGE: AtomicI64 = AtomicI64::new(0);
+
+    #[test]
+    fn guard_lowers_gauge_on_drop() {
+        let first = WaitGuard::enter(&GAUGE);
+        let second = WaitGuard::enter(&GAUGE);
+        assert_eq!(GAUGE.load(Ordering::Relaxed), 2);
+        drop(first);
+        assert_eq!(GAUGE.load(Ordering::Relaxed), 1);
+        drop(second);
+        assert_eq!(GAUGE.load(Ordering::Relaxed), 0);
+    }
+}
@@ -630,6 +630,56 @@ pub fn extract_message_types(buffer: &[u8]) -> String {
     format_grouped_messages(&messages)
 }
 
+/// Number of message types kept by `RecentMessageTypes`.
+const RECENT_MESSAGE_TYPES: usize = 8;
+
+/// Ring of the last message type bytes read from a connection. Kept per
+/// client so a protocol desync can be logged with what led up to it.
+#[derive(Debug, Clone, Default)]
+pub struct RecentMessageTypes {
+    codes: [u8; RECENT_MESSAGE_TYPES],
+    /// Total number of codes pushed; the ring holds the last
+    /// `RECENT_MESSAGE_TYPES` of them.
+    pushed: usize,
+}
+
+impl RecentMessageTypes {
+    /// Records one message type byte, dropping the oldest when full.
+    #[inline]
+    pub fn push(&mut self, code: u8) {
+        self.codes[self.pushed % RECENT_MESSAGE_TYPES] = code;
+        self.pushed += 1;
+    }
+}
+
+impl std::fmt::Display for RecentMessageTypes {
+    /// Oldest first, grouped like `extract_message_types`: "[P,B,E,S,3xQ]".
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kept = self.pushed.min(RECENT_MESSAGE_TYPES);
+        let messages: Vec<(char, Option<String>)> = (self.pushed - kept..self.pushed)
+            .map(|i| (self.codes[i % RECENT_MESSAGE_TYPES] as char, None))
+            .collect();
+        f.write_str(&format_grouped_messages(&messages))
+    }
+}
+
+/// Hex dump of the first `max` bytes, e.g. "51 00 00 00 0d" or
+/// "00 01 02 ... (+120 bytes)" when truncated.
+pub fn hex_preview(bytes: &[u8], max: usize) -> String {
+    let shown = &bytes[..bytes.len().min(max)];
+    let mut result = String::with_capacity(shown.len() * 3 + 16);
+    for (i, b) in shown.iter().enumerate() {
+        if i > 0 {
+            result.push(' ');
+        }
+        result.push_str(&format!("{b:02x}"));
+    }
+    if bytes.len() > shown.len() {
+        result.push_str(&format!(" ... (+{} bytes)", bytes.len() - shown.len()));
+    }
+    result
+}
+
 /// Format messages with grouping for consecutive identical types
 fn format_grouped_messages(messages: &[(char, Option<String>)]) -> String {
     if messages.is_empty() {
@@ -867,4 +917,28 @@ mod tests {
         assert_eq!(buffer.groups.len(), 2);
         assert_eq!(buffer.total_count, 4);
     }
+
+    #[test]
+    fn test_recent_message_types_keeps_last_eight() {
+        let mut recent = RecentMessageTypes::default();
+        assert_eq!(recent.to_string(), "[]");
+
+        for code in b"PBES" {
+            recent.push(*code);
+        }
+        assert_eq!(recent.to_string(), "[P,B,E,S]");
+
+        for _ in 0..6 {
+            recent.push(b'Q');
+        }
+        // The first two (P, B) fell out of the ring.
+        assert_eq!(recent.to_string(), "[E,S,6xQ]");
+    }
+
+    #[test]
+    fn test_hex_preview_truncates() {
+        assert_eq!(hex_preview(&[0x51, 0, 0, 0, 0x0d], 16), "51 00 00 00 0d");
+        assert_eq!(hex_preview(&[0xff; 20], 4), "ff ff ff ff ... (+16 bytes)");
+        assert_eq!(hex_preview(&[], 4), "");
+    }
 }
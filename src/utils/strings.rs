@@ -63,6 +63,14 @@ pub fn preview_query(query: &str) -> String {
     truncate_chars(query, PREVIEW_QUERY_MAX_CHARS)
 }
 
+/// One-line preview for `SHOW ACTIVE_QUERIES`: every whitespace run
+/// (newlines, indentation) collapsed to a single space, then capped at
+/// `PREVIEW_QUERY_MAX_CHARS` so a row fits a terminal.
+pub fn preview_query_one_line(query: &str) -> String {
+    let collapsed = query.split_whitespace().collect::<Vec<_>>().join(" ");
+    truncate_chars(&collapsed, PREVIEW_QUERY_MAX_CHARS)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -111,6 +119,19 @@ mod tests {
         assert_eq!(out, q);
     }
 
+    #[test]
+    fn preview_query_one_line_collapses_whitespace() {
+        assert_eq!(
+            preview_query_one_line("  select *\n\tfrom t\r\n  where id = $1 "),
+            "select * from t where id = $1"
+        );
+        let q = format!("select {}", "x".repeat(PREVIEW_QUERY_MAX_CHARS));
+        assert_eq!(
+            preview_query_one_line(&q).chars().count(),
+            PREVIEW_QUERY_MAX_CHARS
+        );
+    }
+
     #[test]
     fn truncate_query_for_log_keeps_short_query_intact() {
         assert_eq!(truncate_query_for_log("select 1"), "select 1");
@@ -1,4 +1,4 @@
-//! Handler that builds the /metrics body and writes it onto a TcpStream.
+//! Handler that builds the /metrics body and writes it onto the connection.
 //! The accept loop and HTTP routing live in `crate::web::server`.
 
 use flate2::write::GzEncoder;
@@ -8,8 +8,7 @@ use prometheus::{Encoder, TextEncoder};
 use std::io::Write;
 use std::sync::atomic::{AtomicI64, Ordering};
 use std::time::{Duration, Instant};
-use tokio::io::{AsyncWriteExt, BufWriter};
-use tokio::net::tcp::OwnedWriteHalf;
+use tokio::io::{AsyncWrite, AsyncWriteExt, BufWriter};
 
 use super::metrics::update_metrics;
 use super::REGISTRY;
@@ -33,8 +32,8 @@ static SLOW_RESPONSE_LAST_WARN: AtomicI64 = AtomicI64::new(0);
 /// Builds the Prometheus metrics body and writes a complete HTTP/1.1 response
 /// onto the supplied writer. The mux must have already parsed the request
 /// (this function performs no reads on the socket).
-pub(crate) async fn write_metrics_response(
-    writer: &mut BufWriter<OwnedWriteHalf>,
+pub(crate) async fn write_metrics_response<W: AsyncWrite + Unpin>(
+    writer: &mut BufWriter<W>,
     accepts_gzip: bool,
 ) {
     let started = Instant::now();
@@ -3,9 +3,11 @@
 #[cfg(target_os = "linux")]
 use log::error;
 use once_cell::sync::Lazy;
+use prometheus::IntCounter;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
+use crate::messages::PEAK_MEMORY;
 use crate::pool::{PoolIdentifier, AUTH_QUERY_STATE, COORDINATORS, DYNAMIC_POOLS};
 #[cfg(target_os = "linux")]
 use crate::stats::cached_socket_states_count;
@@ -21,19 +23,24 @@ use super::SHOW_SOCKETS;
 use super::{
     AUTH_QUERY_AUTH, AUTH_QUERY_AUTH_TOTAL, AUTH_QUERY_CACHE, AUTH_QUERY_CACHE_TOTAL,
     AUTH_QUERY_DYNAMIC_POOLS, AUTH_QUERY_DYNAMIC_POOLS_TOTAL, AUTH_QUERY_EXECUTOR,
-    AUTH_QUERY_EXECUTOR_TOTAL, COORDINATOR, COORDINATOR_TOTALS, POOL_SCALING_GAUGE,
-    POOL_SCALING_TOTALS, SHOW_ASYNC_CLIENTS_COUNT, SHOW_CLIENT_CACHE_BYTES,
-    SHOW_CLIENT_CACHE_ENTRIES, SHOW_CLIENT_PREPARED_ANONYMOUS_ENTRIES,
-    SHOW_CLIENT_PREPARED_ANONYMOUS_EVICTIONS_TOTAL, SHOW_CLIENT_PREPARED_NAMED_ENTRIES,
-    SHOW_CONNECTIONS, SHOW_CONNECTIONS_TOTAL, SHOW_POOLS_BYTES, SHOW_POOLS_BYTES_TOTAL,
-    SHOW_POOLS_CLIENT, SHOW_POOLS_ERRORS_TOTAL, SHOW_POOLS_MAXWAIT_MICROSECONDS,
-    SHOW_POOLS_OLDEST_ACTIVE_AGE_MS, SHOW_POOLS_PAUSED, SHOW_POOLS_QUERIES_COUNTER,
-    SHOW_POOLS_QUERIES_PERCENTILE, SHOW_POOLS_QUERIES_TOTAL, SHOW_POOLS_QUERIES_TOTAL_TIME,
-    SHOW_POOLS_SERVER, SHOW_POOLS_TRANSACTIONS_COUNTER, SHOW_POOLS_TRANSACTIONS_PERCENTILE,
-    SHOW_POOLS_TRANSACTIONS_TOTAL, SHOW_POOLS_TRANSACTIONS_TOTAL_TIME, SHOW_POOLS_WAIT_TIME_AVG,
-    SHOW_POOL_CACHE_BYTES, SHOW_POOL_CACHE_ENTRIES, SHOW_POOL_SIZE, SHOW_SERVERS_PREPARED_HITS,
-    SHOW_SERVERS_PREPARED_HITS_TOTAL, SHOW_SERVERS_PREPARED_MISSES,
-    SHOW_SERVERS_PREPARED_MISSES_TOTAL, SHOW_SERVER_TLS_CONNECTIONS, TOTAL_MEMORY,
+    AUTH_QUERY_EXECUTOR_TOTAL, BUFFER_POOL_BUFFERS_TOTAL, COORDINATOR, COORDINATOR_TOTALS,
+    MEMORY_LIMIT_HITS_TOTAL, MESSAGE_MEMORY_PEAK, POOL_CLIENTS_BACKPRESSURE, POOL_CURRENT_SIZE,
+    POOL_OLDEST_WAIT_SECONDS, POOL_SCALING_GAUGE, POOL_SCALING_TOTALS, POOL_WAITING_CLIENTS,
+    SHOW_ASYNC_CLIENTS_COUNT, SHOW_CLIENT_CACHE_BYTES, SHOW_CLIENT_CACHE_ENTRIES,
+    SHOW_CLIENT_PREPARED_ANONYMOUS_ENTRIES, SHOW_CLIENT_PREPARED_ANONYMOUS_EVICTIONS_TOTAL,
+    SHOW_CLIENT_PREPARED_NAMED_ENTRIES, SHOW_CLIENT_PREPARED_REMAPS_TOTAL, SHOW_CONNECTIONS,
+    SHOW_CONNECTIONS_TOTAL, SHOW_POOLS_APPLICATION_QUERIES_TOTAL,
+    SHOW_POOLS_APPLICATION_QUERY_TIME_TOTAL, SHOW_POOLS_APPLICATION_TRANSACTIONS_TOTAL,
+    SHOW_POOLS_BYTES, SHOW_POOLS_BYTES_TOTAL, SHOW_POOLS_CLIENT, SHOW_POOLS_ERRORS_TOTAL,
+    SHOW_POOLS_MAXWAIT_MICROSECONDS, SHOW_POOLS_OLDEST_ACTIVE_AGE_MS, SHOW_POOLS_PAUSED,
+    SHOW_POOLS_QUERIES_COUNTER, SHOW_POOLS_QUERIES_PERCENTILE, SHOW_POOLS_QUERIES_TOTAL,
+    SHOW_POOLS_QUERIES_TOTAL_TIME, SHOW_POOLS_SERVER, SHOW_POOLS_TRANSACTIONS_COUNTER,
+    SHOW_POOLS_TRANSACTIONS_PERCENTILE, SHOW_POOLS_TRANSACTIONS_TOTAL,
+    SHOW_POOLS_TRANSACTIONS_TOTAL_TIME, SHOW_POOLS_WAIT_TIME_AVG, SHOW_POOL_CACHE_BYTES,
+    SHOW_POOL_CACHE_ENTRIES, SHOW_POOL_CACHE_EVICTIONS_TOTAL, SHOW_POOL_CACHE_FILL_RATIO,
+    SHOW_POOL_SIZE, SHOW_SERVERS_PREPARED_HITS, SHOW_SERVERS_PREPARED_HITS_TOTAL,
+    SHOW_SERVERS_PREPARED_MISSES, SHOW_SERVERS_PREPARED_MISSES_TOTAL, SHOW_SERVER_TLS_CONNECTIONS,
+    TOTAL_MEMORY,
 };
 
 /// Updates all metrics before they are exposed via the Prometheus endpoint.
@@ -46,6 +53,7 @@ pub fn update_metrics() {
 
     update_pool_metrics();
     update_pool_errors_metrics();
+    update_pool_application_metrics();
     update_server_metrics();
     update_auth_query_metrics();
     update_coordinator_metrics();
@@ -54,6 +62,21 @@ pub fn update_metrics() {
 
 fn update_memory_metrics() {
     TOTAL_MEMORY.set(get_process_memory_usage() as f64);
+    MESSAGE_MEMORY_PEAK.set(PEAK_MEMORY.load(Ordering::Relaxed) as f64);
+    // Export the counter at 0 before the first rejection.
+    Lazy::force(&MEMORY_LIMIT_HITS_TOTAL);
+
+    let buffers = crate::client::buffer_pool::buffer_pool_stats();
+    for (event, value) in [
+        ("allocated", buffers.misses),
+        ("reused", buffers.hits),
+        ("dropped_oversized", buffers.dropped_oversized),
+    ] {
+        emit_process_counter_delta(
+            &BUFFER_POOL_BUFFERS_TOTAL.with_label_values(&[event]),
+            value,
+        );
+    }
 }
 
 fn update_connection_metrics() {
@@ -284,6 +307,12 @@ fn update_pool_state_metrics(identifier: &PoolIdentifier, stats: &PoolStats) {
     SHOW_POOLS_MAXWAIT_MICROSECONDS
         .with_label_values(&[user, database])
         .set(stats.maxwait as f64);
+    POOL_WAITING_CLIENTS
+        .with_label_values(&[user, database])
+        .set(stats.cl_waiting as f64);
+    POOL_OLDEST_WAIT_SECONDS
+        .with_label_values(&[user, database])
+        .set(stats.oldest_wait_ms as f64 / 1_000f64);
 }
 
 fn update_pool_cache_metrics(identifier: &PoolIdentifier, stats: &PoolStats) {
@@ -297,6 +326,13 @@ fn update_pool_cache_metrics(identifier: &PoolIdentifier, stats: &PoolStats) {
     SHOW_POOL_CACHE_BYTES
         .with_label_values(&[user, database])
         .set(stats.prepared_statements_bytes as f64);
+    if stats.prepared_statements_capacity > 0 {
+        SHOW_POOL_CACHE_FILL_RATIO
+            .with_label_values(&[user, database])
+            .set(
+                stats.prepared_statements_count as f64 / stats.prepared_statements_capacity as f64,
+            );
+    }
 
     // Client-level prepared statement cache metrics (aggregated)
     SHOW_CLIENT_CACHE_ENTRIES
@@ -336,6 +372,63 @@ pub fn observe_anonymous_eviction(user: &str, database: &str) {
         .inc();
 }
 
+/// Counts a Named Parse that rebound a client statement name to a different
+/// query. Monotonic like `observe_anonymous_eviction`.
+#[inline]
+pub fn observe_prepared_remap(user: &str, database: &str) {
+    SHOW_CLIENT_PREPARED_REMAPS_TOTAL
+        .with_label_values(&[user, database])
+        .inc();
+}
+
+/// Per-pool eviction counter handed to the pool-level prepared statement
+/// cache when the pool is built, so evictions are counted without a label
+/// lookup on the hot path.
+pub fn pool_prepared_cache_eviction_counter(user: &str, database: &str) -> IntCounter {
+    SHOW_POOL_CACHE_EVICTIONS_TOTAL.with_label_values(&[user, database])
+}
+
+/// Counts a pool entering (`pause`) or leaving (`resume`) auto-pause.
+pub fn record_auto_pause_transition(user: &str, database: &str, transition: &str) {
+    super::POOLS_AUTO_PAUSE_TRANSITIONS_TOTAL
+        .with_label_values(&[user, database, transition])
+        .inc();
+}
+
+/// Counts a server connection closed because it outlived `server_max_age`.
+pub fn record_server_max_age_recycle(user: &str, database: &str) {
+    super::SERVERS_MAX_AGE_RECYCLES_TOTAL
+        .with_label_values(&[user, database])
+        .inc();
+}
+
+/// Counts one checkout of a `prefer_last_server` pool as a hit (the client
+/// got its previous server back) or a miss.
+pub fn record_affinity_checkout(user: &str, database: &str, hit: bool) {
+    super::POOLS_AFFINITY_CHECKOUTS_TOTAL
+        .with_label_values(&[user, database, if hit { "hit" } else { "miss" }])
+        .inc();
+}
+
+/// Counts a statement that uses a session-only feature on a
+/// transaction-mode connection.
+pub fn record_session_feature_in_txn(user: &str, database: &str, feature: &str) {
+    super::SESSION_FEATURE_IN_TXN_TOTAL
+        .with_label_values(&[user, database, feature])
+        .inc();
+}
+
+/// Records a closed server connection: its lifetime goes into the histogram
+/// and the per-pool close counter is bumped, both under `reason`.
+pub fn record_server_close(user: &str, database: &str, reason: &str, lifetime_seconds: f64) {
+    super::SERVER_CONNECTION_LIFETIME_SECONDS
+        .with_label_values(&[reason])
+        .observe(lifetime_seconds);
+    super::SERVER_RECYCLES_TOTAL
+        .with_label_values(&[user, database, reason])
+        .inc();
+}
+
 fn update_server_metrics() {
     SHOW_SERVERS_PREPARED_HITS.reset();
     SHOW_SERVERS_PREPARED_MISSES.reset();
@@ -497,14 +590,21 @@ fn reset_pool_metrics() {
     SHOW_POOLS_QUERIES_COUNTER.reset();
     SHOW_POOLS_QUERIES_TOTAL_TIME.reset();
     SHOW_POOL_SIZE.reset();
+    POOL_CURRENT_SIZE.reset();
     SHOW_POOLS_PAUSED.reset();
     SHOW_POOLS_MAXWAIT_MICROSECONDS.reset();
+    POOL_WAITING_CLIENTS.reset();
+    POOL_OLDEST_WAIT_SECONDS.reset();
+    POOL_CLIENTS_BACKPRESSURE.reset();
 }
 
 fn update_pool_size_metrics(identifier: &PoolIdentifier, stats: &PoolStats) {
     SHOW_POOL_SIZE
         .with_label_values(&[identifier.user.as_str(), identifier.db.as_str()])
         .set(stats.pool_size as f64);
+    POOL_CURRENT_SIZE
+        .with_label_values(&[identifier.user.as_str(), identifier.db.as_str()])
+        .set((stats.sv_active + stats.sv_idle + stats.sv_used + stats.sv_login) as f64);
 }
 
 fn update_client_state_metrics(identifier: &PoolIdentifier, stats: &PoolStats) {
@@ -520,6 +620,9 @@ fn update_client_state_metrics(identifier: &PoolIdentifier, stats: &PoolStats) {
             .with_label_values(&labels)
             .set(count as f64);
     }
+    POOL_CLIENTS_BACKPRESSURE
+        .with_label_values(&[identifier.user.as_str(), identifier.db.as_str()])
+        .set(stats.cl_backpressure as f64);
 }
 
 fn update_byte_metrics(identifier: &PoolIdentifier, stats: &PoolStats) {
@@ -930,6 +1033,78 @@ type PoolErrorsKey = (String, String, &'static str);
 static POOL_ERRORS_PREV: Lazy<CounterDeltaTracker<PoolErrorsKey>> =
     Lazy::new(CounterDeltaTracker::new);
 
+/// (user, database, application bucket) key for the per-application
+/// counters. The bucket is bounded by `general.stats_application_names`.
+type PoolApplicationKey = (String, String, String);
+
+static POOL_APPLICATION_QUERIES_PREV: Lazy<CounterDeltaTracker<PoolApplicationKey>> =
+    Lazy::new(CounterDeltaTracker::new);
+static POOL_APPLICATION_TRANSACTIONS_PREV: Lazy<CounterDeltaTracker<PoolApplicationKey>> =
+    Lazy::new(CounterDeltaTracker::new);
+static POOL_APPLICATION_QUERY_TIME_PREV: Lazy<CounterDeltaTracker<PoolApplicationKey>> =
+    Lazy::new(CounterDeltaTracker::new);
+
+fn update_pool_application_metrics() {
+    use crate::pool::get_all_pools;
+
+    let mut current_keys: std::collections::HashSet<PoolApplicationKey> =
+        std::collections::HashSet::new();
+    for (identifier, pool) in get_all_pools().iter() {
+        let address_stats = &pool.address().stats;
+        for app in address_stats.by_application_snapshot() {
+            let labels = [
+                identifier.user.as_str(),
+                identifier.db.as_str(),
+                app.application_name.as_str(),
+            ];
+            let key = (
+                identifier.user.clone(),
+                identifier.db.clone(),
+                app.application_name.clone(),
+            );
+            POOL_APPLICATION_QUERIES_PREV.observe(
+                &SHOW_POOLS_APPLICATION_QUERIES_TOTAL.with_label_values(&labels),
+                key.clone(),
+                address_stats.generation,
+                app.query_count,
+            );
+            POOL_APPLICATION_TRANSACTIONS_PREV.observe(
+                &SHOW_POOLS_APPLICATION_TRANSACTIONS_TOTAL.with_label_values(&labels),
+                key.clone(),
+                address_stats.generation,
+                app.xact_count,
+            );
+            POOL_APPLICATION_QUERY_TIME_PREV.observe(
+                &SHOW_POOLS_APPLICATION_QUERY_TIME_TOTAL.with_label_values(&labels),
+                key.clone(),
+                address_stats.generation,
+                app.query_time_microseconds,
+            );
+            current_keys.insert(key);
+        }
+    }
+
+    // Pools removed by RELOAD take their application series with them.
+    for (tracker, counter) in [
+        (
+            &POOL_APPLICATION_QUERIES_PREV,
+            &SHOW_POOLS_APPLICATION_QUERIES_TOTAL,
+        ),
+        (
+            &POOL_APPLICATION_TRANSACTIONS_PREV,
+            &SHOW_POOLS_APPLICATION_TRANSACTIONS_TOTAL,
+        ),
+        (
+            &POOL_APPLICATION_QUERY_TIME_PREV,
+            &SHOW_POOLS_APPLICATION_QUERY_TIME_TOTAL,
+        ),
+    ] {
+        for stale in tracker.drain_stale(&current_keys) {
+            let _ = counter.remove_label_values(&[&stale.0, &stale.1, &stale.2]);
+        }
+    }
+}
+
 fn update_pool_errors_metrics() {
     use crate::pool::get_all_pools;
 
@@ -1034,6 +1209,13 @@ pub fn record_synthetic_miss() {
     super::QUERY_INTERNER_SYNTHETIC_MISSES_TOTAL.inc();
 }
 
+/// Increments the memory-limit counter — called when a client message is
+/// rejected because it would push in-flight buffers past
+/// `general.max_memory_usage`.
+pub fn record_memory_limit_hit() {
+    super::MEMORY_LIMIT_HITS_TOTAL.inc();
+}
+
 /// Records one large-message streaming event. Called from the backend
 /// streaming handlers after the outcome is known. `kind` is "data_row",
 /// "copy_data", or "function_call_response"; `result` is "ok" or "error".
@@ -1065,6 +1247,34 @@ pub fn record_listener_rejection(reason: &'static str) {
         .inc();
 }
 
+/// Records one connection closed on a protocol desync. `phase` must be one
+/// of the labels documented on `PROTOCOL_ERRORS_TOTAL`.
+#[inline]
+pub fn record_protocol_error(phase: &'static str) {
+    super::PROTOCOL_ERRORS_TOTAL
+        .with_label_values(&[phase])
+        .inc();
+}
+
+/// Records one failed client TLS handshake. `cause` must be one of the
+/// labels documented on `TLS_HANDSHAKE_FAILURES_TOTAL`.
+#[inline]
+pub fn record_tls_handshake_failure(cause: &'static str) {
+    super::TLS_HANDSHAKE_FAILURES_TOTAL
+        .with_label_values(&[cause])
+        .inc();
+}
+
+/// Records one rejected client login. `reason` must be one of the labels
+/// documented on `AUTH_FAILURES_TOTAL`; `subnet` is empty unless
+/// `general.auth_failures_by_subnet` is on.
+#[inline]
+pub fn record_auth_failure(reason: &'static str, subnet: &str) {
+    super::AUTH_FAILURES_TOTAL
+        .with_label_values(&[reason, subnet])
+        .inc();
+}
+
 /// Observes wall-clock duration of one backend connection setup phase.
 /// `phase` must be one of `tcp_connect`, `tls`, `auth`, `startup` —
 /// passing any other value still works but breaks the cardinality
@@ -1076,6 +1286,16 @@ pub fn observe_backend_create_phase(phase: &'static str, seconds: f64) {
         .observe(seconds);
 }
 
+/// Observes wall-clock duration of one client phase. `phase` must be one
+/// of `tls_handshake`, `auth`, `checkout`, `active`; see
+/// `CLIENT_PHASE_DURATION_SECONDS`.
+#[inline]
+pub fn observe_client_phase(phase: &'static str, seconds: f64) {
+    super::CLIENT_PHASE_DURATION_SECONDS
+        .with_label_values(&[phase])
+        .observe(seconds);
+}
+
 /// Observes one query duration in the per-pool query histogram. Caller
 /// passes microseconds because every existing call site already has
 /// that unit; the conversion to seconds happens once here, behind the
@@ -1114,7 +1334,7 @@ pub fn observe_pool_wait_microseconds(user: &str, database: &str, microseconds:
 }
 
 /// Refreshes the trio of static info gauges: `build_info` (constant
-/// version label), `users_configured` (one series per (user, database,
+/// version and build-stamp labels), `users_configured` (one series per (user, database,
 /// pool_mode) triple from the active config), and `log_level` (current
 /// effective filter from `app::log_level::get_log_level`). Called on
 /// startup and on every config reload — `BUILD_INFO` is idempotent,
@@ -1122,7 +1342,13 @@ pub fn observe_pool_wait_microseconds(user: &str, database: &str, microseconds:
 /// rolled-back log overrides drop their series straight away.
 pub fn refresh_static_info_metrics() {
     super::BUILD_INFO
-        .with_label_values(&[env!("CARGO_PKG_VERSION")])
+        .with_label_values(&[
+            crate::config::VERSION,
+            crate::config::GIT_COMMIT,
+            crate::config::BUILD_DATE,
+            crate::config::RUSTC_VERSION,
+            crate::config::FEATURES,
+        ])
         .set(1);
 
     super::USERS_CONFIGURED.reset();
@@ -1244,6 +1470,14 @@ mod tests {
         assert_eq!(child.get_sample_count(), before + 1);
     }
 
+    #[test]
+    fn client_phase_observe_records_its_phase() {
+        let child = super::super::CLIENT_PHASE_DURATION_SECONDS.with_label_values(&["auth"]);
+        let before = child.get_sample_count();
+        super::observe_client_phase("auth", 0.002);
+        assert_eq!(child.get_sample_count(), before + 1);
+    }
+
     #[test]
     fn counter_delta_tracker_emits_post_reset_delta_only_once() {
         // A pool whose `AddressStats` was replaced by `Pool::from_config`
@@ -21,17 +21,21 @@ mod tests;
 // Re-exports
 pub(crate) use handler::write_metrics_response;
 pub use metrics::{
-    observe_anonymous_eviction, observe_backend_create_phase, observe_pool_query_microseconds,
-    observe_pool_transaction_microseconds, observe_pool_wait_microseconds, observe_streaming_bytes,
-    observe_streaming_event, record_interner_gc, record_listener_rejection, record_synthetic_miss,
-    refresh_static_info_metrics,
+    observe_anonymous_eviction, observe_backend_create_phase, observe_client_phase,
+    observe_pool_query_microseconds, observe_pool_transaction_microseconds,
+    observe_pool_wait_microseconds, observe_streaming_bytes, observe_streaming_event,
+    pool_prepared_cache_eviction_counter, record_affinity_checkout, record_auth_failure,
+    record_auto_pause_transition, record_interner_gc, record_listener_rejection,
+    record_memory_limit_hit, record_protocol_error, record_server_close,
+    record_server_max_age_recycle, record_session_feature_in_txn, record_synthetic_miss,
+    record_tls_handshake_failure, refresh_static_info_metrics,
 };
 
 // Define the metrics we want to expose
 pub(crate) static REGISTRY: Lazy<Registry> = Lazy::new(Registry::new);
 
 /// `build_info`-style gauge that always reports `1` and carries the
-/// pg_doorman version in a label. Pinned to one series per process so
+/// pg_doorman version and build stamp in labels. Pinned to one series per process so
 /// dashboards can join on `version` without affecting cardinality, and
 /// alerts can fire on a missing series after a deploy. Refreshed on
 /// startup and on every config reload (the value never changes mid-run,
@@ -41,9 +45,9 @@ pub(crate) static BUILD_INFO: Lazy<IntGaugeVec> = Lazy::new(|| {
     let gauge = IntGaugeVec::new(
         Opts::new(
             "pg_doorman_build_info",
-            "Static information about the running pg_doorman binary, exposed as a gauge fixed at 1. The version label carries the crate version (Cargo.toml) so dashboards can show 'which build is in production' without parsing logs.",
+            "Static information about the running pg_doorman binary, exposed as a gauge fixed at 1. The version label carries the crate version (Cargo.toml); git_commit, build_date, rustc_version and features identify the exact build, so dashboards can show 'which build is in production' without parsing logs.",
         ),
-        &["version"],
+        &["version", "git_commit", "build_date", "rustc_version", "features"],
     )
     .unwrap();
     REGISTRY.register(Box::new(gauge.clone())).unwrap();
@@ -95,6 +99,48 @@ pub(crate) static TOTAL_MEMORY: Lazy<Gauge> = Lazy::new(|| {
     gauge
 });
 
+/// High-water mark of the in-flight message buffer counter that
+/// `general.max_memory_usage` is enforced against. Compare with the limit to
+/// size it.
+pub(crate) static MESSAGE_MEMORY_PEAK: Lazy<Gauge> = Lazy::new(|| {
+    let gauge = Gauge::new(
+        "pg_doorman_message_memory_peak_bytes",
+        "Peak bytes held in in-flight client message buffers since process start. This is the value general.max_memory_usage is checked against.",
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(gauge.clone())).unwrap();
+    gauge
+});
+
+/// Counter for client messages rejected because accepting them would push
+/// in-flight buffer memory past `general.max_memory_usage`. The client gets
+/// SQLSTATE 53400.
+pub(crate) static MEMORY_LIMIT_HITS_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
+    let counter = IntCounter::new(
+        "pg_doorman_memory_limit_hits_total",
+        "Client messages rejected with SQLSTATE 53400 because in-flight buffer memory would exceed general.max_memory_usage.",
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(counter.clone())).unwrap();
+    counter
+});
+
+/// Client buffer pool activity: `allocated` (no free buffer to reuse),
+/// `reused`, and `dropped_oversized` (grew past
+/// `general.buffer_pool_max_buffer_size`).
+pub(crate) static BUFFER_POOL_BUFFERS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new(
+            "pg_doorman_buffer_pool_buffers_total",
+            "Client buffer pool activity by event: 'allocated' (new buffer, nothing to reuse), 'reused' (taken from a free list), 'dropped_oversized' (dropped on release for exceeding general.buffer_pool_max_buffer_size).",
+        ),
+        &["event"],
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(counter.clone())).unwrap();
+    counter
+});
+
 /// DEPRECATED: monotonic value exposed as a Gauge — `rate()` works in
 /// practice but Prometheus reset detection breaks on restart because the
 /// gauge does not declare itself as monotonic. Prefer
@@ -170,6 +216,20 @@ pub(crate) static SHOW_POOL_SIZE: Lazy<GaugeVec> = Lazy::new(|| {
     gauge
 });
 
+/// Current server connections per pool, next to `pg_doorman_pool_size`.
+pub(crate) static POOL_CURRENT_SIZE: Lazy<GaugeVec> = Lazy::new(|| {
+    let gauge = GaugeVec::new(
+        Opts::new(
+            "pg_doorman_pool_current_size",
+            "Current number of server connections per user and database, in any state. Moves between min_pool_size and pg_doorman_pool_size as the pool grows on demand and shrinks after idle_timeout.",
+        ),
+        &["user", "database"],
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(gauge.clone())).unwrap();
+    gauge
+});
+
 pub(crate) static SHOW_POOLS_SERVER: Lazy<GaugeVec> = Lazy::new(|| {
     let gauge = GaugeVec::new(
         Opts::new(
@@ -196,6 +256,46 @@ pub(crate) static SHOW_POOLS_OLDEST_ACTIVE_AGE_MS: Lazy<GaugeVec> = Lazy::new(||
     gauge
 });
 
+pub(crate) static POOL_WAITING_CLIENTS: Lazy<GaugeVec> = Lazy::new(|| {
+    let gauge = GaugeVec::new(
+        Opts::new(
+            "pg_doorman_pool_waiting_clients",
+            "Number of clients currently queued for a server connection in each pool. A queue that is non-empty on every scrape is the earliest sign that pool_size is too small, before clients start failing with query_wait_timeout.",
+        ),
+        &["user", "database"],
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(gauge.clone())).unwrap();
+    gauge
+});
+
+/// Clients whose reply write is blocked on a full socket, per pool.
+pub(crate) static POOL_CLIENTS_BACKPRESSURE: Lazy<GaugeVec> = Lazy::new(|| {
+    let gauge = GaugeVec::new(
+        Opts::new(
+            "pg_doorman_pools_clients_backpressure",
+            "Active clients pg_doorman is blocked writing a reply to, taken at snapshot time. The backend read for each of them is paused until the client drains its socket, so a sustained non-zero value points at slow consumers holding server connections.",
+        ),
+        &["user", "database"],
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(gauge.clone())).unwrap();
+    gauge
+});
+
+pub(crate) static POOL_OLDEST_WAIT_SECONDS: Lazy<GaugeVec> = Lazy::new(|| {
+    let gauge = GaugeVec::new(
+        Opts::new(
+            "pg_doorman_pool_oldest_wait_seconds",
+            "How long the longest-waiting client of each pool has been blocked on checkout, in seconds, taken at snapshot time. Zero when nobody is queued. Compare against query_wait_timeout to see how close the pool is to rejecting clients.",
+        ),
+        &["user", "database"],
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(gauge.clone())).unwrap();
+    gauge
+});
+
 pub(crate) static SHOW_POOLS_PAUSED: Lazy<IntGaugeVec> = Lazy::new(|| {
     let gauge = IntGaugeVec::new(
         Opts::new(
@@ -249,6 +349,60 @@ pub(crate) static SHOW_POOLS_ERRORS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
     counter
 });
 
+/// Per-application query counter. The `application_name` label is one of
+/// `general.stats_application_names` or `other`, so cardinality is bounded
+/// by that allowlist; no series exist while it is empty.
+pub(crate) static SHOW_POOLS_APPLICATION_QUERIES_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new(
+            "pg_doorman_pools_application_queries_total",
+            "Cumulative query count per pool and client application_name. \
+             Names outside general.stats_application_names are reported as \
+             'other'.",
+        ),
+        &["user", "database", "application_name"],
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(counter.clone())).unwrap();
+    counter
+});
+
+/// Per-application transaction counter; same labels as
+/// `pg_doorman_pools_application_queries_total`.
+pub(crate) static SHOW_POOLS_APPLICATION_TRANSACTIONS_TOTAL: Lazy<IntCounterVec> =
+    Lazy::new(|| {
+        let counter = IntCounterVec::new(
+            Opts::new(
+                "pg_doorman_pools_application_transactions_total",
+                "Cumulative transaction count per pool and client \
+                 application_name. Names outside \
+                 general.stats_application_names are reported as 'other'.",
+            ),
+            &["user", "database", "application_name"],
+        )
+        .unwrap();
+        REGISTRY.register(Box::new(counter.clone())).unwrap();
+        counter
+    });
+
+/// Per-application query time; same labels as
+/// `pg_doorman_pools_application_queries_total`.
+pub(crate) static SHOW_POOLS_APPLICATION_QUERY_TIME_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new(
+            "pg_doorman_pools_application_query_time_microseconds_total",
+            "Cumulative query time in microseconds per pool and client \
+                 application_name. Divide its rate by the rate of \
+                 pg_doorman_pools_application_queries_total for the mean \
+                 query time of one application.",
+        ),
+        &["user", "database", "application_name"],
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(counter.clone())).unwrap();
+    counter
+});
+
 /// DEPRECATED: monotonic byte counter shipped as a Gauge. Prefer
 /// `pg_doorman_pools_bytes_total`. Scheduled for removal in 3.10.
 pub(crate) static SHOW_POOLS_BYTES: Lazy<GaugeVec> = Lazy::new(|| {
@@ -307,6 +461,32 @@ pub(crate) static SHOW_POOL_CACHE_BYTES: Lazy<GaugeVec> = Lazy::new(|| {
     gauge
 });
 
+pub(crate) static SHOW_POOL_CACHE_FILL_RATIO: Lazy<GaugeVec> = Lazy::new(|| {
+    let gauge = GaugeVec::new(
+        Opts::new(
+            "pg_doorman_pool_prepared_cache_fill_ratio",
+            "Entries in the pool-level prepared statement cache divided by prepared_statements_cache_size, by user and database. Stays at 1 once the cache has filled up."
+        ),
+        &["user", "database"],
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(gauge.clone())).unwrap();
+    gauge
+});
+
+pub(crate) static SHOW_POOL_CACHE_EVICTIONS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new(
+            "pg_doorman_prepared_cache_evictions_total",
+            "Cumulative count of entries evicted from the pool-level prepared statement cache because it reached prepared_statements_cache_size, by user and database.",
+        ),
+        &["user", "database"],
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(counter.clone())).unwrap();
+    counter
+});
+
 pub(crate) static SHOW_CLIENT_CACHE_ENTRIES: Lazy<GaugeVec> = Lazy::new(|| {
     let gauge = GaugeVec::new(
         Opts::new(
@@ -372,6 +552,94 @@ pub(crate) static SHOW_CLIENT_PREPARED_ANONYMOUS_ENTRIES: Lazy<GaugeVec> = Lazy:
     gauge
 });
 
+pub(crate) static SESSION_FEATURE_IN_TXN_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new(
+            "pg_doorman_session_feature_in_txn_total",
+            "Statements using a session-only feature (temp table, WITH HOLD cursor, session advisory lock, LISTEN) on transaction-mode connections, by user, database and feature.",
+        ),
+        &["user", "database", "feature"],
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(counter.clone())).unwrap();
+    counter
+});
+
+pub(crate) static POOLS_AUTO_PAUSE_TRANSITIONS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new(
+            "pg_doorman_pools_auto_pause_transitions_total",
+            "Cumulative count of auto_pause_on_unhealthy transitions, by user, database and transition (pause, resume).",
+        ),
+        &["user", "database", "transition"],
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(counter.clone())).unwrap();
+    counter
+});
+
+pub(crate) static SERVERS_MAX_AGE_RECYCLES_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new(
+            "pg_doorman_servers_max_age_recycles_total",
+            "Cumulative count of server connections closed at a transaction boundary because they outlived server_max_age, by user and database.",
+        ),
+        &["user", "database"],
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(counter.clone())).unwrap();
+    counter
+});
+
+/// Checkouts of `prefer_last_server` pools by clients that already had a
+/// server: `hit` when they got the same one back, `miss` otherwise.
+pub(crate) static POOLS_AFFINITY_CHECKOUTS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new(
+            "pg_doorman_pools_affinity_checkouts_total",
+            "Cumulative count of server checkouts in prefer_last_server pools by clients that used a server before, by user, database and result ('hit' when the client got its previous server back, 'miss' otherwise).",
+        ),
+        &["user", "database", "result"],
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(counter.clone())).unwrap();
+    counter
+});
+
+/// How long server connections lived, observed when they close, by close
+/// reason (see `ServerCloseCause`). Aggregated across pools to keep the
+/// series count fixed; the per-pool close rate is on
+/// `pg_doorman_server_recycles_total`.
+pub(crate) static SERVER_CONNECTION_LIFETIME_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    let histogram = HistogramVec::new(
+        HistogramOpts::new(
+            "pg_doorman_server_connection_lifetime_seconds",
+            "Lifetime of server connections in seconds, observed when they close, by close \
+             reason: idle, lifetime, age, bad, reconnect, evicted, shutdown, other.",
+        )
+        .buckets(vec![
+            1.0, 10.0, 60.0, 300.0, 900.0, 1800.0, 3600.0, 7200.0, 21600.0, 86400.0,
+        ]),
+        &["reason"],
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(histogram.clone())).unwrap();
+    histogram
+});
+
+pub(crate) static SERVER_RECYCLES_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new(
+            "pg_doorman_server_recycles_total",
+            "Cumulative count of closed server connections by user, database and close reason.",
+        ),
+        &["user", "database", "reason"],
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(counter.clone())).unwrap();
+    counter
+});
+
 pub(crate) static SHOW_CLIENT_PREPARED_ANONYMOUS_EVICTIONS_TOTAL: Lazy<IntCounterVec> =
     Lazy::new(|| {
         let counter = IntCounterVec::new(
@@ -388,6 +656,20 @@ pub(crate) static SHOW_CLIENT_PREPARED_ANONYMOUS_EVICTIONS_TOTAL: Lazy<IntCounte
         counter
     });
 
+pub(crate) static SHOW_CLIENT_PREPARED_REMAPS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new(
+            "pg_doorman_clients_prepared_remaps_total",
+            "Cumulative count of Named Parses that reused a client statement name for a \
+             different query, by user and database.",
+        ),
+        &["user", "database"],
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(counter.clone())).unwrap();
+    counter
+});
+
 /// Wall-clock duration of each phase of backend connection setup, split
 /// by phase. Phases are disjoint and additive:
 /// - `tcp_connect` — raw socket connect (TcpStream::connect or
@@ -427,6 +709,36 @@ pub(crate) static BACKEND_CREATE_DURATION_SECONDS: Lazy<HistogramVec> = Lazy::ne
     histogram
 });
 
+/// Wall-clock duration of each phase a client goes through, split by
+/// phase:
+/// - `tls_handshake` — client TLS handshake, only for TLS clients.
+/// - `auth` — from the StartupMessage to AuthenticationOK, including
+///   HBA checks, password exchange and auth_query lookups.
+/// - `checkout` — from the first message of a transaction to the moment
+///   a backend is handed to the client (query limits and pool wait).
+/// - `active` — how long the client held that backend: one transaction
+///   in transaction mode, the whole session in session mode.
+///
+/// Like `pg_doorman_backend_create_duration_seconds`, the only label is
+/// `phase`, so the series count does not grow with the number of pools.
+/// Failed phases are not observed.
+pub(crate) static CLIENT_PHASE_DURATION_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    let histogram = HistogramVec::new(
+        prometheus::HistogramOpts::new(
+            "pg_doorman_client_phase_duration_seconds",
+            "Wall-clock duration of each client phase: 'tls_handshake' (client \
+             TLS handshake), 'auth' (StartupMessage to AuthenticationOK), \
+             'checkout' (waiting for a backend), 'active' (holding a backend). \
+             Aggregated across pools.",
+        )
+        .buckets(vec![0.0001, 0.001, 0.01, 0.1, 1.0, 10.0]),
+        &["phase"],
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(histogram.clone())).unwrap();
+    histogram
+});
+
 /// Counter for client connections rejected before authentication completes,
 /// split by reason. The label set is fixed:
 /// - `hba` — HBA configuration explicitly denied the client
@@ -435,6 +747,8 @@ pub(crate) static BACKEND_CREATE_DURATION_SECONDS: Lazy<HistogramVec> = Lazy::ne
 /// - `protocol_error` — unexpected sequence of startup messages
 /// - `invalid_startup` — malformed startup packet or socket error before parameters
 /// - `too_many_clients` — listener at `max_clients` capacity
+/// - `proxy_protocol` — missing or malformed PROXY header with `accept_proxy_protocol` on
+/// - `auth_ban` — client IP is banned after repeated authentication failures
 ///
 /// A sustained non-zero `hba` or `tls_handshake_fail` rate is the bruteforce
 /// signal pg_doorman previously only logged.
@@ -448,7 +762,10 @@ pub(crate) static LISTENER_REJECTIONS_TOTAL: Lazy<IntCounterVec> = Lazy::new(||
              'tls_handshake_fail' (TLS negotiation failed), \
              'protocol_error' (unexpected startup message sequence), \
              'invalid_startup' (malformed startup or socket error), \
-             'too_many_clients' (listener at capacity).",
+             'too_many_clients' (listener at capacity), \
+             'proxy_protocol' (missing or invalid PROXY protocol header), \
+             'auth_ban' (client IP banned after repeated authentication failures), \
+             'startup_timeout' (startup or authentication not finished within startup_timeout).",
         ),
         &["reason"],
     )
@@ -457,6 +774,82 @@ pub(crate) static LISTENER_REJECTIONS_TOTAL: Lazy<IntCounterVec> = Lazy::new(||
     counter
 });
 
+/// Counter for protocol desyncs: a message header with an invalid type
+/// byte or length, split by the phase it was read in:
+/// - `startup` — the startup packet of a new client
+/// - `client` — a message from an authenticated client
+/// - `server` — a message from a PostgreSQL backend
+///
+/// Each desync is also logged with the offending header bytes before the
+/// connection is closed.
+pub(crate) static PROTOCOL_ERRORS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new(
+            "pg_doorman_protocol_errors_total",
+            "Cumulative count of connections closed on a protocol desync \
+             (invalid message type byte or length), by phase: 'startup', \
+             'client', 'server'.",
+        ),
+        &["phase"],
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(counter.clone())).unwrap();
+    counter
+});
+
+/// Counter for failed client TLS handshakes, split by cause:
+/// - `protocol` — no common protocol version or cipher, or not TLS at all
+/// - `cert_expired` — the client certificate has expired
+/// - `cert_unknown_ca` — a certificate does not chain to a trusted CA, on
+///   either side
+/// - `alert` — the client aborted the handshake with another TLS alert
+/// - `other` — anything else, e.g. the client disconnected mid-handshake
+///
+/// Every failure is also counted in `pg_doorman_listener_rejections_total`
+/// under `tls_handshake_fail`.
+pub(crate) static TLS_HANDSHAKE_FAILURES_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new(
+            "pg_doorman_tls_handshake_failures_total",
+            "Cumulative count of failed client TLS handshakes by cause: \
+             'protocol', 'cert_expired', 'cert_unknown_ca', 'alert', 'other'.",
+        ),
+        &["cause"],
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(counter.clone())).unwrap();
+    counter
+});
+
+/// Counter for rejected client logins, split by reason. The label set is
+/// fixed:
+/// - `bad_password` — wrong password, unknown auth_query user, invalid JWT
+///   or Talos token
+/// - `hba_denied` — HBA configuration denied the client
+/// - `no_pool` — no pool (or shard) for the requested database and user
+/// - `unsupported_method` — the user's password type cannot be checked
+/// - `protocol_error` — malformed SCRAM, PAM or JWT message from the client
+///
+/// `subnet` is the client's /24 or /64 network with
+/// `general.auth_failures_by_subnet`, otherwise empty. The username is
+/// deliberately not a label: a credential-stuffing run would create one
+/// series per guessed name.
+pub(crate) static AUTH_FAILURES_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new(
+            "pg_doorman_auth_failures_total",
+            "Cumulative count of rejected client logins by reason and, with \
+             general.auth_failures_by_subnet, client subnet. Reasons: \
+             'bad_password', 'hba_denied', 'no_pool', 'unsupported_method', \
+             'protocol_error'.",
+        ),
+        &["reason", "subnet"],
+    )
+    .unwrap();
+    REGISTRY.register(Box::new(counter.clone())).unwrap();
+    counter
+});
+
 /// Counts backend startup attempts pg_doorman aborted because PostgreSQL
 /// returned an `ErrorResponse` that names a key the pool actually sent in
 /// `StartupMessage`. Labels:
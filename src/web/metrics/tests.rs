@@ -220,6 +220,140 @@ fn test_pool_state_gauges_register_and_export() {
     SHOW_POOLS_MAXWAIT_MICROSECONDS.reset();
 }
 
+#[test]
+fn test_pool_wait_queue_gauges_register_and_export() {
+    use crate::web::metrics::{POOL_OLDEST_WAIT_SECONDS, POOL_WAITING_CLIENTS};
+    use prometheus::core::Collector;
+
+    POOL_WAITING_CLIENTS
+        .with_label_values(&["bob", "shop"])
+        .set(3.0);
+    POOL_OLDEST_WAIT_SECONDS
+        .with_label_values(&["bob", "shop"])
+        .set(1.5);
+
+    let names: Vec<_> = POOL_WAITING_CLIENTS
+        .desc()
+        .iter()
+        .chain(POOL_OLDEST_WAIT_SECONDS.desc().iter())
+        .map(|d| d.fq_name.clone())
+        .collect();
+    assert!(names.iter().any(|n| n == "pg_doorman_pool_waiting_clients"));
+    assert!(names
+        .iter()
+        .any(|n| n == "pg_doorman_pool_oldest_wait_seconds"));
+
+    POOL_WAITING_CLIENTS.reset();
+    POOL_OLDEST_WAIT_SECONDS.reset();
+}
+
+#[test]
+fn test_pool_current_size_gauge_registers_and_exports() {
+    use crate::web::metrics::POOL_CURRENT_SIZE;
+    use prometheus::core::Collector;
+
+    POOL_CURRENT_SIZE
+        .with_label_values(&["bob", "shop"])
+        .set(7.0);
+
+    let names: Vec<_> = POOL_CURRENT_SIZE
+        .desc()
+        .iter()
+        .map(|d| d.fq_name.clone())
+        .collect();
+    assert_eq!(names, vec!["pg_doorman_pool_current_size".to_string()]);
+    assert_eq!(
+        POOL_CURRENT_SIZE.with_label_values(&["bob", "shop"]).get(),
+        7.0
+    );
+
+    POOL_CURRENT_SIZE.reset();
+}
+
+#[test]
+fn test_memory_limit_hits_counter_increments() {
+    use crate::web::metrics::{record_memory_limit_hit, MEMORY_LIMIT_HITS_TOTAL};
+
+    let before = MEMORY_LIMIT_HITS_TOTAL.get();
+    record_memory_limit_hit();
+    assert!(MEMORY_LIMIT_HITS_TOTAL.get() > before);
+}
+
+#[test]
+fn test_server_close_records_lifetime_and_recycle() {
+    use crate::web::metrics::{
+        record_server_close, SERVER_CONNECTION_LIFETIME_SECONDS, SERVER_RECYCLES_TOTAL,
+    };
+
+    let histogram = SERVER_CONNECTION_LIFETIME_SECONDS.with_label_values(&["lifetime"]);
+    let counter = SERVER_RECYCLES_TOTAL.with_label_values(&["close_user", "close_db", "lifetime"]);
+    let samples_before = histogram.get_sample_count();
+    let closes_before = counter.get();
+
+    record_server_close("close_user", "close_db", "lifetime", 1200.0);
+
+    assert!(histogram.get_sample_count() > samples_before);
+    assert_eq!(counter.get(), closes_before + 1);
+}
+
+#[test]
+fn test_session_feature_in_txn_counter() {
+    use crate::web::metrics::{record_session_feature_in_txn, SESSION_FEATURE_IN_TXN_TOTAL};
+
+    let counter = SESSION_FEATURE_IN_TXN_TOTAL.with_label_values(&[
+        "feature_user",
+        "feature_db",
+        "temp_table",
+    ]);
+    let before = counter.get();
+
+    record_session_feature_in_txn("feature_user", "feature_db", "temp_table");
+
+    assert_eq!(counter.get(), before + 1);
+}
+
+#[test]
+fn test_affinity_checkout_counter() {
+    use crate::web::metrics::{record_affinity_checkout, POOLS_AFFINITY_CHECKOUTS_TOTAL};
+
+    let hits =
+        POOLS_AFFINITY_CHECKOUTS_TOTAL.with_label_values(&["affinity_user", "affinity_db", "hit"]);
+    let misses =
+        POOLS_AFFINITY_CHECKOUTS_TOTAL.with_label_values(&["affinity_user", "affinity_db", "miss"]);
+    let (hits_before, misses_before) = (hits.get(), misses.get());
+
+    record_affinity_checkout("affinity_user", "affinity_db", true);
+    record_affinity_checkout("affinity_user", "affinity_db", true);
+    record_affinity_checkout("affinity_user", "affinity_db", false);
+
+    assert_eq!(hits.get(), hits_before + 2);
+    assert_eq!(misses.get(), misses_before + 1);
+}
+
+#[test]
+fn test_tls_handshake_failure_counter() {
+    use crate::web::metrics::{record_tls_handshake_failure, TLS_HANDSHAKE_FAILURES_TOTAL};
+
+    let counter = TLS_HANDSHAKE_FAILURES_TOTAL.with_label_values(&["cert_expired"]);
+    let before = counter.get();
+
+    record_tls_handshake_failure("cert_expired");
+
+    assert_eq!(counter.get(), before + 1);
+}
+
+#[test]
+fn test_protocol_error_counter() {
+    use crate::web::metrics::{record_protocol_error, PROTOCOL_ERRORS_TOTAL};
+
+    let counter = PROTOCOL_ERRORS_TOTAL.with_label_values(&["client"]);
+    let before = counter.get();
+
+    record_protocol_error("client");
+
+    assert_eq!(counter.get(), before + 1);
+}
+
 #[tokio::test]
 #[ignore] // Ignore by default as it requires network access and might conflict with other tests
 async fn test_prometheus_server_integration() {
@@ -317,3 +451,51 @@ async fn test_prometheus_server_integration() {
     // Clean up
     server_handle.abort();
 }
+
+// `#[serial]` for the same reason as `test_prometheus_server_basic`.
+#[cfg(unix)]
+#[tokio::test]
+#[serial]
+async fn test_prometheus_server_over_unix_socket() {
+    use tokio::net::{UnixListener, UnixStream};
+
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("web.sock");
+    let listener = UnixListener::bind(&path).expect("bind unix socket");
+    let server_handle = tokio::spawn(async move {
+        crate::web::serve_unix_on(
+            listener,
+            WebServerOptions {
+                ui_active: false,
+                ui_anonymous: true,
+                admin_username: "admin".into(),
+                admin_password: "secret".into(),
+                sso: None,
+                sso_config_error: None,
+                trusted_proxies: Vec::new(),
+                sso_admin_groups_configured: false,
+                sso_require_https: false,
+            },
+        )
+        .await;
+    });
+
+    let mut stream = UnixStream::connect(&path).await.expect("connect");
+    stream
+        .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = Vec::new();
+    tokio::time::timeout(Duration::from_secs(2), stream.read_to_end(&mut response))
+        .await
+        .expect("response within 2s")
+        .expect("read response");
+    server_handle.abort();
+
+    let response = String::from_utf8_lossy(&response);
+    assert!(
+        response.starts_with("HTTP/1.1 200 OK"),
+        "unexpected response: {response}"
+    );
+    assert!(response.contains("pg_doorman_connection_count"));
+}
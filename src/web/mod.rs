@@ -11,6 +11,8 @@ pub mod server;
 pub mod sso;
 pub mod static_assets;
 
+#[cfg(unix)]
+pub use server::serve_unix_on;
 pub use server::{bind_web_listener, refresh_options_from_config, serve_on, WebServerOptions};
 
 #[cfg(test)]
@@ -1,5 +1,5 @@
 //! `POST /api/admin/{action}` — write surface that mirrors the admin
-//! protocol's RELOAD / PAUSE / RESUME / RECONNECT commands. Authorisation
+//! protocol's RELOAD / PAUSE / RESUME / RECONNECT / DRAIN / UNDRAIN commands. Authorisation
 //! is gated by the listener mux (admin basic-auth, see
 //! `is_admin_only` in server.rs); this module just dispatches to the
 //! async wrappers in `crate::admin::operations` and renders the reply.
@@ -19,6 +19,8 @@ use serde_json::json;
 use crate::admin::operations::{
     pause_now, reconnect_now, reload_now, resume_now, AdminEffect, AdminScope,
 };
+use crate::app::drain;
+use crate::config::ConfigDiff;
 use crate::web::routes::collect::now_unix_ms;
 use crate::web::routes::query::{first, parse_query};
 use crate::web::server::Response;
@@ -51,12 +53,14 @@ pub(crate) async fn handle_admin_action(raw_path: &str) -> Response {
 
     match action {
         "reload" => match reload_now().await {
-            Ok(changed) => json_reload(changed),
+            Ok(diff) => json_reload(&diff),
             Err(err) => json_err("reload", &err.to_string()),
         },
         "pause" => render_effect("pause", pause_now(scope)),
         "resume" => render_effect("resume", resume_now(scope)),
         "reconnect" => render_effect("reconnect", reconnect_now(scope)),
+        "drain" => json_drain("drain", drain::drain()),
+        "undrain" => json_drain("undrain", drain::undrain()),
         _ => Response::ok_json(&json!({
             "error": "unknown_action",
             "message": format!("unknown admin action: {action}"),
@@ -134,11 +138,24 @@ fn json_ok(action: &str, affected: &[crate::pool::PoolIdentifier]) -> Response {
 /// `reload` is global, so `affected_pools` is meaningless. Surface
 /// `changed: true|false` so operators can distinguish a config rotation
 /// from a no-op SIGHUP.
-fn json_reload(changed: bool) -> Response {
+fn json_reload(diff: &ConfigDiff) -> Response {
+    let changes: Vec<String> = diff.changes.iter().map(ToString::to_string).collect();
     Response::ok_json(&json!({
         "ts": now_unix_ms(),
         "action": "reload",
+        "changed": !diff.is_empty(),
+        "changes": changes,
+    }))
+}
+
+/// DRAIN / UNDRAIN are server-wide: report whether the call changed the
+/// state and what the state is now.
+fn json_drain(action: &str, changed: bool) -> Response {
+    Response::ok_json(&json!({
+        "ts": now_unix_ms(),
+        "action": action,
         "changed": changed,
+        "state": drain::server_state().as_str(),
     }))
 }
 
@@ -212,6 +229,21 @@ mod tests {
         assert!(body.contains("bad_scope"), "{body}");
     }
 
+    #[tokio::test]
+    #[serial_test::serial(drain)]
+    async fn drain_and_undrain_report_state() {
+        let r = handle_admin_action("/api/admin/drain").await;
+        assert_eq!(r.status, 200);
+        let body = std::str::from_utf8(&r.body).unwrap();
+        assert!(body.contains(r#""changed":true"#), "{body}");
+        assert!(body.contains(r#""state":"draining""#), "{body}");
+
+        let r = handle_admin_action("/api/admin/undrain").await;
+        let body = std::str::from_utf8(&r.body).unwrap();
+        assert!(body.contains(r#""changed":true"#), "{body}");
+        assert!(!body.contains(r#""state":"draining""#), "{body}");
+    }
+
     #[tokio::test]
     async fn pause_with_malformed_pool_returns_400() {
         let r = handle_admin_action("/api/admin/pause?pool=just_a_user").await;
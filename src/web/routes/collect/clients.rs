@@ -83,7 +83,7 @@ fn client_matches(s: &crate::stats::ClientStats, f: &ClientFilters) -> bool {
             return false;
         }
     }
-    if !f.application_name.is_empty() && !f.application_name.iter().any(|x| x == app) {
+    if !f.application_name.is_empty() && !f.application_name.iter().any(|x| *x == app) {
         return false;
     }
     if !f.state.is_empty() && !f.state.iter().any(|x| x == state) {
@@ -98,7 +98,7 @@ fn client_to_dto(s: &std::sync::Arc<crate::stats::ClientStats>) -> ClientDto {
         client_id: format!("#c{}", s.connection_id()),
         database: s.pool_name().to_string(),
         user: s.username().to_string(),
-        application_name: s.application_name().to_string(),
+        application_name: s.application_name(),
         addr: s.ipaddr().to_string(),
         tls: s.tls(),
         state: s.state_str().to_string(),
@@ -102,6 +102,7 @@ pub(crate) fn collect_overview() -> OverviewDto {
         clients_in_transactions: CLIENTS_IN_TRANSACTIONS.load(Ordering::Relaxed),
         shutdown_in_progress: SHUTDOWN_IN_PROGRESS.load(Ordering::Relaxed),
         migration_in_progress: MIGRATION_IN_PROGRESS.load(Ordering::Relaxed),
+        server_state: crate::app::drain::server_state().as_str(),
     }
 }
 
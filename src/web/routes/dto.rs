@@ -14,6 +14,8 @@ pub(crate) struct VersionDto {
     pub version: &'static str,
     pub git_commit: &'static str,
     pub build_date: &'static str,
+    pub rustc_version: &'static str,
+    pub features: &'static str,
     pub ts: u64,
 }
 
@@ -79,6 +81,9 @@ pub(crate) struct OverviewDto {
     pub shutdown_in_progress: bool,
     /// Set during binary upgrade — clients are migrating to the new process.
     pub migration_in_progress: bool,
+    /// `SHOW STATE` value: `accepting`, `draining`, `paused` or
+    /// `shutting_down`.
+    pub server_state: &'static str,
 }
 
 /// Database that holds the most live backend connections at the moment
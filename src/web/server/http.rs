@@ -4,12 +4,11 @@
 //! response cannot be produced synchronously), and stops when the client
 //! signals close or the per-connection request cap is hit.
 
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
 
-use tokio::io::{AsyncReadExt, BufReader, BufWriter};
-use tokio::net::tcp::OwnedReadHalf;
-use tokio::net::TcpStream;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, BufReader, BufWriter};
 
 use crate::web::auth::{classify, AuthOutcome, Role, SsoTransportPolicy};
 use crate::web::metrics::write_metrics_response;
@@ -28,9 +27,16 @@ const KEEPALIVE_MAX_REQUESTS: u32 = 1000;
 /// because each idle connection still costs an FD and a tokio task.
 const KEEPALIVE_IDLE_TIMEOUT: Duration = Duration::from_secs(30);
 
-pub(super) async fn handle_connection(stream: TcpStream, opts: Arc<WebServerOptions>) {
-    let peer_addr = stream.peer_addr().ok();
-    let (read_half, write_half) = stream.into_split();
+/// `peer_addr` is `None` for connections accepted on the Unix socket;
+/// such requests never match `trusted_proxies` and log `-` as the peer.
+pub(super) async fn handle_connection<S>(
+    stream: S,
+    peer_addr: Option<SocketAddr>,
+    opts: Arc<WebServerOptions>,
+) where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (read_half, write_half) = tokio::io::split(stream);
     let mut reader = BufReader::new(read_half);
     let mut writer = BufWriter::new(write_half);
 
@@ -198,8 +204,8 @@ fn extract_query_token(query: Option<&str>) -> Option<&str> {
 /// pipelined body / next request begin), or `Ok(0)` if the peer closed
 /// cleanly between requests. Caps the buffer at 32 KiB so a malicious
 /// client cannot push us into OOM.
-async fn read_request_head(
-    reader: &mut BufReader<OwnedReadHalf>,
+async fn read_request_head<R: AsyncRead + Unpin>(
+    reader: &mut BufReader<R>,
     buf: &mut Vec<u8>,
 ) -> Result<usize, ReadError> {
     const MAX_HEADER_BYTES: usize = 32 * 1024;
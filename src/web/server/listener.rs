@@ -1,4 +1,4 @@
-//! TCP bind and accept loop. The listener owns the lifetime of the
+//! TCP and Unix socket bind and accept loops. The listener owns the lifetime of the
 //! [`WebServerOptions`] slot — it seeds the slot on bind so every spawned
 //! connection task reads the same reload-aware view via
 //! [`current_options`].
@@ -9,6 +9,8 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use log::{error, info};
+#[cfg(unix)]
+use tokio::net::UnixListener;
 use tokio::net::{TcpListener, TcpSocket};
 
 use crate::messages::configure_web_tcp_socket;
@@ -73,24 +75,47 @@ pub async fn serve_on(listener: TcpListener, opts: WebServerOptions) {
         match listener.accept().await {
             Ok((stream, _)) => {
                 configure_web_tcp_socket(&stream);
+                let peer_addr = stream.peer_addr().ok();
                 let opts = current_options();
                 tokio::spawn(async move {
-                    handle_connection(stream, opts).await;
+                    handle_connection(stream, peer_addr, opts).await;
                 });
             }
-            Err(e) => {
-                if is_fd_exhaustion_io(&e) {
-                    if should_log_web_accept_resource_now() {
-                        error!(
-                            "Failed to accept connection: {e} \
-                             (process fd table exhausted; backing off)"
-                        );
-                    }
-                    tokio::time::sleep(Duration::from_millis(10)).await;
-                } else {
-                    error!("Failed to accept connection: {e}");
-                }
+            Err(e) => on_accept_error(e).await,
+        }
+    }
+}
+
+/// Drive the accept loop on the pre-bound `[web].unix_socket` listener.
+/// Requests on it are served exactly like TCP ones, so a sidecar can
+/// scrape `/metrics` without the endpoint being reachable over the network.
+#[cfg(unix)]
+pub async fn serve_unix_on(listener: UnixListener, opts: WebServerOptions) {
+    install_options(Arc::new(opts));
+
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let opts = current_options();
+                tokio::spawn(async move {
+                    handle_connection(stream, None, opts).await;
+                });
             }
+            Err(e) => on_accept_error(e).await,
         }
     }
 }
+
+async fn on_accept_error(e: std::io::Error) {
+    if is_fd_exhaustion_io(&e) {
+        if should_log_web_accept_resource_now() {
+            error!(
+                "Failed to accept connection: {e} \
+                 (process fd table exhausted; backing off)"
+            );
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    } else {
+        error!("Failed to accept connection: {e}");
+    }
+}
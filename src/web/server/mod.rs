@@ -14,7 +14,7 @@
 //! - [`wire`]     — request parser, response builder, gzip cache, header helpers.
 //! - [`http`]     — keep-alive driven HTTP/1.1 connection handler.
 //! - [`router`]   — path dispatch and admin-only gating.
-//! - [`listener`] — TCP bind and accept loop.
+//! - [`listener`] — TCP bind and accept loop, plus the optional Unix socket one.
 
 mod http;
 mod listener;
@@ -25,6 +25,8 @@ pub(crate) mod wire;
 #[cfg(test)]
 mod tests;
 
+#[cfg(unix)]
+pub use listener::serve_unix_on;
 pub use listener::{bind_web_listener, serve_on};
 pub use state::{refresh_options_from_config, WebServerOptions};
 pub(crate) use wire::Response;
@@ -3,8 +3,7 @@
 //! headers — it just turns bytes into [`ParsedRequest`] and a
 //! [`Response`] back into bytes.
 
-use tokio::io::{AsyncWriteExt, BufWriter};
-use tokio::net::tcp::OwnedWriteHalf;
+use tokio::io::{AsyncWrite, AsyncWriteExt, BufWriter};
 
 #[derive(Debug)]
 pub(super) enum ReadError {
@@ -275,7 +274,10 @@ impl Response {
         }
     }
 
-    pub(super) async fn write(self, writer: &mut BufWriter<OwnedWriteHalf>) -> std::io::Result<()> {
+    pub(super) async fn write<W: AsyncWrite + Unpin>(
+        self,
+        writer: &mut BufWriter<W>,
+    ) -> std::io::Result<()> {
         let mut head = format!(
             "HTTP/1.1 {} {}\r\nContent-Length: {}\r\n",
             self.status,
@@ -297,8 +299,8 @@ impl Response {
     }
 }
 
-pub(super) async fn write_simple(
-    writer: &mut BufWriter<OwnedWriteHalf>,
+pub(super) async fn write_simple<W: AsyncWrite + Unpin>(
+    writer: &mut BufWriter<W>,
     status: u16,
     reason: &'static str,
 ) -> std::io::Result<()> {
@@ -34,7 +34,7 @@ async fn create_executor(world: &mut DoormanWorld, query: String, pool_size: u32
     let pg_port = world.pg_port.expect("PostgreSQL must be running");
     let config = build_test_config(&query, pool_size, None);
 
-    let executor = AuthQueryExecutor::new(&config, "postgres", "127.0.0.1", pg_port)
+    let executor = AuthQueryExecutor::new(&config, "postgres", "postgres", "127.0.0.1", pg_port)
         .await
         .expect("Failed to create AuthQueryExecutor");
 
@@ -53,7 +53,7 @@ async fn create_executor_with_database(
     let pg_port = world.pg_port.expect("PostgreSQL must be running");
     let config = build_test_config(&query, pool_size, Some(database));
 
-    let executor = AuthQueryExecutor::new(&config, "postgres", "127.0.0.1", pg_port)
+    let executor = AuthQueryExecutor::new(&config, "postgres", "postgres", "127.0.0.1", pg_port)
         .await
         .expect("Failed to create AuthQueryExecutor");
 
@@ -67,7 +67,7 @@ async fn executor_creation_should_fail(world: &mut DoormanWorld, host: String, p
     let _ = world; // suppress unused warning (world is required by cucumber signature)
     let config = build_test_config("SELECT $1::text, 'hash'", 1, Some("postgres".to_string()));
 
-    let result = AuthQueryExecutor::new(&config, "test_pool", &host, port).await;
+    let result = AuthQueryExecutor::new(&config, "test_pool", "test_pool", &host, port).await;
 
     match result {
         Err(Error::AuthQueryConnectionError(_)) => {
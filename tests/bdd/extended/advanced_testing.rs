@@ -462,8 +462,47 @@ pub async fn verify_error_response_with_sqlstate(
     );
 }
 
+/// Wait for an unsolicited NoticeResponse on an idle session (DRAIN or
+/// shutdown notice) and assert its message contains `expected`.
+#[then(regex = r#"^session "([^"]+)" should receive NoticeResponse containing "([^"]+)"$"#)]
+pub async fn verify_notice_response_containing(
+    world: &mut DoormanWorld,
+    session_name: String,
+    expected: String,
+) {
+    let conn = super::helpers::get_session(&mut world.named_sessions, &session_name);
+    let mut got_notice: Option<String> = None;
+    while let Ok(Ok((msg_type, data))) =
+        tokio::time::timeout(std::time::Duration::from_secs(5), conn.read_message()).await
+    {
+        if msg_type == 'N' {
+            got_notice = extract_field(&data, 'M');
+            break;
+        }
+    }
+
+    let got = got_notice.unwrap_or_else(|| {
+        panic!(
+            "Session '{}': expected NoticeResponse containing '{}' but none received",
+            session_name, expected
+        )
+    });
+    assert!(
+        got.contains(&expected),
+        "Session '{}': notice '{}' does not contain '{}'",
+        session_name,
+        got,
+        expected
+    );
+}
+
 /// Extract the SQLSTATE (`C` field) from a PostgreSQL ErrorResponse body.
 fn extract_error_code(data: &[u8]) -> Option<String> {
+    extract_field(data, 'C')
+}
+
+/// Extract one field from an ErrorResponse or NoticeResponse body.
+fn extract_field(data: &[u8], field: char) -> Option<String> {
     let mut i = 0;
     while i < data.len() && data[i] != 0 {
         let field_byte = data[i] as char;
@@ -474,7 +513,7 @@ fn extract_error_code(data: &[u8]) -> Option<String> {
         }
         let value = String::from_utf8_lossy(&data[start..i]).to_string();
         i += 1; // skip null terminator
-        if field_byte == 'C' {
+        if field_byte == field {
             return Some(value);
         }
     }
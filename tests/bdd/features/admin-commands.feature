@@ -49,11 +49,43 @@ Feature: Admin console SHOW commands
       | users          | 1        |
       | log_level      | 1        |
 
+  @admin-commands-probes
+  Scenario Outline: introspection probe "<query>" is answered locally
+    When we create admin session "admin" to pg_doorman as "admin" with password "admin"
+    And we execute "<query>" on admin session "admin" and store row count
+    Then admin session "admin" row count should be greater than or equal to 1
+
+    Examples:
+      | query                                         |
+      | SELECT version()                              |
+      | select current_setting('server_version');     |
+      | SELECT pg_catalog.current_setting('TimeZone') |
+      | SHOW server_version                           |
+      | show standard_conforming_strings              |
+
   @admin-commands-sockets
   Scenario: SHOW SOCKETS does not crash (Linux only)
     When we create admin session "admin" to pg_doorman as "admin" with password "admin"
     And we execute "show sockets" on admin session "admin" expecting possible error
 
+  @admin-commands-show-config-filter
+  Scenario: SHOW CONFIG <key> JSON returns one JSON row
+    When we create admin session "admin" to pg_doorman as "admin" with password "admin"
+    And we execute "show config worker_threads" on admin session "admin" and store row count
+    Then admin session "admin" row count should be 1
+    When we execute "show config worker_threads json" on admin session "admin" and store response
+    Then admin session "admin" response should contain "changeable"
+
+  @admin-commands-show-clients-json
+  Scenario: SHOW CLIENTS JSON and SHOW SERVERS JSON return one JSON document
+    When we create admin session "admin" to pg_doorman as "admin" with password "admin"
+    And we execute "show clients json" on admin session "admin" and store row count
+    Then admin session "admin" row count should be 1
+    When we execute "show clients json" on admin session "admin" and store response
+    Then admin session "admin" response should contain "client_id"
+    When we execute "show servers json" on admin session "admin" and store row count
+    Then admin session "admin" row count should be 1
+
   @admin-commands-help
   Scenario: SHOW HELP returns help text
     When we create admin session "admin" to pg_doorman as "admin" with password "admin"
@@ -0,0 +1,86 @@
+@rust @rust-2 @client-label-gucs
+Feature: client_label_gucs labels server connections with the client
+  With client_label_gucs enabled every checkout sets pg_doorman.client_addr
+  and pg_doorman.client_tag on the server connection, pipelined with the
+  client's first message. The SET reply never reaches the client.
+
+  Background:
+    Given PostgreSQL started with pg_hba.conf:
+      """
+      local   all             all                                     trust
+      host    all             all             127.0.0.1/32            trust
+      host    all             all             ::1/128                 trust
+      """
+    And fixtures from "tests/fixture.sql" applied
+    And pg_doorman hba file contains:
+      """
+      host all admin 127.0.0.1/32 trust
+      host all example_user_1 127.0.0.1/32 md5
+      """
+    And self-signed SSL certificates are generated
+
+  Scenario: the client address is readable inside the transaction
+    Given pg_doorman started with config:
+      """
+      [general]
+      host = "127.0.0.1"
+      port = ${DOORMAN_PORT}
+      pg_hba = {path = "${DOORMAN_HBA_FILE}"}
+      admin_username = "admin"
+      admin_password = "admin"
+      tls_private_key = "${DOORMAN_SSL_KEY}"
+      tls_certificate = "${DOORMAN_SSL_CERT}"
+
+      [pools.example_db]
+      server_host = "127.0.0.1"
+      server_port = ${PG_PORT}
+      pool_mode = "transaction"
+      client_label_gucs = true
+
+      [[pools.example_db.users]]
+      username = "example_user_1"
+      password = "md58a67a0c805a5ee0384ea28e0dea557b6"
+      pool_size = 1
+      """
+    When I run shell command:
+      """
+      export PGPASSWORD=test
+      ADDR=$(psql -h 127.0.0.1 -p ${DOORMAN_PORT} -U example_user_1 -d example_db -tAc "select current_setting('pg_doorman.client_addr')")
+      TAG=$(psql -h 127.0.0.1 -p ${DOORMAN_PORT} -U example_user_1 -d example_db -tAc "select '[' || current_setting('pg_doorman.client_tag') || ']'")
+      echo "addr=$ADDR tag=$TAG"
+      test "$ADDR" = "127.0.0.1" || { echo "expected client_addr=127.0.0.1, got $ADDR"; exit 1; }
+      test "$TAG" = "[]" || { echo "expected empty client_tag, got $TAG"; exit 1; }
+      """
+    Then the command should succeed
+    And the command output should contain "addr=127.0.0.1 tag=[]"
+
+  Scenario: without client_label_gucs nothing is set
+    Given pg_doorman started with config:
+      """
+      [general]
+      host = "127.0.0.1"
+      port = ${DOORMAN_PORT}
+      pg_hba = {path = "${DOORMAN_HBA_FILE}"}
+      admin_username = "admin"
+      admin_password = "admin"
+      tls_private_key = "${DOORMAN_SSL_KEY}"
+      tls_certificate = "${DOORMAN_SSL_CERT}"
+
+      [pools.example_db]
+      server_host = "127.0.0.1"
+      server_port = ${PG_PORT}
+      pool_mode = "transaction"
+
+      [[pools.example_db.users]]
+      username = "example_user_1"
+      password = "md58a67a0c805a5ee0384ea28e0dea557b6"
+      pool_size = 1
+      """
+    When I run shell command:
+      """
+      export PGPASSWORD=test
+      ADDR=$(psql -h 127.0.0.1 -p ${DOORMAN_PORT} -U example_user_1 -d example_db -tAc "select coalesce(current_setting('pg_doorman.client_addr', true), 'unset')")
+      echo "addr=$ADDR"
+      test "$ADDR" = "unset" || { echo "expected no client_addr, got $ADDR"; exit 1; }
+      """
+    Then the command should succeed
@@ -0,0 +1,59 @@
+@binary-upgrade-grac-shutdown @client-shutdown-notice
+Feature: Connected clients are told about a shutdown
+  Admin SHUTDOWN sends SIGINT to the process. Idle clients get a WARNING
+  notice (SQLSTATE 01000) that the pooler is going away, in transaction
+  and in session pool mode alike.
+
+  Background:
+    Given PostgreSQL started with pg_hba.conf:
+      """
+      local all all trust
+      host all all 127.0.0.1/32 trust
+      """
+    And fixtures from "tests/fixture.sql" applied
+    And pg_doorman shutdown-only mode
+    And pg_doorman started with config:
+      """
+      [general]
+      host = "127.0.0.1"
+      port = ${DOORMAN_PORT}
+      admin_username = "admin"
+      admin_password = "admin"
+      pg_hba.content = "host all all 127.0.0.1/32 trust"
+      shutdown_timeout = 5000
+
+      [pools.example_db]
+      server_host = "127.0.0.1"
+      server_port = ${PG_PORT}
+      pool_mode = "transaction"
+
+      [[pools.example_db.users]]
+      username = "example_user_1"
+      password = ""
+      pool_size = 2
+
+      [pools.example_db_session]
+      server_host = "127.0.0.1"
+      server_port = ${PG_PORT}
+      server_database = "example_db"
+      pool_mode = "session"
+
+      [[pools.example_db_session.users]]
+      username = "example_user_1"
+      password = ""
+      pool_size = 2
+      """
+
+  Scenario: Admin SHUTDOWN notifies an idle transaction-mode client
+    When we create session "one" to pg_doorman as "example_user_1" with password "" and database "example_db"
+    And we send SimpleQuery "SELECT 1" to session "one"
+    And we create admin session "admin" to pg_doorman as "admin" with password "admin"
+    And we execute "SHUTDOWN" on admin session "admin"
+    Then session "one" should receive NoticeResponse containing "pg_doorman is shutting down"
+
+  Scenario: Admin SHUTDOWN notifies an idle session-mode client holding a server
+    When we create session "one" to pg_doorman as "example_user_1" with password "" and database "example_db_session"
+    And we send SimpleQuery "SELECT 1" to session "one"
+    And we create admin session "admin" to pg_doorman as "admin" with password "admin"
+    And we execute "SHUTDOWN" on admin session "admin"
+    Then session "one" should receive NoticeResponse containing "pg_doorman is shutting down"
@@ -105,3 +105,31 @@ Feature: DEALLOCATE clears client prepared statements cache
     And we send Execute "" to session "one"
     And we send Sync to session "one"
     Then session "one" should receive DataRow with "10"
+
+  Scenario: DEALLOCATE is answered with its own command tag and no ParseComplete
+    When we create session "one" to pg_doorman as "example_user_1" with password "" and database "example_db"
+    And we send SimpleQuery "DEALLOCATE ALL" to session "one"
+    Then session "one" should receive CommandComplete "DEALLOCATE ALL"
+    And session "one" should receive ReadyForQuery "I"
+    When we send SimpleQuery "deallocate prepare Mixed_Case_Name;" to session "one"
+    Then session "one" should receive CommandComplete "DEALLOCATE"
+    And session "one" should receive ReadyForQuery "I"
+
+  Scenario: DEALLOCATE ALL in one client keeps other clients' statements working
+    When we create session "one" to pg_doorman as "example_user_1" with password "" and database "example_db"
+    And we create session "two" to pg_doorman as "example_user_1" with password "" and database "example_db"
+    And we send Parse "shared_stmt" with query "select $1::int + 7" to session "one"
+    And we send Sync to session "one"
+    And we send Parse "shared_stmt" with query "select $1::int + 7" to session "two"
+    And we send Sync to session "two"
+    And we send SimpleQuery "DEALLOCATE ALL" to session "one"
+    And we send Bind "" to "shared_stmt" with params "1" to session "two"
+    And we send Execute "" to session "two"
+    And we send Sync to session "two"
+    Then session "two" should receive DataRow with "8"
+    When we send Parse "shared_stmt" with query "select $1::int + 7" to session "one"
+    And we send Sync to session "one"
+    And we send Bind "" to "shared_stmt" with params "2" to session "one"
+    And we send Execute "" to session "one"
+    And we send Sync to session "one"
+    Then session "one" should receive DataRow with "9"
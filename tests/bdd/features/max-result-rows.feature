@@ -0,0 +1,65 @@
+@rust @rust-2 @max-result-rows
+Feature: max_result_rows cancels runaway result sets
+  A round trip returning more rows than max_result_rows is canceled: the
+  client gets a WARNING naming the limit and ERROR 57014, and the session
+  keeps working. COPY output is not counted.
+
+  Background:
+    Given PostgreSQL started with pg_hba.conf:
+      """
+      local   all             all                                     trust
+      host    all             all             127.0.0.1/32            trust
+      host    all             all             ::1/128                 trust
+      """
+    And fixtures from "tests/fixture.sql" applied
+    And pg_doorman hba file contains:
+      """
+      host all example_user_1 127.0.0.1/32 md5
+      """
+    And pg_doorman started with config:
+      """
+      [general]
+      host = "127.0.0.1"
+      port = ${DOORMAN_PORT}
+      pg_hba = {path = "${DOORMAN_HBA_FILE}"}
+      admin_username = "admin"
+      admin_password = "admin"
+
+      [pools.example_db]
+      server_host = "127.0.0.1"
+      server_port = ${PG_PORT}
+      pool_mode = "transaction"
+      max_result_rows = 1000
+
+      [[pools.example_db.users]]
+      username = "example_user_1"
+      password = "md58a67a0c805a5ee0384ea28e0dea557b6"
+      pool_size = 1
+      """
+
+  Scenario: a query returning too many rows is canceled and the session survives
+    When I run shell command:
+      """
+      export PGPASSWORD=test
+      psql -h 127.0.0.1 -p ${DOORMAN_PORT} -U example_user_1 -d example_db -tA \
+        -c "select generate_series(1, 50000000)" \
+        -c "select 'still alive'" > /dev/null 2> err.txt
+      cat err.txt
+      OK=$(psql -h 127.0.0.1 -p ${DOORMAN_PORT} -U example_user_1 -d example_db -tAc "select count(*) from generate_series(1, 500)")
+      echo "small=$OK"
+      """
+    Then the command should succeed
+    And the command output should contain "exceeded max_result_rows (1000 rows)"
+    And the command output should contain "canceling statement"
+    And the command output should contain "small=500"
+
+  Scenario: COPY output is not counted
+    When I run shell command:
+      """
+      export PGPASSWORD=test
+      LINES=$(psql -h 127.0.0.1 -p ${DOORMAN_PORT} -U example_user_1 -d example_db -tAc "copy (select generate_series(1, 5000)) to stdout" | wc -l)
+      echo "lines=$LINES"
+      test "$LINES" -eq 5000
+      """
+    Then the command should succeed
+    And the command output should contain "lines=5000"
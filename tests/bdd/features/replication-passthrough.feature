@@ -0,0 +1,56 @@
+@replication-passthrough
+Feature: Replication passthrough
+  A client that sends `replication` in its StartupMessage gets a dedicated
+  walsender connection and is relayed without pooling, so pg_basebackup
+  and replication-protocol clients work through pg_doorman.
+
+  Background:
+    Given PostgreSQL started with pg_hba.conf:
+      """
+      local all all trust
+      host all all 127.0.0.1/32 trust
+      host replication all 127.0.0.1/32 trust
+      """
+    And fixtures from "tests/fixture.sql" applied
+    And pg_doorman started with config:
+      """
+      [general]
+      host = "127.0.0.1"
+      port = ${DOORMAN_PORT}
+      admin_username = "admin"
+      admin_password = "admin"
+      pg_hba.content = "host all all 127.0.0.1/32 trust"
+
+      [pools.postgres]
+      server_host = "127.0.0.1"
+      server_port = ${PG_PORT}
+      pool_mode = "transaction"
+
+      [[pools.postgres.users]]
+      username = "postgres"
+      password = ""
+      pool_size = 2
+      """
+
+  Scenario: pg_basebackup through pg_doorman
+    When I run shell command:
+      """
+      target=$(mktemp -d)
+      pg_basebackup -h 127.0.0.1 -p ${DOORMAN_PORT} -U postgres -D "$target" -X stream -c fast --no-sync \
+        && test -f "$target/PG_VERSION" && echo "basebackup ok"
+      status=$?
+      rm -rf "$target"
+      exit $status
+      """
+    Then the command should succeed
+    And the command output should contain "basebackup ok"
+
+  Scenario: replication=database accepts replication commands and SQL
+    When I run shell command:
+      """
+      psql "host=127.0.0.1 port=${DOORMAN_PORT} user=postgres dbname=postgres replication=database" -At -c "IDENTIFY_SYSTEM"
+      psql "host=127.0.0.1 port=${DOORMAN_PORT} user=postgres dbname=postgres replication=database" -At -c "SELECT 'sql ' || 42"
+      """
+    Then the command should succeed
+    And the command output should contain "sql 42"
+
@@ -0,0 +1,48 @@
+@scram-relay
+Feature: SCRAM relay — PostgreSQL checks the client's own password
+
+  In a scram_relay pool the users are listed with an empty password. Each
+  client's SCRAM exchange is relayed to a backend connection opened for
+  it alone, which then serves its whole session.
+
+  Background:
+    Given PostgreSQL started with pg_hba.conf:
+      """
+      local   all             all                                     trust
+      host    all             postgres        127.0.0.1/32            trust
+      host    all             all             127.0.0.1/32            scram-sha-256
+      host    all             all             ::1/128                 trust
+      """
+    And fixtures from "tests/static_passthrough_fixture.sql" applied
+    And pg_doorman hba file contains:
+      """
+      host all all 127.0.0.1/32 scram-sha-256
+      """
+    And pg_doorman started with config:
+      """
+      general:
+        host: "127.0.0.1"
+        port: ${DOORMAN_PORT}
+        connect_timeout: 5000
+        admin_username: "admin"
+        admin_password: "admin"
+        pg_hba:
+          path: "${DOORMAN_HBA_FILE}"
+      pools:
+        postgres:
+          server_host: "127.0.0.1"
+          server_port: ${PG_PORT}
+          pool_mode: "session"
+          scram_relay: true
+          users:
+            - username: "pt_static_scram"
+              password: ""
+              pool_size: 5
+      """
+
+  Scenario: The right password logs in, every time
+    Then psql query "SELECT current_user" via pg_doorman as user "pt_static_scram" to database "postgres" with password "scrampass" returns "pt_static_scram"
+    Then psql query "SELECT current_user" via pg_doorman as user "pt_static_scram" to database "postgres" with password "scrampass" returns "pt_static_scram"
+
+  Scenario: A wrong password is refused by PostgreSQL
+    Then psql connection to pg_doorman as user "pt_static_scram" to database "postgres" with password "wrong" fails with error containing "password authentication failed"
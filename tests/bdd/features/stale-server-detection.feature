@@ -270,3 +270,48 @@ Feature: Detect stale server connections during client idle in transaction
     When we send SimpleQuery "SELECT 3" to session "client3" without waiting
     Then we read SimpleQuery response from session "client3" within 5000ms
     Then session "client3" should receive DataRow with "3"
+
+  @stale-server-killed-mid-query
+  Scenario: Backend killed mid-query in a transaction — client gets 57P01, slot released
+    # The backend dies while the victim waits for a query result. The victim
+    # gets the backend's own SQLSTATE so it can tell a restart from other
+    # failures, and the slot is free for the next client right away.
+    Given pg_doorman started with config:
+      """
+      [general]
+      host = "127.0.0.1"
+      port = ${DOORMAN_PORT}
+      admin_username = "admin"
+      admin_password = "admin"
+      pg_hba.content = "host all all 127.0.0.1/32 trust"
+      server_lifetime = 60000
+      server_idle_check_timeout = 0
+
+      [pools.example_db]
+      server_host = "127.0.0.1"
+      server_port = ${PG_PORT}
+
+      [[pools.example_db.users]]
+      username = "example_user_1"
+      password = ""
+      pool_size = 1
+
+      [[pools.example_db.users]]
+      username = "postgres"
+      password = ""
+      pool_size = 2
+      """
+    When we create session "victim" to pg_doorman as "example_user_1" with password "" and database "example_db"
+    When we send SimpleQuery "SELECT pg_backend_pid()" to session "victim" and store backend_pid as "victim_pid"
+    When we send SimpleQuery "BEGIN" to session "victim" without waiting
+    Then we read SimpleQuery response from session "victim" within 2000ms
+    When we send SimpleQuery "SELECT pg_sleep(10)" to session "victim" without waiting
+    When we sleep for 200 milliseconds
+    When we create session "killer" to pg_doorman as "postgres" with password "" and database "example_db"
+    When we terminate backend "victim_pid" from session "victim" via session "killer"
+    Then session "victim" should receive ErrorResponse with SQLSTATE "57P01"
+    # No sleep: the slot must be free as soon as the victim got its error
+    When we create session "client2" to pg_doorman as "example_user_1" with password "" and database "example_db"
+    When we send SimpleQuery "SELECT 2" to session "client2" without waiting
+    Then we read SimpleQuery response from session "client2" within 5000ms
+    Then session "client2" should receive DataRow with "2"
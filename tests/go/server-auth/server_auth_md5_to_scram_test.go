@@ -0,0 +1,28 @@
+package doorman_test
+
+import (
+	"database/sql"
+	"os"
+	"testing"
+
+	_ "github.com/lib/pq"
+	"github.com/stretchr/testify/assert"
+)
+
+// The client proves its password with MD5 to pg_doorman, while pg_doorman
+// logs in to the SCRAM-only backend with server_password.
+func TestServerAuthMD5ToSCRAMOK(t *testing.T) {
+	db, err := sql.Open("postgres", os.Getenv("DATABASE_URL_MD5_TO_SCRAM_OK"))
+	assert.NoError(t, err)
+	defer db.Close()
+	var user string
+	assert.NoError(t, db.QueryRow("select current_user").Scan(&user))
+	assert.Equal(t, "example_user_2", user)
+}
+
+func TestServerAuthMD5ToSCRAMBAD(t *testing.T) {
+	db, err := sql.Open("postgres", os.Getenv("DATABASE_URL_MD5_TO_SCRAM_BAD"))
+	assert.NoError(t, err)
+	defer db.Close()
+	assert.Error(t, db.Ping())
+}